@@ -0,0 +1,72 @@
+//! Observe latency on a generated page with many interactive elements.
+//!
+//! ```sh
+//! cargo bench -p eoka-agent --bench observe              # 2000 elements, 10 runs
+//! cargo bench -p eoka-agent --bench observe -- 5000 20   # elements, runs
+//! ```
+//!
+//! Half the elements are "Read more" links in product cards, so the
+//! duplicate-label context pass is measured too. Needs Chrome.
+
+use std::time::{Duration, Instant};
+
+use eoka_agent::{observe, Browser, ObserveConfig};
+
+/// Builds `n` cards with a heading, a "Read more" link and a button.
+const PAGE_JS: &str = r#"
+((n) => {
+  const cards = [];
+  for (let i = 0; i < n / 2; i++) {
+    cards.push('<div class="card"><h3>Product ' + i + '</h3><a href="/p/' + i + '">Read more</a>'
+      + '<button>Add ' + i + '</button></div>');
+  }
+  document.body.innerHTML = cards.join('');
+})
+"#;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args()
+        .skip(1)
+        .filter(|a| !a.starts_with("--"))
+        .map(|a| a.parse::<usize>());
+    let elements = args.next().transpose()?.unwrap_or(2000);
+    let runs = args.next().transpose()?.unwrap_or(10).max(1);
+
+    let browser = match Browser::launch().await {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("observe bench skipped: {}", e);
+            return Ok(());
+        }
+    };
+    let page = browser.new_page("about:blank").await?;
+    page.execute(&format!("{}({})", PAGE_JS, elements)).await?;
+
+    let config = ObserveConfig {
+        viewport_only: false,
+        ..ObserveConfig::default()
+    };
+    // Warm up the script compile and style/layout caches
+    let found = observe::snapshot(&page, &config).await?.elements.len();
+
+    let mut times: Vec<Duration> = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let start = Instant::now();
+        observe::snapshot(&page, &config).await?;
+        times.push(start.elapsed());
+    }
+    times.sort();
+    let mean = times.iter().sum::<Duration>() / runs as u32;
+    println!(
+        "observe: {} elements, {} runs: mean {:.1?}, p50 {:.1?}, max {:.1?}",
+        found,
+        runs,
+        mean,
+        times[runs / 2],
+        times[runs - 1]
+    );
+
+    browser.close().await?;
+    Ok(())
+}
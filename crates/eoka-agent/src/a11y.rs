@@ -0,0 +1,7 @@
+//! Accessibility audit — a core rule set (image alt text, form labels, text
+//! contrast, duplicate ids, keyboard traps) run against the live page.
+//!
+//! The rules live in [`eoka_runner::a11y`], so `audit_a11y` here reports the
+//! same violations as the runner's `assert_a11y` action.
+
+pub use eoka_runner::a11y::{at_least, audit, focus_trap, Severity, Violation};
@@ -0,0 +1,210 @@
+//! Action log — an append-only audit trail of what a session did: when each
+//! action ran, what it targeted, how it ended and the URL before and after.
+//!
+//! Secrets never reach it: passwords and TOTP secrets in arguments, and
+//! text filled or typed into password and one-time-code fields, are logged
+//! as [`REDACTED`].
+
+use eoka::{Page, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+/// Stands in for a secret in the log.
+pub const REDACTED: &str = "[redacted]";
+
+/// Argument names whose values are secrets wherever they appear, e.g. in
+/// the MCP `login` tool's arguments.
+pub const SECRET_ARGS: &[&str] = &["password", "totp_secret"];
+
+/// Replace the values of [`SECRET_ARGS`] in `args`, at any depth, with
+/// [`REDACTED`].
+pub fn redact_args(args: &mut Value) {
+    match args {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if SECRET_ARGS.contains(&key.as_str()) {
+                    *value = REDACTED.into();
+                } else {
+                    redact_args(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_args),
+        _ => {}
+    }
+}
+
+/// `(selector) => bool` — a password field, or one whose `autocomplete`
+/// asks for a password or one-time code.
+const SECRET_FIELD_JS: &str = r#"(sel) => {
+    const el = document.querySelector(sel);
+    if (!el) return false;
+    const autocomplete = (el.getAttribute('autocomplete') || '').toLowerCase();
+    return el.type === 'password' || /password|one-time-code/.test(autocomplete);
+}"#;
+
+/// Whether text for the element `selector` matches must be kept out of the
+/// log; `:focus` checks the focused element.
+pub async fn is_secret_field(page: &Page, selector: &str) -> Result<bool> {
+    page.evaluate(&format!(
+        "({})({})",
+        SECRET_FIELD_JS,
+        serde_json::to_string(selector).unwrap()
+    ))
+    .await
+}
+
+/// One logged action.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionRecord {
+    /// When the action started, RFC 3339 in UTC
+    pub timestamp: String,
+    /// The method or MCP tool, e.g. `click`, `fill`, `goto`
+    pub method: String,
+    /// What it acted on: an element line, a URL, key or coordinates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Whether the action succeeded
+    pub ok: bool,
+    /// What it did (e.g. `navigated to ...`), or the error message
+    pub outcome: String,
+    pub url_before: String,
+    pub url_after: String,
+}
+
+impl ActionRecord {
+    /// Start a record now; complete it with [`ActionRecord::finish`].
+    pub fn begin(method: impl Into<String>, target: Option<String>, url_before: String) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            method: method.into(),
+            target,
+            ok: true,
+            outcome: String::new(),
+            url_before,
+            url_after: String::new(),
+        }
+    }
+
+    /// Record how the action ended: `Ok` with a summary, or `Err` with the
+    /// error message.
+    pub fn finish(
+        mut self,
+        url_after: String,
+        outcome: std::result::Result<String, String>,
+    ) -> Self {
+        self.url_after = url_after;
+        self.ok = outcome.is_ok();
+        self.outcome = outcome.unwrap_or_else(|e| e);
+        self
+    }
+}
+
+impl fmt::Display for ActionRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.timestamp, self.method)?;
+        if let Some(ref target) = self.target {
+            write!(f, " {}", target)?;
+        }
+        let status = if self.ok { "ok" } else { "failed" };
+        write!(f, " → {}", status)?;
+        if !self.outcome.is_empty() {
+            write!(f, ": {}", self.outcome)?;
+        }
+        if self.url_after != self.url_before {
+            write!(f, " ({} → {})", self.url_before, self.url_after)?;
+        }
+        Ok(())
+    }
+}
+
+/// Append-only list of [`ActionRecord`]s, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct ActionLog {
+    records: Vec<ActionRecord>,
+}
+
+impl ActionLog {
+    pub fn push(&mut self, record: ActionRecord) {
+        self.records.push(record);
+    }
+
+    /// Every record, oldest first.
+    pub fn records(&self) -> &[ActionRecord] {
+        &self.records
+    }
+
+    /// The last `n` records, oldest first.
+    pub fn last(&self, n: usize) -> &[ActionRecord] {
+        &self.records[self.records.len().saturating_sub(n)..]
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The whole log as a JSON array.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.records).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_log() {
+        let mut log = ActionLog::default();
+        log.push(
+            ActionRecord::begin(
+                "goto",
+                Some("https://example.com".into()),
+                "about:blank".into(),
+            )
+            .finish("https://example.com/".into(), Ok(String::new())),
+        );
+        log.push(
+            ActionRecord::begin("click", None, "https://example.com/".into()).finish(
+                "https://example.com/".into(),
+                Err("Element not found: [4]".into()),
+            ),
+        );
+
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.last(1)[0].method, "click");
+        assert_eq!(log.last(5).len(), 2);
+
+        let line = log.records()[1].to_string();
+        assert!(line.ends_with("click → failed: Element not found: [4]"));
+        assert!(log.records()[0]
+            .to_string()
+            .ends_with("goto https://example.com → ok (about:blank → https://example.com/)"));
+
+        let json: serde_json::Value = serde_json::from_str(&log.to_json()).unwrap();
+        assert_eq!(json[0]["url_after"], "https://example.com/");
+        assert_eq!(json[1]["ok"], false);
+        assert!(json[1].get("target").is_none());
+    }
+
+    #[test]
+    fn redacts_secret_args() {
+        let mut args = serde_json::json!({
+            "url": "https://example.com/login",
+            "username": "ada",
+            "password": "hunter2",
+            "totp_secret": "JBSWY3DPEHPK3PXP",
+            "nested": [{"password": "again"}]
+        });
+        redact_args(&mut args);
+        assert_eq!(args["username"], "ada");
+        assert_eq!(args["password"], REDACTED);
+        assert_eq!(args["totp_secret"], REDACTED);
+        assert_eq!(args["nested"][0]["password"], REDACTED);
+    }
+}
@@ -1,9 +1,97 @@
-//! Screenshot annotation — injects numbered labels over interactive elements.
+//! Screenshot annotation — injects numbered labels over interactive elements,
+//! and diffs screenshots to find what visibly changed.
 
 use eoka::{Page, Result};
+use eoka_runner::visual::{self, DiffMode};
+use image::RgbaImage;
+use serde::Serialize;
+
+pub use eoka_runner::visual::Region;
 
 use crate::InteractiveElement;
 
+/// A Set-of-Marks vision prompt: the annotated screenshot and what each
+/// numbered mark on it labels, built from one observation so the numbers
+/// can't drift.
+#[derive(Debug, Clone)]
+pub struct SomBundle {
+    /// PNG with a numbered box over each element
+    pub image: Vec<u8>,
+    pub marks: Vec<Mark>,
+}
+
+impl SomBundle {
+    /// The marks as a JSON array, for the text part of the prompt.
+    pub fn marks_json(&self) -> String {
+        serde_json::to_string(&self.marks).unwrap_or_default()
+    }
+}
+
+/// Metadata for one mark; `mark` is the element index used by actions.
+#[derive(Debug, Clone, Serialize)]
+pub struct Mark {
+    pub mark: usize,
+    pub tag: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub checked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// `[x, y, width, height]` in viewport pixels
+    pub bbox: [i32; 4],
+}
+
+impl From<&InteractiveElement> for Mark {
+    fn from(el: &InteractiveElement) -> Self {
+        Self {
+            mark: el.index,
+            tag: el.tag.clone(),
+            role: el.role.clone(),
+            text: el.text.clone(),
+            input_type: el.input_type.clone(),
+            placeholder: el.placeholder.clone(),
+            value: el.value.clone(),
+            checked: el.checked,
+            context: el.context.clone(),
+            href: el.href.clone(),
+            name: el.name.clone(),
+            title: el.title.clone(),
+            id: el.id.clone(),
+            bbox: [
+                el.bbox.x as i32,
+                el.bbox.y as i32,
+                el.bbox.width as i32,
+                el.bbox.height as i32,
+            ],
+        }
+    }
+}
+
+/// Annotate `elements` on a screenshot and pair it with their marks.
+pub async fn som_bundle(page: &Page, elements: &[InteractiveElement]) -> Result<SomBundle> {
+    Ok(SomBundle {
+        image: annotated_screenshot(page, elements).await?,
+        marks: elements.iter().map(Mark::from).collect(),
+    })
+}
+
 /// Inject numbered overlay labels, take screenshot, remove overlays.
 pub async fn annotated_screenshot(page: &Page, elements: &[InteractiveElement]) -> Result<Vec<u8>> {
     if elements.is_empty() {
@@ -116,3 +204,121 @@ pub async fn annotated_screenshot(page: &Page, elements: &[InteractiveElement])
 
     Ok(png)
 }
+
+/// What changed between two screenshots.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffResult {
+    /// Boxes around each changed area in screenshot pixels, largest first
+    pub changed_regions: Vec<Region>,
+    /// Share of pixels that changed, 0–100
+    pub percent: f64,
+}
+
+impl DiffResult {
+    pub fn changed(&self) -> bool {
+        !self.changed_regions.is_empty()
+    }
+}
+
+/// Compare two PNG screenshots, e.g. before and after a click. Screenshots
+/// of different sizes count as changed everywhere. Uses the runner's
+/// [`visual::compare`], so a change here is one `assert_screenshot` sees.
+pub fn diff_screenshots(a: &[u8], b: &[u8]) -> Result<DiffResult> {
+    Ok(diff_images(&decode_png(a)?, &decode_png(b)?))
+}
+
+fn decode_png(png: &[u8]) -> Result<RgbaImage> {
+    visual::decode(png).map_err(|e| eoka::Error::Decode(e.to_string()))
+}
+
+fn diff_images(a: &RgbaImage, b: &RgbaImage) -> DiffResult {
+    let comparison = visual::compare(a, b, DiffMode::Pixel, visual::TOLERANCE);
+    DiffResult {
+        changed_regions: comparison.regions,
+        percent: comparison.difference * 100.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eoka::BoundingBox;
+
+    #[test]
+    fn marks_json_skips_empty_fields() {
+        let el = InteractiveElement {
+            index: 3,
+            tag: "input".into(),
+            role: None,
+            text: String::new(),
+            placeholder: Some("Email".into()),
+            input_type: Some("email".into()),
+            selector: "form > input".into(),
+            checked: false,
+            value: None,
+            bbox: BoundingBox {
+                x: 10.4,
+                y: 20.0,
+                width: 200.0,
+                height: 32.6,
+            },
+            visibility: 1.0,
+            fingerprint: 0,
+            context: None,
+            href: None,
+            name: Some("email".into()),
+            title: None,
+            id: None,
+        };
+        let bundle = SomBundle {
+            image: Vec::new(),
+            marks: vec![Mark::from(&el)],
+        };
+        assert_eq!(
+            bundle.marks_json(),
+            r#"[{"mark":3,"tag":"input","input_type":"email","placeholder":"Email","name":"email","bbox":[10,20,200,32]}]"#
+        );
+    }
+
+    #[test]
+    fn diff_finds_changed_regions() {
+        use image::Rgba;
+
+        let a = RgbaImage::from_pixel(100, 50, Rgba([255, 255, 255, 255]));
+        let mut b = a.clone();
+        // A button that turned blue, and a one-pixel change far away
+        for y in 10..20 {
+            for x in 10..30 {
+                b.put_pixel(x, y, Rgba([37, 99, 235, 255]));
+            }
+        }
+        b.put_pixel(90, 40, Rgba([0, 0, 0, 255]));
+        // Below the tolerance: not a change
+        b.put_pixel(60, 5, Rgba([250, 250, 250, 255]));
+
+        let diff = diff_images(&a, &b);
+        assert_eq!(
+            diff.changed_regions,
+            [
+                Region {
+                    x: 8,
+                    y: 8,
+                    width: 24,
+                    height: 16
+                },
+                Region {
+                    x: 88,
+                    y: 40,
+                    width: 8,
+                    height: 8
+                },
+            ]
+        );
+        assert!((diff.percent - 201.0 * 100.0 / 5000.0).abs() < 1e-9);
+
+        assert!(!diff_images(&a, &a).changed());
+        let resized = diff_images(&a, &RgbaImage::new(10, 10));
+        assert_eq!(resized.percent, 100.0);
+        assert_eq!(resized.changed_regions[0].width, 10);
+    }
+}
@@ -0,0 +1,144 @@
+//! Automatic dismissal of cookie banners, consent dialogs and modals.
+//!
+//! Known consent platforms are handled by a rule list (the banner's selector
+//! plus its reject and accept buttons), shared with the runner's
+//! `auto_dismiss` through [`eoka_runner::consent`]. Anything else falls back to
+//! [`overlay::detect_overlays`](crate::overlay::detect_overlays) and its
+//! labelled dismiss buttons.
+
+use eoka::{Page, Result};
+use eoka_runner::consent;
+use std::fmt;
+
+use crate::overlay::{self, DismissKind, OverlayKind};
+
+pub use eoka_runner::consent::{builtin_rules, DismissRule};
+pub use eoka_runner::ConsentPolicy;
+
+/// Rounds of dismissal; a second banner or modal often appears after the first.
+const MAX_ROUNDS: usize = 3;
+
+/// Auto-dismiss settings. Default: reject consent, close modals, built-in rules.
+#[derive(Debug, Clone)]
+pub struct AutoDismiss {
+    pub policy: ConsentPolicy,
+    /// Also close modals and interstitials that have a close button.
+    pub close_modals: bool,
+    pub rules: Vec<DismissRule>,
+}
+
+impl Default for AutoDismiss {
+    fn default() -> Self {
+        Self {
+            policy: ConsentPolicy::default(),
+            close_modals: true,
+            rules: builtin_rules(),
+        }
+    }
+}
+
+impl AutoDismiss {
+    pub fn new(policy: ConsentPolicy) -> Self {
+        Self {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    pub fn close_modals(mut self, close: bool) -> Self {
+        self.close_modals = close;
+        self
+    }
+
+    /// Add a rule, checked before the built-in ones.
+    pub fn rule(mut self, rule: DismissRule) -> Self {
+        self.rules.insert(0, rule);
+        self
+    }
+}
+
+/// One overlay that was dismissed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dismissed {
+    /// Rule name, or the overlay kind for heuristic matches
+    pub source: String,
+    /// Label of the button clicked
+    pub button: String,
+}
+
+impl fmt::Display for Dismissed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: clicked \"{}\"", self.source, self.button)
+    }
+}
+
+/// Dismiss consent banners (and modals, if enabled) on the page.
+/// Returns what was clicked; empty when nothing needed dismissing.
+pub async fn dismiss(page: &Page, options: &AutoDismiss) -> Result<Vec<Dismissed>> {
+    let mut dismissed = Vec::new();
+    for _ in 0..MAX_ROUNDS {
+        match dismiss_one(page, options).await? {
+            Some(d) => dismissed.push(d),
+            None => break,
+        }
+        page.wait(400).await;
+    }
+    Ok(dismissed)
+}
+
+async fn dismiss_one(page: &Page, options: &AutoDismiss) -> Result<Option<Dismissed>> {
+    if let Some(m) = consent::find_rule(page, &options.rules, options.policy).await? {
+        consent::click(page, &m.selector).await?;
+        return Ok(Some(Dismissed {
+            source: m.name,
+            button: m.text,
+        }));
+    }
+
+    let wanted = match options.policy {
+        ConsentPolicy::Reject => DismissKind::Reject,
+        ConsentPolicy::Accept => DismissKind::Accept,
+    };
+    for found in overlay::detect_overlays(page).await? {
+        let button = match found.kind {
+            OverlayKind::CookieBanner => found
+                .dismiss
+                .iter()
+                .find(|b| b.kind == wanted)
+                .or_else(|| found.dismiss.iter().find(|b| b.kind == DismissKind::Close)),
+            OverlayKind::Modal | OverlayKind::Interstitial if options.close_modals => {
+                found.dismiss.iter().find(|b| b.kind == DismissKind::Close)
+            }
+            _ => None,
+        };
+        if let Some(button) = button {
+            consent::click(page, &button.selector).await?;
+            return Ok(Some(Dismissed {
+                source: found.kind.to_string(),
+                button: button.text.clone(),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_rules_come_first() {
+        let options = AutoDismiss::new(ConsentPolicy::Accept).rule(DismissRule::new(
+            "Site",
+            "#banner",
+            None,
+            Some("#ok"),
+        ));
+        assert_eq!(options.rules[0].name, "Site");
+        assert_eq!(options.rules.len(), builtin_rules().len() + 1);
+        assert_eq!(
+            serde_json::to_string(&options.policy).unwrap(),
+            "\"accept\""
+        );
+    }
+}
@@ -0,0 +1,28 @@
+//! Form autofill — a test-data [`FormProfile`] mapped onto a form's fields by
+//! their `autocomplete` attributes, labels, names and placeholders.
+//!
+//! The field matching lives in [`eoka_runner::autofill`], so
+//! `autofill_form` here fills the same fields as the runner's `autofill`
+//! action.
+
+use eoka::{Page, Result};
+use eoka_runner::autofill::FormRef;
+use eoka_runner::Runner;
+
+pub use eoka_runner::autofill::{FieldKind, FilledField, FormProfile, TestCard};
+
+/// Fill every recognized field of the `form_index`-th `<form>` (0 = first;
+/// on pages without forms, 0 is the whole page) from `profile`. Fields that
+/// aren't recognized, and dropdowns with no matching option, are skipped.
+pub async fn autofill_form(
+    page: &Page,
+    form_index: usize,
+    profile: &FormProfile,
+) -> Result<Vec<FilledField>> {
+    Runner::autofill_form(page, &FormRef::Index(form_index), profile)
+        .await
+        .map_err(|e| match e {
+            eoka_runner::Error::Browser(e) => e,
+            e => eoka::Error::ElementNotFound(e.to_string()),
+        })
+}
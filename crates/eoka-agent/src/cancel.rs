@@ -0,0 +1,93 @@
+//! Cooperative cancellation — long waits and navigations race a
+//! [`CancellationToken`] and stop with an error as soon as it fires, instead
+//! of running out their timeout.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+pub use tokio_util::sync::CancellationToken;
+
+/// Error message of a cancelled operation.
+pub const CANCELLED: &str = "cancelled";
+
+/// Run `fut` until it finishes or `token` is cancelled. Works with any eoka
+/// wait or navigation, e.g.
+/// `cancellable(Some(&token), page.wait_for_text("Done", 60_000))`.
+pub async fn cancellable<T, E: From<eoka::Error>>(
+    token: Option<&CancellationToken>,
+    fut: impl Future<Output = std::result::Result<T, E>>,
+) -> std::result::Result<T, E> {
+    let Some(token) = token else {
+        return fut.await;
+    };
+    tokio::select! {
+        result = fut => result,
+        _ = token.cancelled() => Err(eoka::Error::CdpSimple(CANCELLED.into()).into()),
+    }
+}
+
+/// Whether `error` came from a cancelled operation.
+pub fn is_cancelled(error: &eoka::Error) -> bool {
+    matches!(error, eoka::Error::CdpSimple(msg) if msg == CANCELLED)
+}
+
+/// Cancels whatever a [`crate::Session`] is waiting on, from another task.
+/// Each cancel only affects the operations running at the time; later ones
+/// get a fresh token.
+#[derive(Debug, Clone, Default)]
+pub struct Canceller {
+    current: Arc<Mutex<CancellationToken>>,
+}
+
+impl Canceller {
+    /// The token operations starting now watch.
+    pub fn token(&self) -> CancellationToken {
+        self.current
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Cancel the running operations.
+    pub fn cancel_current(&self) {
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        current.cancel();
+        *current = CancellationToken::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cancels_running_operations_only() {
+        let canceller = Canceller::default();
+        let token = canceller.token();
+        let slow = cancellable(Some(&token), async {
+            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+            Ok(())
+        });
+        let cancel = async {
+            tokio::task::yield_now().await;
+            canceller.cancel_current();
+        };
+        let (result, ()) = tokio::join!(slow, cancel);
+        assert!(is_cancelled(&result.unwrap_err()));
+
+        let token = canceller.token();
+        assert!(!token.is_cancelled());
+        assert_eq!(
+            cancellable(Some(&token), async { Ok::<_, eoka::Error>(1) })
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            cancellable(None, async { Ok::<_, eoka::Error>(2) })
+                .await
+                .unwrap(),
+            2
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Click options — where to click inside an element, and how to click it
+//! when a real mouse click can't reach it (obscured, zero-size, off-screen).
+
+use eoka::{Page, Result};
+use serde::Deserialize;
+use std::fmt;
+
+use crate::occlusion;
+use crate::{AgentError, AgentResult, InteractiveElement};
+
+/// How to click an element.
+#[derive(Debug, Clone, Default)]
+pub struct ClickOptions {
+    /// Skip hit-testing and dispatch the click events straight to the element.
+    pub force: bool,
+    /// Dispatch the events from JS if the mouse click fails.
+    pub js_fallback: bool,
+    /// Click point relative to the element's top-left corner, in CSS pixels.
+    /// The element's center when `None`.
+    pub position: Option<(f64, f64)>,
+}
+
+impl ClickOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    pub fn js_fallback(mut self, fallback: bool) -> Self {
+        self.js_fallback = fallback;
+        self
+    }
+
+    pub fn position(mut self, x: f64, y: f64) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+}
+
+/// How a click reached the element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickMethod {
+    /// Real mouse events through CDP, hit-tested by the browser
+    Mouse,
+    /// Pointer, mouse and click events dispatched on the element from JS
+    Dispatched,
+}
+
+impl fmt::Display for ClickMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClickMethod::Mouse => write!(f, "mouse"),
+            ClickMethod::Dispatched => write!(f, "dispatched events"),
+        }
+    }
+}
+
+/// Result of a click.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clicked {
+    pub method: ClickMethod,
+    /// Why events were dispatched instead of a mouse click
+    pub fallback_reason: Option<String>,
+}
+
+impl fmt::Display for Clicked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "clicked via {}", self.method)?;
+        if let Some(reason) = &self.fallback_reason {
+            write!(f, " ({})", reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// JavaScript that returns the element's viewport rect, or null.
+const RECT_JS: &str = r#"
+((sel) => {
+  const el = document.querySelector(sel);
+  if (!el) return 'null';
+  const r = el.getBoundingClientRect();
+  return JSON.stringify({ x: r.left, y: r.top, width: r.width, height: r.height });
+})
+"#;
+
+/// JavaScript that dispatches the events of a click on the element at a point
+/// relative to its top-left corner (center when null). Dispatched clicks still
+/// run default actions: links navigate, checkboxes toggle, forms submit.
+const DISPATCH_JS: &str = r#"
+((sel, pos) => {
+  const el = document.querySelector(sel);
+  if (!el) return false;
+  const r = el.getBoundingClientRect();
+  const clientX = r.left + (pos ? pos[0] : r.width / 2);
+  const clientY = r.top + (pos ? pos[1] : r.height / 2);
+  const init = { bubbles: true, cancelable: true, composed: true, view: window, clientX, clientY, button: 0 };
+  const pointer = { ...init, pointerId: 1, pointerType: 'mouse', isPrimary: true };
+  el.dispatchEvent(new PointerEvent('pointerover', pointer));
+  el.dispatchEvent(new MouseEvent('mouseover', init));
+  el.dispatchEvent(new PointerEvent('pointerdown', { ...pointer, buttons: 1 }));
+  el.dispatchEvent(new MouseEvent('mousedown', { ...init, buttons: 1 }));
+  if (typeof el.focus === 'function') el.focus({ preventScroll: true });
+  el.dispatchEvent(new PointerEvent('pointerup', pointer));
+  el.dispatchEvent(new MouseEvent('mouseup', init));
+  el.dispatchEvent(new MouseEvent('click', init));
+  return true;
+})
+"#;
+
+#[derive(Deserialize)]
+struct Rect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+/// Click an element. Without options this is a mouse click after scrolling
+/// it clear of fixed headers; fails with [`AgentError::ElementObscured`] if
+/// something else would get the click.
+pub async fn click(
+    page: &Page,
+    el: &InteractiveElement,
+    options: &ClickOptions,
+) -> AgentResult<Clicked> {
+    if options.force {
+        dispatch(page, &el.selector, options.position).await?;
+        return Ok(Clicked {
+            method: ClickMethod::Dispatched,
+            fallback_reason: Some("forced".into()),
+        });
+    }
+    match mouse_click(page, el, options.position).await {
+        Ok(()) => Ok(Clicked {
+            method: ClickMethod::Mouse,
+            fallback_reason: None,
+        }),
+        Err(e) if options.js_fallback => {
+            dispatch(page, &el.selector, options.position).await?;
+            Ok(Clicked {
+                method: ClickMethod::Dispatched,
+                fallback_reason: Some(e.to_string()),
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn mouse_click(
+    page: &Page,
+    el: &InteractiveElement,
+    position: Option<(f64, f64)>,
+) -> AgentResult<()> {
+    if let Some(by) = occlusion::reveal_at(page, &el.selector, position).await? {
+        return Err(AgentError::ElementObscured {
+            element: format!("element [{}] \"{}\"", el.index, el.text),
+            by,
+        });
+    }
+    let Some((dx, dy)) = position else {
+        return Ok(page.click(&el.selector).await?);
+    };
+    let js = format!(
+        "{}({})",
+        RECT_JS,
+        serde_json::to_string(&el.selector).unwrap()
+    );
+    let json: String = page.evaluate(&js).await?;
+    let rect: Option<Rect> = serde_json::from_str(&json)
+        .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse element rect: {}", e)))?;
+    match rect {
+        Some(r) if r.width > 0.0 && r.height > 0.0 => Ok(page.click_at(r.x + dx, r.y + dy).await?),
+        Some(_) => Err(eoka::Error::ElementNotVisible {
+            selector: el.selector.clone(),
+        }
+        .into()),
+        None => Err(eoka::Error::ElementNotFound(el.selector.clone()).into()),
+    }
+}
+
+async fn dispatch(page: &Page, selector: &str, position: Option<(f64, f64)>) -> Result<()> {
+    let js = format!(
+        "{}({}, {})",
+        DISPATCH_JS,
+        serde_json::to_string(selector).unwrap(),
+        serde_json::to_string(&position).unwrap()
+    );
+    let dispatched: bool = page.evaluate(&js).await?;
+    if !dispatched {
+        return Err(eoka::Error::ElementNotFound(selector.to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicked_display() {
+        let mouse = Clicked {
+            method: ClickMethod::Mouse,
+            fallback_reason: None,
+        };
+        assert_eq!(mouse.to_string(), "clicked via mouse");
+        let forced = Clicked {
+            method: ClickMethod::Dispatched,
+            fallback_reason: Some("forced".into()),
+        };
+        assert_eq!(forced.to_string(), "clicked via dispatched events (forced)");
+
+        let options = ClickOptions::new().js_fallback(true).position(4.0, 2.0);
+        assert!(!options.force);
+        assert_eq!(options.position, Some((4.0, 2.0)));
+    }
+}
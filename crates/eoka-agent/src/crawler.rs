@@ -0,0 +1,252 @@
+//! Site crawler — breadth-first from seed URLs within origin, depth and URL
+//! pattern limits, running a callback on every page.
+//!
+//! Concurrency is the number of sessions passed to [`crawl`]; give them a
+//! shared [`RateLimiter`](crate::RateLimiter) to stay polite. The queue and
+//! link rules are [`eoka_runner::frontier::Frontier`], shared with the
+//! runner's `crawl:` mode.
+
+use eoka::{Page, Result};
+use eoka_runner::frontier::{self, Frontier};
+use regex::Regex;
+use std::cell::RefCell;
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::Poll;
+
+use crate::Session;
+
+/// What to crawl.
+#[derive(Debug, Clone)]
+pub struct CrawlConfig {
+    /// Starting URLs, depth 0
+    pub seeds: Vec<String>,
+    /// Links followed from a seed, at most this many hops (default 2)
+    pub max_depth: usize,
+    /// Stop after this many pages (default 100)
+    pub max_pages: usize,
+    /// Only follow links to the seeds' origins (default true)
+    pub same_origin: bool,
+    /// Follow only URLs matching one of these, if any are set
+    pub include: Vec<Regex>,
+    /// Never follow URLs matching any of these
+    pub exclude: Vec<Regex>,
+}
+
+impl CrawlConfig {
+    pub fn new<S: Into<String>>(seeds: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            seeds: seeds.into_iter().map(Into::into).collect(),
+            max_depth: 2,
+            max_pages: 100,
+            same_origin: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    pub fn max_pages(mut self, pages: usize) -> Self {
+        self.max_pages = pages;
+        self
+    }
+
+    pub fn same_origin(mut self, same_origin: bool) -> Self {
+        self.same_origin = same_origin;
+        self
+    }
+
+    /// Follow only URLs matching `pattern` (a regex); may be repeated.
+    pub fn include(mut self, pattern: &str) -> Result<Self> {
+        self.include.push(compile(pattern)?);
+        Ok(self)
+    }
+
+    /// Skip URLs matching `pattern` (a regex); may be repeated.
+    pub fn exclude(mut self, pattern: &str) -> Result<Self> {
+        self.exclude.push(compile(pattern)?);
+        Ok(self)
+    }
+
+    /// The frontier this config describes, seeds queued.
+    fn frontier(&self) -> Frontier {
+        Frontier::new(&self.seeds, self.max_depth, self.max_pages)
+            .same_origin(self.same_origin)
+            .include(self.include.clone())
+            .exclude(self.exclude.clone())
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern)
+        .map_err(|e| eoka::Error::CdpSimple(format!("invalid crawl pattern '{}': {}", pattern, e)))
+}
+
+/// A page to visit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrawlPage {
+    pub url: String,
+    /// Hops from the nearest seed
+    pub depth: usize,
+}
+
+/// Pages visited, with the callback's result for each, and pages that failed.
+#[derive(Debug)]
+pub struct CrawlReport<T> {
+    pub pages: Vec<(CrawlPage, T)>,
+    pub errors: Vec<(CrawlPage, eoka::Error)>,
+}
+
+impl<T> CrawlReport<T> {
+    /// Pages attempted, successful or not.
+    pub fn visited(&self) -> usize {
+        self.pages.len() + self.errors.len()
+    }
+}
+
+impl<T> fmt::Display for CrawlReport<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "visited {} pages, {} errors",
+            self.visited(),
+            self.errors.len()
+        )?;
+        for (page, error) in &self.errors {
+            write!(f, "\n  {}: {}", page.url, error)?;
+        }
+        Ok(())
+    }
+}
+
+struct State<T> {
+    frontier: Frontier,
+    in_flight: usize,
+    report: CrawlReport<T>,
+}
+
+/// Links on the current page.
+pub async fn extract_links(page: &Page) -> Result<Vec<String>> {
+    frontier::links(page, None).await
+}
+
+/// Crawl from `config.seeds`, one page at a time per session, calling
+/// `per_page` after each page loads. Links are collected before the callback
+/// runs, so it may click or navigate freely. A failing page is recorded in
+/// the report and the crawl continues.
+pub async fn crawl<T>(
+    sessions: &mut [Session],
+    config: &CrawlConfig,
+    per_page: impl AsyncFn(&mut Session, &CrawlPage) -> Result<T>,
+) -> CrawlReport<T> {
+    let state = RefCell::new(State {
+        frontier: config.frontier(),
+        in_flight: 0,
+        report: CrawlReport {
+            pages: Vec::new(),
+            errors: Vec::new(),
+        },
+    });
+
+    let mut workers: Vec<Pin<Box<dyn Future<Output = ()> + '_>>> = sessions
+        .iter_mut()
+        .map(|session| Box::pin(worker(session, &state, &per_page)) as _)
+        .collect();
+    poll_fn(|cx| {
+        workers.retain_mut(|w| w.as_mut().poll(cx).is_pending());
+        if workers.is_empty() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await;
+    drop(workers);
+
+    state.into_inner().report
+}
+
+async fn worker<T>(
+    session: &mut Session,
+    state: &RefCell<State<T>>,
+    per_page: &impl AsyncFn(&mut Session, &CrawlPage) -> Result<T>,
+) {
+    loop {
+        let next = {
+            let mut f = state.borrow_mut();
+            let next = f
+                .frontier
+                .next_page()
+                .map(|(url, depth)| CrawlPage { url, depth });
+            if next.is_some() {
+                f.in_flight += 1;
+            } else if f.in_flight == 0 {
+                return;
+            }
+            next
+        };
+        let Some(page) = next else {
+            // Another worker may still find links
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            continue;
+        };
+
+        let outcome = visit(session, &page, per_page).await;
+        let mut f = state.borrow_mut();
+        f.in_flight -= 1;
+        match outcome {
+            Ok((result, links)) => {
+                f.frontier.add_links(links, page.depth);
+                f.report.pages.push((page, result));
+            }
+            Err(e) => f.report.errors.push((page, e)),
+        }
+    }
+}
+
+async fn visit<T>(
+    session: &mut Session,
+    page: &CrawlPage,
+    per_page: &impl AsyncFn(&mut Session, &CrawlPage) -> Result<T>,
+) -> Result<(T, Vec<String>)> {
+    session.goto(&page.url).await?;
+    let links = extract_links(session.page()).await?;
+    let result = per_page(session, page).await?;
+    Ok((result, links))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_frontier() {
+        let config = CrawlConfig::new(["https://shop.example/#top"])
+            .include("/products/")
+            .unwrap()
+            .exclude(r"\?sort=")
+            .unwrap();
+        let mut frontier = config.frontier();
+        assert!(frontier.follows("https://shop.example/products/1"));
+        assert!(!frontier.follows("https://shop.example/about"));
+        assert!(!frontier.follows("https://shop.example/products/?sort=asc"));
+        assert!(!frontier.follows("https://other.example/products/1"));
+        assert!(config
+            .clone()
+            .same_origin(false)
+            .frontier()
+            .follows("https://other.example/products/1"));
+        assert_eq!(
+            frontier.next_page(),
+            Some(("https://shop.example/".into(), 0))
+        );
+        assert!(CrawlConfig::new(["https://a.example/"])
+            .include("(")
+            .is_err());
+    }
+}
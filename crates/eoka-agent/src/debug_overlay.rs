@@ -0,0 +1,31 @@
+//! Debug overlay — a panel drawn into the page with the last action, plus a
+//! numbered box over each observed element, for watching headful runs live.
+//!
+//! The overlay lives in [`eoka_runner::debug_overlay`], so a session draws
+//! the same panel as the runner's `browser.debug_overlay`. It ignores the
+//! pointer, so clicks, hit-testing and `observe()` see through it.
+//! Screenshots don't: turn it off before capturing images for a model.
+
+use eoka::{Page, Result};
+
+use crate::InteractiveElement;
+
+pub use eoka_runner::debug_overlay::hide;
+
+/// Draw the overlay with `caption` in the corner and a numbered box per
+/// element, replacing any previous one.
+pub async fn show(page: &Page, caption: &str, elements: &[InteractiveElement]) -> Result<()> {
+    let boxes: Vec<[i64; 5]> = elements
+        .iter()
+        .map(|el| {
+            [
+                el.index as i64,
+                el.bbox.x as i64,
+                el.bbox.y as i64,
+                el.bbox.width as i64,
+                el.bbox.height as i64,
+            ]
+        })
+        .collect();
+    eoka_runner::debug_overlay::show(page, caption, &boxes).await
+}
@@ -0,0 +1,118 @@
+//! Custom (non-native) dropdowns — React-Select, MUI, headless-UI and other
+//! comboboxes that render their options as `role=option` elements instead of
+//! a `<select>`.
+
+use eoka::{Page, Result};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+use crate::input;
+
+/// How long to wait for the listbox to open and show a matching option.
+const LISTBOX_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Searchable comboboxes (React-Select, MUI Autocomplete) get the option
+/// text typed after this long without a match.
+const TYPE_AFTER: Duration = Duration::from_millis(500);
+
+/// JavaScript finding the combobox's open options and the best match for the
+/// wanted text (exact, then prefix, then substring; case-insensitive). The
+/// match is scrolled into view and its center returned for a real click.
+const OPTIONS_JS: &str = r#"
+((sel, wanted) => {
+  const box = document.querySelector(sel);
+  if (!box) return JSON.stringify(null);
+  const norm = s => (s || '').replace(/\s+/g, ' ').trim().toLowerCase();
+  const shown = el => {
+    const r = el.getBoundingClientRect();
+    return r.width > 0 && r.height > 0 && getComputedStyle(el).visibility !== 'hidden';
+  };
+  const ids = [box, ...box.querySelectorAll('[aria-controls], [aria-owns]')]
+    .flatMap(e => ((e.getAttribute('aria-controls') || '') + ' ' + (e.getAttribute('aria-owns') || '')).split(/\s+/))
+    .filter(Boolean);
+  let lists = ids.map(id => document.getElementById(id)).filter(l => l && shown(l));
+  if (!lists.length) lists = Array.from(document.querySelectorAll('[role=listbox]')).filter(shown);
+  let opts = lists.flatMap(l => Array.from(l.querySelectorAll('[role=option]')));
+  // React-Select's options carry no role, only generated ids
+  if (!opts.length) opts = Array.from(document.querySelectorAll('[role=option], [id*="-option-"]'));
+  opts = opts.filter(o => shown(o) && o.getAttribute('aria-disabled') !== 'true');
+
+  const target = norm(wanted);
+  const texts = opts.map(o => norm(o.innerText || o.textContent));
+  let i = texts.indexOf(target);
+  if (i < 0) i = texts.findIndex(t => t.startsWith(target));
+  if (i < 0) i = texts.findIndex(t => t.includes(target));
+  const active = document.activeElement;
+  const searchable = !!active && (active.tagName === 'INPUT' || active.tagName === 'TEXTAREA' || active.isContentEditable);
+  let matched = null;
+  if (target && i >= 0) {
+    const opt = opts[i];
+    opt.scrollIntoView({ block: 'nearest' });
+    const r = opt.getBoundingClientRect();
+    matched = { text: (opt.innerText || opt.textContent).trim(), x: r.x + r.width / 2, y: r.y + r.height / 2 };
+  }
+  return JSON.stringify({
+    options: opts.slice(0, 10).map(o => (o.innerText || o.textContent).trim()),
+    matched,
+    searchable,
+  });
+})
+"#;
+
+#[derive(Deserialize)]
+struct Options {
+    options: Vec<String>,
+    matched: Option<Matched>,
+    searchable: bool,
+}
+
+#[derive(Deserialize)]
+struct Matched {
+    text: String,
+    x: f64,
+    y: f64,
+}
+
+/// Open the combobox at `selector`, wait for its listbox, then scroll to and
+/// click the option matching `option_text`. Searchable comboboxes that show
+/// no match get the text typed to filter them. Returns the clicked option's
+/// text.
+pub async fn select_custom(page: &Page, selector: &str, option_text: &str) -> Result<String> {
+    page.click(selector).await?;
+    let js = format!(
+        "{}({}, {})",
+        OPTIONS_JS,
+        serde_json::to_string(selector).unwrap(),
+        serde_json::to_string(option_text).unwrap()
+    );
+    let start = Instant::now();
+    let mut typed = false;
+    loop {
+        let json: String = page.evaluate(&js).await?;
+        let found: Option<Options> = serde_json::from_str(&json)
+            .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse listbox: {}", e)))?;
+        let Some(found) = found else {
+            return Err(eoka::Error::ElementNotFound(selector.to_string()));
+        };
+        if let Some(m) = found.matched {
+            input::click_at(page, m.x, m.y).await?;
+            return Ok(m.text);
+        }
+        let elapsed = start.elapsed();
+        if found.searchable && !typed && (!found.options.is_empty() || elapsed >= TYPE_AFTER) {
+            input::type_text(page, option_text).await?;
+            typed = true;
+        } else if elapsed >= LISTBOX_TIMEOUT {
+            return Err(eoka::Error::ElementNotFound(if found.options.is_empty() {
+                format!("listbox for {} (no options appeared)", selector)
+            } else {
+                format!(
+                    "option \"{}\" in listbox (options: {})",
+                    option_text,
+                    found.options.join(", ")
+                )
+            }));
+        }
+        page.wait(100).await;
+    }
+}
@@ -0,0 +1,207 @@
+//! Egress check — the IP, country and timezone sites see, compared against
+//! what the session was set up to look like. A proxy that silently didn't
+//! apply, or a timezone that gives the real location away, fails fast here
+//! instead of showing up later as blocks.
+
+use eoka::{Browser, Page, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+/// IP-echo endpoint used by default; answers `{ip, country, timezone}`.
+pub const DEFAULT_ENDPOINT: &str = "https://ipinfo.io/json";
+
+/// What the egress should look like, for [`crate::Session::check_egress_with`].
+#[derive(Debug, Clone)]
+pub struct EgressCheck {
+    /// JSON IP-echo endpoint. ipinfo.io, ip-api.com and ipapi.co shapes are
+    /// understood.
+    pub endpoint: String,
+    /// Exact exit IP.
+    pub ip: Option<String>,
+    /// Two-letter country code, e.g. `DE`.
+    pub country: Option<String>,
+    /// IANA timezone of the exit IP. Defaults to the timezone the browser
+    /// reports, so a proxy in another zone than the fingerprint fails.
+    pub timezone: Option<String>,
+}
+
+impl Default for EgressCheck {
+    fn default() -> Self {
+        Self {
+            endpoint: DEFAULT_ENDPOINT.into(),
+            ip: None,
+            country: None,
+            timezone: None,
+        }
+    }
+}
+
+/// What the endpoint and the browser reported.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Egress {
+    /// Exit IP as the endpoint saw it.
+    pub ip: String,
+    pub country: Option<String>,
+    /// Timezone of the exit IP.
+    pub timezone: Option<String>,
+    /// Timezone the page sees through `Intl`.
+    pub browser_timezone: Option<String>,
+    /// This machine's IP without the proxy, when a proxy is configured.
+    pub direct_ip: Option<String>,
+}
+
+/// `(ip, country, timezone)` from an IP-echo response.
+fn parse(body: &str) -> Option<(String, Option<String>, Option<String>)> {
+    let json: Value = serde_json::from_str(body.trim()).ok()?;
+    let field = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| json.get(*k).and_then(Value::as_str))
+            .map(String::from)
+    };
+    let ip = field(&["ip", "query"])?;
+    let country = field(&["country_code", "countryCode", "country"]);
+    let timezone = field(&["timezone"]);
+    Some((ip, country, timezone))
+}
+
+/// Every way `egress` differs from `check`.
+fn mismatches(egress: &Egress, check: &EgressCheck, proxied: bool) -> Vec<String> {
+    let mut problems = Vec::new();
+    if proxied && egress.direct_ip.as_deref() == Some(egress.ip.as_str()) {
+        problems.push(format!(
+            "exit IP {} is this machine's own; the proxy isn't applied",
+            egress.ip
+        ));
+    }
+    if let Some(ref ip) = check.ip {
+        if *ip != egress.ip {
+            problems.push(format!("exit IP is {}, expected {}", egress.ip, ip));
+        }
+    }
+    if let Some(ref country) = check.country {
+        match egress.country {
+            Some(ref actual) if actual.eq_ignore_ascii_case(country) => {}
+            ref actual => problems.push(format!(
+                "country is {}, expected {}",
+                actual.as_deref().unwrap_or("unknown"),
+                country
+            )),
+        }
+    }
+    let expected_tz = check.timezone.as_ref().or(egress.browser_timezone.as_ref());
+    if let (Some(expected), Some(actual)) = (expected_tz, egress.timezone.as_ref()) {
+        if expected != actual {
+            let whose = if check.timezone.is_some() {
+                "expected"
+            } else {
+                "but the browser reports"
+            };
+            problems.push(format!(
+                "exit IP timezone is {}, {} {}",
+                actual, whose, expected
+            ));
+        }
+    }
+    problems
+}
+
+/// Load `check.endpoint` in a scratch tab (same browser, same proxy), read
+/// the browser's timezone from `page`, and fail on any mismatch. With a
+/// `proxy`, the endpoint is also fetched directly to make sure the two IPs
+/// differ.
+pub async fn check(
+    browser: &Browser,
+    page: &Page,
+    proxy: Option<&str>,
+    check: &EgressCheck,
+) -> Result<Egress> {
+    let scratch = browser.new_page(&check.endpoint).await?;
+    let body = scratch.text().await;
+    let _ = browser.close_tab(scratch.target_id()).await;
+    let body = body?;
+    let (ip, country, timezone) = parse(&body).ok_or_else(|| {
+        eoka::Error::Decode(format!(
+            "{} didn't answer with an IP: {}",
+            check.endpoint,
+            body.chars().take(200).collect::<String>()
+        ))
+    })?;
+    let browser_timezone = page
+        .evaluate::<String>("Intl.DateTimeFormat().resolvedOptions().timeZone")
+        .await
+        .ok();
+    let direct_ip = match proxy {
+        Some(_) => direct_ip(&check.endpoint).await,
+        None => None,
+    };
+    let egress = Egress {
+        ip,
+        country,
+        timezone,
+        browser_timezone,
+        direct_ip,
+    };
+    let problems = mismatches(&egress, check, proxy.is_some());
+    if problems.is_empty() {
+        Ok(egress)
+    } else {
+        Err(eoka::Error::CdpSimple(format!(
+            "egress check failed: {}",
+            problems.join("; ")
+        )))
+    }
+}
+
+/// The endpoint's answer without any proxy. Best-effort: `None` if it
+/// can't be reached directly.
+async fn direct_ip(endpoint: &str) -> Option<String> {
+    let client = reqwest::Client::builder().no_proxy().build().ok()?;
+    let body = client.get(endpoint).send().await.ok()?.text().await.ok()?;
+    parse(&body).map(|(ip, _, _)| ip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_compares() {
+        let ipinfo = r#"{"ip":"203.0.113.7","country":"DE","timezone":"Europe/Berlin"}"#;
+        let ip_api = r#"{"query":"203.0.113.7","countryCode":"DE","country":"Germany","timezone":"Europe/Berlin"}"#;
+        for body in [ipinfo, ip_api] {
+            assert_eq!(
+                parse(body),
+                Some((
+                    "203.0.113.7".into(),
+                    Some("DE".into()),
+                    Some("Europe/Berlin".into())
+                ))
+            );
+        }
+        assert_eq!(parse("<html>blocked</html>"), None);
+
+        let egress = Egress {
+            ip: "203.0.113.7".into(),
+            country: Some("DE".into()),
+            timezone: Some("Europe/Berlin".into()),
+            browser_timezone: Some("America/Los_Angeles".into()),
+            direct_ip: Some("203.0.113.7".into()),
+        };
+        let check = EgressCheck {
+            country: Some("de".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            mismatches(&egress, &check, true),
+            [
+                "exit IP 203.0.113.7 is this machine's own; the proxy isn't applied",
+                "exit IP timezone is Europe/Berlin, but the browser reports America/Los_Angeles",
+            ]
+        );
+        let check = EgressCheck {
+            timezone: Some("Europe/Berlin".into()),
+            ..Default::default()
+        };
+        assert!(mismatches(&egress, &check, false).is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+//! Action evidence — small before/after screenshots of each action plus a
+//! crop of the element acted on, for debugging and for building datasets of
+//! agent actions. Enabled with `Session::set_capture_action_evidence(true)`.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use eoka::{Page, Result};
+use serde::Deserialize;
+
+use crate::InteractiveElement;
+
+/// Viewport screenshots are scaled down by this factor.
+const SCALE: f64 = 0.5;
+
+/// JPEG quality of every capture.
+const QUALITY: u8 = 60;
+
+/// Entries kept per session; the oldest are dropped first.
+pub const MAX_EVIDENCE: usize = 100;
+
+/// What one action looked like.
+#[derive(Debug, Clone)]
+pub struct ActionEvidence {
+    /// The action and its argument, e.g. `fill "jane@example.com"`
+    pub action: String,
+    /// The element acted on, as observed when the action started
+    pub target: Option<InteractiveElement>,
+    /// Viewport JPEG at half scale, just before the action
+    pub before: Vec<u8>,
+    /// Viewport JPEG at half scale, once the page settled after the action
+    pub after: Vec<u8>,
+    /// Full-scale JPEG of the target element's visible part, just before
+    /// the action
+    pub crop: Option<Vec<u8>>,
+}
+
+/// Document-space rectangle.
+#[derive(Deserialize)]
+struct Rect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+#[derive(Deserialize)]
+struct Captured {
+    data: String,
+}
+
+/// Captures taken before the action; [`Pending::finish`] completes them.
+pub(crate) struct Pending {
+    target: Option<InteractiveElement>,
+    before: Vec<u8>,
+    crop: Option<Vec<u8>>,
+}
+
+/// Capture the viewport and the target element before an action.
+pub(crate) async fn begin(page: &Page, target: Option<&InteractiveElement>) -> Result<Pending> {
+    let before = viewport(page).await?;
+    let crop = match target {
+        Some(el) => element(page, &el.selector).await?,
+        None => None,
+    };
+    Ok(Pending {
+        target: target.cloned(),
+        before,
+        crop,
+    })
+}
+
+impl Pending {
+    /// Capture the viewport after the action.
+    pub(crate) async fn finish(self, page: &Page, action: String) -> Result<ActionEvidence> {
+        Ok(ActionEvidence {
+            action,
+            target: self.target,
+            before: self.before,
+            after: viewport(page).await?,
+            crop: self.crop,
+        })
+    }
+}
+
+async fn viewport(page: &Page) -> Result<Vec<u8>> {
+    let json: String = page
+        .evaluate(
+            "JSON.stringify({ x: scrollX, y: scrollY, width: innerWidth, height: innerHeight })",
+        )
+        .await?;
+    let rect: Rect = serde_json::from_str(&json)
+        .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse viewport: {}", e)))?;
+    capture(page, &rect, SCALE).await
+}
+
+/// The crop of the element's on-screen part, or `None` when it's gone or
+/// scrolled out of view.
+async fn element(page: &Page, selector: &str) -> Result<Option<Vec<u8>>> {
+    let js = format!(
+        r#"(() => {{
+            const el = document.querySelector({});
+            if (!el) return JSON.stringify(null);
+            const r = el.getBoundingClientRect();
+            const x = Math.max(r.left, 0), y = Math.max(r.top, 0);
+            const width = Math.min(r.right, innerWidth) - x, height = Math.min(r.bottom, innerHeight) - y;
+            return JSON.stringify({{ x: x + scrollX, y: y + scrollY, width, height }});
+        }})()"#,
+        serde_json::to_string(selector).unwrap()
+    );
+    let json: String = page.evaluate(&js).await?;
+    let rect: Option<Rect> = serde_json::from_str(&json)
+        .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse element rect: {}", e)))?;
+    match rect {
+        Some(r) if r.width >= 1.0 && r.height >= 1.0 => capture(page, &r, 1.0).await.map(Some),
+        _ => Ok(None),
+    }
+}
+
+async fn capture(page: &Page, clip: &Rect, scale: f64) -> Result<Vec<u8>> {
+    let params = serde_json::json!({
+        "format": "jpeg",
+        "quality": QUALITY,
+        "clip": { "x": clip.x, "y": clip.y, "width": clip.width, "height": clip.height, "scale": scale },
+    });
+    let captured: Captured = page
+        .session()
+        .send("Page.captureScreenshot", &params)
+        .await?;
+    BASE64
+        .decode(captured.data)
+        .map_err(|e| eoka::Error::CdpSimple(format!("Failed to decode screenshot: {}", e)))
+}
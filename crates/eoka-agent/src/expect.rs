@@ -0,0 +1,279 @@
+//! Assertions for tests — wait for the page to reach an expected state, or
+//! fail with what it showed instead.
+//!
+//! ```rust,no_run
+//! # use eoka_agent::Session;
+//! # async fn example(session: &mut Session) -> eoka::Result<()> {
+//! session.expect().text_contains("Order placed").await?;
+//! session.expect().element("css:#total").value("$42").await?;
+//! session.expect().timeout(10_000).url_contains("/receipt").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use eoka::{Page, Result};
+use serde::Deserialize;
+
+use crate::target::{self, LivePattern};
+
+/// How long an expectation waits before failing, in milliseconds.
+pub const DEFAULT_TIMEOUT_MS: u64 = 5_000;
+
+/// How often an expectation re-checks the page.
+const POLL_MS: u64 = 100;
+
+/// Longest page or element text quoted in a failure message.
+const SNIPPET_CHARS: usize = 200;
+
+/// `(selector)` → the element's value, text and checked state, or null.
+const ELEMENT_STATE_JS: &str = r#"
+((sel) => {
+  const el = document.querySelector(sel);
+  if (!el) return null;
+  const r = el.getBoundingClientRect();
+  const s = getComputedStyle(el);
+  return {
+    value: 'value' in el ? String(el.value) : null,
+    text: (el.innerText || el.textContent || '').replace(/\s+/g, ' ').trim(),
+    checked: !!el.checked,
+    visible: r.width > 0 && r.height > 0 && s.visibility !== 'hidden' && s.display !== 'none',
+  };
+})
+"#;
+
+#[derive(Debug, Deserialize)]
+struct ElementState {
+    value: Option<String>,
+    text: String,
+    checked: bool,
+    visible: bool,
+}
+
+/// Expectations about a page, from [`crate::Session::expect`].
+#[derive(Clone, Copy)]
+pub struct Expect<'a> {
+    page: &'a Page,
+    timeout_ms: u64,
+}
+
+impl<'a> Expect<'a> {
+    pub fn new(page: &'a Page) -> Self {
+        Self {
+            page,
+            timeout_ms: DEFAULT_TIMEOUT_MS,
+        }
+    }
+
+    /// Wait up to `ms` instead of [`DEFAULT_TIMEOUT_MS`].
+    pub fn timeout(mut self, ms: u64) -> Self {
+        self.timeout_ms = ms;
+        self
+    }
+
+    /// The page's visible text contains `text`.
+    pub async fn text_contains(&self, text: &str) -> Result<()> {
+        let what = format!("page text to contain {:?}", text);
+        self.poll(&what, || async {
+            let page_text = self.page.text().await?;
+            Ok((!page_text.contains(text)).then(|| format!("page text: {}", snippet(&page_text))))
+        })
+        .await
+    }
+
+    /// The page's visible text doesn't contain `text`, e.g. an error banner.
+    pub async fn no_text(&self, text: &str) -> Result<()> {
+        let what = format!("page text not to contain {:?}", text);
+        self.poll(&what, || async {
+            let page_text = self.page.text().await?;
+            Ok(page_text
+                .contains(text)
+                .then(|| format!("page text: {}", snippet(&page_text))))
+        })
+        .await
+    }
+
+    /// The URL contains `part`.
+    pub async fn url_contains(&self, part: &str) -> Result<()> {
+        let what = format!("URL to contain {:?}", part);
+        self.poll(&what, || async {
+            let url = self.page.url().await?;
+            Ok((!url.contains(part)).then(|| format!("URL: {}", url)))
+        })
+        .await
+    }
+
+    /// The title is exactly `title`.
+    pub async fn title(&self, title: &str) -> Result<()> {
+        let what = format!("title {:?}", title);
+        self.poll(&what, || async {
+            let actual = self.page.title().await?;
+            Ok((actual != title).then(|| format!("title: {:?}", actual)))
+        })
+        .await
+    }
+
+    /// Expectations about the element matching a live target, e.g.
+    /// `css:#total`, `placeholder:Email` or `text:Checkout`.
+    pub fn element(&self, target: &str) -> ExpectElement<'a> {
+        ExpectElement {
+            expect: *self,
+            target: target.to_string(),
+            pattern: LivePattern::parse(target),
+        }
+    }
+
+    /// Re-run `check` until it returns `None`, or fail after the timeout
+    /// with the last thing it saw.
+    async fn poll<F, Fut>(&self, what: &str, mut check: F) -> Result<()>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<Option<String>>>,
+    {
+        let deadline = Instant::now() + Duration::from_millis(self.timeout_ms);
+        loop {
+            let seen = check().await?;
+            let Some(seen) = seen else {
+                return Ok(());
+            };
+            if Instant::now() >= deadline {
+                return Err(failure(what, self.timeout_ms, &seen));
+            }
+            self.page.wait(POLL_MS).await;
+        }
+    }
+}
+
+/// Expectations about one element, from [`Expect::element`].
+pub struct ExpectElement<'a> {
+    expect: Expect<'a>,
+    target: String,
+    pattern: LivePattern,
+}
+
+impl ExpectElement<'_> {
+    /// The element is on the page.
+    pub async fn exists(&self) -> Result<()> {
+        self.check("to exist", |_| None).await
+    }
+
+    /// The element is on the page and rendered.
+    pub async fn visible(&self) -> Result<()> {
+        self.check("to be visible", |state| {
+            (!state.visible).then(|| "it is hidden".to_string())
+        })
+        .await
+    }
+
+    /// The element is gone, e.g. a spinner or a dismissed dialog.
+    pub async fn absent(&self) -> Result<()> {
+        let what = format!("{} to be absent", self.target);
+        self.expect
+            .poll(&what, || async {
+                Ok(self.state().await?.map(|_| "it is still there".to_string()))
+            })
+            .await
+    }
+
+    /// The input's value is exactly `expected`.
+    pub async fn value(&self, expected: &str) -> Result<()> {
+        let what = format!("value {:?}", expected);
+        self.check(&what, |state| match state.value {
+            Some(ref v) if v == expected => None,
+            Some(ref v) => Some(format!("value: {:?}", v)),
+            None => Some("it has no value".to_string()),
+        })
+        .await
+    }
+
+    /// The element's text contains `text`.
+    pub async fn text_contains(&self, text: &str) -> Result<()> {
+        let what = format!("text to contain {:?}", text);
+        self.check(&what, |state| {
+            (!state.text.contains(text)).then(|| format!("text: {}", snippet(&state.text)))
+        })
+        .await
+    }
+
+    /// The checkbox or radio is checked (`true`) or not (`false`).
+    pub async fn checked(&self, checked: bool) -> Result<()> {
+        let what = if checked { "checked" } else { "unchecked" };
+        self.check(&format!("to be {}", what), |state| {
+            (state.checked != checked).then(|| format!("checked: {}", state.checked))
+        })
+        .await
+    }
+
+    /// Poll the element's state through `check`; a missing element fails
+    /// every expectation but [`ExpectElement::absent`].
+    async fn check(
+        &self,
+        what: &str,
+        check: impl Fn(&ElementState) -> Option<String>,
+    ) -> Result<()> {
+        let what = format!("{} {}", self.target, what);
+        self.expect
+            .poll(&what, || async {
+                Ok(match self.state().await? {
+                    Some(state) => check(&state),
+                    None => Some("no element matches".to_string()),
+                })
+            })
+            .await
+    }
+
+    async fn state(&self) -> Result<Option<ElementState>> {
+        let page = self.expect.page;
+        let resolved = target::resolve(page, &self.pattern).await?;
+        if !resolved.found {
+            return Ok(None);
+        }
+        let js = format!(
+            "{}({})",
+            ELEMENT_STATE_JS,
+            serde_json::to_string(&resolved.selector).unwrap()
+        );
+        page.evaluate(&js).await
+    }
+}
+
+fn failure(what: &str, timeout_ms: u64, seen: &str) -> eoka::Error {
+    eoka::Error::Timeout(format!(
+        "expected {} within {} ms, but {}",
+        what, timeout_ms, seen
+    ))
+}
+
+/// `text` quoted and cut to [`SNIPPET_CHARS`], whitespace collapsed.
+fn snippet(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(SNIPPET_CHARS) {
+        Some((end, _)) => format!("{:?}…", &text[..end]),
+        None => format!("{:?}", text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failure_message() {
+        let e = failure(
+            "page text to contain \"Order placed\"",
+            5000,
+            &format!("page text: {}", snippet("Your   cart\n is empty")),
+        );
+        assert_eq!(
+            e.to_string(),
+            "Timeout: expected page text to contain \"Order placed\" within 5000 ms, \
+             but page text: \"Your cart is empty\""
+        );
+
+        let long = "x".repeat(SNIPPET_CHARS + 10);
+        assert!(snippet(&long).ends_with("\"…"));
+        assert_eq!(snippet(&long).chars().count(), SNIPPET_CHARS + 3);
+    }
+}
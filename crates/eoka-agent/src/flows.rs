@@ -0,0 +1,617 @@
+//! Multi-step flows that tie a [`Session`] to the other eoka crates.
+
+use chrono::{DateTime, Duration, Utc};
+use eoka::{Page, Result};
+use eoka_email::{
+    AsyncImapClient, EmailMessage, ImapConfig, LinkFilter, SearchCriteria, WaitOptions,
+};
+use hmac::{Hmac, Mac};
+
+use crate::deterministic;
+use crate::target::{self, LivePattern, Target};
+use crate::Session;
+
+/// How [`verify_email_with`] waits for and uses the email.
+#[derive(Debug, Clone)]
+pub struct VerifyOptions {
+    /// How long to wait for the email, and how often to poll without IDLE.
+    /// Default: 2 minutes, polling every 2 seconds.
+    pub wait: WaitOptions,
+    /// Which links may be followed.
+    pub links: LinkFilter,
+    /// Fill the email's code into this input instead of following its link.
+    pub code_input: Option<Target>,
+    /// Delete the email once its link was followed or its code filled, so
+    /// later runs don't pick it up again.
+    pub delete: bool,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        Self {
+            wait: WaitOptions::new(Duration::minutes(2), Duration::seconds(2)),
+            links: LinkFilter::default(),
+            code_input: None,
+            delete: false,
+        }
+    }
+}
+
+impl VerifyOptions {
+    /// Fill the code into `target`, e.g. `placeholder:Code` or `css:#otp`.
+    pub fn fill_code(mut self, target: &str) -> Self {
+        self.code_input = Some(Target::parse(target));
+        self
+    }
+
+    /// Only follow links to these domains (subdomains included).
+    pub fn allow_domains<I, S>(mut self, domains: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.links.allow_domains = Some(domains.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn wait(mut self, wait: WaitOptions) -> Self {
+        self.wait = wait;
+        self
+    }
+
+    pub fn delete(mut self, delete: bool) -> Self {
+        self.delete = delete;
+        self
+    }
+}
+
+/// What [`verify_email`] did with the email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verified {
+    /// Navigated the session to this link.
+    Link(String),
+    /// Filled this code into the code input.
+    Code(String),
+}
+
+/// Wait for a verification email and follow its best link in the session.
+///
+/// ```rust,no_run
+/// # use eoka_agent::{flows, Session};
+/// # use eoka_email::{ImapConfig, SearchCriteria};
+/// # async fn example(session: &mut Session) -> eoka::Result<()> {
+/// let imap = ImapConfig::new("imap.gmail.com", 993, "me@gmail.com", "app-password");
+/// let criteria = SearchCriteria::new().from("no-reply@example.com").since_minutes(10);
+/// flows::verify_email(session, &imap, &criteria).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn verify_email(
+    session: &mut Session,
+    imap: &ImapConfig,
+    criteria: &SearchCriteria,
+) -> Result<Verified> {
+    verify_email_with(session, imap, criteria, &VerifyOptions::default()).await
+}
+
+/// [`verify_email`] with options: fill a code instead, restrict link domains,
+/// change the wait, or delete the email afterwards.
+pub async fn verify_email_with(
+    session: &mut Session,
+    imap: &ImapConfig,
+    criteria: &SearchCriteria,
+    options: &VerifyOptions,
+) -> Result<Verified> {
+    let mut client = AsyncImapClient::connect(imap).await.map_err(email_error)?;
+    let msg = client
+        .wait_for_message(criteria, &options.wait)
+        .await
+        .map_err(email_error);
+    let msg = match msg {
+        Ok(msg) => msg,
+        Err(e) => {
+            let _ = client.logout().await;
+            return Err(e);
+        }
+    };
+
+    // Delete only once the email has been used, so a failed fill can be retried.
+    let verified = use_email(session, &msg, options).await;
+    if options.delete && verified.is_ok() {
+        let _ = client.delete(msg.uid).await;
+    }
+    let _ = client.logout().await;
+    verified
+}
+
+/// Follow the link in `msg`, or fill its code when `code_input` is set.
+async fn use_email(
+    session: &mut Session,
+    msg: &EmailMessage,
+    options: &VerifyOptions,
+) -> Result<Verified> {
+    let Some(ref input) = options.code_input else {
+        let link = eoka_email::extract_first_link(msg, &options.links).ok_or_else(|| {
+            eoka::Error::CdpSimple("no link found in the verification email".into())
+        })?;
+        session.goto(&link).await?;
+        return Ok(Verified::Link(link));
+    };
+
+    let code = eoka_email::otp::best(msg)
+        .ok_or_else(|| eoka::Error::CdpSimple("no code found in the verification email".into()))?;
+    match input {
+        Target::Index(index) => {
+            session.fill(*index, &code).await?;
+        }
+        Target::Live(pattern) => {
+            let resolved = target::resolve(session.page(), pattern).await?;
+            if !resolved.found {
+                return Err(eoka::Error::ElementNotFound(
+                    resolved.error.unwrap_or_default(),
+                ));
+            }
+            session.page().fill(&resolved.selector, &code).await?;
+            session.wait_for_stable().await?;
+        }
+    }
+    Ok(Verified::Code(code))
+}
+
+/// Where [`login`] gets the second-factor code.
+#[derive(Debug, Clone)]
+pub enum TotpSource {
+    /// Base32 authenticator secret (the one behind the QR code); codes are
+    /// generated with [`totp`].
+    Secret(String),
+    /// Wait for an email and use its one-time code.
+    Email(Box<EmailCode>),
+}
+
+impl TotpSource {
+    /// Codes from emails matching `criteria`, waiting up to 2 minutes. Only
+    /// emails received after the password was submitted count.
+    pub fn email(imap: ImapConfig, criteria: SearchCriteria) -> Self {
+        TotpSource::Email(Box::new(EmailCode {
+            imap,
+            criteria,
+            wait: VerifyOptions::default().wait,
+        }))
+    }
+}
+
+/// The mailbox and email a [`TotpSource::Email`] code comes from.
+#[derive(Debug, Clone)]
+pub struct EmailCode {
+    pub imap: ImapConfig,
+    pub criteria: SearchCriteria,
+    pub wait: WaitOptions,
+}
+
+/// How [`login`] knows it worked.
+#[derive(Debug, Clone)]
+pub enum SuccessCheck {
+    /// The URL contains this, e.g. `/dashboard`.
+    UrlContains(String),
+    /// The page text contains this, e.g. `Sign out`.
+    Text(String),
+    /// This element is on the page.
+    Element(LivePattern),
+}
+
+/// A login form: where it is, what to fill and how to tell it worked.
+///
+/// The targets default to the usual `autocomplete`/`type` attributes, so
+/// most forms need only [`LoginSpec::new`] and a success check. A `css:`
+/// target listing several selectors is tried one selector at a time, in
+/// the order written, so earlier ones win over document order.
+#[derive(Debug, Clone)]
+pub struct LoginSpec {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    pub username_target: LivePattern,
+    pub password_target: LivePattern,
+    /// Clicked after the password, and after the username when the password
+    /// field only shows up on a second page.
+    pub submit_target: LivePattern,
+    /// `None`: done once the form is submitted and the page settles.
+    pub success_check: Option<SuccessCheck>,
+    /// `None`: no second factor.
+    pub totp_source: Option<TotpSource>,
+    pub totp_target: LivePattern,
+    /// How long to wait for each step (the password field, the code field,
+    /// the success check). Default: 10 seconds.
+    pub step_timeout_ms: u64,
+}
+
+impl LoginSpec {
+    pub fn new(
+        url: impl Into<String>,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        let css = |s: &str| LivePattern::Css(s.into());
+        Self {
+            url: url.into(),
+            username: username.into(),
+            password: password.into(),
+            username_target: css(
+                "input[autocomplete=username], input[type=email], input[name*=user i], \
+                 input[name*=email i], input[name*=login i], \
+                 form:not([role=search]) input[type=text]:not([name*=search i])",
+            ),
+            password_target: css("input[type=password]"),
+            submit_target: css("button[type=submit], input[type=submit], form button"),
+            success_check: None,
+            totp_source: None,
+            totp_target: css(
+                "input[autocomplete=one-time-code], input[name*=otp i], input[name*=totp i], \
+                 input[name*=mfa i], input[name=code i], input[name*=verification i], \
+                 input[inputmode=numeric]",
+            ),
+            step_timeout_ms: 10_000,
+        }
+    }
+
+    /// Targets use the live pattern syntax, e.g. `placeholder:Email`.
+    pub fn username_target(mut self, target: &str) -> Self {
+        self.username_target = LivePattern::parse(target);
+        self
+    }
+
+    pub fn password_target(mut self, target: &str) -> Self {
+        self.password_target = LivePattern::parse(target);
+        self
+    }
+
+    pub fn submit_target(mut self, target: &str) -> Self {
+        self.submit_target = LivePattern::parse(target);
+        self
+    }
+
+    pub fn success(mut self, check: SuccessCheck) -> Self {
+        self.success_check = Some(check);
+        self
+    }
+
+    pub fn totp(mut self, source: TotpSource) -> Self {
+        self.totp_source = Some(source);
+        self
+    }
+
+    pub fn totp_target(mut self, target: &str) -> Self {
+        self.totp_target = LivePattern::parse(target);
+        self
+    }
+}
+
+/// What [`login`] went through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggedIn {
+    /// The password field was on a second page, after the username.
+    pub two_page: bool,
+    /// A second-factor code was filled.
+    pub totp: bool,
+    /// Where the session ended up.
+    pub url: String,
+}
+
+/// Open the login page, fill the form and submit it, handling two-page
+/// username → password flows and a TOTP or emailed code.
+///
+/// ```rust,no_run
+/// # use eoka_agent::{flows, Session};
+/// # use eoka_agent::flows::{LoginSpec, SuccessCheck, TotpSource};
+/// # async fn example(session: &mut Session) -> eoka::Result<()> {
+/// let spec = LoginSpec::new("https://example.com/login", "me@example.com", "hunter2")
+///     .success(SuccessCheck::UrlContains("/dashboard".into()))
+///     .totp(TotpSource::Secret("JBSWY3DPEHPK3PXP".into()));
+/// flows::login(session, &spec).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn login(session: &mut Session, spec: &LoginSpec) -> Result<LoggedIn> {
+    session.goto(&spec.url).await?;
+    submit_login(session.page(), spec).await
+}
+
+/// [`login`] on a page already showing the login form, for callers
+/// without a [`Session`].
+pub async fn submit_login(page: &Page, spec: &LoginSpec) -> Result<LoggedIn> {
+    let timeout = spec.step_timeout_ms;
+    let username = wait_visible(page, &spec.username_target, timeout)
+        .await?
+        .ok_or_else(|| not_found("username field", &spec.username_target))?;
+    page.fill(&username, &spec.username).await?;
+
+    let mut two_page = false;
+    let password = match wait_visible(page, &spec.password_target, 0).await? {
+        Some(password) => password,
+        None => {
+            two_page = true;
+            click(page, &spec.submit_target).await?;
+            wait_visible(page, &spec.password_target, timeout)
+                .await?
+                .ok_or_else(|| not_found("password field", &spec.password_target))?
+        }
+    };
+    page.fill(&password, &spec.password).await?;
+    let submitted_at = Utc::now();
+    click(page, &spec.submit_target).await?;
+
+    let mut totp_used = false;
+    if let Some(ref source) = spec.totp_source {
+        // A remembered device may skip the second factor
+        if let Some(input) = wait_visible(page, &spec.totp_target, timeout).await? {
+            let code = match source {
+                TotpSource::Secret(secret) => totp(secret, chrono::Utc::now().timestamp() as u64)?,
+                TotpSource::Email(email) => email_code(email, submitted_at).await?,
+            };
+            page.fill(&input, &code).await?;
+            // Many code forms submit themselves once the last digit is in
+            if wait_visible(page, &spec.totp_target, 0).await?.is_some() {
+                match wait_visible(page, &spec.submit_target, 0).await? {
+                    Some(_) => click(page, &spec.submit_target).await?,
+                    None => deterministic::press_key(page, "Enter").await?,
+                }
+            }
+            settle(page).await;
+            totp_used = true;
+        }
+    }
+
+    if let Some(ref check) = spec.success_check {
+        wait_for_success(page, check, timeout).await?;
+    }
+    Ok(LoggedIn {
+        two_page,
+        totp: totp_used,
+        url: page.url().await?,
+    })
+}
+
+/// Selector of a visible element matching `pattern`, polling until
+/// `timeout_ms`; `None` if none shows up. Alternatives of a selector list
+/// are tried in the order written.
+async fn wait_visible(
+    page: &Page,
+    pattern: &LivePattern,
+    timeout_ms: u64,
+) -> Result<Option<String>> {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+    let alternatives = alternatives(pattern);
+    loop {
+        for alternative in &alternatives {
+            let r = target::resolve(page, alternative).await?;
+            if r.found && r.bbox.width > 0.0 && r.bbox.height > 0.0 {
+                return Ok(Some(r.selector));
+            }
+        }
+        if std::time::Instant::now() >= deadline {
+            return Ok(None);
+        }
+        page.wait(250).await;
+    }
+}
+
+/// The selectors of a `css:` list, split at top-level commas; any other
+/// pattern as it is.
+fn alternatives(pattern: &LivePattern) -> Vec<LivePattern> {
+    let LivePattern::Css(list) = pattern else {
+        return vec![pattern.clone()];
+    };
+    let mut parts = Vec::new();
+    let (mut depth, mut quote, mut start) = (0usize, None, 0);
+    for (i, c) in list.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => {
+                parts.push(&list[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&list[start..]);
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| LivePattern::Css(p.into()))
+        .collect()
+}
+
+async fn click(page: &Page, pattern: &LivePattern) -> Result<()> {
+    let selector = wait_visible(page, pattern, 0)
+        .await?
+        .ok_or_else(|| not_found("submit button", pattern))?;
+    page.click(&selector).await?;
+    settle(page).await;
+    Ok(())
+}
+
+/// Best-effort wait for the page to settle after a submit.
+async fn settle(page: &Page) {
+    let _ = page.wait_for_network_idle(200, 2000).await;
+    page.wait(100).await;
+}
+
+async fn wait_for_success(page: &Page, check: &SuccessCheck, timeout_ms: u64) -> Result<()> {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+    loop {
+        let ok = match check {
+            SuccessCheck::UrlContains(part) => page.url().await?.contains(part.as_str()),
+            SuccessCheck::Text(text) => page.text().await?.contains(text.as_str()),
+            SuccessCheck::Element(pattern) => target::resolve(page, pattern).await?.found,
+        };
+        if ok {
+            return Ok(());
+        }
+        if std::time::Instant::now() >= deadline {
+            return Err(eoka::Error::Timeout(format!(
+                "login: success check {:?} not met (at {})",
+                check,
+                page.url().await.unwrap_or_default()
+            )));
+        }
+        page.wait(250).await;
+    }
+}
+
+/// The code from the first email matching `email.criteria` received after
+/// `sent_at`, so a code from an earlier login is never reused.
+async fn email_code(email: &EmailCode, sent_at: DateTime<Utc>) -> Result<String> {
+    let mut criteria = email.criteria.clone();
+    if criteria.since.is_none_or(|since| since < sent_at) {
+        criteria = criteria.since(sent_at);
+    }
+    let mut client = AsyncImapClient::connect(&email.imap)
+        .await
+        .map_err(email_error)?;
+    let msg = client.wait_for_message(&criteria, &email.wait).await;
+    let _ = client.logout().await;
+    let msg = msg.map_err(email_error)?;
+    eoka_email::otp::best(&msg)
+        .ok_or_else(|| eoka::Error::CdpSimple("no code found in the login email".into()))
+}
+
+fn not_found(what: &str, pattern: &LivePattern) -> eoka::Error {
+    eoka::Error::ElementNotFound(format!("login: no visible {} ({:?})", what, pattern))
+}
+
+/// RFC 6238 code for a base32 `secret` at `unix_time`: 6 digits, 30-second
+/// steps, HMAC-SHA1 — what authenticator apps use.
+pub fn totp(secret: &str, unix_time: u64) -> Result<String> {
+    let key = base32_decode(secret)
+        .ok_or_else(|| eoka::Error::CdpSimple("TOTP secret is not valid base32".into()))?;
+    let mut mac = Hmac::<sha1::Sha1>::new_from_slice(&key)
+        .map_err(|e| eoka::Error::CdpSimple(format!("TOTP secret: {}", e)))?;
+    mac.update(&(unix_time / 30).to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[19] & 0x0f) as usize;
+    let bin = u32::from_be_bytes([
+        hash[offset],
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]) & 0x7fff_ffff;
+    Ok(format!("{:06}", bin % 1_000_000))
+}
+
+/// RFC 4648 base32, ignoring case, spaces, dashes and padding.
+fn base32_decode(s: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in s.chars().filter(|c| !matches!(c, ' ' | '-' | '=')) {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u32 - 'A' as u32,
+            c @ '2'..='7' => c as u32 - '2' as u32 + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    (!out.is_empty()).then_some(out)
+}
+
+fn email_error(e: eoka_email::Error) -> eoka::Error {
+    match e {
+        eoka_email::Error::Timeout => eoka::Error::Timeout("no matching email arrived".into()),
+        e => eoka::Error::CdpSimple(format!("email: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LivePattern;
+
+    #[test]
+    fn options_builder() {
+        let options = VerifyOptions::default()
+            .fill_code("placeholder:Code")
+            .allow_domains(["example.com"]);
+        assert!(matches!(
+            options.code_input,
+            Some(Target::Live(LivePattern::Placeholder(ref p))) if p == "Code"
+        ));
+        assert_eq!(
+            options.links.allow_domains.as_deref(),
+            Some(&["example.com".to_string()][..])
+        );
+        assert!(!options.delete);
+    }
+
+    #[test]
+    fn totp_rfc6238_vectors() {
+        // RFC 6238 appendix B, SHA-1 seed "12345678901234567890", last 6 digits
+        let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        assert_eq!(totp(secret, 59).unwrap(), "287082");
+        assert_eq!(totp(secret, 1111111109).unwrap(), "081804");
+        assert_eq!(totp(secret, 2000000000).unwrap(), "279037");
+        // Authenticator apps show secrets lowercase and grouped
+        assert_eq!(
+            totp("gezd gnbv gy3t qojq gezd gnbv gy3t qojq", 59).unwrap(),
+            "287082"
+        );
+        assert!(totp("not base32!", 59).is_err());
+    }
+
+    #[test]
+    fn login_spec_builder() {
+        let spec = LoginSpec::new("https://example.com/login", "me", "pw")
+            .username_target("placeholder:Email")
+            .submit_target("text:Continue");
+        assert!(matches!(spec.username_target, LivePattern::Placeholder(ref p) if p == "Email"));
+        assert!(matches!(spec.submit_target, LivePattern::Text(ref t) if t == "Continue"));
+        assert!(
+            matches!(spec.password_target, LivePattern::Css(ref c) if c == "input[type=password]")
+        );
+        assert!(spec.totp_source.is_none());
+    }
+
+    #[test]
+    fn selector_lists_split_in_order() {
+        let css = |s: &str| LivePattern::Css(s.into());
+        assert_eq!(
+            alternatives(&css("input[type=email], :is(a, b) input, [data-x=\"a,b\"]")),
+            vec![
+                css("input[type=email]"),
+                css(":is(a, b) input"),
+                css("[data-x=\"a,b\"]")
+            ]
+        );
+        let text = LivePattern::Text("Sign in, please".into());
+        assert_eq!(alternatives(&text), vec![text.clone()]);
+
+        let spec = LoginSpec::new("https://example.com/login", "me", "pw");
+        let username = alternatives(&spec.username_target);
+        assert_eq!(username[0], css("input[autocomplete=username]"));
+        assert!(matches!(username.last(), Some(LivePattern::Css(ref c)) if c.starts_with("form")));
+        assert!(alternatives(&spec.totp_target)
+            .iter()
+            .all(|p| !matches!(p, LivePattern::Css(ref c) if c.contains("name*=code"))));
+    }
+
+    #[test]
+    fn email_timeout_maps_to_timeout() {
+        assert!(matches!(
+            email_error(eoka_email::Error::Timeout),
+            eoka::Error::Timeout(_)
+        ));
+        assert!(matches!(
+            email_error(eoka_email::Error::NotFound),
+            eoka::Error::CdpSimple(_)
+        ));
+    }
+}
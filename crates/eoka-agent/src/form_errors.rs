@@ -0,0 +1,142 @@
+//! Form validation messages — what the page shows after a bad fill or a
+//! rejected submit, keyed by the field they belong to, so an agent can read
+//! why a submit failed instead of screenshotting for it.
+//!
+//! Collected from fields marked `aria-invalid` (and their
+//! `aria-errormessage` / `aria-describedby` text), fields the browser
+//! flags `:user-invalid`, visible `[role="alert"]` nodes and elements with
+//! an `error` / `invalid` class.
+
+use eoka::{Page, Result};
+use serde::Deserialize;
+use std::fmt;
+
+/// One visible validation message.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FormError {
+    /// Label of the nearest field (its `<label>`, `aria-label`, placeholder
+    /// or name); `None` for form-level messages
+    pub field: Option<String>,
+    /// CSS selector for that field
+    pub selector: Option<String>,
+    /// The message, whitespace collapsed
+    pub message: String,
+}
+
+impl fmt::Display for FormError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.field {
+            Some(ref field) => write!(f, "{}: {}", field, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// JavaScript that collects visible validation messages, field-level first.
+const FORM_ERRORS_JS: &str = r#"
+(() => {
+  const FIELDS = 'input:not([type="hidden"]):not([type="submit"]):not([type="button"]), select, textarea';
+  const ERROR_CLASS = /(^|[-_\s])(error|errors|invalid)([-_\s]|$)/i;
+  const clean = s => (s || '').replace(/\s+/g, ' ').trim();
+  const shown = el => {
+    const r = el.getBoundingClientRect();
+    const s = getComputedStyle(el);
+    return r.width > 0 && r.height > 0 && s.display !== 'none' && s.visibility !== 'hidden';
+  };
+  const textOf = ids => clean((ids || '').split(/\s+/)
+    .map(id => id && document.getElementById(id)).filter(el => el && shown(el))
+    .map(el => el.innerText).join(' '));
+
+  function selectorFor(el) {
+    if (el.id) return '#' + CSS.escape(el.id);
+    if (el.name) return el.tagName.toLowerCase() + '[name="' + CSS.escape(el.name) + '"]';
+    const parts = [];
+    for (let n = el; n && n.nodeType === 1 && n !== document.body; n = n.parentElement) {
+      let part = n.tagName.toLowerCase();
+      const sibs = n.parentElement ? [...n.parentElement.children].filter(c => c.tagName === n.tagName) : [];
+      if (sibs.length > 1) part += ':nth-of-type(' + (sibs.indexOf(n) + 1) + ')';
+      parts.unshift(part);
+    }
+    return parts.join(' > ');
+  }
+
+  function labelOf(field) {
+    const label = field.labels && field.labels[0];
+    return clean(label && label.innerText) || clean(field.getAttribute('aria-label'))
+      || textOf(field.getAttribute('aria-labelledby')) || clean(field.placeholder)
+      || clean(field.name) || null;
+  }
+
+  // The field an error element describes: one pointing at it by id, or the
+  // only field in a nearby container.
+  function fieldFor(el) {
+    if (el.id) {
+      const escaped = CSS.escape(el.id);
+      const ref = document.querySelector(`[aria-errormessage~="${escaped}"], [aria-describedby~="${escaped}"]`);
+      if (ref) return ref;
+    }
+    let n = el.parentElement;
+    for (let depth = 0; n && n !== document.body && depth < 4; depth++, n = n.parentElement) {
+      const fields = n.querySelectorAll(FIELDS);
+      if (fields.length === 1) return fields[0];
+      if (fields.length > 1) break;
+    }
+    return null;
+  }
+
+  const out = [];
+  const seen = new Set();
+  const add = (field, message) => {
+    message = clean(message);
+    if (!message || message.length > 300) return;
+    const name = field ? labelOf(field) : null;
+    const key = name + '\u0000' + message;
+    if (seen.has(key)) return;
+    seen.add(key);
+    out.push({ field: name, selector: field ? selectorFor(field) : null, message });
+  };
+
+  for (const field of document.querySelectorAll(FIELDS)) {
+    let userInvalid = false;
+    try { userInvalid = field.matches(':user-invalid'); } catch (e) {}
+    if (field.getAttribute('aria-invalid') !== 'true' && !userInvalid) continue;
+    if (!shown(field)) continue;
+    add(field, textOf(field.getAttribute('aria-errormessage'))
+      || textOf(field.getAttribute('aria-describedby'))
+      || field.validationMessage || 'invalid');
+  }
+
+  const candidates = [...document.querySelectorAll('[role="alert"], [class]')]
+    .filter(el => el.getAttribute('role') === 'alert' || ERROR_CLASS.test(el.className.baseVal ?? el.className))
+    .filter(el => !el.matches(FIELDS) && !el.querySelector(FIELDS) && shown(el));
+  for (const el of candidates) {
+    // Nested matches (an .error inside a role=alert) report once, innermost.
+    if (candidates.some(other => other !== el && el.contains(other))) continue;
+    add(fieldFor(el), el.innerText);
+  }
+
+  out.sort((a, b) => (a.field === null) - (b.field === null));
+  return out;
+})()
+"#;
+
+/// Visible validation messages on the page, field-level ones first.
+pub async fn form_errors(page: &Page) -> Result<Vec<FormError>> {
+    page.evaluate(FORM_ERRORS_JS).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        let json = r##"[
+            {"field": "Email", "selector": "#email", "message": "Enter a valid email address"},
+            {"field": null, "selector": null, "message": "Please fix the errors below"}
+        ]"##;
+        let errors: Vec<FormError> = serde_json::from_str(json).unwrap();
+        assert_eq!(errors[0].to_string(), "Email: Enter a valid email address");
+        assert_eq!(errors[1].to_string(), "Please fix the errors below");
+    }
+}
@@ -0,0 +1,262 @@
+//! Box and viewport math — centers, overlaps and on-screen checks for
+//! element boxes, so click-at and hover code doesn't redo it each time.
+//! Boxes are in CSS pixels relative to the viewport, as observed.
+
+use eoka::{BoundingBox, Page, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::target::BBox;
+
+/// Geometry on anything with an `x, y, width, height` box.
+pub trait Geometry {
+    /// `(x, y, width, height)`.
+    fn bounds(&self) -> (f64, f64, f64, f64);
+
+    /// Center point, for `click_at` and mouse moves.
+    fn center(&self) -> (f64, f64) {
+        let (x, y, w, h) = self.bounds();
+        (x + w / 2.0, y + h / 2.0)
+    }
+
+    fn area(&self) -> f64 {
+        let (_, _, w, h) = self.bounds();
+        w.max(0.0) * h.max(0.0)
+    }
+
+    /// Whether `(px, py)` is inside the box, edges included.
+    fn contains_point(&self, px: f64, py: f64) -> bool {
+        let (x, y, w, h) = self.bounds();
+        px >= x && px <= x + w && py >= y && py <= y + h
+    }
+
+    /// The overlapping part of two boxes, if they overlap by a non-zero area.
+    fn intersection(&self, other: &impl Geometry) -> Option<BoundingBox> {
+        let (ax, ay, aw, ah) = self.bounds();
+        let (bx, by, bw, bh) = other.bounds();
+        let left = ax.max(bx);
+        let top = ay.max(by);
+        let right = (ax + aw).min(bx + bw);
+        let bottom = (ay + ah).min(by + bh);
+        (right > left && bottom > top).then_some(BoundingBox {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        })
+    }
+
+    /// Whether the boxes overlap by a non-zero area.
+    fn intersects(&self, other: &impl Geometry) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Intersection over union: 1.0 for the same box, 0.0 when disjoint.
+    fn iou(&self, other: &impl Geometry) -> f64 {
+        let shared = self.intersection(other).map_or(0.0, |i| i.area());
+        let union = self.area() + other.area() - shared;
+        if union > 0.0 {
+            shared / union
+        } else {
+            0.0
+        }
+    }
+
+    /// Share of the box inside `viewport`, 0.0–1.0.
+    fn visible_fraction(&self, viewport: &Viewport) -> f64 {
+        let area = self.area();
+        if area <= 0.0 {
+            return 0.0;
+        }
+        self.intersection(&viewport.rect())
+            .map_or(0.0, |i| i.area() / area)
+    }
+
+    /// Whether any of the box is on screen.
+    fn is_in_viewport(&self, viewport: &Viewport) -> bool {
+        self.intersects(&viewport.rect())
+    }
+}
+
+impl Geometry for BoundingBox {
+    fn bounds(&self) -> (f64, f64, f64, f64) {
+        (self.x, self.y, self.width, self.height)
+    }
+}
+
+impl Geometry for BBox {
+    fn bounds(&self) -> (f64, f64, f64, f64) {
+        (self.x, self.y, self.width, self.height)
+    }
+}
+
+/// The visible area of the page and how far it's scrolled.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Viewport {
+    /// `innerWidth`, in CSS pixels.
+    pub width: f64,
+    /// `innerHeight`, in CSS pixels.
+    pub height: f64,
+    /// `scrollX`: how far the page is scrolled right.
+    pub scroll_x: f64,
+    /// `scrollY`: how far the page is scrolled down.
+    pub scroll_y: f64,
+    pub device_pixel_ratio: f64,
+}
+
+impl Viewport {
+    /// The viewport as a box at `(0, 0)`, in the same coordinates as
+    /// element boxes.
+    pub fn rect(&self) -> BoundingBox {
+        BoundingBox {
+            x: 0.0,
+            y: 0.0,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// A viewport-relative point in page coordinates.
+    pub fn to_page(&self, x: f64, y: f64) -> (f64, f64) {
+        (x + self.scroll_x, y + self.scroll_y)
+    }
+
+    /// A part of the viewport by name: `top`, `bottom`, `left` or `right`
+    /// half, a quadrant like `top-right`, or `center` (the middle half each
+    /// way not otherwise given, so `top center` is the top half's middle).
+    /// Filler words are skipped, so "top-right quadrant" works. Also takes
+    /// explicit `x,y,width,height`. `None` if `spec` isn't either.
+    pub fn region(&self, spec: &str) -> Option<BoundingBox> {
+        let numbers: Vec<f64> = spec
+            .split(',')
+            .map(|n| n.trim().parse().ok())
+            .collect::<Option<_>>()
+            .unwrap_or_default();
+        if let [x, y, width, height] = numbers[..] {
+            return Some(BoundingBox {
+                x,
+                y,
+                width,
+                height,
+            });
+        }
+
+        let spec = spec.to_lowercase();
+        let mut horizontal: Option<(f64, f64)> = None;
+        let mut vertical: Option<(f64, f64)> = None;
+        let mut center = false;
+        for word in spec.split(|c: char| !c.is_alphanumeric()) {
+            let (axis, span) = match word {
+                "" | "the" | "of" | "quadrant" | "half" | "corner" | "side" | "panel" | "area" => {
+                    continue
+                }
+                "top" | "upper" => (&mut vertical, (0.0, 0.5)),
+                "bottom" | "lower" => (&mut vertical, (0.5, 1.0)),
+                "left" => (&mut horizontal, (0.0, 0.5)),
+                "right" => (&mut horizontal, (0.5, 1.0)),
+                "center" | "centre" | "middle" => {
+                    center = true;
+                    continue;
+                }
+                _ => return None,
+            };
+            if axis.replace(span).is_some_and(|old| old != span) {
+                return None;
+            }
+        }
+        if horizontal.is_none() && vertical.is_none() && !center {
+            return None;
+        }
+        let fallback = if center { (0.25, 0.75) } else { (0.0, 1.0) };
+        let (x0, x1) = horizontal.unwrap_or(fallback);
+        let (y0, y1) = vertical.unwrap_or(fallback);
+        Some(BoundingBox {
+            x: x0 * self.width,
+            y: y0 * self.height,
+            width: (x1 - x0) * self.width,
+            height: (y1 - y0) * self.height,
+        })
+    }
+}
+
+const VIEWPORT_JS: &str = r#"
+JSON.stringify({
+  width: window.innerWidth,
+  height: window.innerHeight,
+  scrollX: window.scrollX,
+  scrollY: window.scrollY,
+  devicePixelRatio: window.devicePixelRatio || 1,
+})
+"#;
+
+/// The page's current viewport size and scroll offsets.
+pub async fn viewport(page: &Page) -> Result<Viewport> {
+    let json: String = page.evaluate(VIEWPORT_JS).await?;
+    serde_json::from_str(&json).map_err(|e| eoka::Error::Decode(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(x: f64, y: f64, width: f64, height: f64) -> BoundingBox {
+        BoundingBox {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn box_math() {
+        let a = bbox(0.0, 0.0, 100.0, 50.0);
+        let b = bbox(50.0, 0.0, 100.0, 50.0);
+        assert_eq!(Geometry::center(&a), (50.0, 25.0));
+        assert!(a.contains_point(100.0, 50.0));
+        assert!(!a.contains_point(100.5, 10.0));
+        assert!(a.intersects(&b));
+        assert!(
+            !a.intersects(&bbox(100.0, 0.0, 10.0, 10.0)),
+            "touching edges"
+        );
+        assert!((a.iou(&b) - 2500.0 / 7500.0).abs() < 1e-9);
+        assert_eq!(a.iou(&a), 1.0);
+
+        let viewport = Viewport {
+            width: 800.0,
+            height: 600.0,
+            scroll_x: 0.0,
+            scroll_y: 1000.0,
+            device_pixel_ratio: 2.0,
+        };
+        let half_below = bbox(0.0, 550.0, 100.0, 100.0);
+        assert!(half_below.is_in_viewport(&viewport));
+        assert_eq!(half_below.visible_fraction(&viewport), 0.5);
+        assert!(!bbox(0.0, -200.0, 100.0, 100.0).is_in_viewport(&viewport));
+        assert_eq!(viewport.to_page(10.0, 20.0), (10.0, 1020.0));
+    }
+
+    #[test]
+    fn named_regions() {
+        let viewport = Viewport {
+            width: 800.0,
+            height: 600.0,
+            scroll_x: 0.0,
+            scroll_y: 0.0,
+            device_pixel_ratio: 1.0,
+        };
+        let region = |spec| viewport.region(spec).map(|b| (b.x, b.y, b.width, b.height));
+        assert_eq!(
+            region("top-right quadrant"),
+            Some((400.0, 0.0, 400.0, 300.0))
+        );
+        assert_eq!(region("Left half"), Some((0.0, 0.0, 400.0, 600.0)));
+        assert_eq!(region("center"), Some((200.0, 150.0, 400.0, 300.0)));
+        assert_eq!(region("top center"), Some((200.0, 0.0, 400.0, 300.0)));
+        assert_eq!(region("10, 20, 300,40"), Some((10.0, 20.0, 300.0, 40.0)));
+        assert_eq!(region("top bottom"), None);
+        assert_eq!(region("sidebar"), None);
+        assert_eq!(region("1,2,3"), None);
+    }
+}
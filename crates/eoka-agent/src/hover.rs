@@ -0,0 +1,110 @@
+//! Hover capture — what a hover reveals: tooltip and popover text, and
+//! interactive elements (menus, flyouts) that weren't there before.
+
+use eoka::{Page, Result};
+use std::collections::HashSet;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::InteractiveElement;
+
+/// What hovering an element revealed.
+#[derive(Debug, Clone, Default)]
+pub struct HoverCapture {
+    /// Text of tooltips and popovers shown for the element, or its `title`
+    /// when the browser would draw a native tooltip instead
+    pub tooltips: Vec<String>,
+    /// Interactive elements that appeared, indexed into the refreshed
+    /// element list
+    pub revealed: Vec<InteractiveElement>,
+}
+
+impl fmt::Display for HoverCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.tooltips.is_empty() && self.revealed.is_empty() {
+            return f.write_str("nothing revealed");
+        }
+        for tip in &self.tooltips {
+            writeln!(f, "tooltip: {}", tip)?;
+        }
+        for el in &self.revealed {
+            writeln!(f, "{}", el)?;
+        }
+        Ok(())
+    }
+}
+
+/// JavaScript returning the visible tooltip/popover texts for the element at
+/// `sel` — its `aria-describedby` targets, `role=tooltip` elements and open
+/// popovers — and the `title` of the element or an ancestor.
+const TOOLTIPS_JS: &str = r#"
+((sel) => {
+  const el = document.querySelector(sel);
+  if (!el) return JSON.stringify({ texts: [], title: null });
+  const shown = e => {
+    const r = e.getBoundingClientRect();
+    const s = getComputedStyle(e);
+    return r.width > 0 && r.height > 0 && s.visibility !== 'hidden' && parseFloat(s.opacity) > 0;
+  };
+  const found = new Set();
+  for (const e of [el, ...el.querySelectorAll('[aria-describedby]')]) {
+    for (const id of (e.getAttribute('aria-describedby') || '').split(/\s+/).filter(Boolean)) {
+      const d = document.getElementById(id);
+      if (d && shown(d)) found.add(d);
+    }
+  }
+  let popovers = [];
+  try { popovers = Array.from(document.querySelectorAll(':popover-open')); } catch (_) {}
+  for (const e of [...document.querySelectorAll('[role=tooltip]'), ...popovers]) {
+    if (shown(e)) found.add(e);
+  }
+  const texts = Array.from(found)
+    .map(e => (e.innerText || e.textContent || '').replace(/\s+/g, ' ').trim())
+    .filter(Boolean);
+  const titled = el.closest('[title]');
+  return JSON.stringify({ texts: Array.from(new Set(texts)), title: titled ? titled.title.trim() || null : null });
+})
+"#;
+
+#[derive(serde::Deserialize)]
+struct Tooltips {
+    texts: Vec<String>,
+    title: Option<String>,
+}
+
+/// Poll for tooltips of the element at `selector` until one shows or
+/// `wait_ms` passes, then fall back to its `title`. Call after moving the
+/// mouse onto the element.
+pub async fn tooltips(page: &Page, selector: &str, wait_ms: u64) -> Result<Vec<String>> {
+    let js = format!(
+        "{}({})",
+        TOOLTIPS_JS,
+        serde_json::to_string(selector).unwrap()
+    );
+    let start = Instant::now();
+    loop {
+        let json: String = page.evaluate(&js).await?;
+        let found: Tooltips = serde_json::from_str(&json)
+            .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse tooltips: {}", e)))?;
+        if !found.texts.is_empty() {
+            return Ok(found.texts);
+        }
+        if start.elapsed() >= Duration::from_millis(wait_ms) {
+            return Ok(found.title.into_iter().collect());
+        }
+        page.wait(100).await;
+    }
+}
+
+/// Elements in `after` that weren't in `before`, matched by fingerprint.
+pub fn revealed(
+    before: &[InteractiveElement],
+    after: &[InteractiveElement],
+) -> Vec<InteractiveElement> {
+    let seen: HashSet<u64> = before.iter().map(|e| e.fingerprint).collect();
+    after
+        .iter()
+        .filter(|e| !seen.contains(&e.fingerprint))
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,37 @@
+//! Coordinate and keyboard input — for vision-driven agents and canvas apps
+//! (maps, games, drawing tools) where there is no DOM element to target.
+
+use eoka::cdp::{KeyEventType, MouseEventType};
+use eoka::{Page, Result};
+
+/// Click at viewport coordinates (CSS pixels).
+pub async fn click_at(page: &Page, x: f64, y: f64) -> Result<()> {
+    move_mouse(page, x, y).await?;
+    page.click_at(x, y).await
+}
+
+/// Move the mouse to viewport coordinates, firing hover and move handlers.
+pub async fn move_mouse(page: &Page, x: f64, y: f64) -> Result<()> {
+    page.session()
+        .dispatch_mouse_event(MouseEventType::MouseMoved, x, y, None, None)
+        .await
+}
+
+/// Type into whatever has focus, one key event per character, so canvas
+/// apps listening for `keydown` see the keys. `\n` presses Enter.
+pub async fn type_text(page: &Page, text: &str) -> Result<()> {
+    for ch in text.chars() {
+        if ch == '\n' {
+            page.press_key("Enter").await?;
+            continue;
+        }
+        let key = ch.to_string();
+        page.session()
+            .dispatch_key_event(KeyEventType::KeyDown, Some(&key), Some(&key), None)
+            .await?;
+        page.session()
+            .dispatch_key_event(KeyEventType::KeyUp, Some(&key), None, None)
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,265 @@
+//! Element inspector — click an element in a headful page and get back the
+//! ways to target it: its selector, live target patterns, runner YAML and
+//! the observed [`InteractiveElement`]. Backs `eoka-agent inspect <url>`.
+
+use eoka::{Page, Result};
+use serde::Deserialize;
+use std::fmt;
+
+use crate::{observe, InteractiveElement, Mark, ObserveConfig};
+
+/// Arms the picker: outlines the element under the mouse, and on click
+/// (which the page never sees) tags it `data-eoka-picked` and stores what it
+/// is in `window.__eokaPicked`.
+const PICKER_JS: &str = r#"
+(() => {
+  window.__eokaPicked = null;
+  document.querySelectorAll('[data-eoka-picked]').forEach(e => e.removeAttribute('data-eoka-picked'));
+  if (window.__eokaPicker) return;
+  window.__eokaPicker = true;
+  const outline = document.createElement('div');
+  outline.style.cssText = 'position:fixed;z-index:2147483647;pointer-events:none;border:2px solid #2563eb;'
+    + 'background:rgba(37,99,235,0.1);border-radius:2px;display:none';
+  document.documentElement.appendChild(outline);
+  const path = el => {
+    if (el.id) return '#' + CSS.escape(el.id);
+    const testid = el.getAttribute('data-testid');
+    if (testid) return '[data-testid=' + JSON.stringify(testid) + ']';
+    const parts = [];
+    for (let node = el; node && node !== document.body && parts.length < 5; node = node.parentElement) {
+      if (node.id) { parts.unshift('#' + CSS.escape(node.id)); break; }
+      let s = node.tagName.toLowerCase();
+      const same = node.parentElement ? Array.from(node.parentElement.children).filter(c => c.tagName === node.tagName) : [];
+      if (same.length > 1) s += ':nth-of-type(' + (same.indexOf(node) + 1) + ')';
+      parts.unshift(s);
+    }
+    return parts.join(' > ');
+  };
+  const label = el => {
+    const byFor = el.id ? document.querySelector('label[for=' + JSON.stringify(el.id) + ']') : null;
+    const lbl = byFor || el.closest('label');
+    return el.getAttribute('aria-label') || (lbl ? lbl.textContent.replace(/\s+/g, ' ').trim() : '') || null;
+  };
+  document.addEventListener('mousemove', e => {
+    if (!window.__eokaPicker) return;
+    const r = e.target.getBoundingClientRect();
+    Object.assign(outline.style, {
+      display: 'block', left: r.x + 'px', top: r.y + 'px', width: r.width + 'px', height: r.height + 'px',
+    });
+  }, true);
+  for (const type of ['pointerdown', 'mousedown', 'mouseup', 'click']) {
+    document.addEventListener(type, e => {
+      e.preventDefault();
+      e.stopImmediatePropagation();
+      if (type !== 'click' || window.__eokaPicked) return;
+      const el = e.target;
+      el.setAttribute('data-eoka-picked', '');
+      window.__eokaPicked = {
+        tag: el.tagName.toLowerCase(),
+        selector: path(el),
+        text: (el.innerText || el.textContent || '').replace(/\s+/g, ' ').trim().slice(0, 80),
+        id: el.id || null,
+        test_id: el.getAttribute('data-testid'),
+        label: label(el),
+        placeholder: el.getAttribute('placeholder'),
+      };
+    }, true);
+  }
+})()
+"#;
+
+/// `(selectors)` → index of the selector matching the picked element or its
+/// closest ancestor, or -1.
+const MATCH_JS: &str = r#"
+((sels) => {
+  const picked = document.querySelector('[data-eoka-picked]');
+  for (let node = picked; node; node = node.parentElement) {
+    const i = sels.findIndex(s => { try { return document.querySelector(s) === node; } catch (_) { return false; } });
+    if (i >= 0) return i;
+  }
+  return -1;
+})
+"#;
+
+/// The element clicked in the inspector.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Picked {
+    pub tag: String,
+    /// Selector of the clicked element: the observed element's when it (or
+    /// an ancestor) is interactive, else an id/test-id/path selector
+    pub selector: String,
+    pub text: String,
+    pub id: Option<String>,
+    pub test_id: Option<String>,
+    /// `aria-label` or associated `<label>` text
+    pub label: Option<String>,
+    pub placeholder: Option<String>,
+    /// The interactive element it belongs to, as `observe()` lists it
+    #[serde(skip)]
+    pub element: Option<InteractiveElement>,
+    /// `element` is set and no other observed element has its text
+    #[serde(skip)]
+    unique_text: bool,
+}
+
+impl Picked {
+    /// Live target patterns (see [`crate::LivePattern`]) for the element,
+    /// most robust first.
+    pub fn patterns(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        if let Some(ref id) = self.id {
+            out.push(format!("id:{}", id));
+        }
+        if let Some(ref test_id) = self.test_id {
+            out.push(format!("css:[data-testid=\"{}\"]", test_id));
+        }
+        if let Some(ref placeholder) = self.placeholder {
+            out.push(format!("placeholder:{}", placeholder));
+        }
+        if let Some(el) = self
+            .element
+            .as_ref()
+            .filter(|el| self.unique_text && !el.text.is_empty())
+        {
+            out.push(format!("text:{}", el.text));
+        }
+        out.push(format!("css:{}", self.selector));
+        out
+    }
+
+    /// Runner YAML target, e.g. `selector: "#submit"`; by text when the
+    /// selector is a brittle `nth-of-type` path and the text is unique.
+    pub fn yaml_target(&self) -> String {
+        let text = self
+            .element
+            .as_ref()
+            .filter(|el| self.unique_text && !el.text.is_empty());
+        if let Some(el) = text.filter(|_| self.selector.contains(":nth-of-type")) {
+            format!("text: {}", serde_json::to_string(&el.text).unwrap())
+        } else {
+            format!(
+                "selector: {}",
+                serde_json::to_string(&self.selector).unwrap()
+            )
+        }
+    }
+}
+
+impl fmt::Display for Picked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "selector: {}", self.selector)?;
+        writeln!(f, "targets:  {}", self.patterns().join("  |  "))?;
+        writeln!(f, "yaml:     {}", self.yaml_target())?;
+        if let Some(ref label) = self.label {
+            writeln!(f, "label:    {}", label)?;
+        }
+        match self.element {
+            Some(ref el) => {
+                writeln!(f, "element:  {}", el)?;
+                let mark = serde_json::to_string(&Mark::from(el)).unwrap();
+                writeln!(f, "{}", mark)
+            }
+            None => writeln!(f, "element:  <{}> (not interactive)", self.tag),
+        }
+    }
+}
+
+/// Wait for the next click in `page` and describe the clicked element. The
+/// click is swallowed, so the page doesn't react to it.
+pub async fn pick(page: &Page) -> Result<Picked> {
+    page.execute(PICKER_JS).await?;
+    let mut picked: Picked = loop {
+        let json: String = page
+            .evaluate("JSON.stringify(window.__eokaPicked || null)")
+            .await?;
+        let picked: Option<Picked> = serde_json::from_str(&json)
+            .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse pick: {}", e)))?;
+        if let Some(p) = picked {
+            break p;
+        }
+        page.wait(200).await;
+    };
+
+    let config = ObserveConfig {
+        viewport_only: false,
+        ..ObserveConfig::default()
+    };
+    let elements = observe::observe_with(page, &config).await?;
+    let selectors: Vec<&str> = elements.iter().map(|e| e.selector.as_str()).collect();
+    let js = format!(
+        "{}({})",
+        MATCH_JS,
+        serde_json::to_string(&selectors).unwrap()
+    );
+    let index: i64 = page.evaluate(&js).await?;
+    if let Some(el) = usize::try_from(index).ok().and_then(|i| elements.get(i)) {
+        picked.selector = el.selector.clone();
+        picked.unique_text = elements.iter().filter(|e| e.text == el.text).count() == 1;
+        picked.element = Some(el.clone());
+    }
+    Ok(picked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eoka::BoundingBox;
+
+    fn picked(selector: &str, element: Option<InteractiveElement>) -> Picked {
+        Picked {
+            tag: "button".into(),
+            selector: selector.into(),
+            text: "Sign in".into(),
+            id: None,
+            test_id: Some("login".into()),
+            label: None,
+            placeholder: None,
+            unique_text: element.is_some(),
+            element,
+        }
+    }
+
+    #[test]
+    fn patterns_and_yaml_target() {
+        let el = InteractiveElement {
+            index: 4,
+            tag: "button".into(),
+            role: None,
+            text: "Sign in".into(),
+            placeholder: None,
+            input_type: None,
+            selector: "form > button:nth-of-type(2)".into(),
+            checked: false,
+            value: None,
+            bbox: BoundingBox {
+                x: 0.0,
+                y: 0.0,
+                width: 80.0,
+                height: 30.0,
+            },
+            visibility: 1.0,
+            fingerprint: 0,
+            context: None,
+            href: None,
+            name: None,
+            title: None,
+            id: None,
+        };
+        let p = picked("form > button:nth-of-type(2)", Some(el));
+        assert_eq!(
+            p.patterns(),
+            [
+                "css:[data-testid=\"login\"]",
+                "text:Sign in",
+                "css:form > button:nth-of-type(2)"
+            ]
+        );
+        assert_eq!(p.yaml_target(), r#"text: "Sign in""#);
+
+        // Not interactive: no text pattern, live text search wouldn't find it
+        let p = picked("[data-testid=\"login\"]", None);
+        assert_eq!(p.patterns().len(), 2);
+        assert_eq!(p.yaml_target(), r#"selector: "[data-testid=\"login\"]""#);
+        assert!(p.to_string().contains("(not interactive)"));
+    }
+}
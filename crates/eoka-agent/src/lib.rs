@@ -22,23 +22,116 @@
 //! # }
 //! ```
 
+pub mod a11y;
+pub mod action_log;
 pub mod annotate;
+pub mod auto_dismiss;
+pub mod autofill;
+pub mod cancel;
 pub mod captcha;
+pub mod click;
+pub mod crawler;
+pub mod debug_overlay;
+pub mod dropdown;
+pub mod egress;
+pub mod evidence;
+pub mod expect;
+pub mod flows;
+pub mod form_errors;
+pub mod geometry;
+pub mod hover;
+pub mod input;
+pub mod inspect;
+pub mod links;
+pub mod live;
+pub mod navigation;
+pub mod network;
 pub mod observe;
+pub mod occlusion;
+#[cfg(feature = "ocr")]
+pub mod ocr;
+pub mod outcome;
+pub mod overlay;
+pub mod paginate;
+pub mod popup;
+pub mod rate_limit;
 pub mod spa;
+pub mod summary;
 pub mod target;
-
+pub mod test_server;
+pub mod wait;
+
+pub use action_log::{ActionLog, ActionRecord};
+pub use annotate::{DiffResult, Mark, Region, SomBundle};
+pub use auto_dismiss::{AutoDismiss, ConsentPolicy, Dismissed};
+pub use autofill::{FormProfile, TestCard};
+pub use cancel::{CancellationToken, Canceller};
+pub use click::{ClickMethod, ClickOptions, Clicked};
+pub use crawler::{CrawlConfig, CrawlPage, CrawlReport};
+pub use egress::{Egress, EgressCheck};
+pub use eoka_runner::deterministic::{self, DeterministicConfig};
+pub use evidence::ActionEvidence;
+pub use expect::{Expect, ExpectElement};
+pub use form_errors::FormError;
+pub use geometry::{Geometry, Viewport};
+pub use hover::HoverCapture;
+pub use links::{LinkAudit, LinkCheck, LinkScope};
+pub use live::LiveMessage;
+pub use navigation::{NavFailureKind, NavWatchdog};
+pub use network::{AuthHeader, CapturedAuth, FormPost, NetworkCapture, ResponseMatch, SentRequest};
+pub use occlusion::Occlusion;
+pub use outcome::ActionOutcome;
+pub use overlay::{DismissButton, DismissKind, Overlay, OverlayKind};
+pub use paginate::Paginated;
+pub use popup::PopupPolicy;
+pub use rate_limit::{RateLimit, RateLimiter};
 pub use spa::{RouterType, SpaRouterInfo};
+pub use summary::PageSummary;
 pub use target::{BBox, LivePattern, Resolved, Target};
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::sync::Arc;
 
 use eoka::{BoundingBox, Page, Result};
+use popup::PopupTracker;
 
 // Re-export eoka types that users need
 pub use eoka::{Browser, Error, StealthConfig};
 
+/// Result type for actions that can fail with an [`AgentError`].
+pub type AgentResult<T> = std::result::Result<T, AgentError>;
+
+/// Action failures a caller handles differently from a plain browser error.
+/// Converts to and from [`eoka::Error`], so `?` works either way.
+#[derive(Debug, thiserror::Error)]
+pub enum AgentError {
+    #[error(transparent)]
+    Browser(#[from] eoka::Error),
+
+    /// Another element, e.g. a modal or cookie bar, would get the click
+    #[error("{element} is {by} - dismiss it or scroll first")]
+    ElementObscured { element: String, by: Occlusion },
+
+    /// The navigation watchdog gave up on a `goto`
+    #[error("{kind} loading {url} ({attempts} attempts)")]
+    NavigationFailed {
+        kind: NavFailureKind,
+        url: String,
+        attempts: u32,
+    },
+}
+
+impl From<AgentError> for eoka::Error {
+    fn from(e: AgentError) -> Self {
+        match e {
+            AgentError::Browser(e) => e,
+            e @ AgentError::ElementObscured { .. } => eoka::Error::ElementNotFound(e.to_string()),
+            e @ AgentError::NavigationFailed { .. } => eoka::Error::Navigation(e.to_string()),
+        }
+    }
+}
+
 /// An interactive element on the page, identified by index.
 #[derive(Debug, Clone)]
 pub struct InteractiveElement {
@@ -62,8 +155,22 @@ pub struct InteractiveElement {
     pub value: Option<String>,
     /// Bounding box in viewport coordinates
     pub bbox: BoundingBox,
+    /// Share of the element that is on screen and not covered by other
+    /// elements (sticky headers, overlays), from 0.0 to 1.0
+    pub visibility: f32,
     /// Fingerprint for stale element detection (hash of tag+text+attributes)
     pub fingerprint: u64,
+    /// Nearest heading or surrounding text, set only when other elements
+    /// share this one's label (e.g. ten "Add to cart" buttons)
+    pub context: Option<String>,
+    /// Link target of an `<a>`, as an absolute URL (with `include_href`)
+    pub href: Option<String>,
+    /// `name` attribute (with `include_name`)
+    pub name: Option<String>,
+    /// `title` attribute (with `include_title`)
+    pub title: Option<String>,
+    /// `id` attribute (with `include_id`)
+    pub id: Option<String>,
 }
 
 impl InteractiveElement {
@@ -112,6 +219,15 @@ impl fmt::Display for InteractiveElement {
         if let Some(ref p) = self.placeholder {
             write!(f, " placeholder=\"{}\"", p)?;
         }
+        if let Some(ref n) = self.name {
+            write!(f, " name=\"{}\"", n)?;
+        }
+        if let Some(ref i) = self.id {
+            write!(f, " id=\"{}\"", i)?;
+        }
+        if let Some(ref t) = self.title {
+            write!(f, " title=\"{}\"", t)?;
+        }
         if let Some(ref r) = self.role {
             let redundant = (r == "button" && self.tag == "button")
                 || (r == "link" && self.tag == "a")
@@ -120,22 +236,110 @@ impl fmt::Display for InteractiveElement {
                 write!(f, " role=\"{}\"", r)?;
             }
         }
+        if let Some(ref c) = self.context {
+            write!(f, " in \"{}\"", c)?;
+        }
+        if let Some(ref h) = self.href {
+            write!(f, " → {}", short_href(h))?;
+        }
+        if self.visibility < 1.0 {
+            write!(f, " [{:.0}% visible]", self.visibility * 100.0)?;
+        }
         Ok(())
     }
 }
 
+/// Send a raw CDP command; `null` params go out as `{}`, which commands
+/// without parameters expect.
+async fn cdp_call(
+    page: &Page,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value> {
+    let params = if params.is_null() {
+        serde_json::json!({})
+    } else {
+        params
+    };
+    page.session().send(method, &params).await
+}
+
+/// Run `source` in every new document of `page` and in the current one.
+/// Returns the id to remove it with.
+async fn add_init_script(page: &Page, source: &str) -> Result<Option<String>> {
+    let added = cdp_call(
+        page,
+        "Page.addScriptToEvaluateOnNewDocument",
+        serde_json::json!({ "source": source }),
+    )
+    .await?;
+    page.execute(source).await?;
+    Ok(added["identifier"].as_str().map(String::from))
+}
+
+/// Stop running a script from [`add_init_script`] in new documents.
+async fn remove_init_script(page: &Page, id: String) -> Result<()> {
+    cdp_call(
+        page,
+        "Page.removeScriptToEvaluateOnNewDocument",
+        serde_json::json!({ "identifier": id }),
+    )
+    .await?;
+    Ok(())
+}
+
+/// A link target as host and path, e.g. `example.com/pricing`; other
+/// schemes (`mailto:`, `tel:`) as they are.
+fn short_href(href: &str) -> String {
+    match reqwest::Url::parse(href) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => {
+            let host = url.host_str().unwrap_or("");
+            let path = url.path().trim_end_matches('/');
+            format!("{}{}", host.strip_prefix("www.").unwrap_or(host), path)
+        }
+        _ => href.to_string(),
+    }
+}
+
 /// Configuration for observation behavior.
 #[derive(Debug, Clone)]
 pub struct ObserveConfig {
     /// Only include elements visible in the current viewport.
     /// Dramatically reduces token count on long pages. Default: true.
     pub viewport_only: bool,
+    /// Element text is cut to this many characters, `0` for no limit.
+    /// Default: 60.
+    pub text_limit: usize,
+    /// Record each `<a>`'s target in `href`, shown as host/path in the
+    /// element list so look-alike links ("Read more") can be told apart.
+    /// Default: true.
+    pub include_href: bool,
+    /// Record the `name` attribute. Default: false.
+    pub include_name: bool,
+    /// Record the `title` attribute. Default: false.
+    pub include_title: bool,
+    /// Record the `id` attribute. Default: false.
+    pub include_id: bool,
+    /// Leave out elements with a lower [`InteractiveElement::visibility`],
+    /// e.g. `0.5` to skip buttons half under a sticky bar. Default: 0.0.
+    pub min_visibility: f32,
+    /// Only include elements whose center is inside this box, in viewport
+    /// coordinates, e.g. one panel of a dashboard; see
+    /// [`Viewport::region`] for named parts. Default: None.
+    pub region: Option<BoundingBox>,
 }
 
 impl Default for ObserveConfig {
     fn default() -> Self {
         Self {
             viewport_only: true,
+            text_limit: 60,
+            include_href: true,
+            include_name: false,
+            include_title: false,
+            include_id: false,
+            min_visibility: 0.0,
+            region: None,
         }
     }
 }
@@ -179,6 +383,7 @@ pub struct AgentPage<'a> {
     page: &'a Page,
     elements: Vec<InteractiveElement>,
     config: ObserveConfig,
+    viewport_height: f64,
 }
 
 impl<'a> AgentPage<'a> {
@@ -188,6 +393,7 @@ impl<'a> AgentPage<'a> {
             page,
             elements: Vec::new(),
             config: ObserveConfig::default(),
+            viewport_height: 0.0,
         }
     }
 
@@ -197,6 +403,7 @@ impl<'a> AgentPage<'a> {
             page,
             elements: Vec::new(),
             config,
+            viewport_height: 0.0,
         }
     }
 
@@ -211,7 +418,9 @@ impl<'a> AgentPage<'a> {
 
     /// Snapshot the page: enumerate all interactive elements.
     pub async fn observe(&mut self) -> Result<&[InteractiveElement]> {
-        self.elements = observe::observe(self.page, self.config.viewport_only).await?;
+        let snapshot = observe::snapshot(self.page, &self.config).await?;
+        self.elements = snapshot.elements;
+        self.viewport_height = snapshot.viewport_height;
         Ok(&self.elements)
     }
 
@@ -222,7 +431,9 @@ impl<'a> AgentPage<'a> {
         let old_selectors: HashSet<String> =
             self.elements.iter().map(|e| e.selector.clone()).collect();
 
-        self.elements = observe::observe(self.page, self.config.viewport_only).await?;
+        let snapshot = observe::snapshot(self.page, &self.config).await?;
+        self.elements = snapshot.elements;
+        self.viewport_height = snapshot.viewport_height;
 
         let new_selectors: HashSet<&str> =
             self.elements.iter().map(|e| e.selector.as_str()).collect();
@@ -267,11 +478,37 @@ impl<'a> AgentPage<'a> {
         annotate::annotated_screenshot(self.page, &self.elements).await
     }
 
+    /// Observe, then return the annotated screenshot with each mark's
+    /// element metadata, for Set-of-Marks vision prompting. One call, so the
+    /// numbers on the image match the indices actions take.
+    pub async fn som_bundle(&mut self) -> Result<SomBundle> {
+        self.observe().await?;
+        annotate::som_bundle(self.page, &self.elements).await
+    }
+
     /// Take a plain screenshot without annotations.
     pub async fn screenshot_plain(&self) -> Result<Vec<u8>> {
         self.page.screenshot().await
     }
 
+    /// Find cookie banners, modals, chat widgets and interstitials covering
+    /// the page, topmost first, each with candidate dismiss buttons.
+    pub async fn detect_overlays(&self) -> Result<Vec<Overlay>> {
+        overlay::detect_overlays(self.page).await
+    }
+
+    /// OCR fallback for canvas-only pages: when the last `observe()` found
+    /// no elements and a canvas fills the page, the text lines on screen with
+    /// their positions (click one at its `center()` with `page().click_at`).
+    /// Empty otherwise.
+    #[cfg(feature = "ocr")]
+    pub async fn text_regions(&self) -> Result<Vec<ocr::TextRegion>> {
+        if !self.elements.is_empty() || !ocr::is_canvas_page(self.page).await? {
+            return Ok(Vec::new());
+        }
+        ocr::text_regions(self.page).await
+    }
+
     /// Compact text list for LLM consumption.
     /// Each line: `[index] <tag type="x"> "text" placeholder="y"`
     pub fn element_list(&self) -> String {
@@ -283,6 +520,13 @@ impl<'a> AgentPage<'a> {
         out
     }
 
+    /// Element list trimmed to about `max_tokens`: inputs and buttons in the
+    /// viewport first, repeated nav links dropped, with a footer counting
+    /// the elements left out.
+    pub fn element_list_budgeted(&self, max_tokens: usize) -> String {
+        observe::budgeted_list(&self.elements, max_tokens, self.viewport_height)
+    }
+
     /// Get element info by index.
     pub fn get(&self, index: usize) -> Option<&InteractiveElement> {
         self.elements.get(index)
@@ -327,10 +571,20 @@ impl<'a> AgentPage<'a> {
     // Actions (index-based)
     // =========================================================================
 
-    /// Click an element by its index.
-    pub async fn click(&self, index: usize) -> Result<()> {
+    /// Click an element by its index. Scrolls it into view and clear of
+    /// fixed headers first; errors naming the covering element if something
+    /// else (e.g. a modal) would receive the click.
+    pub async fn click(&self, index: usize) -> AgentResult<()> {
+        self.click_with(index, &ClickOptions::default()).await?;
+        Ok(())
+    }
+
+    /// Click with options: `force` dispatches the events without hit-testing,
+    /// `js_fallback` dispatches them if the mouse click fails. The result
+    /// records which was used.
+    pub async fn click_with(&self, index: usize, options: &ClickOptions) -> AgentResult<Clicked> {
         let el = self.require(index)?;
-        self.page.click(&el.selector).await
+        click::click(self.page, el, options).await
     }
 
     /// Try to click — returns `Ok(false)` if element is missing or not visible.
@@ -339,10 +593,10 @@ impl<'a> AgentPage<'a> {
         self.page.try_click(&el.selector).await
     }
 
-    /// Human-like click by index.
+    /// Human-like click by index; seeded under [`deterministic`] mode.
     pub async fn human_click(&self, index: usize) -> Result<()> {
         let el = self.require(index)?;
-        self.page.human_click(&el.selector).await
+        deterministic::human_click(self.page, &el.selector).await
     }
 
     /// Clear and type into an element by index.
@@ -351,10 +605,10 @@ impl<'a> AgentPage<'a> {
         self.page.fill(&el.selector, text).await
     }
 
-    /// Human-like fill by index.
+    /// Human-like fill by index; seeded under [`deterministic`] mode.
     pub async fn human_fill(&self, index: usize, text: &str) -> Result<()> {
         let el = self.require(index)?;
-        self.page.human_fill(&el.selector, text).await
+        deterministic::human_fill(self.page, &el.selector, text).await
     }
 
     /// Focus an element by index.
@@ -395,6 +649,25 @@ impl<'a> AgentPage<'a> {
         Ok(())
     }
 
+    /// Select from a custom (non-`<select>`) dropdown by index: clicks the
+    /// combobox, waits for its `role=listbox` options, then scrolls to and
+    /// clicks the one matching `option_text` (exact, prefix, then substring).
+    /// Returns the clicked option's text.
+    pub async fn select_custom(&self, index: usize, option_text: &str) -> Result<String> {
+        let el = self.require(index)?;
+        dropdown::select_custom(self.page, &el.selector, option_text).await
+    }
+
+    /// Fill the `form_index`-th form (0 = first) from a test-data profile,
+    /// matching fields by autocomplete attribute, label, name or placeholder.
+    pub async fn autofill_form(
+        &self,
+        form_index: usize,
+        profile: &FormProfile,
+    ) -> Result<Vec<autofill::FilledField>> {
+        autofill::autofill_form(self.page, form_index, profile).await
+    }
+
     /// Get dropdown options for a select element. Returns vec of (value, text) pairs.
     pub async fn options(&self, index: usize) -> Result<Vec<(String, String)>> {
         let el = self.require(index)?;
@@ -412,14 +685,17 @@ impl<'a> AgentPage<'a> {
         Ok(pairs)
     }
 
-    /// Scroll element at index into view.
+    /// Scroll element at index into view, clear of fixed headers and footers.
     pub async fn scroll_to(&self, index: usize) -> Result<()> {
         let el = self.require(index)?;
         let js = format!(
             "document.querySelector({})?.scrollIntoView({{behavior:'smooth',block:'center'}})",
             serde_json::to_string(&el.selector).unwrap()
         );
-        self.page.execute(&js).await
+        self.page.execute(&js).await?;
+        self.page.wait(SMOOTH_SCROLL_MS).await;
+        occlusion::reveal(self.page, &el.selector).await?;
+        Ok(())
     }
 
     // =========================================================================
@@ -450,6 +726,48 @@ impl<'a> AgentPage<'a> {
         self.page.reload().await
     }
 
+    /// Follow the page's next-page control (`rel=next`, "Next", "»").
+    /// Returns `false` when there is none or it didn't change the page.
+    pub async fn next_page(&mut self) -> Result<bool> {
+        self.elements.clear();
+        paginate::next_page(self.page).await
+    }
+
+    /// Run `per_page` on the current page and each following one, up to
+    /// `max_pages` pages in total.
+    pub async fn paginate<T>(
+        &mut self,
+        max_pages: usize,
+        mut per_page: impl AsyncFnMut(&mut Self) -> Result<T>,
+    ) -> Result<Paginated<T>> {
+        let mut results = Vec::new();
+        let mut exhausted = false;
+        while results.len() < max_pages {
+            results.push(per_page(self).await?);
+            if results.len() < max_pages && !self.next_page().await? {
+                exhausted = true;
+                break;
+            }
+        }
+        Ok(Paginated {
+            pages: results.len(),
+            results,
+            exhausted,
+        })
+    }
+
+    /// Request every link on the page within `scope` and report the ones
+    /// answering 4xx/5xx or not at all (see [`LinkAudit::broken`]).
+    pub async fn audit_links(&self, scope: LinkScope) -> Result<LinkAudit> {
+        links::audit_links(self.page, scope).await
+    }
+
+    /// Check the page for missing alt text and labels, low contrast,
+    /// duplicate ids and keyboard traps. Presses Tab, so focus moves.
+    pub async fn audit_a11y(&self) -> Result<Vec<a11y::Violation>> {
+        a11y::audit(self.page).await
+    }
+
     // =========================================================================
     // Page state
     // =========================================================================
@@ -538,20 +856,32 @@ impl<'a> AgentPage<'a> {
         self.page.execute(js).await
     }
 
+    /// Send any DevTools protocol command to the page and return its result,
+    /// for commands the crate doesn't wrap yet. Protocol errors come back as
+    /// [`eoka::Error::Cdp`] with Chrome's code and message.
+    pub async fn cdp_call(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        cdp_call(self.page, method, params).await
+    }
+
     // =========================================================================
     // Keyboard
     // =========================================================================
 
-    /// Press a key (e.g. "Enter", "Tab", "Escape", "ArrowDown", "Backspace").
+    /// Press a key (e.g. "Enter", "Tab", "Escape", "ArrowDown", "Backspace");
+    /// seeded under [`deterministic`] mode.
     pub async fn press_key(&self, key: &str) -> Result<()> {
-        self.page.human().press_key(key).await
+        deterministic::press_key(self.page, key).await
     }
 
     /// Focus element by index and press Enter (common for form submission).
     pub async fn submit(&self, index: usize) -> Result<()> {
         self.focus(index).await?;
         tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-        self.page.human().press_key("Enter").await
+        deterministic::press_key(self.page, "Enter").await
     }
 
     // =========================================================================
@@ -561,14 +891,47 @@ impl<'a> AgentPage<'a> {
     /// Hover over element by index (triggers hover states, tooltips, menus).
     pub async fn hover(&self, index: usize) -> Result<()> {
         let el = self.require(index)?;
-        let cx = el.bbox.x + el.bbox.width / 2.0;
-        let cy = el.bbox.y + el.bbox.height / 2.0;
+        let (cx, cy) = el.bbox.center();
         self.page
             .session()
             .dispatch_mouse_event(eoka::cdp::MouseEventType::MouseMoved, cx, cy, None, None)
             .await
     }
 
+    /// Hover over an element, wait up to `wait_ms` for tooltips or popovers,
+    /// and report their text plus the interactive elements that appeared.
+    /// Re-observes the page, so `revealed` indices are current.
+    pub async fn hover_and_capture(&mut self, index: usize, wait_ms: u64) -> Result<HoverCapture> {
+        let el = self.require(index)?.clone();
+        let before = observe::observe_with(self.page, &self.config).await?;
+        self.hover(index).await?;
+        let tooltips = hover::tooltips(self.page, &el.selector, wait_ms).await?;
+        self.observe().await?;
+        Ok(HoverCapture {
+            tooltips,
+            revealed: hover::revealed(&before, &self.elements),
+        })
+    }
+
+    // =========================================================================
+    // Coordinate input (canvas apps, vision-driven agents)
+    // =========================================================================
+
+    /// Click at viewport coordinates.
+    pub async fn click_at(&self, x: f64, y: f64) -> Result<()> {
+        input::click_at(self.page, x, y).await
+    }
+
+    /// Move the mouse to viewport coordinates.
+    pub async fn move_mouse(&self, x: f64, y: f64) -> Result<()> {
+        input::move_mouse(self.page, x, y).await
+    }
+
+    /// Type into the focused element or canvas, one key event per character.
+    pub async fn type_text(&self, text: &str) -> Result<()> {
+        input::type_text(self.page, text).await
+    }
+
     // =========================================================================
     // Extraction
     // =========================================================================
@@ -653,6 +1016,24 @@ impl<'a> AgentPage<'a> {
         Ok(())
     }
 
+    /// Wait until `text` disappears from the page, e.g. "Loading…".
+    pub async fn wait_for_text_gone(&self, text: &str, timeout_ms: u64) -> Result<()> {
+        wait::wait_for_text_gone(self.page, text, timeout_ms).await
+    }
+
+    /// Wait up to 5s for an element to stop moving (same bounding box for a
+    /// few animation frames), e.g. a modal sliding in.
+    pub async fn wait_for_element_stable(&self, index: usize) -> Result<()> {
+        let el = self.require(index)?;
+        wait::wait_for_element_stable(self.page, &el.selector, wait::STABLE_FRAMES, 5000).await
+    }
+
+    /// Assertions that wait for the page to get there, e.g.
+    /// `expect().text_contains("Order placed")`; see [`Expect`].
+    pub fn expect(&self) -> Expect<'_> {
+        Expect::new(self.page)
+    }
+
     // =========================================================================
     // SPA Navigation
     // =========================================================================
@@ -672,6 +1053,32 @@ impl<'a> AgentPage<'a> {
         Ok(result)
     }
 
+    /// Wait until React/Vue/Next/Angular have hydrated server-rendered markup.
+    /// Clicking before hydration silently does nothing on SSR pages.
+    pub async fn wait_for_hydration(&self, timeout_ms: u64) -> Result<()> {
+        spa::wait_for_hydration(self.page, timeout_ms).await?;
+        Ok(())
+    }
+
+    /// Set query params through the SPA router, keeping the other params.
+    /// An empty value removes that param. Returns the new path.
+    /// Clears element list since the DOM will change.
+    pub async fn spa_set_query(&mut self, params: &HashMap<String, String>) -> Result<String> {
+        let info = spa::detect_router(self.page).await?;
+        let result = spa::spa_set_query(self.page, &info.router_type, params).await?;
+        self.elements.clear();
+        Ok(result)
+    }
+
+    /// Set the hash fragment through the SPA router. Returns the new path.
+    /// Clears element list since the DOM will change.
+    pub async fn spa_set_hash(&mut self, fragment: &str) -> Result<String> {
+        let info = spa::detect_router(self.page).await?;
+        let result = spa::spa_set_hash(self.page, &info.router_type, fragment).await?;
+        self.elements.clear();
+        Ok(result)
+    }
+
     /// Navigate browser history by delta steps.
     /// delta = -1 goes back, delta = 1 goes forward.
     /// Clears element list since the DOM will change.
@@ -681,6 +1088,21 @@ impl<'a> AgentPage<'a> {
         Ok(())
     }
 
+    /// Wait for a client-side route change (pushState, popstate, hashchange),
+    /// optionally to a path containing `path_pattern`. Returns the new path.
+    /// Unlike `wait_for_stable`, this sees transitions that make no requests.
+    /// Call `spa::watch_route_changes` first if the transition may finish
+    /// before this is called. Clears element list since the DOM will change.
+    pub async fn wait_for_route_change(
+        &mut self,
+        timeout_ms: u64,
+        path_pattern: Option<&str>,
+    ) -> Result<String> {
+        let path = spa::wait_for_route_change(self.page, timeout_ms, path_pattern).await?;
+        self.elements.clear();
+        Ok(path)
+    }
+
     // =========================================================================
     // Internal
     // =========================================================================
@@ -707,6 +1129,32 @@ pub struct Session {
     page: Page,
     elements: Vec<InteractiveElement>,
     config: ObserveConfig,
+    viewport_height: f64,
+    auto_dismiss: Option<AutoDismiss>,
+    auto_recover: bool,
+    nav_watchdog: Option<NavWatchdog>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    capture_evidence: bool,
+    evidence: Vec<ActionEvidence>,
+    accept_dialogs: bool,
+    log: ActionLog,
+    debug_overlay: bool,
+    popup_policy: PopupPolicy,
+    /// Tabs open when the popup policy was set, and the pages followed
+    /// popups were opened from.
+    popup_tracker: PopupTracker,
+    /// `Page.addScriptToEvaluateOnNewDocument` id of the same-tab script.
+    same_tab_script: Option<String>,
+    /// `Page.addScriptToEvaluateOnNewDocument` id of the network capture.
+    network_script: Option<String>,
+    network_capture: Option<NetworkCapture>,
+    /// `Page.addScriptToEvaluateOnNewDocument` id of the live-region monitor.
+    live_script: Option<String>,
+    /// Proxy the browser was launched with, for [`Session::check_egress`].
+    proxy: Option<String>,
+    /// Browser context [`Session::set_proxy_bypass`] moved the page into.
+    proxy_context: Option<String>,
+    canceller: Canceller,
 }
 
 impl Session {
@@ -714,31 +1162,453 @@ impl Session {
     pub async fn launch() -> Result<Self> {
         let browser = Browser::launch().await?;
         let page = browser.new_page("about:blank").await?;
+        if let Some(config) = deterministic::current() {
+            add_init_script(&page, &deterministic::page_script(&config)).await?;
+        }
         Ok(Self {
             browser,
             page,
             elements: Vec::new(),
             config: ObserveConfig::default(),
+            viewport_height: 0.0,
+            auto_dismiss: None,
+            auto_recover: false,
+            nav_watchdog: None,
+            rate_limiter: None,
+            capture_evidence: false,
+            evidence: Vec::new(),
+            accept_dialogs: false,
+            log: ActionLog::default(),
+            debug_overlay: false,
+            popup_policy: PopupPolicy::default(),
+            popup_tracker: PopupTracker::default(),
+            same_tab_script: None,
+            network_script: None,
+            network_capture: None,
+            live_script: None,
+            proxy: None,
+            proxy_context: None,
+            canceller: Canceller::default(),
         })
     }
 
     /// Launch with custom stealth config.
     pub async fn launch_with_config(stealth: StealthConfig) -> Result<Self> {
+        let proxy = stealth.proxy.clone();
         let browser = Browser::launch_with_config(stealth).await?;
         let page = browser.new_page("about:blank").await?;
+        if let Some(config) = deterministic::current() {
+            add_init_script(&page, &deterministic::page_script(&config)).await?;
+        }
         Ok(Self {
             browser,
             page,
             elements: Vec::new(),
             config: ObserveConfig::default(),
+            viewport_height: 0.0,
+            auto_dismiss: None,
+            auto_recover: false,
+            nav_watchdog: None,
+            rate_limiter: None,
+            capture_evidence: false,
+            evidence: Vec::new(),
+            accept_dialogs: false,
+            log: ActionLog::default(),
+            debug_overlay: false,
+            popup_policy: PopupPolicy::default(),
+            popup_tracker: PopupTracker::default(),
+            same_tab_script: None,
+            network_script: None,
+            network_capture: None,
+            live_script: None,
+            proxy,
+            proxy_context: None,
+            canceller: Canceller::default(),
         })
     }
 
+    /// Launch with custom stealth config, sending hosts matching
+    /// `proxy_bypass` (Chrome's `--proxy-bypass-list` syntax, e.g.
+    /// "localhost" or "*.internal") around `stealth.proxy`. Change the rules
+    /// later with [`Session::set_proxy_bypass`].
+    pub async fn launch_with_proxy_bypass(
+        mut stealth: StealthConfig,
+        proxy_bypass: &[String],
+    ) -> Result<Self> {
+        if !proxy_bypass.is_empty() {
+            stealth
+                .extra_args
+                .push(format!("--proxy-bypass-list={}", proxy_bypass.join(";")));
+        }
+        Self::launch_with_config(stealth).await
+    }
+
+    /// Send hosts matching `rules` around the launch proxy from now on
+    /// (same syntax as [`Session::launch_with_proxy_bypass`]).
+    ///
+    /// Chrome only takes bypass rules at launch or for a new browser
+    /// context, so this moves the session into a fresh context using the
+    /// launch proxy and `rules`: cookies are copied over and the current URL
+    /// is reopened, but localStorage, sessionStorage and other tabs stay
+    /// behind. Errors if the session was launched without a proxy.
+    pub async fn set_proxy_bypass(&mut self, rules: &[String]) -> Result<()> {
+        let Some(ref proxy) = self.proxy else {
+            return Err(eoka::Error::CdpSimple(
+                "set_proxy_bypass: the session was launched without a proxy".into(),
+            ));
+        };
+        let transport = self.page.session().transport().clone();
+        let context: serde_json::Value = transport
+            .send(
+                "Target.createBrowserContext",
+                &serde_json::json!({
+                    "proxyServer": proxy,
+                    "proxyBypassList": rules.join(";"),
+                }),
+            )
+            .await?;
+        let context_id = context["browserContextId"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let target: serde_json::Value = transport
+            .send(
+                "Target.createTarget",
+                &serde_json::json!({ "url": "about:blank", "browserContextId": context_id }),
+            )
+            .await?;
+        let page = self
+            .browser
+            .attach_page(target["targetId"].as_str().unwrap_or_default())
+            .await?;
+
+        let cookies =
+            cdp_call(&self.page, "Network.getAllCookies", serde_json::Value::Null).await?;
+        cdp_call(
+            &page,
+            "Network.setCookies",
+            serde_json::json!({ "cookies": cookies["cookies"] }),
+        )
+        .await?;
+        let url = self.page.url().await?;
+        let old = std::mem::replace(&mut self.page, page);
+        let _ = self.browser.close_tab(old.target_id()).await;
+        if let Some(old_context) = self.proxy_context.replace(context_id) {
+            let params = serde_json::json!({ "browserContextId": old_context });
+            let _ = transport
+                .send::<_, serde_json::Value>("Target.disposeBrowserContext", &params)
+                .await;
+        }
+
+        self.elements.clear();
+        self.reinstall_page_scripts().await;
+        if url != "about:blank" {
+            self.page.goto(&url).await?;
+        }
+        Ok(())
+    }
+
     /// Set observation config.
     pub fn set_observe_config(&mut self, config: ObserveConfig) {
         self.config = config;
     }
 
+    /// Dismiss cookie banners and modals after every `goto` (off by default).
+    pub fn set_auto_dismiss(&mut self, auto_dismiss: Option<AutoDismiss>) {
+        self.auto_dismiss = auto_dismiss;
+    }
+
+    /// Retarget actions to the element's new index when it moved since the
+    /// last `observe()`, instead of erroring (off by default). Still errors
+    /// when the element is gone or several elements match it.
+    pub fn set_auto_recover(&mut self, auto_recover: bool) {
+        self.auto_recover = auto_recover;
+    }
+
+    /// Check every `goto` for Chrome error pages, 5xx documents and
+    /// `about:blank` dead-ends, retrying with backoff (off by default).
+    /// Failures come back as [`AgentError::NavigationFailed`] with the
+    /// [`NavFailureKind`].
+    pub fn set_nav_watchdog(&mut self, watchdog: Option<NavWatchdog>) {
+        self.nav_watchdog = watchdog;
+    }
+
+    /// Pace `goto` per origin (off by default). Give several sessions the
+    /// same limiter to share one budget per site.
+    pub fn set_rate_limiter(&mut self, limiter: Option<Arc<RateLimiter>>) {
+        self.rate_limiter = limiter;
+    }
+
+    /// Keep before/after screenshots and a crop of the target element for
+    /// every click, fill, select and `click_at` (off by default). Read them
+    /// with [`Session::evidence`]; the last [`evidence::MAX_EVIDENCE`] are kept.
+    pub fn set_capture_action_evidence(&mut self, capture: bool) {
+        self.capture_evidence = capture;
+    }
+
+    /// Accept `confirm`, `prompt` and "leave site?" dialogs opened by click,
+    /// fill, select and `click_at` (off by default: they are cancelled, and
+    /// only alerts are closed). Either way their messages are reported in
+    /// [`ActionOutcome::dialogs`].
+    pub fn set_accept_dialogs(&mut self, accept: bool) {
+        self.accept_dialogs = accept;
+    }
+
+    /// Evidence recorded so far, oldest first.
+    pub fn evidence(&self) -> &[ActionEvidence] {
+        &self.evidence
+    }
+
+    /// Take the recorded evidence, leaving none.
+    pub fn take_evidence(&mut self) -> Vec<ActionEvidence> {
+        std::mem::take(&mut self.evidence)
+    }
+
+    /// Captures before an action. Evidence is a debugging aid, so a failed
+    /// capture is logged and that entry skipped rather than failing the action.
+    async fn begin_evidence(
+        &self,
+        target: Option<&InteractiveElement>,
+    ) -> Option<evidence::Pending> {
+        if !self.capture_evidence {
+            return None;
+        }
+        evidence::begin(&self.page, target)
+            .await
+            .map_err(|e| eprintln!("[eoka-agent] skipping action evidence: {}", e))
+            .ok()
+    }
+
+    async fn finish_evidence(&mut self, pending: Option<evidence::Pending>, action: String) {
+        let Some(pending) = pending else {
+            return;
+        };
+        match pending.finish(&self.page, action).await {
+            Ok(evidence) => {
+                if self.evidence.len() >= evidence::MAX_EVIDENCE {
+                    self.evidence.remove(0);
+                }
+                self.evidence.push(evidence);
+            }
+            Err(e) => eprintln!("[eoka-agent] skipping action evidence: {}", e),
+        }
+    }
+
+    /// Every click, fill, select, hover, key press and navigation so far,
+    /// with its outcome and the URL before and after. Export it with
+    /// [`ActionLog::to_json`].
+    pub fn action_log(&self) -> &ActionLog {
+        &self.log
+    }
+
+    /// Draw the last action and numbered element boxes into the page after
+    /// every action and `observe()` (off by default). For watching headful
+    /// runs; the overlay shows up in screenshots, so leave it off when
+    /// capturing images for a model.
+    pub async fn set_debug_overlay(&mut self, on: bool) -> Result<()> {
+        self.debug_overlay = on;
+        if on {
+            self.refresh_debug_overlay().await;
+            Ok(())
+        } else {
+            debug_overlay::hide(&self.page).await
+        }
+    }
+
+    /// Best-effort: a page that refuses the overlay shouldn't fail the action.
+    async fn refresh_debug_overlay(&self) {
+        if !self.debug_overlay {
+            return;
+        }
+        let caption = match self.log.records().last() {
+            Some(record) => format!("last: {}", record),
+            None => "no actions yet".into(),
+        };
+        let _ = debug_overlay::show(&self.page, &caption, &self.elements).await;
+    }
+
+    /// What to do when the page opens a new window (background by default);
+    /// see [`PopupPolicy`]. Tabs open now are never treated as popups.
+    pub async fn set_popup_policy(&mut self, policy: PopupPolicy) -> Result<()> {
+        if let Some(id) = self.same_tab_script.take() {
+            remove_init_script(&self.page, id).await?;
+        }
+        if policy == PopupPolicy::SameTab {
+            self.same_tab_script = add_init_script(&self.page, popup::SAME_TAB_JS).await?;
+        }
+        self.popup_tracker = PopupTracker::new(&self.browser).await?;
+        self.popup_policy = policy;
+        Ok(())
+    }
+
+    /// Windows the page opened that are still open, e.g. under
+    /// [`PopupPolicy::Background`].
+    pub async fn popups(&self) -> Result<Vec<eoka::TabInfo>> {
+        self.popup_tracker.popups(&self.browser, &self.page).await
+    }
+
+    /// Record requests the page sends with a body — form submits, `fetch`
+    /// and XHR — with size caps and header redaction (off by default). Read
+    /// them with [`Session::sent_requests`] and [`Session::last_form_post`];
+    /// see [`network`] for what the page can see of it.
+    pub async fn set_network_capture(&mut self, capture: Option<NetworkCapture>) -> Result<()> {
+        if let Some(id) = self.network_script.take() {
+            remove_init_script(&self.page, id).await?;
+        }
+        match capture {
+            Some(ref capture) => {
+                self.network_script = add_init_script(&self.page, &capture.script()).await?;
+            }
+            None => self.page.execute(network::OFF_JS).await?,
+        }
+        self.network_capture = capture;
+        Ok(())
+    }
+
+    /// Requests the current origin's pages sent since capture started, oldest
+    /// first.
+    pub async fn sent_requests(&self) -> Result<Vec<SentRequest>> {
+        network::sent_requests(&self.page).await
+    }
+
+    /// The last request whose body decodes to form fields, e.g. to check a
+    /// fill and submit sent what was intended.
+    pub async fn last_form_post(&self) -> Result<Option<FormPost>> {
+        network::last_form_post(&self.page).await
+    }
+
+    /// Auth headers the page sent (matching
+    /// [`NetworkCapture::auth_headers`]) and session cookies of the current
+    /// URL (matching [`NetworkCapture::auth_cookies`]), to bootstrap a plain
+    /// HTTP client after a browser login. Headers are only seen while
+    /// [`Session::set_network_capture`] is on.
+    pub async fn capture_auth(&self) -> Result<CapturedAuth> {
+        let default = NetworkCapture::default();
+        let capture = self.network_capture.as_ref().unwrap_or(&default);
+        network::capture_auth(&self.page, capture).await
+    }
+
+    /// Regex-search the responses captured by [`Session::set_network_capture`]
+    /// and the current page's HTML, newest first, e.g. for a CSRF token or
+    /// product id that only an XHR returned. Each match carries its URL and
+    /// capture groups.
+    pub async fn find_in_responses(&self, pattern: &str) -> Result<Vec<ResponseMatch>> {
+        network::find_in_responses(&self.page, pattern).await
+    }
+
+    /// Record what `aria-live` regions and `role="alert"`/`"status"` nodes
+    /// announce — toasts and snackbars that vanish before the next
+    /// `observe()` (off by default). Read them with
+    /// [`Session::live_messages`].
+    pub async fn set_live_monitor(&mut self, on: bool) -> Result<()> {
+        if let Some(id) = self.live_script.take() {
+            remove_init_script(&self.page, id).await?;
+        }
+        if on {
+            self.live_script = add_init_script(&self.page, live::MONITOR_JS).await?;
+        } else {
+            self.page.execute(live::OFF_JS).await?;
+        }
+        Ok(())
+    }
+
+    /// Live-region messages the current origin's pages announced since the
+    /// monitor started, oldest first.
+    pub async fn live_messages(&self) -> Result<Vec<LiveMessage>> {
+        live::messages(&self.page).await
+    }
+
+    /// Forget the recorded live-region messages, e.g. before an action
+    /// whose feedback you want on its own.
+    pub async fn clear_live_messages(&self) -> Result<()> {
+        live::clear(&self.page).await
+    }
+
+    /// The IP, country and timezone sites see, from [`egress::DEFAULT_ENDPOINT`].
+    /// Errors if the exit IP is this machine's own although a proxy is
+    /// configured, or its timezone differs from the browser's.
+    pub async fn check_egress(&self) -> Result<Egress> {
+        self.check_egress_with(&EgressCheck::default()).await
+    }
+
+    /// [`Session::check_egress`] with a custom endpoint and expected IP,
+    /// country or timezone.
+    pub async fn check_egress_with(&self, check: &EgressCheck) -> Result<Egress> {
+        egress::check(&self.browser, &self.page, self.proxy.as_deref(), check).await
+    }
+
+    /// Under [`PopupPolicy::Follow`], switch to a window the page opened, or
+    /// back to the opener of one that closed itself. Best-effort: a popup
+    /// that can't be followed shouldn't fail the action.
+    async fn follow_popups(&mut self) {
+        if self.popup_policy != PopupPolicy::Follow {
+            return;
+        }
+        let followed = self
+            .popup_tracker
+            .follow(&self.browser, &mut self.page)
+            .await;
+        if let Ok(true) = followed {
+            self.elements.clear();
+            self.reinstall_page_scripts().await;
+        }
+    }
+
+    /// Carry the deterministic, same-tab popup, network capture and
+    /// live-region scripts that are on over to a page the session just
+    /// switched to.
+    async fn reinstall_page_scripts(&mut self) {
+        if let Some(config) = deterministic::current() {
+            let _ = add_init_script(&self.page, &deterministic::page_script(&config)).await;
+        }
+        if self.same_tab_script.is_some() {
+            if let Ok(id) = add_init_script(&self.page, popup::SAME_TAB_JS).await {
+                self.same_tab_script = id;
+            }
+        }
+        if let Some(ref capture) = self.network_capture {
+            if let Ok(id) = add_init_script(&self.page, &capture.script()).await {
+                self.network_script = id;
+            }
+        }
+        if self.live_script.is_some() {
+            if let Ok(id) = add_init_script(&self.page, live::MONITOR_JS).await {
+                self.live_script = id;
+            }
+        }
+    }
+
+    async fn begin_log(&self, method: &str, target: Option<String>) -> ActionRecord {
+        ActionRecord::begin(method, target, self.page.url().await.unwrap_or_default())
+    }
+
+    async fn end_log(
+        &mut self,
+        record: ActionRecord,
+        outcome: std::result::Result<String, String>,
+    ) {
+        self.follow_popups().await;
+        let url = self.page.url().await.unwrap_or_default();
+        self.log.push(record.finish(url, outcome));
+        self.refresh_debug_overlay().await;
+    }
+
+    /// `text` quoted for the log, or [`action_log::REDACTED`] when it goes
+    /// into a password or one-time-code field (or that can't be checked).
+    async fn log_text(&self, selector: &str, text: &str) -> String {
+        match action_log::is_secret_field(&self.page, selector).await {
+            Ok(false) => format!("{:?}", text),
+            _ => action_log::REDACTED.into(),
+        }
+    }
+
+    /// The element's list line, or just its index when it isn't observed.
+    fn log_target(&self, index: usize) -> String {
+        self.elements
+            .get(index)
+            .map_or_else(|| format!("[{}]", index), |el| el.to_string())
+    }
+
     /// Get reference to underlying page.
     pub fn page(&self) -> &Page {
         &self.page
@@ -755,7 +1625,10 @@ impl Session {
 
     /// Snapshot the page: enumerate all interactive elements.
     pub async fn observe(&mut self) -> Result<&[InteractiveElement]> {
-        self.elements = observe::observe(&self.page, self.config.viewport_only).await?;
+        let snapshot = observe::snapshot(&self.page, &self.config).await?;
+        self.elements = snapshot.elements;
+        self.viewport_height = snapshot.viewport_height;
+        self.refresh_debug_overlay().await;
         Ok(&self.elements)
     }
 
@@ -767,6 +1640,32 @@ impl Session {
         annotate::annotated_screenshot(&self.page, &self.elements).await
     }
 
+    /// Observe, then return the annotated screenshot with each mark's
+    /// element metadata, for Set-of-Marks vision prompting. One call, so the
+    /// numbers on the image match the indices actions take.
+    pub async fn som_bundle(&mut self) -> Result<SomBundle> {
+        self.observe().await?;
+        annotate::som_bundle(&self.page, &self.elements).await
+    }
+
+    /// Find cookie banners, modals, chat widgets and interstitials covering
+    /// the page, topmost first, each with candidate dismiss buttons.
+    pub async fn detect_overlays(&self) -> Result<Vec<Overlay>> {
+        overlay::detect_overlays(&self.page).await
+    }
+
+    /// OCR fallback for canvas-only pages: when the last `observe()` found
+    /// no elements and a canvas fills the page, the text lines on screen with
+    /// their positions (click one at its `center()` with `page().click_at`).
+    /// Empty otherwise.
+    #[cfg(feature = "ocr")]
+    pub async fn text_regions(&self) -> Result<Vec<ocr::TextRegion>> {
+        if !self.elements.is_empty() || !ocr::is_canvas_page(&self.page).await? {
+            return Ok(Vec::new());
+        }
+        ocr::text_regions(&self.page).await
+    }
+
     /// Compact text list for LLM consumption.
     pub fn element_list(&self) -> String {
         let mut out = String::with_capacity(self.elements.len() * 40);
@@ -777,6 +1676,13 @@ impl Session {
         out
     }
 
+    /// Element list trimmed to about `max_tokens`: inputs and buttons in the
+    /// viewport first, repeated nav links dropped, with a footer counting
+    /// the elements left out.
+    pub fn element_list_budgeted(&self, max_tokens: usize) -> String {
+        observe::budgeted_list(&self.elements, max_tokens, self.viewport_height)
+    }
+
     /// Get element info by index.
     pub fn get(&self, index: usize) -> Option<&InteractiveElement> {
         self.elements.get(index)
@@ -811,7 +1717,8 @@ impl Session {
     // =========================================================================
 
     /// Get an element, verifying it still exists in DOM.
-    /// If element moved, returns error with hint about new location.
+    /// If element moved, returns error with hint about new location, or with
+    /// auto-recover on, the element at its new location.
     async fn require_fresh(&mut self, index: usize) -> Result<&InteractiveElement> {
         // First check if element exists at index
         let stored = self.elements.get(index).cloned();
@@ -834,11 +1741,21 @@ impl Session {
             self.observe().await?;
 
             // Try to find element with matching fingerprint
-            if let Some(new_idx) = self
+            let matches: Vec<usize> = self
                 .elements
                 .iter()
-                .position(|e| e.fingerprint == el.fingerprint)
-            {
+                .filter(|e| e.fingerprint == el.fingerprint)
+                .map(|e| e.index)
+                .collect();
+            if self.auto_recover && matches.len() == 1 {
+                let new_idx = matches[0];
+                eprintln!(
+                    "[eoka-agent] element [{}] \"{}\" moved to [{}], retargeting",
+                    index, el.text, new_idx
+                );
+                return Ok(&self.elements[new_idx]);
+            }
+            if let Some(new_idx) = matches.first() {
                 // Found at different index - error with helpful message
                 return Err(eoka::Error::ElementNotFound(format!(
                     "element [{}] \"{}\" moved to [{}] - call observe() to refresh",
@@ -859,103 +1776,372 @@ impl Session {
         )))
     }
 
-    /// Click an element, auto-recovering if stale.
+    /// Click an element, auto-recovering if stale. Returns what the click did.
     /// Clears element cache since clicks often trigger navigation/DOM changes.
-    pub async fn click(&mut self, index: usize) -> Result<()> {
-        let el = self.require_fresh(index).await?;
-        let selector = el.selector.clone();
-        self.page.click(&selector).await?;
-        self.wait_for_stable().await?;
-        self.elements.clear(); // Clicks often change the page
-        Ok(())
+    pub async fn click(&mut self, index: usize) -> AgentResult<ActionOutcome> {
+        self.click_with(index, &ClickOptions::default()).await
     }
 
-    /// Fill an element, auto-recovering if stale.
+    /// Click with options, auto-recovering if stale. `force` dispatches the
+    /// events without hit-testing, `js_fallback` dispatches them if the mouse
+    /// click fails; `clicked` in the result records which was used.
+    /// Clears element cache.
+    pub async fn click_with(
+        &mut self,
+        index: usize,
+        options: &ClickOptions,
+    ) -> AgentResult<ActionOutcome> {
+        let record = self.begin_log("click", Some(self.log_target(index))).await;
+        let result: AgentResult<_> = async {
+            let el = self.require_fresh(index).await?.clone();
+            let evidence = self.begin_evidence(Some(&el)).await;
+            let probe = outcome::arm(&self.page, self.accept_dialogs).await?;
+            let clicked = click::click(&self.page, &el, options).await?;
+            self.wait_for_stable().await?;
+            self.elements.clear(); // Clicks often change the page
+            let mut outcome = probe.finish(&self.page).await;
+            outcome.clicked = Some(clicked);
+            self.finish_evidence(evidence, "click".into()).await;
+            Ok(outcome)
+        }
+        .await;
+        self.end_log(record, summary(&result, |o| o.to_string()))
+            .await;
+        result
+    }
+
+    /// Fill an element, auto-recovering if stale. Returns what typing did.
     /// Does NOT clear element cache (typing rarely changes DOM structure).
-    pub async fn fill(&mut self, index: usize, text: &str) -> Result<()> {
-        let el = self.require_fresh(index).await?;
-        let selector = el.selector.clone();
-        self.page.fill(&selector, text).await?;
-        self.wait_for_stable().await?;
-        Ok(())
+    pub async fn fill(&mut self, index: usize, text: &str) -> Result<ActionOutcome> {
+        let target = format!("{} = {}", self.log_target(index), action_log::REDACTED);
+        let mut record = self.begin_log("fill", Some(target)).await;
+        let result = async {
+            let el = self.require_fresh(index).await?.clone();
+            let shown = self.log_text(&el.selector, text).await;
+            record.target = Some(format!("{} = {}", el, shown));
+            let evidence = self.begin_evidence(Some(&el)).await;
+            let probe = outcome::arm(&self.page, self.accept_dialogs).await?;
+            self.page.fill(&el.selector, text).await?;
+            self.wait_for_stable().await?;
+            let outcome = probe.finish(&self.page).await;
+            self.finish_evidence(evidence, format!("fill {}", shown))
+                .await;
+            Ok(outcome)
+        }
+        .await;
+        self.end_log(record, summary(&result, |o| o.to_string()))
+            .await;
+        result
+    }
+
+    /// Select a dropdown option, auto-recovering if stale. Returns what the
+    /// change did. Clears element cache since onChange handlers may modify DOM.
+    pub async fn select(&mut self, index: usize, value: &str) -> Result<ActionOutcome> {
+        let target = format!("{} = {:?}", self.log_target(index), value);
+        let record = self.begin_log("select", Some(target)).await;
+        let result = async {
+            let el = self.require_fresh(index).await?.clone();
+            let evidence = self.begin_evidence(Some(&el)).await;
+            let probe = outcome::arm(&self.page, self.accept_dialogs).await?;
+            let arg = serde_json::json!({ "sel": el.selector, "val": value });
+            let js = format!(
+                r#"(() => {{
+                    const arg = {arg};
+                    const sel = document.querySelector(arg.sel);
+                    if (!sel) return false;
+                    const opt = Array.from(sel.options).find(o => o.value === arg.val || o.text === arg.val);
+                    if (!opt) return false;
+                    sel.value = opt.value;
+                    sel.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                    return true;
+                }})()"#,
+                arg = serde_json::to_string(&arg).unwrap()
+            );
+            let selected: bool = self.page.evaluate(&js).await?;
+            if !selected {
+                return Err(eoka::Error::ElementNotFound(format!(
+                    "option \"{}\" in element [{}]",
+                    value, index
+                )));
+            }
+            self.wait_for_stable().await?;
+            self.elements.clear(); // onChange handlers may modify DOM
+            let outcome = probe.finish(&self.page).await;
+            self.finish_evidence(evidence, format!("select {:?}", value))
+                .await;
+            Ok(outcome)
+        }
+        .await;
+        self.end_log(record, summary(&result, |o| o.to_string()))
+            .await;
+        result
     }
 
-    /// Select a dropdown option, auto-recovering if stale.
-    /// Clears element cache since onChange handlers may modify DOM.
-    pub async fn select(&mut self, index: usize, value: &str) -> Result<()> {
-        let el = self.require_fresh(index).await?;
-        let selector = el.selector.clone();
-        let arg = serde_json::json!({ "sel": selector, "val": value });
-        let js = format!(
-            r#"(() => {{
-                const arg = {arg};
-                const sel = document.querySelector(arg.sel);
-                if (!sel) return false;
-                const opt = Array.from(sel.options).find(o => o.value === arg.val || o.text === arg.val);
-                if (!opt) return false;
-                sel.value = opt.value;
-                sel.dispatchEvent(new Event('change', {{ bubbles: true }}));
-                return true;
-            }})()"#,
-            arg = serde_json::to_string(&arg).unwrap()
-        );
-        let selected: bool = self.page.evaluate(&js).await?;
-        if !selected {
-            return Err(eoka::Error::ElementNotFound(format!(
-                "option \"{}\" in element [{}]",
-                value, index
-            )));
+    /// Select from a custom (non-`<select>`) dropdown — React-Select, MUI,
+    /// headless-UI — auto-recovering if stale. Returns what the selection did.
+    /// Clears element cache.
+    pub async fn select_custom(
+        &mut self,
+        index: usize,
+        option_text: &str,
+    ) -> Result<ActionOutcome> {
+        let target = format!("{} = {:?}", self.log_target(index), option_text);
+        let record = self.begin_log("select_custom", Some(target)).await;
+        let result = async {
+            let el = self.require_fresh(index).await?.clone();
+            let evidence = self.begin_evidence(Some(&el)).await;
+            let probe = outcome::arm(&self.page, self.accept_dialogs).await?;
+            dropdown::select_custom(&self.page, &el.selector, option_text).await?;
+            self.wait_for_stable().await?;
+            self.elements.clear();
+            let outcome = probe.finish(&self.page).await;
+            self.finish_evidence(evidence, format!("select_custom {:?}", option_text))
+                .await;
+            Ok(outcome)
         }
-        self.wait_for_stable().await?;
-        self.elements.clear(); // onChange handlers may modify DOM
-        Ok(())
+        .await;
+        self.end_log(record, summary(&result, |o| o.to_string()))
+            .await;
+        result
+    }
+
+    /// Fill the `form_index`-th form (0 = first) from a test-data profile,
+    /// matching fields by autocomplete attribute, label, name or placeholder.
+    /// Returns the fields filled. Clears element cache.
+    pub async fn autofill_form(
+        &mut self,
+        form_index: usize,
+        profile: &FormProfile,
+    ) -> Result<Vec<autofill::FilledField>> {
+        let record = self
+            .begin_log("autofill_form", Some(format!("form {}", form_index)))
+            .await;
+        let result = async {
+            let filled = autofill::autofill_form(&self.page, form_index, profile).await?;
+            self.wait_for_stable().await?;
+            self.elements.clear();
+            Ok(filled)
+        }
+        .await;
+        self.end_log(
+            record,
+            summary(&result, |f| format!("{} fields filled", f.len())),
+        )
+        .await;
+        result
     }
 
     /// Hover over element.
     pub async fn hover(&mut self, index: usize) -> Result<()> {
-        let el = self.require_fresh(index).await?;
-        let cx = el.bbox.x + el.bbox.width / 2.0;
-        let cy = el.bbox.y + el.bbox.height / 2.0;
-        self.page
-            .session()
-            .dispatch_mouse_event(eoka::cdp::MouseEventType::MouseMoved, cx, cy, None, None)
-            .await
+        let record = self.begin_log("hover", Some(self.log_target(index))).await;
+        let result = async {
+            let el = self.require_fresh(index).await?;
+            let (cx, cy) = el.bbox.center();
+            self.page
+                .session()
+                .dispatch_mouse_event(eoka::cdp::MouseEventType::MouseMoved, cx, cy, None, None)
+                .await
+        }
+        .await;
+        self.end_log(record, summary(&result, |_| String::new()))
+            .await;
+        result
+    }
+
+    /// Hover over an element, wait up to `wait_ms` for tooltips or popovers
+    /// (`aria-describedby`, `role=tooltip`), and report their text plus the
+    /// interactive elements that appeared, e.g. a flyout menu. Re-observes
+    /// the page, so `revealed` indices are current.
+    pub async fn hover_and_capture(&mut self, index: usize, wait_ms: u64) -> Result<HoverCapture> {
+        let selector = self.require_fresh(index).await?.selector.clone();
+        let before = observe::observe_with(&self.page, &self.config).await?;
+        self.hover(index).await?;
+        let tooltips = hover::tooltips(&self.page, &selector, wait_ms).await?;
+        self.observe().await?;
+        Ok(HoverCapture {
+            tooltips,
+            revealed: hover::revealed(&before, &self.elements),
+        })
+    }
+
+    /// Click at viewport coordinates, for canvas apps and vision-driven
+    /// agents. Returns what the click did. Clears element cache.
+    pub async fn click_at(&mut self, x: f64, y: f64) -> Result<ActionOutcome> {
+        let record = self
+            .begin_log("click_at", Some(format!("({}, {})", x, y)))
+            .await;
+        let result = async {
+            let evidence = self.begin_evidence(None).await;
+            let probe = outcome::arm(&self.page, self.accept_dialogs).await?;
+            input::click_at(&self.page, x, y).await?;
+            self.wait_for_stable().await?;
+            self.elements.clear();
+            let outcome = probe.finish(&self.page).await;
+            self.finish_evidence(evidence, format!("click_at ({}, {})", x, y))
+                .await;
+            Ok(outcome)
+        }
+        .await;
+        self.end_log(record, summary(&result, |o| o.to_string()))
+            .await;
+        result
     }
 
-    /// Scroll element into view.
+    /// Move the mouse to viewport coordinates.
+    pub async fn move_mouse(&self, x: f64, y: f64) -> Result<()> {
+        input::move_mouse(&self.page, x, y).await
+    }
+
+    /// Type into the focused element or canvas, one key event per character.
+    pub async fn type_text(&mut self, text: &str) -> Result<()> {
+        let shown = self.log_text(":focus", text).await;
+        let record = self.begin_log("type_text", Some(shown)).await;
+        let result = input::type_text(&self.page, text).await;
+        self.end_log(record, summary(&result, |_| String::new()))
+            .await;
+        result
+    }
+
+    /// Scroll element into view, clear of fixed headers and footers.
     pub async fn scroll_to(&mut self, index: usize) -> Result<()> {
-        let el = self.require_fresh(index).await?;
-        let selector = el.selector.clone();
-        let js = format!(
-            "document.querySelector({})?.scrollIntoView({{behavior:'smooth',block:'center'}})",
-            serde_json::to_string(&selector).unwrap()
-        );
-        self.page.execute(&js).await
+        let record = self
+            .begin_log("scroll_to", Some(self.log_target(index)))
+            .await;
+        let result = async {
+            let el = self.require_fresh(index).await?;
+            let selector = el.selector.clone();
+            let js = format!(
+                "document.querySelector({})?.scrollIntoView({{behavior:'smooth',block:'center'}})",
+                serde_json::to_string(&selector).unwrap()
+            );
+            self.page.execute(&js).await?;
+            self.page.wait(SMOOTH_SCROLL_MS).await;
+            occlusion::reveal(&self.page, &selector).await?;
+            Ok(())
+        }
+        .await;
+        self.end_log(record, summary(&result, |_| String::new()))
+            .await;
+        result
     }
 
     // =========================================================================
     // Navigation
     // =========================================================================
 
-    /// Navigate to a URL.
-    pub async fn goto(&mut self, url: &str) -> Result<()> {
-        self.elements.clear();
-        self.page.goto(url).await?;
-        self.wait_for_stable().await
+    /// Navigate to a URL. Dismisses overlays afterwards if auto-dismiss is on.
+    pub async fn goto(&mut self, url: &str) -> AgentResult<()> {
+        let record = self.begin_log("goto", Some(url.to_string())).await;
+        let token = self.canceller.token();
+        let result: AgentResult<()> = cancel::cancellable(Some(&token), async {
+            self.elements.clear();
+            let _permit = match self.rate_limiter {
+                Some(ref limiter) => Some(limiter.acquire(url).await?),
+                None => None,
+            };
+            match self.nav_watchdog {
+                Some(ref watchdog) => navigation::goto(&self.page, url, watchdog).await?,
+                None => self.page.goto(url).await?,
+            }
+            self.wait_for_stable().await?;
+            if let Some(ref options) = self.auto_dismiss {
+                // Best-effort: a banner we can't dismiss shouldn't fail navigation
+                if let Ok(dismissed) = auto_dismiss::dismiss(&self.page, options).await {
+                    if !dismissed.is_empty() {
+                        self.wait_for_stable().await?;
+                    }
+                }
+            }
+            Ok(())
+        })
+        .await;
+        self.end_log(record, summary(&result, |_| String::new()))
+            .await;
+        result
+    }
+
+    /// Dismiss cookie banners and modals now, using the auto-dismiss
+    /// settings or the defaults. Returns what was clicked.
+    pub async fn dismiss_overlays(&mut self) -> Result<Vec<Dismissed>> {
+        let options = self.auto_dismiss.clone().unwrap_or_default();
+        let dismissed = auto_dismiss::dismiss(&self.page, &options).await?;
+        if !dismissed.is_empty() {
+            self.elements.clear();
+            self.wait_for_stable().await?;
+        }
+        Ok(dismissed)
     }
 
     /// Go back in history.
     pub async fn back(&mut self) -> Result<()> {
+        let record = self.begin_log("back", None).await;
         self.elements.clear();
-        self.page.back().await?;
-        self.wait_for_stable().await
+        let result = self
+            .cancellable(async {
+                self.page.back().await?;
+                self.wait_for_stable().await
+            })
+            .await;
+        self.end_log(record, summary(&result, |_| String::new()))
+            .await;
+        result
     }
 
     /// Go forward in history.
     pub async fn forward(&mut self) -> Result<()> {
+        let record = self.begin_log("forward", None).await;
         self.elements.clear();
-        self.page.forward().await?;
-        self.wait_for_stable().await
+        let result = self
+            .cancellable(async {
+                self.page.forward().await?;
+                self.wait_for_stable().await
+            })
+            .await;
+        self.end_log(record, summary(&result, |_| String::new()))
+            .await;
+        result
+    }
+
+    /// Follow the page's next-page control (`rel=next`, "Next", "»").
+    /// Returns `false` when there is none or it didn't change the page.
+    pub async fn next_page(&mut self) -> Result<bool> {
+        self.elements.clear();
+        self.cancellable(paginate::next_page(&self.page)).await
+    }
+
+    /// Run `per_page` on the current page and each following one, up to
+    /// `max_pages` pages in total.
+    pub async fn paginate<T>(
+        &mut self,
+        max_pages: usize,
+        mut per_page: impl AsyncFnMut(&mut Session) -> Result<T>,
+    ) -> Result<Paginated<T>> {
+        let mut results = Vec::new();
+        let mut exhausted = false;
+        while results.len() < max_pages {
+            results.push(per_page(self).await?);
+            if results.len() < max_pages && !self.next_page().await? {
+                exhausted = true;
+                break;
+            }
+        }
+        Ok(Paginated {
+            pages: results.len(),
+            results,
+            exhausted,
+        })
+    }
+
+    /// Request every link on the page within `scope` and report the ones
+    /// answering 4xx/5xx or not at all (see [`LinkAudit::broken`]).
+    pub async fn audit_links(&self, scope: LinkScope) -> Result<LinkAudit> {
+        links::audit_links(&self.page, scope).await
+    }
+
+    /// Check the page for missing alt text and labels, low contrast,
+    /// duplicate ids and keyboard traps. Presses Tab, so focus moves.
+    pub async fn audit_a11y(&self) -> Result<Vec<a11y::Violation>> {
+        a11y::audit(&self.page).await
     }
 
     // =========================================================================
@@ -977,6 +2163,24 @@ impl Session {
         self.page.text().await
     }
 
+    /// Viewport size and scroll offsets; element boxes are relative to it.
+    pub async fn viewport(&self) -> Result<Viewport> {
+        geometry::viewport(&self.page).await
+    }
+
+    /// Element counts by kind, page size and detected frameworks — a cheap
+    /// look before choosing between `observe()`, `text()` and a screenshot.
+    pub async fn page_summary(&self) -> Result<PageSummary> {
+        summary::page_summary(&self.page).await
+    }
+
+    /// Visible validation messages keyed by the label of the field they
+    /// belong to — why the last fill or submit was rejected. Also in the
+    /// [`ActionOutcome`] of every click, fill and select.
+    pub async fn form_errors(&self) -> Result<Vec<FormError>> {
+        form_errors::form_errors(&self.page).await
+    }
+
     // =========================================================================
     // Scrolling
     // =========================================================================
@@ -1015,16 +2219,82 @@ impl Session {
     /// Waits up to 2s for network idle, then 50ms for DOM settle.
     /// Intentionally succeeds even if network doesn't fully idle (some sites never stop polling).
     pub async fn wait_for_stable(&self) -> Result<()> {
-        // Best-effort network wait - ignore timeout (some sites have constant polling)
-        let _ = self.page.wait_for_network_idle(200, 2000).await;
-        // Brief DOM settle time
-        self.page.wait(50).await;
-        Ok(())
+        self.cancellable(async {
+            // Best-effort network wait - ignore timeout (some sites have constant polling)
+            let _ = self.page.wait_for_network_idle(200, 2000).await;
+            // Brief DOM settle time
+            self.page.wait(50).await;
+            Ok(())
+        })
+        .await
     }
 
-    /// Fixed delay in milliseconds.
+    /// Fixed delay in milliseconds. Ends early on [`Session::cancel_current`].
     pub async fn wait(&self, ms: u64) {
-        self.page.wait(ms).await;
+        let _ = self
+            .cancellable(async {
+                self.page.wait(ms).await;
+                Ok(())
+            })
+            .await;
+    }
+
+    /// Wait for text to appear on the page.
+    pub async fn wait_for_text(&self, text: &str, timeout_ms: u64) -> Result<()> {
+        self.cancellable(async {
+            self.page.wait_for_text(text, timeout_ms).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Wait for the URL to contain `pattern`.
+    pub async fn wait_for_url(&self, pattern: &str, timeout_ms: u64) -> Result<()> {
+        self.cancellable(self.page.wait_for_url_contains(pattern, timeout_ms))
+            .await
+    }
+
+    /// Wait until `text` disappears from the page, e.g. "Loading…".
+    pub async fn wait_for_text_gone(&self, text: &str, timeout_ms: u64) -> Result<()> {
+        self.cancellable(wait::wait_for_text_gone(&self.page, text, timeout_ms))
+            .await
+    }
+
+    /// Wait up to 5s for an element to stop moving (same bounding box for a
+    /// few animation frames), e.g. a modal sliding in.
+    pub async fn wait_for_element_stable(&mut self, index: usize) -> Result<()> {
+        let selector = self.require_fresh(index).await?.selector.clone();
+        self.cancellable(wait::wait_for_element_stable(
+            &self.page,
+            &selector,
+            wait::STABLE_FRAMES,
+            5000,
+        ))
+        .await
+    }
+
+    /// Stop the waits and navigations running now; they fail with a
+    /// `cancelled` error (see [`cancel::is_cancelled`]). Later calls run
+    /// normally.
+    pub fn cancel_current(&self) {
+        self.canceller.cancel_current();
+    }
+
+    /// A handle for [`Canceller::cancel_current`] from another task, e.g. a
+    /// Ctrl-C handler, while this session is busy.
+    pub fn canceller(&self) -> Canceller {
+        self.canceller.clone()
+    }
+
+    /// Run `fut` until it finishes or [`Session::cancel_current`] is called.
+    async fn cancellable<T>(&self, fut: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+        cancel::cancellable(Some(&self.canceller.token()), fut).await
+    }
+
+    /// Assertions that wait for the page to get there, e.g.
+    /// `expect().text_contains("Order placed")`; see [`Expect`].
+    pub fn expect(&self) -> Expect<'_> {
+        Expect::new(&self.page)
     }
 
     // =========================================================================
@@ -1032,8 +2302,12 @@ impl Session {
     // =========================================================================
 
     /// Press a key.
-    pub async fn press_key(&self, key: &str) -> Result<()> {
-        self.page.human().press_key(key).await
+    pub async fn press_key(&mut self, key: &str) -> Result<()> {
+        let record = self.begin_log("press_key", Some(key.to_string())).await;
+        let result = deterministic::press_key(&self.page, key).await;
+        self.end_log(record, summary(&result, |_| String::new()))
+            .await;
+        result
     }
 
     // =========================================================================
@@ -1050,6 +2324,17 @@ impl Session {
         self.page.execute(js).await
     }
 
+    /// Send any DevTools protocol command to the page and return its result,
+    /// for commands the crate doesn't wrap yet. Protocol errors come back as
+    /// [`eoka::Error::Cdp`] with Chrome's code and message.
+    pub async fn cdp_call(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        cdp_call(&self.page, method, params).await
+    }
+
     // =========================================================================
     // SPA Navigation
     // =========================================================================
@@ -1069,6 +2354,35 @@ impl Session {
         Ok(result)
     }
 
+    /// Wait until React/Vue/Next/Angular have hydrated server-rendered markup.
+    /// Clicking before hydration silently does nothing on SSR pages.
+    pub async fn wait_for_hydration(&self, timeout_ms: u64) -> Result<()> {
+        self.cancellable(async {
+            spa::wait_for_hydration(&self.page, timeout_ms).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Set query params through the SPA router, keeping the other params.
+    /// An empty value removes that param. Returns the new path.
+    /// Clears element cache since the DOM will change.
+    pub async fn spa_set_query(&mut self, params: &HashMap<String, String>) -> Result<String> {
+        let info = spa::detect_router(&self.page).await?;
+        let result = spa::spa_set_query(&self.page, &info.router_type, params).await?;
+        self.elements.clear();
+        Ok(result)
+    }
+
+    /// Set the hash fragment through the SPA router. Returns the new path.
+    /// Clears element cache since the DOM will change.
+    pub async fn spa_set_hash(&mut self, fragment: &str) -> Result<String> {
+        let info = spa::detect_router(&self.page).await?;
+        let result = spa::spa_set_hash(&self.page, &info.router_type, fragment).await?;
+        self.elements.clear();
+        Ok(result)
+    }
+
     /// Navigate browser history by delta steps.
     /// delta = -1 goes back, delta = 1 goes forward.
     /// Clears element cache since the DOM will change.
@@ -1078,6 +2392,27 @@ impl Session {
         Ok(())
     }
 
+    /// Wait for a client-side route change (pushState, popstate, hashchange),
+    /// optionally to a path containing `path_pattern`. Returns the new path.
+    /// Unlike `wait_for_stable`, this sees transitions that make no requests.
+    /// Call `spa::watch_route_changes` first if the transition may finish
+    /// before this is called. Clears element list since the DOM will change.
+    pub async fn wait_for_route_change(
+        &mut self,
+        timeout_ms: u64,
+        path_pattern: Option<&str>,
+    ) -> Result<String> {
+        let path = self
+            .cancellable(spa::wait_for_route_change(
+                &self.page,
+                timeout_ms,
+                path_pattern,
+            ))
+            .await?;
+        self.elements.clear();
+        Ok(path)
+    }
+
     // =========================================================================
     // Cleanup
     // =========================================================================
@@ -1088,6 +2423,17 @@ impl Session {
     }
 }
 
+/// How long a smooth `scrollIntoView` takes to settle before hit-testing.
+const SMOOTH_SCROLL_MS: u64 = 400;
+
+/// An action's result as an action-log outcome: its summary, or the error.
+fn summary<T, E: fmt::Display>(
+    result: &std::result::Result<T, E>,
+    describe: impl FnOnce(&T) -> String,
+) -> std::result::Result<String, String> {
+    result.as_ref().map(describe).map_err(|e| e.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1127,7 +2473,13 @@ mod tests {
                 width: 100.0,
                 height: 30.0,
             },
+            visibility: 1.0,
             fingerprint,
+            context: None,
+            href: None,
+            name: None,
+            title: None,
+            id: None,
         }
     }
 
@@ -1219,6 +2571,35 @@ mod tests {
         assert_eq!(el.to_string(), "[0] <div> \"Click\" role=\"button\"");
     }
 
+    #[test]
+    fn test_element_display_context() {
+        let mut el = make_element(3, "button", "Add to cart", None, None, None, None, false);
+        el.context = Some("Blue Widget".into());
+        assert_eq!(
+            el.to_string(),
+            "[3] <button> \"Add to cart\" in \"Blue Widget\""
+        );
+    }
+
+    #[test]
+    fn test_element_display_attributes() {
+        let mut el = make_element(4, "a", "Docs", None, None, None, None, false);
+        el.href = Some("https://example.com/docs".into());
+        el.id = Some("nav-docs".into());
+        el.title = Some("Read the docs".into());
+        assert_eq!(
+            el.to_string(),
+            "[4] <a> \"Docs\" id=\"nav-docs\" title=\"Read the docs\" → example.com/docs"
+        );
+    }
+
+    #[test]
+    fn test_element_display_partly_hidden() {
+        let mut el = make_element(5, "button", "Checkout", None, None, None, None, false);
+        el.visibility = 0.33;
+        assert_eq!(el.to_string(), "[5] <button> \"Checkout\" [33% visible]");
+    }
+
     #[test]
     fn test_observe_diff_display_no_changes() {
         let diff = ObserveDiff {
@@ -1259,9 +2640,60 @@ mod tests {
         assert_eq!(diff.to_string(), "+2 added, -1 removed (5 total)");
     }
 
+    #[test]
+    fn test_element_list_budgeted() {
+        let mut elements = vec![
+            make_element(0, "a", "Home", None, None, None, None, false),
+            make_element(1, "a", "Shop", None, None, None, None, false),
+            make_element(
+                2,
+                "input",
+                "",
+                None,
+                Some("search"),
+                Some("Search"),
+                None,
+                false,
+            ),
+            make_element(3, "button", "Add to cart", None, None, None, None, false),
+            make_element(4, "a", "Home", None, None, None, None, false),
+            make_element(5, "button", "Subscribe", None, None, None, None, false),
+        ];
+        elements[4].bbox.y = 2000.0;
+        elements[5].bbox.y = 2000.0;
+
+        // Everything fits; the repeated "Home" link is dropped
+        let list = observe::budgeted_list(&elements, 1000, 800.0);
+        assert!(!list.contains("[4]"));
+        assert!(list.starts_with("[0] <a> \"Home\"\n"));
+        assert!(list.ends_with("… and 1 more (filter with observe)\n"));
+
+        // Tight budget: in-viewport input and button win, in index order
+        let list = observe::budgeted_list(&elements, 32, 800.0);
+        assert_eq!(
+            list,
+            "[2] <input type=\"search\"> placeholder=\"Search\"\n[3] <button> \"Add to cart\"\n… and 4 more (filter with observe)\n"
+        );
+    }
+
+    #[test]
+    fn test_short_href() {
+        assert_eq!(
+            short_href("https://www.example.com/blog/post-1/?utm=x#top"),
+            "example.com/blog/post-1"
+        );
+        assert_eq!(short_href("https://example.com/"), "example.com");
+        assert_eq!(
+            short_href("mailto:sales@example.com"),
+            "mailto:sales@example.com"
+        );
+    }
+
     #[test]
     fn test_observe_config_default() {
         let config = ObserveConfig::default();
         assert!(config.viewport_only);
+        assert_eq!(config.text_limit, 60);
+        assert!(config.include_href && !config.include_id);
     }
 }
@@ -0,0 +1,7 @@
+//! Link audit — requests every anchor on the page and reports the targets
+//! that answer 4xx/5xx or don't answer at all.
+//!
+//! The checks live in [`eoka_runner::links`], so the `audit_links` action
+//! and [`crate::Session::audit_links`] request links the same way.
+
+pub use eoka_runner::links::{audit_links, LinkAudit, LinkCheck, LinkScope};
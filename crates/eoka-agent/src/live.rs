@@ -0,0 +1,130 @@
+//! Live-region monitor — records what the page announces through
+//! `aria-live` regions and `role="alert"`, `"status"` and `"log"` nodes:
+//! toasts and snackbars like "Item added to cart" or "Invalid password"
+//! that are gone again before the next `observe()`.
+//!
+//! Runs as a page script with a `MutationObserver`, keeping messages in
+//! `sessionStorage` so an announcement survives a same-origin navigation
+//! right after it. The stored entries are visible to the page, so the
+//! monitor is off until [`crate::Session::set_live_monitor`] turns it on.
+
+use eoka::{Page, Result};
+use serde::Deserialize;
+use std::fmt;
+
+/// Installs the observer, or switches a stopped one back on.
+pub(crate) const MONITOR_JS: &str = r#"
+(() => {
+  if (window.__eokaLive) { window.__eokaLive.off = false; return; }
+  const live = window.__eokaLive = { off: false };
+  const KEY = '__eokaLive';
+  const MAX = 200;
+  const REGION = '[aria-live]:not([aria-live="off"]), [role="alert"], [role="status"], [role="log"], output';
+  const last = new WeakMap();
+
+  function politeness(el) {
+    const attr = el.getAttribute('aria-live');
+    if (attr) return attr;
+    return el.getAttribute('role') === 'alert' ? 'assertive' : 'polite';
+  }
+
+  function record(region) {
+    if (live.off) return;
+    const text = (region.innerText || region.textContent || '').replace(/\s+/g, ' ').trim();
+    if (!text || last.get(region) === text) return;
+    last.set(region, text);
+    let all = [];
+    try { all = JSON.parse(sessionStorage.getItem(KEY) || '[]'); } catch (e) {}
+    all.push({
+      text: text.slice(0, 500),
+      politeness: politeness(region),
+      role: region.getAttribute('role'),
+      url: location.href,
+      at: Date.now(),
+    });
+    while (all.length > MAX) all.shift();
+    try { sessionStorage.setItem(KEY, JSON.stringify(all)); } catch (e) {}
+  }
+
+  const observer = new MutationObserver(mutations => {
+    const touched = new Set();
+    for (const m of mutations) {
+      const node = m.target.nodeType === 1 ? m.target : m.target.parentElement;
+      const region = node && node.closest(REGION);
+      if (region) touched.add(region);
+      for (const added of m.addedNodes) {
+        if (added.nodeType !== 1) continue;
+        if (added.matches(REGION)) touched.add(added);
+        added.querySelectorAll(REGION).forEach(r => touched.add(r));
+      }
+    }
+    // Let the page finish filling the region before reading it.
+    if (touched.size) setTimeout(() => touched.forEach(record), 0);
+  });
+  observer.observe(document, { childList: true, subtree: true, characterData: true });
+})()
+"#;
+
+/// Stops recording in the current document.
+pub(crate) const OFF_JS: &str = "window.__eokaLive && (window.__eokaLive.off = true)";
+
+const MESSAGES_JS: &str = r#"
+(() => {
+  try { return JSON.parse(sessionStorage.getItem('__eokaLive') || '[]'); } catch (e) { return []; }
+})()
+"#;
+
+const CLEAR_JS: &str = "sessionStorage.removeItem('__eokaLive')";
+
+/// One announcement from a live region.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LiveMessage {
+    /// Region text after the update, whitespace collapsed, up to 500 chars
+    pub text: String,
+    /// `assertive` or `polite`
+    pub politeness: String,
+    /// `alert`, `status` or `log`, if the region has a role
+    pub role: Option<String>,
+    /// Page the message appeared on
+    pub url: String,
+    /// Page clock at the update, in ms since the epoch
+    pub at: f64,
+}
+
+impl fmt::Display for LiveMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.role {
+            Some(ref role) => write!(f, "[{} {}] {}", self.politeness, role, self.text),
+            None => write!(f, "[{}] {}", self.politeness, self.text),
+        }
+    }
+}
+
+/// Messages recorded on the current origin, oldest first.
+pub async fn messages(page: &Page) -> Result<Vec<LiveMessage>> {
+    page.evaluate(MESSAGES_JS).await
+}
+
+/// Forget the messages recorded on the current origin.
+pub async fn clear(page: &Page) -> Result<()> {
+    page.execute(CLEAR_JS).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        let json = r#"[
+            {"text": "Invalid password", "politeness": "assertive", "role": "alert", "url": "https://a.test/login", "at": 1704067200000},
+            {"text": "Item added to cart", "politeness": "polite", "role": null, "url": "https://a.test/p/1", "at": 1704067201000}
+        ]"#;
+        let messages: Vec<LiveMessage> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            messages[0].to_string(),
+            "[assertive alert] Invalid password"
+        );
+        assert_eq!(messages[1].to_string(), "[polite] Item added to cart");
+    }
+}
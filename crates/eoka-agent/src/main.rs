@@ -1,6 +1,43 @@
 mod mcp;
+mod tool_hints;
+
+use eoka_agent::{inspect, Browser, StealthConfig};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    mcp::run_server().await
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("inspect") => match args.get(1) {
+            Some(url) => run_inspect(url).await,
+            None => anyhow::bail!("usage: eoka-agent inspect <url>"),
+        },
+        _ => mcp::run_server().await,
+    }
+}
+
+/// Open `url` headful and describe each element clicked, until the window
+/// is closed or Ctrl-C.
+async fn run_inspect(url: &str) -> anyhow::Result<()> {
+    let browser = Browser::launch_with_config(StealthConfig {
+        headless: false,
+        ..Default::default()
+    })
+    .await?;
+    let page = browser.new_page(url).await?;
+    println!("Click an element to inspect it (Ctrl-C to quit).\n");
+    loop {
+        tokio::select! {
+            picked = inspect::pick(&page) => match picked {
+                Ok(picked) => println!("{}", picked),
+                // Usually the window being closed
+                Err(e) => {
+                    eprintln!("inspect stopped: {}", e);
+                    break;
+                }
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+    let _ = browser.close().await;
+    Ok(())
 }
@@ -1,17 +1,31 @@
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use rmcp::{
-    handler::server::{tool::ToolRouter, wrapper::Parameters},
+    handler::server::{
+        tool::{ToolCallContext, ToolRouter},
+        wrapper::Parameters,
+    },
     model::*,
-    tool, tool_handler, tool_router, ServerHandler,
+    service::RequestContext,
+    tool, tool_router, RoleServer, ServerHandler,
 };
 use serde::Deserialize;
 use serde_json::Value;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use crate::tool_hints::ToolHints;
 use eoka::{Browser, Page, StealthConfig, TabInfo};
-use eoka_agent::{annotate, captcha, observe, spa, target, InteractiveElement, ObserveConfig, Target};
+use eoka_agent::flows::{self, LoginSpec, SuccessCheck, TotpSource};
+use eoka_agent::{
+    action_log, annotate, captcha, deterministic, dropdown, geometry, hover, input, observe,
+    occlusion, outcome, overlay, paginate, spa, summary, target, ActionLog, ActionRecord, Geometry,
+    InteractiveElement, ObserveConfig, Target,
+};
+use eoka_email::{
+    AsyncImapClient, EmailMessage, ImapConfig, LinkFilter, SearchCriteria, WaitOptions,
+};
 
 // ---------------------------------------------------------------------------
 // Constants
@@ -19,6 +33,13 @@ use eoka_agent::{annotate, captcha, observe, spa, target, InteractiveElement, Ob
 
 const ERR_NO_BROWSER: &str = "No browser open. Use navigate first.";
 const ERR_NO_TAB: &str = "No tab open. Use navigate first.";
+const ERR_NO_EMAIL: &str = "No email yet. Use email_wait first.";
+
+/// How long `hover` waits for a tooltip to show.
+const HOVER_TOOLTIP_MS: u64 = 500;
+
+/// Tool calls from the action log attached to each error response.
+const ERROR_LOG_ACTIONS: usize = 5;
 
 // ---------------------------------------------------------------------------
 // Request types
@@ -33,7 +54,7 @@ pub struct NavigateRequest {
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct TargetRequest {
     #[schemars(
-        description = "Target element. Supports: index (0), text:Submit, placeholder:Email, role:button, css:form button, id:my-btn, or plain text search"
+        description = "Target element. Supports: index (0), text:Submit, placeholder:Email, label:Email, role:button, css:form button, id:my-btn, or plain text search"
     )]
     pub target: String,
 }
@@ -41,7 +62,7 @@ pub struct TargetRequest {
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct FillRequest {
     #[schemars(
-        description = "Target input. Supports: index (0), text:Email, placeholder:Enter code, css:input.search, id:email-field, or plain text search"
+        description = "Target input. Supports: index (0), text:Email, placeholder:Enter code, label:Email, css:input.search, id:email-field, or plain text search"
     )]
     pub target: String,
     #[schemars(description = "Text to type into the element")]
@@ -62,6 +83,22 @@ pub struct TypeKeyRequest {
     pub key: String,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct PointRequest {
+    #[schemars(description = "X in viewport CSS pixels")]
+    pub x: f64,
+    #[schemars(description = "Y in viewport CSS pixels")]
+    pub y: f64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct TypeTextRequest {
+    #[schemars(
+        description = "Text to type into the focused element or canvas (\\n presses Enter)"
+    )]
+    pub text: String,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct ScrollRequest {
     #[schemars(
@@ -82,6 +119,26 @@ pub struct JsRequest {
     pub js: String,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CdpRequest {
+    #[schemars(description = "DevTools protocol method, e.g. 'Network.setExtraHTTPHeaders'")]
+    pub method: String,
+    #[schemars(description = "Command parameters as a JSON object (default: {})")]
+    pub params: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct PaginateRequest {
+    #[schemars(
+        description = "JavaScript run on each page; the last expression's value is collected as JSON"
+    )]
+    pub js: String,
+    #[schemars(
+        description = "Maximum number of pages to visit, including the current one (default: 5)"
+    )]
+    pub max_pages: Option<usize>,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct SetCookieRequest {
     #[schemars(description = "Cookie name")]
@@ -108,12 +165,34 @@ pub struct TabIdRequest {
     pub tab_id: String,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ObserveTabRequest {
+    #[schemars(description = "Tab ID (from list_tabs). If omitted, observes every open tab.")]
+    pub tab_id: Option<String>,
+    #[schemars(description = "Maximum elements to return per tab (default: unlimited)")]
+    pub max: Option<usize>,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct SpaNavigateRequest {
     #[schemars(description = "Target path to navigate to (e.g. '/docs', '/about')")]
     pub path: String,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SpaSetQueryRequest {
+    #[schemars(
+        description = "Query params to set (e.g. {\"q\": \"shoes\", \"page\": \"2\"}). Other params are kept; an empty value removes a param."
+    )]
+    pub params: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SpaSetHashRequest {
+    #[schemars(description = "Hash fragment, with or without '#' (e.g. 'section-2')")]
+    pub fragment: String,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct HistoryGoRequest {
     #[schemars(description = "History delta: -1 for back, 1 for forward, -2 for back twice, etc.")]
@@ -128,6 +207,14 @@ pub struct ObserveRequest {
     pub filter: Option<String>,
     #[schemars(description = "Maximum elements to return (default: unlimited)")]
     pub max: Option<usize>,
+    #[schemars(
+        description = "Token budget for the list: keeps inputs/buttons in view first, drops repeated nav links, notes how many were left out"
+    )]
+    pub max_tokens: Option<usize>,
+    #[schemars(
+        description = "Only list elements inside this part of the viewport: 'top-right quadrant', 'left half', 'bottom', 'center', or 'x,y,width,height' in CSS pixels"
+    )]
+    pub region: Option<String>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -168,6 +255,78 @@ pub struct DetectCaptchaRequest {
     pub auto_detect: Option<bool>,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct EmailWaitRequest {
+    #[schemars(description = "Sender address or name substring")]
+    pub from: Option<String>,
+    #[schemars(description = "Recipient address substring (useful with plus-addressing)")]
+    pub to: Option<String>,
+    #[schemars(description = "Subject substring")]
+    pub subject_contains: Option<String>,
+    #[schemars(description = "Body substring")]
+    pub body_contains: Option<String>,
+    #[schemars(
+        description = "Also accept emails received in the last N minutes (default: only emails arriving after this call starts)"
+    )]
+    pub since_minutes: Option<i64>,
+    #[schemars(description = "How long to wait in milliseconds (default: 120000)")]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct EmailCodeRequest {
+    #[schemars(
+        description = "Regex for the code; capture group 1 is returned if present. If omitted, the most likely one-time code is picked."
+    )]
+    pub pattern: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct EmailLinkRequest {
+    #[schemars(description = "Only accept links to these domains (subdomains included)")]
+    pub allow_domains: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct RunRunnerConfigRequest {
+    #[schemars(description = "Path to an eoka-runner config (YAML, TOML or JSON)")]
+    pub path: Option<String>,
+    #[schemars(description = "Inline YAML config, instead of path")]
+    pub yaml: Option<String>,
+    #[schemars(description = "Values for the config's ${param} placeholders")]
+    pub params: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct LoginRequest {
+    #[schemars(description = "Login page URL")]
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    #[schemars(
+        description = "Username field, e.g. placeholder:Email or css:#user (default: the usual username/email inputs)"
+    )]
+    pub username_target: Option<String>,
+    #[schemars(description = "Password field (default: css:input[type=password])")]
+    pub password_target: Option<String>,
+    #[schemars(
+        description = "Submit / Next button, also clicked between the pages of a two-page login (default: the form's submit button)"
+    )]
+    pub submit_target: Option<String>,
+    #[schemars(description = "Succeed once the URL contains this")]
+    pub success_url: Option<String>,
+    #[schemars(description = "Succeed once the page text contains this")]
+    pub success_text: Option<String>,
+    #[schemars(description = "Base32 authenticator secret, to fill a TOTP code")]
+    pub totp_secret: Option<String>,
+    #[schemars(
+        description = "Fill a code emailed by this sender instead (IMAP account from EOKA_IMAP_* env vars)"
+    )]
+    pub totp_email_from: Option<String>,
+    #[schemars(description = "Code field (default: one-time-code / otp / code inputs)")]
+    pub totp_target: Option<String>,
+}
+
 // ---------------------------------------------------------------------------
 // Tab State
 // ---------------------------------------------------------------------------
@@ -404,14 +563,108 @@ async fn wait_for_stable(page: &Page) -> eoka::Result<()> {
     Ok(())
 }
 
+/// IMAP settings from `EOKA_IMAP_HOST`, `EOKA_IMAP_PORT` (default 993),
+/// `EOKA_IMAP_USER`, `EOKA_IMAP_PASSWORD`, `EOKA_IMAP_MAILBOX` (default INBOX)
+/// and `EOKA_IMAP_TLS` (default true).
+fn imap_config_from_env() -> Result<ImapConfig, ErrorData> {
+    let var = |name: &str| std::env::var(name).map_err(|_| err(format!("{} is not set", name)));
+    let port = match std::env::var("EOKA_IMAP_PORT") {
+        Ok(p) => p
+            .parse()
+            .map_err(|_| err(format!("EOKA_IMAP_PORT is not a port: {}", p)))?,
+        Err(_) => 993,
+    };
+    let mut config = ImapConfig::new(
+        var("EOKA_IMAP_HOST")?,
+        port,
+        var("EOKA_IMAP_USER")?,
+        var("EOKA_IMAP_PASSWORD")?,
+    );
+    if let Ok(mailbox) = std::env::var("EOKA_IMAP_MAILBOX") {
+        config = config.mailbox(mailbox);
+    }
+    if let Ok(tls) = std::env::var("EOKA_IMAP_TLS") {
+        config = config.tls(tls != "false" && tls != "0");
+    }
+    Ok(config)
+}
+
+/// Login spec for the `login` tool.
+fn login_spec(req: LoginRequest) -> Result<LoginSpec, ErrorData> {
+    let mut spec = LoginSpec::new(req.url, req.username, req.password);
+    if let Some(ref t) = req.username_target {
+        spec = spec.username_target(t);
+    }
+    if let Some(ref t) = req.password_target {
+        spec = spec.password_target(t);
+    }
+    if let Some(ref t) = req.submit_target {
+        spec = spec.submit_target(t);
+    }
+    if let Some(ref t) = req.totp_target {
+        spec = spec.totp_target(t);
+    }
+    spec.success_check = match (req.success_url, req.success_text) {
+        (Some(url), _) => Some(SuccessCheck::UrlContains(url)),
+        (None, Some(text)) => Some(SuccessCheck::Text(text)),
+        (None, None) => None,
+    };
+    spec.totp_source = match (req.totp_secret, req.totp_email_from) {
+        (Some(secret), _) => Some(TotpSource::Secret(secret)),
+        (None, Some(from)) => Some(TotpSource::email(
+            imap_config_from_env()?,
+            SearchCriteria::new().from(&from),
+        )),
+        (None, None) => None,
+    };
+    Ok(spec)
+}
+
+/// Search criteria for `email_wait`: emails received after `call_start`,
+/// unless the request widens that with `since_minutes`.
+fn email_criteria(
+    req: &EmailWaitRequest,
+    call_start: chrono::DateTime<chrono::Utc>,
+) -> SearchCriteria {
+    let mut criteria = match req.since_minutes {
+        Some(minutes) => SearchCriteria::new().since_minutes(minutes),
+        None => SearchCriteria::new().since(call_start),
+    };
+    if let Some(ref from) = req.from {
+        criteria = criteria.from(from);
+    }
+    if let Some(ref to) = req.to {
+        criteria = criteria.to(to);
+    }
+    if let Some(ref subject) = req.subject_contains {
+        criteria = criteria.subject_contains(subject);
+    }
+    if let Some(ref body) = req.body_contains {
+        criteria = criteria.body_contains(body);
+    }
+    criteria
+}
+
 #[derive(Clone)]
 pub struct EokaServer {
     state: Arc<Mutex<Option<BrowserState>>>,
+    /// Last email returned by `email_wait`
+    email: Arc<Mutex<Option<EmailMessage>>>,
+    /// Every tool call, kept across browser relaunches
+    log: Arc<Mutex<ActionLog>>,
     tool_router: ToolRouter<Self>,
+    /// Annotations sent with `tools/list`
+    hints: Arc<ToolHints>,
     headless: bool,
 }
 
 impl EokaServer {
+    /// Replace the default read-only/destructive/idempotent hints.
+    pub fn with_tool_hints(mut self, hints: ToolHints) -> Self {
+        self.hints = Arc::new(hints);
+        self
+    }
+
     async fn ensure_browser(&self) -> Result<(), ErrorData> {
         let mut guard = self.state.lock().await;
         // If browser is unhealthy (previous transport error), kill and relaunch
@@ -436,6 +689,15 @@ impl EokaServer {
         }
     }
 
+    /// URL of the current tab, or empty when there is none.
+    async fn current_url(&self) -> String {
+        let guard = self.state.lock().await;
+        match guard.as_ref().and_then(|s| s.current_tab()) {
+            Some(tab) => tab.page.url().await.unwrap_or_default(),
+            None => String::new(),
+        }
+    }
+
     /// Check error and mark state unhealthy if it's a transport error.
     /// The unhealthy state will trigger a relaunch on the next ensure_browser() call.
     async fn check_transport_err<E: std::fmt::Display>(&self, e: E) -> ErrorData {
@@ -465,7 +727,10 @@ impl EokaServer {
 
         Self {
             state: Arc::new(Mutex::new(None)),
+            email: Arc::new(Mutex::new(None)),
+            log: Arc::new(Mutex::new(ActionLog::default())),
             tool_router: Self::tool_router(),
+            hints: Arc::new(ToolHints::default()),
             headless,
         }
     }
@@ -542,6 +807,78 @@ impl EokaServer {
         text_ok(format!("Closed tab [{}]", req.0.tab_id))
     }
 
+    #[tool(
+        description = "List interactive elements of a tab without switching to it, or of every tab at once (popups the page opened included) when tab_id is omitted. Tabs are observed concurrently; indices are kept per tab for after switch_tab."
+    )]
+    async fn observe_tab(
+        &self,
+        req: Parameters<ObserveTabRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut guard = self.state.lock().await;
+        let state = guard.as_mut().ok_or_else(|| err(ERR_NO_BROWSER))?;
+        let config = state.config.clone();
+
+        let open: Vec<String> = state
+            .list_tabs()
+            .await
+            .map_err(err)?
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        let ids: Vec<String> = match req.0.tab_id {
+            Some(id) if open.contains(&id) => vec![id],
+            Some(id) => return Err(err(format!("Tab {} not found", id))),
+            None => open,
+        };
+        // Popups the page opened have no state yet; register them so
+        // switch_tab and the element indices work for them too.
+        for id in &ids {
+            if !state.tabs.contains_key(id) {
+                if let Ok(page) = state.browser.attach_page(id).await {
+                    state.tabs.insert(id.clone(), TabState::new(page));
+                }
+            }
+        }
+        // A tab that closed since it was listed couldn't be attached
+        let ids: Vec<String> = ids
+            .into_iter()
+            .filter(|id| state.tabs.contains_key(id))
+            .collect();
+        let pages: Vec<&Page> = ids.iter().map(|id| &state.tabs[id].page).collect();
+        let snapshots = observe::snapshot_all(&pages, &config).await;
+
+        let max = req.0.max.unwrap_or(usize::MAX);
+        let mut out = String::new();
+        for (id, snapshot) in ids.iter().zip(snapshots) {
+            let snapshot = match snapshot {
+                Ok(s) => s,
+                Err(e) => {
+                    drop(guard);
+                    return Err(self.check_transport_err(e).await);
+                }
+            };
+            let tab = state.tabs.get_mut(id).unwrap();
+            tab.elements = snapshot.elements;
+            let marker = if state.current_tab_id.as_deref() == Some(id.as_str()) {
+                " *"
+            } else {
+                ""
+            };
+            let url = tab.page.url().await.unwrap_or_default();
+            out.push_str(&format!("=== [{}]{} {} ===\n", id, marker, url));
+            if tab.elements.is_empty() {
+                out.push_str("No interactive elements found.\n");
+            }
+            for el in tab.elements.iter().take(max) {
+                out.push_str(&format!("{}\n", el));
+            }
+        }
+        if out.is_empty() {
+            out = "No tabs open.".into();
+        }
+        text_ok(out)
+    }
+
     // =========================================================================
     // Navigation
     // =========================================================================
@@ -570,21 +907,32 @@ impl EokaServer {
     }
 
     #[tool(
-        description = "List interactive elements. Optional filter: 'inputs' (form elements), 'buttons' (clickables), 'all'. Optional max limit. Use live targeting (text:, css:) to skip observe."
+        description = "List interactive elements. Optional filter: 'inputs' (form elements), 'buttons' (clickables), 'all'. Optional max limit or max_tokens budget for dense pages, or a region ('top-right quadrant', 'x,y,width,height') to list one panel. Use live targeting (text:, css:) to skip observe."
     )]
     async fn observe(&self, req: Parameters<ObserveRequest>) -> Result<CallToolResult, ErrorData> {
         let mut guard = self.state.lock().await;
         let state = guard.as_mut().ok_or_else(|| err(ERR_NO_BROWSER))?;
-        let viewport_only = state.config.viewport_only;
+        let mut config = state.config.clone();
         let tab = state.current_tab_mut().ok_or_else(|| err(ERR_NO_TAB))?;
 
-        tab.elements = match observe::observe(&tab.page, viewport_only).await {
-            Ok(e) => e,
+        if let Some(ref spec) = req.0.region {
+            let viewport = geometry::viewport(&tab.page).await.map_err(err)?;
+            config.region = Some(viewport.region(spec).ok_or_else(|| {
+                err(format!(
+                    "unknown region '{}': use e.g. 'top-right quadrant', 'left half', 'center' or 'x,y,width,height'",
+                    spec
+                ))
+            })?);
+        }
+
+        let snapshot = match observe::snapshot(&tab.page, &config).await {
+            Ok(s) => s,
             Err(e) => {
                 drop(guard);
                 return Err(self.check_transport_err(e).await);
             }
         };
+        tab.elements = snapshot.elements;
 
         // Apply filter
         let filtered: Vec<&InteractiveElement> = match req.0.filter.as_deref() {
@@ -614,7 +962,13 @@ impl EokaServer {
             None => filtered,
         };
 
-        let list: String = limited.iter().map(|e| format!("{}\n", e)).collect();
+        let list: String = match req.0.max_tokens {
+            Some(max_tokens) => {
+                let elements: Vec<InteractiveElement> = limited.into_iter().cloned().collect();
+                observe::budgeted_list(&elements, max_tokens, snapshot.viewport_height)
+            }
+            None => limited.iter().map(|e| format!("{}\n", e)).collect(),
+        };
         text_ok(if list.is_empty() {
             "No interactive elements found.".into()
         } else {
@@ -658,7 +1012,36 @@ impl EokaServer {
     }
 
     #[tool(
-        description = "Click an element. Target: index (0), text:Submit, placeholder:Search, role:button, css:selector, id:my-btn, or plain text. Auto-retries once on stale element."
+        description = "Set-of-Marks bundle for vision prompting: re-observes, then returns the annotated PNG and a JSON array mapping each mark number to its element (tag, role, text, value, bbox). Mark numbers are the indices other tools take."
+    )]
+    async fn som_bundle(&self) -> Result<CallToolResult, ErrorData> {
+        let mut guard = self.state.lock().await;
+        let state = guard.as_mut().ok_or_else(|| err(ERR_NO_BROWSER))?;
+        let config_viewport_only = state.config.viewport_only;
+        let tab = state.current_tab_mut().ok_or_else(|| err(ERR_NO_TAB))?;
+
+        let bundle = match observe::observe(&tab.page, config_viewport_only).await {
+            Ok(elements) => {
+                tab.elements = elements;
+                annotate::som_bundle(&tab.page, &tab.elements).await
+            }
+            Err(e) => Err(e),
+        };
+        let bundle = match bundle {
+            Ok(b) => b,
+            Err(e) => {
+                drop(guard);
+                return Err(self.check_transport_err(e).await);
+            }
+        };
+        Ok(CallToolResult::success(vec![
+            Content::image(BASE64.encode(&bundle.image), "image/png"),
+            Content::text(bundle.marks_json()),
+        ]))
+    }
+
+    #[tool(
+        description = "Click an element. Target: index (0), text:Submit, placeholder:Search, label:Email, role:button, css:selector, id:my-btn, or plain text. Auto-retries once on stale element. Reports navigation, dialogs, new elements and console errors."
     )]
     async fn click(&self, req: Parameters<TargetRequest>) -> Result<CallToolResult, ErrorData> {
         self.ensure_browser().await?;
@@ -678,6 +1061,22 @@ impl EokaServer {
 
         let resolved = resolve_target(&tab.page, &tab.elements, &req.0.target).await?;
 
+        // Scroll clear of sticky headers; refuse clicks that would hit a covering element
+        if let Some(o) = occlusion::reveal(&tab.page, &resolved.selector)
+            .await
+            .map_err(err)?
+        {
+            return Err(ErrorData::invalid_params(
+                format!(
+                    "{} is {} - dismiss it first (see detect_overlays)",
+                    resolved.desc, o
+                ),
+                None::<Value>,
+            ));
+        }
+
+        let probe = outcome::arm(&tab.page, false).await.map_err(err)?;
+
         // Try click with auto-retry on element not found
         match tab.page.click(&resolved.selector).await {
             Ok(_) => {}
@@ -699,11 +1098,12 @@ impl EokaServer {
 
         let _ = wait_for_stable(&tab.page).await;
         tab.elements.clear();
-        text_ok(format!("Clicked {}", resolved.desc))
+        let outcome = probe.finish(&tab.page).await;
+        text_ok(format!("Clicked {}\n{}", resolved.desc, outcome))
     }
 
     #[tool(
-        description = "Type text into an input. Target: index, text:Label, placeholder:Enter code, css:input, id:field. Auto-retries once on stale element. Clears existing text."
+        description = "Type text into an input. Target: index, text:Label, placeholder:Enter code, label:Email, css:input, id:field. Auto-retries once on stale element. Clears existing text. Reports what changed."
     )]
     async fn fill(&self, req: Parameters<FillRequest>) -> Result<CallToolResult, ErrorData> {
         self.ensure_browser().await?;
@@ -721,6 +1121,9 @@ impl EokaServer {
         }
 
         let resolved = resolve_target(&tab.page, &tab.elements, &req.0.target).await?;
+        mark_secret_text(&tab.page, &resolved.selector, "/text").await;
+
+        let probe = outcome::arm(&tab.page, false).await.map_err(err)?;
 
         // Try fill with auto-retry on element not found
         match tab.page.fill(&resolved.selector, &req.0.text).await {
@@ -743,11 +1146,15 @@ impl EokaServer {
 
         let _ = wait_for_stable(&tab.page).await;
         tab.elements.clear();
-        text_ok(format!("Filled {} with \"{}\"", resolved.desc, req.0.text))
+        let outcome = probe.finish(&tab.page).await;
+        text_ok(format!(
+            "Filled {} with \"{}\"\n{}",
+            resolved.desc, req.0.text, outcome
+        ))
     }
 
     #[tool(
-        description = "Select dropdown option. Target: index, text:Label, css:select, id:dropdown. Value matches option value or visible text."
+        description = "Select dropdown option. Target: index, text:Label, css:select, id:dropdown. Value matches option value or visible text. Custom dropdowns (React-Select, MUI, role=combobox) are opened and the matching role=option clicked. Reports what changed."
     )]
     async fn select(&self, req: Parameters<SelectRequest>) -> Result<CallToolResult, ErrorData> {
         let mut guard = self.state.lock().await;
@@ -763,6 +1170,27 @@ impl EokaServer {
         }
 
         let resolved = resolve_target(&tab.page, &tab.elements, &req.0.target).await?;
+        let native: bool = tab
+            .page
+            .evaluate(&format!(
+                "document.querySelector({})?.tagName === 'SELECT'",
+                serde_json::to_string(&resolved.selector).unwrap()
+            ))
+            .await
+            .map_err(err)?;
+        if !native {
+            let probe = outcome::arm(&tab.page, false).await.map_err(err)?;
+            let picked = dropdown::select_custom(&tab.page, &resolved.selector, &req.0.value)
+                .await
+                .map_err(|e| ErrorData::invalid_params(e.to_string(), None::<Value>))?;
+            wait_for_stable(&tab.page).await.map_err(err)?;
+            tab.elements.clear();
+            let outcome = probe.finish(&tab.page).await;
+            return text_ok(format!(
+                "Selected \"{}\" in {}\n{}",
+                picked, resolved.desc, outcome
+            ));
+        }
         let arg = serde_json::json!({ "sel": resolved.selector, "val": req.0.value });
         let js = format!(
             r#"(() => {{
@@ -777,6 +1205,7 @@ impl EokaServer {
             }})()"#,
             arg = serde_json::to_string(&arg).unwrap()
         );
+        let probe = outcome::arm(&tab.page, false).await.map_err(err)?;
         let selected: bool = tab.page.evaluate(&js).await.map_err(err)?;
         if !selected {
             return Err(ErrorData::invalid_params(
@@ -786,11 +1215,15 @@ impl EokaServer {
         }
         wait_for_stable(&tab.page).await.map_err(err)?;
         tab.elements.clear();
-        text_ok(format!("Selected \"{}\" in {}", req.0.value, resolved.desc))
+        let outcome = probe.finish(&tab.page).await;
+        text_ok(format!(
+            "Selected \"{}\" in {}\n{}",
+            req.0.value, resolved.desc, outcome
+        ))
     }
 
     #[tool(
-        description = "Hover over element to trigger tooltips, menus, or hover states. Target: index, text:Label, css:selector, etc."
+        description = "Hover over element to trigger tooltips, menus, or hover states, and report tooltip text. Target: index, text:Label, css:selector, etc."
     )]
     async fn hover(&self, req: Parameters<TargetRequest>) -> Result<CallToolResult, ErrorData> {
         let mut guard = self.state.lock().await;
@@ -806,14 +1239,23 @@ impl EokaServer {
         }
 
         let resolved = resolve_target(&tab.page, &tab.elements, &req.0.target).await?;
-        let cx = resolved.bbox.x + resolved.bbox.width / 2.0;
-        let cy = resolved.bbox.y + resolved.bbox.height / 2.0;
+        let (cx, cy) = resolved.bbox.center();
         tab.page
             .session()
             .dispatch_mouse_event(eoka::cdp::MouseEventType::MouseMoved, cx, cy, None, None)
             .await
             .map_err(err)?;
-        text_ok(format!("Hovered {}", resolved.desc))
+        let tips = hover::tooltips(&tab.page, &resolved.selector, HOVER_TOOLTIP_MS)
+            .await
+            .map_err(err)?;
+        if tips.is_empty() {
+            return text_ok(format!("Hovered {}", resolved.desc));
+        }
+        text_ok(format!(
+            "Hovered {}\nTooltip: {}",
+            resolved.desc,
+            tips.join("\n")
+        ))
     }
 
     #[tool(
@@ -823,10 +1265,61 @@ impl EokaServer {
         let guard = self.state.lock().await;
         let state = guard.as_ref().ok_or_else(|| err(ERR_NO_BROWSER))?;
         let tab = state.current_tab().ok_or_else(|| err(ERR_NO_TAB))?;
-        tab.page.human().press_key(&req.0.key).await.map_err(err)?;
+        deterministic::press_key(&tab.page, &req.0.key).await.map_err(err)?;
         text_ok(format!("Pressed {}", req.0.key))
     }
 
+    #[tool(
+        description = "Click at viewport coordinates, for canvas apps (maps, games, drawing tools) or when working from a screenshot. Reports what changed."
+    )]
+    async fn click_at(&self, req: Parameters<PointRequest>) -> Result<CallToolResult, ErrorData> {
+        let mut guard = self.state.lock().await;
+        let state = guard.as_mut().ok_or_else(|| err(ERR_NO_BROWSER))?;
+        let tab = state.current_tab_mut().ok_or_else(|| err(ERR_NO_TAB))?;
+        let probe = outcome::arm(&tab.page, false).await.map_err(err)?;
+        if let Err(e) = input::click_at(&tab.page, req.0.x, req.0.y).await {
+            drop(guard);
+            return Err(self.check_transport_err(e).await);
+        }
+        let _ = wait_for_stable(&tab.page).await;
+        tab.elements.clear();
+        let outcome = probe.finish(&tab.page).await;
+        text_ok(format!(
+            "Clicked at ({}, {})\n{}",
+            req.0.x, req.0.y, outcome
+        ))
+    }
+
+    #[tool(
+        description = "Move the mouse to viewport coordinates (hover, drag previews on canvas)."
+    )]
+    async fn move_mouse(&self, req: Parameters<PointRequest>) -> Result<CallToolResult, ErrorData> {
+        let guard = self.state.lock().await;
+        let state = guard.as_ref().ok_or_else(|| err(ERR_NO_BROWSER))?;
+        let tab = state.current_tab().ok_or_else(|| err(ERR_NO_TAB))?;
+        input::move_mouse(&tab.page, req.0.x, req.0.y)
+            .await
+            .map_err(err)?;
+        text_ok(format!("Moved mouse to ({}, {})", req.0.x, req.0.y))
+    }
+
+    #[tool(
+        description = "Type text into whatever has focus, one key event per character. Works on canvas apps that listen for keys; use fill for form inputs."
+    )]
+    async fn type_text(
+        &self,
+        req: Parameters<TypeTextRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let guard = self.state.lock().await;
+        let state = guard.as_ref().ok_or_else(|| err(ERR_NO_BROWSER))?;
+        let tab = state.current_tab().ok_or_else(|| err(ERR_NO_TAB))?;
+        mark_secret_text(&tab.page, ":focus", "/text").await;
+        input::type_text(&tab.page, &req.0.text)
+            .await
+            .map_err(err)?;
+        text_ok(format!("Typed \"{}\"", req.0.text))
+    }
+
     #[tool(
         description = "Execute multiple actions in sequence. Reduces round trips. Actions: click, fill, type_key. Uses live targeting."
     )]
@@ -864,6 +1357,8 @@ impl EokaServer {
                         )
                     })?;
                     let resolved = resolve_target(&tab.page, &tab.elements, target).await?;
+                    let pointer = format!("/actions/{}/text", i);
+                    mark_secret_text(&tab.page, &resolved.selector, &pointer).await;
                     tab.page.fill(&resolved.selector, text).await.map_err(err)?;
                     format!("fill {} with \"{}\"", resolved.desc, text)
                 }
@@ -874,7 +1369,7 @@ impl EokaServer {
                             None::<Value>,
                         )
                     })?;
-                    tab.page.human().press_key(key).await.map_err(err)?;
+                    deterministic::press_key(&tab.page, key).await.map_err(err)?;
                     format!("press {}", key)
                 }
                 other => {
@@ -998,6 +1493,124 @@ impl EokaServer {
         text_ok(json_str)
     }
 
+    #[tool(
+        description = "Run JavaScript on the current page and each following page (follows rel=next, 'Next', '»' controls) up to max_pages. Returns one JSON result per page and how many pages were visited."
+    )]
+    async fn paginate_extract(
+        &self,
+        req: Parameters<PaginateRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut guard = self.state.lock().await;
+        let state = guard.as_mut().ok_or_else(|| err(ERR_NO_BROWSER))?;
+        let tab = state.current_tab_mut().ok_or_else(|| err(ERR_NO_TAB))?;
+        let max_pages = req.0.max_pages.unwrap_or(5).max(1);
+        let escaped_js = serde_json::to_string(&req.0.js).map_err(err)?;
+        let js = format!("JSON.stringify(eval({}))", escaped_js);
+
+        let mut out = String::new();
+        let mut pages = 0;
+        let mut exhausted = false;
+        while pages < max_pages {
+            let json: String = tab.page.evaluate(&js).await.map_err(err)?;
+            pages += 1;
+            out.push_str(&format!("Page {}: {}\n", pages, json));
+            if pages < max_pages {
+                tab.elements.clear(); // DOM will change
+                if !paginate::next_page(&tab.page).await.map_err(err)? {
+                    exhausted = true;
+                    break;
+                }
+            }
+        }
+        let reason = if exhausted {
+            "no more pages"
+        } else {
+            "reached max_pages"
+        };
+        out.push_str(&format!("Visited {} pages ({})", pages, reason));
+        text_ok(out)
+    }
+
+    #[tool(
+        description = "Run an eoka-runner config (file path or inline YAML, with params) in the current tab: navigates to its target, runs its actions once and checks its success conditions. Returns the RunResult as JSON. Use for deterministic sub-flows like login or cookie dismissal. Launches browser if needed."
+    )]
+    async fn run_runner_config(
+        &self,
+        req: Parameters<RunRunnerConfigRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut params = eoka_runner::Params::new();
+        for (key, value) in req.0.params.unwrap_or_default() {
+            params = params.set(key, value);
+        }
+        let (config, base_path) = match (req.0.path, req.0.yaml) {
+            (Some(path), None) => {
+                let config = eoka_runner::Config::load_with_params(&path, &params).map_err(err)?;
+                let base = std::path::Path::new(&path)
+                    .parent()
+                    .map(|p| p.to_path_buf())
+                    .unwrap_or_default();
+                (config, base)
+            }
+            (None, Some(yaml)) => (
+                eoka_runner::Config::parse_with_params(&yaml, &params).map_err(err)?,
+                std::path::PathBuf::from("."),
+            ),
+            _ => {
+                return Err(ErrorData::invalid_params(
+                    "Give exactly one of path or yaml",
+                    None::<Value>,
+                ))
+            }
+        };
+
+        self.ensure_browser().await?;
+        let mut guard = self.state.lock().await;
+        let state = guard.as_mut().unwrap();
+        if state.current_tab().is_none() {
+            if let Err(e) = state.ensure_tab("about:blank").await {
+                drop(guard);
+                return Err(self.check_transport_err(e).await);
+            }
+        }
+        let tab = state.current_tab_mut().ok_or_else(|| err(ERR_NO_TAB))?;
+        tab.elements.clear(); // DOM will change
+
+        let result = eoka_runner::Runner::run_on_page(&tab.page, &config, &base_path)
+            .await
+            .map_err(err)?;
+        text_ok(serde_json::to_string_pretty(&result).map_err(err)?)
+    }
+
+    #[tool(
+        description = "Log in: open the URL, fill username and password (also when the password is on a second page), fill a TOTP or emailed code if asked, submit, and wait for the success URL or text. Launches browser if needed."
+    )]
+    async fn login(&self, req: Parameters<LoginRequest>) -> Result<CallToolResult, ErrorData> {
+        let spec = login_spec(req.0)?;
+        self.ensure_browser().await?;
+        let mut guard = self.state.lock().await;
+        let state = guard.as_mut().unwrap();
+
+        let tab = match state.ensure_tab(&spec.url).await {
+            Ok(t) => t,
+            Err(e) => {
+                drop(guard);
+                return Err(self.check_transport_err(e).await);
+            }
+        };
+        wait_for_stable(&tab.page).await.map_err(err)?;
+        tab.elements.clear(); // DOM will change
+        let logged_in = flows::submit_login(&tab.page, &spec).await.map_err(err)?;
+
+        let mut out = format!("Logged in, now at {}", logged_in.url);
+        if logged_in.two_page {
+            out.push_str("\nPassword was on a second page");
+        }
+        if logged_in.totp {
+            out.push_str("\nFilled a one-time code");
+        }
+        text_ok(out)
+    }
+
     #[tool(
         description = "Execute JavaScript without expecting a return value. Use for side effects like clicking elements via JS."
     )]
@@ -1010,6 +1623,21 @@ impl EokaServer {
         text_ok("Executed successfully")
     }
 
+    #[tool(
+        description = "Send a raw Chrome DevTools protocol command to the current tab and return its JSON result. Escape hatch for anything the other tools don't cover."
+    )]
+    async fn cdp(&self, req: Parameters<CdpRequest>) -> Result<CallToolResult, ErrorData> {
+        let guard = self.state.lock().await;
+        let state = guard.as_ref().ok_or_else(|| err(ERR_NO_BROWSER))?;
+        let tab = state.current_tab().ok_or_else(|| err(ERR_NO_TAB))?;
+        let params = req.0.params.unwrap_or(Value::Null);
+        let result: Value = eoka_agent::AgentPage::new(&tab.page)
+            .cdp_call(&req.0.method, params)
+            .await
+            .map_err(err)?;
+        text_ok(serde_json::to_string_pretty(&result).map_err(err)?)
+    }
+
     #[tool(
         description = "Get all visible text on the page. Useful for reading content without elements."
     )]
@@ -1039,6 +1667,46 @@ impl EokaServer {
         }
     }
 
+    #[tool(
+        description = "Cheap first look at a page: counts of links, buttons, inputs, forms, iframes, images and canvases, page and viewport size, text length, detected frameworks (React, Next.js, Vue, Angular...) and a suggested next tool (observe, page_text or screenshot)."
+    )]
+    async fn page_summary(&self) -> Result<CallToolResult, ErrorData> {
+        self.ensure_browser().await?;
+        let guard = self.state.lock().await;
+        let state = guard.as_ref().ok_or_else(|| err(ERR_NO_BROWSER))?;
+        let tab = state.current_tab().ok_or_else(|| err(ERR_NO_TAB))?;
+        match summary::page_summary(&tab.page).await {
+            Ok(summary) => text_ok(summary.to_string()),
+            Err(e) => {
+                drop(guard);
+                Err(self.check_transport_err(e).await)
+            }
+        }
+    }
+
+    #[tool(
+        description = "Audit trail of every tool call this server ran: timestamp, tool, arguments, outcome, URL before and after. Returns JSON, oldest first."
+    )]
+    async fn action_log(&self) -> Result<CallToolResult, ErrorData> {
+        text_ok(self.log.lock().await.to_json())
+    }
+
+    #[tool(
+        description = "Detect overlays covering the page (cookie banners, modals, chat widgets, interstitials), topmost first, with dismiss buttons as css: targets labelled accept/reject/close."
+    )]
+    async fn detect_overlays(&self) -> Result<CallToolResult, ErrorData> {
+        let guard = self.state.lock().await;
+        let state = guard.as_ref().ok_or_else(|| err(ERR_NO_BROWSER))?;
+        let tab = state.current_tab().ok_or_else(|| err(ERR_NO_TAB))?;
+
+        let overlays = overlay::detect_overlays(&tab.page).await.map_err(err)?;
+        text_ok(if overlays.is_empty() {
+            "No overlays detected.".into()
+        } else {
+            overlay::overlay_list(&overlays)
+        })
+    }
+
     #[tool(description = "Go back in browser history.")]
     async fn back(&self) -> Result<CallToolResult, ErrorData> {
         let mut guard = self.state.lock().await;
@@ -1102,6 +1770,50 @@ impl EokaServer {
         ))
     }
 
+    #[tool(
+        description = "Set URL query params through the SPA router (no page reload) so filter/search UIs react. Keeps other params; an empty value removes one."
+    )]
+    async fn spa_set_query(
+        &self,
+        req: Parameters<SpaSetQueryRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut guard = self.state.lock().await;
+        let state = guard.as_mut().ok_or_else(|| err(ERR_NO_BROWSER))?;
+        let tab = state.current_tab_mut().ok_or_else(|| err(ERR_NO_TAB))?;
+
+        let info = spa::detect_router(&tab.page).await.map_err(err)?;
+        let new_path = spa::spa_set_query(&tab.page, &info.router_type, &req.0.params)
+            .await
+            .map_err(err)?;
+
+        tab.elements.clear(); // DOM will change
+        text_ok(format!(
+            "Navigated to {} via {}",
+            new_path, info.router_type
+        ))
+    }
+
+    #[tool(description = "Set the URL hash fragment through the SPA router (no page reload).")]
+    async fn spa_set_hash(
+        &self,
+        req: Parameters<SpaSetHashRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut guard = self.state.lock().await;
+        let state = guard.as_mut().ok_or_else(|| err(ERR_NO_BROWSER))?;
+        let tab = state.current_tab_mut().ok_or_else(|| err(ERR_NO_TAB))?;
+
+        let info = spa::detect_router(&tab.page).await.map_err(err)?;
+        let new_path = spa::spa_set_hash(&tab.page, &info.router_type, &req.0.fragment)
+            .await
+            .map_err(err)?;
+
+        tab.elements.clear(); // DOM will change
+        text_ok(format!(
+            "Navigated to {} via {}",
+            new_path, info.router_type
+        ))
+    }
+
     #[tool(
         description = "Navigate browser history by delta steps. Use delta=-1 for back, delta=1 for forward, delta=-2 for back twice, etc. Works with both SPAs and regular pages."
     )]
@@ -1236,6 +1948,113 @@ impl EokaServer {
         text_ok("Captcha token injected")
     }
 
+    // =========================================================================
+    // Email
+    // =========================================================================
+
+    #[tool(
+        description = "Wait for an email matching the criteria (IMAP account from EOKA_IMAP_* env vars). Only emails arriving after the call starts count unless since_minutes is given. Returns sender, subject and date; use email_extract_code / email_extract_link next."
+    )]
+    async fn email_wait(
+        &self,
+        req: Parameters<EmailWaitRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let call_start = chrono::Utc::now();
+        let config = imap_config_from_env()?;
+        let criteria = email_criteria(&req.0, call_start);
+        let timeout = chrono::Duration::milliseconds(req.0.timeout_ms.unwrap_or(120_000) as i64);
+        let options = WaitOptions::new(timeout, chrono::Duration::seconds(2));
+
+        let mut client = AsyncImapClient::connect(&config).await.map_err(err)?;
+        let msg = client.wait_for_message(&criteria, &options).await;
+        let _ = client.logout().await;
+        let msg = msg.map_err(|e| match e {
+            eoka_email::Error::Timeout => err("No matching email arrived before the timeout"),
+            e => err(e),
+        })?;
+
+        let out = format!(
+            "From: {}\nSubject: {}\nDate: {}",
+            msg.from.as_deref().unwrap_or("-"),
+            msg.subject.as_deref().unwrap_or("-"),
+            msg.date.as_deref().unwrap_or("-"),
+        );
+        *self.email.lock().await = Some(msg);
+        text_ok(out)
+    }
+
+    #[tool(description = "Extract a verification code from the last email returned by email_wait.")]
+    async fn email_extract_code(
+        &self,
+        req: Parameters<EmailCodeRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let guard = self.email.lock().await;
+        let msg = guard.as_ref().ok_or_else(|| err(ERR_NO_EMAIL))?;
+
+        let code = match req.0.pattern {
+            Some(ref pattern) => {
+                let re = regex::Regex::new(pattern).map_err(|e| {
+                    ErrorData::invalid_params(format!("Invalid pattern: {}", e), None::<Value>)
+                })?;
+                eoka_email::extract_code(msg, &re)
+            }
+            None => eoka_email::otp::best(msg),
+        };
+        text_ok(code.ok_or_else(|| err("No code found in the email"))?)
+    }
+
+    #[tool(
+        description = "Extract the best link (e.g. verify/confirm) from the last email returned by email_wait."
+    )]
+    async fn email_extract_link(
+        &self,
+        req: Parameters<EmailLinkRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let guard = self.email.lock().await;
+        let msg = guard.as_ref().ok_or_else(|| err(ERR_NO_EMAIL))?;
+
+        let filter = LinkFilter {
+            allow_domains: req.0.allow_domains,
+        };
+        let link = eoka_email::extract_first_link(msg, &filter);
+        text_ok(link.ok_or_else(|| err("No link found in the email"))?)
+    }
+
+    #[tool(
+        description = "Open the best link from the last email returned by email_wait in the current tab. Launches browser if needed. Returns page title."
+    )]
+    async fn email_open_link_in_browser(
+        &self,
+        req: Parameters<EmailLinkRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let link = {
+            let guard = self.email.lock().await;
+            let msg = guard.as_ref().ok_or_else(|| err(ERR_NO_EMAIL))?;
+            let filter = LinkFilter {
+                allow_domains: req.0.allow_domains,
+            };
+            eoka_email::extract_first_link(msg, &filter)
+                .ok_or_else(|| err("No link found in the email"))?
+        };
+
+        self.ensure_browser().await?;
+        let mut guard = self.state.lock().await;
+        let state = guard.as_mut().unwrap();
+
+        let tab = match state.ensure_tab(&link).await {
+            Ok(t) => t,
+            Err(e) => {
+                drop(guard);
+                return Err(self.check_transport_err(e).await);
+            }
+        };
+
+        wait_for_stable(&tab.page).await.map_err(err)?;
+        let url = tab.page.url().await.map_err(err)?;
+        let title = tab.page.title().await.map_err(err)?;
+        text_ok(format!("Opened email link: {}\nTitle: {}", url, title))
+    }
+
     #[tool(description = "Close the browser. Call when done to free resources.")]
     async fn close(&self) -> Result<CallToolResult, ErrorData> {
         let mut guard = self.state.lock().await;
@@ -1256,8 +2075,98 @@ fn element_list(elements: &[InteractiveElement]) -> String {
     out
 }
 
-#[tool_handler]
+tokio::task_local! {
+    // JSON pointers into the running call's arguments to keep out of the
+    // action log, beyond `action_log::SECRET_ARGS`.
+    static SECRET_TEXT: RefCell<Vec<String>>;
+}
+
+/// Keep the argument at `pointer` out of the log if it is text for a
+/// password or one-time-code field. Unknown fields count as secret.
+async fn mark_secret_text(page: &Page, selector: &str, pointer: &str) {
+    if !matches!(action_log::is_secret_field(page, selector).await, Ok(false)) {
+        let _ = SECRET_TEXT.try_with(|s| s.borrow_mut().push(pointer.to_string()));
+    }
+}
+
+/// A tool call's arguments as logged: secret arguments and the `secret`
+/// pointers replaced with [`action_log::REDACTED`].
+fn logged_args(args: Option<&serde_json::Map<String, Value>>, secret: &[String]) -> Option<String> {
+    let mut args = Value::Object(args.filter(|args| !args.is_empty())?.clone());
+    action_log::redact_args(&mut args);
+    for pointer in secret {
+        if let Some(value) = args.pointer_mut(pointer) {
+            *value = action_log::REDACTED.into();
+        }
+    }
+    Some(args.to_string())
+}
+
+/// Append the last logged tool calls to an error, so the caller sees what
+/// led up to it; the records also go in `data.recent_actions` as JSON.
+fn with_recent_actions(mut e: ErrorData, recent: &[ActionRecord]) -> ErrorData {
+    if recent.is_empty() {
+        return e;
+    }
+    let lines: Vec<String> = recent.iter().map(|r| r.to_string()).collect();
+    e.message = format!("{}\n\nRecent actions:\n{}", e.message, lines.join("\n")).into();
+    e.data = Some(serde_json::json!({ "recent_actions": recent }));
+    e
+}
+
 impl ServerHandler for EokaServer {
+    /// Runs the tool and appends it to the action log; errors carry the
+    /// last few logged calls. A call the client cancels stops right away.
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let args = request.arguments.clone();
+        let mut record = ActionRecord::begin(request.name.clone(), None, self.current_url().await);
+        // Dropping the tool's future on notifications/cancelled stops its waits
+        let cancelled = context.ct.clone();
+        let tcc = ToolCallContext::new(self, request, context);
+        let (result, secret) = SECRET_TEXT
+            .scope(RefCell::default(), async {
+                let result = tokio::select! {
+                    result = self.tool_router.call(tcc) => result,
+                    _ = cancelled.cancelled() => Err(ErrorData::internal_error(
+                        "cancelled by the client",
+                        None::<Value>,
+                    )),
+                };
+                (result, SECRET_TEXT.with(|s| s.take()))
+            })
+            .await;
+        record.target = logged_args(args.as_ref(), &secret);
+        let outcome = match result {
+            Ok(_) => Ok(String::new()),
+            Err(ref e) => Err(e.message.to_string()),
+        };
+        let record = record.finish(self.current_url().await, outcome);
+
+        let mut log = self.log.lock().await;
+        log.push(record);
+        result.map_err(|e| with_recent_actions(e, log.last(ERROR_LOG_ACTIONS)))
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        let mut tools = self.tool_router.list_all();
+        for tool in &mut tools {
+            self.hints.apply(tool);
+        }
+        Ok(ListToolsResult {
+            tools,
+            meta: None,
+            next_cursor: None,
+        })
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::LATEST,
@@ -1272,12 +2181,15 @@ impl ServerHandler for EokaServer {
             instructions: Some(
                 "Browser automation.\n\n\
                  TARGETING: Index (0) uses cache. Everything else is LIVE (resolved at action time):\n\
-                 Submit, text:Submit, placeholder:code, css:button, id:btn, role:button\n\n\
+                 Submit, text:Submit, placeholder:code, label:Email, css:button, id:btn, role:button\n\n\
                  OBSERVE: filter='inputs'|'buttons', max=N\n\
                  BATCH: batch([{action:'fill',target:'placeholder:code',text:'X'},{action:'click',target:'Submit'}])\n\
                  AUTO-RETRY: click/fill retry once on stale\n\
-                 SPA: spa_info, spa_navigate, history_go\n\
-                 Tabs: list_tabs, new_tab, switch_tab, close_tab"
+                 COORDINATES: click_at, move_mouse, type_text for canvas apps with no DOM targets\n\
+                 PAGINATION: paginate_extract(js, max_pages) runs js on each result page\n\
+                 SPA: spa_info, spa_navigate, spa_set_query, spa_set_hash, history_go\n\
+                 Tabs: list_tabs, new_tab, switch_tab, close_tab\n\
+                 EMAIL: email_wait, then email_extract_code, email_extract_link or email_open_link_in_browser"
                     .into(),
             ),
         }
@@ -1287,8 +2199,54 @@ impl ServerHandler for EokaServer {
 pub async fn run_server() -> anyhow::Result<()> {
     use rmcp::ServiceExt;
 
-    let server = EokaServer::new();
+    let server = EokaServer::new().with_tool_hints(ToolHints::from_env()?);
     let service = server.serve(rmcp::transport::stdio()).await?;
     service.waiting().await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logged_login_has_no_password() {
+        let args = serde_json::json!({
+            "url": "https://example.com/login",
+            "username": "ada@example.com",
+            "password": "hunter2",
+            "totp_secret": "JBSWY3DPEHPK3PXP"
+        });
+        let logged = logged_args(args.as_object(), &[]).unwrap();
+        assert!(!logged.contains("hunter2"));
+        assert!(!logged.contains("JBSWY3DPEHPK3PXP"));
+        assert!(logged.contains("ada@example.com"));
+
+        let record = ActionRecord::begin("login", Some(logged), String::new())
+            .finish(String::new(), Err("Login failed".into()));
+        let e = with_recent_actions(err("Login failed"), &[record]);
+        assert!(!e.message.contains("hunter2"));
+        assert!(!e.data.unwrap().to_string().contains("hunter2"));
+
+        let batch = serde_json::json!({
+            "actions": [
+                {"action": "fill", "target": "label:Email", "text": "ada@example.com"},
+                {"action": "fill", "target": "label:Password", "text": "hunter2"}
+            ]
+        });
+        let logged = logged_args(batch.as_object(), &["/actions/1/text".into()]).unwrap();
+        assert!(!logged.contains("hunter2"));
+        assert!(logged.contains("ada@example.com"));
+    }
+
+    #[test]
+    fn every_tool_has_hints() {
+        for tool in EokaServer::tool_router().list_all() {
+            assert!(
+                crate::tool_hints::known(&tool.name),
+                "{} is missing from tool_hints",
+                tool.name
+            );
+        }
+    }
+}
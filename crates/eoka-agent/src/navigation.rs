@@ -0,0 +1,207 @@
+//! Navigation watchdog — catches Chrome error pages, 5xx main documents and
+//! `about:blank` dead-ends after `goto`, retries with backoff, and reports
+//! what went wrong as a [`NavFailureKind`].
+
+use eoka::{Page, Result};
+use serde::Deserialize;
+use std::fmt;
+
+use crate::{AgentError, AgentResult};
+
+/// Why a navigation failed, carried by [`AgentError::NavigationFailed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NavFailureKind {
+    /// DNS lookup failed (`ERR_NAME_NOT_RESOLVED`)
+    NameNotResolved,
+    /// The proxy refused or dropped the connection
+    ProxyFailed,
+    /// Connection refused, reset, timed out or otherwise lost
+    ConnectionFailed,
+    /// The main document came back with this 5xx status
+    ServerError(u16),
+    /// The page ended up on `about:blank`
+    Blank,
+    /// Any other Chrome network error, e.g. `ERR_CERT_DATE_INVALID`
+    Other(String),
+}
+
+impl NavFailureKind {
+    /// Classify a Chrome net error code (`net::ERR_...` or `ERR_...`).
+    pub fn from_net_error(code: &str) -> Self {
+        let code = code.trim_start_matches("net::");
+        match code {
+            "ERR_NAME_NOT_RESOLVED" => Self::NameNotResolved,
+            "ERR_TUNNEL_CONNECTION_FAILED" => Self::ProxyFailed,
+            c if c.starts_with("ERR_PROXY_") || c.starts_with("ERR_SOCKS_") => Self::ProxyFailed,
+            "ERR_TIMED_OUT"
+            | "ERR_ADDRESS_UNREACHABLE"
+            | "ERR_INTERNET_DISCONNECTED"
+            | "ERR_EMPTY_RESPONSE"
+            | "ERR_NETWORK_CHANGED" => Self::ConnectionFailed,
+            c if c.starts_with("ERR_CONNECTION_") => Self::ConnectionFailed,
+            c => Self::Other(c.to_string()),
+        }
+    }
+
+    /// Worth retrying: the same request may well succeed a moment later.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::ProxyFailed | Self::ConnectionFailed | Self::ServerError(_) | Self::Blank
+        )
+    }
+}
+
+impl fmt::Display for NavFailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NameNotResolved => write!(f, "ERR_NAME_NOT_RESOLVED"),
+            Self::ProxyFailed => write!(f, "ERR_PROXY_CONNECTION_FAILED"),
+            Self::ConnectionFailed => write!(f, "ERR_CONNECTION_FAILED"),
+            Self::ServerError(status) => write!(f, "HTTP {}", status),
+            Self::Blank => write!(f, "blank page"),
+            Self::Other(code) => write!(f, "{}", code),
+        }
+    }
+}
+
+/// Watchdog settings. Default: 2 retries, 500ms backoff doubling each time.
+#[derive(Debug, Clone)]
+pub struct NavWatchdog {
+    /// Extra attempts after the first failure (retryable kinds only)
+    pub retries: u32,
+    /// Delay before the first retry; doubles on each further retry
+    pub backoff_ms: u64,
+}
+
+impl Default for NavWatchdog {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            backoff_ms: 500,
+        }
+    }
+}
+
+impl NavWatchdog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn backoff_ms(mut self, backoff_ms: u64) -> Self {
+        self.backoff_ms = backoff_ms;
+        self
+    }
+}
+
+/// JavaScript that reports the URL, main document status and, on a Chrome
+/// error page, the net error code.
+const CHECK_JS: &str = r#"
+(() => {
+  const nav = performance.getEntriesByType('navigation')[0];
+  const code = location.protocol === 'chrome-error:'
+    ? ((document.body ? document.body.innerText : '').match(/ERR_[A-Z_]+/) || ['ERR_FAILED'])[0]
+    : null;
+  return JSON.stringify({ url: location.href, status: (nav && nav.responseStatus) || 0, error: code });
+})()
+"#;
+
+#[derive(Deserialize)]
+struct PageCheck {
+    url: String,
+    status: u16,
+    error: Option<String>,
+}
+
+/// Inspect the page that a navigation to `requested` landed on.
+pub async fn check(page: &Page, requested: &str) -> Result<Option<NavFailureKind>> {
+    let json: String = page.evaluate(CHECK_JS).await?;
+    let check: PageCheck = serde_json::from_str(&json)
+        .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse page check: {}", e)))?;
+    if let Some(code) = check.error {
+        return Ok(Some(NavFailureKind::from_net_error(&code)));
+    }
+    if check.status >= 500 {
+        return Ok(Some(NavFailureKind::ServerError(check.status)));
+    }
+    if check.url == "about:blank" && requested != "about:blank" {
+        return Ok(Some(NavFailureKind::Blank));
+    }
+    Ok(None)
+}
+
+/// Navigate, wait for the load to settle and check the result, retrying
+/// retryable failures with exponential backoff.
+pub async fn goto(page: &Page, url: &str, watchdog: &NavWatchdog) -> AgentResult<()> {
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let failure = match page.goto(url).await {
+            Ok(()) => {
+                let _ = page.wait_for_network_idle(200, 2000).await;
+                check(page, url).await?
+            }
+            // Chrome's errorText, e.g. `net::ERR_NAME_NOT_RESOLVED`
+            Err(eoka::Error::Navigation(code)) => Some(NavFailureKind::from_net_error(&code)),
+            Err(e) => return Err(e.into()),
+        };
+        let Some(kind) = failure else {
+            return Ok(());
+        };
+        if attempts > watchdog.retries || !kind.is_retryable() {
+            return Err(AgentError::NavigationFailed {
+                kind,
+                url: url.to_string(),
+                attempts,
+            });
+        }
+        let delay = watchdog.backoff_ms << (attempts - 1).min(16);
+        eprintln!(
+            "[eoka-agent] {} loading {}, retrying in {}ms",
+            kind, url, delay
+        );
+        page.wait(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_navigation_errors() {
+        assert_eq!(
+            NavFailureKind::from_net_error("net::ERR_NAME_NOT_RESOLVED"),
+            NavFailureKind::NameNotResolved
+        );
+        assert_eq!(
+            NavFailureKind::from_net_error("ERR_TUNNEL_CONNECTION_FAILED"),
+            NavFailureKind::ProxyFailed
+        );
+        assert_eq!(
+            NavFailureKind::from_net_error("net::ERR_CONNECTION_REFUSED"),
+            NavFailureKind::ConnectionFailed
+        );
+        assert_eq!(
+            NavFailureKind::from_net_error("ERR_CERT_DATE_INVALID"),
+            NavFailureKind::Other("ERR_CERT_DATE_INVALID".into())
+        );
+        assert!(NavFailureKind::ServerError(502).is_retryable());
+        assert!(!NavFailureKind::NameNotResolved.is_retryable());
+
+        let err = AgentError::NavigationFailed {
+            kind: NavFailureKind::ServerError(503),
+            url: "https://example.com/".into(),
+            attempts: 3,
+        };
+        assert_eq!(
+            err.to_string(),
+            "HTTP 503 loading https://example.com/ (3 attempts)"
+        );
+    }
+}
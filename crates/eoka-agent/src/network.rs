@@ -0,0 +1,492 @@
+//! Network capture — requests the page sends with a body (form submits,
+//! `fetch`, `XMLHttpRequest`), recorded in the page for checking that a fill
+//! and submit actually sent what was intended, and the text responses to
+//! `fetch` and XHR, for values that only exist there (CSRF tokens, ids).
+//! Auth headers the page sends are kept too, unredacted, so a browser login
+//! can bootstrap a plain HTTP client.
+//!
+//! eoka doesn't stream CDP network events, so capture runs as a page script,
+//! [`eoka_runner::capture`], shared with the runner's traffic checks: it
+//! wraps `fetch`, `XMLHttpRequest` and form submission, and keeps entries in
+//! `sessionStorage` so a POST survives the navigation it triggers. Entries
+//! live with the origin that sent them. The wrappers are visible to the page;
+//! use this for debugging, not against bot detection.
+
+use std::collections::BTreeMap;
+
+use eoka::{Page, Result};
+use serde::{Deserialize, Serialize};
+
+/// Captured entries of the current origin, oldest first.
+const ENTRIES_JS: &str = r#"
+(() => {
+  try { return JSON.parse(sessionStorage.getItem('__eokaNet') || '[]'); } catch (e) { return []; }
+})()
+"#;
+
+/// Captured responses of the current origin plus the document itself.
+const RESPONSES_JS: &str = r#"
+(() => {
+  let all = [];
+  try { all = JSON.parse(sessionStorage.getItem('__eokaNetResponses') || '[]'); } catch (e) {}
+  const html = document.documentElement ? document.documentElement.outerHTML : '';
+  all.push({ kind: 'document', url: location.href, status: null, contentType: document.contentType, body: html, size: html.length, truncated: false });
+  return all;
+})()
+"#;
+
+/// Auth headers of the current origin, by name.
+const AUTH_JS: &str = r#"
+(() => {
+  try { return JSON.parse(sessionStorage.getItem('__eokaNetAuth') || '{}'); } catch (e) { return {}; }
+})()
+"#;
+
+/// Stops recording in the current document; the runner's traffic log, if
+/// any, keeps going.
+pub(crate) const OFF_JS: &str =
+    "window.__eokaNet && Object.assign(window.__eokaNet.cfg, { requests: false, responses: false })";
+
+/// What to record, for [`crate::Session::set_network_capture`].
+#[derive(Debug, Clone)]
+pub struct NetworkCapture {
+    /// Longer bodies are cut to this many bytes and lose their decoded
+    /// fields.
+    pub max_body_bytes: usize,
+    /// Header names (lowercase) recorded as `[redacted]`.
+    pub redact_headers: Vec<String>,
+    /// Also record text responses (JSON, HTML, XML, plain text) to `fetch`
+    /// and XHR, for [`crate::Session::find_in_responses`].
+    pub responses: bool,
+    /// Entries kept per origin, of requests and of responses each; the
+    /// oldest are dropped first.
+    pub max_entries: usize,
+    /// Case-insensitive patterns for request headers that
+    /// [`crate::Session::capture_auth`] returns, unredacted.
+    pub auth_headers: Vec<String>,
+    /// Case-insensitive patterns for cookie names
+    /// [`crate::Session::capture_auth`] returns.
+    pub auth_cookies: Vec<String>,
+}
+
+impl Default for NetworkCapture {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 64 * 1024,
+            redact_headers: [
+                "authorization",
+                "cookie",
+                "proxy-authorization",
+                "x-csrf-token",
+                "x-xsrf-token",
+            ]
+            .map(String::from)
+            .to_vec(),
+            responses: true,
+            max_entries: 50,
+            auth_headers: [
+                "^authorization$",
+                "^x-.*(token|auth|key|session)",
+                "^api-?key$",
+            ]
+            .map(String::from)
+            .to_vec(),
+            auth_cookies: ["sess", "sid", "auth", "token", "jwt", "login"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+impl NetworkCapture {
+    /// The capture script, configured.
+    pub(crate) fn script(&self) -> String {
+        let settings = serde_json::json!({
+            "requests": true,
+            "maxBodyBytes": self.max_body_bytes,
+            "redactHeaders": self
+                .redact_headers
+                .iter()
+                .map(|h| h.to_ascii_lowercase())
+                .collect::<Vec<_>>(),
+            "responses": self.responses,
+            "maxEntries": self.max_entries,
+            "authHeaders": self.auth_headers,
+        });
+        eoka_runner::capture::script(&settings)
+    }
+}
+
+/// A request the page sent with a body.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SentRequest {
+    /// `form`, `fetch` or `xhr`
+    pub kind: String,
+    pub method: String,
+    pub url: String,
+    /// The page that sent it.
+    pub page: String,
+    /// Request headers the page set, lowercase, redacted.
+    pub headers: BTreeMap<String, String>,
+    pub content_type: Option<String>,
+    /// Body as text, cut at the size cap; binary bodies as `[binary N bytes]`.
+    pub body: Option<String>,
+    /// Body size in bytes before the cap.
+    pub size: usize,
+    pub truncated: bool,
+    /// Fields of a form, `FormData` or `URLSearchParams` body.
+    #[serde(default)]
+    fields: Option<Vec<(String, String)>>,
+}
+
+impl SentRequest {
+    /// The body as form fields: form and `FormData` entries, an urlencoded
+    /// body, or the top-level keys of a JSON object. `None` for other bodies.
+    pub fn form_fields(&self) -> Option<Vec<(String, String)>> {
+        if let Some(ref fields) = self.fields {
+            return Some(fields.clone());
+        }
+        if self.truncated {
+            return None;
+        }
+        let body = self.body.as_deref()?;
+        let content_type = self.content_type.as_deref().unwrap_or_default();
+        if content_type.contains("x-www-form-urlencoded") {
+            let mut url = reqwest::Url::parse("http://form.invalid/").ok()?;
+            url.set_query(Some(body));
+            return Some(url.query_pairs().into_owned().collect());
+        }
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let fields = json.as_object()?.iter().map(|(k, v)| {
+            let value = match v {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (k.clone(), value)
+        });
+        Some(fields.collect())
+    }
+}
+
+/// A decoded form submission, from [`crate::Session::last_form_post`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FormPost {
+    /// `form`, `fetch` or `xhr`
+    pub kind: String,
+    pub method: String,
+    pub url: String,
+    pub fields: Vec<(String, String)>,
+}
+
+/// A text response, from [`ResponseMatch`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CapturedResponse {
+    kind: String,
+    url: String,
+    status: Option<u16>,
+    content_type: Option<String>,
+    body: String,
+}
+
+/// Where [`crate::Session::find_in_responses`] found the pattern.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResponseMatch {
+    /// `fetch`, `xhr`, or `document` for the current page's HTML
+    pub kind: String,
+    /// URL of the response.
+    pub url: String,
+    pub status: Option<u16>,
+    pub content_type: Option<String>,
+    /// The whole match.
+    pub matched: String,
+    /// Capture groups, `None` where a group didn't participate.
+    pub groups: Vec<Option<String>>,
+    /// The match with up to [`CONTEXT_CHARS`] on each side.
+    pub context: String,
+}
+
+/// Characters of surrounding text kept on each side of a match.
+pub const CONTEXT_CHARS: usize = 40;
+
+/// Every match of `re` in `responses`, newest response first.
+fn search(responses: Vec<CapturedResponse>, re: &regex::Regex) -> Vec<ResponseMatch> {
+    let mut found = Vec::new();
+    for r in responses.into_iter().rev() {
+        for caps in re.captures_iter(&r.body) {
+            let whole = caps.get(0).unwrap();
+            found.push(ResponseMatch {
+                kind: r.kind.clone(),
+                url: r.url.clone(),
+                status: r.status,
+                content_type: r.content_type.clone(),
+                matched: whole.as_str().to_string(),
+                groups: caps
+                    .iter()
+                    .skip(1)
+                    .map(|g| g.map(|g| g.as_str().to_string()))
+                    .collect(),
+                context: context(&r.body, whole.start(), whole.end()),
+            });
+        }
+    }
+    found
+}
+
+/// `text[start..end]` widened by [`CONTEXT_CHARS`] each way, on char
+/// boundaries, whitespace collapsed.
+fn context(text: &str, start: usize, end: usize) -> String {
+    let from = text[..start]
+        .char_indices()
+        .rev()
+        .nth(CONTEXT_CHARS - 1)
+        .map_or(0, |(i, _)| i);
+    let to = text[end..]
+        .char_indices()
+        .nth(CONTEXT_CHARS)
+        .map_or(text.len(), |(i, _)| end + i);
+    text[from..to]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Search the captured responses and the current document for `pattern`.
+pub async fn find_in_responses(page: &Page, pattern: &str) -> Result<Vec<ResponseMatch>> {
+    let re = regex::Regex::new(pattern).map_err(|e| {
+        eoka::Error::CdpSimple(format!("invalid response pattern '{}': {}", pattern, e))
+    })?;
+    let responses: Vec<CapturedResponse> = page.evaluate(RESPONSES_JS).await?;
+    Ok(search(responses, &re))
+}
+
+/// An auth header the page sent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthHeader {
+    /// Lowercase header name.
+    pub name: String,
+    pub value: String,
+    /// The last request it was sent with.
+    pub url: String,
+}
+
+/// Credentials a logged-in page uses, from [`crate::Session::capture_auth`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CapturedAuth {
+    /// Matching headers set by page scripts, newest value of each.
+    pub headers: Vec<AuthHeader>,
+    /// Matching cookies for the current URL, including `HttpOnly` ones.
+    pub cookies: Vec<(String, String)>,
+}
+
+impl CapturedAuth {
+    /// The cookies as a `Cookie` header value, e.g. `sid=abc; token=xyz`.
+    pub fn cookie_header(&self) -> String {
+        self.cookies
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Headers to send from an HTTP client: the captured ones plus `cookie`.
+    pub fn to_headers(&self) -> Vec<(String, String)> {
+        let mut headers: Vec<_> = self
+            .headers
+            .iter()
+            .map(|h| (h.name.clone(), h.value.clone()))
+            .collect();
+        if !self.cookies.is_empty() && !headers.iter().any(|(name, _)| name == "cookie") {
+            headers.push(("cookie".into(), self.cookie_header()));
+        }
+        headers
+    }
+}
+
+/// Case-insensitive regexes; invalid patterns are an error.
+fn patterns(patterns: &[String]) -> Result<Vec<regex::Regex>> {
+    patterns
+        .iter()
+        .map(|p| {
+            regex::RegexBuilder::new(p)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| eoka::Error::CdpSimple(format!("invalid auth pattern '{}': {}", p, e)))
+        })
+        .collect()
+}
+
+/// Auth headers recorded by the page and cookies of the current URL whose
+/// names match `capture.auth_cookies`.
+pub async fn capture_auth(page: &Page, capture: &NetworkCapture) -> Result<CapturedAuth> {
+    let cookie_patterns = patterns(&capture.auth_cookies)?;
+    let recorded: BTreeMap<String, serde_json::Value> = page.evaluate(AUTH_JS).await?;
+    let headers = recorded
+        .into_iter()
+        .filter_map(|(name, entry)| {
+            Some(AuthHeader {
+                value: entry["value"].as_str()?.to_string(),
+                url: entry["url"].as_str().unwrap_or_default().to_string(),
+                name,
+            })
+        })
+        .collect();
+    let cookies = page
+        .cookies()
+        .await?
+        .into_iter()
+        .filter(|c| cookie_patterns.iter().any(|p| p.is_match(&c.name)))
+        .map(|c| (c.name, c.value))
+        .collect();
+    Ok(CapturedAuth { headers, cookies })
+}
+
+/// Entries the page recorded, oldest first.
+pub async fn sent_requests(page: &Page) -> Result<Vec<SentRequest>> {
+    page.evaluate(ENTRIES_JS).await
+}
+
+/// The newest entry whose body decodes to fields.
+pub async fn last_form_post(page: &Page) -> Result<Option<FormPost>> {
+    let requests = sent_requests(page).await?;
+    Ok(requests.into_iter().rev().find_map(|r| {
+        let fields = r.form_fields()?;
+        Some(FormPost {
+            kind: r.kind,
+            method: r.method,
+            url: r.url,
+            fields,
+        })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sent(content_type: &str, body: &str) -> SentRequest {
+        SentRequest {
+            kind: "fetch".into(),
+            method: "POST".into(),
+            url: "https://example.com/api".into(),
+            page: "https://example.com/".into(),
+            headers: BTreeMap::new(),
+            content_type: Some(content_type.into()),
+            body: Some(body.into()),
+            size: body.len(),
+            truncated: false,
+            fields: None,
+        }
+    }
+
+    #[test]
+    fn searches_responses() {
+        let responses = vec![
+            CapturedResponse {
+                kind: "document".into(),
+                url: "https://example.com/".into(),
+                status: None,
+                content_type: Some("text/html".into()),
+                body: r#"<meta name="csrf-token" content="abc123">"#.into(),
+            },
+            CapturedResponse {
+                kind: "fetch".into(),
+                url: "https://example.com/api/cart".into(),
+                status: Some(200),
+                content_type: Some("application/json".into()),
+                body: r#"{"items":[{"productId":"P-42"},{"productId":"P-7"}],"csrf":"def456"}"#
+                    .into(),
+            },
+        ];
+        let re = regex::Regex::new(r#""productId":"([^"]+)""#).unwrap();
+        let found = search(responses.clone(), &re);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].url, "https://example.com/api/cart");
+        assert_eq!(found[0].groups, [Some("P-42".to_string())]);
+        assert_eq!(found[1].groups, [Some("P-7".to_string())]);
+
+        let re = regex::Regex::new(r#"csrf-token" content="(\w+)"#).unwrap();
+        let found = search(responses, &re);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, "document");
+        assert_eq!(
+            found[0].context,
+            r#"<meta name="csrf-token" content="abc123">"#
+        );
+
+        let long = format!("{}needle{}", "a".repeat(100), "b".repeat(100));
+        let at = long.find("needle").unwrap();
+        assert_eq!(
+            context(&long, at, at + 6),
+            format!("{}needle{}", "a".repeat(40), "b".repeat(40))
+        );
+    }
+
+    #[test]
+    fn auth_headers_for_http_clients() {
+        let auth = CapturedAuth {
+            headers: vec![AuthHeader {
+                name: "authorization".into(),
+                value: "Bearer abc".into(),
+                url: "https://api.example.com/me".into(),
+            }],
+            cookies: vec![
+                ("sid".into(), "s1".into()),
+                ("auth_token".into(), "t2".into()),
+            ],
+        };
+        assert_eq!(auth.cookie_header(), "sid=s1; auth_token=t2");
+        assert_eq!(
+            auth.to_headers(),
+            [
+                ("authorization".to_string(), "Bearer abc".to_string()),
+                ("cookie".to_string(), "sid=s1; auth_token=t2".to_string())
+            ]
+        );
+
+        let defaults = patterns(&NetworkCapture::default().auth_headers).unwrap();
+        let matches = |name: &str| defaults.iter().any(|p| p.is_match(name));
+        assert!(matches("authorization"));
+        assert!(matches("X-Auth-Token"));
+        assert!(matches("x-api-key"));
+        assert!(!matches("content-type"));
+        assert!(patterns(&["(".to_string()]).is_err());
+    }
+
+    #[test]
+    fn decodes_form_fields() {
+        let form = sent(
+            "application/x-www-form-urlencoded",
+            "email=jane%40example.com&note=a+b",
+        );
+        assert_eq!(
+            form.form_fields().unwrap(),
+            [
+                ("email".to_string(), "jane@example.com".to_string()),
+                ("note".to_string(), "a b".to_string())
+            ]
+        );
+
+        let json = sent("application/json", r#"{"qty":2,"sku":"A-1"}"#);
+        assert_eq!(
+            json.form_fields().unwrap(),
+            [
+                ("qty".to_string(), "2".to_string()),
+                ("sku".to_string(), "A-1".to_string())
+            ]
+        );
+
+        assert_eq!(sent("text/plain", "hello").form_fields(), None);
+        let mut cut = form.clone();
+        cut.truncated = true;
+        assert_eq!(cut.form_fields(), None);
+
+        let raw = r#"{"kind":"form","method":"POST","url":"https://example.com/login","page":"https://example.com/","headers":{"content-type":"multipart/form-data"},"contentType":"multipart/form-data","body":"user=jane","size":9,"truncated":false,"fields":[["user","jane"]]}"#;
+        let entry: SentRequest = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            entry.form_fields().unwrap(),
+            [("user".to_string(), "jane".to_string())]
+        );
+    }
+}
@@ -1,9 +1,9 @@
 //! DOM enumeration — finds all interactive elements on the page.
 
-use eoka::{Page, Result};
+use eoka::{Browser, Page, Result, TabInfo};
 use serde::Deserialize;
 
-use crate::InteractiveElement;
+use crate::{InteractiveElement, ObserveConfig};
 
 #[derive(Deserialize)]
 struct RawElement {
@@ -19,13 +19,37 @@ struct RawElement {
     y: f64,
     width: f64,
     height: f64,
+    visibility: f32,
+    #[serde(default)]
+    context: Option<String>,
+    href: Option<String>,
+    name: Option<String>,
+    title: Option<String>,
+    id: Option<String>,
 }
 
-/// JavaScript that enumerates all interactive elements on the page.
+#[derive(Deserialize)]
+struct RawSnapshot {
+    elements: Vec<RawElement>,
+    viewport_height: f64,
+}
+
+/// One observation: the elements and the viewport height they were
+/// measured against.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub elements: Vec<InteractiveElement>,
+    pub viewport_height: f64,
+}
+
+/// JavaScript that enumerates all interactive elements on the page, with
+/// their boxes, in a single call: pages with thousands of candidates cost
+/// one round trip.
 const OBSERVE_JS: &str = r#"
 (() => {
     const INTERACTIVE = 'a, button, input, select, textarea, [role="button"], [role="link"], [role="tab"], [role="menuitem"], [onclick], [contenteditable="true"]';
     const results = [];
+    const nodes = [];
     const seen = new Set();
 
     // Helper: find associated label for a form element
@@ -51,6 +75,29 @@ const OBSERVE_JS: &str = r#"
         return '';
     }
 
+    // Share of the element on screen and not covered by something else:
+    // the part inside the viewport times the share of a 3x3 grid of points
+    // over that part where the element (or its label) is hit.
+    function visibility(el, rect) {
+        const left = Math.max(rect.left, 0), top = Math.max(rect.top, 0);
+        const right = Math.min(rect.right, window.innerWidth), bottom = Math.min(rect.bottom, window.innerHeight);
+        if (right <= left || bottom <= top) return 0;
+        const onScreen = (right - left) * (bottom - top) / (rect.width * rect.height);
+        const root = el.getRootNode();
+        const hitTest = root.elementFromPoint ? root : document;
+        const labels = el.labels ? Array.from(el.labels) : [];
+        let hits = 0;
+        for (let i = 0; i < 3; i++) {
+            for (let j = 0; j < 3; j++) {
+                const x = left + (right - left) * (i + 0.5) / 3;
+                const y = top + (bottom - top) * (j + 0.5) / 3;
+                const hit = hitTest.elementFromPoint(x, y);
+                if (hit && (el.contains(hit) || hit.contains(el) && hit.tagName === 'LABEL' || labels.some(l => l.contains(hit)))) hits++;
+            }
+        }
+        return Math.round(onScreen * hits / 9 * 100) / 100;
+    }
+
     // Collect elements from a root (document or shadowRoot)
     function collect(root) {
         const all = root.querySelectorAll('*');
@@ -68,21 +115,29 @@ const OBSERVE_JS: &str = r#"
         if (style.display === 'none' || style.visibility === 'hidden' || parseFloat(style.opacity) < 0.1) return;
 
         // Viewport filtering
-        if (typeof __eoka_viewport_only !== 'undefined' && __eoka_viewport_only) {
+        if (__eoka_config.viewport_only) {
             if (rect.bottom < 0 || rect.top > window.innerHeight) return;
             if (rect.right < 0 || rect.left > window.innerWidth) return;
         }
+        const region = __eoka_config.region;
+        if (region) {
+            const cx = rect.left + rect.width / 2, cy = rect.top + rect.height / 2;
+            if (cx < region.x || cx > region.x + region.width) return;
+            if (cy < region.y || cy > region.y + region.height) return;
+        }
 
         const tag = el.tagName.toLowerCase();
         const isFormEl = tag === 'input' || tag === 'select' || tag === 'textarea';
         const inputType = el.getAttribute('type') || '';
 
         // Get meaningful text
+        const limit = __eoka_config.text_limit;
+        const cut = s => limit > 0 && s.length > limit ? s.substring(0, Math.max(limit - 3, 0)) + '...' : s;
         let text = el.getAttribute('aria-label') || '';
         if (!text) {
             if (tag === 'a' || tag === 'button') {
                 text = (el.textContent || '').trim().replace(/\s+/g, ' ');
-                if (text.length > 80) text = '';
+                if (text.length > Math.max(80, limit || Infinity)) text = '';
             } else if (isFormEl) {
                 const label = getLabel(el);
                 if (label) {
@@ -96,7 +151,7 @@ const OBSERVE_JS: &str = r#"
                 text = (el.textContent || '').trim().replace(/\s+/g, ' ');
             }
         }
-        if (text.length > 60) text = text.substring(0, 57) + '...';
+        text = cut(text);
 
         const placeholder = el.getAttribute('placeholder') || '';
         const ariaLabel = el.getAttribute('aria-label') || '';
@@ -151,6 +206,8 @@ const OBSERVE_JS: &str = r#"
         }
 
         if (seen.has(selector)) return;
+        const shown = visibility(el, rect);
+        if (shown < __eoka_config.min_visibility) return;
         seen.add(selector);
 
         // Get current value for form elements
@@ -178,26 +235,122 @@ const OBSERVE_JS: &str = r#"
             y: Math.round(rect.y),
             width: Math.round(rect.width),
             height: Math.round(rect.height),
+            visibility: shown,
+            href: __eoka_config.include_href && tag === 'a' && typeof el.href === 'string' && el.href && !/^javascript:/i.test(el.href) ? el.href : null,
+            name: __eoka_config.include_name ? el.getAttribute('name') || null : null,
+            title: __eoka_config.include_title && title ? cut(title) : null,
+            id: __eoka_config.include_id ? el.id || null : null,
         });
+        nodes.push(el);
     }
 
     collect(document);
-    return JSON.stringify(results);
+
+    const clip = s => {
+        s = (s || '').trim().replace(/\s+/g, ' ');
+        return s.length > 40 ? s.substring(0, 37) + '...' : s;
+    };
+
+    // Lists of repeated cards share ancestors: query and read each once
+    const headingsIn = new Map();
+    const textOf = new Map();
+    const innerText = node => {
+        if (!textOf.has(node)) textOf.set(node, node.innerText || '');
+        return textOf.get(node);
+    };
+
+    // Nearest heading or title in an enclosing container (e.g. a product card)
+    function headingFor(el) {
+        const HEADING = 'h1, h2, h3, h4, h5, h6, [role="heading"], [class*="title" i], [class*="name" i]';
+        let node = el.parentElement;
+        for (let depth = 0; node && node !== document.body && depth < 6; depth++) {
+            if (!headingsIn.has(node)) headingsIn.set(node, Array.from(node.querySelectorAll(HEADING)));
+            const heading = headingsIn.get(node)
+                .find(h => !h.contains(el) && !el.contains(h) && innerText(h).trim());
+            if (heading) return clip(innerText(heading));
+            node = node.parentElement;
+        }
+        return '';
+    }
+
+    // Text of the closest container that has any besides the element's own
+    function containerText(el) {
+        const own = innerText(el).trim();
+        let node = el.parentElement;
+        for (let depth = 0; node && node !== document.body && depth < 6; depth++) {
+            const text = clip(innerText(node).replace(own, ''));
+            if (text) return text;
+            node = node.parentElement;
+        }
+        return '';
+    }
+
+    // Elements sharing a label get context so they can be told apart
+    const groups = new Map();
+    results.forEach((r, i) => {
+        const key = r.tag + '|' + r.text + '|' + (r.placeholder || '');
+        if (!groups.has(key)) groups.set(key, []);
+        groups.get(key).push(i);
+    });
+    for (const group of groups.values()) {
+        if (group.length < 2) continue;
+        const headings = group.map(i => headingFor(nodes[i]));
+        group.forEach((i, k) => {
+            let context = headings[k];
+            if (!context || headings.indexOf(context) !== headings.lastIndexOf(context)) {
+                context = containerText(nodes[i]) || context;
+            }
+            results[i].context = context || null;
+        });
+    }
+
+    return JSON.stringify({ elements: results, viewport_height: window.innerHeight });
 })()
 "#;
 
-/// Run the observe script and return parsed interactive elements.
+/// Run the observe script and return parsed interactive elements, with the
+/// default text limit and attribute set.
 pub async fn observe(page: &Page, viewport_only: bool) -> Result<Vec<InteractiveElement>> {
+    let config = ObserveConfig {
+        viewport_only,
+        ..ObserveConfig::default()
+    };
+    observe_with(page, &config).await
+}
+
+/// Run the observe script with a full [`ObserveConfig`].
+pub async fn observe_with(page: &Page, config: &ObserveConfig) -> Result<Vec<InteractiveElement>> {
+    Ok(snapshot(page, config).await?.elements)
+}
+
+/// Observe elements and the viewport height in one evaluation.
+pub async fn snapshot(page: &Page, config: &ObserveConfig) -> Result<Snapshot> {
     let js = format!(
-        "var __eoka_viewport_only = {}; {}",
-        viewport_only, OBSERVE_JS
+        "var __eoka_config = {}; {}",
+        serde_json::json!({
+            "viewport_only": config.viewport_only,
+            "text_limit": config.text_limit,
+            "include_href": config.include_href,
+            "include_name": config.include_name,
+            "include_title": config.include_title,
+            "include_id": config.include_id,
+            "min_visibility": config.min_visibility,
+            "region": config.region.map(|r| serde_json::json!({
+                "x": r.x,
+                "y": r.y,
+                "width": r.width,
+                "height": r.height,
+            })),
+        }),
+        OBSERVE_JS
     );
     let json_str: String = page.evaluate(&js).await?;
 
-    let raw: Vec<RawElement> = serde_json::from_str(&json_str)
+    let raw: RawSnapshot = serde_json::from_str(&json_str)
         .map_err(|e| eoka::Error::CdpSimple(format!("observe parse error: {}", e)))?;
 
-    Ok(raw
+    let elements = raw
+        .elements
         .into_iter()
         .enumerate()
         .map(|(i, r)| {
@@ -229,8 +382,137 @@ pub async fn observe(page: &Page, viewport_only: bool) -> Result<Vec<Interactive
                     width: r.width,
                     height: r.height,
                 },
+                visibility: r.visibility,
                 fingerprint,
+                context: r.context,
+                href: r.href,
+                name: r.name,
+                title: r.title,
+                id: r.id,
             }
         })
+        .collect();
+    Ok(Snapshot {
+        elements,
+        viewport_height: raw.viewport_height,
+    })
+}
+
+/// Snapshot several tabs at once. Observation runs concurrently, and
+/// background tabs are read without being activated, so multi-tab flows can
+/// look at every tab without switching. Results are in the order of `pages`.
+pub async fn snapshot_all(pages: &[&Page], config: &ObserveConfig) -> Vec<Result<Snapshot>> {
+    futures::future::join_all(pages.iter().map(|page| snapshot(page, config))).await
+}
+
+/// A tab observed by [`observe_all_tabs`].
+pub struct TabSnapshot {
+    pub tab: TabInfo,
+    /// Handle for acting on the tab
+    pub page: Page,
+    pub snapshot: Result<Snapshot>,
+}
+
+/// Snapshot every open tab of `browser`, including popups the page opened
+/// and tabs no [`Page`] handle exists for yet. Each tab is attached, then all
+/// are observed concurrently as in [`snapshot_all`]. Tabs that close while
+/// being attached are left out.
+pub async fn observe_all_tabs(
+    browser: &Browser,
+    config: &ObserveConfig,
+) -> Result<Vec<TabSnapshot>> {
+    let mut attached = Vec::new();
+    for tab in browser.tabs().await? {
+        if let Ok(page) = browser.attach_page(&tab.id).await {
+            attached.push((tab, page));
+        }
+    }
+    let pages: Vec<&Page> = attached.iter().map(|(_, page)| page).collect();
+    let snapshots = snapshot_all(&pages, config).await;
+    Ok(attached
+        .into_iter()
+        .zip(snapshots)
+        .map(|((tab, page), snapshot)| TabSnapshot {
+            tab,
+            page,
+            snapshot,
+        })
         .collect())
 }
+
+/// Height of the viewport in CSS pixels.
+pub async fn viewport_height(page: &Page) -> Result<f64> {
+    page.evaluate("window.innerHeight").await
+}
+
+/// Rough tokens for a line of element list text (~4 chars per token).
+fn estimate_tokens(line: &str) -> usize {
+    line.len() / 4 + 1
+}
+
+/// Form controls and buttons: what an agent most often needs to act on.
+fn is_actionable(el: &InteractiveElement) -> bool {
+    matches!(el.tag.as_str(), "input" | "select" | "textarea" | "button")
+        || matches!(el.role.as_deref(), Some("button") | Some("tab"))
+}
+
+fn is_link(el: &InteractiveElement) -> bool {
+    el.tag == "a" || el.role.as_deref() == Some("link")
+}
+
+/// Element list trimmed to about `max_tokens`.
+///
+/// Inputs and buttons inside the viewport (`0..viewport_height`) are kept
+/// first, then other elements in the viewport, then the rest top to bottom.
+/// Links repeating an earlier link's text (nav menus duplicated in header
+/// and footer) are dropped. Kept elements stay in index order; a footer
+/// counts what was left out.
+pub fn budgeted_list(
+    elements: &[InteractiveElement],
+    max_tokens: usize,
+    viewport_height: f64,
+) -> String {
+    let mut link_texts = std::collections::HashSet::new();
+    let mut candidates: Vec<&InteractiveElement> = elements
+        .iter()
+        .filter(|el| !is_link(el) || el.text.is_empty() || link_texts.insert(el.text.as_str()))
+        .collect();
+    candidates.sort_by(|a, b| {
+        let rank = |el: &InteractiveElement| {
+            let in_viewport = el.bbox.y + el.bbox.height > 0.0 && el.bbox.y < viewport_height;
+            match (in_viewport, is_actionable(el)) {
+                (true, true) => 0,
+                (true, false) => 1,
+                (false, true) => 2,
+                (false, false) => 3,
+            }
+        };
+        rank(a).cmp(&rank(b)).then(a.bbox.y.total_cmp(&b.bbox.y))
+    });
+
+    // Leave room for the footer
+    let budget = max_tokens.saturating_sub(12);
+    let mut used = 0;
+    let mut kept: Vec<(usize, String)> = Vec::new();
+    for el in candidates {
+        let line = el.to_string();
+        let cost = estimate_tokens(&line);
+        if used + cost > budget {
+            continue;
+        }
+        used += cost;
+        kept.push((el.index, line));
+    }
+    kept.sort_by_key(|(index, _)| *index);
+
+    let mut out = String::with_capacity(used * 4 + 64);
+    for (_, line) in &kept {
+        out.push_str(line);
+        out.push('\n');
+    }
+    let omitted = elements.len() - kept.len();
+    if omitted > 0 {
+        out.push_str(&format!("… and {} more (filter with observe)\n", omitted));
+    }
+    out
+}
@@ -0,0 +1,121 @@
+//! Occlusion checks — is something (a sticky header, cookie bar, modal)
+//! covering the point a click on an element would land?
+
+use eoka::{Page, Result};
+use serde::Deserialize;
+use std::fmt;
+
+/// An element covering the click point of another.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Occlusion {
+    /// Short description of the covering element, e.g. `<header id="top">`
+    pub by: String,
+    /// Whether the covering element is fixed or sticky (scrolling can uncover)
+    pub fixed: bool,
+    /// Vertical scroll that would move the element clear of a fixed coverer;
+    /// 0 when scrolling won't help
+    pub scroll_by: f64,
+}
+
+impl fmt::Display for Occlusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "obscured by {}", self.by)
+    }
+}
+
+/// JavaScript that hit-tests a point relative to the element's top-left
+/// corner (its center when null). Returns null when the element (or a
+/// descendant) is on top, or the element is missing.
+const CHECK_JS: &str = r#"
+((sel, reveal, pos) => {
+  const el = document.querySelector(sel);
+  if (!el) return 'null';
+  let r = el.getBoundingClientRect();
+  const offscreen = () => r.top < 0 || r.left < 0 || r.bottom > innerHeight || r.right > innerWidth;
+  if (reveal && offscreen()) {
+    el.scrollIntoView({ block: 'center', inline: 'center', behavior: 'instant' });
+    r = el.getBoundingClientRect();
+  }
+  const x = r.left + (pos ? pos[0] : r.width / 2), y = r.top + (pos ? pos[1] : r.height / 2);
+  if (x < 0 || y < 0 || x > innerWidth || y > innerHeight) return 'null';
+
+  let hit = document.elementFromPoint(x, y);
+  while (hit && hit.shadowRoot) {
+    const inner = hit.shadowRoot.elementFromPoint(x, y);
+    if (!inner || inner === hit) break;
+    hit = inner;
+  }
+  // A hit on the element, inside it, or on a wrapper (e.g. a label) still reaches it
+  if (!hit || hit === el || el.contains(hit) || hit.contains(el)) return 'null';
+
+  // Describe the outermost fixed/sticky ancestor: that's what needs scrolling past
+  let cover = hit, fixed = false;
+  for (let n = hit; n && n !== document.body; n = n.parentElement) {
+    const pos = getComputedStyle(n).position;
+    if (pos === 'fixed' || pos === 'sticky') { cover = n; fixed = true; }
+  }
+  const c = cover.getBoundingClientRect();
+  let scroll_by = 0;
+  if (fixed && c.height < innerHeight / 2) {
+    if (c.top <= r.top) scroll_by = -(c.bottom - r.top + 8);
+    else scroll_by = r.bottom - c.top + 8;
+  }
+  let by = '<' + cover.tagName.toLowerCase();
+  if (cover.id) by += ' id="' + cover.id + '"';
+  else if (typeof cover.className === 'string' && cover.className.trim()) {
+    by += ' class="' + cover.className.trim().split(/\s+/).slice(0, 2).join(' ') + '"';
+  }
+  by += '>';
+  const text = (cover.innerText || '').trim().replace(/\s+/g, ' ');
+  if (text) by += ' "' + (text.length > 40 ? text.substring(0, 37) + '...' : text) + '"';
+  return JSON.stringify({ by, fixed, scroll_by });
+})
+"#;
+
+/// Hit-test the center of the element matching `selector`.
+pub async fn check(page: &Page, selector: &str) -> Result<Option<Occlusion>> {
+    hit_test(page, selector, false, None).await
+}
+
+/// Scroll the element into view if needed, then past any fixed header or
+/// footer covering it. Returns what still covers it (e.g. a modal), if anything.
+pub async fn reveal(page: &Page, selector: &str) -> Result<Option<Occlusion>> {
+    reveal_at(page, selector, None).await
+}
+
+/// [`reveal`], hit-testing `position` (relative to the element's top-left
+/// corner) instead of the center.
+pub async fn reveal_at(
+    page: &Page,
+    selector: &str,
+    position: Option<(f64, f64)>,
+) -> Result<Option<Occlusion>> {
+    let occlusion = match hit_test(page, selector, true, position).await? {
+        Some(o) if o.scroll_by != 0.0 => o,
+        other => return Ok(other),
+    };
+    page.execute(&format!(
+        "window.scrollBy({{ top: {}, behavior: 'instant' }})",
+        occlusion.scroll_by
+    ))
+    .await?;
+    hit_test(page, selector, false, position).await
+}
+
+async fn hit_test(
+    page: &Page,
+    selector: &str,
+    reveal: bool,
+    position: Option<(f64, f64)>,
+) -> Result<Option<Occlusion>> {
+    let js = format!(
+        "{}({}, {}, {})",
+        CHECK_JS,
+        serde_json::to_string(selector).unwrap(),
+        reveal,
+        serde_json::to_string(&position).unwrap()
+    );
+    let json: String = page.evaluate(&js).await?;
+    serde_json::from_str(&json)
+        .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse occlusion check: {}", e)))
+}
@@ -0,0 +1,202 @@
+//! OCR fallback for canvas-only UIs (maps, games, drawing tools, Flutter web)
+//! where `observe()` finds no DOM elements to target.
+//!
+//! Runs the `tesseract` CLI (4.0 or later, must be on `PATH`) over a
+//! screenshot and returns text lines with their boxes in CSS pixels, ready for
+//! a coordinate click. Enabled with the `ocr` feature.
+
+use eoka::{Page, Result};
+use std::fmt;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::{BBox, Geometry};
+
+/// Words below this confidence (0–100) are treated as noise.
+const MIN_CONFIDENCE: f64 = 40.0;
+
+/// A line of text found in a screenshot.
+#[derive(Debug, Clone)]
+pub struct TextRegion {
+    pub text: String,
+    /// Box in viewport CSS pixels
+    pub bbox: BBox,
+    /// Mean word confidence, 0–100
+    pub confidence: f64,
+}
+
+impl TextRegion {
+    /// Center point, for `click_at`.
+    pub fn center(&self) -> (f64, f64) {
+        self.bbox.center()
+    }
+}
+
+impl fmt::Display for TextRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (x, y) = self.center();
+        write!(f, "\"{}\" at ({:.0}, {:.0})", self.text, x, y)
+    }
+}
+
+/// JavaScript that reports whether a visible canvas covers at least a
+/// quarter of the viewport.
+const CANVAS_JS: &str = r#"
+(() => {
+  const area = innerWidth * innerHeight;
+  return Array.from(document.querySelectorAll('canvas')).some(c => {
+    const r = c.getBoundingClientRect();
+    const w = Math.max(0, Math.min(r.right, innerWidth) - Math.max(r.left, 0));
+    const h = Math.max(0, Math.min(r.bottom, innerHeight) - Math.max(r.top, 0));
+    return getComputedStyle(c).visibility !== 'hidden' && w * h >= area / 4;
+  });
+})()
+"#;
+
+/// Whether the page draws its UI to a large canvas.
+pub async fn is_canvas_page(page: &Page) -> Result<bool> {
+    page.evaluate(CANVAS_JS).await
+}
+
+/// Screenshot the viewport and OCR it. Boxes are in CSS pixels.
+pub async fn text_regions(page: &Page) -> Result<Vec<TextRegion>> {
+    let png = page.screenshot().await?;
+    let scale: f64 = page.evaluate("window.devicePixelRatio || 1").await?;
+    let mut regions = recognize(&png).await?;
+    for region in &mut regions {
+        region.bbox.x /= scale;
+        region.bbox.y /= scale;
+        region.bbox.width /= scale;
+        region.bbox.height /= scale;
+    }
+    Ok(regions)
+}
+
+/// OCR a PNG. Boxes are in image pixels.
+pub async fn recognize(png: &[u8]) -> Result<Vec<TextRegion>> {
+    let mut child = Command::new("tesseract")
+        .args(["stdin", "stdout", "--psm", "11", "tsv"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| eoka::Error::CdpSimple(format!("failed to run tesseract: {}", e)))?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    stdin
+        .write_all(png)
+        .await
+        .map_err(|e| eoka::Error::CdpSimple(format!("failed to send image to tesseract: {}", e)))?;
+    drop(stdin);
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| eoka::Error::CdpSimple(format!("tesseract failed: {}", e)))?;
+    if !output.status.success() {
+        return Err(eoka::Error::CdpSimple(format!(
+            "tesseract exited with {}",
+            output.status
+        )));
+    }
+    Ok(parse_tsv(&String::from_utf8_lossy(&output.stdout)))
+}
+
+struct Word {
+    text: String,
+    left: f64,
+    top: f64,
+    right: f64,
+    bottom: f64,
+    conf: f64,
+}
+
+/// Group tesseract's word rows into lines.
+fn parse_tsv(tsv: &str) -> Vec<TextRegion> {
+    // Keyed by (block, paragraph, line)
+    let mut lines: Vec<((u32, u32, u32), Vec<Word>)> = Vec::new();
+    for row in tsv.lines().skip(1) {
+        let cols: Vec<&str> = row.split('\t').collect();
+        if cols.len() < 12 || cols[0] != "5" {
+            continue;
+        }
+        let text = cols[11].trim();
+        let conf: f64 = cols[10].parse().unwrap_or(-1.0);
+        if text.is_empty() || conf < MIN_CONFIDENCE {
+            continue;
+        }
+        let num = |i: usize| cols[i].parse::<f64>().unwrap_or(0.0);
+        let key = (
+            cols[2].parse().unwrap_or(0),
+            cols[3].parse().unwrap_or(0),
+            cols[4].parse().unwrap_or(0),
+        );
+        let word = Word {
+            text: text.to_string(),
+            left: num(6),
+            top: num(7),
+            right: num(6) + num(8),
+            bottom: num(7) + num(9),
+            conf,
+        };
+        match lines.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, words)) => words.push(word),
+            None => lines.push((key, vec![word])),
+        }
+    }
+
+    lines
+        .into_iter()
+        .map(|(_, words)| {
+            let left = words.iter().map(|w| w.left).fold(f64::INFINITY, f64::min);
+            let top = words.iter().map(|w| w.top).fold(f64::INFINITY, f64::min);
+            let right = words.iter().map(|w| w.right).fold(0.0, f64::max);
+            let bottom = words.iter().map(|w| w.bottom).fold(0.0, f64::max);
+            let confidence = words.iter().map(|w| w.conf).sum::<f64>() / words.len() as f64;
+            let text: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+            TextRegion {
+                text: text.join(" "),
+                bbox: BBox {
+                    x: left,
+                    y: top,
+                    width: right - left,
+                    height: bottom - top,
+                },
+                confidence,
+            }
+        })
+        .collect()
+}
+
+/// One region per line, for LLM consumption.
+pub fn region_list(regions: &[TextRegion]) -> String {
+    let mut out = String::new();
+    for region in regions {
+        out.push_str(&region.to_string());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_words_into_lines() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+            1\t1\t0\t0\t0\t0\t0\t0\t800\t600\t-1\t\n\
+            5\t1\t1\t1\t1\t1\t100\t50\t60\t20\t96.5\tStart\n\
+            5\t1\t1\t1\t1\t2\t170\t52\t50\t20\t91.5\tGame\n\
+            5\t1\t2\t1\t1\t1\t300\t400\t10\t10\t12.0\t~\n\
+            5\t1\t3\t1\t1\t1\t20\t500\t80\t30\t88.0\tQuit\n";
+        let regions = parse_tsv(tsv);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].text, "Start Game");
+        assert_eq!(regions[0].confidence, 94.0);
+        assert_eq!(regions[0].center(), (160.0, 61.0));
+        assert_eq!(
+            region_list(&regions),
+            "\"Start Game\" at (160, 61)\n\"Quit\" at (60, 515)\n"
+        );
+    }
+}
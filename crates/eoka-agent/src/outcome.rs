@@ -0,0 +1,308 @@
+//! What an action did — navigation, dialogs, new elements, console errors,
+//! validation messages — so an agent can decide its next step without another observe round trip.
+
+use eoka::cdp::transport::CdpMessage;
+use eoka::{Page, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::click::{ClickMethod, Clicked};
+use crate::form_errors::{self, FormError};
+
+/// Summary of the page's reaction to a click, fill or select.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActionOutcome {
+    /// A new document was loaded
+    pub navigated: bool,
+    /// The URL changed (full navigation, SPA route or hash change)
+    pub url_changed: bool,
+    /// URL after the action
+    pub url: String,
+    /// Messages of `alert`/`confirm`/`prompt` dialogs the action opened.
+    /// Only alerts are accepted unless the caller opted in; see [`arm`]
+    pub dialogs: Vec<String>,
+    /// Visible interactive elements that weren't on the page before
+    pub new_elements: usize,
+    /// `console.error` calls, uncaught errors and unhandled rejections
+    pub console_errors: Vec<String>,
+    /// Validation messages visible after the action, e.g. why a submit
+    /// was rejected
+    pub form_errors: Vec<FormError>,
+    /// How a click reached its element (clicks only)
+    pub clicked: Option<Clicked>,
+}
+
+impl ActionOutcome {
+    /// Nothing noteworthy happened (values may still have changed).
+    pub fn is_quiet(&self) -> bool {
+        !self.navigated
+            && !self.url_changed
+            && self.dialogs.is_empty()
+            && self.new_elements == 0
+            && self.console_errors.is_empty()
+            && self.form_errors.is_empty()
+            && self
+                .clicked
+                .as_ref()
+                .is_none_or(|c| c.method == ClickMethod::Mouse)
+    }
+}
+
+impl fmt::Display for ActionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(c) = self
+            .clicked
+            .as_ref()
+            .filter(|c| c.method != ClickMethod::Mouse)
+        {
+            parts.push(c.to_string());
+        }
+        if self.navigated {
+            parts.push(format!("navigated to {}", self.url));
+        } else if self.url_changed {
+            parts.push(format!("URL changed to {}", self.url));
+        }
+        if self.new_elements > 0 {
+            parts.push(format!("{} new elements", self.new_elements));
+        }
+        for dialog in &self.dialogs {
+            parts.push(format!("dialog \"{}\"", dialog));
+        }
+        for error in &self.console_errors {
+            parts.push(format!("console error: {}", error));
+        }
+        for error in &self.form_errors {
+            parts.push(format!("form error: {}", error));
+        }
+        if parts.is_empty() {
+            return write!(f, "no page change");
+        }
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// JavaScript that installs the probe: wraps `console.error` and listens for
+/// uncaught errors until [`READ_JS`] puts everything back, and remembers which
+/// interactive elements exist now.
+const ARM_JS: &str = r#"
+(() => {
+  const INTERACTIVE = 'a, button, input, select, textarea, [role="button"], [role="link"], [role="tab"], [role="menuitem"], [onclick], [contenteditable="true"]';
+  if (window.__eokaProbe && window.__eokaProbe.restore) window.__eokaProbe.restore();
+  const probe = window.__eokaProbe = {
+    errors: [],
+    seen: new WeakSet(document.querySelectorAll(INTERACTIVE)),
+    armed: true,
+  };
+  const text = v => (v instanceof Error ? v.message : String(v)).substring(0, 200);
+  const error = console.error;
+  const wrapped = function (...args) { probe.errors.push(args.map(text).join(' ')); return error.apply(this, args); };
+  const onError = e => probe.errors.push(text(e.error || e.message));
+  const onRejection = e => probe.errors.push('Unhandled rejection: ' + text(e.reason));
+  console.error = wrapped;
+  addEventListener('error', onError);
+  addEventListener('unhandledrejection', onRejection);
+  probe.restore = () => {
+    if (console.error === wrapped) console.error = error;
+    removeEventListener('error', onError);
+    removeEventListener('unhandledrejection', onRejection);
+    probe.restore = null;
+  };
+})()
+"#;
+
+/// JavaScript that reads the probe and restores what [`ARM_JS`] replaced.
+/// `navigated` when the document was replaced.
+const READ_JS: &str = r#"
+(() => {
+  const INTERACTIVE = 'a, button, input, select, textarea, [role="button"], [role="link"], [role="tab"], [role="menuitem"], [onclick], [contenteditable="true"]';
+  const probe = window.__eokaProbe;
+  const navigated = !probe || !probe.armed;
+  const shown = el => {
+    const r = el.getBoundingClientRect();
+    const s = getComputedStyle(el);
+    return r.width >= 2 && r.height >= 2 && s.display !== 'none' && s.visibility !== 'hidden';
+  };
+  const added = Array.from(document.querySelectorAll(INTERACTIVE))
+    .filter(el => (navigated || !probe.seen.has(el)) && shown(el)).length;
+  const out = {
+    navigated,
+    new_elements: added,
+    console_errors: navigated ? [] : probe.errors,
+  };
+  if (probe) {
+    probe.armed = false;
+    if (probe.restore) probe.restore();
+  }
+  return JSON.stringify(out);
+})()
+"#;
+
+#[derive(Deserialize, Default)]
+struct ProbeRead {
+    navigated: bool,
+    new_elements: usize,
+    console_errors: Vec<String>,
+}
+
+/// Page state captured before an action.
+pub struct Probe {
+    url: String,
+    dialogs: DialogWatch,
+}
+
+/// Start watching the page. Call before the action, then [`Probe::finish`]
+/// once the page has settled.
+///
+/// Dialogs the action opens are answered through DevTools, leaving the
+/// page's `alert`/`confirm`/`prompt` untouched: alerts are closed, and
+/// `confirm`, `prompt` and "leave site?" dialogs are cancelled unless
+/// `accept_dialogs` is set.
+pub async fn arm(page: &Page, accept_dialogs: bool) -> Result<Probe> {
+    let dialogs = DialogWatch::start(page, accept_dialogs).await;
+    page.execute(ARM_JS).await?;
+    Ok(Probe {
+        url: page.url().await?,
+        dialogs,
+    })
+}
+
+impl Probe {
+    /// Summarize what changed since [`arm`]. Never fails: the action has
+    /// already happened, so whatever can't be read is left out — a page
+    /// whose context is gone is reported as `navigated`.
+    pub async fn finish(self, page: &Page) -> ActionOutcome {
+        let dialogs = self.dialogs.stop();
+        let read = match page.evaluate::<String>(READ_JS).await {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("[eoka-agent] failed to parse action outcome: {}", e);
+                ProbeRead::default()
+            }),
+            // Reading fails when the action replaced the document under us
+            Err(e) => {
+                eprintln!("[eoka-agent] action outcome unavailable: {}", e);
+                ProbeRead {
+                    navigated: true,
+                    ..Default::default()
+                }
+            }
+        };
+        let url = page.url().await.unwrap_or_else(|_| self.url.clone());
+        let form_errors = form_errors::form_errors(page).await.unwrap_or_default();
+        ActionOutcome {
+            navigated: read.navigated,
+            url_changed: url != self.url,
+            url,
+            dialogs,
+            new_elements: read.new_elements,
+            console_errors: read.console_errors,
+            form_errors,
+            clicked: None,
+        }
+    }
+}
+
+/// Answers `Page.javascriptDialogOpening` for one page while an action runs.
+struct DialogWatch {
+    task: JoinHandle<()>,
+    messages: Arc<Mutex<Vec<String>>>,
+}
+
+impl DialogWatch {
+    async fn start(page: &Page, accept: bool) -> Self {
+        let transport = page.session().transport().clone();
+        let session_id = page.session().session_id().to_string();
+        // Nothing else reads the event channel, and Chrome's events are
+        // dropped once it is full: empty it so the action's dialogs fit.
+        while transport.try_recv_event().await.is_some() {}
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let seen = messages.clone();
+        let task = tokio::spawn(async move {
+            while let Some(event) = transport.recv_event().await {
+                let CdpMessage::Event {
+                    method,
+                    params,
+                    session_id: from,
+                } = event
+                else {
+                    continue;
+                };
+                if method != "Page.javascriptDialogOpening"
+                    || from.as_deref() != Some(session_id.as_str())
+                {
+                    continue;
+                }
+                let message = params["message"].as_str().unwrap_or_default();
+                seen.lock()
+                    .unwrap()
+                    .push(message.chars().take(200).collect());
+                let mut answer = json!({ "accept": accept || params["type"] == "alert" });
+                if accept && params["type"] == "prompt" {
+                    answer["promptText"] = params["defaultPrompt"].clone();
+                }
+                let _ = transport
+                    .send_to_session::<_, Value>(
+                        &session_id,
+                        "Page.handleJavaScriptDialog",
+                        &answer,
+                    )
+                    .await;
+            }
+        });
+        Self { task, messages }
+    }
+
+    /// Stop answering dialogs and return the messages seen.
+    fn stop(&self) -> Vec<String> {
+        self.task.abort();
+        std::mem::take(&mut *self.messages.lock().unwrap())
+    }
+}
+
+impl Drop for DialogWatch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outcome_display() {
+        let quiet = ActionOutcome {
+            url: "https://example.com/".into(),
+            clicked: Some(Clicked {
+                method: ClickMethod::Mouse,
+                fallback_reason: None,
+            }),
+            ..Default::default()
+        };
+        assert!(quiet.is_quiet());
+        assert_eq!(quiet.to_string(), "no page change");
+
+        let busy = ActionOutcome {
+            navigated: true,
+            url_changed: true,
+            url: "https://example.com/cart".into(),
+            dialogs: vec!["Added!".into()],
+            new_elements: 4,
+            console_errors: vec!["TypeError: x is undefined".into()],
+            form_errors: vec![FormError {
+                field: Some("Email".into()),
+                selector: Some("#email".into()),
+                message: "Required".into(),
+            }],
+            clicked: None,
+        };
+        assert!(!busy.is_quiet());
+        assert_eq!(
+            busy.to_string(),
+            "navigated to https://example.com/cart; 4 new elements; dialog \"Added!\"; console error: TypeError: x is undefined; form error: Email: Required"
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Overlay detection — cookie banners, modals, chat widgets and interstitials
+//! covering the page, with the buttons that dismiss them.
+
+use eoka::{Page, Result};
+use serde::Deserialize;
+use std::fmt;
+
+use crate::BBox;
+
+/// What an overlay is, judged from its text, attributes and size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverlayKind {
+    /// Cookie or consent banner / dialog
+    CookieBanner,
+    /// Dialog in front of the page (`role="dialog"`, `<dialog>`, centered box)
+    Modal,
+    /// Support or sales chat launcher
+    ChatWidget,
+    /// Full-screen layer: newsletter prompt, age gate, paywall, ad
+    Interstitial,
+    /// Fixed layer that fits none of the above
+    Other,
+}
+
+impl fmt::Display for OverlayKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverlayKind::CookieBanner => write!(f, "cookie-banner"),
+            OverlayKind::Modal => write!(f, "modal"),
+            OverlayKind::ChatWidget => write!(f, "chat-widget"),
+            OverlayKind::Interstitial => write!(f, "interstitial"),
+            OverlayKind::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// What clicking a dismiss button does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DismissKind {
+    /// Accept / agree / allow
+    Accept,
+    /// Reject / decline / necessary only
+    Reject,
+    /// Close / not now / skip
+    Close,
+}
+
+impl fmt::Display for DismissKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DismissKind::Accept => write!(f, "accept"),
+            DismissKind::Reject => write!(f, "reject"),
+            DismissKind::Close => write!(f, "close"),
+        }
+    }
+}
+
+/// A button inside an overlay that should make it go away.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DismissButton {
+    /// CSS selector for the button
+    pub selector: String,
+    /// Button label (text, aria-label or title)
+    pub text: String,
+    pub kind: DismissKind,
+}
+
+/// A fixed or high-z-index layer covering part of the viewport.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Overlay {
+    pub kind: OverlayKind,
+    /// CSS selector for the overlay root
+    pub selector: String,
+    /// Start of the overlay's text, truncated to 80 chars
+    pub text: String,
+    /// Computed z-index (0 when `auto`)
+    pub z_index: i64,
+    /// Fraction of the viewport covered, 0.0–1.0
+    pub coverage: f64,
+    pub bbox: BBox,
+    /// Candidate dismiss buttons, best first
+    pub dismiss: Vec<DismissButton>,
+}
+
+impl fmt::Display for Overlay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} css:{} (z={}, {:.0}% of viewport)",
+            self.kind,
+            self.selector,
+            self.z_index,
+            self.coverage * 100.0
+        )?;
+        if !self.text.is_empty() {
+            write!(f, " \"{}\"", self.text)?;
+        }
+        for button in &self.dismiss {
+            write!(
+                f,
+                "\n  [{}] css:{} \"{}\"",
+                button.kind, button.selector, button.text
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// JavaScript that finds and classifies overlays. Topmost first.
+const DETECT_OVERLAYS_JS: &str = r#"
+(() => {
+  const vw = window.innerWidth, vh = window.innerHeight;
+  const DIALOG = 'dialog[open], [role="dialog"], [role="alertdialog"], [aria-modal="true"]';
+  const BUTTONS = 'button, a, [role="button"], input[type="button"], input[type="submit"]';
+
+  function selectorFor(el) {
+    if (el.id) return '#' + CSS.escape(el.id);
+    const parts = [];
+    let node = el;
+    while (node && node !== document.body && parts.length < 5) {
+      if (node.id) {
+        parts.unshift('#' + CSS.escape(node.id));
+        break;
+      }
+      let s = node.tagName.toLowerCase();
+      const parent = node.parentElement;
+      if (parent) {
+        const siblings = Array.from(parent.children).filter(c => c.tagName === node.tagName);
+        if (siblings.length > 1) s += ':nth-of-type(' + (siblings.indexOf(node) + 1) + ')';
+      }
+      parts.unshift(s);
+      node = parent;
+    }
+    return parts.join(' > ');
+  }
+
+  function shown(el, style) {
+    const rect = el.getBoundingClientRect();
+    return style.display !== 'none' && style.visibility !== 'hidden'
+      && parseFloat(style.opacity) >= 0.1 && rect.width > 1 && rect.height > 1;
+  }
+
+  const clean = s => (s || '').trim().replace(/\s+/g, ' ');
+
+  function dismissKind(label, el) {
+    const t = label.toLowerCase();
+    if (/\b(reject|decline|deny|refuse|disagree)\b|necessary only|only necessary|essential only|only essential/.test(t)) return 'reject';
+    if (/\b(accept|agree|allow|consent|got it|i understand|understood)\b|^ok(ay)?!?$|^continue$/.test(t)) return 'accept';
+    if (/^(close|dismiss|x|×|✕|✖|no,? thanks|not now|maybe later|skip|cancel|later)$/.test(t)) return 'close';
+    const hint = ((el.getAttribute('aria-label') || '') + ' ' + (el.className || '')).toLowerCase();
+    if (/close|dismiss/.test(hint)) return 'close';
+    return null;
+  }
+
+  function dismissButtons(root) {
+    const out = [];
+    for (const el of root.querySelectorAll(BUTTONS)) {
+      if (!shown(el, getComputedStyle(el))) continue;
+      const label = clean(el.getAttribute('aria-label') || el.innerText || el.value || el.title);
+      if (label.length > 40) continue;
+      const kind = dismissKind(label, el);
+      if (kind) out.push({ selector: selectorFor(el), text: label, kind });
+    }
+    const rank = { reject: 0, close: 1, accept: 2 };
+    out.sort((a, b) => rank[a.kind] - rank[b.kind]);
+    return out.slice(0, 5);
+  }
+
+  function classify(el, text, coverage, isDialog) {
+    const attrs = (el.id + ' ' + (el.className || '') + ' ' + (el.getAttribute('aria-label') || '')).toLowerCase();
+    const frames = Array.from(el.querySelectorAll('iframe')).map(f => f.src).join(' ').toLowerCase();
+    const t = text.toLowerCase();
+    if (/cookie|consent|gdpr|onetrust|cookiebot|didomi|\bcmp\b|usercentrics|truste/.test(attrs)
+        || /\bcookies?\b|your privacy|we value your privacy|consent/.test(t)) return 'cookie-banner';
+    if (coverage < 0.25 && /chat|intercom|drift|zendesk|livechat|crisp|tawk|hubspot-messages|messenger/.test(attrs + ' ' + frames)) return 'chat-widget';
+    if (coverage >= 0.6) return 'interstitial';
+    if (isDialog || coverage >= 0.1) return 'modal';
+    return 'other';
+  }
+
+  const found = [];
+  for (const el of document.querySelectorAll('body *')) {
+    if (found.some(o => o.el.contains(el))) continue;
+    const style = getComputedStyle(el);
+    const isDialog = el.matches(DIALOG);
+    const z = parseInt(style.zIndex, 10) || 0;
+    const layered = style.position === 'fixed' || (style.position === 'absolute' && z >= 100);
+    if (!layered && !isDialog) continue;
+    // Sticky headers and nav bars are page chrome, not overlays
+    if (!isDialog && el.matches('header, nav, footer, [role="banner"], [role="navigation"]')) continue;
+    if (!shown(el, style)) continue;
+
+    const r = el.getBoundingClientRect();
+    const w = Math.max(0, Math.min(r.right, vw) - Math.max(r.left, 0));
+    const h = Math.max(0, Math.min(r.bottom, vh) - Math.max(r.top, 0));
+    const coverage = (w * h) / (vw * vh);
+    if (coverage < 0.02 && !isDialog) continue;
+
+    const text = clean(el.innerText);
+    if (!text && coverage < 0.6) continue;
+    found.push({
+      el,
+      kind: classify(el, text, coverage, isDialog),
+      selector: selectorFor(el),
+      text: text.length > 80 ? text.substring(0, 77) + '...' : text,
+      z_index: z,
+      coverage: Math.round(coverage * 100) / 100,
+      bbox: { x: r.x, y: r.y, width: r.width, height: r.height },
+      dismiss: dismissButtons(el),
+    });
+  }
+
+  found.sort((a, b) => b.z_index - a.z_index);
+  return JSON.stringify(found.map(({ el, ...rest }) => rest));
+})()
+"#;
+
+/// Find overlays covering the page, topmost first.
+pub async fn detect_overlays(page: &Page) -> Result<Vec<Overlay>> {
+    let json: String = page.evaluate(DETECT_OVERLAYS_JS).await?;
+    serde_json::from_str(&json)
+        .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse overlays: {}", e)))
+}
+
+/// One overlay per line (dismiss buttons indented below), for LLM consumption.
+pub fn overlay_list(overlays: &[Overlay]) -> String {
+    let mut out = String::new();
+    for overlay in overlays {
+        out.push_str(&overlay.to_string());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        let json = r##"[{
+            "kind": "cookie-banner",
+            "selector": "#consent",
+            "text": "We use cookies",
+            "z_index": 9999,
+            "coverage": 0.18,
+            "bbox": {"x": 0, "y": 600, "width": 1280, "height": 120},
+            "dismiss": [{"selector": "#reject", "text": "Reject all", "kind": "reject"}]
+        }]"##;
+        let overlays: Vec<Overlay> = serde_json::from_str(json).unwrap();
+        assert_eq!(overlays[0].kind, OverlayKind::CookieBanner);
+        assert_eq!(overlays[0].dismiss[0].kind, DismissKind::Reject);
+        assert_eq!(
+            overlay_list(&overlays),
+            "cookie-banner css:#consent (z=9999, 18% of viewport) \"We use cookies\"\n  [reject] css:#reject \"Reject all\"\n"
+        );
+    }
+}
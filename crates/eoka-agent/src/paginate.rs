@@ -0,0 +1,92 @@
+//! Pagination — find a page's "next" control and step through result pages.
+
+use eoka::{Page, Result};
+use serde::Deserialize;
+
+/// Attribute set on the next control found by [`find_next`].
+const NEXT_SELECTOR: &str = "[data-eoka-next]";
+
+/// JavaScript that finds the next-page control: `a[rel=next]`, then an
+/// `aria-label` starting with "Next", then a control labelled "Next", "›",
+/// "»" or similar. Disabled controls are skipped. Falls back to
+/// `<link rel="next">` in the head, which has a URL but nothing to click.
+const FIND_NEXT_JS: &str = r#"
+(() => {
+  document.querySelectorAll('[data-eoka-next]').forEach(el => el.removeAttribute('data-eoka-next'));
+  const CLICKABLE = 'a, button, [role="button"], [role="link"], input[type="button"], input[type="submit"]';
+  const shown = el => {
+    const r = el.getBoundingClientRect();
+    const s = getComputedStyle(el);
+    return r.width > 0 && r.height > 0 && s.display !== 'none' && s.visibility !== 'hidden';
+  };
+  const cls = el => (el && typeof el.className === 'string') ? el.className : '';
+  const disabled = el => el.disabled || el.getAttribute('aria-disabled') === 'true'
+    || /\bdisabled\b/i.test(cls(el)) || /\bdisabled\b/i.test(cls(el.parentElement));
+  const usable = el => shown(el) && !disabled(el);
+  const label = el => (el.getAttribute('aria-label') || el.innerText || el.value || el.title || '').trim().replace(/\s+/g, ' ');
+  const clickable = Array.from(document.querySelectorAll(CLICKABLE)).filter(usable);
+
+  const found = Array.from(document.querySelectorAll('a[rel~="next"]')).find(usable)
+    || clickable.find(el => /^next\b/i.test(el.getAttribute('aria-label') || ''))
+    || clickable.find(el => /^(next( page)?( ?[›»>→])?|[›»→]|>>?)$/i.test(label(el)));
+  if (found) {
+    found.setAttribute('data-eoka-next', '');
+    return JSON.stringify({ text: label(found), href: null });
+  }
+  const link = document.querySelector('link[rel~="next"][href]');
+  return link ? JSON.stringify({ text: '', href: link.href }) : 'null';
+})()
+"#;
+
+/// Cheap fingerprint of the page content, to tell whether "next" did anything.
+const SIGNATURE_JS: &str =
+    "location.href + '|' + (document.body ? document.body.innerText.substring(0, 2000) : '')";
+
+/// The control that leads to the next page.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NextControl {
+    /// Label of the control (empty for `<link rel="next">`)
+    pub text: String,
+    /// URL to navigate to when there is no control to click
+    pub href: Option<String>,
+}
+
+/// Pages visited by a pagination run and what was extracted from each.
+#[derive(Debug, Clone)]
+pub struct Paginated<T> {
+    /// Number of pages visited, including the first
+    pub pages: usize,
+    /// One result per page
+    pub results: Vec<T>,
+    /// The last page had no working next control (as opposed to hitting the limit)
+    pub exhausted: bool,
+}
+
+/// Find the next-page control on the current page.
+pub async fn find_next(page: &Page) -> Result<Option<NextControl>> {
+    let json: String = page.evaluate(FIND_NEXT_JS).await?;
+    serde_json::from_str(&json)
+        .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse next control: {}", e)))
+}
+
+/// Go to the next page and wait for it to settle. Returns `false` when there
+/// is no next control, or using it didn't change the page.
+pub async fn next_page(page: &Page) -> Result<bool> {
+    let Some(next) = find_next(page).await? else {
+        return Ok(false);
+    };
+    let before: String = page.evaluate(SIGNATURE_JS).await?;
+    match next.href {
+        Some(href) => page.goto(&href).await?,
+        None => {
+            if page.click(NEXT_SELECTOR).await.is_err() {
+                let js = format!("document.querySelector('{}')?.click()", NEXT_SELECTOR);
+                page.execute(&js).await?;
+            }
+        }
+    }
+    let _ = page.wait_for_network_idle(200, 2000).await;
+    page.wait(50).await;
+    let after: String = page.evaluate(SIGNATURE_JS).await?;
+    Ok(after != before)
+}
@@ -0,0 +1,9 @@
+//! Popup policy — what a [`crate::Session`] does when the page opens a new
+//! window through `window.open` or a `target=_blank` link or form.
+//!
+//! The policy and the tab handling live in [`eoka_runner::popup`], so a
+//! session treats popups the same way as the runner's `browser.popups`.
+
+pub use eoka_runner::PopupPolicy;
+
+pub(crate) use eoka_runner::popup::{PopupTracker, SAME_TAB_JS};
@@ -0,0 +1,336 @@
+//! Per-origin rate limiting — a minimum delay between navigations, a cap on
+//! concurrent ones and optional robots.txt checks, shared by every
+//! [`Session`](crate::Session) holding the same [`RateLimiter`].
+
+use eoka::Result;
+use eoka_runner::frontier::origin_of;
+use regex::Regex;
+use reqwest::Url;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+/// Product token matched against robots.txt `User-agent` lines.
+const ROBOTS_AGENT: &str = "eoka";
+
+/// Politeness settings for one origin. Default: 1s between navigations, two
+/// at a time, robots.txt ignored.
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    /// Minimum delay between the starts of two navigations
+    pub min_delay_ms: u64,
+    /// Navigations allowed in flight at once
+    pub max_concurrent: usize,
+    /// Refuse URLs that robots.txt disallows, and honor its `Crawl-delay`
+    pub respect_robots: bool,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            min_delay_ms: 1000,
+            max_concurrent: 2,
+            respect_robots: false,
+        }
+    }
+}
+
+impl RateLimit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn min_delay_ms(mut self, ms: u64) -> Self {
+        self.min_delay_ms = ms;
+        self
+    }
+
+    pub fn max_concurrent(mut self, n: usize) -> Self {
+        self.max_concurrent = n.max(1);
+        self
+    }
+
+    pub fn respect_robots(mut self, respect: bool) -> Self {
+        self.respect_robots = respect;
+        self
+    }
+}
+
+struct OriginState {
+    next_slot: Instant,
+    permits: Arc<Semaphore>,
+    robots: Option<Arc<Robots>>,
+}
+
+/// Rate limiter keyed by origin (`https://example.com`). Wrap it in an `Arc`
+/// and hand it to each session that should share the budget.
+pub struct RateLimiter {
+    default: RateLimit,
+    overrides: HashMap<String, RateLimit>,
+    origins: Mutex<HashMap<String, OriginState>>,
+    client: reqwest::Client,
+}
+
+/// Held while a navigation is in flight; dropping it frees the slot.
+pub struct RatePermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl RateLimiter {
+    /// Limiter applying `default` to every origin.
+    pub fn new(default: RateLimit) -> Self {
+        Self {
+            default,
+            overrides: HashMap::new(),
+            origins: Mutex::new(HashMap::new()),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Use different settings for one origin, e.g. `https://api.example.com`.
+    pub fn origin(mut self, origin: &str, limit: RateLimit) -> Self {
+        let key = origin_of(origin, true).unwrap_or_else(|| origin.to_string());
+        self.overrides.insert(key, limit);
+        self
+    }
+
+    /// Settings that apply to a URL's origin.
+    pub fn limit_for(&self, url: &str) -> &RateLimit {
+        origin_of(url, true)
+            .and_then(|o| self.overrides.get(&o))
+            .unwrap_or(&self.default)
+    }
+
+    /// Wait until a navigation to `url` is allowed. URLs without an HTTP(S)
+    /// origin (`data:`, `about:`) pass straight through. Errors when
+    /// robots.txt disallows the URL.
+    pub async fn acquire(&self, url: &str) -> Result<RatePermit> {
+        let Some(origin) = origin_of(url, true) else {
+            return Ok(RatePermit { _permit: None });
+        };
+        let limit = self.limit_for(url).clone();
+        let mut delay = Duration::from_millis(limit.min_delay_ms);
+
+        if limit.respect_robots {
+            let robots = self.robots(&origin, &limit).await;
+            let path = Url::parse(url)
+                .map(|u| match u.query() {
+                    Some(q) => format!("{}?{}", u.path(), q),
+                    None => u.path().to_string(),
+                })
+                .unwrap_or_else(|_| "/".into());
+            if !robots.allows(&path) {
+                return Err(eoka::Error::Navigation(format!(
+                    "{} is disallowed by robots.txt",
+                    url
+                )));
+            }
+            delay = delay.max(robots.crawl_delay.unwrap_or_default());
+        }
+
+        let permits = self.state(&origin, &limit, |s| s.permits.clone());
+        let permit = permits
+            .acquire_owned()
+            .await
+            .map_err(|e| eoka::Error::CdpSimple(format!("rate limiter closed: {}", e)))?;
+        let start = self.state(&origin, &limit, |s| {
+            let start = s.next_slot.max(Instant::now());
+            s.next_slot = start + delay;
+            start
+        });
+        tokio::time::sleep_until(start).await;
+        Ok(RatePermit {
+            _permit: Some(permit),
+        })
+    }
+
+    fn state<T>(
+        &self,
+        origin: &str,
+        limit: &RateLimit,
+        f: impl FnOnce(&mut OriginState) -> T,
+    ) -> T {
+        let mut origins = self.origins.lock().unwrap();
+        let state = origins
+            .entry(origin.to_string())
+            .or_insert_with(|| OriginState {
+                next_slot: Instant::now(),
+                permits: Arc::new(Semaphore::new(limit.max_concurrent.max(1))),
+                robots: None,
+            });
+        f(state)
+    }
+
+    /// robots.txt for an origin, fetched once. Unreachable or missing files
+    /// allow everything.
+    async fn robots(&self, origin: &str, limit: &RateLimit) -> Arc<Robots> {
+        let cached = self
+            .origins
+            .lock()
+            .unwrap()
+            .get(origin)
+            .and_then(|s| s.robots.clone());
+        if let Some(robots) = cached {
+            return robots;
+        }
+        let text = match self
+            .client
+            .get(format!("{}/robots.txt", origin))
+            .send()
+            .await
+        {
+            Ok(resp) if resp.status().is_success() => resp.text().await.unwrap_or_default(),
+            _ => String::new(),
+        };
+        let robots = Arc::new(Robots::parse(&text, ROBOTS_AGENT));
+        self.state(origin, limit, |s| s.robots = Some(robots.clone()));
+        robots
+    }
+}
+
+/// The robots.txt group that applies to us.
+#[derive(Debug, Default)]
+struct Robots {
+    /// (allow, pattern, pattern length) — the longest match wins
+    rules: Vec<(bool, Regex, usize)>,
+    crawl_delay: Option<Duration>,
+}
+
+impl Robots {
+    /// Parse robots.txt, keeping the group naming `agent` or else the `*` group.
+    fn parse(text: &str, agent: &str) -> Self {
+        let agent = agent.to_lowercase();
+        let mut named = Robots::default();
+        let mut wildcard = Robots::default();
+        let mut has_named = false;
+        let mut group: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim();
+            if key == "user-agent" {
+                if in_rules {
+                    group.clear();
+                    in_rules = false;
+                }
+                group.push(value.to_lowercase());
+                continue;
+            }
+            if !matches!(key.as_str(), "allow" | "disallow" | "crawl-delay") {
+                continue;
+            }
+            in_rules = true;
+            for ua in &group {
+                let target = if ua == "*" {
+                    &mut wildcard
+                } else if !ua.is_empty() && agent.contains(ua.as_str()) {
+                    has_named = true;
+                    &mut named
+                } else {
+                    continue;
+                };
+                if key == "crawl-delay" {
+                    target.crawl_delay = value.parse().ok().map(Duration::from_secs_f64);
+                } else if !value.is_empty() {
+                    if let Some(re) = pattern(value) {
+                        target.rules.push((key == "allow", re, value.len()));
+                    }
+                }
+            }
+        }
+        if has_named {
+            named
+        } else {
+            wildcard
+        }
+    }
+
+    /// Whether `path` (with query) may be fetched.
+    fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, re, _)| re.is_match(path))
+            .max_by_key(|(allow, _, len)| (*len, *allow))
+            .is_none_or(|(allow, _, _)| *allow)
+    }
+}
+
+/// Compile a robots.txt path pattern (`*` wildcard, trailing `$` anchor).
+fn pattern(value: &str) -> Option<Regex> {
+    let (body, anchored) = match value.strip_suffix('$') {
+        Some(body) => (body, true),
+        None => (value, false),
+    };
+    let escaped: Vec<String> = body.split('*').map(regex::escape).collect();
+    let re = format!("^{}{}", escaped.join(".*"), if anchored { "$" } else { "" });
+    Regex::new(&re).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn robots_groups_and_precedence() {
+        let txt = "\
+User-agent: Googlebot
+Disallow: /
+
+User-agent: *
+Disallow: /search
+Disallow: /*.pdf$
+Allow: /search/help
+Crawl-delay: 2
+";
+        let robots = Robots::parse(txt, "eoka");
+        assert!(robots.allows("/"));
+        assert!(!robots.allows("/search?q=x"));
+        assert!(robots.allows("/search/help"));
+        assert!(!robots.allows("/files/report.pdf"));
+        assert!(robots.allows("/files/report.pdf?v=2"));
+        assert_eq!(robots.crawl_delay, Some(Duration::from_secs(2)));
+
+        let google = Robots::parse(txt, "Googlebot");
+        assert!(!google.allows("/anything"));
+        assert!(Robots::parse("", "eoka").allows("/private"));
+    }
+
+    #[test]
+    fn origins() {
+        assert_eq!(
+            origin_of("https://Example.com:443/a?b", true).as_deref(),
+            Some("https://example.com")
+        );
+        assert_eq!(origin_of("data:text/html,hi", true), None);
+
+        let limiter = RateLimiter::new(RateLimit::default())
+            .origin("https://api.example.com", RateLimit::new().min_delay_ms(0));
+        assert_eq!(
+            limiter.limit_for("https://api.example.com/v1").min_delay_ms,
+            0
+        );
+        assert_eq!(limiter.limit_for("https://example.com/").min_delay_ms, 1000);
+    }
+
+    #[tokio::test]
+    async fn spaces_navigations_per_origin() {
+        let limiter = RateLimiter::new(RateLimit::new().min_delay_ms(100));
+        let start = Instant::now();
+        limiter.acquire("https://a.example/1").await.unwrap();
+        limiter.acquire("https://b.example/1").await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(100));
+        limiter.acquire("https://a.example/2").await.unwrap();
+        limiter.acquire("https://a.example/3").await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+}
@@ -14,6 +14,7 @@ struct JsDetectionResult {
     query: HashMap<String, String>,
     hash: String,
     can_navigate: bool,
+    hydrated: bool,
     details: Option<String>,
 }
 
@@ -26,9 +27,41 @@ const DETECT_JS: &str = r#"
     query: Object.fromEntries(new URLSearchParams(location.search)),
     hash: location.hash.slice(1),
     can_navigate: false,
+    hydrated: false,
     details: null
   };
 
+  // Hydration: the framework has attached to server-rendered markup.
+  // Pages without a recognised SSR framework count as hydrated once loaded.
+  result.hydrated = (() => {
+    if (document.readyState !== 'complete') return false;
+
+    // React (incl. Next.js and Remix) tags its root container on hydrateRoot/createRoot
+    const reactMarked = el => !!el && Object.keys(el).some(k =>
+      k.startsWith('__reactContainer$') || k.startsWith('__reactFiber$') || k === '_reactRootContainer');
+    if (window.__NEXT_DATA__ || window.__next_f || window.__remixContext || document.querySelector('[data-reactroot]')) {
+      const roots = [document, document.body, document.getElementById('__next'),
+        document.getElementById('root'), document.querySelector('[data-reactroot]')];
+      return roots.some(el => reactMarked(el) || reactMarked(el?.firstElementChild));
+    }
+
+    // Nuxt / Vue set __vue_app__ (3) or __vue__ (2) on the mounted root
+    const nuxtRoot = document.getElementById('__nuxt');
+    if (window.__NUXT__ || nuxtRoot) {
+      return !!(nuxtRoot?.__vue_app__ || nuxtRoot?.__vue__ || window.$nuxt);
+    }
+    const vueSsrRoot = document.querySelector('[data-server-rendered]');
+    if (vueSsrRoot) return !!vueSsrRoot.__vue__;
+
+    // Angular registers testabilities on bootstrap; stable means no pending work
+    if (document.querySelector('[ng-version]')) {
+      const testabilities = window.getAllAngularTestabilities?.();
+      return !!testabilities?.length && testabilities.every(t => t.isStable());
+    }
+
+    return true;
+  })();
+
   // Check for Remix (superset of React Router v6)
   if (window.__remixContext || window.__remixManifest) {
     result.router_type = 'react-router';
@@ -46,6 +79,25 @@ const DETECT_JS: &str = r#"
     return JSON.stringify(result);
   }
 
+  // Check for Nuxt before plain Vue: Nuxt 3 mounts a Vue app on #__nuxt
+  if (window.__NUXT__ || window.$nuxt || document.getElementById('__nuxt')) {
+    result.router_type = 'nuxt';
+    const nuxt3Router = document.getElementById('__nuxt')?.__vue_app__?.config?.globalProperties?.$router;
+    result.can_navigate = !!(nuxt3Router || window.$nuxt?.$router);
+    result.details = nuxt3Router ? 'Nuxt 3' : (window.$nuxt ? 'Nuxt 2' : 'Nuxt (router not found)');
+    return JSON.stringify(result);
+  }
+
+  // Check for SvelteKit: its globals are named __sveltekit_<hash>
+  const hasSvelteKitGlobal = Object.keys(window).some(k => k.startsWith('__sveltekit'));
+  if (hasSvelteKitGlobal || document.querySelector('[data-sveltekit-preload-data], [data-sveltekit-hydrate]')) {
+    result.router_type = 'sveltekit';
+    // goto() is not exposed, but the router intercepts same-origin link clicks
+    result.can_navigate = true;
+    result.details = 'SvelteKit (via link interception)';
+    return JSON.stringify(result);
+  }
+
   // Check for Vue 3 with Vue Router
   const vueApp = document.querySelector('[data-v-app]')?.__vue_app__;
   if (vueApp?.config?.globalProperties?.$router) {
@@ -64,6 +116,15 @@ const DETECT_JS: &str = r#"
     return JSON.stringify(result);
   }
 
+  // Check for hash routing (#/path or #!/path) before Angular and the History API
+  // fallback: pushState to a plain path breaks these apps
+  if (/^#!?\//.test(location.hash)) {
+    result.router_type = 'hash-router';
+    result.can_navigate = true;
+    result.details = window.angular ? 'AngularJS (hash routing)' : 'Hash routing';
+    return JSON.stringify(result);
+  }
+
   // Check for Angular
   if (window.ng || document.querySelector('[ng-version]')) {
     result.router_type = 'angular-router';
@@ -109,7 +170,10 @@ pub async fn detect_router(page: &Page) -> Result<SpaRouterInfo> {
         "react-router" => RouterType::ReactRouter,
         "nextjs" => RouterType::NextJs,
         "vue-router" => RouterType::VueRouter,
+        "nuxt" => RouterType::Nuxt,
+        "sveltekit" => RouterType::SvelteKit,
         "angular-router" => RouterType::AngularRouter,
+        "hash-router" => RouterType::HashRouter,
         "history-api" => RouterType::HistoryApi,
         _ => RouterType::Unknown,
     };
@@ -120,10 +184,32 @@ pub async fn detect_router(page: &Page) -> Result<SpaRouterInfo> {
         query_params: raw.query,
         hash: raw.hash,
         can_navigate: raw.can_navigate,
+        hydrated: raw.hydrated,
         details: raw.details,
     })
 }
 
+/// Wait until the framework has hydrated the page (see [`SpaRouterInfo::hydrated`]).
+///
+/// Clicks on server-rendered buttons before hydration are silently lost,
+/// so call this after navigating to an SSR page and before interacting.
+pub async fn wait_for_hydration(page: &Page, timeout_ms: u64) -> Result<SpaRouterInfo> {
+    let start = std::time::Instant::now();
+    loop {
+        let info = detect_router(page).await?;
+        if info.hydrated {
+            return Ok(info);
+        }
+        if start.elapsed().as_millis() as u64 > timeout_ms {
+            return Err(eoka::Error::Timeout(format!(
+                "{} page not hydrated within {}ms",
+                info.router_type, timeout_ms
+            )));
+        }
+        page.wait(100).await;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -133,7 +219,10 @@ mod tests {
         assert_eq!(RouterType::ReactRouter.to_string(), "React Router");
         assert_eq!(RouterType::NextJs.to_string(), "Next.js");
         assert_eq!(RouterType::VueRouter.to_string(), "Vue Router");
+        assert_eq!(RouterType::Nuxt.to_string(), "Nuxt");
+        assert_eq!(RouterType::SvelteKit.to_string(), "SvelteKit");
         assert_eq!(RouterType::AngularRouter.to_string(), "Angular Router");
+        assert_eq!(RouterType::HashRouter.to_string(), "Hash Router");
         assert_eq!(RouterType::HistoryApi.to_string(), "History API");
         assert_eq!(RouterType::Unknown.to_string(), "Unknown");
     }
@@ -6,14 +6,19 @@
 //! - React Router (v5 and v6)
 //! - Next.js (App Router and Pages Router)
 //! - Vue Router
+//! - Nuxt (2 and 3)
+//! - SvelteKit
 //! - Remix
+//! - Hash-based routers (`#/path`, `#!/path`)
 //! - History API fallback (works with any SPA)
 
 mod detect;
 mod navigate;
+mod route;
 
-pub use detect::detect_router;
-pub use navigate::{history_go, spa_navigate};
+pub use detect::{detect_router, wait_for_hydration};
+pub use navigate::{history_go, spa_navigate, spa_set_hash, spa_set_query};
+pub use route::{wait_for_route_change, watch_route_changes};
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -28,8 +33,14 @@ pub enum RouterType {
     NextJs,
     /// Vue Router
     VueRouter,
+    /// Nuxt (2 or 3), navigated through its Vue Router instance
+    Nuxt,
+    /// SvelteKit, navigated through its link interception
+    SvelteKit,
     /// Angular Router
     AngularRouter,
+    /// Route lives in the hash (`#/path`), as in AngularJS or Vue hash mode
+    HashRouter,
     /// History API (fallback, works with most SPAs)
     HistoryApi,
     /// Could not detect any SPA router
@@ -42,7 +53,10 @@ impl std::fmt::Display for RouterType {
             RouterType::ReactRouter => write!(f, "React Router"),
             RouterType::NextJs => write!(f, "Next.js"),
             RouterType::VueRouter => write!(f, "Vue Router"),
+            RouterType::Nuxt => write!(f, "Nuxt"),
+            RouterType::SvelteKit => write!(f, "SvelteKit"),
             RouterType::AngularRouter => write!(f, "Angular Router"),
+            RouterType::HashRouter => write!(f, "Hash Router"),
             RouterType::HistoryApi => write!(f, "History API"),
             RouterType::Unknown => write!(f, "Unknown"),
         }
@@ -62,6 +76,10 @@ pub struct SpaRouterInfo {
     pub hash: String,
     /// Whether programmatic navigation is available.
     pub can_navigate: bool,
+    /// Whether the framework has attached to server-rendered markup
+    /// (React/Next root markers, Vue app on its root, Angular stable) and the
+    /// page has finished loading. True for pages with no SSR framework.
+    pub hydrated: bool,
     /// Additional router-specific details.
     pub details: Option<String>,
 }
@@ -81,6 +99,7 @@ impl std::fmt::Display for SpaRouterInfo {
             "Can navigate: {}",
             if self.can_navigate { "yes" } else { "no" }
         )?;
+        writeln!(f, "Hydrated: {}", if self.hydrated { "yes" } else { "no" })?;
         if let Some(ref details) = self.details {
             writeln!(f, "Details: {}", details)?;
         }
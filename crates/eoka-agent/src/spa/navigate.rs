@@ -1,6 +1,7 @@
 //! SPA navigation logic.
 
 use eoka::{Page, Result};
+use std::collections::HashMap;
 
 use super::RouterType;
 
@@ -48,13 +49,53 @@ const NAVIGATE_JS: &str = r#"
         }
         break;
 
+      case 'nuxt':
+        // Nuxt 3 (Vue app on #__nuxt) or Nuxt 2 ($nuxt)
+        const nuxtRouter = document.getElementById('__nuxt')?.__vue_app__?.config?.globalProperties?.$router
+          || window.$nuxt?.$router;
+        if (nuxtRouter) {
+          nuxtRouter.push(path);
+          result.success = true;
+          result.newPath = path;
+        } else {
+          result.error = 'Nuxt router not found';
+        }
+        break;
+
+      case 'sveltekit':
+        // SvelteKit ignores pushState; click a same-origin link so its router handles it
+        const link = document.createElement('a');
+        link.href = path;
+        link.style.display = 'none';
+        document.body.appendChild(link);
+        link.click();
+        link.remove();
+        result.success = true;
+        result.newPath = path;
+        break;
+
+      case 'hash-router':
+        // Route lives in the hash: '/x' becomes '#/x' (or '#!/x'); setting
+        // location.hash fires hashchange without a reload
+        const prefix = location.hash.startsWith('#!') ? '#!' : '#';
+        const target = path.startsWith('#') ? path : prefix + (path.startsWith('/') ? '' : '/') + path;
+        location.hash = target.slice(1);
+        result.success = true;
+        result.newPath = location.hash;
+        break;
+
       case 'react-router':
       case 'angular-router':
       case 'history-api':
       default:
         // Use History API + popstate event (works for most SPAs)
+        const oldUrl = location.href;
         history.pushState({}, '', path);
         window.dispatchEvent(new PopStateEvent('popstate', { state: {} }));
+        // pushState never fires hashchange; hash-driven UIs listen for it
+        if (new URL(oldUrl).hash !== location.hash) {
+          window.dispatchEvent(new HashChangeEvent('hashchange', { oldURL: oldUrl, newURL: location.href }));
+        }
         result.success = true;
         result.newPath = location.pathname;
         break;
@@ -81,6 +122,29 @@ const HISTORY_GO_JS: &str = r#"
 })
 "#;
 
+/// JavaScript that builds the current path with query params and/or hash replaced.
+/// An empty param value removes the param; a null hash keeps the current one.
+/// With a hash router the route, and its query, live inside the hash.
+const TARGET_PATH_JS: &str = r#"
+((query, hash, hashRouter) => {
+  if (hashRouter) {
+    const route = new URL(location.hash.replace(/^#!?/, '') || '/', location.origin);
+    for (const [key, value] of Object.entries(query)) {
+      if (value === '') route.searchParams.delete(key);
+      else route.searchParams.set(key, value);
+    }
+    return route.pathname + route.search;
+  }
+  const url = new URL(location.href);
+  for (const [key, value] of Object.entries(query)) {
+    if (value === '') url.searchParams.delete(key);
+    else url.searchParams.set(key, value);
+  }
+  if (hash !== null) url.hash = hash;
+  return url.pathname + url.search + url.hash;
+})
+"#;
+
 /// Result from navigation JavaScript.
 #[derive(Debug, serde::Deserialize)]
 struct NavResult {
@@ -99,7 +163,10 @@ pub async fn spa_navigate(page: &Page, router_type: &RouterType, path: &str) ->
         RouterType::ReactRouter => "react-router",
         RouterType::NextJs => "nextjs",
         RouterType::VueRouter => "vue-router",
+        RouterType::Nuxt => "nuxt",
+        RouterType::SvelteKit => "sveltekit",
         RouterType::AngularRouter => "angular-router",
+        RouterType::HashRouter => "hash-router",
         RouterType::HistoryApi => "history-api",
         RouterType::Unknown => "history-api", // Fallback
     };
@@ -127,6 +194,45 @@ pub async fn spa_navigate(page: &Page, router_type: &RouterType, path: &str) ->
     }
 }
 
+/// Set query params on the current path through the router, keeping the
+/// other params. An empty value removes that param. Returns the new path.
+pub async fn spa_set_query(
+    page: &Page,
+    router_type: &RouterType,
+    params: &HashMap<String, String>,
+) -> Result<String> {
+    let path = target_path(page, router_type, params, None).await?;
+    spa_navigate(page, router_type, &path).await
+}
+
+/// Set the hash fragment (with or without `#`) through the router.
+/// With a [`RouterType::HashRouter`] the fragment is the route itself.
+/// Returns the new path.
+pub async fn spa_set_hash(page: &Page, router_type: &RouterType, fragment: &str) -> Result<String> {
+    let fragment = fragment.strip_prefix('#').unwrap_or(fragment);
+    if *router_type == RouterType::HashRouter {
+        return spa_navigate(page, router_type, &format!("#{}", fragment)).await;
+    }
+    let path = target_path(page, router_type, &HashMap::new(), Some(fragment)).await?;
+    spa_navigate(page, router_type, &path).await
+}
+
+async fn target_path(
+    page: &Page,
+    router_type: &RouterType,
+    query: &HashMap<String, String>,
+    hash: Option<&str>,
+) -> Result<String> {
+    let js = format!(
+        "{}({}, {}, {})",
+        TARGET_PATH_JS,
+        serde_json::to_string(query).unwrap(),
+        serde_json::to_string(&hash).unwrap(),
+        *router_type == RouterType::HashRouter
+    );
+    page.evaluate(&js).await
+}
+
 /// Navigate browser history by delta steps.
 ///
 /// - delta = -1: go back one step
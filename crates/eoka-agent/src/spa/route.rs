@@ -0,0 +1,91 @@
+//! Waiting for client-side route changes.
+
+use eoka::{Page, Result};
+
+/// JavaScript that hooks the History API and navigation events once per document.
+/// Each change of path, query or hash bumps `count`; `seen` is how far
+/// [`wait_for_route_change`] has consumed.
+const WATCH_JS: &str = r#"
+(() => {
+  if (window.__eokaRoutes) return;
+  const current = () => location.pathname + location.search + location.hash;
+  const state = { count: 0, seen: 0, path: current() };
+  const record = () => {
+    const path = current();
+    if (path !== state.path) {
+      state.path = path;
+      state.count++;
+    }
+  };
+  for (const method of ['pushState', 'replaceState']) {
+    const original = history[method];
+    history[method] = function (...args) {
+      const ret = original.apply(this, args);
+      record();
+      return ret;
+    };
+  }
+  window.addEventListener('popstate', record);
+  window.addEventListener('hashchange', record);
+  // Catches routers that hold their own reference to the original pushState
+  window.navigation?.addEventListener('currententrychange', record);
+  window.__eokaRoutes = state;
+})()
+"#;
+
+/// JavaScript that consumes an unseen route change matching a pattern.
+/// Returns the new path, or '' if there is none yet.
+const POLL_JS: &str = r#"
+((pattern) => {
+  const state = window.__eokaRoutes;
+  if (!state || state.count === state.seen) return '';
+  if (pattern && !state.path.includes(pattern)) return '';
+  state.seen = state.count;
+  return state.path;
+})
+"#;
+
+/// Start recording route changes on the page.
+///
+/// [`wait_for_route_change`] does this itself, but a transition that happens
+/// before the hooks are in place is missed, so call this before the click
+/// that triggers the transition. Hooks are lost on a full page load.
+pub async fn watch_route_changes(page: &Page) -> Result<()> {
+    page.execute(WATCH_JS).await
+}
+
+/// Wait until the SPA router changes the path, query or hash.
+///
+/// Changes recorded since the previous call (or since
+/// [`watch_route_changes`]) count, so a transition that finished before this
+/// call still resolves it. With `path_pattern`, only a route whose path
+/// (with query and hash) contains the pattern counts. Returns the new path.
+pub async fn wait_for_route_change(
+    page: &Page,
+    timeout_ms: u64,
+    path_pattern: Option<&str>,
+) -> Result<String> {
+    let start = std::time::Instant::now();
+    let js = format!(
+        "{}({})",
+        POLL_JS,
+        serde_json::to_string(&path_pattern.unwrap_or("")).unwrap()
+    );
+
+    loop {
+        // Re-installs after a full page load wiped the hooks
+        watch_route_changes(page).await?;
+        let path: String = page.evaluate(&js).await?;
+        if !path.is_empty() {
+            return Ok(path);
+        }
+
+        if start.elapsed().as_millis() as u64 > timeout_ms {
+            return Err(eoka::Error::Timeout(match path_pattern {
+                Some(p) => format!("no route change to '{}' within {}ms", p, timeout_ms),
+                None => format!("no route change within {}ms", timeout_ms),
+            }));
+        }
+        page.wait(50).await;
+    }
+}
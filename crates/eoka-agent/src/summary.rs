@@ -0,0 +1,198 @@
+//! Page summary — element counts, page size and detected frameworks. A
+//! cheap first look for deciding between `observe`, `page_text` and a
+//! screenshot before paying for any of them.
+
+use eoka::{Page, Result};
+use serde::Deserialize;
+use std::fmt;
+
+/// Counts and sizes describing how complex a page is.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct PageSummary {
+    pub url: String,
+    pub title: String,
+    /// `<a href>` elements
+    pub links: usize,
+    /// `<button>`, `[role=button]` and button-type inputs
+    pub buttons: usize,
+    /// Text fields, checkboxes, radios, selects, textareas and editable regions
+    pub inputs: usize,
+    pub forms: usize,
+    pub iframes: usize,
+    pub images: usize,
+    pub canvases: usize,
+    /// Length of the page's visible text, in characters
+    pub text_chars: usize,
+    /// Full scrollable size of the document
+    pub page_width: f64,
+    pub page_height: f64,
+    pub viewport_width: f64,
+    pub viewport_height: f64,
+    /// Frameworks and platforms found on the page, e.g. `React`, `Next.js`
+    pub frameworks: Vec<String>,
+}
+
+impl PageSummary {
+    /// Links, buttons and inputs together.
+    pub fn interactive(&self) -> usize {
+        self.links + self.buttons + self.inputs
+    }
+
+    /// How many viewports tall the page is.
+    pub fn screens(&self) -> f64 {
+        if self.viewport_height > 0.0 {
+            self.page_height / self.viewport_height
+        } else {
+            0.0
+        }
+    }
+
+    /// Which tool is likely the best next look at this page.
+    pub fn suggestion(&self) -> &'static str {
+        if self.canvases > 0 && self.interactive() < 5 {
+            "screenshot (canvas-drawn, little DOM to observe)"
+        } else if self.interactive() > 150 {
+            "observe with a region or max_tokens (many interactive elements)"
+        } else if self.inputs == 0 && self.text_chars > 5000 {
+            "page_text (text-heavy, no form fields)"
+        } else {
+            "observe"
+        }
+    }
+}
+
+impl fmt::Display for PageSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "URL: {}", self.url)?;
+        writeln!(f, "Title: {}", self.title)?;
+        writeln!(
+            f,
+            "Elements: {} links, {} buttons, {} inputs, {} forms, {} iframes, {} images, {} canvases",
+            self.links,
+            self.buttons,
+            self.inputs,
+            self.forms,
+            self.iframes,
+            self.images,
+            self.canvases
+        )?;
+        writeln!(
+            f,
+            "Page: {:.0}x{:.0} (viewport {:.0}x{:.0}, {:.1} screens)",
+            self.page_width,
+            self.page_height,
+            self.viewport_width,
+            self.viewport_height,
+            self.screens()
+        )?;
+        writeln!(f, "Text: {} chars", self.text_chars)?;
+        if self.frameworks.is_empty() {
+            writeln!(f, "Frameworks: none detected")?;
+        } else {
+            writeln!(f, "Frameworks: {}", self.frameworks.join(", "))?;
+        }
+        write!(f, "Suggested: {}", self.suggestion())
+    }
+}
+
+/// JavaScript that counts elements and sniffs frameworks from globals,
+/// attributes and meta tags.
+const SUMMARY_JS: &str = r#"
+(() => {
+  const count = sel => document.querySelectorAll(sel).length;
+  const de = document.documentElement;
+  const body = document.body;
+
+  const frameworks = [];
+  const has = (name, test) => {
+    try { if (test()) frameworks.push(name); } catch {}
+  };
+  const reactRoot = () => [...document.querySelectorAll('body > div, #root, #__next, #app')]
+    .some(el => Object.keys(el).some(k => k.startsWith('__react')));
+  has('React', () => window.React || window.__REACT_DEVTOOLS_GLOBAL_HOOK__?.renderers?.size || document.querySelector('[data-reactroot]') || reactRoot());
+  has('Next.js', () => window.__NEXT_DATA__ || window.next?.router || document.getElementById('__next'));
+  has('Remix', () => window.__remixContext || window.__remixRouter);
+  has('Gatsby', () => document.getElementById('___gatsby'));
+  has('Vue', () => window.Vue || window.__VUE__ || document.querySelector('[data-v-app]') || document.querySelector('#app')?.__vue_app__);
+  has('Nuxt', () => window.__NUXT__ || window.$nuxt || document.getElementById('__nuxt'));
+  has('Angular', () => document.querySelector('[ng-version]'));
+  has('AngularJS', () => window.angular?.version);
+  has('Svelte', () => document.querySelector('[class*="svelte-"]'));
+  has('SvelteKit', () => Object.keys(window).some(k => k.startsWith('__sveltekit')));
+  has('Astro', () => document.querySelector('astro-island'));
+  has('Ember', () => window.Ember);
+  has('jQuery', () => window.jQuery);
+  has('WordPress', () => /wordpress/i.test(document.querySelector('meta[name="generator"]')?.content || ''));
+  has('Shopify', () => window.Shopify);
+
+  return {
+    url: location.href,
+    title: document.title,
+    links: count('a[href]'),
+    buttons: count('button, [role="button"], input[type="button"], input[type="submit"], input[type="reset"]'),
+    inputs: count('input:not([type="hidden"]):not([type="button"]):not([type="submit"]):not([type="reset"]), select, textarea, [contenteditable=""], [contenteditable="true"]'),
+    forms: count('form'),
+    iframes: count('iframe, frame'),
+    images: count('img'),
+    canvases: count('canvas'),
+    text_chars: (body?.innerText || '').length,
+    page_width: Math.max(de.scrollWidth, body?.scrollWidth || 0),
+    page_height: Math.max(de.scrollHeight, body?.scrollHeight || 0),
+    viewport_width: window.innerWidth,
+    viewport_height: window.innerHeight,
+    frameworks,
+  };
+})()
+"#;
+
+/// Count the page's elements and detect its frameworks.
+pub async fn page_summary(page: &Page) -> Result<PageSummary> {
+    page.evaluate(SUMMARY_JS).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_and_suggestion() {
+        let mut s = PageSummary {
+            url: "https://example.com/".into(),
+            title: "Example".into(),
+            links: 12,
+            buttons: 3,
+            inputs: 2,
+            forms: 1,
+            images: 4,
+            text_chars: 1800,
+            page_width: 1280.0,
+            page_height: 2160.0,
+            viewport_width: 1280.0,
+            viewport_height: 720.0,
+            frameworks: vec!["React".into(), "Next.js".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            s.to_string(),
+            "URL: https://example.com/\n\
+             Title: Example\n\
+             Elements: 12 links, 3 buttons, 2 inputs, 1 forms, 0 iframes, 4 images, 0 canvases\n\
+             Page: 1280x2160 (viewport 1280x720, 3.0 screens)\n\
+             Text: 1800 chars\n\
+             Frameworks: React, Next.js\n\
+             Suggested: observe"
+        );
+
+        s.inputs = 0;
+        s.text_chars = 20_000;
+        assert!(s.suggestion().starts_with("page_text"));
+        s.links = 400;
+        assert!(s.suggestion().starts_with("observe with a region"));
+        s = PageSummary {
+            canvases: 1,
+            ..Default::default()
+        };
+        assert!(s.suggestion().starts_with("screenshot"));
+        assert_eq!(s.screens(), 0.0);
+    }
+}
@@ -1,7 +1,10 @@
 //! Live element targeting - resolves elements at action time via JS.
+//!
+//! The patterns and their matching live in [`eoka_runner::target`], so
+//! `label:Email` here finds the same element as `label: "Email"` in a
+//! runner config.
 
-use eoka::{Page, Result};
-use serde::Deserialize;
+pub use eoka_runner::target::{resolve, BBox, LivePattern, Resolved};
 
 /// Target selector - either an index or a live pattern.
 #[derive(Debug, Clone)]
@@ -12,21 +15,6 @@ pub enum Target {
     Live(LivePattern),
 }
 
-/// Live targeting patterns - all resolved via JS injection.
-#[derive(Debug, Clone)]
-pub enum LivePattern {
-    /// `text:Submit` - find by visible text
-    Text(String),
-    /// `placeholder:Enter code` - find by placeholder
-    Placeholder(String),
-    /// `role:button` - find by tag/ARIA role
-    Role(String),
-    /// `css:form button` - direct CSS selector
-    Css(String),
-    /// `id:submit-btn` - find by ID
-    Id(String),
-}
-
 impl Target {
     /// Parse target string. Numbers become Index, everything else is Live.
     pub fn parse(s: &str) -> Self {
@@ -42,137 +30,6 @@ impl Target {
     }
 }
 
-impl LivePattern {
-    /// Parse a live pattern. Unprefixed strings default to text search.
-    pub fn parse(s: &str) -> Self {
-        if let Some(v) = s.strip_prefix("text:") {
-            return LivePattern::Text(v.into());
-        }
-        if let Some(v) = s.strip_prefix("placeholder:") {
-            return LivePattern::Placeholder(v.into());
-        }
-        if let Some(v) = s.strip_prefix("role:") {
-            return LivePattern::Role(v.into());
-        }
-        if let Some(v) = s.strip_prefix("css:") {
-            return LivePattern::Css(v.into());
-        }
-        if let Some(v) = s.strip_prefix("id:") {
-            return LivePattern::Id(v.into());
-        }
-        // Default: treat as text search
-        LivePattern::Text(s.into())
-    }
-
-    fn as_js_args(&self) -> (&'static str, &str) {
-        match self {
-            LivePattern::Text(v) => ("text", v),
-            LivePattern::Placeholder(v) => ("placeholder", v),
-            LivePattern::Role(v) => ("role", v),
-            LivePattern::Css(v) => ("css", v),
-            LivePattern::Id(v) => ("id", v),
-        }
-    }
-}
-
-/// Bounding box.
-#[derive(Debug, Deserialize, Clone, Default)]
-pub struct BBox {
-    pub x: f64,
-    pub y: f64,
-    pub width: f64,
-    pub height: f64,
-}
-
-/// Result from live resolution.
-#[derive(Debug, Deserialize)]
-pub struct Resolved {
-    pub selector: String,
-    pub tag: String,
-    pub text: String,
-    pub found: bool,
-    #[serde(default)]
-    pub error: Option<String>,
-    #[serde(default)]
-    pub bbox: BBox,
-}
-
-const RESOLVE_JS: &str = r#"
-((type, value) => {
-    const lc = s => (s || '').toLowerCase().trim();
-    const valLc = lc(value);
-
-    function selector(el) {
-        if (el.id) return '#' + CSS.escape(el.id);
-        const path = [];
-        let n = el;
-        while (n && n.nodeType === 1) {
-            let s = n.tagName.toLowerCase();
-            if (n.id) { path.unshift('#' + CSS.escape(n.id)); break; }
-            const p = n.parentElement;
-            if (p) {
-                const sibs = [...p.children].filter(c => c.tagName === n.tagName);
-                if (sibs.length > 1) s += ':nth-of-type(' + (sibs.indexOf(n) + 1) + ')';
-            }
-            path.unshift(s);
-            n = p;
-        }
-        return path.join(' > ');
-    }
-
-    function text(el) {
-        return el.innerText?.trim() || el.value || el.getAttribute('aria-label') || el.title || el.placeholder || '';
-    }
-
-    function interactive() {
-        return [...document.querySelectorAll('a,button,input,select,textarea,[role="button"],[onclick],[tabindex]')]
-            .filter(el => {
-                const r = el.getBoundingClientRect();
-                const s = getComputedStyle(el);
-                return r.width > 0 && r.height > 0 && s.visibility !== 'hidden' && s.display !== 'none';
-            });
-    }
-
-    let el = null;
-    switch (type) {
-        case 'text':
-            el = interactive().find(e => lc(text(e)).includes(valLc));
-            break;
-        case 'placeholder':
-            el = document.querySelector(`input[placeholder*="${value}" i],textarea[placeholder*="${value}" i]`)
-                || interactive().find(e => lc(e.placeholder).includes(valLc));
-            break;
-        case 'role':
-            el = document.querySelector(valLc) || document.querySelector(`[role="${value}"]`)
-                || interactive().find(e => e.tagName.toLowerCase() === valLc || e.getAttribute('role') === value);
-            break;
-        case 'css':
-            el = document.querySelector(value);
-            break;
-        case 'id':
-            el = document.getElementById(value);
-            break;
-    }
-
-    if (!el) return { found: false, error: `${type}:${value} not found`, selector: '', tag: '', text: '', bbox: {x:0,y:0,width:0,height:0} };
-
-    const r = el.getBoundingClientRect();
-    return { found: true, selector: selector(el), tag: el.tagName.toLowerCase(), text: text(el).slice(0, 50), bbox: {x:r.x,y:r.y,width:r.width,height:r.height} };
-})
-"#;
-
-/// Resolve a live pattern to element info via JS.
-pub async fn resolve(page: &Page, pattern: &LivePattern) -> Result<Resolved> {
-    let (t, v) = pattern.as_js_args();
-    let js = format!(
-        "{}({},{})",
-        RESOLVE_JS,
-        serde_json::to_string(t).unwrap(),
-        serde_json::to_string(v).unwrap()
-    );
-    page.evaluate(&js).await
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,292 @@
+//! Local fixture server for tests and examples — serves a directory or a
+//! few inline pages on `127.0.0.1`, so pages load from a real `http://`
+//! origin instead of a `data:` URL (which has no cookies, storage or
+//! same-origin requests, and breaks on `#` and `%` in the markup).
+//!
+//! ```rust,no_run
+//! # fn example() -> std::io::Result<()> {
+//! use eoka_agent::test_server::{self, Route};
+//!
+//! let server = test_server::serve([
+//!     ("/", r#"<a href="/next">Next</a>"#),
+//!     ("/next", "<h1>Done</h1>"),
+//! ])?;
+//! let url = server.url_for("/next");
+//!
+//! let api = test_server::serve(vec![
+//!     Route::new("/api", "application/json", r#"{"ok":true}"#).header("Set-Cookie", "sid=1"),
+//! ])?;
+//! let site = test_server::serve(std::path::Path::new("tests/fixtures"))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// One inline response.
+#[derive(Debug, Clone)]
+pub struct Route {
+    /// Request path it answers, without the query string, e.g. `/login`.
+    pub path: String,
+    pub status: u16,
+    pub content_type: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Route {
+    pub fn new(path: &str, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            path: path.into(),
+            status: 200,
+            content_type: content_type.into(),
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// A `text/html` page.
+    pub fn html(path: &str, body: &str) -> Self {
+        Self::new(path, "text/html; charset=utf-8", body)
+    }
+
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Add a response header, e.g. `Set-Cookie` or `Content-Security-Policy`.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// What [`serve`] serves.
+#[derive(Debug, Clone)]
+pub enum Fixtures {
+    /// Files under a directory; `/` and directories serve `index.html`.
+    Dir(PathBuf),
+    /// Inline routes; other paths get a 404.
+    Routes(Vec<Route>),
+}
+
+impl From<&Path> for Fixtures {
+    fn from(dir: &Path) -> Self {
+        Self::Dir(dir.to_path_buf())
+    }
+}
+
+impl From<PathBuf> for Fixtures {
+    fn from(dir: PathBuf) -> Self {
+        Self::Dir(dir)
+    }
+}
+
+impl From<Vec<Route>> for Fixtures {
+    fn from(routes: Vec<Route>) -> Self {
+        Self::Routes(routes)
+    }
+}
+
+/// `(path, html)` pages.
+impl From<&[(&str, &str)]> for Fixtures {
+    fn from(pages: &[(&str, &str)]) -> Self {
+        Self::Routes(pages.iter().map(|(p, html)| Route::html(p, html)).collect())
+    }
+}
+
+impl<const N: usize> From<[(&str, &str); N]> for Fixtures {
+    fn from(pages: [(&str, &str); N]) -> Self {
+        Self::from(&pages[..])
+    }
+}
+
+/// A running fixture server; stops when dropped.
+#[derive(Debug)]
+pub struct TestServer {
+    url: String,
+    stop: Arc<AtomicBool>,
+}
+
+impl TestServer {
+    /// Base URL, e.g. `http://127.0.0.1:4123`.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Absolute URL of `path`.
+    pub fn url_for(&self, path: &str) -> String {
+        format!("{}/{}", self.url, path.trim_start_matches('/'))
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag
+        let _ = TcpStream::connect(self.url.trim_start_matches("http://"));
+    }
+}
+
+/// Serve `fixtures` on a free local port, on a background thread. Any
+/// method is answered by path; the query string is ignored.
+pub fn serve(fixtures: impl Into<Fixtures>) -> io::Result<TestServer> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let url = format!("http://{}", listener.local_addr()?);
+    let stop = Arc::new(AtomicBool::new(false));
+    let fixtures = Arc::new(fixtures.into());
+    let stopped = Arc::clone(&stop);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            if stopped.load(Ordering::SeqCst) {
+                break;
+            }
+            let Ok(stream) = stream else { continue };
+            let fixtures = Arc::clone(&fixtures);
+            // Chrome opens speculative connections that never send a request
+            std::thread::spawn(move || handle(stream, &fixtures));
+        }
+    });
+    Ok(TestServer { url, stop })
+}
+
+fn handle(stream: TcpStream, fixtures: &Fixtures) {
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    if reader.read_line(&mut request).is_err() {
+        return;
+    }
+    // Drain the headers; a body, if any, is ignored
+    let mut line = String::new();
+    while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+        line.clear();
+    }
+    let target = request.split_whitespace().nth(1).unwrap_or("/");
+    let path = target.split(['?', '#']).next().unwrap_or("/");
+    let response = match fixtures {
+        Fixtures::Routes(routes) => routes.iter().find(|r| r.path == path).cloned(),
+        Fixtures::Dir(dir) => from_dir(dir, path),
+    }
+    .unwrap_or_else(|| Route::new(path, "text/plain", "not found").status(404));
+    let _ = write_response(&stream, &response);
+}
+
+fn write_response(mut stream: &TcpStream, route: &Route) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        route.status,
+        reason(route.status),
+        route.content_type,
+        route.body.len()
+    )?;
+    for (name, value) in &route.headers {
+        write!(stream, "{}: {}\r\n", name, value)?;
+    }
+    stream.write_all(b"\r\n")?;
+    stream.write_all(&route.body)
+}
+
+/// The file for `path` under `dir`; `None` for anything outside it.
+fn from_dir(dir: &Path, path: &str) -> Option<Route> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+    let mut file = dir.join(relative);
+    if file.is_dir() {
+        file.push("index.html");
+    }
+    let body = std::fs::read(&file).ok()?;
+    Some(Route::new(path, content_type(&file), body))
+}
+
+fn content_type(file: &Path) -> &'static str {
+    match file.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "js" | "mjs" => "text/javascript",
+        "css" => "text/css",
+        "json" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "wasm" => "application/wasm",
+        "txt" => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(url: &str) -> String {
+        let rest = url.trim_start_matches("http://");
+        let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        let path = if path.is_empty() { "/" } else { path };
+        let mut stream = TcpStream::connect(host).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", path, host).unwrap();
+        let mut response = String::new();
+        io::Read::read_to_string(&mut stream, &mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn serves_routes_and_files() {
+        let server = serve(vec![
+            Route::html("/", "<h1>Home</h1>"),
+            Route::new("/api", "application/json", "{}")
+                .status(201)
+                .header("Set-Cookie", "sid=1"),
+        ])
+        .unwrap();
+        let home = get(&server.url_for("/?q=1"));
+        assert!(home.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(home.ends_with("\r\n\r\n<h1>Home</h1>"));
+        let api = get(&server.url_for("api"));
+        assert!(api.starts_with("HTTP/1.1 201 Created\r\n"));
+        assert!(api.contains("Set-Cookie: sid=1\r\n"));
+        assert!(get(&server.url_for("/missing")).starts_with("HTTP/1.1 404"));
+
+        let dir = std::env::temp_dir().join(format!("eoka-test-server-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("css")).unwrap();
+        std::fs::write(dir.join("index.html"), "<p>index</p>").unwrap();
+        std::fs::write(dir.join("css/site.css"), "p {}").unwrap();
+        let site = serve(dir.as_path()).unwrap();
+        assert!(get(site.url()).ends_with("<p>index</p>"));
+        let css = get(&site.url_for("/css/site.css"));
+        assert!(css.contains("Content-Type: text/css\r\n"));
+        assert!(get(&site.url_for("/../etc/passwd")).starts_with("HTTP/1.1 404"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
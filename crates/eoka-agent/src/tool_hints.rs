@@ -0,0 +1,173 @@
+//! MCP tool annotations — whether each tool only reads, can do something
+//! irreversible, or is safe to repeat, so clients with safety policies can
+//! auto-approve reads and gate the rest. Per-tool overrides come from the
+//! JSON file named by `EOKA_TOOL_HINTS`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Context;
+use rmcp::model::{Tool, ToolAnnotations};
+
+/// Tools that don't change the page, the browser or anything outside it.
+const READ_ONLY: &[&str] = &[
+    "list_tabs",
+    "observe_tab",
+    "observe",
+    "screenshot",
+    "som_bundle",
+    "find_text",
+    "page_text",
+    "page_info",
+    "page_summary",
+    "action_log",
+    "detect_overlays",
+    "spa_info",
+    "cookies",
+    "detect_captcha",
+    "email_wait",
+    "email_extract_code",
+    "email_extract_link",
+];
+
+/// Tools that can submit, delete or run arbitrary code: a click may place
+/// an order, and JavaScript or a CDP command may do anything.
+const DESTRUCTIVE: &[&str] = &[
+    "close_tab",
+    "click",
+    "click_at",
+    "type_key",
+    "batch",
+    "extract",
+    "paginate_extract",
+    "run_runner_config",
+    "login",
+    "exec",
+    "cdp",
+    "set_cookie",
+    "close",
+];
+
+/// Tools that change state but leave it the same when repeated with the
+/// same arguments.
+const IDEMPOTENT: &[&str] = &[
+    "switch_tab",
+    "close_tab",
+    "navigate",
+    "fill",
+    "select",
+    "hover",
+    "move_mouse",
+    "scroll",
+    "spa_navigate",
+    "spa_set_query",
+    "spa_set_hash",
+    "set_cookie",
+    "inject_captcha_token",
+    "email_open_link_in_browser",
+    "close",
+];
+
+/// Tools that only touch this server's own state, not the web.
+const CLOSED_WORLD: &[&str] = &["action_log", "email_extract_code", "email_extract_link"];
+
+/// Tools that change state additively and aren't safe to repeat.
+#[cfg(test)]
+const NEITHER: &[&str] = &[
+    "new_tab",
+    "type_text",
+    "back",
+    "forward",
+    "history_go",
+    "solve_captcha",
+];
+
+/// Whether the lists above cover `tool`.
+#[cfg(test)]
+pub fn known(tool: &str) -> bool {
+    [READ_ONLY, DESTRUCTIVE, IDEMPOTENT, CLOSED_WORLD]
+        .iter()
+        .any(|list| list.contains(&tool))
+        || NEITHER.contains(&tool)
+}
+
+/// Built-in hints for `tool`.
+pub fn defaults(tool: &str) -> ToolAnnotations {
+    let read_only = READ_ONLY.contains(&tool);
+    ToolAnnotations {
+        title: None,
+        read_only_hint: Some(read_only),
+        destructive_hint: Some(DESTRUCTIVE.contains(&tool)),
+        idempotent_hint: Some(read_only || IDEMPOTENT.contains(&tool)),
+        open_world_hint: Some(!CLOSED_WORLD.contains(&tool)),
+    }
+}
+
+/// Hints for every tool: the defaults, with any overrides laid over them
+/// field by field.
+#[derive(Debug, Clone, Default)]
+pub struct ToolHints {
+    overrides: HashMap<String, ToolAnnotations>,
+}
+
+impl ToolHints {
+    /// Overrides from the file in `EOKA_TOOL_HINTS`, if set.
+    pub fn from_env() -> anyhow::Result<Self> {
+        match std::env::var_os("EOKA_TOOL_HINTS") {
+            Some(path) => Self::load(Path::new(&path)),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Overrides from a JSON object keyed by tool name, e.g.
+    /// `{"click": {"destructiveHint": false}}`.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("reading tool hints from {}", path.display()))?;
+        let overrides = serde_json::from_str(&text)
+            .with_context(|| format!("parsing tool hints in {}", path.display()))?;
+        Ok(Self { overrides })
+    }
+
+    /// The hints for `tool`.
+    pub fn get(&self, tool: &str) -> ToolAnnotations {
+        let mut hints = defaults(tool);
+        if let Some(o) = self.overrides.get(tool) {
+            hints.title = o.title.clone().or(hints.title);
+            hints.read_only_hint = o.read_only_hint.or(hints.read_only_hint);
+            hints.destructive_hint = o.destructive_hint.or(hints.destructive_hint);
+            hints.idempotent_hint = o.idempotent_hint.or(hints.idempotent_hint);
+            hints.open_world_hint = o.open_world_hint.or(hints.open_world_hint);
+        }
+        hints
+    }
+
+    /// Set `tool.annotations`.
+    pub fn apply(&self, tool: &mut Tool) {
+        tool.annotations = Some(self.get(&tool.name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_merge_over_defaults() {
+        let click = defaults("click");
+        assert_eq!(click.read_only_hint, Some(false));
+        assert_eq!(click.destructive_hint, Some(true));
+        assert_eq!(click.idempotent_hint, Some(false));
+        let observe = defaults("observe");
+        assert_eq!(observe.read_only_hint, Some(true));
+        assert_eq!(observe.idempotent_hint, Some(true));
+
+        let hints = ToolHints {
+            overrides: serde_json::from_str(r#"{"click": {"destructiveHint": false}}"#).unwrap(),
+        };
+        let click = hints.get("click");
+        assert_eq!(click.destructive_hint, Some(false));
+        assert_eq!(click.read_only_hint, Some(false));
+        assert_eq!(hints.get("observe"), observe);
+    }
+}
@@ -0,0 +1,40 @@
+//! Waits that `wait_for_stable` doesn't cover: loading text going away, and
+//! elements that are still animating into place.
+//!
+//! Both run through [`eoka_runner::Runner`], so they behave like the
+//! runner's `wait_for_text_gone` and `wait_for_element_stable` actions.
+
+use eoka::{Page, Result};
+use eoka_runner::Runner;
+
+/// Consecutive animation frames an element's box must hold still.
+pub const STABLE_FRAMES: u32 = 3;
+
+/// Wait until `text` no longer appears in the page's visible text, e.g. a
+/// "Loading…" label or spinner caption.
+pub async fn wait_for_text_gone(page: &Page, text: &str, timeout_ms: u64) -> Result<()> {
+    Runner::wait_for_text_gone(page, text, timeout_ms)
+        .await
+        .map_err(into_eoka)
+}
+
+/// Wait until the element matching `selector` keeps the same bounding box for
+/// `frames` animation frames — for modals and drawers that slide or scale in.
+pub async fn wait_for_element_stable(
+    page: &Page,
+    selector: &str,
+    frames: u32,
+    timeout_ms: u64,
+) -> Result<()> {
+    Runner::wait_for_element_stable(page, selector, frames, timeout_ms)
+        .await
+        .map_err(into_eoka)
+}
+
+fn into_eoka(e: eoka_runner::Error) -> eoka::Error {
+    match e {
+        eoka_runner::Error::Browser(e) => e,
+        eoka_runner::Error::Timeout(msg) => eoka::Error::Timeout(msg),
+        e => eoka::Error::ElementNotFound(e.to_string()),
+    }
+}
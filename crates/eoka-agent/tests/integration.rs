@@ -3,7 +3,7 @@
 //! These tests require Chrome to be installed and available.
 //! Run with: cargo test --test integration -- --ignored
 
-use eoka_agent::{AgentPage, Browser, ObserveConfig};
+use eoka_agent::{test_server, AgentError, AgentPage, Browser, ObserveConfig};
 
 /// Check if Chrome is available
 fn chrome_available() -> bool {
@@ -315,6 +315,7 @@ async fn test_viewport_only_config() {
     // With viewport_only = false
     let config = ObserveConfig {
         viewport_only: false,
+        ..ObserveConfig::default()
     };
     let mut agent_all = AgentPage::with_config(&page, config);
     agent_all.observe().await.expect("Failed to observe");
@@ -423,6 +424,54 @@ async fn test_stale_element_detection() {
     agent.close().await.expect("Failed to close");
 }
 
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_auto_recover_moved_element() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    // Selectors are long enough that the li position falls outside the fingerprint
+    agent
+        .goto(
+            r#"data:text/html,
+            <div></div>
+            <div><ul></ul><ul>
+              <li><button>A</button></li>
+              <li><button>B</button></li>
+              <li><button onclick="document.title='C'">C</button></li>
+              <li><button>D</button></li>
+            </ul></div>
+        "#,
+        )
+        .await
+        .unwrap();
+    agent.observe().await.unwrap();
+    let idx = agent.find_by_text("C").unwrap();
+    agent
+        .exec("document.querySelector('li').remove()")
+        .await
+        .unwrap();
+
+    let err = agent.click(idx).await.unwrap_err().to_string();
+    assert!(err.contains("moved to"), "got: {}", err);
+
+    agent.observe().await.unwrap();
+    let idx = agent.find_by_text("C").unwrap();
+    agent
+        .exec("document.querySelector('li').remove()")
+        .await
+        .unwrap();
+    agent.set_auto_recover(true);
+    agent.click(idx).await.unwrap();
+    assert_eq!(agent.title().await.unwrap(), "C");
+
+    agent.close().await.unwrap();
+}
+
 #[tokio::test]
 #[ignore = "requires Chrome"]
 async fn test_session_basic() {
@@ -676,6 +725,65 @@ async fn test_spa_info_history_api() {
     agent.close().await.unwrap();
 }
 
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_wait_for_hydration() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+
+    // Plain page: no SSR framework, hydrated once loaded
+    agent
+        .goto(r#"data:text/html,<button>Hello</button>"#)
+        .await
+        .unwrap();
+    assert!(agent.spa_info().await.unwrap().hydrated);
+
+    // Server-rendered Vue 2 root that never mounts
+    agent
+        .goto(r#"data:text/html,<div id="root" data-server-rendered="true"><button>Go</button></div>"#)
+        .await
+        .unwrap();
+    assert!(!agent.spa_info().await.unwrap().hydrated);
+    assert!(agent.wait_for_hydration(300).await.is_err());
+
+    agent
+        .exec("document.querySelector('[data-server-rendered]').__vue__ = {}")
+        .await
+        .unwrap();
+    agent.wait_for_hydration(1000).await.unwrap();
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_spa_navigate_hash_router() {
+    use eoka_agent::{RouterType, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    agent
+        .goto(r#"data:text/html,<h1>App</h1>#/home"#)
+        .await
+        .unwrap();
+
+    let info = agent.spa_info().await.unwrap();
+    assert_eq!(info.router_type, RouterType::HashRouter);
+
+    let path = agent.spa_navigate("/settings").await.unwrap();
+    assert_eq!(path, "#/settings");
+
+    agent.close().await.unwrap();
+}
+
 #[tokio::test]
 #[ignore = "requires Chrome"]
 async fn test_history_go() {
@@ -770,6 +878,7 @@ async fn test_session_scroll_to() {
     // Use AgentPage with viewport_only=false
     let config = ObserveConfig {
         viewport_only: false,
+        ..ObserveConfig::default()
     };
     let mut agent = AgentPage::with_config(&page, config);
     agent.observe().await.unwrap();
@@ -1133,3 +1242,1907 @@ async fn test_hidden_elements_filtered() {
 
     agent.close().await.unwrap();
 }
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_wait_for_route_change() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    agent
+        .goto(r#"data:text/html,<h1>Router</h1>"#)
+        .await
+        .unwrap();
+
+    agent
+        .exec("setTimeout(() => { location.hash = '#/a'; location.hash = '#/next'; }, 200)")
+        .await
+        .unwrap();
+    let path = agent
+        .wait_for_route_change(2000, Some("next"))
+        .await
+        .unwrap();
+    assert!(path.ends_with("#/next"), "got: {}", path);
+
+    // Already consumed: nothing new arrives
+    assert!(agent.wait_for_route_change(300, None).await.is_err());
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_detect_overlays() {
+    use eoka_agent::{DismissKind, OverlayKind, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    agent
+        .goto(
+            r#"data:text/html,
+            <header style="position:fixed;top:0;width:100%;height:60px">Site</header>
+            <div id="consent" style="position:fixed;bottom:0;left:0;right:0;height:150px;z-index:9999;background:white">
+                We use cookies to improve your experience.
+                <button id="accept">Accept all</button>
+                <button id="reject">Reject all</button>
+            </div>
+        "#,
+        )
+        .await
+        .unwrap();
+
+    let overlays = agent.detect_overlays().await.unwrap();
+    assert_eq!(overlays.len(), 1, "got: {:?}", overlays);
+    assert_eq!(overlays[0].kind, OverlayKind::CookieBanner);
+    assert_eq!(overlays[0].selector, "#consent");
+    assert_eq!(overlays[0].dismiss[0].kind, DismissKind::Reject);
+    assert_eq!(overlays[0].dismiss[0].selector, "#reject");
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_click_under_sticky_header() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    agent
+        .goto(
+            r#"data:text/html,
+            <div style="position:fixed;top:0;left:0;right:0;height:100px;background:red;z-index:10">Header</div>
+            <div style="height:3000px"></div>
+            <button onclick="document.title='clicked'">Buy</button>
+            <div style="height:3000px"></div>
+        "#,
+        )
+        .await
+        .unwrap();
+    // Put the button right under the header
+    agent
+        .exec("window.scrollTo(0, document.querySelector('button').offsetTop - 40)")
+        .await
+        .unwrap();
+    agent.observe().await.unwrap();
+    let idx = agent.find_by_text("Buy").unwrap();
+    let selector = agent.get(idx).unwrap().selector.clone();
+    assert!(eoka_agent::occlusion::check(agent.page(), &selector)
+        .await
+        .unwrap()
+        .is_some());
+
+    agent.click(idx).await.unwrap();
+    assert_eq!(agent.title().await.unwrap(), "clicked");
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_click_obscured_by_modal() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    agent
+        .goto(
+            r#"data:text/html,
+            <button>Buy</button>
+            <div id="modal" style="position:fixed;inset:0;background:rgba(0,0,0,.5)">Sign up!</div>
+        "#,
+        )
+        .await
+        .unwrap();
+    agent.set_observe_config(ObserveConfig {
+        viewport_only: false,
+        ..ObserveConfig::default()
+    });
+    agent.observe().await.unwrap();
+    let idx = agent.find_by_text("Buy").unwrap();
+
+    let err = agent.click(idx).await.unwrap_err();
+    assert!(
+        err.to_string().contains("obscured by <div id=\"modal\">"),
+        "got: {}",
+        err
+    );
+    match err {
+        AgentError::ElementObscured { by, .. } => {
+            assert_eq!(by.by, "<div id=\"modal\">");
+            assert!(by.fixed);
+        }
+        other => panic!("expected ElementObscured, got {:?}", other),
+    }
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_click_position_obscured() {
+    use eoka_agent::{ClickOptions, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    // The badge covers the button's left edge but not its center
+    let mut agent = Session::launch().await.unwrap();
+    agent
+        .goto(
+            r#"data:text/html,
+            <button style="position:absolute;left:100px;top:100px;width:200px;height:40px"
+                onclick="document.title = 'clicked'">Buy</button>
+            <div id="badge" style="position:fixed;left:90px;top:90px;width:40px;height:60px;background:red"></div>
+        "#,
+        )
+        .await
+        .unwrap();
+    agent.observe().await.unwrap();
+    let idx = agent.find_by_text("Buy").unwrap();
+
+    let err = agent
+        .click_with(idx, &ClickOptions::new().position(10.0, 20.0))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, AgentError::ElementObscured { by, .. } if by.by == "<div id=\"badge\">"),
+        "got: {:?}",
+        err
+    );
+
+    agent
+        .click_with(idx, &ClickOptions::new().position(150.0, 20.0))
+        .await
+        .unwrap();
+    assert_eq!(agent.title().await.unwrap(), "clicked");
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_click_with_fallback() {
+    use eoka_agent::{ClickMethod, ClickOptions, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    agent
+        .goto(
+            r#"data:text/html,
+            <button onclick="document.title += 'a'">Buy</button>
+            <div id="modal" style="position:fixed;inset:0;background:rgba(0,0,0,.5)">Sign up!</div>
+        "#,
+        )
+        .await
+        .unwrap();
+    agent.set_observe_config(ObserveConfig {
+        viewport_only: false,
+        ..ObserveConfig::default()
+    });
+    agent.observe().await.unwrap();
+    let idx = agent.find_by_text("Buy").unwrap();
+
+    let clicked = agent
+        .click_with(idx, &ClickOptions::new().js_fallback(true))
+        .await
+        .unwrap()
+        .clicked
+        .unwrap();
+    assert_eq!(clicked.method, ClickMethod::Dispatched);
+    assert!(clicked
+        .fallback_reason
+        .unwrap()
+        .contains("obscured by <div id=\"modal\">"));
+    assert_eq!(agent.title().await.unwrap(), "a");
+
+    agent.observe().await.unwrap();
+    let idx = agent.find_by_text("Buy").unwrap();
+    let clicked = agent
+        .click_with(idx, &ClickOptions::new().force(true))
+        .await
+        .unwrap()
+        .clicked
+        .unwrap();
+    assert_eq!(
+        clicked.to_string(),
+        "clicked via dispatched events (forced)"
+    );
+    assert_eq!(agent.title().await.unwrap(), "aa");
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_click_at_position() {
+    use eoka_agent::{ClickMethod, ClickOptions, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    agent
+        .goto(
+            r#"data:text/html,
+            <button style="width:200px;height:40px" onclick="document.title = event.offsetX < 50 ? 'left' : 'right'">Pay</button>
+        "#,
+        )
+        .await
+        .unwrap();
+    agent.observe().await.unwrap();
+    let idx = agent.find_by_text("Pay").unwrap();
+
+    let clicked = agent
+        .click_with(idx, &ClickOptions::new().position(10.0, 20.0))
+        .await
+        .unwrap()
+        .clicked
+        .unwrap();
+    assert_eq!(clicked.method, ClickMethod::Mouse);
+    assert_eq!(agent.title().await.unwrap(), "left");
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_action_outcome() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    agent
+        .goto(
+            r#"data:text/html,
+            <button onclick="alert('Saved'); console.error('boom'); document.body.append(Object.assign(document.createElement('button'), {textContent: 'Undo'}))">Save</button>
+            <button onclick="document.title = confirm('Delete account?') ? 'deleted' : 'kept'">Delete</button>
+            <a href="data:text/html,<p>done</p>">Next</a>
+        "#,
+        )
+        .await
+        .unwrap();
+    agent.observe().await.unwrap();
+
+    let idx = agent.find_by_text("Save").unwrap();
+    let outcome = agent.click(idx).await.unwrap();
+    assert!(!outcome.navigated);
+    assert_eq!(outcome.dialogs, vec!["Saved".to_string()]);
+    assert_eq!(outcome.console_errors, vec!["boom".to_string()]);
+    assert_eq!(outcome.new_elements, 1);
+
+    // confirm() is cancelled unless dialogs are accepted, and the page's
+    // own functions stay native
+    agent.observe().await.unwrap();
+    let idx = agent.find_by_text("Delete").unwrap();
+    let outcome = agent.click(idx).await.unwrap();
+    assert_eq!(outcome.dialogs, vec!["Delete account?".to_string()]);
+    assert_eq!(agent.title().await.unwrap(), "kept");
+    let native: bool = agent
+        .eval("[alert, confirm, console.error].every(f => f.toString().includes('[native code]'))")
+        .await
+        .unwrap();
+    assert!(native);
+    agent.set_accept_dialogs(true);
+    agent.observe().await.unwrap();
+    let idx = agent.find_by_text("Delete").unwrap();
+    agent.click(idx).await.unwrap();
+    assert_eq!(agent.title().await.unwrap(), "deleted");
+
+    agent.observe().await.unwrap();
+    let idx = agent.find_by_text("Next").unwrap();
+    let outcome = agent.click(idx).await.unwrap();
+    assert!(outcome.navigated);
+    assert!(outcome.url_changed);
+    assert!(outcome.to_string().starts_with("navigated to data:"));
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_observe_disambiguates_identical_labels() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    agent
+        .goto(
+            r#"data:text/html,
+            <div class="card"><h3>Blue Widget</h3><button>Add to cart</button></div>
+            <div class="card"><h3>Red Widget</h3><button>Add to cart</button></div>
+            <div class="card"><span>Green Widget $5</span><button>Add to cart</button></div>
+            <button>Checkout</button>
+        "#,
+        )
+        .await
+        .unwrap();
+    agent.observe().await.unwrap();
+
+    let list = agent.element_list();
+    assert!(
+        list.contains("\"Add to cart\" in \"Blue Widget\""),
+        "{}",
+        list
+    );
+    assert!(
+        list.contains("\"Add to cart\" in \"Red Widget\""),
+        "{}",
+        list
+    );
+    assert!(
+        list.contains("\"Add to cart\" in \"Green Widget $5\""),
+        "{}",
+        list
+    );
+    // Unique labels get no context
+    assert!(list.contains("<button> \"Checkout\"\n"), "{}", list);
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_coordinate_input_on_canvas() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    agent
+        .goto(
+            r#"data:text/html,
+            <canvas width="400" height="300" style="position:absolute;left:0;top:0"></canvas>
+            <script>
+              const c = document.querySelector('canvas');
+              window.log = [];
+              c.addEventListener('mousemove', e => log.push('move ' + e.clientX + ',' + e.clientY));
+              c.addEventListener('click', e => log.push('click ' + e.clientX + ',' + e.clientY));
+              addEventListener('keydown', e => log.push('key ' + e.key));
+            </script>
+        "#,
+        )
+        .await
+        .unwrap();
+    agent.observe().await.unwrap();
+    assert!(agent.is_empty());
+
+    agent.move_mouse(50.0, 60.0).await.unwrap();
+    agent.click_at(120.0, 80.0).await.unwrap();
+    agent.type_text("ok").await.unwrap();
+
+    let log: Vec<String> = agent.eval("window.log").await.unwrap();
+    assert!(log.contains(&"move 50,60".to_string()), "{:?}", log);
+    assert!(log.contains(&"click 120,80".to_string()), "{:?}", log);
+    assert!(log.ends_with(&["key o".to_string(), "key k".to_string()]));
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_paginate() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    agent
+        .goto(
+            r#"data:text/html,
+            <ul id="items"><li>a1</li><li>a2</li></ul>
+            <nav><a href="%23" class="prev disabled">Previous</a> <button id="next">Next »</button></nav>
+            <script>
+              let page = 1;
+              document.getElementById('next').onclick = () => {
+                page++;
+                document.getElementById('items').innerHTML =
+                  '<li>' + 'abc'[page - 1] + '1</li><li>' + 'abc'[page - 1] + '2</li>';
+                if (page === 3) document.getElementById('next').disabled = true;
+              };
+            </script>
+        "#,
+        )
+        .await
+        .unwrap();
+
+    let next = eoka_agent::paginate::find_next(agent.page()).await.unwrap();
+    assert_eq!(next.unwrap().text, "Next »");
+
+    let run = agent
+        .paginate(10, async |s: &mut Session| {
+            s.eval::<String>("document.getElementById('items').innerText")
+                .await
+        })
+        .await
+        .unwrap();
+    assert_eq!(run.pages, 3);
+    assert!(run.exhausted);
+    assert_eq!(run.results, vec!["a1\na2", "b1\nb2", "c1\nc2"]);
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_wait_for_text_gone_and_element_stable() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    agent
+        .goto(
+            r#"data:text/html,
+            <p id="status">Loading…</p>
+            <button id="modal" style="position:fixed;top:0;left:0;transition:left 1000ms linear">OK</button>
+            <script>
+              setTimeout(() => document.getElementById('status').textContent = 'Done', 1000);
+              requestAnimationFrame(() => requestAnimationFrame(() =>
+                document.getElementById('modal').style.left = '300px'));
+            </script>
+        "#,
+        )
+        .await
+        .unwrap();
+
+    agent.wait_for_text_gone("Loading…", 3000).await.unwrap();
+    assert!(agent.text().await.unwrap().contains("Done"));
+
+    agent.observe().await.unwrap();
+    let button = agent.find_by_text("OK").unwrap();
+    agent.wait_for_element_stable(button).await.unwrap();
+    let left: f64 = agent
+        .eval("document.getElementById('modal').getBoundingClientRect().left")
+        .await
+        .unwrap();
+    assert_eq!(left, 300.0);
+
+    let err = agent.wait_for_text_gone("Done", 300).await.unwrap_err();
+    assert!(matches!(err, eoka_agent::Error::Timeout(_)), "{}", err);
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_nav_watchdog() {
+    use eoka_agent::{NavFailureKind, NavWatchdog, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut agent = Session::launch().await.unwrap();
+    agent.set_nav_watchdog(Some(NavWatchdog::new().retries(1).backoff_ms(10)));
+
+    agent.goto("data:text/html,<h1>fine</h1>").await.unwrap();
+
+    let err = agent
+        .goto("http://eoka-watchdog.invalid/")
+        .await
+        .unwrap_err();
+    match err {
+        AgentError::NavigationFailed { kind, attempts, .. } => {
+            assert_eq!(kind, NavFailureKind::NameNotResolved);
+            // DNS failures aren't retried
+            assert_eq!(attempts, 1);
+        }
+        other => panic!("expected NavigationFailed, got {:?}", other),
+    }
+
+    agent.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_crawl_site() {
+    use eoka_agent::crawler::{crawl, CrawlConfig};
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let server = test_server::serve([
+        (
+            "/",
+            r#"<a href="/products/1">One</a> <a href="/products/2#reviews">Two</a>
+               <a href="/about">About</a> <a href="/missing">Gone</a>
+               <a href="https://example.com/">Elsewhere</a>"#,
+        ),
+        (
+            "/products/1",
+            r#"<h1>Widget</h1><a href="/products/3">Next</a>"#,
+        ),
+        ("/products/2", "<h1>Gadget</h1>"),
+        ("/products/3", "<h1>Gizmo</h1>"),
+        ("/about", "<h1>About us</h1>"),
+    ])
+    .unwrap();
+    let base = server.url();
+
+    let mut sessions = vec![
+        Session::launch().await.unwrap(),
+        Session::launch().await.unwrap(),
+    ];
+    let config = CrawlConfig::new([format!("{}/", base)])
+        .max_depth(1)
+        .exclude("/about")
+        .unwrap();
+    let report = crawl(&mut sessions, &config, async |s: &mut Session, _page| {
+        s.eval::<String>("document.querySelector('h1')?.textContent || ''")
+            .await
+    })
+    .await;
+
+    let mut titles: Vec<&str> = report.pages.iter().map(|(_, t)| t.as_str()).collect();
+    titles.sort();
+    // /products/3 is two hops away, /about is excluded, example.com is off-origin
+    assert_eq!(titles, ["", "", "Gadget", "Widget"], "{}", report);
+    assert!(report.errors.is_empty(), "{}", report);
+
+    for session in sessions {
+        session.close().await.unwrap();
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_audit_links() {
+    use eoka_agent::{LinkScope, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let server = test_server::serve([
+        (
+            "/",
+            r#"<a href="/ok">Fine</a> <a href="/ok#top">Fine again</a>
+               <a href="/missing">Gone</a> <a href="mailto:a@b.example">Mail</a>
+               <a href="http://nowhere.invalid/">Elsewhere</a>"#,
+        ),
+        ("/ok", "<h1>OK</h1>"),
+    ])
+    .unwrap();
+    let base = server.url();
+
+    let mut session = Session::launch().await.unwrap();
+    session.goto(&format!("{}/", base)).await.unwrap();
+
+    let audit = session.audit_links(LinkScope::SameOrigin).await.unwrap();
+    assert_eq!(audit.links.len(), 2, "{}", audit);
+    let broken: Vec<_> = audit.broken().collect();
+    assert_eq!(broken.len(), 1, "{}", audit);
+    assert!(broken[0].url.ends_with("/missing"));
+    assert_eq!(broken[0].status, Some(404));
+    assert_eq!(broken[0].text, "Gone");
+
+    let audit = session.audit_links(LinkScope::All).await.unwrap();
+    assert_eq!(audit.links.len(), 3, "{}", audit);
+    assert_eq!(audit.broken().count(), 2, "{}", audit);
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_a11y_audit() {
+    use eoka_agent::a11y::{self, Severity};
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(
+            r##"data:text/html,
+            <img src="logo.png">
+            <img src="deco.png" alt="">
+            <input type="email">
+            <label>Name <input type="text"></label>
+            <p style="color: rgb(187, 187, 187)">Faint</p>
+            <p style="color: rgb(17, 17, 17)">Plain</p>
+            <div id="dup"></div><div id="dup"></div>
+            <button>One</button>
+            <button onkeydown="if (event.key === 'Tab') { event.preventDefault(); this.previousElementSibling.focus(); }">Two</button>
+            <button>Three</button>
+        "##,
+        )
+        .await
+        .unwrap();
+
+    let violations = session.audit_a11y().await.unwrap();
+    let rules: Vec<&str> = violations.iter().map(|v| v.rule.as_str()).collect();
+    for rule in [
+        "image-alt",
+        "label",
+        "color-contrast",
+        "duplicate-id",
+        "focus-trap",
+    ] {
+        assert_eq!(
+            rules.iter().filter(|r| **r == rule).count(),
+            1,
+            "{}: {:#?}",
+            rule,
+            violations
+        );
+    }
+    assert_eq!(violations[0].severity, Severity::Critical);
+    assert_eq!(a11y::at_least(&violations, Severity::Serious).len(), 4);
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_autofill_form() {
+    use eoka_agent::{FormProfile, Session, TestCard};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(
+            r#"data:text/html,
+            <form id="search"><input name="q" placeholder="Search"></form>
+            <form id="checkout">
+              <label>Email <input type="email" name="email"></label>
+              <input autocomplete="given-name"> <input autocomplete="family-name">
+              <label for="zip">ZIP code</label><input id="zip">
+              <select name="country"><option value="">Choose</option><option value="CA">Canada</option><option value="US">United States</option></select>
+              <input name="cardnumber" placeholder="Card number">
+              <select autocomplete="cc-exp-month"><option>01</option><option>12</option></select>
+              <input placeholder="Coupon code">
+            </form>
+        "#,
+        )
+        .await
+        .unwrap();
+
+    let profile = FormProfile::new().card(TestCard::Mastercard);
+    let filled = session.autofill_form(1, &profile).await.unwrap();
+    assert_eq!(filled.len(), 7, "{:#?}", filled);
+
+    let values: Vec<String> = session
+        .eval("JSON.stringify(Array.from(document.forms[1].elements).map(e => e.value))")
+        .await
+        .map(|json: String| serde_json::from_str(&json).unwrap())
+        .unwrap();
+    assert_eq!(
+        values,
+        [
+            "jane.doe@example.com",
+            "Jane",
+            "Doe",
+            "94105",
+            "US",
+            "5555555555554444",
+            "12",
+            ""
+        ]
+    );
+    let search: String = session
+        .eval("document.forms[0].elements[0].value")
+        .await
+        .unwrap();
+    assert_eq!(search, "");
+    assert!(session.autofill_form(5, &profile).await.is_err());
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_select_custom() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(
+            r#"data:text/html,
+            <button id="fruit" role="combobox" aria-controls="fruits" onclick="fruits.hidden = !fruits.hidden">Pick a fruit</button>
+            <ul id="fruits" role="listbox" hidden style="max-height: 60px; overflow: auto; margin: 0">
+              <li role="option">Apple</li><li role="option">Banana</li><li role="option">Cherry</li>
+              <li role="option">Grape</li><li role="option">Mango</li><li role="option">Pineapple</li>
+            </ul>
+            <script>
+              fruits.addEventListener('click', e => {
+                if (e.target.getAttribute('role') !== 'option') return;
+                fruit.textContent = e.target.textContent;
+                fruits.hidden = true;
+              });
+            </script>
+        "#,
+        )
+        .await
+        .unwrap();
+
+    session.observe().await.unwrap();
+    let index = session.find_by_text("Pick a fruit").unwrap();
+    // Exact match beats "Pineapple"
+    session.select_custom(index, "apple").await.unwrap();
+    let picked: String = session
+        .eval("document.getElementById('fruit').textContent")
+        .await
+        .unwrap();
+    assert_eq!(picked, "Apple");
+
+    session.observe().await.unwrap();
+    let index = session.find_by_text("Apple").unwrap();
+    // Below the listbox's scroll area until scrolled to
+    session.select_custom(index, "pine").await.unwrap();
+    let picked: String = session
+        .eval("document.getElementById('fruit').textContent")
+        .await
+        .unwrap();
+    assert_eq!(picked, "Pineapple");
+
+    session.observe().await.unwrap();
+    let index = session.find_by_text("Pineapple").unwrap();
+    let err = session.select_custom(index, "Durian").await.unwrap_err();
+    assert!(err.to_string().contains("Durian"), "{}", err);
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_hover_and_capture() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(
+            r#"data:text/html,
+            <style>
+              .menu { display: none } nav:hover .menu { display: block }
+              [role=tooltip] { display: none } .help:hover + [role=tooltip] { display: block }
+            </style>
+            <nav><button>Products</button><div class="menu"><a href="/a">Analytics</a> <a href="/b">Billing</a></div></nav>
+            <button class="help" aria-describedby="tip">?</button><span id="tip" role="tooltip">Opens the help center</span>
+            <button title="Saves a draft">Save</button>
+        "#,
+        )
+        .await
+        .unwrap();
+
+    session.observe().await.unwrap();
+    let index = session.find_by_text("Products").unwrap();
+    let capture = session.hover_and_capture(index, 300).await.unwrap();
+    let revealed: Vec<&str> = capture.revealed.iter().map(|e| e.text.as_str()).collect();
+    assert_eq!(revealed, ["Analytics", "Billing"]);
+    assert!(capture.tooltips.is_empty());
+    let analytics = capture.revealed[0].index;
+    assert_eq!(session.get(analytics).unwrap().text, "Analytics");
+
+    let index = session.find_by_text("?").unwrap();
+    let capture = session.hover_and_capture(index, 1000).await.unwrap();
+    assert_eq!(capture.tooltips, ["Opens the help center"]);
+
+    let index = session.find_by_text("Save").unwrap();
+    let capture = session.hover_and_capture(index, 200).await.unwrap();
+    assert_eq!(capture.tooltips, ["Saves a draft"]);
+    assert!(capture.revealed.is_empty());
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_action_evidence() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(r#"data:text/html,<input placeholder="Name"><button onclick="this.textContent='Done'">Go</button>"#)
+        .await
+        .unwrap();
+
+    session.observe().await.unwrap();
+    session.fill(0, "untracked").await.unwrap();
+    assert!(session.evidence().is_empty());
+
+    session.set_capture_action_evidence(true);
+    session.fill(0, "Jane").await.unwrap();
+    session.observe().await.unwrap();
+    let go = session.find_by_text("Go").unwrap();
+    session.click(go).await.unwrap();
+    session.click_at(5.0, 5.0).await.unwrap();
+
+    let evidence = session.evidence();
+    let actions: Vec<&str> = evidence.iter().map(|e| e.action.as_str()).collect();
+    assert_eq!(actions, ["fill \"Jane\"", "click", "click_at (5, 5)"]);
+    let jpeg = [0xFF, 0xD8];
+    for e in evidence {
+        assert!(e.before.starts_with(&jpeg) && e.after.starts_with(&jpeg));
+    }
+    assert_eq!(evidence[1].target.as_ref().unwrap().text, "Go");
+    assert!(evidence[1].crop.as_ref().unwrap().starts_with(&jpeg));
+    assert!(evidence[1].before != evidence[1].after);
+    assert!(evidence[2].target.is_none() && evidence[2].crop.is_none());
+
+    assert_eq!(session.take_evidence().len(), 3);
+    assert!(session.evidence().is_empty());
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_som_bundle() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(r#"data:text/html,<input placeholder="Email"><button>Sign in</button>"#)
+        .await
+        .unwrap();
+
+    let bundle = session.som_bundle().await.unwrap();
+    assert!(bundle.image.starts_with(&[0x89, b'P', b'N', b'G']));
+    assert_eq!(bundle.marks.len(), session.len());
+    let marks: Vec<serde_json::Value> = serde_json::from_str(&bundle.marks_json()).unwrap();
+    assert_eq!(marks[0]["mark"], 0);
+    assert_eq!(marks[0]["placeholder"], "Email");
+    assert_eq!(marks[1]["text"], "Sign in");
+    assert_eq!(session.get(1).unwrap().text, "Sign in");
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_observe_config_attributes() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(
+            r#"data:text/html,
+            <a href="https://example.com/pricing" id="plans" title="See plans">Pricing and plans for teams of every size</a>
+            <input name="q" placeholder="Search">
+        "#,
+        )
+        .await
+        .unwrap();
+
+    session.set_observe_config(ObserveConfig {
+        include_href: false,
+        ..ObserveConfig::default()
+    });
+    session.observe().await.unwrap();
+    let link = session.get(0).unwrap();
+    assert_eq!(link.href, None);
+    assert_eq!(link.text, "Pricing and plans for teams of every size");
+
+    session.set_observe_config(ObserveConfig {
+        text_limit: 20,
+        include_name: true,
+        include_title: true,
+        include_id: true,
+        ..ObserveConfig::default()
+    });
+    session.observe().await.unwrap();
+    let link = session.get(0).unwrap();
+    assert_eq!(link.text, "Pricing and plans...");
+    assert_eq!(link.href.as_deref(), Some("https://example.com/pricing"));
+    assert_eq!(link.id.as_deref(), Some("plans"));
+    assert_eq!(link.title.as_deref(), Some("See plans"));
+    assert_eq!(session.get(1).unwrap().name.as_deref(), Some("q"));
+    assert!(session.element_list().contains("→ example.com/pricing"));
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_snapshot_all_tabs() {
+    use eoka_agent::{observe, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(r#"data:text/html,<button>Pay now</button>"#)
+        .await
+        .unwrap();
+    // Opened second, so the first tab observes in the background
+    let other = session
+        .browser()
+        .new_page(r#"data:text/html,<input placeholder="Code"><a href="/help">Help</a>"#)
+        .await
+        .unwrap();
+
+    let snapshots =
+        observe::snapshot_all(&[session.page(), &other], &ObserveConfig::default()).await;
+    let first = snapshots[0].as_ref().unwrap();
+    let second = snapshots[1].as_ref().unwrap();
+    assert_eq!(first.elements.len(), 1);
+    assert_eq!(first.elements[0].text, "Pay now");
+    assert_eq!(second.elements.len(), 2);
+    assert_eq!(second.elements[0].placeholder.as_deref(), Some("Code"));
+
+    // A popup the page opened has no Page handle, but is observed too
+    session
+        .page()
+        .execute(r#"window.open('data:text/html,<button>Popup OK</button>')"#)
+        .await
+        .unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    let tabs = observe::observe_all_tabs(session.browser(), &ObserveConfig::default())
+        .await
+        .unwrap();
+    assert_eq!(tabs.len(), 3);
+    assert!(tabs.iter().any(|t| t
+        .snapshot
+        .as_ref()
+        .is_ok_and(|s| s.elements.iter().any(|e| e.text == "Popup OK"))));
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_action_log() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(r#"data:text/html,<input placeholder="Name"><button>Save</button>"#)
+        .await
+        .unwrap();
+    session.observe().await.unwrap();
+    session.fill(0, "Jane").await.unwrap();
+    session.click(1).await.unwrap();
+    assert!(session.click(42).await.is_err());
+
+    let log = session.action_log();
+    let methods: Vec<&str> = log.records().iter().map(|r| r.method.as_str()).collect();
+    assert_eq!(methods, ["goto", "fill", "click", "click"]);
+    assert_eq!(log.records()[0].url_before, "about:blank");
+    assert!(log.records()[0].url_after.starts_with("data:text/html"));
+    assert!(log.records()[1]
+        .target
+        .as_deref()
+        .unwrap()
+        .ends_with(r#"= "Jane""#));
+    assert!(log.records()[2].ok);
+    assert!(!log.records()[3].ok);
+
+    let json: serde_json::Value = serde_json::from_str(&log.to_json()).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 4);
+    assert_eq!(json[3]["target"], "[42]");
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_debug_overlay() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session.set_debug_overlay(true).await.unwrap();
+    session
+        .goto(r#"data:text/html,<input placeholder="Name"><button>Save</button>"#)
+        .await
+        .unwrap();
+    // The overlay holds no interactive elements and doesn't cover any
+    assert_eq!(session.observe().await.unwrap().len(), 2);
+
+    let boxes: usize = session
+        .eval("document.getElementById('__eoka_debug').children.length - 1")
+        .await
+        .unwrap();
+    assert_eq!(boxes, 2);
+    let caption: String = session
+        .eval("document.getElementById('__eoka_debug').lastChild.textContent")
+        .await
+        .unwrap();
+    assert!(caption.starts_with("last: "));
+    assert!(caption.contains("goto data:text/html"));
+
+    session.fill(0, "Jane").await.unwrap();
+    let caption: String = session
+        .eval("document.getElementById('__eoka_debug').lastChild.textContent")
+        .await
+        .unwrap();
+    assert!(caption.contains("fill"));
+
+    session.set_debug_overlay(false).await.unwrap();
+    let gone: bool = session
+        .eval("!document.getElementById('__eoka_debug')")
+        .await
+        .unwrap();
+    assert!(gone);
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_cdp_call() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let session = Session::launch().await.unwrap();
+    let result = session
+        .cdp_call(
+            "Runtime.evaluate",
+            serde_json::json!({"expression": "1 + 2", "returnByValue": true}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result["result"]["value"], 3);
+
+    // Commands without parameters take null
+    session
+        .cdp_call("Page.bringToFront", serde_json::Value::Null)
+        .await
+        .unwrap();
+
+    match session
+        .cdp_call("Nope.notAMethod", serde_json::Value::Null)
+        .await
+    {
+        Err(eoka::Error::Cdp { method, .. }) => assert_eq!(method, "Nope.notAMethod"),
+        other => panic!("expected a CDP error, got {:?}", other),
+    }
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_login_two_page_totp() {
+    use eoka_agent::flows::{self, LoginSpec, SuccessCheck, TotpSource};
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    // Email first, then password, then a code field that auto-submits
+    let html = r#"<form id="f"><input type="email" name="email"><button type="submit">Next</button></form>
+<script>
+const f = document.getElementById('f');
+let step = 0;
+f.onsubmit = e => {
+  e.preventDefault();
+  step++;
+  if (step === 1) {
+    f.innerHTML = '<input type="password" name="pw"><button type="submit">Sign in</button>';
+  } else if (step === 2) {
+    f.innerHTML = '<input name="otp" autocomplete="one-time-code" maxlength="6">';
+    f.querySelector('input').oninput = ev => {
+      if (ev.target.value.length === 6) {
+        window.code = ev.target.value;
+        document.body.innerHTML = '<h1>Welcome back</h1>';
+      }
+    };
+  }
+};
+</script>"#;
+    let url = format!("data:text/html,{}", urlencoding::encode(html));
+
+    let mut session = Session::launch().await.unwrap();
+    let spec = LoginSpec::new(url, "me@example.com", "hunter2")
+        .success(SuccessCheck::Text("Welcome back".into()))
+        .totp(TotpSource::Secret("JBSWY3DPEHPK3PXP".into()));
+    let logged_in = flows::login(&mut session, &spec).await.unwrap();
+    assert!(logged_in.two_page);
+    assert!(logged_in.totp);
+
+    let code: String = session.eval("window.code").await.unwrap();
+    assert_eq!(code.len(), 6);
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_runner_config_on_session_page() {
+    use eoka_agent::Session;
+    use eoka_runner::{Config, Params, Runner};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let html = r#"<input id="name"><button onclick="document.title = 'Hi ' + document.getElementById('name').value">Go</button>"#;
+    let yaml = format!(
+        r##"
+name: greet
+target:
+  url: "data:text/html,{}"
+actions:
+  - fill:
+      selector: "#name"
+      value: "${{who}}"
+  - click:
+      text: "Go"
+success:
+  any:
+    - js_true: "document.title === 'Hi Jane'"
+"##,
+        urlencoding::encode(html)
+    );
+    let config = Config::parse_with_params(&yaml, &Params::new().set("who", "Jane")).unwrap();
+
+    let session = Session::launch().await.unwrap();
+    let result = Runner::run_on_page(session.page(), &config, ".")
+        .await
+        .unwrap();
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(result.actions_executed, 2);
+    assert_eq!(result.actions.len(), 2);
+    assert_eq!(session.title().await.unwrap(), "Hi Jane");
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_runner_cross_origin_frame() {
+    use eoka_agent::Session;
+    use eoka_runner::{Config, Runner};
+
+    if !chrome_available() {
+        return;
+    }
+
+    // A second port is a second origin, so the frame's contentDocument is null
+    let widget = test_server::serve([(
+        "/card",
+        r#"<input id="card"><button onclick="document.getElementById('out').textContent = 'Paid ' + document.getElementById('card').value">Pay</button><p id="out"></p>"#,
+    )])
+    .unwrap();
+    let outer = format!(
+        r#"<h1>Checkout</h1><iframe name="pay" src="{}" width="400" height="200"></iframe>"#,
+        widget.url_for("/card")
+    );
+    let server = test_server::serve([("/", outer.as_str())]).unwrap();
+
+    let yaml = format!(
+        r##"
+name: pay
+target:
+  url: "{}/"
+actions:
+  - within_frame:
+      name: pay
+      timeout_ms: 5000
+      actions:
+        - fill:
+            selector: "#card"
+            value: "4242"
+        - click:
+            text: "Pay"
+        - wait_for_text:
+            text: "Paid 4242"
+            timeout_ms: 5000
+"##,
+        server.url()
+    );
+    let config = Config::parse(&yaml).unwrap();
+
+    let session = Session::launch().await.unwrap();
+    let result = Runner::run_on_page(session.page(), &config, ".")
+        .await
+        .unwrap();
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(result.actions_executed, 1);
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_expect() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(r#"data:text/html,<input id="total" value="$40"><p>Cart</p><script>setTimeout(() => { document.getElementById('total').value = '$42'; document.querySelector('p').textContent = 'Order placed'; }, 300)</script>"#)
+        .await
+        .unwrap();
+
+    // Both wait out the 300 ms update
+    session
+        .expect()
+        .text_contains("Order placed")
+        .await
+        .unwrap();
+    session
+        .expect()
+        .element("css:#total")
+        .value("$42")
+        .await
+        .unwrap();
+    session
+        .expect()
+        .element("css:#missing")
+        .absent()
+        .await
+        .unwrap();
+
+    let e = session
+        .expect()
+        .timeout(200)
+        .element("css:#total")
+        .value("$50")
+        .await
+        .unwrap_err();
+    assert!(matches!(e, eoka::Error::Timeout(_)));
+    assert!(e.to_string().contains(r#"value: "$42""#), "{}", e);
+
+    let e = session
+        .expect()
+        .timeout(200)
+        .text_contains("Refunded")
+        .await
+        .unwrap_err();
+    assert!(e.to_string().contains("Order placed"), "{}", e);
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_popup_policy() {
+    use eoka_agent::{PopupPolicy, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let page = r#"data:text/html,<a href="data:text/html,<p>Popup</p>" target="_blank">open</a>"#;
+    let mut session = Session::launch().await.unwrap();
+
+    // Background: the popup opens, the session stays put
+    session
+        .set_popup_policy(PopupPolicy::Background)
+        .await
+        .unwrap();
+    session.goto(page).await.unwrap();
+    session.observe().await.unwrap();
+    session.click(0).await.unwrap();
+    session.page().wait(500).await;
+    assert_eq!(session.popups().await.unwrap().len(), 1);
+    assert!(!session.text().await.unwrap().contains("Popup"));
+
+    // Follow: the session drives the popup, its opener stays open
+    session.set_popup_policy(PopupPolicy::Follow).await.unwrap();
+    session.goto(page).await.unwrap();
+    let opener = session.page().target_id().to_string();
+    let tabs_before = session.browser().tabs().await.unwrap().len();
+    session.observe().await.unwrap();
+    session.click(0).await.unwrap();
+    assert!(session.popups().await.unwrap().is_empty());
+    assert!(session.text().await.unwrap().contains("Popup"));
+    assert_ne!(session.page().target_id(), opener);
+    assert_eq!(
+        session.browser().tabs().await.unwrap().len(),
+        tabs_before + 1
+    );
+
+    // A followed popup that closes itself hands control back to its opener
+    let closer = r#"data:text/html,<a href="data:text/html,<button onclick='window.close()'>close</button>" target="_blank">open</a>"#;
+    session.goto(closer).await.unwrap();
+    let opener = session.page().target_id().to_string();
+    session.observe().await.unwrap();
+    session.click(0).await.unwrap();
+    assert_ne!(session.page().target_id(), opener);
+    session.observe().await.unwrap();
+    session.click(0).await.unwrap();
+    assert_eq!(session.page().target_id(), opener);
+
+    // Same tab: nothing opens, the link navigates this page
+    session
+        .set_popup_policy(PopupPolicy::SameTab)
+        .await
+        .unwrap();
+    session.goto(page).await.unwrap();
+    session.observe().await.unwrap();
+    session.click(0).await.unwrap();
+    session.page().wait(500).await;
+    assert!(session.popups().await.unwrap().is_empty());
+    assert!(session.text().await.unwrap().contains("Popup"));
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_check_egress() {
+    use eoka_agent::{EgressCheck, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let session = Session::launch().await.unwrap();
+    let endpoint =
+        r#"data:application/json,{"ip":"203.0.113.7","country":"DE","timezone":"Europe/Berlin"}"#;
+
+    let egress = session
+        .check_egress_with(&EgressCheck {
+            endpoint: endpoint.into(),
+            timezone: Some("Europe/Berlin".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(egress.ip, "203.0.113.7");
+    assert_eq!(egress.country.as_deref(), Some("DE"));
+    assert!(egress.browser_timezone.is_some());
+
+    let e = session
+        .check_egress_with(&EgressCheck {
+            endpoint: endpoint.into(),
+            country: Some("US".into()),
+            timezone: Some("Europe/Berlin".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert!(
+        e.to_string().contains("country is DE, expected US"),
+        "{}",
+        e
+    );
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_set_proxy_bypass() {
+    use eoka::StealthConfig;
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    assert!(session
+        .set_proxy_bypass(&["localhost".into()])
+        .await
+        .is_err());
+    session.close().await.unwrap();
+
+    // Loopback skips the proxy anyway, so the dead proxy doesn't matter here
+    let server = test_server::serve([("/", "<p>Fixture</p>")]).unwrap();
+    let url = format!("{}/", server.url());
+    let stealth = StealthConfig {
+        proxy: Some("http://127.0.0.1:9".into()),
+        ..Default::default()
+    };
+    let mut session = Session::launch_with_proxy_bypass(stealth, &["*.internal".into()])
+        .await
+        .unwrap();
+    session.goto(&url).await.unwrap();
+    session
+        .page()
+        .execute("document.cookie = 'sid=1'")
+        .await
+        .unwrap();
+
+    session
+        .set_proxy_bypass(&["localhost".into(), "*.internal".into()])
+        .await
+        .unwrap();
+    assert_eq!(session.url().await.unwrap(), url);
+    assert!(session.text().await.unwrap().contains("Fixture"));
+    let cookie: String = session.eval("document.cookie").await.unwrap();
+    assert_eq!(cookie, "sid=1");
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_last_form_post() {
+    use eoka_agent::{NetworkCapture, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let server = test_server::serve([
+        (
+            "/",
+            r#"<form method="post" action="/done"><input name="email"><input type="password" name="password"><button>Sign in</button></form>"#,
+        ),
+        ("/done", "<p>Welcome</p>"),
+    ]).unwrap();
+    let base = server.url();
+    let mut session = Session::launch().await.unwrap();
+    session
+        .set_network_capture(Some(NetworkCapture::default()))
+        .await
+        .unwrap();
+    session.goto(&format!("{}/", base)).await.unwrap();
+    session.observe().await.unwrap();
+    session.fill(0, "jane@example.com").await.unwrap();
+    session.fill(1, "hunter2").await.unwrap();
+    session.click(2).await.unwrap();
+    session.page().wait(500).await;
+
+    // Recorded before the navigation, read back on the next page
+    let post = session.last_form_post().await.unwrap().unwrap();
+    assert_eq!(post.kind, "form");
+    assert_eq!(post.url, format!("{}/done", base));
+    assert_eq!(
+        post.fields,
+        [
+            ("email".to_string(), "jane@example.com".to_string()),
+            ("password".to_string(), "hunter2".to_string())
+        ]
+    );
+
+    session
+        .page()
+        .execute(r#"fetch('/api', { method: 'POST', headers: { Authorization: 'Bearer x', 'Content-Type': 'application/json' }, body: '{"qty":2}' }).catch(() => {})"#)
+        .await
+        .unwrap();
+    let sent = session.sent_requests().await.unwrap();
+    let api = sent.last().unwrap();
+    assert_eq!(api.kind, "fetch");
+    assert_eq!(api.headers["authorization"], "[redacted]");
+    assert_eq!(
+        api.form_fields().unwrap(),
+        [("qty".to_string(), "2".to_string())]
+    );
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_find_in_responses() {
+    use eoka_agent::{NetworkCapture, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let server = test_server::serve([
+        (
+            "/",
+            r#"<meta name="csrf-token" content="tok-123"><script>fetch('/cart').then(r => r.text()).then(t => document.title = t.length)</script>"#,
+        ),
+        ("/cart", r#"{"items":[{"productId":"P-42"}]}"#),
+    ]).unwrap();
+    let base = server.url();
+    let mut session = Session::launch().await.unwrap();
+    session
+        .set_network_capture(Some(NetworkCapture::default()))
+        .await
+        .unwrap();
+    session.goto(&format!("{}/", base)).await.unwrap();
+    session.page().wait(500).await;
+
+    let found = session
+        .find_in_responses(r#""productId":"([^"]+)""#)
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].url, format!("{}/cart", base));
+    assert_eq!(found[0].groups, [Some("P-42".to_string())]);
+
+    // The page's own HTML is searched too
+    let found = session
+        .find_in_responses(r#"csrf-token" content="([^"]+)"#)
+        .await
+        .unwrap();
+    assert_eq!(found[0].kind, "document");
+    assert_eq!(found[0].groups, [Some("tok-123".to_string())]);
+
+    assert!(session.find_in_responses("(").await.is_err());
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_capture_auth() {
+    use eoka_agent::{NetworkCapture, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let server = test_server::serve([
+        (
+            "/",
+            r#"<script>document.cookie = 'sid=s1; path=/'; document.cookie = 'theme=dark; path=/'; fetch('/me', { headers: { Authorization: 'Bearer abc' } })</script>"#,
+        ),
+        ("/me", "{}"),
+    ]).unwrap();
+    let base = server.url();
+    let mut session = Session::launch().await.unwrap();
+    session
+        .set_network_capture(Some(NetworkCapture::default()))
+        .await
+        .unwrap();
+    session.goto(&format!("{}/", base)).await.unwrap();
+    session.page().wait(300).await;
+
+    let auth = session.capture_auth().await.unwrap();
+    assert_eq!(auth.headers.len(), 1);
+    assert_eq!(auth.headers[0].name, "authorization");
+    assert_eq!(auth.headers[0].value, "Bearer abc");
+    assert_eq!(auth.headers[0].url, format!("{}/me", base));
+    assert_eq!(auth.cookies, [("sid".to_string(), "s1".to_string())]);
+    assert_eq!(
+        auth.to_headers(),
+        [
+            ("authorization".to_string(), "Bearer abc".to_string()),
+            ("cookie".to_string(), "sid=s1".to_string())
+        ]
+    );
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_cancel_current() {
+    use eoka_agent::{cancel, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session.goto("data:text/html,<p>Loading</p>").await.unwrap();
+
+    let canceller = session.canceller();
+    let start = std::time::Instant::now();
+    let (result, ()) = tokio::join!(session.wait_for_text("Never shows", 60_000), async {
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        canceller.cancel_current();
+    });
+    assert!(cancel::is_cancelled(&result.unwrap_err()));
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+    // Later waits aren't affected
+    session.wait_for_text("Loading", 5000).await.unwrap();
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_visibility_score() {
+    use eoka_agent::{ObserveConfig, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let html = r#"<html><body style="margin:0">
+        <div style="position:fixed;top:0;left:0;right:0;height:40px;background:#333;z-index:10"></div>
+        <button style="position:absolute;top:20px;left:10px;height:40px">Under bar</button>
+        <button style="position:absolute;top:100px;left:10px;height:40px">Clear</button>
+        </body></html>"#;
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(&format!("data:text/html,{}", urlencoding::encode(html)))
+        .await
+        .unwrap();
+
+    let elements = session.observe().await.unwrap().to_vec();
+    let under = elements.iter().find(|e| e.text == "Under bar").unwrap();
+    let clear = elements.iter().find(|e| e.text == "Clear").unwrap();
+    assert!(under.visibility > 0.2 && under.visibility < 0.8);
+    assert_eq!(clear.visibility, 1.0);
+
+    session.set_observe_config(ObserveConfig {
+        min_visibility: 0.9,
+        ..Default::default()
+    });
+    let elements = session.observe().await.unwrap();
+    assert!(elements.iter().all(|e| e.text != "Under bar"));
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_deterministic_mode() {
+    use eoka_agent::{deterministic, DeterministicConfig, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let server = test_server::serve([(
+        "/",
+        r#"<input id="q"><div id="note" contenteditable="true">draft</div><script>
+            window.moves = [];
+            document.addEventListener('mousemove', e => moves.push([e.clientX, e.clientY]));
+        </script>"#,
+    )])
+    .unwrap();
+
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        deterministic::enable(DeterministicConfig {
+            rng_seed: 42,
+            freeze_time: true,
+            zero_delays: true,
+        });
+        let session = Session::launch().await.unwrap();
+        session.page().goto(server.url()).await.unwrap();
+        let mut agent = AgentPage::new(session.page());
+        agent.observe().await.unwrap();
+        let start = std::time::Instant::now();
+        agent.human_fill(0, "hello world").await.unwrap();
+        // contenteditable has no select(); its text is replaced all the same
+        agent.human_fill(1, "final").await.unwrap();
+        assert!(start.elapsed() < std::time::Duration::from_secs(4));
+        deterministic::disable();
+
+        let state: String = session
+            .page()
+            .evaluate(
+                "JSON.stringify([moves, Math.random(), Date.now(), document.querySelector('#q').value, document.querySelector('#note').innerText])",
+            )
+            .await
+            .unwrap();
+        assert!(state.contains(&deterministic::FROZEN_TIME_MS.to_string()));
+        assert!(state.ends_with(r#","hello world","final"]"#));
+        runs.push(state);
+        session.close().await.unwrap();
+    }
+    assert_eq!(runs[0], runs[1]);
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_observe_region() {
+    use eoka_agent::{ObserveConfig, Session};
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(
+            "data:text/html,<style>button{position:fixed}</style>\
+             <button style='top:10px;left:10px'>NW</button>\
+             <button style='top:10px;right:10px'>NE</button>\
+             <button style='bottom:10px;left:10px'>SW</button>\
+             <button style='bottom:10px;right:10px'>SE</button>",
+        )
+        .await
+        .unwrap();
+    let viewport = session.viewport().await.unwrap();
+    session.set_observe_config(ObserveConfig {
+        region: viewport.region("top-right quadrant"),
+        ..Default::default()
+    });
+    let texts: Vec<String> = session
+        .observe()
+        .await
+        .unwrap()
+        .iter()
+        .map(|e| e.text.clone())
+        .collect();
+    assert_eq!(texts, ["NE"]);
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_page_summary() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(
+            "data:text/html,<div id='__next'><form><input name='q'><select></select>\
+             <button>Go</button></form><a href='/a'>A</a><a href='/b'>B</a>\
+             <img src=''><iframe></iframe></div>",
+        )
+        .await
+        .unwrap();
+    let summary = session.page_summary().await.unwrap();
+    assert_eq!(summary.links, 2);
+    assert_eq!(summary.buttons, 1);
+    assert_eq!(summary.inputs, 2);
+    assert_eq!(summary.forms, 1);
+    assert_eq!(summary.iframes, 1);
+    assert_eq!(summary.images, 1);
+    assert!(summary.frameworks.contains(&"Next.js".to_string()));
+    assert!(summary.page_height > 0.0);
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_live_messages() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let server = test_server::serve([(
+        "/",
+        "<div id='toasts' aria-live='polite'></div>\
+         <button onclick=\"document.getElementById('toasts').textContent = 'Item added to cart'; \
+         setTimeout(() => document.getElementById('toasts').textContent = '', 50)\">Add</button>\
+         <button onclick=\"const a = document.createElement('div'); a.setAttribute('role', 'alert'); \
+         a.textContent = 'Invalid password'; document.body.append(a); setTimeout(() => a.remove(), 50)\">Login</button>",
+    )])
+    .unwrap();
+    let mut session = Session::launch().await.unwrap();
+    session.set_live_monitor(true).await.unwrap();
+    session.goto(server.url()).await.unwrap();
+    session.observe().await.unwrap();
+    session.click(0).await.unwrap();
+    session.click(1).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    let texts: Vec<String> = session
+        .live_messages()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|m| m.to_string())
+        .collect();
+    assert_eq!(
+        texts,
+        [
+            "[polite] Item added to cart",
+            "[assertive alert] Invalid password"
+        ]
+    );
+    session.clear_live_messages().await.unwrap();
+    assert!(session.live_messages().await.unwrap().is_empty());
+    session.close().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Chrome"]
+async fn test_form_errors() {
+    use eoka_agent::Session;
+
+    if !chrome_available() {
+        return;
+    }
+
+    let mut session = Session::launch().await.unwrap();
+    session
+        .goto(
+            "data:text/html,<form onsubmit=\"event.preventDefault(); \
+             const e = document.getElementById('email'); e.setAttribute('aria-invalid', 'true'); \
+             document.getElementById('email-err').textContent = 'Enter a valid email address'; \
+             document.getElementById('summary').textContent = 'Please fix the errors below'\">\
+             <div role='alert' id='summary'></div>\
+             <div class='field'><label for='email'>Email</label>\
+             <input id='email' aria-describedby='email-err'><span id='email-err' class='error'></span></div>\
+             <button>Sign up</button></form>",
+        )
+        .await
+        .unwrap();
+    assert!(session.form_errors().await.unwrap().is_empty());
+
+    session.observe().await.unwrap();
+    let idx = session.find_by_text("Sign up").unwrap();
+    let outcome = session.click(idx).await.unwrap();
+    let errors: Vec<String> = outcome.form_errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        errors,
+        [
+            "Email: Enter a valid email address",
+            "Please fix the errors below"
+        ]
+    );
+    assert_eq!(outcome.form_errors[0].selector.as_deref(), Some("#email"));
+    assert!(outcome
+        .to_string()
+        .contains("form error: Email: Enter a valid email address"));
+    assert_eq!(session.form_errors().await.unwrap(), outcome.form_errors);
+    session.close().await.unwrap();
+}
@@ -0,0 +1,313 @@
+//! Gmail REST API backend, for Google Workspace tenants that disable IMAP.
+
+use crate::{
+    parse_message, EmailMessage, Error, ImapConfig, OAuth2, Result, SearchCriteria, WaitOptions,
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+
+const API: &str = "https://gmail.googleapis.com/gmail/v1/users";
+
+/// Candidates fetched per search when `headers` criteria, which Gmail can't
+/// search, have to be checked on each message.
+const HEADER_CANDIDATES: u32 = 25;
+
+/// Gmail API client with the same waiting and criteria API as
+/// [`ImapClient`](crate::ImapClient).
+///
+/// Criteria become a Gmail search query, so filtering happens server-side and
+/// `since`/`before` are exact. Mailboxes map to labels (`INBOX`, or a label
+/// name) and `include_junk` adds spam. Messages are identified by
+/// [`EmailMessage::gmail_id`]; `uid` is 0.
+pub struct GmailClient {
+    oauth2: OAuth2,
+    user: String,
+    mailbox: String,
+}
+
+impl GmailClient {
+    /// A client for the account in `config`, which needs `oauth2` with a
+    /// Gmail scope: `gmail.modify` to mark seen or clean up, otherwise
+    /// `gmail.readonly`. Host, port, and password are unused.
+    pub fn connect(config: &ImapConfig) -> Result<Self> {
+        let oauth2 = config
+            .oauth2
+            .clone()
+            .ok_or_else(|| Error::Auth("the Gmail API needs oauth2 credentials".into()))?;
+        // Fail on bad credentials now rather than on the first search
+        oauth2.token()?;
+        let user = match config.username.as_str() {
+            "" => "me".to_string(),
+            user => user.to_string(),
+        };
+        Ok(Self {
+            oauth2,
+            user,
+            mailbox: config.mailbox.clone(),
+        })
+    }
+
+    /// Wait for a matching message, polling every `poll_interval`.
+    pub fn wait_for_message(
+        &mut self,
+        criteria: &SearchCriteria,
+        options: &WaitOptions,
+    ) -> Result<EmailMessage> {
+        let deadline = Utc::now() + options.timeout;
+
+        loop {
+            if Utc::now() > deadline {
+                return Err(Error::Timeout);
+            }
+
+            if let Some(msg) = self.fetch_latest(criteria)? {
+                return Ok(msg);
+            }
+
+            let remaining = (deadline - Utc::now()).to_std().unwrap_or_default();
+            let poll = options.poll_interval.to_std().unwrap_or_default();
+            std::thread::sleep(poll.min(remaining));
+        }
+    }
+
+    /// The newest matching message.
+    pub fn fetch_latest(&mut self, criteria: &SearchCriteria) -> Result<Option<EmailMessage>> {
+        let query = gmail_query(criteria, &self.mailbox);
+        let max = if criteria.headers.is_empty() {
+            1
+        } else {
+            HEADER_CANDIDATES
+        };
+        let mut params = vec![("q", query), ("maxResults", max.to_string())];
+        if criteria.include_junk {
+            params.push(("includeSpamTrash", "true".into()));
+        }
+
+        let list = self.call("GET", "messages", &params, None)?;
+        let ids = list["messages"].as_array().cloned().unwrap_or_default();
+        for id in ids.iter().filter_map(|m| m["id"].as_str()) {
+            let msg = self.fetch_message(id)?;
+            let (headers, _) = mailparse::parse_headers(&msg.raw)?;
+            if !criteria.header_pairs_match(&headers) {
+                continue;
+            }
+            if criteria.mark_seen {
+                let _ = self.modify(id, &[], &["UNREAD"]);
+            }
+            return Ok(Some(msg));
+        }
+        Ok(None)
+    }
+
+    /// Download and parse one message.
+    pub fn fetch_message(&mut self, id: &str) -> Result<EmailMessage> {
+        let path = format!("messages/{}", id);
+        let json = self.call("GET", &path, &[("format", "raw".into())], None)?;
+        message_from_json(&json)
+    }
+
+    /// Move a message to the trash, where Gmail deletes it after 30 days.
+    pub fn delete(&mut self, id: &str) -> Result<()> {
+        self.call("POST", &format!("messages/{}/trash", id), &[], None)?;
+        Ok(())
+    }
+
+    /// Label a message with `label` (a name or ID) and take it out of the inbox.
+    pub fn move_to(&mut self, id: &str, label: &str) -> Result<()> {
+        let labels = self.call("GET", "labels", &[], None)?;
+        let label_id = labels["labels"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|l| {
+                l["id"].as_str() == Some(label)
+                    || l["name"]
+                        .as_str()
+                        .is_some_and(|n| n.eq_ignore_ascii_case(label))
+            })
+            .and_then(|l| l["id"].as_str())
+            .ok_or_else(|| Error::Gmail(format!("no label named {}", label)))?
+            .to_string();
+        self.modify(id, &[&label_id], &["INBOX"])
+    }
+
+    fn modify(&mut self, id: &str, add: &[&str], remove: &[&str]) -> Result<()> {
+        let body = json!({ "addLabelIds": add, "removeLabelIds": remove });
+        let path = format!("messages/{}/modify", id);
+        self.call("POST", &path, &[], Some(body))?;
+        Ok(())
+    }
+
+    fn call(
+        &self,
+        method: &str,
+        path: &str,
+        params: &[(&str, String)],
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let url = format!("{}/{}/{}", API, self.user, path);
+        let mut request = ureq::request(method, &url)
+            .set("Authorization", &format!("Bearer {}", self.oauth2.token()?));
+        for (name, value) in params {
+            request = request.query(name, value);
+        }
+        let response = match body {
+            Some(body) => request
+                .set("Content-Type", "application/json")
+                .send_string(&body.to_string()),
+            None if method == "POST" => request.send_string(""),
+            None => request.call(),
+        };
+
+        let text = match response {
+            Ok(resp) => resp.into_string(),
+            Err(ureq::Error::Status(code, resp)) => {
+                let body = resp.into_string().unwrap_or_default();
+                return Err(Error::Gmail(format!(
+                    "{} returned {}: {}",
+                    path,
+                    code,
+                    body.trim()
+                )));
+            }
+            Err(e) => return Err(Error::Gmail(format!("{} failed: {}", path, e))),
+        }
+        .map_err(|e| Error::Gmail(format!("failed to read response: {}", e)))?;
+
+        serde_json::from_str(&text).map_err(|e| Error::Gmail(format!("invalid response: {}", e)))
+    }
+}
+
+/// A Gmail search query for the criteria. Values are quoted so addresses and
+/// phrases match as typed.
+fn gmail_query(criteria: &SearchCriteria, mailbox: &str) -> String {
+    let quote = |v: &str| format!("\"{}\"", v.replace('"', ""));
+    let place = |m: &str| match m.to_ascii_uppercase().as_str() {
+        "INBOX" => "in:inbox".to_string(),
+        "SPAM" | "[GMAIL]/SPAM" => "in:spam".to_string(),
+        _ => format!("label:{}", quote(m)),
+    };
+
+    let mut places: Vec<String> = if criteria.mailboxes.is_empty() {
+        vec![place(mailbox)]
+    } else {
+        criteria.mailboxes.iter().map(|m| place(m)).collect()
+    };
+    if criteria.include_junk && !places.iter().any(|p| p == "in:spam") {
+        places.push("in:spam".into());
+    }
+    let mut parts = vec![match places.len() {
+        1 => places.remove(0),
+        _ => format!("{{{}}}", places.join(" ")),
+    }];
+
+    if criteria.unseen_only {
+        parts.push("is:unread".into());
+    }
+    let quoted = [
+        ("from", &criteria.from),
+        ("to", &criteria.to),
+        ("cc", &criteria.cc),
+        ("subject", &criteria.subject_contains),
+    ];
+    for (operator, value) in quoted {
+        if let Some(ref v) = value {
+            parts.push(format!("{}:{}", operator, quote(v)));
+        }
+    }
+    if let Some(ref body) = criteria.body_contains {
+        parts.push(quote(body));
+    }
+    if let Some(bytes) = criteria.larger {
+        parts.push(format!("larger:{}", bytes));
+    }
+    if let Some(bytes) = criteria.smaller {
+        parts.push(format!("smaller:{}", bytes));
+    }
+
+    let minutes = criteria
+        .since_minutes
+        .map(|m| Utc::now() - Duration::minutes(m));
+    if let Some(after) = minutes.into_iter().chain(criteria.since).max() {
+        parts.push(format!("after:{}", after.timestamp()));
+    }
+    if let Some(before) = criteria.before {
+        parts.push(format!("before:{}", before.timestamp()));
+    }
+
+    parts.join(" ")
+}
+
+/// A message from a `format=raw` response.
+fn message_from_json(json: &Value) -> Result<EmailMessage> {
+    let raw = json["raw"]
+        .as_str()
+        .ok_or_else(|| Error::Gmail("message has no raw content".into()))?;
+    let raw = URL_SAFE_NO_PAD
+        .decode(raw.trim_end_matches('='))
+        .map_err(|e| Error::Gmail(format!("invalid raw content: {}", e)))?;
+
+    let mut msg = parse_message(0, raw)?;
+    msg.gmail_id = json["id"].as_str().map(String::from);
+    let labels: Vec<&str> = json["labelIds"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    msg.mailbox = ["INBOX", "SPAM", "TRASH"]
+        .into_iter()
+        .find(|l| labels.contains(l))
+        .or(labels.first().copied())
+        .unwrap_or_default()
+        .to_string();
+    Ok(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn query_from_criteria() {
+        let criteria = SearchCriteria::new()
+            .from("no-reply@acme.test")
+            .unseen_only(true)
+            .subject_contains("Your \"code\"")
+            .body_contains("verification")
+            .larger(1000)
+            .since(Utc.with_ymd_and_hms(2024, 3, 10, 8, 0, 0).unwrap());
+        assert_eq!(
+            gmail_query(&criteria, "INBOX"),
+            "in:inbox is:unread from:\"no-reply@acme.test\" subject:\"Your code\" \
+             \"verification\" larger:1000 after:1710057600"
+        );
+
+        let criteria = SearchCriteria::new()
+            .mailboxes(["INBOX", "Receipts"])
+            .include_junk(true);
+        assert_eq!(
+            gmail_query(&criteria, "INBOX"),
+            "{in:inbox label:\"Receipts\" in:spam}"
+        );
+    }
+
+    #[test]
+    fn message_from_raw_response() {
+        let raw =
+            "Subject: Verify\r\nDate: Sun, 10 Mar 2024 09:00:00 +0000\r\n\r\nhttps://acme.test/v";
+        let json = json!({
+            "id": "18e2a1b2c3d4e5f6",
+            "labelIds": ["UNREAD", "SPAM"],
+            "raw": URL_SAFE_NO_PAD.encode(raw),
+        });
+        let msg = message_from_json(&json).unwrap();
+        assert_eq!(msg.gmail_id.as_deref(), Some("18e2a1b2c3d4e5f6"));
+        assert_eq!(msg.mailbox, "SPAM");
+        assert_eq!(msg.subject.as_deref(), Some("Verify"));
+        assert!(message_from_json(&json!({ "id": "1" })).is_err());
+    }
+}
@@ -1,7 +1,31 @@
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
+use imap_proto::NameAttribute;
 use mailparse::MailHeaderMap;
 use regex::Regex;
 
+mod gmail;
+#[cfg(feature = "async-native")]
+mod native;
+mod oauth2;
+pub mod otp;
+mod pop3;
+
+pub use gmail::GmailClient;
+pub use oauth2::{OAuth2, RefreshToken};
+pub use pop3::Pop3Client;
+
+/// How the mailbox is reached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Protocol {
+    #[default]
+    Imap,
+    /// For providers that only offer POP3; connect with [`Pop3Client`].
+    Pop3,
+    /// The Gmail REST API, for Workspace tenants with IMAP disabled; connect
+    /// with [`GmailClient`].
+    Gmail,
+}
+
 #[derive(Debug, Clone)]
 pub struct ImapConfig {
     pub host: String,
@@ -10,6 +34,9 @@ pub struct ImapConfig {
     pub username: String,
     pub password: String,
     pub mailbox: String,
+    /// Authenticate with XOAUTH2 instead of LOGIN; `password` is then unused.
+    pub oauth2: Option<OAuth2>,
+    pub protocol: Protocol,
 }
 
 impl ImapConfig {
@@ -26,6 +53,8 @@ impl ImapConfig {
             username: username.into(),
             password: password.into(),
             mailbox: "INBOX".into(),
+            oauth2: None,
+            protocol: Protocol::Imap,
         }
     }
 
@@ -38,15 +67,41 @@ impl ImapConfig {
         self.tls = tls;
         self
     }
+
+    pub fn oauth2(mut self, oauth2: OAuth2) -> Self {
+        self.oauth2 = Some(oauth2);
+        self
+    }
+
+    pub fn protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct SearchCriteria {
     pub from: Option<String>,
+    pub to: Option<String>,
+    pub cc: Option<String>,
     pub subject_contains: Option<String>,
+    pub body_contains: Option<String>,
+    /// Header name/value pairs; each value matches as a substring.
+    pub headers: Vec<(String, String)>,
+    /// Size in bytes, exclusive.
+    pub larger: Option<u32>,
+    pub smaller: Option<u32>,
     pub unseen_only: bool,
     pub since_minutes: Option<i64>,
+    /// Exact bounds on the server's received time. IMAP only searches by
+    /// day, so these are narrowed down after the search.
+    pub since: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
     pub mark_seen: bool,
+    /// Mailboxes to search instead of the configured one.
+    pub mailboxes: Vec<String>,
+    /// Also search the junk/spam mailbox.
+    pub include_junk: bool,
 }
 
 impl SearchCriteria {
@@ -59,11 +114,42 @@ impl SearchCriteria {
         self
     }
 
+    pub fn to(mut self, v: impl Into<String>) -> Self {
+        self.to = Some(v.into());
+        self
+    }
+
+    pub fn cc(mut self, v: impl Into<String>) -> Self {
+        self.cc = Some(v.into());
+        self
+    }
+
     pub fn subject_contains(mut self, v: impl Into<String>) -> Self {
         self.subject_contains = Some(v.into());
         self
     }
 
+    pub fn body_contains(mut self, v: impl Into<String>) -> Self {
+        self.body_contains = Some(v.into());
+        self
+    }
+
+    /// Add a header match, e.g. `header("X-Campaign", "signup")`.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn larger(mut self, bytes: u32) -> Self {
+        self.larger = Some(bytes);
+        self
+    }
+
+    pub fn smaller(mut self, bytes: u32) -> Self {
+        self.smaller = Some(bytes);
+        self
+    }
+
     pub fn unseen_only(mut self, v: bool) -> Self {
         self.unseen_only = v;
         self
@@ -74,16 +160,89 @@ impl SearchCriteria {
         self
     }
 
+    pub fn since(mut self, v: DateTime<Utc>) -> Self {
+        self.since = Some(v);
+        self
+    }
+
+    pub fn before(mut self, v: DateTime<Utc>) -> Self {
+        self.before = Some(v);
+        self
+    }
+
+    /// The mailboxes to search: these criteria's, or the configured one,
+    /// plus junk if asked for.
+    fn search_mailboxes(&self, configured: &str, junk: Option<String>) -> Vec<String> {
+        let mut mailboxes = if self.mailboxes.is_empty() {
+            vec![configured.to_string()]
+        } else {
+            self.mailboxes.clone()
+        };
+        if let Some(junk) = junk.filter(|_| self.include_junk) {
+            if !mailboxes.contains(&junk) {
+                mailboxes.push(junk);
+            }
+        }
+        mailboxes
+    }
+
+    /// Whether hits need their received time: to order them across
+    /// mailboxes, or to apply exact bounds.
+    fn needs_received(&self, mailboxes: usize) -> bool {
+        mailboxes > 1 || self.since.is_some() || self.before.is_some()
+    }
+
+    /// Whether a message received at `received` is within `since`/`before`.
+    fn received_in_range(&self, received: DateTime<Utc>) -> bool {
+        self.since.is_none_or(|t| received >= t) && self.before.is_none_or(|t| received < t)
+    }
+
     pub fn mark_seen(mut self, v: bool) -> Self {
         self.mark_seen = v;
         self
     }
+
+    pub fn mailboxes<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.mailboxes = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn include_junk(mut self, v: bool) -> Self {
+        self.include_junk = v;
+        self
+    }
+
+    /// Whether every `header(name, value)` pair matches, for backends that
+    /// can't search headers server-side.
+    pub(crate) fn header_pairs_match(&self, headers: &[mailparse::MailHeader]) -> bool {
+        self.headers
+            .iter()
+            .all(|(name, value)| header_contains(headers, name, value))
+    }
+}
+
+/// Whether a `name` header contains `needle`, ignoring case.
+pub(crate) fn header_contains(headers: &[mailparse::MailHeader], name: &str, needle: &str) -> bool {
+    let needle = needle.to_lowercase();
+    headers
+        .get_all_values(name)
+        .iter()
+        .any(|v| v.to_lowercase().contains(&needle))
 }
 
 #[derive(Debug, Clone)]
 pub struct WaitOptions {
     pub timeout: Duration,
     pub poll_interval: Duration,
+    /// Consecutive reconnects allowed when the connection drops mid-wait.
+    pub max_reconnects: u32,
+    /// Send NOOP (or re-issue IDLE) at least this often, so servers and NAT
+    /// gateways don't drop a quiet connection. `None` disables it.
+    pub keepalive: Option<Duration>,
 }
 
 impl WaitOptions {
@@ -91,19 +250,219 @@ impl WaitOptions {
         Self {
             timeout,
             poll_interval,
+            max_reconnects: 5,
+            keepalive: Some(Duration::minutes(5)),
+        }
+    }
+
+    pub fn max_reconnects(mut self, n: u32) -> Self {
+        self.max_reconnects = n;
+        self
+    }
+
+    pub fn keepalive(mut self, interval: Option<Duration>) -> Self {
+        self.keepalive = interval;
+        self
+    }
+
+    fn keepalive_std(&self) -> Option<std::time::Duration> {
+        self.keepalive
+            .and_then(|k| k.to_std().ok())
+            .filter(|k| !k.is_zero())
+    }
+}
+
+/// Delays between reconnects within one wait: 1s, doubling up to 30s.
+pub(crate) struct Backoff {
+    attempts: u32,
+    max: u32,
+}
+
+impl Backoff {
+    pub(crate) fn new(max: u32) -> Self {
+        Self { attempts: 0, max }
+    }
+
+    /// How long to wait before reconnecting after `error`. Errors other than
+    /// a dropped connection are returned as they are; once attempts run out,
+    /// or the deadline passes, the wait ends with [`Error::ConnectionLost`].
+    pub(crate) fn retry(
+        &mut self,
+        error: Error,
+        deadline: DateTime<Utc>,
+    ) -> Result<std::time::Duration> {
+        if !error.is_disconnect() {
+            return Err(error);
+        }
+        let remaining = (deadline - Utc::now()).to_std().unwrap_or_default();
+        if self.attempts >= self.max || remaining.is_zero() {
+            return Err(Error::ConnectionLost(format!(
+                "{} (gave up after {} reconnects)",
+                error, self.attempts
+            )));
         }
+        let delay = std::time::Duration::from_secs(1 << self.attempts.min(5));
+        self.attempts += 1;
+        Ok(delay.min(BACKOFF_MAX).min(remaining))
+    }
+
+    /// Start over once the connection works again.
+    pub(crate) fn reset(&mut self) {
+        self.attempts = 0;
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct EmailMessage {
+    /// Mailbox the message was found in; `uid` is only unique within it.
+    pub mailbox: String,
     pub uid: u32,
     pub subject: Option<String>,
     pub from: Option<String>,
     pub date: Option<String>,
     pub body_text: Option<String>,
     pub body_html: Option<String>,
+    /// Every header, in message order.
+    pub headers: Headers,
     pub raw: Vec<u8>,
+    /// Gmail message ID, set by [`GmailClient`], which identifies messages
+    /// by ID rather than `uid`.
+    pub gmail_id: Option<String>,
+}
+
+impl EmailMessage {
+    /// `Message-ID`, without angle brackets.
+    pub fn message_id(&self) -> Option<&str> {
+        self.headers
+            .get("Message-ID")
+            .and_then(|v| message_ids(v).next())
+    }
+
+    /// `In-Reply-To`: the message this one answers, without angle brackets.
+    pub fn in_reply_to(&self) -> Option<&str> {
+        self.headers
+            .get("In-Reply-To")
+            .and_then(|v| message_ids(v).next())
+    }
+
+    /// `References`: the thread's message IDs, oldest first.
+    pub fn references(&self) -> Vec<&str> {
+        self.headers
+            .get("References")
+            .map(|v| message_ids(v).collect())
+            .unwrap_or_default()
+    }
+
+    /// `List-Unsubscribe` URIs (`mailto:` or `https:`), in the sender's order
+    /// of preference.
+    pub fn list_unsubscribe(&self) -> Vec<&str> {
+        self.headers
+            .get_all("List-Unsubscribe")
+            .flat_map(message_ids)
+            .collect()
+    }
+}
+
+/// Message headers: an ordered multimap with case-insensitive lookup.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Headers(Vec<(String, String)>);
+
+impl Headers {
+    /// The first value of `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every value of `name`, in message order.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.0
+            .iter()
+            .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every header as (name, value), in message order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<&[mailparse::MailHeader<'_>]> for Headers {
+    fn from(headers: &[mailparse::MailHeader<'_>]) -> Self {
+        Self(
+            headers
+                .iter()
+                .map(|h| (h.get_key(), h.get_value()))
+                .collect(),
+        )
+    }
+}
+
+/// The `<...>` items of a header such as `References`, or the whole trimmed
+/// value when it has none.
+fn message_ids(value: &str) -> impl Iterator<Item = &str> {
+    let bracketed: Vec<&str> = value
+        .split('<')
+        .skip(1)
+        .filter_map(|part| part.split_once('>'))
+        .map(|(id, _)| id.trim())
+        .filter(|id| !id.is_empty())
+        .collect();
+    let bare = Some(value.trim()).filter(|v| bracketed.is_empty() && !v.is_empty());
+    bracketed.into_iter().chain(bare)
+}
+
+/// A message flag for [`ImapClient::flag`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Flag {
+    Seen,
+    Answered,
+    Flagged,
+    Deleted,
+    Draft,
+    /// A custom keyword such as `$Processed`. Characters IMAP doesn't allow
+    /// in keywords are dropped.
+    Keyword(String),
+}
+
+impl Flag {
+    fn to_imap(&self) -> String {
+        match self {
+            Self::Seen => "\\Seen".into(),
+            Self::Answered => "\\Answered".into(),
+            Self::Flagged => "\\Flagged".into(),
+            Self::Deleted => "\\Deleted".into(),
+            Self::Draft => "\\Draft".into(),
+            Self::Keyword(k) => k
+                .chars()
+                .filter(|c| c.is_ascii_graphic() && !"(){%*\"\\]".contains(*c))
+                .collect(),
+        }
+    }
+}
+
+/// Headers of a message, fetched without its body.
+#[derive(Debug, Clone)]
+pub struct EmailHeader {
+    pub mailbox: String,
+    pub uid: u32,
+    pub subject: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub date: Option<String>,
+    /// Message size in bytes.
+    pub size: Option<u32>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -112,6 +471,8 @@ pub enum Error {
     Imap(#[from] imap::Error),
     #[error("TLS error: {0}")]
     Tls(#[from] native_tls::Error),
+    #[error("OAuth2 error: {0}")]
+    Auth(String),
     #[error("Parse error: {0}")]
     Parse(#[from] mailparse::MailParseError),
     #[error("Timeout waiting for email")]
@@ -121,12 +482,118 @@ pub enum Error {
     #[cfg(feature = "async")]
     #[error("Join error: {0}")]
     Join(String),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("POP3 error: {0}")]
+    Pop3(String),
+    #[error("Gmail API error: {0}")]
+    Gmail(String),
+    #[error("Connection lost: {0}")]
+    ConnectionLost(String),
+    #[cfg(feature = "async-native")]
+    #[error("IMAP error: {0}")]
+    AsyncImap(#[from] async_imap::error::Error),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+impl Error {
+    /// Whether the connection dropped, rather than the server refusing a
+    /// command, so reconnecting may help.
+    pub(crate) fn is_disconnect(&self) -> bool {
+        match self {
+            Self::Io(_) | Self::ConnectionLost(_) => true,
+            Self::Imap(e) => matches!(
+                e,
+                imap::Error::Io(_) | imap::Error::ConnectionLost | imap::Error::Bye(_)
+            ),
+            #[cfg(feature = "async-native")]
+            Self::AsyncImap(e) => matches!(
+                e,
+                async_imap::error::Error::Io(_) | async_imap::error::Error::ConnectionLost
+            ),
+            _ => false,
+        }
+    }
+}
+
+/// RFC 2177 asks clients to re-issue IDLE at least every 29 minutes.
+const IDLE_MAX: std::time::Duration = std::time::Duration::from_secs(29 * 60);
+
+/// Longest delay between reconnect attempts.
+const BACKOFF_MAX: std::time::Duration = std::time::Duration::from_secs(30);
+
 pub struct ImapClient {
     session: imap::Session<imap::Connection>,
+    /// Kept to reconnect when the connection drops.
+    config: ImapConfig,
+    /// Server advertised IDLE and hasn't refused it yet.
+    idle: bool,
+    /// UID EXPUNGE (RFC 4315).
+    uidplus: bool,
+    /// UID MOVE (RFC 6851).
+    move_ext: bool,
+    /// Configured mailbox, searched unless the criteria name others.
+    mailbox: String,
+    selected: String,
+    /// Junk mailbox once looked up.
+    junk: Option<Option<String>>,
+}
+
+/// A search match. UIDs only order messages within one mailbox, so searches
+/// across mailboxes order by received time.
+struct Hit {
+    mailbox: String,
+    uid: u32,
+    received: Option<DateTime<Utc>>,
+}
+
+impl Hit {
+    /// Hits for `uids` in `mailbox`; with received times, only those within
+    /// the criteria's exact bounds.
+    fn matching(
+        mailbox: &str,
+        uids: Vec<u32>,
+        received: Option<Vec<(u32, DateTime<Utc>)>>,
+        criteria: &SearchCriteria,
+    ) -> Vec<Hit> {
+        let hit = |uid, received| Hit {
+            mailbox: mailbox.to_string(),
+            uid,
+            received,
+        };
+        match received {
+            None => uids.into_iter().map(|uid| hit(uid, None)).collect(),
+            Some(received) => received
+                .into_iter()
+                .filter(|(_, r)| criteria.received_in_range(*r))
+                .map(|(uid, r)| hit(uid, Some(r)))
+                .collect(),
+        }
+    }
+
+    fn sort_newest_first(hits: &mut [Hit]) {
+        hits.sort_unstable_by_key(|h| std::cmp::Reverse((h.received, h.uid)));
+    }
+
+    /// Distinct mailboxes in `hits`, in order.
+    fn mailboxes(hits: &[Hit]) -> Vec<&str> {
+        let mut mailboxes: Vec<&str> = Vec::new();
+        for hit in hits {
+            if !mailboxes.contains(&hit.mailbox.as_str()) {
+                mailboxes.push(&hit.mailbox);
+            }
+        }
+        mailboxes
+    }
+
+    /// Put headers in the order of the hits they were fetched for.
+    fn sort_like(hits: &[Hit], headers: &mut [EmailHeader]) {
+        headers.sort_by_key(|h| {
+            hits.iter()
+                .position(|p| p.uid == h.uid && p.mailbox == h.mailbox)
+        });
+    }
 }
 
 impl Drop for ImapClient {
@@ -137,6 +604,24 @@ impl Drop for ImapClient {
 
 impl ImapClient {
     pub fn connect(config: &ImapConfig) -> Result<Self> {
+        let (mut session, [idle, uidplus, move_ext]) = Self::open(config)?;
+        session.select(&config.mailbox)?;
+
+        Ok(Self {
+            session,
+            config: config.clone(),
+            idle,
+            uidplus,
+            move_ext,
+            mailbox: config.mailbox.clone(),
+            selected: config.mailbox.clone(),
+            junk: None,
+        })
+    }
+
+    /// A logged-in session, and whether the server has IDLE, UIDPLUS, and
+    /// MOVE.
+    fn open(config: &ImapConfig) -> Result<(imap::Session<imap::Connection>, [bool; 3])> {
         let mut builder = imap::ClientBuilder::new(&config.host, config.port);
         if config.tls {
             builder = builder.mode(imap::ConnectionMode::AutoTls);
@@ -146,56 +631,326 @@ impl ImapClient {
 
         let client = builder.connect()?;
 
-        let mut session = client
-            .login(&config.username, &config.password)
-            .map_err(|e| e.0)?;
+        let mut session = match config.oauth2 {
+            Some(ref oauth2) => {
+                let auth = oauth2::XOAuth2 {
+                    user: config.username.clone(),
+                    token: oauth2.token()?,
+                };
+                client.authenticate("XOAUTH2", &auth).map_err(|e| e.0)?
+            }
+            None => client
+                .login(&config.username, &config.password)
+                .map_err(|e| e.0)?,
+        };
 
-        session.select(&config.mailbox)?;
+        let caps = session.capabilities().ok();
+        let has = |name: &str| caps.as_ref().is_some_and(|c| c.has_str(name));
+        Ok((session, [has("IDLE"), has("UIDPLUS"), has("MOVE")]))
+    }
+
+    /// Log in again on a new connection and select the mailbox that was
+    /// selected, so a wait carries on where it was.
+    fn reconnect(&mut self) -> Result<()> {
+        let (mut session, [idle, uidplus, move_ext]) = Self::open(&self.config)?;
+        if self.selected.is_empty() {
+            self.selected = self.mailbox.clone();
+        }
+        session.select(&self.selected)?;
 
-        Ok(Self { session })
+        self.session = session;
+        (self.idle, self.uidplus, self.move_ext) = (idle, uidplus, move_ext);
+        Ok(())
     }
 
+    /// Wait for a matching message. With IDLE the server wakes the client as
+    /// soon as the mailbox changes; otherwise, or when searching several
+    /// mailboxes, it polls every `poll_interval`. If the connection drops,
+    /// the client reconnects with backoff and carries on.
     pub fn wait_for_message(
         &mut self,
         criteria: &SearchCriteria,
         options: &WaitOptions,
     ) -> Result<EmailMessage> {
-        let start = Utc::now();
-        let deadline = start + options.timeout;
+        let deadline = Utc::now() + options.timeout;
+        let mut backoff = Backoff::new(options.max_reconnects);
 
         loop {
             if Utc::now() > deadline {
                 return Err(Error::Timeout);
             }
 
-            if let Some(msg) = self.fetch_latest(criteria)? {
+            let mut step = self.wait_step(criteria, options, deadline);
+            while let Err(error) = step {
+                std::thread::sleep(backoff.retry(error, deadline)?);
+                step = self
+                    .reconnect()
+                    .and_then(|_| self.wait_step(criteria, options, deadline));
+            }
+            if let Ok(Some(msg)) = step {
                 return Ok(msg);
             }
+            backoff.reset();
+        }
+    }
+
+    /// Search once, then wait in IDLE or until the next poll.
+    fn wait_step(
+        &mut self,
+        criteria: &SearchCriteria,
+        options: &WaitOptions,
+        deadline: DateTime<Utc>,
+    ) -> Result<Option<EmailMessage>> {
+        if let Some(msg) = self.fetch_latest(criteria)? {
+            return Ok(Some(msg));
+        }
+
+        let remaining = (deadline - Utc::now()).to_std().unwrap_or_default();
+        let keepalive = options.keepalive_std();
+        if !self.idle(criteria, remaining, keepalive)? {
+            let poll = options.poll_interval.to_std().unwrap_or_default();
+            self.pause(poll.min(remaining), keepalive)?;
+        }
+        Ok(None)
+    }
+
+    /// Sleep for `duration`, sending NOOP every `keepalive`.
+    fn pause(
+        &mut self,
+        duration: std::time::Duration,
+        keepalive: Option<std::time::Duration>,
+    ) -> Result<()> {
+        let end = std::time::Instant::now() + duration;
+        let chunk = keepalive.unwrap_or(duration);
+        loop {
+            let left = end.saturating_duration_since(std::time::Instant::now());
+            if left.is_zero() {
+                return Ok(());
+            }
+            std::thread::sleep(left.min(chunk));
+            if left > chunk {
+                self.session.noop()?;
+            }
+        }
+    }
 
-            std::thread::sleep(options.poll_interval.to_std().unwrap_or_default());
+    /// Block in IDLE until the mailbox changes or `max` elapses, re-issuing
+    /// it every `keepalive`. Returns false without waiting when the server
+    /// doesn't support IDLE, so the caller polls.
+    fn idle(
+        &mut self,
+        criteria: &SearchCriteria,
+        max: std::time::Duration,
+        keepalive: Option<std::time::Duration>,
+    ) -> Result<bool> {
+        // IDLE only watches the selected mailbox
+        let single = self.search_mailboxes(criteria)?.len() == 1;
+        if !self.idle || !single {
+            return Ok(false);
         }
+        // Changes reported alongside the last command would not wake IDLE
+        if self.session.take_all_unsolicited().len() > 0 {
+            return Ok(true);
+        }
+        let limit = keepalive.unwrap_or(IDLE_MAX).min(IDLE_MAX);
+        let timeout = max.clamp(std::time::Duration::from_secs(1), limit);
+        let outcome = self
+            .session
+            .idle()
+            .timeout(timeout)
+            .keepalive(false)
+            .wait_while(imap::extensions::idle::stop_on_any);
+        match outcome.map_err(Error::from) {
+            Err(e) if e.is_disconnect() => return Err(e),
+            // Advertised but refused; poll from now on
+            Err(_) => self.idle = false,
+            Ok(_) => {}
+        }
+        Ok(true)
     }
 
+    /// The newest matching message. Its mailbox stays selected, so `flag`,
+    /// `delete`, and `move_to` apply to it.
     pub fn fetch_latest(&mut self, criteria: &SearchCriteria) -> Result<Option<EmailMessage>> {
-        let query = build_search_query(criteria);
-        let uids = self.session.uid_search(query)?;
-        let uid = match uids.iter().max() {
-            Some(u) => *u,
+        let hit = match self.search(criteria)?.into_iter().next() {
+            Some(h) => h,
             None => return Ok(None),
         };
 
+        self.select(&hit.mailbox)?;
+        let msg = self.fetch_message(hit.uid)?;
+
+        if criteria.mark_seen {
+            let _ = self.flag(hit.uid, &[Flag::Seen]);
+        }
+
+        Ok(Some(msg))
+    }
+
+    /// Headers of matching messages, newest first: skips `offset`, returns at
+    /// most `limit`. Bodies aren't downloaded and nothing is marked seen;
+    /// call [`fetch_message`](Self::fetch_message) for the ones you need,
+    /// after selecting their mailbox if the search covered several.
+    pub fn fetch_all(
+        &mut self,
+        criteria: &SearchCriteria,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<EmailHeader>> {
+        let page: Vec<Hit> = self
+            .search(criteria)?
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .collect();
+
+        let mut headers = Vec::with_capacity(page.len());
+        for mailbox in Hit::mailboxes(&page) {
+            let uids: Vec<u32> = page
+                .iter()
+                .filter(|h| h.mailbox == mailbox)
+                .map(|h| h.uid)
+                .collect();
+            self.select(mailbox)?;
+            let fetches = self
+                .session
+                .uid_fetch(uid_set(&uids), "(RFC822.SIZE BODY.PEEK[HEADER])")?;
+            for fetch in fetches.iter() {
+                if let (Some(uid), Some(raw)) = (fetch.uid, fetch.header()) {
+                    let mut header = parse_header(uid, fetch.size, raw)?;
+                    header.mailbox = mailbox.to_string();
+                    headers.push(header);
+                }
+            }
+        }
+        Hit::sort_like(&page, &mut headers);
+        Ok(headers)
+    }
+
+    /// Download and parse one message from the selected mailbox. Most
+    /// servers mark it seen.
+    pub fn fetch_message(&mut self, uid: u32) -> Result<EmailMessage> {
         let fetches = self.session.uid_fetch(uid.to_string(), "RFC822")?;
         let fetch = fetches.iter().next().ok_or(Error::NotFound)?;
         let raw = fetch.body().ok_or(Error::NotFound)?.to_vec();
+        let mut msg = parse_message(uid, raw)?;
+        msg.mailbox = self.selected.clone();
+        Ok(msg)
+    }
 
-        if criteria.mark_seen {
-            let _ = self.session.uid_store(uid.to_string(), "+FLAGS (\\Seen)");
+    /// Select the mailbox that `fetch_message`, `flag`, `delete`, and
+    /// `move_to` act on.
+    pub fn select(&mut self, mailbox: &str) -> Result<()> {
+        if self.selected != mailbox {
+            self.selected.clear();
+            self.session.select(mailbox)?;
+            self.selected = mailbox.to_string();
+        }
+        Ok(())
+    }
+
+    /// The mailbox the server marks `\Junk` (RFC 6154), or else one with a
+    /// common spam folder name.
+    pub fn junk_mailbox(&mut self) -> Result<Option<String>> {
+        if let Some(ref junk) = self.junk {
+            return Ok(junk.clone());
+        }
+        let names = self.session.list(None, Some("*"))?;
+        let junk = names
+            .iter()
+            .find(|n| n.attributes().contains(&NameAttribute::Junk))
+            .or_else(|| names.iter().find(|n| is_junk_name(n.name())))
+            .map(|n| n.name().to_string());
+        self.junk = Some(junk.clone());
+        Ok(junk)
+    }
+
+    /// Add flags to a message.
+    pub fn flag(&mut self, uid: u32, flags: &[Flag]) -> Result<()> {
+        let flags = flags
+            .iter()
+            .map(Flag::to_imap)
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.session
+            .uid_store(uid.to_string(), format!("+FLAGS.SILENT ({})", flags))?;
+        Ok(())
+    }
+
+    /// Delete a message. Without UIDPLUS this falls back to EXPUNGE, which
+    /// also removes other messages already flagged `\Deleted`.
+    pub fn delete(&mut self, uid: u32) -> Result<()> {
+        self.flag(uid, &[Flag::Deleted])?;
+        if self.uidplus {
+            self.session.uid_expunge(uid.to_string())?;
+        } else {
+            self.session.expunge()?;
+        }
+        Ok(())
+    }
+
+    /// Move a message to another mailbox, copying and deleting it when the
+    /// server lacks MOVE.
+    pub fn move_to(&mut self, uid: u32, mailbox: &str) -> Result<()> {
+        if self.move_ext {
+            self.session.uid_mv(uid.to_string(), mailbox)?;
+            return Ok(());
+        }
+        self.session
+            .uid_copy(uid.to_string(), format!("\"{}\"", escape_imap(mailbox)))?;
+        self.delete(uid)
+    }
+
+    /// Matches across the criteria's mailboxes, newest first, within the
+    /// exact `since`/`before` bounds.
+    fn search(&mut self, criteria: &SearchCriteria) -> Result<Vec<Hit>> {
+        let mailboxes = self.search_mailboxes(criteria)?;
+        let dated = criteria.needs_received(mailboxes.len());
+        let query = build_search_query(criteria);
+
+        let mut hits = Vec::new();
+        for mailbox in mailboxes {
+            self.select(&mailbox)?;
+            let uids: Vec<u32> = self.session.uid_search(&query)?.into_iter().collect();
+            let received = if dated {
+                Some(self.received(&uids)?)
+            } else {
+                None
+            };
+            hits.extend(Hit::matching(&mailbox, uids, received, criteria));
         }
+        Hit::sort_newest_first(&mut hits);
+        Ok(hits)
+    }
+
+    fn search_mailboxes(&mut self, criteria: &SearchCriteria) -> Result<Vec<String>> {
+        let junk = if criteria.include_junk {
+            self.junk_mailbox()?
+        } else {
+            None
+        };
+        Ok(criteria.search_mailboxes(&self.mailbox, junk))
+    }
 
-        Ok(Some(parse_message(uid, raw)?))
+    /// INTERNALDATE of each message in the selected mailbox.
+    fn received(&mut self, uids: &[u32]) -> Result<Vec<(u32, DateTime<Utc>)>> {
+        if uids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let fetches = self.session.uid_fetch(uid_set(uids), "INTERNALDATE")?;
+        Ok(fetches
+            .iter()
+            .filter_map(|f| Some((f.uid?, f.internal_date()?.with_timezone(&Utc))))
+            .collect())
     }
 }
 
+fn is_junk_name(name: &str) -> bool {
+    let leaf = name.rsplit(['/', '.']).next().unwrap_or(name);
+    ["junk", "spam", "junk email", "junk e-mail", "bulk mail"]
+        .contains(&leaf.to_lowercase().as_str())
+}
+
 fn build_search_query(criteria: &SearchCriteria) -> String {
     let mut parts: Vec<String> = Vec::new();
 
@@ -203,18 +958,48 @@ fn build_search_query(criteria: &SearchCriteria) -> String {
         parts.push("UNSEEN".into());
     }
 
-    if let Some(ref from) = criteria.from {
-        parts.push(format!("FROM \"{}\"", escape_imap(from)));
+    let quoted = [
+        ("FROM", &criteria.from),
+        ("TO", &criteria.to),
+        ("CC", &criteria.cc),
+        ("SUBJECT", &criteria.subject_contains),
+        ("BODY", &criteria.body_contains),
+    ];
+    for (key, value) in quoted {
+        if let Some(ref v) = value {
+            parts.push(format!("{} \"{}\"", key, escape_imap(v)));
+        }
     }
 
-    if let Some(ref subject) = criteria.subject_contains {
-        parts.push(format!("SUBJECT \"{}\"", escape_imap(subject)));
+    for (name, value) in &criteria.headers {
+        parts.push(format!(
+            "HEADER \"{}\" \"{}\"",
+            escape_imap(name),
+            escape_imap(value)
+        ));
+    }
+
+    if let Some(bytes) = criteria.larger {
+        parts.push(format!("LARGER {}", bytes));
+    }
+
+    if let Some(bytes) = criteria.smaller {
+        parts.push(format!("SMALLER {}", bytes));
     }
 
     if let Some(minutes) = criteria.since_minutes {
         let since = Utc::now() - Duration::minutes(minutes);
-        let date = since.format("%d-%b-%Y").to_string();
-        parts.push(format!("SINCE {}", date));
+        parts.push(format!("SINCE {}", imap_date(since)));
+    }
+
+    // Dates compare in the server's time zone, up to a day off from UTC
+    // either way, so search wide; fetch_latest applies the exact bounds.
+    if let Some(since) = criteria.since {
+        parts.push(format!("SINCE {}", imap_date(since - Duration::days(1))));
+    }
+
+    if let Some(before) = criteria.before {
+        parts.push(format!("BEFORE {}", imap_date(before + Duration::days(2))));
     }
 
     if parts.is_empty() {
@@ -224,6 +1009,17 @@ fn build_search_query(criteria: &SearchCriteria) -> String {
     }
 }
 
+fn uid_set(uids: &[u32]) -> String {
+    uids.iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn imap_date(t: DateTime<Utc>) -> String {
+    t.format("%d-%b-%Y").to_string()
+}
+
 fn escape_imap(s: &str) -> String {
     s.chars()
         .filter(|c| !c.is_control())
@@ -235,13 +1031,26 @@ fn escape_imap(s: &str) -> String {
         .collect()
 }
 
+fn parse_header(uid: u32, size: Option<u32>, raw: &[u8]) -> Result<EmailHeader> {
+    let (headers, _) = mailparse::parse_headers(raw)?;
+    Ok(EmailHeader {
+        mailbox: String::new(),
+        uid,
+        subject: headers.get_first_value("Subject"),
+        from: headers.get_first_value("From"),
+        to: headers.get_first_value("To"),
+        date: headers.get_first_value("Date"),
+        size,
+    })
+}
+
 fn parse_message(uid: u32, raw: Vec<u8>) -> Result<EmailMessage> {
     let parsed = mailparse::parse_mail(&raw)?;
 
-    let headers = parsed.get_headers();
-    let subject = headers.get_first_value("Subject");
-    let from = headers.get_first_value("From");
-    let date = headers.get_first_value("Date");
+    let headers = Headers::from(parsed.headers.as_slice());
+    let subject = headers.get("Subject").map(String::from);
+    let from = headers.get("From").map(String::from);
+    let date = headers.get("Date").map(String::from);
 
     let mut body_text: Option<String> = None;
     let mut body_html: Option<String> = None;
@@ -266,13 +1075,16 @@ fn parse_message(uid: u32, raw: Vec<u8>) -> Result<EmailMessage> {
     }
 
     Ok(EmailMessage {
+        mailbox: String::new(),
         uid,
         subject,
         from,
         date,
         body_text,
         body_html,
+        headers,
         raw,
+        gmail_id: None,
     })
 }
 
@@ -281,20 +1093,213 @@ pub struct LinkFilter {
     pub allow_domains: Option<Vec<String>>,
 }
 
+/// A link found in an email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailLink {
+    /// Destination, with click-tracking redirects unwrapped.
+    pub url: String,
+    /// Anchor text, if the link came from an `<a>` in the HTML body.
+    pub text: Option<String>,
+    /// Higher is likelier to be the link the email is about.
+    pub score: i32,
+}
+
+/// Words that mark the link an email is about, in its text or URL.
+const LINK_KEYWORDS: &[&str] = &[
+    "verify",
+    "verification",
+    "confirm",
+    "activate",
+    "activation",
+    "validate",
+    "magic",
+    "sign in",
+    "sign-in",
+    "signin",
+    "log in",
+    "login",
+    "reset",
+    "accept",
+    "invite",
+];
+
+/// Words that mark boilerplate links.
+const LINK_NOISE: &[&str] = &[
+    "unsubscribe",
+    "opt-out",
+    "opt out",
+    "preferences",
+    "privacy",
+    "terms",
+    "view in browser",
+    "view online",
+    "help",
+    "support",
+    "contact",
+];
+
+/// The highest-ranked link allowed by `filter`; see [`extract_links`].
 pub fn extract_first_link(msg: &EmailMessage, filter: &LinkFilter) -> Option<String> {
-    let hay = msg
-        .body_html
-        .as_deref()
-        .or(msg.body_text.as_deref())?;
+    extract_links(msg, filter).into_iter().next().map(|l| l.url)
+}
 
-    let re = Regex::new(r#"https?://[^\s"'<>)]+"#).ok()?;
-    for m in re.find_iter(hay) {
-        let link = m.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']);
-        if link_allowed(link, filter) {
-            return Some(link.to_string());
+/// Links allowed by `filter`, best first.
+///
+/// Anchors in the HTML body come first, then bare URLs in the text body or
+/// the HTML's visible text. Links whose text or URL says "verify", "confirm",
+/// "activate" and the like rank up; unsubscribe, privacy and help links rank
+/// down. Ties keep document order.
+///
+/// Click-tracking links (SendGrid, Mailgun, Outlook Safe Links) are replaced
+/// by their destination when it is carried in the link, either plainly or
+/// base64-encoded, and the domain filter applies to the destination. Opaque
+/// tracking links are kept as they are; opening them still redirects.
+pub fn extract_links(msg: &EmailMessage, filter: &LinkFilter) -> Vec<EmailLink> {
+    let mut found: Vec<(String, Option<String>)> = Vec::new();
+
+    if let Some(html) = msg.body_html.as_deref() {
+        let anchor = Regex::new(
+            r#"(?is)<a\b[^>]*?\bhref\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))[^>]*>(.*?)</a\s*>"#,
+        )
+        .unwrap();
+        for caps in anchor.captures_iter(html) {
+            let href = caps.get(1).or(caps.get(2)).or(caps.get(3)).unwrap();
+            let text = html_to_text(&caps[4]);
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            found.push((decode_entities(href.as_str().trim()), Some(text)));
         }
     }
-    None
+
+    let bare = Regex::new(r#"https?://[^\s"'<>)]+"#).unwrap();
+    let visible = msg.body_html.as_deref().map(html_to_text);
+    for hay in [msg.body_text.as_deref(), visible.as_deref()]
+        .into_iter()
+        .flatten()
+    {
+        for m in bare.find_iter(hay) {
+            let link = m.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            found.push((link.to_string(), None));
+        }
+    }
+
+    let mut links: Vec<EmailLink> = Vec::new();
+    for (link, text) in found {
+        if !link.starts_with("http://") && !link.starts_with("https://") {
+            continue;
+        }
+        let url = unwrap_tracking(&link);
+        if !link_allowed(&url, filter) {
+            continue;
+        }
+        let text = text.filter(|t| !t.is_empty());
+        match links.iter_mut().find(|l| l.url == url) {
+            Some(existing) => {
+                if existing.text.is_none() {
+                    existing.text = text;
+                }
+            }
+            None => links.push(EmailLink {
+                url,
+                text,
+                score: 0,
+            }),
+        }
+    }
+
+    for link in &mut links {
+        link.score = score_link(&link.url, link.text.as_deref());
+    }
+    // Stable: ties keep document order
+    links.sort_by_key(|l| std::cmp::Reverse(l.score));
+    links
+}
+
+fn score_link(url: &str, text: Option<&str>) -> i32 {
+    let url = url.to_lowercase();
+    let text = text.unwrap_or_default().to_lowercase();
+    let has = |hay: &str, words: &[&str]| words.iter().any(|w| hay.contains(w));
+
+    let mut score = 0;
+    if has(&text, LINK_KEYWORDS) {
+        score += 20;
+    }
+    if has(&url, LINK_KEYWORDS) {
+        score += 10;
+    }
+    if has(&text, LINK_NOISE) {
+        score -= 20;
+    }
+    if has(&url, LINK_NOISE) {
+        score -= 10;
+    }
+    score
+}
+
+/// The destination of a click-tracking link, or `link` itself when it isn't
+/// one or the destination can't be recovered.
+fn unwrap_tracking(link: &str) -> String {
+    let Ok(url) = url::Url::parse(link) else {
+        return link.to_string();
+    };
+    let host = url.host_str().unwrap_or_default();
+    let tracker = host.ends_with("sendgrid.net")
+        || host.ends_with("mailgun.org")
+        || (host.starts_with("email.") && url.path().starts_with("/c/"))
+        || host.ends_with("safelinks.protection.outlook.com");
+    if !tracker {
+        return link.to_string();
+    }
+
+    let query = url.query_pairs().map(|(_, v)| v.into_owned());
+    let path = url.path_segments().into_iter().flatten().map(String::from);
+    query
+        .chain(path)
+        .find_map(|value| embedded_url(&value))
+        .unwrap_or_else(|| link.to_string())
+}
+
+/// A URL carried in a tracking parameter, plainly or base64-encoded.
+fn embedded_url(value: &str) -> Option<String> {
+    use base64::engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+    use base64::Engine;
+
+    if value.starts_with("http://") || value.starts_with("https://") {
+        return Some(value.to_string());
+    }
+    let value = value.trim_end_matches('=');
+    let bytes = URL_SAFE_NO_PAD
+        .decode(value)
+        .or_else(|_| STANDARD_NO_PAD.decode(value))
+        .ok()?;
+    let decoded = String::from_utf8_lossy(&bytes);
+    let start = decoded
+        .find("https://")
+        .or_else(|| decoded.find("http://"))?;
+    let url: String = decoded[start..]
+        .chars()
+        .take_while(|c| !c.is_whitespace() && !matches!(c, '"' | '\'' | '<' | '>'))
+        .collect();
+    url::Url::parse(&url).ok().map(|_| url)
+}
+
+/// Visible text of an HTML fragment: scripts and styles dropped, tags
+/// replaced by spaces, and common entities decoded.
+pub(crate) fn html_to_text(html: &str) -> String {
+    let hidden = Regex::new(r"(?is)<(script|style|head)\b.*?</(?:script|style|head)>").unwrap();
+    let tag = Regex::new(r"<[^>]*>").unwrap();
+
+    let text = hidden.replace_all(html, " ");
+    decode_entities(&tag.replace_all(&text, " "))
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&#160;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
 }
 
 fn link_allowed(link: &str, filter: &LinkFilter) -> bool {
@@ -313,10 +1318,7 @@ fn link_allowed(link: &str, filter: &LinkFilter) -> bool {
 }
 
 pub fn extract_code(msg: &EmailMessage, regex: &Regex) -> Option<String> {
-    let hay = msg
-        .body_text
-        .as_deref()
-        .or(msg.body_html.as_deref())?;
+    let hay = msg.body_text.as_deref().or(msg.body_html.as_deref())?;
 
     regex
         .captures(hay)
@@ -324,7 +1326,10 @@ pub fn extract_code(msg: &EmailMessage, regex: &Regex) -> Option<String> {
         .map(|m| m.as_str().to_string())
 }
 
-#[cfg(feature = "async")]
+/// Runs the blocking client on Tokio's blocking pool. Calls on one client
+/// queue behind a mutex, and each client holds a thread while it waits; the
+/// `async-native` feature replaces this with a client on Tokio's own sockets.
+#[cfg(all(feature = "async", not(feature = "async-native")))]
 pub mod async_client {
     use super::*;
     use std::sync::{Arc, Mutex};
@@ -344,30 +1349,69 @@ pub mod async_client {
             })
         }
 
-        /// Poll for a matching message with async sleep between attempts.
-        /// Unlike the sync version, this releases the mutex between polls.
+        /// Wait for a matching message, in IDLE on a blocking thread when the
+        /// server supports it and with async sleep between polls otherwise.
+        /// If the connection drops, the client reconnects with backoff.
         pub async fn wait_for_message(
             &mut self,
             criteria: &SearchCriteria,
             options: &WaitOptions,
         ) -> Result<EmailMessage> {
             let deadline = Utc::now() + options.timeout;
+            let mut backoff = Backoff::new(options.max_reconnects);
 
             loop {
                 if Utc::now() > deadline {
                     return Err(Error::Timeout);
                 }
 
-                if let Some(msg) = self.fetch_latest(criteria).await? {
+                let mut step = self.wait_step(criteria, options, deadline).await;
+                while let Err(error) = step {
+                    tokio::time::sleep(backoff.retry(error, deadline)?).await;
+                    step = match self.blocking(|c| c.reconnect()).await {
+                        Ok(()) => self.wait_step(criteria, options, deadline).await,
+                        Err(e) => Err(e),
+                    };
+                }
+                if let Ok(Some(msg)) = step {
                     return Ok(msg);
                 }
+                backoff.reset();
+            }
+        }
 
-                let sleep_ms = options
-                    .poll_interval
-                    .num_milliseconds()
-                    .max(100) as u64;
-                tokio::time::sleep(std::time::Duration::from_millis(sleep_ms)).await;
+        /// Search once, then wait in IDLE or until the next poll, sending
+        /// NOOP every `keepalive` while sleeping.
+        async fn wait_step(
+            &mut self,
+            criteria: &SearchCriteria,
+            options: &WaitOptions,
+            deadline: DateTime<Utc>,
+        ) -> Result<Option<EmailMessage>> {
+            if let Some(msg) = self.fetch_latest(criteria).await? {
+                return Ok(Some(msg));
+            }
+
+            let remaining = (deadline - Utc::now()).to_std().unwrap_or_default();
+            let keepalive = options.keepalive_std();
+            let watch = criteria.clone();
+            let idled = self.blocking(move |c| c.idle(&watch, remaining, keepalive));
+            if idled.await? {
+                return Ok(None);
+            }
+
+            let sleep_ms = options.poll_interval.num_milliseconds().max(100) as u64;
+            let mut left = std::time::Duration::from_millis(sleep_ms);
+            let chunk = keepalive.unwrap_or(left);
+            while !left.is_zero() {
+                let nap = left.min(chunk);
+                tokio::time::sleep(nap).await;
+                left -= nap;
+                if !left.is_zero() {
+                    self.blocking(|c| Ok(c.session.noop()?)).await?;
+                }
             }
+            Ok(None)
         }
 
         pub async fn fetch_latest(
@@ -375,10 +1419,62 @@ pub mod async_client {
             criteria: &SearchCriteria,
         ) -> Result<Option<EmailMessage>> {
             let criteria = criteria.clone();
+            self.blocking(move |c| c.fetch_latest(&criteria)).await
+        }
+
+        pub async fn fetch_all(
+            &mut self,
+            criteria: &SearchCriteria,
+            limit: usize,
+            offset: usize,
+        ) -> Result<Vec<EmailHeader>> {
+            let criteria = criteria.clone();
+            self.blocking(move |c| c.fetch_all(&criteria, limit, offset))
+                .await
+        }
+
+        pub async fn fetch_message(&mut self, uid: u32) -> Result<EmailMessage> {
+            self.blocking(move |c| c.fetch_message(uid)).await
+        }
+
+        pub async fn select(&mut self, mailbox: &str) -> Result<()> {
+            let mailbox = mailbox.to_string();
+            self.blocking(move |c| c.select(&mailbox)).await
+        }
+
+        pub async fn junk_mailbox(&mut self) -> Result<Option<String>> {
+            self.blocking(|c| c.junk_mailbox()).await
+        }
+
+        pub async fn flag(&mut self, uid: u32, flags: &[Flag]) -> Result<()> {
+            let flags = flags.to_vec();
+            self.blocking(move |c| c.flag(uid, &flags)).await
+        }
+
+        pub async fn delete(&mut self, uid: u32) -> Result<()> {
+            self.blocking(move |c| c.delete(uid)).await
+        }
+
+        pub async fn move_to(&mut self, uid: u32, mailbox: &str) -> Result<()> {
+            let mailbox = mailbox.to_string();
+            self.blocking(move |c| c.move_to(uid, &mailbox)).await
+        }
+
+        /// End the session with LOGOUT.
+        pub async fn logout(self) -> Result<()> {
+            self.blocking(|c| Ok(c.session.logout()?)).await
+        }
+
+        /// Run `f` on the sync client on a blocking thread.
+        async fn blocking<T, F>(&self, f: F) -> Result<T>
+        where
+            T: Send + 'static,
+            F: FnOnce(&mut ImapClient) -> Result<T> + Send + 'static,
+        {
             let inner = self.inner.clone();
             tokio::task::spawn_blocking(move || {
                 let mut guard = inner.lock().unwrap();
-                guard.fetch_latest(&criteria)
+                f(&mut guard)
             })
             .await
             .map_err(|e| Error::Join(e.to_string()))?
@@ -386,8 +1482,10 @@ pub mod async_client {
     }
 }
 
-#[cfg(feature = "async")]
+#[cfg(all(feature = "async", not(feature = "async-native")))]
 pub use async_client::AsyncImapClient;
+#[cfg(feature = "async-native")]
+pub use native::AsyncImapClient;
 
 #[cfg(test)]
 mod tests {
@@ -395,13 +1493,16 @@ mod tests {
 
     fn make_msg(body_text: Option<&str>, body_html: Option<&str>) -> EmailMessage {
         EmailMessage {
+            mailbox: "INBOX".into(),
             uid: 1,
             subject: Some("Test".into()),
             from: Some("sender@example.com".into()),
             date: Some("Mon, 1 Jan 2024 00:00:00 +0000".into()),
             body_text: body_text.map(String::from),
             body_html: body_html.map(String::from),
+            headers: Headers::default(),
             raw: Vec::new(),
+            gmail_id: None,
         }
     }
 
@@ -463,6 +1564,50 @@ mod tests {
         assert!(extract_first_link(&msg, &LinkFilter::default()).is_none());
     }
 
+    #[test]
+    fn extract_links_ranks_by_keywords() {
+        let msg = make_msg(
+            Some("Questions? https://example.com/help"),
+            Some(
+                r#"<a href="https://example.com/">Acme</a>
+                <a href='https://example.com/u?id=1&amp;t=2'><b>Unsubscribe</b></a>
+                <a href="https://example.com/a/8f3k">Activate your account</a>"#,
+            ),
+        );
+        let links = extract_links(&msg, &LinkFilter::default());
+        assert_eq!(links[0].url, "https://example.com/a/8f3k");
+        assert_eq!(links[0].text.as_deref(), Some("Activate your account"));
+        assert_eq!(links[1].url, "https://example.com/");
+        assert_eq!(links.last().unwrap().url, "https://example.com/u?id=1&t=2");
+        assert_eq!(links.len(), 4);
+        assert_eq!(
+            extract_first_link(&msg, &LinkFilter::default()).as_deref(),
+            Some("https://example.com/a/8f3k")
+        );
+    }
+
+    #[test]
+    fn extract_links_unwraps_tracking_redirects() {
+        use base64::Engine;
+        let target = "https://app.example.com/verify?token=abc";
+        let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(target);
+        let html = format!(
+            r#"<a href="https://u123.ct.sendgrid.net/ls/click?upn={}">Verify</a>
+            <a href="https://email.mg.example.com/c/{}">Confirm</a>
+            <a href="https://u123.ct.sendgrid.net/ls/click?upn=opaque">Help</a>
+            <a href="https://nam02.safelinks.protection.outlook.com/?url=https%3A%2F%2Fexample.com%2Fconfirm&amp;data=1">Confirm</a>"#,
+            encoded, encoded
+        );
+        let msg = make_msg(None, Some(&html));
+        let filter = LinkFilter {
+            allow_domains: Some(vec!["example.com".into()]),
+        };
+        let links = extract_links(&msg, &filter);
+        let urls: Vec<&str> = links.iter().map(|l| l.url.as_str()).collect();
+        // Duplicates merge; the opaque SendGrid link fails the domain filter
+        assert_eq!(urls, [target, "https://example.com/confirm"]);
+    }
+
     // --- extract_code ---
 
     #[test]
@@ -537,6 +1682,39 @@ mod tests {
         assert!(q.starts_with("SINCE "));
     }
 
+    #[test]
+    fn search_query_addresses_headers_and_size() {
+        let criteria = SearchCriteria::new()
+            .to("me+signup@test.com")
+            .cc("team@test.com")
+            .body_contains("one-time code")
+            .header("X-Campaign", "otp")
+            .larger(1_000)
+            .smaller(50_000);
+        assert_eq!(
+            build_search_query(&criteria),
+            r#"TO "me+signup@test.com" CC "team@test.com" BODY "one-time code" HEADER "X-Campaign" "otp" LARGER 1000 SMALLER 50000"#
+        );
+    }
+
+    #[test]
+    fn search_query_exact_bounds() {
+        let since = DateTime::parse_from_rfc3339("2024-03-10T08:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let before = since + Duration::hours(1);
+        let criteria = SearchCriteria::new().since(since).before(before);
+        assert_eq!(
+            build_search_query(&criteria),
+            "SINCE 09-Mar-2024 BEFORE 12-Mar-2024"
+        );
+
+        assert!(criteria.received_in_range(since));
+        assert!(criteria.received_in_range(since + Duration::minutes(30)));
+        assert!(!criteria.received_in_range(since - Duration::seconds(1)));
+        assert!(!criteria.received_in_range(before));
+    }
+
     // --- escape_imap ---
 
     #[test]
@@ -570,4 +1748,269 @@ mod tests {
         assert!(msg.body_html.as_ref().unwrap().contains("<b>bold</b>"));
         assert!(msg.body_text.is_none());
     }
+
+    #[test]
+    fn parse_message_headers() {
+        let raw = b"Received: from a\r\nReceived: from b\r\nMessage-ID: <abc@mail.test>\r\n\
+            In-Reply-To: <root@mail.test>\r\nReferences: <root@mail.test>\r\n <mid@mail.test>\r\n\
+            List-Unsubscribe: <mailto:u@mail.test?subject=stop>, <https://mail.test/u/1>\r\n\
+            X-Campaign: signup\r\n\r\nBody";
+        let msg = parse_message(1, raw.to_vec()).unwrap();
+        assert_eq!(msg.headers.len(), 7);
+        assert_eq!(msg.headers.get("x-campaign"), Some("signup"));
+        assert_eq!(
+            msg.headers.get_all("Received").collect::<Vec<_>>(),
+            ["from a", "from b"]
+        );
+        assert_eq!(msg.message_id(), Some("abc@mail.test"));
+        assert_eq!(msg.in_reply_to(), Some("root@mail.test"));
+        assert_eq!(msg.references(), ["root@mail.test", "mid@mail.test"]);
+        assert_eq!(
+            msg.list_unsubscribe(),
+            ["mailto:u@mail.test?subject=stop", "https://mail.test/u/1"]
+        );
+    }
+
+    // --- IDLE ---
+
+    /// Replays canned server responses and records what the client sends.
+    struct Script {
+        responses: std::io::Cursor<Vec<u8>>,
+        sent: std::sync::Arc<std::sync::Mutex<Vec<u8>>>,
+    }
+
+    impl std::io::Read for Script {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.responses.read(buf)
+        }
+    }
+
+    impl std::io::Write for Script {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.sent.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl imap::extensions::idle::SetReadTimeout for Script {
+        fn set_read_timeout(&mut self, _: Option<std::time::Duration>) -> imap::error::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A logged-in client talking to `responses`, and the bytes it sends.
+    fn scripted(responses: String) -> (ImapClient, std::sync::Arc<std::sync::Mutex<Vec<u8>>>) {
+        let sent = std::sync::Arc::default();
+        let stream: imap::Connection = Box::new(Script {
+            responses: std::io::Cursor::new(format!("a1 OK LOGIN done\r\n{}", responses).into()),
+            sent: std::sync::Arc::clone(&sent),
+        });
+        let session = imap::Client::new(stream)
+            .login("user", "pass")
+            .map_err(|e| e.0)
+            .unwrap();
+        let client = ImapClient {
+            session,
+            config: ImapConfig::new("127.0.0.1", 143, "user", "pass").tls(false),
+            idle: true,
+            uidplus: true,
+            move_ext: false,
+            mailbox: "INBOX".into(),
+            selected: "INBOX".into(),
+            junk: None,
+        };
+        (client, sent)
+    }
+
+    #[test]
+    fn wait_for_message_idles_until_mailbox_changes() {
+        let raw = "Subject: Code\r\n\r\nYour code is 123456";
+        let (mut client, sent) = scripted(format!(
+            "* SEARCH\r\na2 OK SEARCH done\r\n\
+             + idling\r\n* 1 EXISTS\r\na3 OK IDLE done\r\n\
+             * SEARCH 7\r\na4 OK SEARCH done\r\n\
+             * 1 FETCH (UID 7 RFC822 {{{}}}\r\n{})\r\na5 OK FETCH done\r\n",
+            raw.len(),
+            raw
+        ));
+
+        let criteria = SearchCriteria::new().unseen_only(false);
+        // A poll interval longer than the timeout would fail without IDLE
+        let options = WaitOptions::new(Duration::seconds(30), Duration::minutes(5));
+        let msg = client.wait_for_message(&criteria, &options).unwrap();
+        assert_eq!(msg.uid, 7);
+        assert_eq!(msg.subject.as_deref(), Some("Code"));
+
+        let sent = String::from_utf8(sent.lock().unwrap().clone()).unwrap();
+        assert!(sent.contains("a3 IDLE\r\nDONE\r\n"));
+    }
+
+    /// A plaintext IMAP server for one connection that finds UID 7, holding
+    /// `message`. Returns its port and, once the client logs out, the
+    /// commands it received.
+    pub(crate) fn serve_imap(message: &'static str) -> (u16, std::thread::JoinHandle<Vec<String>>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut received = Vec::new();
+            writer.write_all(b"* OK ready\r\n").unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 {
+                let (tag, command) = line.trim_end().split_once(' ').unwrap();
+                let data = match command.split(' ').next().unwrap() {
+                    "CAPABILITY" => "* CAPABILITY IMAP4rev1\r\n".to_string(),
+                    "UID" if command.starts_with("UID SEARCH") => "* SEARCH 7\r\n".into(),
+                    "UID" => format!(
+                        "* 1 FETCH (UID 7 RFC822 {{{}}}\r\n{})\r\n",
+                        message.len(),
+                        message
+                    ),
+                    "LOGOUT" => "* BYE\r\n".into(),
+                    _ => String::new(),
+                };
+                write!(writer, "{}{} OK done\r\n", data, tag).unwrap();
+                received.push(command.to_string());
+                line.clear();
+            }
+            received
+        });
+        (port, server)
+    }
+
+    #[test]
+    fn wait_for_message_reconnects_after_drop() {
+        let (port, server) = serve_imap("Subject: Code\r\n\r\nYour code is 123456");
+
+        // The scripted connection ends before answering the first search
+        let (mut client, _) = scripted(String::new());
+        client.config.port = port;
+        let criteria = SearchCriteria::new().unseen_only(false);
+        let options = WaitOptions::new(Duration::seconds(30), Duration::seconds(1));
+        let msg = client.wait_for_message(&criteria, &options).unwrap();
+        assert_eq!(msg.uid, 7);
+        drop(client);
+
+        let received = server.join().unwrap();
+        assert!(received[0].starts_with("LOGIN"));
+        assert!(received.contains(&"SELECT \"INBOX\"".to_string()));
+    }
+
+    // --- fetch_all ---
+
+    #[test]
+    fn fetch_all_pages_newest_first() {
+        let (h9, h5) = (
+            "Subject: Code 2\r\nTo: me@test.com\r\n\r\n",
+            "Subject: Code 1\r\nTo: me@test.com\r\n\r\n",
+        );
+        let body = "Subject: Code 1\r\n\r\nYour code is 111111";
+        let (mut client, sent) = scripted(format!(
+            "* SEARCH 3 9 5\r\na2 OK SEARCH done\r\n\
+             * 2 FETCH (UID 5 RFC822.SIZE 1200 BODY[HEADER] {{{}}}\r\n{})\r\n\
+             * 3 FETCH (UID 9 RFC822.SIZE 900 BODY[HEADER] {{{}}}\r\n{})\r\na3 OK FETCH done\r\n\
+             * 2 FETCH (UID 5 RFC822 {{{}}}\r\n{})\r\na4 OK FETCH done\r\n",
+            h5.len(),
+            h5,
+            h9.len(),
+            h9,
+            body.len(),
+            body
+        ));
+
+        let criteria = SearchCriteria::new().unseen_only(false);
+        let headers = client.fetch_all(&criteria, 2, 0).unwrap();
+        assert_eq!(headers.iter().map(|h| h.uid).collect::<Vec<_>>(), [9, 5]);
+        assert_eq!(headers[0].subject.as_deref(), Some("Code 2"));
+        assert_eq!(headers[0].to.as_deref(), Some("me@test.com"));
+        assert_eq!(headers[1].size, Some(1200));
+
+        let msg = client.fetch_message(headers[1].uid).unwrap();
+        assert!(msg.body_text.unwrap().contains("111111"));
+
+        let sent = String::from_utf8(sent.lock().unwrap().clone()).unwrap();
+        assert!(sent.contains("a3 UID FETCH 9,5 (RFC822.SIZE BODY.PEEK[HEADER])"));
+        assert!(sent.contains("a4 UID FETCH 5 RFC822"));
+    }
+
+    #[test]
+    fn fetch_all_past_the_end() {
+        let (mut client, _) = scripted("* SEARCH 3\r\na2 OK SEARCH done\r\n".into());
+        let criteria = SearchCriteria::new().unseen_only(false);
+        assert!(client.fetch_all(&criteria, 10, 1).unwrap().is_empty());
+    }
+
+    // --- flags, delete, move ---
+
+    #[test]
+    fn flag_names() {
+        assert_eq!(Flag::Seen.to_imap(), r"\Seen");
+        assert_eq!(
+            Flag::Keyword("$Processed (otp)".into()).to_imap(),
+            "$Processedotp"
+        );
+    }
+
+    #[test]
+    fn move_without_move_extension_copies_and_deletes() {
+        let (mut client, sent) = scripted(
+            "a2 OK COPY done\r\n\
+             a3 OK STORE done\r\n\
+             * 3 EXPUNGE\r\na4 OK EXPUNGE done\r\n\
+             a5 OK STORE done\r\n"
+                .into(),
+        );
+        client.move_to(5, "Archive/OTP codes").unwrap();
+        client.flag(6, &[Flag::Seen, Flag::Flagged]).unwrap();
+
+        let sent = String::from_utf8(sent.lock().unwrap().clone()).unwrap();
+        assert!(sent.contains("a2 UID COPY 5 \"Archive/OTP codes\"\r\n"));
+        assert!(sent.contains("a3 UID STORE 5 +FLAGS.SILENT (\\Deleted)\r\n"));
+        assert!(sent.contains("a4 UID EXPUNGE 5\r\n"));
+        assert!(sent.contains("a5 UID STORE 6 +FLAGS.SILENT (\\Seen \\Flagged)\r\n"));
+    }
+
+    // --- multiple mailboxes ---
+
+    #[test]
+    fn fetch_latest_searches_junk_too() {
+        let raw = "Subject: Verify\r\n\r\nhttps://example.com/verify";
+        let (mut client, sent) = scripted(format!(
+            "* LIST (\\HasNoChildren) \"/\" \"INBOX\"\r\n\
+             * LIST (\\HasNoChildren \\Junk) \"/\" \"[Gmail]/Spam\"\r\na2 OK LIST done\r\n\
+             * SEARCH 4\r\na3 OK SEARCH done\r\n\
+             * 1 FETCH (UID 4 INTERNALDATE \"10-Mar-2024 08:00:00 +0000\")\r\na4 OK FETCH done\r\n\
+             * 2 EXISTS\r\na5 OK [READ-WRITE] SELECT done\r\n\
+             * SEARCH 2\r\na6 OK SEARCH done\r\n\
+             * 2 FETCH (UID 2 INTERNALDATE \"10-Mar-2024 09:00:00 +0000\")\r\na7 OK FETCH done\r\n\
+             * 2 FETCH (UID 2 RFC822 {{{}}}\r\n{})\r\na8 OK FETCH done\r\n",
+            raw.len(),
+            raw
+        ));
+
+        let criteria = SearchCriteria::new().unseen_only(false).include_junk(true);
+        let msg = client.fetch_latest(&criteria).unwrap().unwrap();
+        assert_eq!(msg.mailbox, "[Gmail]/Spam");
+        assert_eq!(msg.uid, 2);
+
+        let sent = String::from_utf8(sent.lock().unwrap().clone()).unwrap();
+        assert!(sent.contains("a5 SELECT \"[Gmail]/Spam\"\r\n"));
+        assert!(sent.contains("a8 UID FETCH 2 RFC822\r\n"));
+    }
+
+    #[test]
+    fn junk_names() {
+        assert!(is_junk_name("Spam"));
+        assert!(is_junk_name("INBOX.Junk"));
+        assert!(is_junk_name("Junk Email"));
+        assert!(!is_junk_name("INBOX"));
+    }
 }
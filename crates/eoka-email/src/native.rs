@@ -0,0 +1,660 @@
+//! Tokio-native IMAP client on `async-imap`. Each client owns its connection
+//! and speaks IMAP directly on the runtime, so many inboxes don't need a
+//! blocking thread each and calls on different clients never wait on one
+//! another.
+
+use crate::{
+    build_search_query, escape_imap, is_junk_name, oauth2, parse_header, parse_message, uid_set,
+    Backoff, EmailHeader, EmailMessage, Error, Flag, Hit, ImapConfig, Result, SearchCriteria,
+    WaitOptions, IDLE_MAX,
+};
+use async_imap::types::{Fetch, Name, NameAttribute, UnsolicitedResponse};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use std::fmt::Debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send + Debug {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send + Debug> Stream for T {}
+
+type Session = async_imap::Session<Box<dyn Stream>>;
+
+fn quoted(s: &str) -> String {
+    format!("\"{}\"", escape_imap(s))
+}
+
+/// Async IMAP client on Tokio's own sockets.
+///
+/// Same API as the blocking-thread client. Dropping it closes the connection
+/// without LOGOUT; call [`logout`](Self::logout) to end the session cleanly.
+pub struct AsyncImapClient {
+    /// Lent to IDLE while it waits; `None` if IDLE failed, until reconnect.
+    session: Option<Session>,
+    /// Kept to reconnect when the connection drops.
+    config: ImapConfig,
+    /// Server advertised IDLE and hasn't refused it yet.
+    idle: bool,
+    uidplus: bool,
+    move_ext: bool,
+    mailbox: String,
+    selected: String,
+    junk: Option<Option<String>>,
+}
+
+impl AsyncImapClient {
+    /// Connect and log in. Like the blocking client, `tls` means TLS from the
+    /// start on port 993 and STARTTLS on any other port.
+    pub async fn connect(config: &ImapConfig) -> Result<Self> {
+        let (session, [idle, uidplus, move_ext]) = Self::open(config).await?;
+        let mut client = Self {
+            session: Some(session),
+            config: config.clone(),
+            idle,
+            uidplus,
+            move_ext,
+            mailbox: config.mailbox.clone(),
+            selected: String::new(),
+            junk: None,
+        };
+        client.select(&config.mailbox).await?;
+        Ok(client)
+    }
+
+    /// A logged-in session, and whether the server has IDLE, UIDPLUS, and
+    /// MOVE.
+    async fn open(config: &ImapConfig) -> Result<(Session, [bool; 3])> {
+        let tcp = TcpStream::connect((config.host.as_str(), config.port)).await?;
+        let client = if !config.tls {
+            let mut client = async_imap::Client::new(Box::new(tcp) as Box<dyn Stream>);
+            greeting(&mut client).await?;
+            client
+        } else if config.port == 993 {
+            let tls = tls(config, tcp).await?;
+            let mut client = async_imap::Client::new(Box::new(tls) as Box<dyn Stream>);
+            greeting(&mut client).await?;
+            client
+        } else {
+            let mut plain = async_imap::Client::new(tcp);
+            greeting(&mut plain).await?;
+            plain.run_command_and_check_ok("STARTTLS", None).await?;
+            let tls = tls(config, plain.into_inner()).await?;
+            async_imap::Client::new(Box::new(tls) as Box<dyn Stream>)
+        };
+
+        let mut session = match config.oauth2 {
+            Some(ref oauth2) => {
+                // Refreshing the token is a blocking HTTP request
+                let oauth2 = oauth2.clone();
+                let token = tokio::task::spawn_blocking(move || oauth2.token())
+                    .await
+                    .map_err(|e| Error::Join(e.to_string()))??;
+                let auth = oauth2::XOAuth2 {
+                    user: config.username.clone(),
+                    token,
+                };
+                client
+                    .authenticate("XOAUTH2", &auth)
+                    .await
+                    .map_err(|e| e.0)?
+            }
+            None => client
+                .login(&config.username, &config.password)
+                .await
+                .map_err(|e| e.0)?,
+        };
+
+        let caps = session.capabilities().await?;
+        let has = |name: &str| caps.has_str(name);
+        Ok((session, [has("IDLE"), has("UIDPLUS"), has("MOVE")]))
+    }
+
+    /// Log in again on a new connection and select the mailbox that was
+    /// selected, so a wait carries on where it was.
+    async fn reconnect(&mut self) -> Result<()> {
+        let (session, [idle, uidplus, move_ext]) = Self::open(&self.config).await?;
+        let mailbox = match self.selected.as_str() {
+            "" => self.mailbox.clone(),
+            selected => selected.to_string(),
+        };
+
+        self.session = Some(session);
+        (self.idle, self.uidplus, self.move_ext) = (idle, uidplus, move_ext);
+        self.selected.clear();
+        self.select(&mailbox).await
+    }
+
+    fn session(&mut self) -> Result<&mut Session> {
+        self.session
+            .as_mut()
+            .ok_or_else(|| Error::ConnectionLost("IDLE failed".into()))
+    }
+
+    /// Whether the server reported new or expunged messages in the selected
+    /// mailbox since the last call.
+    fn take_changes(&mut self) -> bool {
+        let Some(session) = &self.session else {
+            return false;
+        };
+        let mut changed = false;
+        while let Ok(response) = session.unsolicited_responses.try_recv() {
+            changed |= matches!(
+                response,
+                UnsolicitedResponse::Exists(_)
+                    | UnsolicitedResponse::Recent(_)
+                    | UnsolicitedResponse::Expunge(_)
+            );
+        }
+        changed
+    }
+
+    /// End the session with LOGOUT.
+    pub async fn logout(mut self) -> Result<()> {
+        self.session()?.logout().await?;
+        Ok(())
+    }
+
+    /// Wait for a matching message, in IDLE when the server supports it and
+    /// with async sleep between polls otherwise. If the connection drops, the
+    /// client reconnects with backoff and carries on.
+    pub async fn wait_for_message(
+        &mut self,
+        criteria: &SearchCriteria,
+        options: &WaitOptions,
+    ) -> Result<EmailMessage> {
+        let deadline = Utc::now() + options.timeout;
+        let mut backoff = Backoff::new(options.max_reconnects);
+
+        loop {
+            if Utc::now() > deadline {
+                return Err(Error::Timeout);
+            }
+
+            let mut step = self.wait_step(criteria, options, deadline).await;
+            while let Err(error) = step {
+                tokio::time::sleep(backoff.retry(error, deadline)?).await;
+                step = match self.reconnect().await {
+                    Ok(()) => self.wait_step(criteria, options, deadline).await,
+                    Err(e) => Err(e),
+                };
+            }
+            if let Ok(Some(msg)) = step {
+                return Ok(msg);
+            }
+            backoff.reset();
+        }
+    }
+
+    /// Search once, then wait in IDLE or until the next poll.
+    async fn wait_step(
+        &mut self,
+        criteria: &SearchCriteria,
+        options: &WaitOptions,
+        deadline: DateTime<Utc>,
+    ) -> Result<Option<EmailMessage>> {
+        if let Some(msg) = self.fetch_latest(criteria).await? {
+            return Ok(Some(msg));
+        }
+
+        let remaining = (deadline - Utc::now()).to_std().unwrap_or_default();
+        let keepalive = options.keepalive_std();
+        if !self.idle(criteria, remaining, keepalive).await? {
+            let poll = options.poll_interval.to_std().unwrap_or_default();
+            self.pause(poll.min(remaining), keepalive).await?;
+        }
+        Ok(None)
+    }
+
+    /// Sleep for `duration`, sending NOOP every `keepalive`.
+    async fn pause(
+        &mut self,
+        duration: std::time::Duration,
+        keepalive: Option<std::time::Duration>,
+    ) -> Result<()> {
+        let end = tokio::time::Instant::now() + duration;
+        let chunk = keepalive.unwrap_or(duration);
+        loop {
+            let left = end.saturating_duration_since(tokio::time::Instant::now());
+            if left.is_zero() {
+                return Ok(());
+            }
+            tokio::time::sleep(left.min(chunk)).await;
+            if left > chunk {
+                self.session()?.noop().await?;
+            }
+        }
+    }
+
+    /// Wait in IDLE until the mailbox changes or `max` elapses, re-issuing it
+    /// every `keepalive`. Returns false without waiting when the server
+    /// doesn't support IDLE, so the caller polls.
+    async fn idle(
+        &mut self,
+        criteria: &SearchCriteria,
+        max: std::time::Duration,
+        keepalive: Option<std::time::Duration>,
+    ) -> Result<bool> {
+        // IDLE only watches the selected mailbox
+        let single = self.search_mailboxes(criteria).await?.len() == 1;
+        if !self.idle || !single {
+            return Ok(false);
+        }
+        // Changes reported alongside the last command would not wake IDLE
+        if self.take_changes() {
+            return Ok(true);
+        }
+
+        self.session()?;
+        let mut handle = self.session.take().unwrap().idle();
+        if handle.init().await.is_err() {
+            // Advertised but refused, which leaves the session mid-command;
+            // start over and poll from now on
+            self.reconnect().await?;
+            self.idle = false;
+            return Ok(false);
+        }
+
+        let limit = keepalive.unwrap_or(IDLE_MAX).min(IDLE_MAX);
+        let max = max.clamp(std::time::Duration::from_secs(1), limit);
+        {
+            // Any response restarts the inner timeout; the outer one doesn't
+            let (wait, _stop) = handle.wait_with_timeout(max);
+            if let Ok(woken) = tokio::time::timeout(max, wait).await {
+                woken?;
+            }
+        }
+        self.session = Some(handle.done().await?);
+        self.take_changes();
+        Ok(true)
+    }
+
+    /// The newest matching message. Its mailbox stays selected, so `flag`,
+    /// `delete`, and `move_to` apply to it.
+    pub async fn fetch_latest(
+        &mut self,
+        criteria: &SearchCriteria,
+    ) -> Result<Option<EmailMessage>> {
+        let hit = match self.search(criteria).await?.into_iter().next() {
+            Some(h) => h,
+            None => return Ok(None),
+        };
+
+        self.select(&hit.mailbox).await?;
+        let msg = self.fetch_message(hit.uid).await?;
+
+        if criteria.mark_seen {
+            let _ = self.flag(hit.uid, &[Flag::Seen]).await;
+        }
+
+        Ok(Some(msg))
+    }
+
+    /// Headers of matching messages, newest first: skips `offset`, returns at
+    /// most `limit`. See [`ImapClient::fetch_all`](crate::ImapClient::fetch_all).
+    pub async fn fetch_all(
+        &mut self,
+        criteria: &SearchCriteria,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<EmailHeader>> {
+        let page: Vec<Hit> = self
+            .search(criteria)
+            .await?
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .collect();
+
+        let mut headers = Vec::with_capacity(page.len());
+        for mailbox in Hit::mailboxes(&page) {
+            let uids: Vec<u32> = page
+                .iter()
+                .filter(|h| h.mailbox == mailbox)
+                .map(|h| h.uid)
+                .collect();
+            self.select(mailbox).await?;
+            let fetches = self
+                .uid_fetch(&uid_set(&uids), "(RFC822.SIZE BODY.PEEK[HEADER])")
+                .await?;
+            for f in &fetches {
+                if let (Some(uid), Some(raw)) = (f.uid, f.header()) {
+                    let mut header = parse_header(uid, f.size, raw)?;
+                    header.mailbox = mailbox.to_string();
+                    headers.push(header);
+                }
+            }
+        }
+        Hit::sort_like(&page, &mut headers);
+        Ok(headers)
+    }
+
+    /// Download and parse one message from the selected mailbox. Most
+    /// servers mark it seen.
+    pub async fn fetch_message(&mut self, uid: u32) -> Result<EmailMessage> {
+        let fetches = self.uid_fetch(&uid.to_string(), "RFC822").await?;
+        let raw = fetches
+            .iter()
+            .find(|f| f.uid == Some(uid))
+            .and_then(Fetch::body)
+            .ok_or(Error::NotFound)?
+            .to_vec();
+        let mut msg = parse_message(uid, raw)?;
+        msg.mailbox = self.selected.clone();
+        Ok(msg)
+    }
+
+    /// Select the mailbox that `fetch_message`, `flag`, `delete`, and
+    /// `move_to` act on.
+    pub async fn select(&mut self, mailbox: &str) -> Result<()> {
+        if self.selected != mailbox {
+            self.selected.clear();
+            self.session()?.select(mailbox).await?;
+            self.selected = mailbox.to_string();
+            self.take_changes();
+        }
+        Ok(())
+    }
+
+    /// The mailbox the server marks `\Junk` (RFC 6154), or else one with a
+    /// common spam folder name.
+    pub async fn junk_mailbox(&mut self) -> Result<Option<String>> {
+        if let Some(ref junk) = self.junk {
+            return Ok(junk.clone());
+        }
+        let names: Vec<Name> = self
+            .session()?
+            .list(Some(""), Some("*"))
+            .await?
+            .try_collect()
+            .await?;
+        let junk = names
+            .iter()
+            .find(|n| n.attributes().contains(&NameAttribute::Junk))
+            .or_else(|| names.iter().find(|n| is_junk_name(n.name())))
+            .map(|n| n.name().to_string());
+        self.junk = Some(junk.clone());
+        Ok(junk)
+    }
+
+    /// Add flags to a message.
+    pub async fn flag(&mut self, uid: u32, flags: &[Flag]) -> Result<()> {
+        let flags = flags
+            .iter()
+            .map(Flag::to_imap)
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.command(&format!("UID STORE {} +FLAGS.SILENT ({})", uid, flags))
+            .await
+    }
+
+    /// Delete a message. Without UIDPLUS this falls back to EXPUNGE, which
+    /// also removes other messages already flagged `\Deleted`.
+    pub async fn delete(&mut self, uid: u32) -> Result<()> {
+        self.flag(uid, &[Flag::Deleted]).await?;
+        if self.uidplus {
+            self.command(&format!("UID EXPUNGE {}", uid)).await
+        } else {
+            self.command("EXPUNGE").await
+        }
+    }
+
+    /// Move a message to another mailbox, copying and deleting it when the
+    /// server lacks MOVE.
+    pub async fn move_to(&mut self, uid: u32, mailbox: &str) -> Result<()> {
+        if self.move_ext {
+            self.session()?.uid_mv(uid.to_string(), mailbox).await?;
+            return Ok(());
+        }
+        // Unlike MOVE, async-imap sends COPY's mailbox unquoted
+        self.session()?
+            .uid_copy(uid.to_string(), quoted(mailbox))
+            .await?;
+        self.delete(uid).await
+    }
+
+    /// Matches across the criteria's mailboxes, newest first, within the
+    /// exact `since`/`before` bounds.
+    async fn search(&mut self, criteria: &SearchCriteria) -> Result<Vec<Hit>> {
+        let mailboxes = self.search_mailboxes(criteria).await?;
+        let dated = criteria.needs_received(mailboxes.len());
+        let query = build_search_query(criteria);
+
+        let mut hits = Vec::new();
+        for mailbox in mailboxes {
+            self.select(&mailbox).await?;
+            let mut uids: Vec<u32> = self
+                .session()?
+                .uid_search(&query)
+                .await?
+                .into_iter()
+                .collect();
+            uids.sort_unstable();
+            let received = if dated {
+                Some(self.received(&uids).await?)
+            } else {
+                None
+            };
+            hits.extend(Hit::matching(&mailbox, uids, received, criteria));
+        }
+        Hit::sort_newest_first(&mut hits);
+        Ok(hits)
+    }
+
+    async fn search_mailboxes(&mut self, criteria: &SearchCriteria) -> Result<Vec<String>> {
+        let junk = if criteria.include_junk {
+            self.junk_mailbox().await?
+        } else {
+            None
+        };
+        Ok(criteria.search_mailboxes(&self.mailbox, junk))
+    }
+
+    /// INTERNALDATE of each message in the selected mailbox.
+    async fn received(&mut self, uids: &[u32]) -> Result<Vec<(u32, DateTime<Utc>)>> {
+        if uids.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .uid_fetch(&uid_set(uids), "INTERNALDATE")
+            .await?
+            .iter()
+            .filter_map(|f| Some((f.uid?, f.internal_date()?.with_timezone(&Utc))))
+            .collect())
+    }
+
+    /// Run a command whose only result is its status. async-imap's STORE
+    /// and EXPUNGE streams end quietly on NO, so they're sent as is.
+    async fn command(&mut self, command: &str) -> Result<()> {
+        Ok(self.session()?.run_command_and_check_ok(command).await?)
+    }
+
+    async fn uid_fetch(&mut self, uids: &str, query: &str) -> Result<Vec<Fetch>> {
+        Ok(self
+            .session()?
+            .uid_fetch(uids, query)
+            .await?
+            .try_collect()
+            .await?)
+    }
+}
+
+async fn tls(
+    config: &ImapConfig,
+    tcp: TcpStream,
+) -> Result<tokio_native_tls::TlsStream<TcpStream>> {
+    let connector = tokio_native_tls::TlsConnector::from(native_tls::TlsConnector::new()?);
+    Ok(connector.connect(&config.host, tcp).await?)
+}
+
+async fn greeting<S: AsyncRead + AsyncWrite + Unpin + Send + Debug>(
+    client: &mut async_imap::Client<S>,
+) -> Result<()> {
+    match client.read_response().await {
+        Some(response) => Ok(response.map(|_| ())?),
+        None => Err(Error::ConnectionLost("closed before greeting".into())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::io::Cursor;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll};
+
+    /// A server that replies with a fixed script and records what it's sent.
+    #[derive(Debug)]
+    struct Script {
+        responses: Cursor<Vec<u8>>,
+        sent: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl AsyncRead for Script {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut tokio::io::ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            Pin::new(&mut self.get_mut().responses).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for Script {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.sent.lock().unwrap().extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// A client logged in (tag A0001) on a scripted server; the script
+    /// answers the commands after that.
+    async fn scripted(responses: &str) -> (AsyncImapClient, Arc<Mutex<Vec<u8>>>) {
+        let sent = Arc::default();
+        let stream: Box<dyn Stream> = Box::new(Script {
+            responses: Cursor::new(format!("A0001 OK LOGIN done\r\n{}", responses).into()),
+            sent: Arc::clone(&sent),
+        });
+        let session = async_imap::Client::new(stream)
+            .login("user", "pass")
+            .await
+            .map_err(|e| e.0)
+            .unwrap();
+        let client = AsyncImapClient {
+            session: Some(session),
+            config: ImapConfig::new("127.0.0.1", 143, "user", "pass").tls(false),
+            idle: true,
+            uidplus: true,
+            move_ext: false,
+            mailbox: "INBOX".into(),
+            selected: "INBOX".into(),
+            junk: None,
+        };
+        (client, sent)
+    }
+
+    fn block_on<F: std::future::Future>(f: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(f)
+    }
+
+    #[test]
+    fn wait_for_message_idles_until_mailbox_changes() {
+        let raw = "Subject: Code\r\n\r\nYour code is 123456";
+        let script = format!(
+            "* SEARCH\r\nA0002 OK SEARCH done\r\n\
+             + idling\r\n* 1 EXISTS\r\nA0003 OK IDLE done\r\n\
+             * SEARCH 7\r\nA0004 OK SEARCH done\r\n\
+             * 1 FETCH (UID 7 RFC822 {{{}}}\r\n{})\r\nA0005 OK FETCH done\r\n",
+            raw.len(),
+            raw
+        );
+
+        let criteria = SearchCriteria::new().unseen_only(false);
+        // A poll interval longer than the timeout would fail without IDLE
+        let options = WaitOptions::new(Duration::seconds(30), Duration::minutes(5));
+        let (msg, sent) = block_on(async {
+            let (mut client, sent) = scripted(&script).await;
+            (client.wait_for_message(&criteria, &options).await, sent)
+        });
+        let msg = msg.unwrap();
+        assert_eq!(msg.uid, 7);
+        assert_eq!(msg.subject.as_deref(), Some("Code"));
+
+        let sent = String::from_utf8(sent.lock().unwrap().clone()).unwrap();
+        assert!(sent.contains("A0003 IDLE\r\nDONE\r\nA0004 UID SEARCH ALL\r\n"));
+    }
+
+    #[test]
+    fn wait_for_message_reconnects_after_drop() {
+        let (port, server) = crate::tests::serve_imap("Subject: Code\r\n\r\n123456");
+
+        let criteria = SearchCriteria::new().unseen_only(false);
+        let options = WaitOptions::new(Duration::seconds(30), Duration::seconds(1));
+        let msg = block_on(async {
+            // The scripted connection ends before answering the first search
+            let (mut client, _) = scripted("").await;
+            client.config.port = port;
+            let msg = client.wait_for_message(&criteria, &options).await;
+            client.logout().await.unwrap();
+            msg
+        })
+        .unwrap();
+        assert_eq!(msg.uid, 7);
+
+        let received = server.join().unwrap();
+        assert_eq!(received[0], "LOGIN \"user\" \"pass\"");
+        assert!(received.contains(&"SELECT \"INBOX\"".to_string()));
+    }
+
+    #[test]
+    fn fetch_all_and_move_across_mailboxes() {
+        let header = "Subject: Verify\r\nTo: me@test.com\r\n\r\n";
+        let script = format!(
+            "* LIST (\\HasNoChildren) \"/\" \"INBOX\"\r\n\
+             * LIST (\\HasNoChildren \\Junk) \"/\" \"[Gmail]/Spam\"\r\nA0002 OK LIST done\r\n\
+             * SEARCH 4\r\nA0003 OK SEARCH done\r\n\
+             * 1 FETCH (UID 4 INTERNALDATE \" 9-Mar-2024 08:00:00 +0000\")\r\nA0004 OK FETCH done\r\n\
+             * 2 EXISTS\r\nA0005 OK [READ-WRITE] SELECT done\r\n\
+             * SEARCH 2\r\nA0006 OK SEARCH done\r\n\
+             * 2 FETCH (UID 2 INTERNALDATE \"10-Mar-2024 09:00:00 +0000\")\r\nA0007 OK FETCH done\r\n\
+             * 2 FETCH (UID 2 RFC822.SIZE 800 BODY[HEADER] {{{}}}\r\n{})\r\nA0008 OK FETCH done\r\n\
+             A0009 OK COPY done\r\nA0010 OK STORE done\r\nA0011 NO refused\r\n",
+            header.len(),
+            header
+        );
+
+        let criteria = SearchCriteria::new().unseen_only(false).include_junk(true);
+        let (headers, moved, sent) = block_on(async {
+            let (mut client, sent) = scripted(&script).await;
+            let headers = client.fetch_all(&criteria, 1, 0).await.unwrap();
+            (headers, client.move_to(2, "INBOX").await, sent)
+        });
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].mailbox, "[Gmail]/Spam");
+        assert_eq!(headers[0].size, Some(800));
+        assert!(moved.unwrap_err().to_string().contains("refused"));
+
+        let sent = String::from_utf8(sent.lock().unwrap().clone()).unwrap();
+        assert!(sent.contains("A0005 SELECT \"[Gmail]/Spam\"\r\n"));
+        assert!(sent.contains("A0008 UID FETCH 2 (RFC822.SIZE BODY.PEEK[HEADER])\r\n"));
+        assert!(sent.contains("A0009 UID COPY 2 \"INBOX\"\r\n"));
+        assert!(sent.contains("A0011 UID EXPUNGE 2\r\n"));
+    }
+}
@@ -0,0 +1,233 @@
+//! OAuth2 access tokens for XOAUTH2 authentication.
+
+use crate::{Error, Result};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const MICROSOFT_TOKEN_URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/token";
+const MICROSOFT_IMAP_SCOPE: &str =
+    "https://outlook.office.com/IMAP.AccessAsUser.All offline_access";
+
+/// Refresh this long before the provider says the token expires.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// Give up on the token endpoint after this long.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where XOAUTH2 gets its access token.
+#[derive(Debug, Clone)]
+pub enum OAuth2 {
+    /// A token obtained elsewhere. The caller replaces it when it expires.
+    AccessToken(String),
+    /// Exchange a refresh token for access tokens, fetching a new one when
+    /// the current one expires. Clones share the cached token.
+    Refresh(RefreshToken),
+}
+
+/// Client credentials and a refresh token for the token endpoint.
+#[derive(Debug, Clone)]
+pub struct RefreshToken {
+    pub token_url: String,
+    pub client_id: String,
+    /// Omit for public clients.
+    pub client_secret: Option<String>,
+    pub refresh_token: String,
+    pub scope: Option<String>,
+    cached: Arc<Mutex<Option<(String, Instant)>>>,
+}
+
+impl OAuth2 {
+    pub fn access_token(token: impl Into<String>) -> Self {
+        Self::AccessToken(token.into())
+    }
+
+    pub fn refresh_token(
+        token_url: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: Option<String>,
+        refresh_token: impl Into<String>,
+    ) -> Self {
+        Self::Refresh(RefreshToken {
+            token_url: token_url.into(),
+            client_id: client_id.into(),
+            client_secret,
+            refresh_token: refresh_token.into(),
+            scope: None,
+            cached: Arc::default(),
+        })
+    }
+
+    /// Refresh against Google's token endpoint (Gmail).
+    pub fn google(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        refresh_token: impl Into<String>,
+    ) -> Self {
+        Self::refresh_token(
+            GOOGLE_TOKEN_URL,
+            client_id,
+            Some(client_secret.into()),
+            refresh_token,
+        )
+    }
+
+    /// Refresh against the Microsoft identity platform (Office 365 / Outlook)
+    /// with the IMAP scope.
+    pub fn microsoft(
+        client_id: impl Into<String>,
+        client_secret: Option<String>,
+        refresh_token: impl Into<String>,
+    ) -> Self {
+        Self::refresh_token(MICROSOFT_TOKEN_URL, client_id, client_secret, refresh_token)
+            .scope(MICROSOFT_IMAP_SCOPE)
+    }
+
+    /// Scope to request when refreshing. No effect on `AccessToken`.
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        if let Self::Refresh(ref mut r) = self {
+            r.scope = Some(scope.into());
+        }
+        self
+    }
+
+    /// A valid access token, refreshing if the cached one is missing or
+    /// expiring. Refreshing is a blocking HTTP request; async callers run
+    /// this on a blocking thread.
+    pub fn token(&self) -> Result<String> {
+        match self {
+            Self::AccessToken(token) => Ok(token.clone()),
+            Self::Refresh(r) => r.token(),
+        }
+    }
+}
+
+impl RefreshToken {
+    fn token(&self) -> Result<String> {
+        if let Some((token, expires)) = self.cached.lock().unwrap().as_ref() {
+            if Instant::now() + EXPIRY_MARGIN < *expires {
+                return Ok(token.clone());
+            }
+        }
+        // Not holding the lock during the request: a slow endpoint shouldn't
+        // block clones that only read a fresh token.
+        let (token, lifetime) = self.fetch()?;
+        *self.cached.lock().unwrap() = Some((token.clone(), Instant::now() + lifetime));
+        Ok(token)
+    }
+
+    fn fetch(&self) -> Result<(String, Duration)> {
+        let mut form = vec![
+            ("grant_type", "refresh_token"),
+            ("client_id", self.client_id.as_str()),
+            ("refresh_token", self.refresh_token.as_str()),
+        ];
+        if let Some(ref secret) = self.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+        if let Some(ref scope) = self.scope {
+            form.push(("scope", scope.as_str()));
+        }
+
+        let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+        let body = match agent.post(&self.token_url).send_form(&form) {
+            Ok(resp) => resp.into_string(),
+            Err(ureq::Error::Status(code, resp)) => {
+                let body = resp.into_string().unwrap_or_default();
+                return Err(Error::Auth(format!(
+                    "token endpoint returned {}: {}",
+                    code,
+                    body.trim()
+                )));
+            }
+            Err(e) => return Err(Error::Auth(format!("token request failed: {}", e))),
+        }
+        .map_err(|e| Error::Auth(format!("failed to read token response: {}", e)))?;
+
+        parse_token_response(&body)
+    }
+}
+
+fn parse_token_response(body: &str) -> Result<(String, Duration)> {
+    let json: serde_json::Value = serde_json::from_str(body)
+        .map_err(|e| Error::Auth(format!("invalid token response: {}", e)))?;
+    let token = json["access_token"]
+        .as_str()
+        .ok_or_else(|| Error::Auth(format!("token response has no access_token: {}", body)))?;
+    let lifetime = json["expires_in"].as_u64().unwrap_or(3600);
+    Ok((token.to_string(), Duration::from_secs(lifetime)))
+}
+
+/// SASL XOAUTH2 initial response, as used by Gmail and Office 365.
+fn xoauth2(user: &str, token: &str) -> String {
+    format!("user={}\x01auth=Bearer {}\x01\x01", user, token)
+}
+
+pub(crate) struct XOAuth2 {
+    pub user: String,
+    pub token: String,
+}
+
+impl imap::Authenticator for XOAuth2 {
+    type Response = String;
+
+    /// The first (empty) challenge gets the credentials. A later challenge
+    /// carries the server's JSON error and must be answered with an empty
+    /// response so the server can finish with NO.
+    fn process(&self, challenge: &[u8]) -> Self::Response {
+        if challenge.is_empty() {
+            xoauth2(&self.user, &self.token)
+        } else {
+            String::new()
+        }
+    }
+}
+
+#[cfg(feature = "async-native")]
+impl async_imap::Authenticator for &XOAuth2 {
+    type Response = String;
+
+    fn process(&mut self, challenge: &[u8]) -> Self::Response {
+        imap::Authenticator::process(*self, challenge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use imap::Authenticator;
+
+    #[test]
+    fn xoauth2_response() {
+        let auth = XOAuth2 {
+            user: "me@example.com".into(),
+            token: "ya29.abc".into(),
+        };
+        assert_eq!(
+            auth.process(b""),
+            "user=me@example.com\x01auth=Bearer ya29.abc\x01\x01"
+        );
+        assert_eq!(auth.process(br#"{"status":"401"}"#), "");
+    }
+
+    #[test]
+    fn token_response_and_cache() {
+        let (token, lifetime) =
+            parse_token_response(r#"{"access_token":"t1","expires_in":1800}"#).unwrap();
+        assert_eq!(token, "t1");
+        assert_eq!(lifetime, Duration::from_secs(1800));
+        assert!(parse_token_response(r#"{"error":"invalid_grant"}"#).is_err());
+
+        let oauth = OAuth2::microsoft("id", None, "refresh");
+        let OAuth2::Refresh(ref r) = oauth else {
+            panic!("expected refresh credentials");
+        };
+        assert_eq!(r.scope.as_deref(), Some(MICROSOFT_IMAP_SCOPE));
+
+        // A cached token that hasn't expired is reused without a request
+        *r.cached.lock().unwrap() =
+            Some(("cached".into(), Instant::now() + Duration::from_secs(600)));
+        assert_eq!(oauth.clone().token().unwrap(), "cached");
+        assert_eq!(OAuth2::access_token("fixed").token().unwrap(), "fixed");
+    }
+}
@@ -0,0 +1,213 @@
+//! One-time code extraction without hand-written regexes.
+
+use crate::{html_to_text, EmailMessage};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// A possible code, with how likely it is to be the one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtpCandidate {
+    pub code: String,
+    /// Higher is likelier; only meaningful relative to other candidates.
+    pub score: u32,
+}
+
+/// Codes shaped like OTPs: digit runs (optionally split in two groups) or
+/// uppercase alphanumerics, optionally hyphenated.
+static CODE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:\d{3,4}[ -]\d{3,4}|\d{4,8}|[A-Z0-9]{4,10}(?:-[A-Z0-9]{3,6})?)\b").unwrap()
+});
+
+static KEYWORD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:code|verification|verify|otp|one[- ]time|passcode|pin|security|2fa|sign[- ]?in|log[- ]?in|confirm\w*|token)\b").unwrap()
+});
+
+/// An element whose whole text could be a code, e.g. `<strong>482913</strong>`.
+static EMPHASIZED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)<(strong|b|code|h[1-6]|span|td|div|p)\b[^>]*>\s*([A-Za-z0-9][A-Za-z0-9 -]{2,18}[A-Za-z0-9])\s*</").unwrap()
+});
+
+static URL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"https?://[^\s"'<>]+"#).unwrap());
+
+/// How far before or after a code a keyword still counts.
+const KEYWORD_BEFORE: usize = 80;
+const KEYWORD_AFTER: usize = 40;
+
+/// Candidate codes from the subject and body, best first.
+///
+/// Six-digit numbers score highest on shape alone. Codes set apart in the
+/// HTML (`<strong>`, `<code>`, headings, or a cell holding nothing else) or
+/// near words like "code" and "verification" score higher. Alphanumeric
+/// codes need one of those signals, so IDs and words aren't mistaken for
+/// codes; four-digit years are ignored.
+pub fn extract(msg: &EmailMessage) -> Vec<OtpCandidate> {
+    let mut signals: Vec<(String, Signals)> = Vec::new();
+    let mut note = |code: String, update: &dyn Fn(&mut Signals)| match signals
+        .iter_mut()
+        .find(|(c, _)| *c == code)
+    {
+        Some((_, s)) => update(s),
+        None => {
+            let mut s = Signals::default();
+            update(&mut s);
+            signals.push((code, s));
+        }
+    };
+
+    let html_text = msg.body_html.as_deref().map(html_to_text);
+    let body = msg.body_text.as_deref().or(html_text.as_deref());
+    for text in [msg.subject.as_deref(), body].into_iter().flatten() {
+        let text = URL.replace_all(text, " ");
+        for m in CODE.find_iter(&text) {
+            let Some(code) = normalize(m.as_str()) else {
+                continue;
+            };
+            let near = keyword_near(&text, m.start(), m.end());
+            note(code, &|s| s.keyword |= near);
+        }
+    }
+
+    if let Some(html) = msg.body_html.as_deref() {
+        for caps in EMPHASIZED.captures_iter(html) {
+            let Some(code) = normalize(&caps[2]) else {
+                continue;
+            };
+            let strong = matches!(
+                caps[1].to_ascii_lowercase().as_str(),
+                "strong" | "b" | "code" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+            );
+            let bonus = if strong { 40 } else { 25 };
+            note(code, &|s| s.emphasis = s.emphasis.max(bonus));
+        }
+    }
+
+    let mut candidates: Vec<OtpCandidate> = signals
+        .into_iter()
+        .filter_map(|(code, s)| {
+            let score = s.score(&code)?;
+            Some(OtpCandidate { code, score })
+        })
+        .collect();
+    // Stable: ties keep the order codes appeared in
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.score));
+    candidates
+}
+
+/// The best candidate's code.
+pub fn best(msg: &EmailMessage) -> Option<String> {
+    extract(msg).into_iter().next().map(|c| c.code)
+}
+
+#[derive(Debug, Default)]
+struct Signals {
+    keyword: bool,
+    emphasis: u32,
+}
+
+impl Signals {
+    fn score(&self, code: &str) -> Option<u32> {
+        let digits = code.chars().all(|c| c.is_ascii_digit());
+        let shape = match code.len() {
+            6 if digits => 20,
+            _ if digits => 10,
+            _ if self.keyword || self.emphasis > 0 => 5,
+            _ => return None,
+        };
+        Some(shape + if self.keyword { 30 } else { 0 } + self.emphasis)
+    }
+}
+
+/// The code as it should be typed, or `None` if it isn't code-shaped.
+/// Digit groups are joined; alphanumerics must mix letters and digits.
+fn normalize(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let compact: String = raw.chars().filter(|c| !matches!(c, ' ' | '-')).collect();
+    if compact.chars().all(|c| c.is_ascii_digit()) {
+        let year = compact.len() == 4 && matches!(compact.parse::<u32>(), Ok(1900..=2099));
+        return ((4..=8).contains(&compact.len()) && !year).then_some(compact);
+    }
+    let valid = raw.len() <= 17
+        && raw
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-')
+        && raw.chars().any(|c| c.is_ascii_digit())
+        && raw.chars().any(|c| c.is_ascii_uppercase());
+    valid.then(|| raw.to_string())
+}
+
+fn keyword_near(text: &str, start: usize, end: usize) -> bool {
+    let before = floor_char_boundary(text, start.saturating_sub(KEYWORD_BEFORE));
+    let after = floor_char_boundary(text, (end + KEYWORD_AFTER).min(text.len()));
+    KEYWORD.is_match(&text[before..start]) || KEYWORD.is_match(&text[end..after])
+}
+
+fn floor_char_boundary(text: &str, mut i: usize) -> usize {
+    while !text.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msg(subject: &str, text: Option<&str>, html: Option<&str>) -> EmailMessage {
+        EmailMessage {
+            mailbox: "INBOX".into(),
+            uid: 1,
+            subject: Some(subject.into()),
+            from: None,
+            date: None,
+            body_text: text.map(String::from),
+            body_html: html.map(String::from),
+            headers: Default::default(),
+            raw: Vec::new(),
+            gmail_id: None,
+        }
+    }
+
+    #[test]
+    fn ranks_codes_near_keywords_first() {
+        let m = msg(
+            "Welcome to Acme",
+            Some(
+                "Order 20240517 shipped in 2024.\nYour verification code is 482 913. Call 555123.",
+            ),
+            None,
+        );
+        let candidates = extract(&m);
+        assert_eq!(candidates[0].code, "482913");
+        assert!(candidates.iter().all(|c| c.code != "2024"));
+        assert_eq!(best(&m).as_deref(), Some("482913"));
+    }
+
+    #[test]
+    fn emphasized_html_and_alphanumeric_codes() {
+        let m = msg(
+            "Sign in to Acme",
+            None,
+            Some(
+                "<style>.x{color:#123456}</style><p>Ref 99887766</p>\
+                 <p>Use this code to sign in:</p><p><strong>K7Q-2M9</strong></p>\
+                 <a href=\"https://acme.com/t/123456\">link</a>",
+            ),
+        );
+        let candidates = extract(&m);
+        assert_eq!(candidates[0].code, "K7Q-2M9");
+        assert!(candidates.iter().all(|c| c.code != "123456"));
+        assert!(candidates.iter().any(|c| c.code == "99887766"));
+    }
+
+    #[test]
+    fn ignores_words_and_bare_ids() {
+        let m = msg(
+            "HELLO WORLD",
+            Some("Ticket AB12CD was closed. HTTPS only."),
+            None,
+        );
+        assert!(extract(&m).is_empty());
+        assert_eq!(normalize("123-4567").as_deref(), Some("1234567"));
+        assert_eq!(normalize("1999"), None);
+    }
+}
@@ -0,0 +1,415 @@
+//! POP3 client for providers that don't offer IMAP.
+
+use crate::{
+    header_contains, oauth2, parse_message, EmailMessage, Error, ImapConfig, Result,
+    SearchCriteria, WaitOptions,
+};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use mailparse::{MailHeader, MailHeaderMap};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+
+/// Give up on a server that stops answering mid-reply.
+const READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+trait Stream: Read + Write + Send {}
+impl<T: Read + Write + Send> Stream for T {}
+
+struct Connection<S: Read + Write> {
+    stream: BufReader<S>,
+}
+
+impl<S: Read + Write> Connection<S> {
+    fn new(stream: S) -> Self {
+        Self {
+            stream: BufReader::new(stream),
+        }
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<()> {
+        let stream = self.stream.get_mut();
+        stream.write_all(format!("{}\r\n", line).as_bytes())?;
+        stream.flush()?;
+        Ok(())
+    }
+
+    fn read_line(&mut self) -> Result<Vec<u8>> {
+        let mut line = Vec::new();
+        if self.stream.read_until(b'\n', &mut line)? == 0 {
+            return Err(Error::Pop3("connection closed".into()));
+        }
+        Ok(line)
+    }
+
+    /// The text after `+OK`, or the server's `-ERR` reason as an error.
+    fn status(&mut self, verb: &str) -> Result<String> {
+        let line = String::from_utf8_lossy(&self.read_line()?)
+            .trim()
+            .to_string();
+        match line.strip_prefix("+OK") {
+            Some(rest) => Ok(rest.trim().to_string()),
+            None => {
+                let reason = line.strip_prefix("-ERR").unwrap_or(&line).trim();
+                Err(Error::Pop3(format!("{} failed: {}", verb, reason)))
+            }
+        }
+    }
+
+    fn command(&mut self, command: &str) -> Result<String> {
+        self.write_line(command)?;
+        // The verb only: PASS carries the password
+        self.status(command.split(' ').next().unwrap_or_default())
+    }
+
+    /// A multi-line reply up to the terminating `.`, with dot-stuffing removed.
+    fn multiline(&mut self) -> Result<Vec<u8>> {
+        let mut body = Vec::new();
+        loop {
+            let line = self.read_line()?;
+            if line == b".\r\n" || line == b".\n" {
+                return Ok(body);
+            }
+            body.extend_from_slice(line.strip_prefix(b".").unwrap_or(&line));
+        }
+    }
+}
+
+/// POP3 client with the same waiting and criteria API as [`ImapClient`](crate::ImapClient).
+///
+/// POP3 has no server-side search, so criteria are checked against each
+/// message's headers, newest first. It also has no flags or folders:
+/// `unseen_only`, `mark_seen`, `mailboxes`, and `include_junk` are ignored,
+/// so bound the search with `since_minutes` or `since` instead. Received-time
+/// bounds use the `Date` header.
+pub struct Pop3Client {
+    config: ImapConfig,
+    conn: Connection<Box<dyn Stream>>,
+    quit: bool,
+}
+
+impl Drop for Pop3Client {
+    fn drop(&mut self) {
+        if !self.quit {
+            let _ = self.conn.command("QUIT");
+        }
+    }
+}
+
+impl Pop3Client {
+    /// Connect and log in. `tls` means TLS from the start on port 995 and
+    /// STLS on any other port.
+    pub fn connect(config: &ImapConfig) -> Result<Self> {
+        Ok(Self {
+            config: config.clone(),
+            conn: open(config)?,
+            quit: false,
+        })
+    }
+
+    /// Wait for a matching message, polling every `poll_interval`.
+    pub fn wait_for_message(
+        &mut self,
+        criteria: &SearchCriteria,
+        options: &WaitOptions,
+    ) -> Result<EmailMessage> {
+        let deadline = Utc::now() + options.timeout;
+
+        loop {
+            if Utc::now() > deadline {
+                return Err(Error::Timeout);
+            }
+
+            if let Some(msg) = self.fetch_latest(criteria)? {
+                return Ok(msg);
+            }
+
+            let remaining = (deadline - Utc::now()).to_std().unwrap_or_default();
+            let poll = options.poll_interval.to_std().unwrap_or_default();
+            std::thread::sleep(poll.min(remaining));
+            self.reconnect()?;
+        }
+    }
+
+    /// The newest matching message. Its `uid` is the message number, valid
+    /// until the session ends.
+    pub fn fetch_latest(&mut self, criteria: &SearchCriteria) -> Result<Option<EmailMessage>> {
+        let oldest = criteria.oldest();
+        for (number, size) in self.list()?.into_iter().rev() {
+            if criteria.larger.is_some_and(|min| size <= min)
+                || criteria.smaller.is_some_and(|max| size >= max)
+            {
+                continue;
+            }
+
+            self.conn.command(&format!("TOP {} 0", number))?;
+            let head = self.conn.multiline()?;
+            let (headers, _) = mailparse::parse_headers(&head)?;
+            let date = headers
+                .get_first_value("Date")
+                .and_then(|d| mailparse::dateparse(&d).ok())
+                .and_then(|t| DateTime::from_timestamp(t, 0));
+            // Messages are listed in arrival order; a day of slack covers
+            // senders whose clocks or queues are off
+            if let (Some(date), Some(oldest)) = (date, oldest) {
+                if date < oldest - Duration::days(1) {
+                    break;
+                }
+            }
+            if !matches(criteria, &headers, date) {
+                continue;
+            }
+
+            self.conn.command(&format!("RETR {}", number))?;
+            let mut msg = parse_message(number, self.conn.multiline()?)?;
+            msg.mailbox = "INBOX".into();
+            if let Some(ref needle) = criteria.body_contains {
+                let needle = needle.to_lowercase();
+                let bodies = [msg.body_text.as_deref(), msg.body_html.as_deref()];
+                if !bodies
+                    .into_iter()
+                    .flatten()
+                    .any(|b| b.to_lowercase().contains(&needle))
+                {
+                    continue;
+                }
+            }
+            return Ok(Some(msg));
+        }
+        Ok(None)
+    }
+
+    /// Mark a message for deletion. The server deletes it when the session
+    /// ends with [`quit`](Self::quit) or the client is dropped.
+    pub fn delete(&mut self, number: u32) -> Result<()> {
+        self.conn.command(&format!("DELE {}", number))?;
+        Ok(())
+    }
+
+    /// End the session, applying deletions.
+    pub fn quit(mut self) -> Result<()> {
+        self.quit = true;
+        self.conn.command("QUIT")?;
+        Ok(())
+    }
+
+    /// Message numbers and sizes, oldest first.
+    fn list(&mut self) -> Result<Vec<(u32, u32)>> {
+        self.conn.command("LIST")?;
+        let listing = String::from_utf8_lossy(&self.conn.multiline()?).into_owned();
+        Ok(listing
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+            })
+            .collect())
+    }
+
+    /// The server's view of the maildrop is fixed for a session, so new mail
+    /// only shows up after logging in again.
+    fn reconnect(&mut self) -> Result<()> {
+        let _ = self.conn.command("QUIT");
+        self.conn = open(&self.config)?;
+        Ok(())
+    }
+}
+
+impl SearchCriteria {
+    /// The earliest received time these criteria allow.
+    fn oldest(&self) -> Option<DateTime<Utc>> {
+        let minutes = self
+            .since_minutes
+            .map(|m| Utc::now() - Duration::minutes(m));
+        minutes.into_iter().chain(self.since).max()
+    }
+}
+
+/// Whether headers match the criteria, as IMAP SEARCH would: case-insensitive
+/// substrings. Messages without a `Date` fail any time bound.
+fn matches(criteria: &SearchCriteria, headers: &[MailHeader], date: Option<DateTime<Utc>>) -> bool {
+    let contains = |name: &str, needle: &str| header_contains(headers, name, needle);
+
+    let addressed = [
+        ("From", &criteria.from),
+        ("To", &criteria.to),
+        ("Cc", &criteria.cc),
+        ("Subject", &criteria.subject_contains),
+    ];
+    for (name, value) in addressed {
+        if value.as_ref().is_some_and(|v| !contains(name, v)) {
+            return false;
+        }
+    }
+    if !criteria.header_pairs_match(headers) {
+        return false;
+    }
+
+    let bounded = criteria.oldest().is_some() || criteria.before.is_some();
+    match date {
+        Some(date) => {
+            criteria.oldest().is_none_or(|oldest| date >= oldest)
+                && criteria.before.is_none_or(|before| date < before)
+        }
+        None => !bounded,
+    }
+}
+
+fn open(config: &ImapConfig) -> Result<Connection<Box<dyn Stream>>> {
+    let tcp = TcpStream::connect((config.host.as_str(), config.port))?;
+    tcp.set_read_timeout(Some(READ_TIMEOUT))?;
+
+    let mut conn: Connection<Box<dyn Stream>> = if !config.tls {
+        let mut conn = Connection::new(Box::new(tcp) as Box<dyn Stream>);
+        conn.status("greeting")?;
+        conn
+    } else if config.port == 995 {
+        let mut conn = Connection::new(Box::new(tls(config, tcp)?) as Box<dyn Stream>);
+        conn.status("greeting")?;
+        conn
+    } else {
+        let mut plain = Connection::new(tcp);
+        plain.status("greeting")?;
+        plain.command("STLS")?;
+        let tcp = plain.stream.into_inner();
+        Connection::new(Box::new(tls(config, tcp)?) as Box<dyn Stream>)
+    };
+
+    match config.oauth2 {
+        Some(ref oauth2) => {
+            let auth = oauth2::XOAuth2 {
+                user: config.username.clone(),
+                token: oauth2.token()?,
+            };
+            authenticate(&mut conn, &auth)?;
+        }
+        None => {
+            conn.command(&format!("USER {}", config.username))?;
+            conn.command(&format!("PASS {}", config.password))?;
+        }
+    }
+    Ok(conn)
+}
+
+fn tls(config: &ImapConfig, tcp: TcpStream) -> Result<native_tls::TlsStream<TcpStream>> {
+    native_tls::TlsConnector::new()?
+        .connect(&config.host, tcp)
+        .map_err(|e| Error::Pop3(format!("TLS handshake failed: {}", e)))
+}
+
+/// AUTH XOAUTH2 with the credentials up front (RFC 5034). A failure comes
+/// back as a challenge carrying the error, answered with an empty line.
+fn authenticate<S: Read + Write>(conn: &mut Connection<S>, auth: &oauth2::XOAuth2) -> Result<()> {
+    use imap::Authenticator;
+
+    let initial = BASE64.encode(auth.process(b""));
+    conn.write_line(&format!("AUTH XOAUTH2 {}", initial))?;
+    let mut line = String::from_utf8_lossy(&conn.read_line()?)
+        .trim()
+        .to_string();
+    if line == "+" || line.starts_with("+ ") {
+        conn.write_line("")?;
+        line = String::from_utf8_lossy(&conn.read_line()?)
+            .trim()
+            .to_string();
+    }
+    if line.starts_with("+OK") {
+        Ok(())
+    } else {
+        let reason = line.strip_prefix("-ERR").unwrap_or(&line).trim();
+        Err(Error::Auth(format!("XOAUTH2 rejected: {}", reason)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+
+    struct Script {
+        responses: Cursor<Vec<u8>>,
+        sent: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl Read for Script {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.responses.read(buf)
+        }
+    }
+
+    impl Write for Script {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.sent.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn scripted(responses: &str) -> (Pop3Client, Arc<Mutex<Vec<u8>>>) {
+        let sent = Arc::default();
+        let stream: Box<dyn Stream> = Box::new(Script {
+            responses: Cursor::new(responses.as_bytes().to_vec()),
+            sent: Arc::clone(&sent),
+        });
+        let client = Pop3Client {
+            config: ImapConfig::new("pop.test", 995, "user", "pass"),
+            conn: Connection::new(stream),
+            quit: false,
+        };
+        (client, sent)
+    }
+
+    #[test]
+    fn fetch_latest_checks_headers_newest_first() {
+        let (mut client, sent) = scripted(
+            "+OK 3 messages\r\n1 500\r\n2 900\r\n3 40000\r\n.\r\n\
+             +OK\r\nFrom: news@shop.test\r\nSubject: Sale\r\n\r\n.\r\n\
+             +OK\r\nFrom: no-reply@acme.test\r\nSubject: Your code\r\n\r\n.\r\n\
+             +OK\r\nFrom: no-reply@acme.test\r\nSubject: Your code\r\n\r\n\
+             ..123456 is your code\r\n.\r\n\
+             +OK\r\n+OK bye\r\n",
+        );
+        let criteria = SearchCriteria::new()
+            .from("ACME.test")
+            .smaller(10_000)
+            .body_contains("IS YOUR");
+        let msg = client.fetch_latest(&criteria).unwrap().unwrap();
+        assert_eq!(msg.uid, 1);
+        assert_eq!(msg.mailbox, "INBOX");
+        assert_eq!(
+            msg.body_text.as_deref().map(str::trim),
+            Some(".123456 is your code")
+        );
+
+        client.delete(msg.uid).unwrap();
+        client.quit().unwrap();
+        let sent = String::from_utf8(sent.lock().unwrap().clone()).unwrap();
+        assert_eq!(
+            sent,
+            "LIST\r\nTOP 2 0\r\nTOP 1 0\r\nRETR 1\r\nDELE 1\r\nQUIT\r\n"
+        );
+    }
+
+    #[test]
+    fn time_bounds_use_the_date_header() {
+        let (headers, _) =
+            mailparse::parse_headers(b"Date: Sun, 10 Mar 2024 09:00:00 +0000\r\n\r\n").unwrap();
+        let date = headers
+            .get_first_value("Date")
+            .and_then(|d| mailparse::dateparse(&d).ok())
+            .and_then(|t| DateTime::from_timestamp(t, 0));
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+
+        let criteria = SearchCriteria::new().since(at("2024-03-10T08:00:00Z"));
+        assert!(matches(&criteria, &headers, date));
+        assert!(!matches(&criteria, &headers, None));
+        let criteria = criteria.before(at("2024-03-10T09:00:00Z"));
+        assert!(!matches(&criteria, &headers, date));
+        assert!(matches(&SearchCriteria::new(), &headers, None));
+    }
+}
@@ -0,0 +1,282 @@
+//! Accessibility audit — a core rule set (image alt text, form labels, text
+//! contrast, duplicate ids, keyboard traps) run against the live page.
+//! Shared by `assert_a11y` and eoka-agent's `audit_a11y`.
+//!
+//! Not a replacement for a full WCAG review; it catches the regressions that
+//! are cheap to detect from the DOM and computed styles.
+
+use eoka::{Page, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How badly a violation affects users, lowest first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Minor,
+    Moderate,
+    #[default]
+    Serious,
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Minor => "minor",
+            Self::Moderate => "moderate",
+            Self::Serious => "serious",
+            Self::Critical => "critical",
+        })
+    }
+}
+
+/// Tab presses tried when looking for a keyboard trap.
+const MAX_TABS: usize = 100;
+
+/// `(el)` → a short selector for an element in its own document.
+const SELECTOR_JS: &str = r#"((el) => {
+    const doc = el.ownerDocument;
+    const parts = [];
+    for (let e = el; e && e.nodeType === 1; e = e.parentElement) {
+        if (e.id && doc.querySelectorAll('#' + CSS.escape(e.id)).length === 1) {
+            parts.unshift('#' + CSS.escape(e.id));
+            break;
+        }
+        let part = e.tagName.toLowerCase();
+        const same = e.parentElement
+            ? Array.from(e.parentElement.children).filter(c => c.tagName === e.tagName)
+            : [];
+        if (same.length > 1) part += ':nth-of-type(' + (same.indexOf(e) + 1) + ')';
+        parts.unshift(part);
+    }
+    return parts.join(' > ');
+})"#;
+
+/// `(doc, path)` → static rule violations.
+const RULES_JS: &str = r#"((doc, path) => {
+    const win = doc.defaultView;
+    const out = [];
+    const add = (rule, severity, el, message) => out.push({ rule, severity, selector: path(el), message });
+    const shown = el => {
+        if (el.closest('[aria-hidden="true"], [inert]')) return false;
+        const s = win.getComputedStyle(el);
+        return el.getClientRects().length > 0 && s.visibility !== 'hidden' && s.display !== 'none';
+    };
+    const text = s => (s || '').trim();
+    const labelledby = el => text((el.getAttribute('aria-labelledby') || '').split(/\s+/)
+        .map(id => { const l = doc.getElementById(id); return l ? l.textContent : ''; }).join(' '));
+    const aria = el => text(el.getAttribute('aria-label')) || labelledby(el) || text(el.getAttribute('title'));
+
+    for (const el of doc.querySelectorAll('img, input[type=image], [role=img]')) {
+        const role = el.getAttribute('role');
+        if (role === 'presentation' || role === 'none' || !shown(el)) continue;
+        const native = el.tagName === 'IMG' || el.tagName === 'INPUT';
+        if (!(native && el.hasAttribute('alt')) && !aria(el)) add('image-alt', 'critical', el, 'image has no alt text');
+    }
+
+    const fields = 'input:not([type=hidden]):not([type=submit]):not([type=button]):not([type=reset]):not([type=image]), select, textarea';
+    for (const el of doc.querySelectorAll(fields)) {
+        if (!shown(el)) continue;
+        const label = Array.from(el.labels || []).map(l => l.textContent).join(' ');
+        if (!text(label) && !aria(el) && !text(el.getAttribute('placeholder'))) add('label', 'critical', el, 'form field has no label');
+    }
+
+    const rgba = c => {
+        const m = (c || '').match(/^rgba?\(([^)]+)\)$/);
+        if (!m) return null;
+        const v = m[1].split(/[\s,\/]+/).filter(Boolean).map(Number);
+        return { r: v[0], g: v[1], b: v[2], a: v.length > 3 ? v[3] : 1 };
+    };
+    const luminance = c => {
+        const ch = [c.r, c.g, c.b].map(v => { v /= 255; return v <= 0.03928 ? v / 12.92 : Math.pow((v + 0.055) / 1.055, 2.4); });
+        return 0.2126 * ch[0] + 0.7152 * ch[1] + 0.0722 * ch[2];
+    };
+    const background = el => {
+        for (let e = el; e; e = e.parentElement) {
+            const s = win.getComputedStyle(e);
+            if (s.backgroundImage !== 'none') return null;
+            const bg = rgba(s.backgroundColor);
+            if (bg && bg.a >= 1) return bg;
+            if (bg && bg.a > 0) return null;
+        }
+        return { r: 255, g: 255, b: 255, a: 1 };
+    };
+    let checked = 0;
+    for (const el of doc.body ? doc.body.querySelectorAll('*') : []) {
+        if (checked >= 2000) break;
+        if (!Array.from(el.childNodes).some(n => n.nodeType === 3 && n.textContent.trim())) continue;
+        if (['SCRIPT', 'STYLE', 'NOSCRIPT', 'OPTION'].includes(el.tagName) || el.disabled || !shown(el)) continue;
+        checked++;
+        const s = win.getComputedStyle(el);
+        const fg = rgba(s.color), bg = background(el);
+        if (!fg || !bg || parseFloat(s.opacity) === 0) continue;
+        const a = fg.a;
+        const mixed = { r: fg.r * a + bg.r * (1 - a), g: fg.g * a + bg.g * (1 - a), b: fg.b * a + bg.b * (1 - a) };
+        const [l1, l2] = [luminance(mixed), luminance(bg)].sort((x, y) => y - x);
+        const ratio = (l1 + 0.05) / (l2 + 0.05);
+        const size = parseFloat(s.fontSize), bold = parseInt(s.fontWeight, 10) >= 700;
+        const needed = size >= 24 || (size >= 18.66 && bold) ? 3 : 4.5;
+        if (ratio < needed) add('color-contrast', 'serious', el, 'contrast ' + ratio.toFixed(2) + ':1, needs ' + needed + ':1');
+    }
+
+    const ids = new Map();
+    for (const el of doc.querySelectorAll('[id]')) {
+        if (el.id) ids.set(el.id, (ids.get(el.id) || []).concat(el));
+    }
+    for (const [id, els] of ids) {
+        if (els.length > 1) add('duplicate-id', 'minor', els[1], 'id "' + id + '" is used ' + els.length + ' times');
+    }
+    return out;
+})"#;
+
+/// Clears focus and counts the elements Tab should reach (one per radio group).
+const TABBABLE_JS: &str = r#"(() => {
+    if (document.activeElement) document.activeElement.blur();
+    const sel = 'a[href], button, input:not([type=hidden]), select, textarea, summary, [tabindex], [contenteditable=""], [contenteditable=true]';
+    const radios = new Set();
+    return Array.from(document.querySelectorAll(sel)).filter(el => {
+        if (el.tabIndex < 0 || el.disabled || el.closest('[inert]')) return false;
+        if (el.getClientRects().length === 0 || getComputedStyle(el).visibility === 'hidden') return false;
+        if (el.type === 'radio' && el.name) {
+            if (radios.has(el.name)) return false;
+            radios.add(el.name);
+        }
+        return true;
+    }).length;
+})()"#;
+
+/// `(path)` → the focused element, or null when focus is outside the document.
+const FOCUS_JS: &str = r#"((path) => {
+    let el = document.activeElement;
+    while (el && el.shadowRoot && el.shadowRoot.activeElement) el = el.shadowRoot.activeElement;
+    if (!el || el === document.body || el === document.documentElement) return null;
+    return {
+        selector: path(el),
+        frame: el.tagName === 'IFRAME' || el.tagName === 'FRAME',
+        modal: !!el.closest('[aria-modal="true"], dialog[open]'),
+    };
+})"#;
+
+/// One failed check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Violation {
+    /// Rule id: `image-alt`, `label`, `color-contrast`, `duplicate-id` or `focus-trap`
+    pub rule: String,
+    pub severity: Severity,
+    /// CSS selector of the offending element
+    pub selector: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {} {}: {}",
+            self.severity, self.rule, self.selector, self.message
+        )
+    }
+}
+
+/// Violations at or above `min`.
+pub fn at_least(violations: &[Violation], min: Severity) -> Vec<&Violation> {
+    violations.iter().filter(|v| v.severity >= min).collect()
+}
+
+#[derive(Deserialize)]
+struct Focus {
+    selector: String,
+    frame: bool,
+    modal: bool,
+}
+
+/// Expression for the static rules, given an expression for the document.
+pub fn rules_js(doc: &str) -> String {
+    format!("JSON.stringify({}({}, {}))", RULES_JS, doc, SELECTOR_JS)
+}
+
+/// Parse the output of [`rules_js`].
+pub fn parse(json: &str) -> Result<Vec<Violation>> {
+    serde_json::from_str(json)
+        .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse a11y audit: {}", e)))
+}
+
+/// Run every rule against the page, most severe first.
+pub async fn audit(page: &Page) -> Result<Vec<Violation>> {
+    let json: String = page.evaluate(&rules_js("document")).await?;
+    let mut violations = parse(&json)?;
+    violations.extend(focus_trap(page).await?);
+    violations.sort_by_key(|v| std::cmp::Reverse(v.severity));
+    Ok(violations)
+}
+
+/// Press Tab through the page and report focus cycling through fewer
+/// elements than the page has, outside a modal dialog. Stops at iframes,
+/// whose focus can't be followed.
+pub async fn focus_trap(page: &Page) -> Result<Option<Violation>> {
+    let total: usize = page.evaluate(TABBABLE_JS).await?;
+    if total < 2 {
+        return Ok(None);
+    }
+    let js = format!("JSON.stringify({}({}))", FOCUS_JS, SELECTOR_JS);
+    let mut order: Vec<String> = Vec::new();
+    let mut trap = None;
+    for _ in 0..(total + 2).min(MAX_TABS) {
+        page.press_key("Tab").await?;
+        let json: String = page.evaluate(&js).await?;
+        let focus: Option<Focus> = serde_json::from_str(&json)
+            .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse focus: {}", e)))?;
+        let Some(focus) = focus else {
+            // Focus left the document: the tab order wrapped normally
+            order.clear();
+            continue;
+        };
+        if focus.frame {
+            break;
+        }
+        if let Some(pos) = order.iter().position(|s| *s == focus.selector) {
+            let cycle = order.len() - pos;
+            if cycle < total && !focus.modal {
+                trap = Some(Violation {
+                    rule: "focus-trap".into(),
+                    severity: Severity::Critical,
+                    selector: focus.selector,
+                    message: format!(
+                        "Tab cycles through {} of {} focusable elements",
+                        cycle, total
+                    ),
+                });
+            }
+            break;
+        }
+        order.push(focus.selector);
+    }
+    page.execute("document.activeElement && document.activeElement.blur()")
+        .await?;
+    Ok(trap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn severity_threshold() {
+        let violations: Vec<Violation> = serde_json::from_str(
+            r##"[
+              {"rule":"image-alt","severity":"critical","selector":"img","message":"image has no alt text"},
+              {"rule":"color-contrast","severity":"serious","selector":"p.muted","message":"contrast 2.10:1, needs 4.5:1"},
+              {"rule":"duplicate-id","severity":"minor","selector":"div:nth-of-type(2)","message":"id \"x\" is used 2 times"}
+            ]"##,
+        )
+        .unwrap();
+        assert!(Severity::Critical > Severity::Serious);
+        assert_eq!(at_least(&violations, Severity::Serious).len(), 2);
+        assert_eq!(at_least(&violations, Severity::Minor).len(), 3);
+        assert_eq!(
+            violations[1].to_string(),
+            "[serious] color-contrast p.muted: contrast 2.10:1, needs 4.5:1"
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Form autofill — a test-data [`FormProfile`] mapped onto a form's fields
+//! by their `autocomplete` attributes, labels, names and placeholders.
+//! Shared by the `autofill` action and eoka-agent's `autofill_form`, so both
+//! recognize the same fields.
+
+use chrono::Datelike;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Payment sandbox test cards.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestCard {
+    #[default]
+    Visa,
+    Mastercard,
+    Amex,
+    Discover,
+    /// Always declined by Stripe's test mode.
+    Declined,
+}
+
+impl TestCard {
+    pub fn number(self) -> &'static str {
+        match self {
+            Self::Visa => "4242424242424242",
+            Self::Mastercard => "5555555555554444",
+            Self::Amex => "378282246310005",
+            Self::Discover => "6011111111111117",
+            Self::Declined => "4000000000000002",
+        }
+    }
+
+    pub fn cvc(self) -> &'static str {
+        match self {
+            Self::Amex => "1234",
+            _ => "123",
+        }
+    }
+}
+
+/// Test data for one person. Defaults to a US shopper paying with the Visa
+/// test card, expiring three years from now.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FormProfile {
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub phone: String,
+    pub organization: String,
+    pub address_line1: String,
+    pub address_line2: String,
+    pub city: String,
+    /// State or province, spelled out.
+    pub region: String,
+    /// Short form of `region`, tried for dropdowns.
+    pub region_code: String,
+    pub postal_code: String,
+    pub country: String,
+    /// ISO 3166 code, tried for dropdowns and `autocomplete="country"`.
+    pub country_code: String,
+    pub card: TestCard,
+    pub card_exp_month: u32,
+    pub card_exp_year: i32,
+}
+
+impl Default for FormProfile {
+    fn default() -> Self {
+        Self {
+            first_name: "Jane".into(),
+            last_name: "Doe".into(),
+            email: "jane.doe@example.com".into(),
+            phone: "4155550123".into(),
+            organization: "Example Inc".into(),
+            address_line1: "1 Market St".into(),
+            address_line2: "Suite 100".into(),
+            city: "San Francisco".into(),
+            region: "California".into(),
+            region_code: "CA".into(),
+            postal_code: "94105".into(),
+            country: "United States".into(),
+            country_code: "US".into(),
+            card: TestCard::Visa,
+            card_exp_month: 12,
+            card_exp_year: chrono::Utc::now().year() + 3,
+        }
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+impl FormProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pay with a different test card.
+    pub fn card(mut self, card: TestCard) -> Self {
+        self.card = card;
+        self
+    }
+
+    pub fn email(mut self, email: &str) -> Self {
+        self.email = email.into();
+        self
+    }
+
+    pub fn name(mut self, first: &str, last: &str) -> Self {
+        self.first_name = first.into();
+        self.last_name = last.into();
+        self
+    }
+
+    /// Values to try for a field, best first: text inputs get the first,
+    /// dropdowns the first matching an option.
+    pub fn values(&self, kind: FieldKind, max_length: Option<usize>) -> Vec<String> {
+        let yy = format!("{:02}", self.card_exp_year % 100);
+        let mm = format!("{:02}", self.card_exp_month);
+        match kind {
+            FieldKind::GivenName => vec![self.first_name.clone()],
+            FieldKind::FamilyName => vec![self.last_name.clone()],
+            FieldKind::FullName | FieldKind::CardName => {
+                vec![format!("{} {}", self.first_name, self.last_name)]
+            }
+            FieldKind::Email => vec![self.email.clone()],
+            FieldKind::Phone => vec![self.phone.clone()],
+            FieldKind::Organization => vec![self.organization.clone()],
+            FieldKind::AddressLine1 => vec![self.address_line1.clone()],
+            FieldKind::AddressLine2 => vec![self.address_line2.clone()],
+            FieldKind::City => vec![self.city.clone()],
+            FieldKind::Region => vec![self.region.clone(), self.region_code.clone()],
+            FieldKind::PostalCode => vec![self.postal_code.clone()],
+            FieldKind::Country => vec![self.country.clone(), self.country_code.clone()],
+            FieldKind::CountryCode => vec![self.country_code.clone(), self.country.clone()],
+            FieldKind::CardNumber => vec![self.card.number().into()],
+            FieldKind::CardExpiry => vec![format!("{}/{}", mm, yy)],
+            FieldKind::CardExpMonth => vec![
+                mm,
+                self.card_exp_month.to_string(),
+                MONTHS[(self.card_exp_month.clamp(1, 12) - 1) as usize].into(),
+            ],
+            FieldKind::CardExpYear if max_length == Some(2) => vec![yy],
+            FieldKind::CardExpYear => vec![self.card_exp_year.to_string(), yy],
+            FieldKind::CardCvc => vec![self.card.cvc().into()],
+        }
+    }
+}
+
+/// What a form field asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    GivenName,
+    FamilyName,
+    FullName,
+    Email,
+    Phone,
+    Organization,
+    AddressLine1,
+    AddressLine2,
+    City,
+    Region,
+    PostalCode,
+    Country,
+    CountryCode,
+    CardNumber,
+    CardName,
+    CardExpiry,
+    CardExpMonth,
+    CardExpYear,
+    CardCvc,
+}
+
+/// Label/name patterns, in order: card fields first so "Name on card"
+/// isn't taken for the shopper's name.
+fn hint_patterns() -> &'static [(FieldKind, Regex)] {
+    static PATTERNS: OnceLock<Vec<(FieldKind, Regex)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            (
+                FieldKind::CardNumber,
+                r"card.?(num|no)|cc.?num|credit.?card",
+            ),
+            (FieldKind::CardName, r"name.?on.?card|card.?holder|cc.?name"),
+            (FieldKind::CardCvc, r"cvc|cvv|csc|security.?code"),
+            (
+                FieldKind::CardExpMonth,
+                r"exp\w*.?month|cc.?month|exp\w*.?mm\b",
+            ),
+            (FieldKind::CardExpYear, r"exp\w*.?year|cc.?year|exp\w*.?yy"),
+            (FieldKind::CardExpiry, r"expir|exp.?date|mm.?/.?yy"),
+            (FieldKind::Email, r"e.?mail"),
+            (FieldKind::Phone, r"phone|mobile|\btel\b"),
+            (FieldKind::GivenName, r"first.?name|given.?name|\bfname"),
+            (
+                FieldKind::FamilyName,
+                r"last.?name|family.?name|surname|\blname",
+            ),
+            (FieldKind::Organization, r"company|organi[sz]ation|business"),
+            (
+                FieldKind::AddressLine2,
+                r"address.?(line)?.?2|\bapt\b|apartment|suite",
+            ),
+            (FieldKind::AddressLine1, r"address|street"),
+            (FieldKind::City, r"city|town|locality"),
+            (FieldKind::Country, r"country"),
+            (FieldKind::Region, r"state|province|region|county"),
+            (FieldKind::PostalCode, r"zip|postal|post.?code"),
+            (FieldKind::FullName, r"full.?name|\bname\b"),
+        ]
+        .into_iter()
+        .map(|(kind, re)| (kind, Regex::new(re).unwrap()))
+        .collect()
+    })
+}
+
+impl FieldKind {
+    /// Classify from the `autocomplete` token, falling back to the label,
+    /// name, id and placeholder text.
+    pub fn classify(autocomplete: &str, hints: &str) -> Option<Self> {
+        let token = autocomplete.split_whitespace().last().unwrap_or("");
+        let by_token = match token.to_ascii_lowercase().as_str() {
+            "given-name" => Some(Self::GivenName),
+            "family-name" => Some(Self::FamilyName),
+            "name" => Some(Self::FullName),
+            "email" => Some(Self::Email),
+            "tel" | "tel-national" => Some(Self::Phone),
+            "organization" => Some(Self::Organization),
+            "street-address" | "address-line1" => Some(Self::AddressLine1),
+            "address-line2" => Some(Self::AddressLine2),
+            "address-level2" => Some(Self::City),
+            "address-level1" => Some(Self::Region),
+            "postal-code" => Some(Self::PostalCode),
+            "country" => Some(Self::CountryCode),
+            "country-name" => Some(Self::Country),
+            "cc-number" => Some(Self::CardNumber),
+            "cc-name" => Some(Self::CardName),
+            "cc-exp" => Some(Self::CardExpiry),
+            "cc-exp-month" => Some(Self::CardExpMonth),
+            "cc-exp-year" => Some(Self::CardExpYear),
+            "cc-csc" => Some(Self::CardCvc),
+            _ => None,
+        };
+        if by_token.is_some() {
+            return by_token;
+        }
+        let hints = hints.to_lowercase();
+        hint_patterns()
+            .iter()
+            .find(|(_, re)| re.is_match(&hints))
+            .map(|(kind, _)| *kind)
+    }
+}
+
+/// Which form to fill.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum FormRef {
+    /// The `n`-th `<form>`; on pages without forms, 0 is the whole page.
+    Index(usize),
+    /// The first element matching a CSS selector.
+    Selector(String),
+}
+
+impl fmt::Display for FormRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(i) => write!(f, "[{}]", i),
+            Self::Selector(s) => write!(f, "'{}'", s),
+        }
+    }
+}
+
+/// A field that was filled.
+#[derive(Debug, Clone)]
+pub struct FilledField {
+    pub kind: FieldKind,
+    /// The label, name or placeholder the field was recognized by
+    pub label: String,
+    pub value: String,
+}
+
+/// `(doc, form)` → the fillable fields of the [`FormRef`], each tagged with
+/// `data-eoka-fill`, or null when the form is missing.
+pub(crate) const FIELDS_JS: &str = r#"((doc, form) => {
+    doc.querySelectorAll('[data-eoka-fill]').forEach(el => el.removeAttribute('data-eoka-fill'));
+    const root = typeof form === 'string'
+        ? doc.querySelector(form)
+        : doc.forms.length ? doc.forms[form] : (form === 0 ? doc.body : null);
+    if (!root) return null;
+    const skip = ['hidden', 'submit', 'button', 'reset', 'image', 'checkbox', 'radio', 'file', 'password'];
+    const fields = Array.from(root.querySelectorAll('input, select, textarea')).filter(el =>
+        !skip.includes((el.type || '').toLowerCase()) && !el.disabled && !el.readOnly && el.getClientRects().length > 0);
+    return fields.map((el, i) => {
+        el.setAttribute('data-eoka-fill', String(i));
+        const labels = Array.from(el.labels || []).map(l => l.innerText).join(' ');
+        return {
+            id: i,
+            select: el.tagName === 'SELECT',
+            autocomplete: el.getAttribute('autocomplete') || '',
+            hints: [labels, el.name, el.id, el.placeholder, el.getAttribute('aria-label')].filter(Boolean).join(' ').trim(),
+            max_length: el.maxLength > 0 ? el.maxLength : null,
+        };
+    });
+})"#;
+
+/// `(el, candidates)` → picks the option matching a candidate (value or
+/// text, case-insensitive; then text prefix), returning its text or null.
+pub(crate) const SELECT_JS: &str = r#"((el, candidates) => {
+    const norm = s => (s || '').trim().toLowerCase();
+    const wanted = candidates.map(norm).filter(Boolean);
+    const opts = Array.from(el.options);
+    const opt = opts.find(o => wanted.includes(norm(o.value)) || wanted.includes(norm(o.text)))
+        || opts.find(o => wanted.some(w => w.length > 1 && norm(o.text).startsWith(w)));
+    if (!opt) return null;
+    el.value = opt.value;
+    el.dispatchEvent(new Event('input', { bubbles: true }));
+    el.dispatchEvent(new Event('change', { bubbles: true }));
+    return opt.text;
+})"#;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Field {
+    pub id: usize,
+    pub select: bool,
+    pub autocomplete: String,
+    pub hints: String,
+    pub max_length: Option<usize>,
+}
+
+impl Field {
+    pub fn selector(&self) -> String {
+        format!("[data-eoka-fill=\"{}\"]", self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_fields() {
+        let cases = [
+            ("shipping given-name", "", Some(FieldKind::GivenName)),
+            ("billing cc-number", "Number", Some(FieldKind::CardNumber)),
+            ("cc-exp-month", "Month", Some(FieldKind::CardExpMonth)),
+            ("off", "Email address", Some(FieldKind::Email)),
+            ("on", "Country/Region", Some(FieldKind::Country)),
+            ("", "Name on card", Some(FieldKind::CardName)),
+            ("", "cardNumber", Some(FieldKind::CardNumber)),
+            ("", "Expiration (MM/YY)", Some(FieldKind::CardExpiry)),
+            ("", "billing_first_name", Some(FieldKind::GivenName)),
+            ("", "checkout_last_name", Some(FieldKind::FamilyName)),
+            (
+                "",
+                "Apt, suite, etc. (optional)",
+                Some(FieldKind::AddressLine2),
+            ),
+            ("", "Street address", Some(FieldKind::AddressLine1)),
+            ("", "State / Province", Some(FieldKind::Region)),
+            ("", "ZIP code", Some(FieldKind::PostalCode)),
+            ("", "Full name", Some(FieldKind::FullName)),
+            ("", "name", Some(FieldKind::FullName)),
+            ("", "username", None),
+            ("", "Coupon code", None),
+        ];
+        for (autocomplete, hints, expected) in cases {
+            assert_eq!(
+                FieldKind::classify(autocomplete, hints),
+                expected,
+                "{:?} / {:?}",
+                autocomplete,
+                hints
+            );
+        }
+    }
+
+    #[test]
+    fn profile_values() {
+        let profile = FormProfile {
+            card_exp_month: 4,
+            card_exp_year: 2031,
+            ..FormProfile::new().card(TestCard::Amex)
+        };
+        assert_eq!(
+            profile.values(FieldKind::CardNumber, None),
+            ["378282246310005"]
+        );
+        assert_eq!(profile.values(FieldKind::CardCvc, None), ["1234"]);
+        assert_eq!(profile.values(FieldKind::CardExpiry, None), ["04/31"]);
+        assert_eq!(profile.values(FieldKind::CardExpYear, Some(2)), ["31"]);
+        assert_eq!(
+            profile.values(FieldKind::CardExpMonth, None),
+            ["04", "4", "April"]
+        );
+        assert_eq!(
+            profile.values(FieldKind::CountryCode, None),
+            ["US", "United States"]
+        );
+        assert_eq!(profile.values(FieldKind::CardName, None), ["Jane Doe"]);
+    }
+
+    #[test]
+    fn form_ref_json() {
+        assert_eq!(serde_json::to_string(&FormRef::Index(1)).unwrap(), "1");
+        assert_eq!(
+            serde_json::to_string(&FormRef::Selector("#checkout".into())).unwrap(),
+            r##""#checkout""##
+        );
+    }
+}
@@ -1,20 +1,62 @@
-use clap::Parser;
-use std::path::PathBuf;
-use tracing::Level;
+use clap::{Args, Parser, Subcommand};
+use eoka_runner::schedule::{self, CronSchedule, RunRecord, Schedule};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
 #[derive(Parser)]
 #[command(name = "eoka-runner")]
 #[command(about = "Config-based browser automation")]
 #[command(version)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(subcommand_negates_reqs = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    run: RunArgs,
+
+    /// Validate config without running
+    #[arg(long)]
+    check: bool,
+
+    /// Print the fully expanded action list without launching a browser
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Leave the browser open after a failure until Enter is pressed
+    #[arg(long)]
+    keep_open: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run a config repeatedly on a cron schedule or interval
+    Schedule(ScheduleArgs),
+    /// Check that Chrome can be found, launched and driven, and that the proxy works
+    Doctor(DoctorArgs),
+}
+
+/// Options shared by one-off and scheduled runs.
+#[derive(Args)]
+struct RunArgs {
     /// Config file to run
-    config: PathBuf,
+    #[arg(required = true)]
+    config: Option<PathBuf>,
+
+    /// Merge this entry from the config's `profiles:` section
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
 
     /// Run in headless mode (overrides config)
     #[arg(long)]
     headless: bool,
 
+    /// Show the current step in an on-page panel (headful runs only)
+    #[arg(long)]
+    debug_overlay: bool,
+
     /// Set a parameter (can be used multiple times)
     #[arg(short = 'P', long = "param", value_name = "KEY=VALUE")]
     params: Vec<String>,
@@ -23,24 +65,101 @@ struct Cli {
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
 
-    /// Validate config without running
-    #[arg(long)]
-    check: bool,
-
     /// Quiet mode (only errors)
     #[arg(short, long)]
     quiet: bool,
+
+    /// Append one JSON event per run and action to this file
+    #[arg(long, value_name = "PATH")]
+    log_json: Option<PathBuf>,
+
+    /// Checkpoint after each action and resume from the last checkpoint
+    #[arg(long)]
+    resume: bool,
+
+    /// Checkpoint file for --resume (default: <config>.checkpoint.json)
+    #[arg(long, value_name = "PATH", requires = "resume")]
+    checkpoint: Option<PathBuf>,
+
+    /// Overwrite assert_screenshot baselines and assert_snapshot golden files
+    #[arg(long)]
+    update_baselines: bool,
+
+    /// Save the page after each action as a film strip PNG, or an animated
+    /// GIF if PATH ends in .gif
+    #[arg(long, value_name = "PATH")]
+    strip: Option<PathBuf>,
+
+    /// Print how each action's targets resolved: the selector, how many
+    /// elements it matches and where, or what was searched when none did
+    #[arg(long)]
+    explain: bool,
+
+    /// Show the actions as a live list with status, timing and retries
+    #[cfg(feature = "tui")]
+    #[arg(long)]
+    tui: bool,
 }
 
-#[tokio::main]
-async fn main() -> eoka_runner::Result<()> {
-    let cli = Cli::parse();
+#[derive(Args)]
+struct ScheduleArgs {
+    #[command(flatten)]
+    run: RunArgs,
+
+    /// Cron expression in local time, e.g. "*/15 * * * *"
+    #[arg(
+        long,
+        conflicts_with = "interval",
+        required_unless_present = "interval"
+    )]
+    cron: Option<String>,
+
+    /// Delay between runs, e.g. 30s, 15m, 2h
+    #[arg(long)]
+    interval: Option<String>,
+
+    /// Random extra delay before each run, up to this long (e.g. 30s)
+    #[arg(long)]
+    jitter: Option<String>,
 
+    /// Stop after this many consecutive failures
+    #[arg(long, value_name = "N")]
+    max_failures: Option<u32>,
+
+    /// Stop after this many runs
+    #[arg(long, value_name = "N")]
+    max_runs: Option<u64>,
+
+    /// Append one JSON line per run to this file
+    #[arg(long, value_name = "PATH")]
+    history: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct DoctorArgs {
+    /// Use this config's browser settings (executable path, proxy)
+    config: Option<PathBuf>,
+
+    /// Proxy to check (overrides config)
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// Chrome/Chromium binary to check (overrides config)
+    #[arg(long, value_name = "PATH")]
+    executable_path: Option<String>,
+
+    /// Page to load through the proxy
+    #[arg(long, value_name = "URL")]
+    proxy_check_url: Option<String>,
+}
+
+fn init_logging(args: &RunArgs) {
     // Set up logging based on verbosity
-    let level = if cli.quiet {
+    let level = if args.quiet || tui(args) && args.verbose == 0 {
+        // Log lines would tear through the live list
         Level::ERROR
     } else {
-        match cli.verbose {
+        match args.verbose {
             0 => Level::WARN,
             1 => Level::INFO,
             _ => Level::DEBUG,
@@ -55,55 +174,175 @@ async fn main() -> eoka_runner::Result<()> {
         .with_line_number(false)
         .compact()
         .init();
+}
 
-    // Parse parameters
-    let params = eoka_runner::Params::from_args(&cli.params)?;
+#[cfg(feature = "tui")]
+fn tui(args: &RunArgs) -> bool {
+    args.tui
+}
 
-    // Load and validate config with parameters
-    let mut config = eoka_runner::Config::load_with_params(&cli.config, &params)?;
+#[cfg(not(feature = "tui"))]
+fn tui(_: &RunArgs) -> bool {
+    false
+}
 
-    if cli.check {
-        println!("Config valid: {}", config.name);
-        println!("  Target: {}", config.target.url);
-        println!("  Actions: {}", config.actions.len());
-        if !config.params.is_empty() {
-            println!("  Parameters: {}", config.params.len());
-            for (name, def) in &config.params {
-                let req = if def.required { " (required)" } else { "" };
-                let desc = def.description.as_deref().unwrap_or("");
-                println!("    - {}{}: {}", name, req, desc);
-            }
-        }
-        if let Some(ref success) = config.success {
-            let count = success.any.as_ref().map(|v| v.len()).unwrap_or(0)
-                + success.all.as_ref().map(|v| v.len()).unwrap_or(0);
-            println!("  Success conditions: {}", count);
+fn base_path(args: &RunArgs) -> &Path {
+    // Directory containing the config file, for resolving includes
+    config_path(args)
+        .parent()
+        .unwrap_or_else(|| std::path::Path::new("."))
+}
+
+fn config_path(args: &RunArgs) -> &Path {
+    args.config.as_deref().expect("clap enforces a config path")
+}
+
+/// Load the config (one per matrix combination) and apply CLI overrides.
+fn load_configs(args: &RunArgs) -> eoka_runner::Result<Vec<eoka_runner::Config>> {
+    let params = eoka_runner::Params::from_args(&args.params)?;
+    let mut configs =
+        eoka_runner::Config::load_matrix(config_path(args), &params, args.profile.as_deref())?;
+    for config in &mut configs {
+        config.browser.headless |= args.headless;
+        config.browser.debug_overlay |= args.debug_overlay;
+    }
+    Ok(configs)
+}
+
+/// Checkpoint file for --resume; matrix combinations get one each.
+fn checkpoint_path(args: &RunArgs, combination: Option<usize>) -> PathBuf {
+    let path = args
+        .checkpoint
+        .clone()
+        .unwrap_or_else(|| config_path(args).with_extension("checkpoint.json"));
+    match combination {
+        Some(i) => path.with_extension(format!("{}.json", i + 1)),
+        None => path,
+    }
+}
+
+/// Film strip file for --strip; matrix combinations get one each.
+fn strip_path(path: &Path, combination: Option<usize>) -> PathBuf {
+    match combination {
+        Some(i) => {
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("png");
+            path.with_extension(format!("{}.{}", i + 1, ext))
         }
-        if let Some(ref on_failure) = config.on_failure {
-            if let Some(ref retry) = on_failure.retry {
-                println!("  Retry attempts: {}", retry.attempts);
-            }
+        None => path.to_path_buf(),
+    }
+}
+
+/// Launch a browser, run the config once, and close it.
+async fn run_config(
+    args: &RunArgs,
+    config: &eoka_runner::Config,
+    combination: Option<usize>,
+    keep_open: bool,
+) -> eoka_runner::Result<eoka_runner::RunResult> {
+    let mut runner = eoka_runner::Runner::new(&config.browser)
+        .await?
+        .with_update_baselines(args.update_baselines)
+        .with_artifacts(args.strip.is_some());
+    if let Some(ref path) = args.log_json {
+        runner = runner.with_event_log(path)?;
+    }
+    if args.resume {
+        runner = runner.with_checkpoint(checkpoint_path(args, combination));
+    }
+    if args.explain {
+        runner = runner.with_listener(explain);
+    }
+    #[cfg(feature = "tui")]
+    if args.tui {
+        let mut progress = eoka_runner::progress::Progress::new(config);
+        runner = runner.with_listener(move |event| progress.handle(event));
+    }
+    let result = runner.run_with_base_path(config, base_path(args)).await;
+    if let (Some(path), Some(artifacts)) = (&args.strip, runner.artifacts()) {
+        let path = strip_path(path, combination);
+        match artifacts.save(&path) {
+            Ok(()) => println!("Film strip: {}", path.display()),
+            Err(e) => warn!("Failed to save film strip: {}", e),
         }
-        return Ok(());
     }
+    if keep_open && !result.as_ref().is_ok_and(|r| r.success) {
+        println!("Run failed; browser left open for inspection. Press Enter to close.");
+        let _ =
+            tokio::task::spawn_blocking(|| std::io::stdin().read_line(&mut String::new())).await;
+    }
+    runner.close().await?;
+    result
+}
 
-    // Override headless if specified
-    if cli.headless {
-        config.browser.headless = true;
+/// `--explain` output: a line per target an action resolved.
+fn explain(event: &eoka_runner::Event<'_>) {
+    if let eoka_runner::Event::ActionEnd {
+        index,
+        action,
+        resolved,
+        ..
+    } = event
+    {
+        for resolution in resolved {
+            println!("  #{} {}: {}", index + 1, action, resolution);
+        }
     }
+}
 
-    println!("Running: {}", config.name);
+/// A failed result for a run that errored before producing one.
+fn error_result(
+    error: eoka_runner::Error,
+    started_at: chrono::DateTime<chrono::Local>,
+) -> eoka_runner::RunResult {
+    eoka_runner::RunResult {
+        success: false,
+        error: Some(error.to_string()),
+        actions_executed: 0,
+        duration_ms: (chrono::Local::now() - started_at)
+            .num_milliseconds()
+            .max(0) as u64,
+        retries: 0,
+        actions: Vec::new(),
+        crawl: None,
+    }
+}
 
-    // Get base path for resolving includes (directory containing the config file)
-    let base_path = cli
-        .config
-        .parent()
-        .unwrap_or_else(|| std::path::Path::new("."));
+/// Run every matrix combination in turn. A single config's error is returned as-is.
+async fn run_all(
+    args: &RunArgs,
+    configs: &[eoka_runner::Config],
+    keep_open: bool,
+) -> eoka_runner::Result<Vec<eoka_runner::RunResult>> {
+    let matrix = configs.len() > 1;
+    let mut results = Vec::new();
+    for (i, config) in configs.iter().enumerate() {
+        println!("Running: {}", config.name);
+        let started_at = chrono::Local::now();
+        let result = match run_config(args, config, matrix.then_some(i), keep_open).await {
+            Ok(result) => result,
+            Err(e) if matrix => error_result(e, started_at),
+            Err(e) => return Err(e),
+        };
+        print_result(&result);
+        results.push(result);
+    }
+    if matrix {
+        print_matrix_summary(configs, &results);
+    }
+    Ok(results)
+}
 
-    let mut runner = eoka_runner::Runner::new(&config.browser).await?;
-    let result = runner.run_with_base_path(&config, base_path).await?;
+fn print_matrix_summary(configs: &[eoka_runner::Config], results: &[eoka_runner::RunResult]) {
+    let passed = results.iter().filter(|r| r.success).count();
+    println!();
+    println!("Matrix: {}/{} passed", passed, results.len());
+    for (config, result) in configs.iter().zip(results) {
+        let mark = if result.success { "✓" } else { "✗" };
+        println!("  {} {} ({}ms)", mark, config.name, result.duration_ms);
+    }
+}
 
-    // Print result
+fn print_result(result: &eoka_runner::RunResult) {
     println!();
     if result.success {
         println!("✓ Success");
@@ -118,10 +357,200 @@ async fn main() -> eoka_runner::Result<()> {
     if result.retries > 0 {
         println!("  Retries: {}", result.retries);
     }
+    if let Some(ref crawl) = result.crawl {
+        println!(
+            "  Crawled: {} pages, {} errors",
+            crawl.pages.len() + crawl.errors.len(),
+            crawl.errors.len()
+        );
+        for e in &crawl.errors {
+            println!("    {}: {}", e.url, e.error);
+        }
+    }
+    let slowest = result.slowest_actions(5);
+    if !slowest.is_empty() {
+        println!("  Slowest actions:");
+        for t in slowest {
+            let mut line = format!("    {:>7}ms  #{} {}", t.duration_ms, t.index + 1, t.action);
+            if let Some(ref target) = t.target {
+                line.push_str(&format!(" {}", target));
+            }
+            if t.bytes > 0 {
+                line.push_str(&format!(" ({:.1} KB)", t.bytes as f64 / 1024.0));
+            }
+            if t.retries > 0 {
+                line.push_str(&format!(" [{} attempts]", t.retries + 1));
+            }
+            println!("{}", line);
+        }
+    }
+}
 
-    runner.close().await?;
+fn print_check(config: &eoka_runner::Config) {
+    println!("Config valid: {}", config.name);
+    println!("  Target: {}", config.target.url);
+    println!("  Actions: {}", config.actions.len());
+    if !config.params.is_empty() {
+        println!("  Parameters: {}", config.params.len());
+        for (name, def) in &config.params {
+            let req = if def.required { " (required)" } else { "" };
+            let desc = def.description.as_deref().unwrap_or("");
+            println!("    - {}{}: {}", name, req, desc);
+        }
+    }
+    if let Some(ref success) = config.success {
+        let count = success.any.as_ref().map(|v| v.len()).unwrap_or(0)
+            + success.all.as_ref().map(|v| v.len()).unwrap_or(0);
+        println!("  Success conditions: {}", count);
+    }
+    if let Some(ref on_failure) = config.on_failure {
+        if let Some(ref retry) = on_failure.retry {
+            println!("  Retry attempts: {}", retry.attempts);
+        }
+    }
+}
+
+async fn run_schedule(args: &ScheduleArgs) -> eoka_runner::Result<()> {
+    let schedule = match (&args.cron, &args.interval) {
+        (Some(expr), _) => Schedule::Cron(CronSchedule::parse(expr)?),
+        (None, Some(interval)) => Schedule::Interval(schedule::parse_duration(interval)?),
+        (None, None) => unreachable!("clap requires --cron or --interval"),
+    };
+    let max_jitter = args
+        .jitter
+        .as_deref()
+        .map(schedule::parse_duration)
+        .transpose()?
+        .unwrap_or_default();
+
+    // Validate up front so a typo fails immediately rather than at the first tick.
+    let configs = load_configs(&args.run)?;
+    for config in &configs {
+        println!("Scheduling: {}", config.name);
+    }
+
+    let mut run = 0u64;
+    let mut consecutive_failures = 0u32;
+    loop {
+        // Interval runs start immediately; cron runs wait for the first match.
+        if run > 0 || matches!(schedule, Schedule::Cron(_)) {
+            let delay = schedule.delay_from(chrono::Local::now())? + schedule::jitter(max_jitter);
+            info!("Next run in {}s", delay.as_secs());
+            tokio::time::sleep(delay).await;
+        }
+
+        run += 1;
+        let started_at = chrono::Local::now();
+        println!("[{}] Run {}", started_at.format("%Y-%m-%d %H:%M:%S"), run);
+
+        // Reload each time so edits to the config apply to the next run.
+        let (names, results) = match load_configs(&args.run) {
+            Ok(configs) => {
+                let names: Vec<String> = configs.iter().map(|c| c.name.clone()).collect();
+                let results = run_all(&args.run, &configs, false)
+                    .await
+                    .unwrap_or_else(|e| vec![error_result(e, started_at)]);
+                (names, results)
+            }
+            Err(e) => {
+                let result = error_result(e, started_at);
+                print_result(&result);
+                (
+                    configs.iter().map(|c| c.name.clone()).collect(),
+                    vec![result],
+                )
+            }
+        };
+
+        if results.iter().all(|r| r.success) {
+            consecutive_failures = 0;
+        } else {
+            consecutive_failures += 1;
+        }
+
+        if let Some(ref path) = args.history {
+            for (name, result) in names.iter().zip(&results) {
+                let record = RunRecord::new(run, started_at, name, result, consecutive_failures);
+                if let Err(e) = record.append_to(path) {
+                    warn!("Failed to write run history: {}", e);
+                }
+            }
+        }
+
+        if let Some(max) = args.max_failures {
+            if consecutive_failures >= max {
+                eprintln!("Stopping: {} consecutive failures", consecutive_failures);
+                std::process::exit(1);
+            }
+        }
+        if args.max_runs.is_some_and(|max| run >= max) {
+            return Ok(());
+        }
+    }
+}
+
+/// Print the self-test report; exit non-zero if a check failed.
+async fn run_doctor(args: &DoctorArgs) -> eoka_runner::Result<()> {
+    let mut browser = match args.config {
+        Some(ref path) => eoka_runner::Config::load(path)?.browser,
+        None => eoka_runner::BrowserConfig::default(),
+    };
+    if args.proxy.is_some() {
+        browser.proxy.clone_from(&args.proxy);
+    }
+    if args.executable_path.is_some() {
+        browser.executable_path.clone_from(&args.executable_path);
+    }
+    let report = eoka_runner::doctor::run(&browser, args.proxy_check_url.as_deref()).await;
+    print!("{}", report);
+    if !report.passed() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> eoka_runner::Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Some(Command::Schedule(ref args)) => {
+            init_logging(&args.run);
+            return run_schedule(args).await;
+        }
+        Some(Command::Doctor(ref args)) => return run_doctor(args).await,
+        None => {}
+    }
+
+    init_logging(&cli.run);
+
+    // Load and validate config with parameters
+    let configs = load_configs(&cli.run)?;
+
+    if cli.check {
+        for config in &configs {
+            print_check(config);
+        }
+        return Ok(());
+    }
+
+    if cli.dry_run {
+        let params = eoka_runner::Params::from_args(&cli.run.params)?;
+        let mut warnings = false;
+        for config in &configs {
+            let plan = config.plan(base_path(&cli.run), &params)?;
+            print!("{}", plan);
+            warnings |= !plan.warnings.is_empty();
+        }
+        if warnings {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let results = run_all(&cli.run, &configs, cli.keep_open).await?;
 
-    if !result.success {
+    if !results.iter().all(|r| r.success) {
         std::process::exit(1);
     }
 
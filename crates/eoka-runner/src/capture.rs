@@ -0,0 +1,222 @@
+//! Network capture script — one page script that wraps `fetch`,
+//! `XMLHttpRequest`, `sendBeacon` and form submission, shared by the
+//! `request_made`/`response_status` success conditions and eoka-agent's
+//! `Session::set_network_capture`.
+//!
+//! eoka doesn't stream CDP network events, so entries are kept in
+//! `sessionStorage`, where they survive same-origin navigations (a POST
+//! outlives the page it triggers). Each install merges its settings into
+//! the page's, so the runner and an agent session can share a page. The
+//! wrappers are visible to the page; use this for checks and debugging, not
+//! against bot detection.
+//!
+//! Storage keys, all per origin:
+//! - `__eokaTraffic` (`traffic`): every request's method and URL, once when
+//!   sent and again with the status when answered
+//! - `__eokaNet` (`requests`): requests with a body — headers (redacted),
+//!   body and decoded form fields
+//! - `__eokaNetResponses` (`responses`): text responses to `fetch` and XHR
+//! - `__eokaNetAuth` (`requests`): newest value of each auth header sent
+
+/// `(settings)` → installs the wrappers, or merges `settings` into the
+/// installed ones. Settings and their defaults:
+/// `traffic` (false), `maxTraffic` (500), `requests` (false), `responses`
+/// (false), `maxEntries` (50), `maxBodyBytes` (65536), `redactHeaders`
+/// (`[]`, lowercase names), `authHeaders` (`[]`, case-insensitive regexes).
+pub const CAPTURE_JS: &str = r#"
+((settings) => {
+  if (window.__eokaNet) { Object.assign(window.__eokaNet.cfg, settings); return; }
+  const net = window.__eokaNet = {
+    cfg: Object.assign({
+      traffic: false, maxTraffic: 500,
+      requests: false, responses: false, maxEntries: 50, maxBodyBytes: 65536,
+      redactHeaders: [], authHeaders: [],
+    }, settings),
+  };
+  const load = key => {
+    try { return JSON.parse(sessionStorage.getItem(key) || '[]'); } catch (e) { return []; }
+  };
+  // Drops the oldest entries until the list fits the cap and the quota.
+  const save = (key, entry, max) => {
+    const all = load(key);
+    all.push(entry);
+    while (all.length > max) all.shift();
+    while (all.length) {
+      try { sessionStorage.setItem(key, JSON.stringify(all)); return; } catch (e) { all.shift(); }
+    }
+  };
+  const abs = url => new URL(String(url), location.href).href;
+  const upper = method => String(method || 'GET').toUpperCase();
+  const traffic = (method, url, status) => {
+    if (!net.cfg.traffic) return;
+    save('__eokaTraffic', { method: upper(method), url: abs(url), status }, net.cfg.maxTraffic);
+  };
+  const cap = text => {
+    const bytes = new TextEncoder().encode(text);
+    const truncated = bytes.length > net.cfg.maxBodyBytes;
+    if (truncated) text = new TextDecoder().decode(bytes.slice(0, net.cfg.maxBodyBytes));
+    return { text, size: bytes.length, truncated };
+  };
+  const textual = type => /json|html|text|xml/i.test(type || '');
+  const response = (kind, url, status, type, text) => {
+    if (!net.cfg.responses || !textual(type) || text == null) return;
+    const body = cap(text);
+    save('__eokaNetResponses', {
+      kind, status, body: body.text, size: body.size, truncated: body.truncated,
+      url: abs(url),
+      contentType: type,
+    }, net.cfg.maxEntries);
+  };
+  const entries = h => {
+    const out = [];
+    if (!h) return out;
+    if (h instanceof Headers) h.forEach((v, k) => out.push([k, v]));
+    else if (Array.isArray(h)) h.forEach(([k, v]) => out.push([k, v]));
+    else Object.keys(h).forEach(k => out.push([k, h[k]]));
+    return out.map(([k, v]) => [String(k).toLowerCase(), String(v)]);
+  };
+  const headersOf = h => {
+    const out = {};
+    for (const [k, v] of entries(h)) out[k] = net.cfg.redactHeaders.includes(k) ? '[redacted]' : v;
+    return out;
+  };
+  // Newest value of each auth header, on any request.
+  const auth = (h, url) => {
+    if (!net.cfg.requests) return;
+    const found = entries(h).filter(([k]) => net.cfg.authHeaders.some(p => new RegExp(p, 'i').test(k)));
+    if (!found.length) return;
+    let all = {};
+    try { all = JSON.parse(sessionStorage.getItem('__eokaNetAuth') || '{}'); } catch (e) {}
+    for (const [k, v] of found) all[k] = { value: v, url: abs(url) };
+    try { sessionStorage.setItem('__eokaNetAuth', JSON.stringify(all)); } catch (e) {}
+  };
+  const fieldsOf = fd => {
+    const fields = [];
+    for (const [k, v] of fd.entries()) fields.push([k, typeof v === 'string' ? v : (v.name || '[blob]')]);
+    return fields;
+  };
+  const record = (kind, method, url, headers, body) => {
+    method = upper(method);
+    if (!net.cfg.requests || method === 'GET' || method === 'HEAD') return;
+    let text = null, fields = null, type = headers['content-type'] || null;
+    if (body == null) {
+    } else if (typeof body === 'string') {
+      text = body;
+    } else if (body instanceof URLSearchParams) {
+      fields = [...body.entries()];
+      text = body.toString();
+      type = type || 'application/x-www-form-urlencoded';
+    } else if (body instanceof FormData) {
+      fields = fieldsOf(body);
+      text = new URLSearchParams(fields).toString();
+      type = type || 'multipart/form-data';
+    } else if (body instanceof Blob) {
+      text = '[binary ' + body.size + ' bytes]';
+    } else if (body instanceof ArrayBuffer || ArrayBuffer.isView(body)) {
+      text = '[binary ' + body.byteLength + ' bytes]';
+    } else {
+      text = String(body);
+    }
+    const sent = text == null ? { text: null, size: 0, truncated: false } : cap(text);
+    save('__eokaNet', {
+      kind, method, headers,
+      body: sent.text,
+      fields: sent.truncated ? null : fields,
+      truncated: sent.truncated,
+      url: abs(url),
+      page: location.href,
+      contentType: type,
+      size: sent.size,
+    }, net.cfg.maxEntries);
+  };
+
+  const fetch = window.fetch;
+  window.fetch = function (input, init) {
+    const req = input instanceof Request ? input : null;
+    const method = (init && init.method) || (req && req.method);
+    const url = req ? req.url : input;
+    try {
+      traffic(method, url, null);
+      const raw = (init && init.headers) || (req && req.headers);
+      const headers = headersOf(raw);
+      auth(raw, url);
+      if (!net.cfg.requests) {
+      } else if (init && 'body' in init) record('fetch', method, url, headers, init.body);
+      else if (req) req.clone().text().then(t => record('fetch', method, url, headers, t || null), () => {});
+      else record('fetch', method, url, headers, null);
+    } catch (e) {}
+    const pending = fetch.apply(this, arguments);
+    pending.then(res => {
+      try { traffic(method, res.url || url, res.status); } catch (e) {}
+      const type = res.headers.get('content-type');
+      if (net.cfg.responses && textual(type)) {
+        res.clone().text().then(t => response('fetch', res.url, res.status, type, t), () => {});
+      }
+    }, () => {});
+    return pending;
+  };
+
+  const xhr = XMLHttpRequest.prototype;
+  const open = xhr.open, setRequestHeader = xhr.setRequestHeader, send = xhr.send;
+  xhr.open = function (method, url) {
+    this.__eokaNet = { method, url, headers: {} };
+    return open.apply(this, arguments);
+  };
+  xhr.setRequestHeader = function (k, v) {
+    if (this.__eokaNet) this.__eokaNet.headers[k] = v;
+    return setRequestHeader.apply(this, arguments);
+  };
+  xhr.send = function (body) {
+    try {
+      const r = this.__eokaNet;
+      if (r) {
+        traffic(r.method, r.url, null);
+        auth(r.headers, r.url);
+        record('xhr', r.method, r.url, headersOf(r.headers), body);
+        this.addEventListener('loadend', () => {
+          try {
+            if (this.status) traffic(r.method, this.responseURL || r.url, this.status);
+            const type = this.getResponseHeader('content-type');
+            let text = null;
+            if (this.responseType === '' || this.responseType === 'text') text = this.responseText;
+            else if (this.responseType === 'json') text = JSON.stringify(this.response);
+            response('xhr', this.responseURL || r.url, this.status, type, text);
+          } catch (e) {}
+        });
+      }
+    } catch (e) {}
+    return send.apply(this, arguments);
+  };
+
+  if (navigator.sendBeacon) {
+    const beacon = navigator.sendBeacon;
+    navigator.sendBeacon = function (url) {
+      try { traffic('POST', url, null); } catch (e) {}
+      return beacon.apply(this, arguments);
+    };
+  }
+
+  const submitted = (form, submitter) => {
+    traffic(form.method, form.action || location.href, null);
+    if (form.method !== 'post') return;
+    record('form', 'POST', form.action || location.href, { 'content-type': form.enctype },
+      new FormData(form, submitter || null));
+  };
+  // Bubble phase on window: runs after the page's handlers, so a submit
+  // they cancel (and send with fetch instead) isn't recorded twice.
+  window.addEventListener('submit', e => {
+    if (!e.defaultPrevented) try { submitted(e.target, e.submitter); } catch (err) {}
+  });
+  const formSubmit = HTMLFormElement.prototype.submit;
+  HTMLFormElement.prototype.submit = function () {
+    try { submitted(this, null); } catch (e) {}
+    return formSubmit.apply(this, arguments);
+  };
+})
+"#;
+
+/// [`CAPTURE_JS`] called with `settings`, to evaluate or run in every new
+/// document.
+pub fn script(settings: &serde_json::Value) -> String {
+    format!("{}({})", CAPTURE_JS, settings)
+}
@@ -1,22 +1,52 @@
+use crate::a11y::Severity;
+use crate::autofill::FormProfile;
+use crate::target::LivePattern;
+use crate::visual::DiffMode;
 use serde::de::{self, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
 use std::fmt;
 
-/// A target element - either by CSS selector or visible text.
+/// A target element - by CSS selector, visible text, placeholder, label,
+/// role or id. The first one set is used, in that order.
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct Target {
     /// CSS selector.
     pub selector: Option<String>,
     /// Visible text to find.
     pub text: Option<String>,
+    /// Placeholder of an input or textarea.
+    pub placeholder: Option<String>,
+    /// Text of a form control's `<label>`, or an `aria-label`.
+    pub label: Option<String>,
+    /// Tag name or ARIA role, e.g. `button` or `dialog`.
+    pub role: Option<String>,
+    /// Element id, without `#`.
+    pub id: Option<String>,
+}
+
+impl Target {
+    /// The live pattern this target resolves with; `None` if nothing is set.
+    pub fn pattern(&self) -> Option<LivePattern> {
+        let field =
+            |value: &Option<String>, make: fn(String) -> LivePattern| value.clone().map(make);
+        field(&self.selector, LivePattern::Css)
+            .or_else(|| field(&self.text, LivePattern::Text))
+            .or_else(|| field(&self.placeholder, LivePattern::Placeholder))
+            .or_else(|| field(&self.label, LivePattern::Label))
+            .or_else(|| field(&self.role, LivePattern::Role))
+            .or_else(|| field(&self.id, LivePattern::Id))
+    }
 }
 
 impl fmt::Display for Target {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match (&self.selector, &self.text) {
-            (Some(s), _) => write!(f, "selector '{}'", s),
-            (_, Some(t)) => write!(f, "text '{}'", t),
-            _ => write!(f, "unknown"),
+        match self.pattern() {
+            Some(LivePattern::Css(s)) => write!(f, "selector '{}'", s),
+            Some(pattern) => {
+                let (kind, value) = pattern.as_js_args();
+                write!(f, "{} '{}'", kind, value)
+            }
+            None => write!(f, "unknown"),
         }
     }
 }
@@ -37,23 +67,29 @@ pub enum Action {
     WaitForVisible(WaitForAction),
     WaitForHidden(WaitForAction),
     WaitForText(WaitForTextAction),
+    WaitForTextGone(WaitForTextAction),
     WaitForUrl(WaitForUrlAction),
-    WaitForEmail(WaitForEmailAction),
+    WaitForElementStable(WaitForElementStableAction),
+    WaitForEmail(Box<WaitForEmailAction>),
 
     // Clicking
     Click(ClickAction),
     TryClick(TargetAction),
     TryClickAny(TryClickAnyAction),
+    ClickAll(ClickAllAction),
 
     // Input
     Fill(FillAction),
+    FillAll(FillAllAction),
     Type(TypeAction),
     Clear(ClearAction),
     Select(SelectAction),
+    Autofill(Box<AutofillAction>),
     PressKey(PressKeyAction),
 
     // Mouse
     Hover(TargetAction),
+    MenuClick(MenuClickAction),
 
     // Cookies
     SetCookie(SetCookieAction),
@@ -65,12 +101,19 @@ pub enum Action {
     // Scrolling
     Scroll(ScrollAction),
     ScrollTo(TargetAction),
+    ScrollUntil(ScrollUntilAction),
+    ScrollInfinite(ScrollInfiniteAction),
 
     // Debug
     Screenshot(ScreenshotAction),
     Log(LogAction),
+    Pause(PauseAction),
     AssertText(AssertTextAction),
     AssertUrl(AssertUrlAction),
+    AssertScreenshot(AssertScreenshotAction),
+    AssertSnapshot(AssertSnapshotAction),
+    AuditLinks(AuditLinksAction),
+    AssertA11y(AssertA11yAction),
 
     // Control flow
     IfTextExists(IfTextExistsAction),
@@ -79,6 +122,15 @@ pub enum Action {
 
     // Composition
     Include(IncludeAction),
+    AgentTask(AgentTaskAction),
+
+    // Frames
+    WithinFrame(WithinFrameAction),
+
+    // Files
+    Upload(UploadAction),
+    WaitForDownload(WaitForDownloadAction),
+    AssertDownload(AssertDownloadAction),
 }
 
 impl Action {
@@ -95,30 +147,84 @@ impl Action {
             Self::WaitForVisible(_) => "wait_for_visible",
             Self::WaitForHidden(_) => "wait_for_hidden",
             Self::WaitForText(_) => "wait_for_text",
+            Self::WaitForTextGone(_) => "wait_for_text_gone",
             Self::WaitForUrl(_) => "wait_for_url",
+            Self::WaitForElementStable(_) => "wait_for_element_stable",
             Self::WaitForEmail(_) => "wait_for_email",
             Self::Click(_) => "click",
             Self::TryClick(_) => "try_click",
             Self::TryClickAny(_) => "try_click_any",
+            Self::ClickAll(_) => "click_all",
             Self::Fill(_) => "fill",
+            Self::FillAll(_) => "fill_all",
             Self::Type(_) => "type",
             Self::Clear(_) => "clear",
             Self::Select(_) => "select",
+            Self::Autofill(_) => "autofill",
             Self::PressKey(_) => "press_key",
             Self::Hover(_) => "hover",
+            Self::MenuClick(_) => "menu_click",
             Self::SetCookie(_) => "set_cookie",
             Self::DeleteCookie(_) => "delete_cookie",
             Self::Execute(_) => "execute",
             Self::Scroll(_) => "scroll",
             Self::ScrollTo(_) => "scroll_to",
+            Self::ScrollUntil(_) => "scroll_until",
+            Self::ScrollInfinite(_) => "scroll_infinite",
             Self::Screenshot(_) => "screenshot",
             Self::Log(_) => "log",
+            Self::Pause(_) => "pause",
             Self::AssertText(_) => "assert_text",
             Self::AssertUrl(_) => "assert_url",
+            Self::AssertScreenshot(_) => "assert_screenshot",
+            Self::AssertSnapshot(_) => "assert_snapshot",
+            Self::AuditLinks(_) => "audit_links",
+            Self::AssertA11y(_) => "assert_a11y",
             Self::IfTextExists(_) => "if_text_exists",
             Self::IfSelectorExists(_) => "if_selector_exists",
             Self::Repeat(_) => "repeat",
             Self::Include(_) => "include",
+            Self::AgentTask(_) => "agent_task",
+            Self::WithinFrame(_) => "within_frame",
+            Self::Upload(_) => "upload",
+            Self::WaitForDownload(_) => "wait_for_download",
+            Self::AssertDownload(_) => "assert_download",
+        }
+    }
+
+    /// What the action points at (element, URL, file), without any values it types.
+    pub fn target(&self) -> Option<String> {
+        match self {
+            Self::Goto(a) => Some(a.url.clone()),
+            Self::WaitFor(a) | Self::WaitForVisible(a) | Self::WaitForHidden(a) => {
+                Some(format!("selector '{}'", a.selector))
+            }
+            Self::WaitForText(a) | Self::WaitForTextGone(a) => Some(format!("text '{}'", a.text)),
+            Self::WaitForUrl(a) => Some(a.contains.clone()),
+            Self::WaitForElementStable(a) => Some(a.target.to_string()),
+            Self::Click(a) => Some(a.target.to_string()),
+            Self::TryClick(a) | Self::Hover(a) | Self::ScrollTo(a) => Some(a.target.to_string()),
+            Self::Fill(a) => Some(a.target.to_string()),
+            Self::ClickAll(a) => Some(a.target.to_string()),
+            Self::FillAll(a) => Some(a.target.to_string()),
+            Self::MenuClick(a) => Some(format!("{} > {}", a.parent, a.item)),
+            Self::Type(a) => Some(a.target.to_string()),
+            Self::Clear(a) => Some(a.target.to_string()),
+            Self::Select(a) => Some(a.target.to_string()),
+            Self::Autofill(a) => a.form.clone(),
+            Self::Upload(a) => Some(a.target.to_string()),
+            Self::ScrollUntil(a) => Some(a.target.to_string()),
+            Self::SetCookie(a) => Some(a.name.clone()),
+            Self::DeleteCookie(a) => Some(a.name.clone()),
+            Self::Screenshot(a) => Some(a.path.clone()),
+            Self::AssertScreenshot(a) => Some(a.baseline.clone()),
+            Self::AssertSnapshot(a) => Some(a.golden.clone()),
+            Self::IfTextExists(a) => Some(format!("text '{}'", a.text)),
+            Self::IfSelectorExists(a) => Some(format!("selector '{}'", a.selector)),
+            Self::Include(a) => Some(a.path.clone()),
+            Self::AgentTask(a) => Some(a.goal.clone()),
+            Self::WithinFrame(a) => Some(a.frame.to_string()),
+            _ => None,
         }
     }
 }
@@ -134,30 +240,48 @@ const ACTION_NAMES: &[&str] = &[
     "wait_for_visible",
     "wait_for_hidden",
     "wait_for_text",
+    "wait_for_text_gone",
     "wait_for_url",
+    "wait_for_element_stable",
     "wait_for_email",
     "click",
     "try_click",
     "try_click_any",
+    "click_all",
     "fill",
+    "fill_all",
     "type",
     "clear",
     "select",
+    "autofill",
     "press_key",
     "hover",
+    "menu_click",
     "set_cookie",
     "delete_cookie",
     "execute",
     "scroll",
     "scroll_to",
+    "scroll_until",
+    "scroll_infinite",
     "screenshot",
     "log",
+    "pause",
     "assert_text",
     "assert_url",
+    "assert_screenshot",
+    "assert_snapshot",
+    "audit_links",
+    "assert_a11y",
     "if_text_exists",
     "if_selector_exists",
     "repeat",
     "include",
+    "agent_task",
+    "within_frame",
+    "upload",
+    "wait_for_download",
+    "assert_download",
 ];
 
 impl<'de> Deserialize<'de> for Action {
@@ -186,9 +310,21 @@ impl<'de> Visitor<'de> for ActionVisitor {
             "back" => Ok(Action::Back),
             "forward" => Ok(Action::Forward),
             "reload" => Ok(Action::Reload),
+            "pause" => Ok(Action::Pause(PauseAction::default())),
+            "audit_links" => Ok(Action::AuditLinks(AuditLinksAction::default())),
+            "autofill" => Ok(Action::Autofill(Box::default())),
+            "assert_a11y" => Ok(Action::AssertA11y(AssertA11yAction::default())),
             other => Err(de::Error::unknown_variant(
                 other,
-                &["back", "forward", "reload"],
+                &[
+                    "back",
+                    "forward",
+                    "reload",
+                    "pause",
+                    "audit_links",
+                    "assert_a11y",
+                    "autofill",
+                ],
             )),
         }
     }
@@ -221,30 +357,60 @@ impl<'de> Visitor<'de> for ActionVisitor {
             "wait_for_visible" => Action::WaitForVisible(map.next_value()?),
             "wait_for_hidden" => Action::WaitForHidden(map.next_value()?),
             "wait_for_text" => Action::WaitForText(map.next_value()?),
+            "wait_for_text_gone" => Action::WaitForTextGone(map.next_value()?),
             "wait_for_url" => Action::WaitForUrl(map.next_value()?),
+            "wait_for_element_stable" => Action::WaitForElementStable(map.next_value()?),
             "wait_for_email" => Action::WaitForEmail(map.next_value()?),
             "click" => Action::Click(map.next_value()?),
             "try_click" => Action::TryClick(map.next_value()?),
             "try_click_any" => Action::TryClickAny(map.next_value()?),
+            "click_all" => Action::ClickAll(map.next_value()?),
             "fill" => Action::Fill(map.next_value()?),
+            "fill_all" => Action::FillAll(map.next_value()?),
             "type" => Action::Type(map.next_value()?),
             "clear" => Action::Clear(map.next_value()?),
             "select" => Action::Select(map.next_value()?),
+            "autofill" => {
+                let autofill: Option<AutofillAction> = map.next_value()?;
+                Action::Autofill(Box::new(autofill.unwrap_or_default()))
+            }
             "press_key" => Action::PressKey(map.next_value()?),
             "hover" => Action::Hover(map.next_value()?),
+            "menu_click" => Action::MenuClick(map.next_value()?),
             "set_cookie" => Action::SetCookie(map.next_value()?),
             "delete_cookie" => Action::DeleteCookie(map.next_value()?),
             "execute" => Action::Execute(map.next_value()?),
             "scroll" => Action::Scroll(map.next_value()?),
             "scroll_to" => Action::ScrollTo(map.next_value()?),
+            "scroll_until" => Action::ScrollUntil(map.next_value()?),
+            "scroll_infinite" => Action::ScrollInfinite(map.next_value()?),
             "screenshot" => Action::Screenshot(map.next_value()?),
             "log" => Action::Log(map.next_value()?),
+            "pause" => {
+                let pause: Option<PauseAction> = map.next_value()?;
+                Action::Pause(pause.unwrap_or_default())
+            }
             "assert_text" => Action::AssertText(map.next_value()?),
             "assert_url" => Action::AssertUrl(map.next_value()?),
+            "assert_screenshot" => Action::AssertScreenshot(map.next_value()?),
+            "assert_snapshot" => Action::AssertSnapshot(map.next_value()?),
+            "audit_links" => {
+                let audit: Option<AuditLinksAction> = map.next_value()?;
+                Action::AuditLinks(audit.unwrap_or_default())
+            }
+            "assert_a11y" => {
+                let assert: Option<AssertA11yAction> = map.next_value()?;
+                Action::AssertA11y(assert.unwrap_or_default())
+            }
             "if_text_exists" => Action::IfTextExists(map.next_value()?),
             "if_selector_exists" => Action::IfSelectorExists(map.next_value()?),
             "repeat" => Action::Repeat(map.next_value()?),
             "include" => Action::Include(map.next_value()?),
+            "agent_task" => Action::AgentTask(map.next_value()?),
+            "within_frame" => Action::WithinFrame(map.next_value()?),
+            "upload" => Action::Upload(map.next_value()?),
+            "wait_for_download" => Action::WaitForDownload(map.next_value()?),
+            "assert_download" => Action::AssertDownload(map.next_value()?),
             other => return Err(de::Error::unknown_variant(other, ACTION_NAMES)),
         };
 
@@ -300,48 +466,140 @@ pub struct WaitForUrlAction {
     pub timeout_ms: u64,
 }
 
+/// Wait for an element's bounding box to stop changing (animating modals,
+/// sliding drawers).
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaitForElementStableAction {
+    #[serde(flatten)]
+    pub target: Target,
+    /// Consecutive animation frames the box must hold still.
+    #[serde(default = "WaitForElementStableAction::default_frames")]
+    pub frames: u32,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl WaitForElementStableAction {
+    fn default_frames() -> u32 {
+        3
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ImapConfigAction {
+    /// Not needed with `protocol: gmail`.
+    #[serde(default)]
     pub host: String,
     #[serde(default = "ImapConfigAction::default_port")]
     pub port: u16,
     #[serde(default = "ImapConfigAction::default_tls")]
     pub tls: bool,
     pub username: String,
+    /// Not needed with `oauth2`.
+    #[serde(default)]
     pub password: String,
     #[serde(default = "ImapConfigAction::default_mailbox")]
     pub mailbox: String,
+    /// Authenticate with XOAUTH2 instead of a password.
+    pub oauth2: Option<ImapOAuth2Action>,
+    /// `pop3` for providers without IMAP; set `port` to match (usually 995).
+    /// `gmail` for the Gmail API, which needs `oauth2`.
+    #[serde(default)]
+    pub protocol: MailProtocol,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MailProtocol {
+    #[default]
+    Imap,
+    Pop3,
+    Gmail,
 }
 
 impl ImapConfigAction {
-    fn default_port() -> u16 { 993 }
-    fn default_tls() -> bool { true }
-    fn default_mailbox() -> String { "INBOX".into() }
+    fn default_port() -> u16 {
+        993
+    }
+    fn default_tls() -> bool {
+        true
+    }
+    fn default_mailbox() -> String {
+        "INBOX".into()
+    }
+}
+
+/// XOAUTH2 credentials: a ready `access_token`, or `client_id` and
+/// `refresh_token` to fetch one from the provider's token endpoint.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImapOAuth2Action {
+    pub access_token: Option<String>,
+    #[serde(default)]
+    pub provider: OAuth2Provider,
+    /// Overrides the provider's token endpoint.
+    pub token_url: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuth2Provider {
+    #[default]
+    Google,
+    Microsoft,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct EmailFilterAction {
     pub from: Option<String>,
+    pub to: Option<String>,
+    pub cc: Option<String>,
     pub subject_contains: Option<String>,
+    pub body_contains: Option<String>,
+    /// Header name to substring of its value.
+    #[serde(default)]
+    pub headers: std::collections::BTreeMap<String, String>,
+    /// Message size in bytes.
+    pub larger_than: Option<u32>,
+    pub smaller_than: Option<u32>,
     #[serde(default = "EmailFilterAction::default_unseen_only")]
     pub unseen_only: bool,
     pub since_minutes: Option<i64>,
     #[serde(default)]
     pub mark_seen: bool,
+    /// Mailboxes to search instead of the one in `imap`.
+    #[serde(default)]
+    pub mailboxes: Vec<String>,
+    /// Also search the junk/spam mailbox.
+    #[serde(default)]
+    pub include_junk: bool,
 }
 
 impl EmailFilterAction {
-    fn default_unseen_only() -> bool { true }
+    fn default_unseen_only() -> bool {
+        true
+    }
 }
 
 impl Default for EmailFilterAction {
     fn default() -> Self {
         Self {
             from: None,
+            to: None,
+            cc: None,
             subject_contains: None,
+            body_contains: None,
+            headers: Default::default(),
+            larger_than: None,
+            smaller_than: None,
             unseen_only: true,
             since_minutes: None,
             mark_seen: false,
+            mailboxes: Vec::new(),
+            include_junk: false,
         }
     }
 }
@@ -359,11 +617,18 @@ pub struct WaitForEmailAction {
     pub extract: EmailExtractAction,
     #[serde(default)]
     pub action: Option<EmailAction>,
+    /// What to do with the email once values are extracted, so the next run
+    /// doesn't find it again.
+    pub cleanup: Option<EmailCleanup>,
 }
 
 impl WaitForEmailAction {
-    fn default_timeout_ms() -> u64 { 120_000 }
-    fn default_poll_interval_ms() -> u64 { 2_000 }
+    fn default_timeout_ms() -> u64 {
+        120_000
+    }
+    fn default_poll_interval_ms() -> u64 {
+        2_000
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -377,9 +642,19 @@ pub struct EmailLinkExtract {
     pub allow_domains: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Extract a code with `regex` (first capture group, else the whole match),
+/// or with the built-in OTP patterns when it's omitted.
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct EmailCodeExtract {
-    pub regex: String,
+    pub regex: Option<String>,
+}
+
+/// Delete the email, or move it to `move_to`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EmailCleanup {
+    #[serde(default)]
+    pub delete: bool,
+    pub move_to: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -402,7 +677,9 @@ impl<'de> Deserialize<'de> for EmailOpenLinkAction {
         let v = serde_yaml::Value::deserialize(deserializer)?;
         match v {
             serde_yaml::Value::Null | serde_yaml::Value::Mapping(_) => Ok(Self),
-            _ => Err(serde::de::Error::custom("expected null or empty map for open_link")),
+            _ => Err(serde::de::Error::custom(
+                "expected null or empty map for open_link",
+            )),
         }
     }
 }
@@ -428,6 +705,24 @@ pub struct TryClickAnyAction {
     pub texts: Option<Vec<String>>,
 }
 
+/// Click every element matching the target, in document order.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClickAllAction {
+    #[serde(flatten)]
+    pub target: Target,
+    /// Pause between clicks.
+    #[serde(default = "ClickAllAction::default_delay_ms")]
+    pub delay_ms: u64,
+    /// Click at most this many elements.
+    pub max: Option<usize>,
+}
+
+impl ClickAllAction {
+    fn default_delay_ms() -> u64 {
+        100
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct FillAction {
     #[serde(flatten)]
@@ -437,6 +732,18 @@ pub struct FillAction {
     pub human: bool,
 }
 
+/// Fill every input matching the target with the same value.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FillAllAction {
+    #[serde(flatten)]
+    pub target: Target,
+    pub value: String,
+    #[serde(default)]
+    pub human: bool,
+    /// Fill at most this many inputs.
+    pub max: Option<usize>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct TypeAction {
     #[serde(flatten)]
@@ -457,9 +764,40 @@ pub struct SelectAction {
     pub value: String,
 }
 
+/// Fill a form from test data, matching fields by autocomplete attribute,
+/// label, name or placeholder.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AutofillAction {
+    /// Form selector (default: the first form, or the whole page if it has none).
+    pub form: Option<String>,
+    /// Test data; unset fields keep their defaults.
+    #[serde(default)]
+    pub profile: FormProfile,
+    #[serde(default)]
+    pub human: bool,
+}
+
+/// Press a key, a chord (`Ctrl+A`), or a sequence of them.
 #[derive(Debug, Clone, Deserialize)]
 pub struct PressKeyAction {
-    pub key: String,
+    pub key: Option<String>,
+    /// Keys or chords pressed in order, after `key` if both are given.
+    #[serde(default)]
+    pub keys: Vec<String>,
+    /// Delay between keys in milliseconds.
+    #[serde(default)]
+    pub delay_ms: u64,
+}
+
+impl PressKeyAction {
+    /// All keys to press, in order.
+    pub fn sequence(&self) -> Vec<&str> {
+        self.key
+            .iter()
+            .chain(self.keys.iter())
+            .map(String::as_str)
+            .collect()
+    }
 }
 
 /// Generic action that just needs a target element.
@@ -469,6 +807,24 @@ pub struct TargetAction {
     pub target: Target,
 }
 
+/// Hover a menu, wait for the submenu item to show, then click it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MenuClickAction {
+    /// Element that opens the menu on hover.
+    pub parent: Target,
+    /// Menu entry to click once visible.
+    pub item: Target,
+    /// How long to wait for the item to become visible.
+    #[serde(default = "MenuClickAction::default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl MenuClickAction {
+    fn default_timeout_ms() -> u64 {
+        5_000
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct SetCookieAction {
     pub name: String,
@@ -499,6 +855,48 @@ pub struct ScrollAction {
     pub amount: u32,
 }
 
+/// Scroll down until a target appears, waiting for network idle between scrolls.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScrollUntilAction {
+    #[serde(flatten)]
+    pub target: Target,
+    #[serde(default = "ScrollUntilAction::default_max_scrolls")]
+    pub max_scrolls: u32,
+    /// Network idle time to wait for after each scroll.
+    #[serde(default = "default_idle_ms")]
+    pub idle_ms: u64,
+}
+
+impl ScrollUntilAction {
+    fn default_max_scrolls() -> u32 {
+        20
+    }
+}
+
+/// Scroll to the bottom repeatedly until no new content loads.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScrollInfiniteAction {
+    /// Consecutive scrolls without new content before stopping.
+    #[serde(default = "ScrollInfiniteAction::default_idle_rounds")]
+    pub idle_rounds: u32,
+    #[serde(default = "ScrollInfiniteAction::default_max_scrolls")]
+    pub max_scrolls: u32,
+    /// Count these elements to detect new content (default: page height only).
+    pub item_selector: Option<String>,
+    /// Network idle time to wait for after each scroll.
+    #[serde(default = "default_idle_ms")]
+    pub idle_ms: u64,
+}
+
+impl ScrollInfiniteAction {
+    fn default_idle_rounds() -> u32 {
+        3
+    }
+    fn default_max_scrolls() -> u32 {
+        100
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ScrollDirection {
@@ -518,6 +916,14 @@ pub struct LogAction {
     pub message: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct PauseAction {
+    /// Shown while paused.
+    pub message: Option<String>,
+    /// Continue automatically after this long (default: wait for Enter).
+    pub timeout_ms: Option<u64>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct AssertTextAction {
     pub text: String,
@@ -528,6 +934,123 @@ pub struct AssertUrlAction {
     pub contains: String,
 }
 
+/// Compare a screenshot of the page or an element with a stored baseline.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssertScreenshotAction {
+    /// Baseline PNG. Written on the first run if missing.
+    pub baseline: String,
+    /// Capture only this element.
+    pub selector: Option<String>,
+    /// Largest allowed difference: the fraction of changed pixels, or 1 - SSIM
+    /// in structural mode.
+    #[serde(default = "AssertScreenshotAction::default_threshold")]
+    pub threshold: f64,
+    /// Per-channel change (0-255) a pixel may have and still count as unchanged.
+    #[serde(default = "AssertScreenshotAction::default_tolerance")]
+    pub tolerance: u8,
+    #[serde(default)]
+    pub mode: DiffMode,
+    /// Diff image written on mismatch (default: `<baseline>.diff.png`).
+    pub diff: Option<String>,
+}
+
+impl AssertScreenshotAction {
+    fn default_threshold() -> f64 {
+        0.01
+    }
+    fn default_tolerance() -> u8 {
+        crate::visual::TOLERANCE
+    }
+
+    /// Where the diff image goes on mismatch.
+    pub fn diff_path(&self) -> std::path::PathBuf {
+        match self.diff {
+            Some(ref path) => path.into(),
+            None => std::path::Path::new(&self.baseline).with_extension("diff.png"),
+        }
+    }
+}
+
+/// Compare page text or sanitized HTML with a golden file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssertSnapshotAction {
+    /// Golden file. Written on the first run if missing.
+    pub golden: String,
+    /// Snapshot only this element (default: the whole body).
+    pub selector: Option<String>,
+    #[serde(default)]
+    pub format: SnapshotFormat,
+    /// Regexes whose matches are masked before comparing (timestamps, ids).
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+impl AssertSnapshotAction {
+    /// Where the actual snapshot goes on mismatch: `<golden>.actual`.
+    pub fn actual_path(&self) -> std::path::PathBuf {
+        format!("{}.actual", self.golden).into()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotFormat {
+    /// Visible text, whitespace-normalized.
+    #[default]
+    Text,
+    /// Element tree without scripts, styles, comments, or inline handlers.
+    Html,
+}
+
+/// Request every link on the page and fail if any answers 4xx/5xx.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuditLinksAction {
+    /// Only check links to the page's own origin (default: true).
+    #[serde(default = "AuditLinksAction::default_same_origin")]
+    pub same_origin: bool,
+    /// Anchors to check (default: `a[href]`).
+    pub selector: Option<String>,
+    /// Regexes for URLs to skip (logout links, rate-limited APIs).
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+impl AuditLinksAction {
+    fn default_same_origin() -> bool {
+        true
+    }
+}
+
+impl Default for AuditLinksAction {
+    fn default() -> Self {
+        Self {
+            same_origin: true,
+            selector: None,
+            ignore: Vec::new(),
+        }
+    }
+}
+
+/// Rule ids checked by `assert_a11y`.
+pub const A11Y_RULES: &[&str] = &[
+    "image-alt",
+    "label",
+    "color-contrast",
+    "duplicate-id",
+    "focus-trap",
+];
+
+/// Run the accessibility checks and fail on violations at or above `severity`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AssertA11yAction {
+    /// Lowest severity that fails the action (default: serious).
+    #[serde(default)]
+    pub severity: Severity,
+    /// Rule ids to skip (see [`A11Y_RULES`]).
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct IfTextExistsAction {
     pub text: String,
@@ -561,4 +1084,98 @@ pub struct IncludeAction {
     /// Parameters to pass to the included config.
     #[serde(default)]
     pub params: std::collections::HashMap<String, String>,
+
+    /// Params of the including config to forward under the same name.
+    #[serde(default)]
+    pub pass: Vec<String>,
+}
+
+/// Hand a bounded sub-task to the LLM agent registered with
+/// `Runner::with_agent_task`, then carry on with the next action.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AgentTaskAction {
+    /// What the agent should achieve, e.g. "dismiss whatever popup appears
+    /// and reach the search box".
+    pub goal: String,
+    /// Most turns the agent gets before the action fails.
+    #[serde(default = "AgentTaskAction::default_max_turns")]
+    pub max_turns: u32,
+    /// Model to use (default: `EOKA_AGENT_MODEL`, then the agent's own).
+    pub model: Option<String>,
+}
+
+impl AgentTaskAction {
+    fn default_max_turns() -> u32 {
+        10
+    }
+}
+
+/// Locates an iframe by CSS selector, `name`/`id` attribute, or URL substring.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct FrameLocator {
+    /// CSS selector for the `<iframe>` element.
+    pub selector: Option<String>,
+    /// Frame `name` (or `id`) attribute.
+    pub name: Option<String>,
+    /// Substring of the frame's `src` or current URL.
+    pub url_contains: Option<String>,
+}
+
+impl fmt::Display for FrameLocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.selector, &self.name, &self.url_contains) {
+            (Some(s), _, _) => write!(f, "selector '{}'", s),
+            (_, Some(n), _) => write!(f, "name '{}'", n),
+            (_, _, Some(u)) => write!(f, "url containing '{}'", u),
+            _ => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Run nested actions inside an iframe's document.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WithinFrameAction {
+    #[serde(flatten)]
+    pub frame: FrameLocator,
+    /// How long to wait for the frame to appear and load.
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    pub actions: Vec<Action>,
+}
+
+/// Attach a local file to an `<input type="file">`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UploadAction {
+    #[serde(flatten)]
+    pub target: Target,
+    /// Path to the file, relative to the config's directory.
+    pub file: String,
+}
+
+/// Wait for a download to finish in the browser's download directory.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaitForDownloadAction {
+    #[serde(default = "WaitForDownloadAction::default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Move the downloaded file here once complete.
+    pub save_as: Option<String>,
+}
+
+impl WaitForDownloadAction {
+    fn default_timeout_ms() -> u64 {
+        30_000
+    }
+}
+
+/// Check the most recent download's name and size.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssertDownloadAction {
+    /// Exact file name.
+    pub filename: Option<String>,
+    /// Substring of the file name.
+    pub filename_contains: Option<String>,
+    /// Minimum size in bytes.
+    pub min_bytes: Option<u64>,
+    /// Maximum size in bytes.
+    pub max_bytes: Option<u64>,
 }
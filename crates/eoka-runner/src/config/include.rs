@@ -0,0 +1,103 @@
+//! Include resolution — param scoping, depth limits, and cycle detection.
+
+use super::actions::IncludeAction;
+use super::{Config, Params};
+use crate::{Error, Result};
+use std::path::{Path, PathBuf};
+
+/// Maximum nesting of includes.
+pub const MAX_INCLUDE_DEPTH: usize = 10;
+
+/// The include files currently being expanded, outermost first.
+#[derive(Debug, Clone, Default)]
+pub struct IncludeChain {
+    files: Vec<PathBuf>,
+}
+
+impl IncludeChain {
+    /// Extend the chain with `path`, failing on a cycle or past the depth limit.
+    pub fn enter(&self, path: &Path) -> Result<Self> {
+        let key = canonical(path);
+        if self.files.iter().any(|f| canonical(f) == key) {
+            return Err(Error::Config(format!(
+                "include cycle: {}",
+                self.describe(path)
+            )));
+        }
+        if self.files.len() >= MAX_INCLUDE_DEPTH {
+            return Err(Error::Config(format!(
+                "maximum include depth ({}) exceeded: {}",
+                MAX_INCLUDE_DEPTH,
+                self.describe(path)
+            )));
+        }
+        let mut files = self.files.clone();
+        files.push(path.to_path_buf());
+        Ok(Self { files })
+    }
+
+    fn describe(&self, next: &Path) -> String {
+        self.files
+            .iter()
+            .map(|f| f.as_path())
+            .chain(std::iter::once(next))
+            .map(|f| f.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+impl IncludeAction {
+    /// Params the included file sees: only the ones passed explicitly.
+    pub fn scoped_params(&self) -> Params {
+        let mut params = Params::new();
+        for (k, v) in &self.params {
+            params = params.set(k.clone(), v.clone());
+        }
+        params
+    }
+
+    /// Load the included config from its resolved `path`.
+    pub fn load(&self, path: &Path) -> Result<Config> {
+        Config::load_with_params(path, &self.scoped_params()).map_err(|e| {
+            Error::Config(format!(
+                "failed to load include '{}': {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_cycles_and_depth() {
+        let chain = IncludeChain::default()
+            .enter(Path::new("flows/a.yaml"))
+            .unwrap()
+            .enter(Path::new("flows/b.yaml"))
+            .unwrap();
+        let err = chain.enter(Path::new("flows/a.yaml")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "config error: include cycle: flows/a.yaml -> flows/b.yaml -> flows/a.yaml"
+        );
+
+        let mut deep = IncludeChain::default();
+        for i in 0..MAX_INCLUDE_DEPTH {
+            deep = deep.enter(Path::new(&format!("{}.yaml", i))).unwrap();
+        }
+        let err = deep.enter(Path::new("last.yaml")).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("maximum include depth (10) exceeded"));
+        assert!(err.to_string().ends_with("9.yaml -> last.yaml"));
+    }
+}
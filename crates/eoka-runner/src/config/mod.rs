@@ -1,7 +1,11 @@
 pub mod actions;
+pub mod include;
 pub mod params;
 pub mod schema;
 
 pub use actions::{Action, Target};
 pub use params::{ParamDef, Params};
-pub use schema::{BrowserConfig, Config, SuccessCondition, TargetUrl};
+pub use schema::{
+    BrowserConfig, Config, ConfigFormat, ConsentPolicy, CrawlConfig, DevicePreset, PopupPolicy,
+    SuccessCondition, TargetUrl,
+};
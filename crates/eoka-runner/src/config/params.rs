@@ -30,6 +30,11 @@ impl Params {
         self.values.is_empty()
     }
 
+    /// Iterate over all name/value pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
     /// Parse from CLI args like "key=value".
     pub fn from_args(args: &[String]) -> Result<Self> {
         let mut params = Self::new();
@@ -55,6 +60,10 @@ pub struct ParamDef {
 
     /// Description for documentation.
     pub description: Option<String>,
+
+    /// Mask the value in plans and logs.
+    #[serde(default)]
+    pub secret: bool,
 }
 
 /// Substitute `${var}` patterns in a string.
@@ -157,6 +166,7 @@ mod tests {
                 required: false,
                 default: Some("default".to_string()),
                 description: None,
+                secret: false,
             },
         );
         let result = substitute("hello ${name}", &params, &defs).unwrap();
@@ -173,6 +183,7 @@ mod tests {
                 required: true,
                 default: None,
                 description: None,
+                secret: false,
             },
         );
         let result = substitute("hello ${name}", &params, &defs);
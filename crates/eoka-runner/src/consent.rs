@@ -0,0 +1,184 @@
+//! Consent platforms — how to spot a known cookie banner and which buttons
+//! answer it. Shared by `browser.auto_dismiss` and eoka-agent's
+//! `AutoDismiss`, so both click the same button on the same banner.
+
+use crate::ConsentPolicy;
+use eoka::{Page, Result};
+use serde::{Deserialize, Serialize};
+
+/// A consent platform: how to spot its banner and which buttons answer it.
+#[derive(Debug, Clone, Serialize)]
+pub struct DismissRule {
+    pub name: String,
+    /// CSS selector that matches the visible banner
+    pub detect: String,
+    /// CSS selector for the reject button
+    pub reject: Option<String>,
+    /// CSS selector for the accept button
+    pub accept: Option<String>,
+}
+
+impl DismissRule {
+    pub fn new(name: &str, detect: &str, reject: Option<&str>, accept: Option<&str>) -> Self {
+        Self {
+            name: name.into(),
+            detect: detect.into(),
+            reject: reject.map(Into::into),
+            accept: accept.map(Into::into),
+        }
+    }
+}
+
+/// Rules for common consent management platforms.
+pub fn builtin_rules() -> Vec<DismissRule> {
+    vec![
+        DismissRule::new(
+            "OneTrust",
+            "#onetrust-banner-sdk",
+            Some("#onetrust-reject-all-handler"),
+            Some("#onetrust-accept-btn-handler"),
+        ),
+        DismissRule::new(
+            "Cookiebot",
+            "#CybotCookiebotDialog",
+            Some("#CybotCookiebotDialogBodyButtonDecline"),
+            Some("#CybotCookiebotDialogBodyLevelButtonLevelOptinAllowAll, #CybotCookiebotDialogBodyButtonAccept"),
+        ),
+        DismissRule::new(
+            "Didomi",
+            "#didomi-notice",
+            Some("#didomi-notice-disagree-button"),
+            Some("#didomi-notice-agree-button"),
+        ),
+        DismissRule::new(
+            "Quantcast",
+            ".qc-cmp2-container",
+            Some(".qc-cmp2-summary-buttons button[mode=\"secondary\"]"),
+            Some(".qc-cmp2-summary-buttons button[mode=\"primary\"]"),
+        ),
+        DismissRule::new(
+            "TrustArc",
+            "#truste-consent-track",
+            Some("#truste-consent-required"),
+            Some("#truste-consent-button"),
+        ),
+        DismissRule::new(
+            "Osano",
+            ".osano-cm-window",
+            Some(".osano-cm-denyAll"),
+            Some(".osano-cm-accept-all"),
+        ),
+        DismissRule::new(
+            "CookieYes",
+            ".cky-consent-container",
+            Some(".cky-btn-reject"),
+            Some(".cky-btn-accept"),
+        ),
+        DismissRule::new(
+            "Complianz",
+            ".cmplz-cookiebanner",
+            Some(".cmplz-deny"),
+            Some(".cmplz-accept"),
+        ),
+        DismissRule::new(
+            "Iubenda",
+            "#iubenda-cs-banner",
+            Some(".iubenda-cs-reject-btn"),
+            Some(".iubenda-cs-accept-btn"),
+        ),
+        DismissRule::new(
+            "Klaro",
+            ".klaro .cookie-notice",
+            Some(".cn-decline"),
+            Some(".cm-btn-success"),
+        ),
+    ]
+}
+
+/// JavaScript that finds the first rule whose banner is visible and returns
+/// the button to click under the policy, or null.
+const RULES_JS: &str = r#"
+((rules, policy) => {
+  const shown = el => {
+    if (!el) return false;
+    const r = el.getBoundingClientRect();
+    const s = getComputedStyle(el);
+    return r.width > 1 && r.height > 1 && s.display !== 'none' && s.visibility !== 'hidden';
+  };
+  for (const rule of rules) {
+    if (!shown(document.querySelector(rule.detect))) continue;
+    const selector = policy === 'accept' ? rule.accept : rule.reject;
+    if (!selector) continue;
+    const button = Array.from(document.querySelectorAll(selector)).find(shown);
+    if (!button) continue;
+    return JSON.stringify({
+      name: rule.name,
+      selector,
+      text: (button.innerText || button.value || button.getAttribute('aria-label') || '').trim(),
+    });
+  }
+  return 'null';
+})
+"#;
+
+/// The button a rule picked.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuleMatch {
+    /// Name of the rule whose banner is showing
+    pub name: String,
+    /// Selector of the button to click
+    pub selector: String,
+    /// The button's label
+    pub text: String,
+}
+
+/// The button answering the first visible banner among `rules`, if any.
+pub async fn find_rule(
+    page: &Page,
+    rules: &[DismissRule],
+    policy: ConsentPolicy,
+) -> Result<Option<RuleMatch>> {
+    let js = format!(
+        "{}({}, {})",
+        RULES_JS,
+        serde_json::to_string(rules).unwrap(),
+        serde_json::to_string(&policy).unwrap()
+    );
+    let json: String = page.evaluate(&js).await?;
+    serde_json::from_str(&json)
+        .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse dismiss rule: {}", e)))
+}
+
+/// Click via CDP, falling back to a DOM click when hit-testing fails.
+pub async fn click(page: &Page, selector: &str) -> Result<()> {
+    if page.click(selector).await.is_ok() {
+        return Ok(());
+    }
+    let js = format!(
+        "document.querySelector({})?.click()",
+        serde_json::to_string(selector).unwrap()
+    );
+    page.execute(&js).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_serialize_for_the_page() {
+        let rules = builtin_rules();
+        assert!(rules
+            .iter()
+            .all(|r| r.reject.is_some() && r.accept.is_some()));
+        let json = serde_json::to_string(&rules[0]).unwrap();
+        assert_eq!(
+            json,
+            r##"{"name":"OneTrust","detect":"#onetrust-banner-sdk","reject":"#onetrust-reject-all-handler","accept":"#onetrust-accept-btn-handler"}"##
+        );
+        assert_eq!(
+            serde_json::to_string(&ConsentPolicy::Accept).unwrap(),
+            "\"accept\""
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! Debug overlay — a panel drawn into the page with the current step or
+//! last action, plus a numbered box over each interactive element, for
+//! watching headful runs live. Shared by `browser.debug_overlay` and
+//! eoka-agent's `Session::set_debug_overlay`.
+//!
+//! The overlay ignores the pointer and holds no interactive elements, so
+//! clicks and hit-testing see through it. Screenshots don't: turn it off
+//! before capturing images for a model.
+
+use crate::config::Action;
+use eoka::{Page, Result};
+
+/// `(caption, boxes)` → replaces the overlay; `boxes` are `[index, x, y, w, h]`
+/// in viewport pixels, or `null` to number the visible interactive elements
+/// in the viewport in document order.
+const SHOW_JS: &str = r#"
+((caption, boxes) => {
+  document.getElementById('__eoka_debug')?.remove();
+  if (boxes === null) {
+    const INTERACTIVE = 'a, button, input, select, textarea, [role="button"], [role="link"], [role="tab"], [role="menuitem"], [onclick], [contenteditable="true"]';
+    boxes = [];
+    for (const el of document.querySelectorAll(INTERACTIVE)) {
+      const r = el.getBoundingClientRect();
+      if (r.width < 2 || r.height < 2) continue;
+      if (r.bottom < 0 || r.top > innerHeight || r.right < 0 || r.left > innerWidth) continue;
+      const style = getComputedStyle(el);
+      if (style.display === 'none' || style.visibility === 'hidden' || parseFloat(style.opacity) < 0.1) continue;
+      boxes.push([boxes.length, Math.round(r.left), Math.round(r.top), Math.round(r.width), Math.round(r.height)]);
+    }
+  }
+  const root = document.createElement('div');
+  root.id = '__eoka_debug';
+  root.style.cssText = 'position:fixed;inset:0;z-index:2147483647;pointer-events:none';
+  for (const [i, x, y, w, h] of boxes) {
+    const box = document.createElement('div');
+    box.style.cssText = 'position:fixed;border:1.5px solid rgba(37,99,235,0.8);border-radius:1px;'
+      + 'left:' + x + 'px;top:' + y + 'px;width:' + w + 'px;height:' + h + 'px';
+    const label = document.createElement('div');
+    label.style.cssText = 'position:absolute;left:-1px;top:-14px;background:rgba(37,99,235,0.9);'
+      + 'color:#fff;font:bold 10px/12px monospace;padding:1px 3px;border-radius:2px;white-space:nowrap';
+    label.textContent = String(i);
+    box.appendChild(label);
+    root.appendChild(box);
+  }
+  if (caption) {
+    const panel = document.createElement('div');
+    panel.style.cssText = 'position:fixed;left:8px;bottom:8px;max-width:min(640px,calc(100vw - 16px));'
+      + 'background:rgba(17,24,39,0.85);color:#f9fafb;font:12px/16px monospace;padding:6px 8px;'
+      + 'border-radius:4px;white-space:pre-wrap;word-break:break-all';
+    panel.textContent = caption;
+    root.appendChild(panel);
+  }
+  (document.body || document.documentElement).appendChild(root);
+})
+"#;
+
+/// `step 3/7: click #submit`, numbered from 1.
+pub fn step(index: usize, total: usize, action: &Action) -> String {
+    match action.target() {
+        Some(target) => format!("step {}/{}: {} {}", index + 1, total, action.name(), target),
+        None => format!("step {}/{}: {}", index + 1, total, action.name()),
+    }
+}
+
+/// Draw the overlay with `caption` in the corner and a numbered box per
+/// `[index, x, y, width, height]`, replacing any previous one.
+pub async fn show(page: &Page, caption: &str, boxes: &[[i64; 5]]) -> Result<()> {
+    let js = format!(
+        "{}({}, {})",
+        SHOW_JS,
+        serde_json::to_string(caption).unwrap(),
+        serde_json::to_string(boxes).unwrap()
+    );
+    page.execute(&js).await
+}
+
+/// Like [`show`], numbering the visible interactive elements in the
+/// viewport, for callers that haven't observed the page themselves.
+pub async fn show_visible(page: &Page, caption: &str) -> Result<()> {
+    let js = format!(
+        "{}({}, null)",
+        SHOW_JS,
+        serde_json::to_string(caption).unwrap()
+    );
+    page.execute(&js).await
+}
+
+/// Remove the overlay.
+pub async fn hide(page: &Page) -> Result<()> {
+    page.execute("document.getElementById('__eoka_debug')?.remove()")
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::actions::GotoAction;
+
+    #[test]
+    fn step_caption() {
+        assert_eq!(step(1, 4, &Action::Back), "step 2/4: back");
+        let goto = Action::Goto(GotoAction {
+            url: "https://example.com/cart".into(),
+        });
+        assert_eq!(step(0, 4, &goto), "step 1/4: goto https://example.com/cart");
+    }
+}
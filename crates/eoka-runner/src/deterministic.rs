@@ -0,0 +1,318 @@
+//! Deterministic mode for tests — seeds the mouse paths, typing jitter and
+//! pauses of the human-like actions, and the page's `Math.random`, and can
+//! freeze the page clock and skip the pauses, so a flow that passes once
+//! passes the same way every run.
+//!
+//! eoka's own human-like helpers draw from a thread-local RNG that can't be
+//! seeded, so the runner's `click` (`human: true`), `fill` and `press_key`
+//! actions and eoka-agent's humanized actions go through [`human_click`],
+//! [`human_fill`] and [`press_key`] here, which use eoka's helpers when the
+//! mode is off and seeded versions when it is on.
+//!
+//! ```rust,no_run
+//! # async fn example() -> eoka_runner::Result<()> {
+//! use eoka_runner::deterministic::{self, DeterministicConfig};
+//! use eoka_runner::{Config, Runner};
+//!
+//! deterministic::enable(DeterministicConfig {
+//!     rng_seed: 42,
+//!     freeze_time: true,
+//!     zero_delays: true,
+//! });
+//! // Pages opened from now on get the seeded `Math.random` and frozen clock
+//! let config = Config::load("flow.yaml")?;
+//! let mut runner = Runner::new(&config.browser).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use eoka::cdp::{KeyEventType, MouseButton, MouseEventType};
+use eoka::{Page, Result};
+
+/// Frozen page time: 2024-01-01T00:00:00Z, in ms since the epoch.
+pub const FROZEN_TIME_MS: u64 = 1_704_067_200_000;
+
+/// Process-wide switches for reproducible runs; see [`enable`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeterministicConfig {
+    /// Seed for mouse paths, click jitter, typing delays and the page's
+    /// `Math.random`.
+    pub rng_seed: u64,
+    /// Pin the page's `Date` to [`FROZEN_TIME_MS`].
+    pub freeze_time: bool,
+    /// Skip the pauses between mouse moves and keystrokes; the events and
+    /// coordinates stay the same.
+    pub zero_delays: bool,
+}
+
+struct State {
+    config: DeterministicConfig,
+    rng: Rng,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+/// Turn deterministic mode on for the whole process, restarting the RNG
+/// from `config.rng_seed`. Page settings apply to sessions launched after
+/// this call.
+pub fn enable(config: DeterministicConfig) {
+    *STATE.lock().unwrap() = Some(State {
+        config,
+        rng: Rng::new(config.rng_seed),
+    });
+}
+
+/// Turn deterministic mode off.
+pub fn disable() {
+    *STATE.lock().unwrap() = None;
+}
+
+/// The active config, if deterministic mode is on.
+pub fn current() -> Option<DeterministicConfig> {
+    STATE.lock().unwrap().as_ref().map(|s| s.config)
+}
+
+/// SplitMix64 — small, fast and the same on every platform.
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[min, max)`.
+    fn range(&mut self, min: f64, max: f64) -> f64 {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        min + (max - min) * unit
+    }
+}
+
+/// A value in `[min, max)` from the seeded RNG.
+fn random(min: f64, max: f64) -> f64 {
+    match STATE.lock().unwrap().as_mut() {
+        Some(state) => state.rng.range(min, max),
+        None => (min + max) / 2.0,
+    }
+}
+
+/// Sleep a seeded random time in `[min_ms, max_ms)`, or not at all with
+/// `zero_delays`. The RNG advances either way, so paths don't depend on it.
+async fn pause(min_ms: u64, max_ms: u64) {
+    let ms = random(min_ms as f64, max_ms as f64) as u64;
+    if !current().is_some_and(|c| c.zero_delays) {
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+    }
+}
+
+/// Points along a cubic Bézier from `start` to `end`, with control points
+/// pulled off the straight line, like eoka's human mouse.
+fn mouse_path(start: (f64, f64), end: (f64, f64)) -> Vec<(f64, f64)> {
+    let distance = ((end.0 - start.0).powi(2) + (end.1 - start.1).powi(2)).sqrt();
+    let n = (distance / 10.0).clamp(10.0, 50.0) as usize;
+    let cp1 = (
+        start.0 + (end.0 - start.0) * random(0.2, 0.4) + random(-50.0, 50.0),
+        start.1 + (end.1 - start.1) * random(0.0, 0.3) + random(-50.0, 50.0),
+    );
+    let cp2 = (
+        start.0 + (end.0 - start.0) * random(0.6, 0.8) + random(-50.0, 50.0),
+        start.1 + (end.1 - start.1) * random(0.7, 1.0) + random(-50.0, 50.0),
+    );
+    (0..n)
+        .map(|i| {
+            let t = i as f64 / (n - 1) as f64;
+            let mt = 1.0 - t;
+            let a = mt * mt * mt;
+            let b = 3.0 * mt * mt * t;
+            let c = 3.0 * mt * t * t;
+            let d = t * t * t;
+            (
+                a * start.0 + b * cp1.0 + c * cp2.0 + d * end.0,
+                a * start.1 + b * cp1.1 + c * cp2.1 + d * end.1,
+            )
+        })
+        .collect()
+}
+
+/// Move along a seeded path to `(x, y)` and click near it.
+pub async fn human_click_at(page: &Page, x: f64, y: f64) -> Result<()> {
+    let start = (random(100.0, 800.0), random(100.0, 600.0));
+    for (px, py) in mouse_path(start, (x, y)) {
+        page.session()
+            .dispatch_mouse_event(MouseEventType::MouseMoved, px, py, None, None)
+            .await?;
+        pause(5, 25).await;
+    }
+    pause(50, 150).await;
+    let (cx, cy) = (x + random(-2.0, 2.0), y + random(-2.0, 2.0));
+    page.session()
+        .dispatch_mouse_event(
+            MouseEventType::MousePressed,
+            cx,
+            cy,
+            Some(MouseButton::Left),
+            Some(1),
+        )
+        .await?;
+    pause(50, 120).await;
+    page.session()
+        .dispatch_mouse_event(
+            MouseEventType::MouseReleased,
+            cx,
+            cy,
+            Some(MouseButton::Left),
+            Some(1),
+        )
+        .await?;
+    pause(30, 100).await;
+    Ok(())
+}
+
+/// Type into whatever has focus, with seeded per-key delays.
+pub async fn human_type(page: &Page, text: &str) -> Result<()> {
+    for ch in text.chars() {
+        page.session()
+            .dispatch_key_event(KeyEventType::Char, None, Some(&ch.to_string()), None)
+            .await?;
+        match ch {
+            ' ' => pause(80, 180).await,
+            c if c.is_ascii_punctuation() => pause(100, 200).await,
+            _ => pause(50, 150).await,
+        }
+    }
+    Ok(())
+}
+
+/// Human-like click on the element matching `selector`; seeded while
+/// deterministic mode is on.
+pub async fn human_click(page: &Page, selector: &str) -> Result<()> {
+    if current().is_none() {
+        return page.human_click(selector).await;
+    }
+    let (x, y) = page.find(selector).await?.center().await?;
+    human_click_at(page, x, y).await
+}
+
+/// Human-like clear and type into the element matching `selector`; seeded
+/// while deterministic mode is on.
+pub async fn human_fill(page: &Page, selector: &str, text: &str) -> Result<()> {
+    if current().is_none() {
+        return page.human_fill(selector, text).await;
+    }
+    let (x, y) = page.find(selector).await?.center().await?;
+    human_click_at(page, x, y).await?;
+    // Inputs and textareas have select(); contenteditable needs selectAll
+    page.execute(
+        "(el => el && ('select' in el ? el.select() : document.execCommand('selectAll')))(document.activeElement)",
+    )
+    .await?;
+    human_type(page, text).await
+}
+
+/// Press and release `key` (e.g. "Enter", "Tab") with a human pause in
+/// between; seeded while deterministic mode is on.
+pub async fn press_key(page: &Page, key: &str) -> Result<()> {
+    if current().is_none() {
+        return page.human().press_key(key).await;
+    }
+    page.session()
+        .dispatch_key_event(KeyEventType::KeyDown, Some(key), None, Some(key))
+        .await?;
+    pause(50, 100).await;
+    page.session()
+        .dispatch_key_event(KeyEventType::KeyUp, Some(key), None, Some(key))
+        .await?;
+    Ok(())
+}
+
+/// Seeds `Math.random` (mulberry32) and, with a time, pins `Date` to it.
+const PAGE_JS: &str = r#"
+(seed, frozen) => {
+    let s = seed >>> 0;
+    Math.random = function random() {
+        s = (s + 0x6D2B79F5) | 0;
+        let t = Math.imul(s ^ (s >>> 15), 1 | s);
+        t = (t + Math.imul(t ^ (t >>> 7), 61 | t)) ^ t;
+        return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+    };
+    if (frozen !== null) {
+        const RealDate = Date;
+        function FrozenDate(...args) {
+            if (!new.target) return new RealDate(frozen).toString();
+            return new RealDate(...(args.length ? args : [frozen]));
+        }
+        FrozenDate.prototype = RealDate.prototype;
+        FrozenDate.now = () => frozen;
+        FrozenDate.parse = RealDate.parse;
+        FrozenDate.UTC = RealDate.UTC;
+        window.Date = FrozenDate;
+    }
+}
+"#;
+
+/// Script that applies `config` to a page; run it before the page's own.
+pub fn page_script(config: &DeterministicConfig) -> String {
+    let seed = (config.rng_seed ^ (config.rng_seed >> 32)) as u32;
+    let frozen = if config.freeze_time {
+        FROZEN_TIME_MS.to_string()
+    } else {
+        "null".into()
+    };
+    format!("({})({}, {})", PAGE_JS.trim(), seed, frozen)
+}
+
+/// Run [`page_script`] in `page` and every later document of it, if
+/// deterministic mode is on.
+pub async fn install(page: &Page) -> Result<()> {
+    let Some(config) = current() else {
+        return Ok(());
+    };
+    let script = page_script(&config);
+    let _: serde_json::Value = page
+        .session()
+        .send(
+            "Page.addScriptToEvaluateOnNewDocument",
+            &serde_json::json!({ "source": script }),
+        )
+        .await?;
+    page.execute(&script).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_path() {
+        let config = DeterministicConfig {
+            rng_seed: 7,
+            ..Default::default()
+        };
+        enable(config);
+        let first = mouse_path((0.0, 0.0), (300.0, 200.0));
+        enable(config);
+        let second = mouse_path((0.0, 0.0), (300.0, 200.0));
+        disable();
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 36);
+        assert_eq!(first[0], (0.0, 0.0));
+        assert_eq!(*first.last().unwrap(), (300.0, 200.0));
+
+        let mut a = Rng::new(1);
+        let mut b = Rng::new(2);
+        assert_ne!(a.next_u64(), b.next_u64());
+        assert!((0..1000).all(|_| (0.0..1.0).contains(&a.range(0.0, 1.0))));
+        assert!(page_script(&config).ends_with("(7, null)"));
+    }
+}
@@ -0,0 +1,189 @@
+//! Crawl frontier — the breadth-first queue of pages still to visit and the
+//! rules for which discovered links join it. Shared by `crawl:` mode and
+//! eoka-agent's crawler, so both follow the same links.
+
+use eoka::{Page, Result};
+use regex::Regex;
+use reqwest::Url;
+use std::collections::{HashSet, VecDeque};
+
+/// Lists link targets matching a selector, fragments stripped.
+const LINKS_JS: &str = r#"((selector) => JSON.stringify(
+    Array.from(document.querySelectorAll(selector))
+        .map(a => (a.href || '').split('#')[0])
+        .filter(h => /^https?:/.test(h))
+))"#;
+
+/// Link targets on the current page matching `selector` (default `a[href]`).
+pub async fn links(page: &Page, selector: Option<&str>) -> Result<Vec<String>> {
+    let js = format!(
+        "{}({})",
+        LINKS_JS,
+        serde_json::to_string(selector.unwrap_or("a[href]")).unwrap()
+    );
+    let json: String = page.evaluate(&js).await?;
+    serde_json::from_str(&json)
+        .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse links: {}", e)))
+}
+
+/// Breadth-first queue of `(url, depth)` with a visited set.
+#[derive(Debug, Clone)]
+pub struct Frontier {
+    queue: VecDeque<(String, usize)>,
+    seen: HashSet<String>,
+    origins: HashSet<String>,
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+    max_depth: usize,
+    max_pages: usize,
+    same_origin: bool,
+}
+
+impl Frontier {
+    /// Queue `seeds` at depth 0, following links at most `max_depth` hops
+    /// and visiting at most `max_pages` pages. Same-origin by default.
+    pub fn new<S: AsRef<str>>(
+        seeds: impl IntoIterator<Item = S>,
+        max_depth: usize,
+        max_pages: usize,
+    ) -> Self {
+        let mut frontier = Self {
+            queue: VecDeque::new(),
+            seen: HashSet::new(),
+            origins: HashSet::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            max_depth,
+            max_pages,
+            same_origin: true,
+        };
+        for seed in seeds {
+            let seed = seed.as_ref();
+            frontier.origins.extend(origin_of(seed, false));
+            frontier.push(seed.split('#').next().unwrap_or(seed).to_string(), 0);
+        }
+        frontier
+    }
+
+    /// Only follow links to the seeds' origins.
+    pub fn same_origin(mut self, same_origin: bool) -> Self {
+        self.same_origin = same_origin;
+        self
+    }
+
+    /// Follow only URLs matching one of these, if any are set.
+    pub fn include(mut self, patterns: Vec<Regex>) -> Self {
+        self.include = patterns;
+        self
+    }
+
+    /// Never follow URLs matching any of these.
+    pub fn exclude(mut self, patterns: Vec<Regex>) -> Self {
+        self.exclude = patterns;
+        self
+    }
+
+    /// Next page to visit.
+    pub fn next_page(&mut self) -> Option<(String, usize)> {
+        self.queue.pop_front()
+    }
+
+    /// Queue the links found on a page at `depth`.
+    pub fn add_links(&mut self, links: Vec<String>, depth: usize) {
+        if depth >= self.max_depth {
+            return;
+        }
+        for url in links {
+            if self.follows(&url) {
+                self.push(url, depth + 1);
+            }
+        }
+    }
+
+    /// Whether a discovered link should be followed. Seeds are always visited.
+    pub fn follows(&self, url: &str) -> bool {
+        if self.same_origin && !origin_of(url, false).is_some_and(|o| self.origins.contains(&o)) {
+            return false;
+        }
+        if !self.include.is_empty() && !self.include.iter().any(|re| re.is_match(url)) {
+            return false;
+        }
+        !self.exclude.iter().any(|re| re.is_match(url))
+    }
+
+    fn push(&mut self, url: String, depth: usize) {
+        if self.seen.len() < self.max_pages && self.seen.insert(url.clone()) {
+            self.queue.push_back((url, depth));
+        }
+    }
+}
+
+/// `scheme://host[:port]` of a URL, shared with [`crate::links`] and
+/// eoka-agent's rate limiter. With `http_only`, other schemes (`data:`,
+/// `mailto:`) have no origin.
+pub fn origin_of(url: &str, http_only: bool) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    if http_only && !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    Some(url.origin().ascii_serialization())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origins() {
+        assert_eq!(
+            origin_of("https://Example.com:443/a?b", false).as_deref(),
+            Some("https://example.com")
+        );
+        assert_eq!(
+            origin_of("data:text/html,hi", false).as_deref(),
+            Some("null")
+        );
+        assert_eq!(origin_of("data:text/html,hi", true), None);
+        assert_eq!(origin_of("not a url", false), None);
+    }
+
+    #[test]
+    fn link_filters() {
+        let frontier = Frontier::new(["https://shop.example/"], 2, 100)
+            .include(vec![Regex::new("/products/").unwrap()])
+            .exclude(vec![Regex::new(r"\?sort=").unwrap()]);
+        assert!(frontier.follows("https://shop.example/products/1"));
+        assert!(!frontier.follows("https://shop.example/about"));
+        assert!(!frontier.follows("https://shop.example/products/?sort=asc"));
+        assert!(!frontier.follows("https://other.example/products/1"));
+        assert!(frontier
+            .same_origin(false)
+            .follows("https://other.example/products/1"));
+    }
+
+    #[test]
+    fn dedupes_and_caps() {
+        let mut frontier = Frontier::new(["https://a.example/#top", "https://b.example/"], 1, 3);
+        frontier.add_links(
+            vec![
+                "https://a.example/1".into(),
+                "https://b.example/1".into(),
+                "https://a.example/1".into(),
+                "https://a.example/2".into(),
+            ],
+            0,
+        );
+        let mut queued = Vec::new();
+        while let Some(page) = frontier.next_page() {
+            queued.push(page);
+        }
+        assert_eq!(
+            queued,
+            [
+                ("https://a.example/".to_string(), 0),
+                ("https://b.example/".to_string(), 0),
+                ("https://a.example/1".to_string(), 1),
+            ]
+        );
+    }
+}
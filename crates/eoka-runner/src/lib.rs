@@ -1,6 +1,6 @@
 //! # eoka-runner
 //!
-//! Config-based browser automation. Define actions in YAML, execute deterministically.
+//! Config-based browser automation. Define actions in YAML (or TOML/JSON), execute deterministically.
 //!
 //! ## Quick Start
 //!
@@ -17,13 +17,34 @@
 //! # }
 //! ```
 
+pub mod a11y;
+pub mod autofill;
+pub mod capture;
 mod config;
+pub mod consent;
+pub mod debug_overlay;
+pub mod deterministic;
+pub mod doctor;
+pub mod frontier;
+pub mod links;
+mod plan;
+pub mod popup;
+#[cfg(feature = "tui")]
+pub mod progress;
 mod runner;
+pub mod schedule;
+pub mod target;
+pub mod visual;
 
 pub use config::{
-    Action, BrowserConfig, Config, ParamDef, Params, SuccessCondition, Target, TargetUrl,
+    Action, BrowserConfig, Config, ConfigFormat, ConsentPolicy, CrawlConfig, DevicePreset,
+    ParamDef, Params, PopupPolicy, SuccessCondition, Target, TargetUrl,
+};
+pub use plan::{Plan, PlanStep};
+pub use runner::{
+    proxy_bypass_arg, ActionTiming, AgentTask, AgentTaskFuture, AgentTaskHandler, CrawlError,
+    CrawlReport, Event, Frame, Resolution, RunArtifacts, RunResult, Runner, Variables,
 };
-pub use runner::{RunResult, Runner};
 
 /// Result type for eoka-runner operations.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -37,6 +58,12 @@ pub enum Error {
     #[error("yaml parse error: {0}")]
     Yaml(#[from] serde_yaml::Error),
 
+    #[error("toml parse error: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("json parse error: {0}")]
+    Json(#[from] serde_json::Error),
+
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -227,6 +254,46 @@ actions:
         }
     }
 
+    #[test]
+    fn test_parse_live_targets() {
+        let yaml = r##"
+name: "Test"
+target:
+  url: "https://example.com"
+actions:
+  - fill:
+      label: "Email"
+      value: "a@example.com"
+  - fill:
+      placeholder: "Search"
+      value: "shoes"
+  - click:
+      role: "dialog"
+  - hover:
+      id: "menu"
+"##;
+        let config = Config::parse(yaml).unwrap();
+        let targets: Vec<String> = config.actions.iter().filter_map(|a| a.target()).collect();
+        assert_eq!(
+            targets,
+            [
+                "label 'Email'",
+                "placeholder 'Search'",
+                "role 'dialog'",
+                "id 'menu'"
+            ]
+        );
+
+        if let Action::Fill(a) = &config.actions[0] {
+            assert_eq!(
+                a.target.pattern(),
+                Some(target::LivePattern::Label("Email".into()))
+            );
+        } else {
+            panic!("Expected Fill action");
+        }
+    }
+
     #[test]
     fn test_parse_scroll_actions() {
         let yaml = r##"
@@ -343,6 +410,109 @@ success:
         assert_eq!(any.len(), 2);
     }
 
+    #[test]
+    fn test_parse_nested_success_conditions() {
+        use crate::config::schema::Condition;
+
+        let yaml = r#"
+name: "Test"
+target:
+  url: "https://example.com"
+success:
+  all:
+    - element_exists: ".order-confirmation"
+    - element_absent: ".error"
+    - any:
+        - cookie_exists: "session_id"
+        - js_true: "window.loggedIn === true"
+"#;
+        let config = Config::parse(yaml).unwrap();
+        let all = config.success.unwrap().all.unwrap();
+        assert_eq!(all.len(), 3);
+        assert!(matches!(&all[0], Condition::ElementExists(s) if s == ".order-confirmation"));
+        assert!(matches!(&all[1], Condition::ElementAbsent(s) if s == ".error"));
+        if let Condition::Any(nested) = &all[2] {
+            assert!(matches!(&nested[0], Condition::CookieExists(n) if n == "session_id"));
+            assert!(matches!(&nested[1], Condition::JsTrue(_)));
+        } else {
+            panic!("Expected nested any");
+        }
+    }
+
+    #[test]
+    fn test_parse_traffic_conditions() {
+        use crate::config::schema::Condition;
+
+        let yaml = r#"
+name: "Test"
+target:
+  url: "https://example.com"
+success:
+  all:
+    - url_contains: "/done"
+    - any:
+        - request_made: { url_contains: "/collect", method: POST }
+        - response_status: { url_contains: "/api/order", status: 201 }
+"#;
+        let config = Config::parse(yaml).unwrap();
+        let success = config.success.unwrap();
+        assert!(success.uses_traffic());
+        let all = success.all.unwrap();
+        assert!(!all[0].uses_traffic());
+        let Condition::Any(ref nested) = all[1] else {
+            panic!("Expected nested any");
+        };
+        assert!(matches!(&nested[0], Condition::RequestMade(r)
+            if r.url_contains == "/collect" && r.method.as_deref() == Some("POST")));
+        assert!(matches!(&nested[1], Condition::ResponseStatus(r) if r.status == 201));
+    }
+
+    #[test]
+    fn test_parse_unknown_condition() {
+        let yaml = r#"
+name: "Test"
+target:
+  url: "https://example.com"
+success:
+  any:
+    - status_code: 200
+"#;
+        assert!(Config::parse(yaml).is_err());
+    }
+
+    #[test]
+    fn test_parse_hooks() {
+        use crate::config::schema::WebhookFormat;
+
+        let yaml = r#"
+name: "Test"
+target:
+  url: "https://example.com"
+on_success:
+  write_result: "result.json"
+  include:
+    path: "flows/logout.yaml"
+on_failure:
+  screenshot: "error.png"
+  webhook:
+    url: "https://discord.com/api/webhooks/1/abc"
+    format: discord
+    headers:
+      X-Source: "eoka"
+"#;
+        let config = Config::parse(yaml).unwrap();
+        let on_success = config.on_success.unwrap();
+        assert_eq!(on_success.write_result, Some("result.json".into()));
+        assert_eq!(on_success.include.unwrap().path, "flows/logout.yaml");
+        assert!(on_success.webhook.is_none());
+
+        let on_failure = config.on_failure.unwrap();
+        assert_eq!(on_failure.screenshot, Some("error.png".into()));
+        let webhook = on_failure.hooks.webhook.unwrap();
+        assert!(matches!(webhook.format, WebhookFormat::Discord));
+        assert_eq!(webhook.headers.get("X-Source"), Some(&"eoka".to_string()));
+    }
+
     #[test]
     fn test_parse_on_failure() {
         let yaml = r#"
@@ -464,7 +634,8 @@ actions:
         }
 
         if let Action::PressKey(a) = &config.actions[1] {
-            assert_eq!(a.key, "Enter");
+            assert_eq!(a.key, Some("Enter".into()));
+            assert_eq!(a.sequence(), vec!["Enter"]);
         } else {
             panic!("Expected PressKey action");
         }
@@ -691,4 +862,888 @@ actions:
             panic!("Expected Include action");
         }
     }
+
+    #[test]
+    fn test_parse_within_frame() {
+        let yaml = r##"
+name: "Test"
+target:
+  url: "https://example.com"
+actions:
+  - within_frame:
+      selector: "iframe#checkout"
+      actions:
+        - within_frame:
+            url_contains: "js.stripe.com"
+            timeout_ms: 5000
+            actions:
+              - fill:
+                  selector: "input[name=cardnumber]"
+                  value: "4242424242424242"
+"##;
+        let config = Config::parse(yaml).unwrap();
+
+        if let Action::WithinFrame(outer) = &config.actions[0] {
+            assert_eq!(outer.frame.selector, Some("iframe#checkout".to_string()));
+            assert_eq!(outer.timeout_ms, 10000);
+            if let Action::WithinFrame(inner) = &outer.actions[0] {
+                assert_eq!(inner.frame.url_contains, Some("js.stripe.com".to_string()));
+                assert_eq!(inner.timeout_ms, 5000);
+                assert!(matches!(inner.actions[0], Action::Fill(_)));
+            } else {
+                panic!("Expected nested WithinFrame action");
+            }
+        } else {
+            panic!("Expected WithinFrame action");
+        }
+    }
+
+    #[test]
+    fn test_parse_file_actions() {
+        let yaml = r##"
+name: "Test"
+browser:
+  download_dir: "/tmp/downloads"
+target:
+  url: "https://example.com"
+actions:
+  - upload:
+      selector: "input[type=file]"
+      file: "fixtures/avatar.png"
+  - wait_for_download:
+      save_as: "out/export.csv"
+  - assert_download:
+      filename_contains: ".csv"
+      min_bytes: 100
+"##;
+        let config = Config::parse(yaml).unwrap();
+        assert_eq!(
+            config.browser.download_dir,
+            Some("/tmp/downloads".to_string())
+        );
+
+        if let Action::Upload(a) = &config.actions[0] {
+            assert_eq!(a.target.selector, Some("input[type=file]".to_string()));
+            assert_eq!(a.file, "fixtures/avatar.png");
+        } else {
+            panic!("Expected Upload action");
+        }
+        if let Action::WaitForDownload(a) = &config.actions[1] {
+            assert_eq!(a.timeout_ms, 30_000);
+            assert_eq!(a.save_as, Some("out/export.csv".to_string()));
+        } else {
+            panic!("Expected WaitForDownload action");
+        }
+        if let Action::AssertDownload(a) = &config.actions[2] {
+            assert_eq!(a.filename_contains, Some(".csv".to_string()));
+            assert_eq!(a.min_bytes, Some(100));
+            assert!(a.max_bytes.is_none());
+        } else {
+            panic!("Expected AssertDownload action");
+        }
+    }
+
+    #[test]
+    fn test_parse_agent_task() {
+        let yaml = r#"
+name: "Test"
+target:
+  url: "https://example.com"
+actions:
+  - agent_task:
+      goal: "dismiss whatever popup appears and reach the search box"
+  - agent_task:
+      goal: "accept the terms"
+      max_turns: 3
+      model: "small"
+"#;
+        let config = Config::parse(yaml).unwrap();
+        if let Action::AgentTask(a) = &config.actions[0] {
+            assert_eq!(
+                a.goal,
+                "dismiss whatever popup appears and reach the search box"
+            );
+            assert_eq!(a.max_turns, 10);
+            assert!(a.model.is_none());
+        } else {
+            panic!("Expected AgentTask action");
+        }
+        if let Action::AgentTask(a) = &config.actions[1] {
+            assert_eq!(a.max_turns, 3);
+            assert_eq!(a.model, Some("small".to_string()));
+        } else {
+            panic!("Expected AgentTask action");
+        }
+    }
+
+    #[test]
+    fn test_parse_scroll_until_and_infinite() {
+        let yaml = r##"
+name: "Test"
+target:
+  url: "https://example.com"
+actions:
+  - scroll_until:
+      text: "Load more"
+      max_scrolls: 5
+  - scroll_infinite:
+      item_selector: ".post"
+"##;
+        let config = Config::parse(yaml).unwrap();
+
+        if let Action::ScrollUntil(a) = &config.actions[0] {
+            assert_eq!(a.target.text, Some("Load more".to_string()));
+            assert_eq!(a.max_scrolls, 5);
+            assert_eq!(a.idle_ms, 500);
+        } else {
+            panic!("Expected ScrollUntil action");
+        }
+        if let Action::ScrollInfinite(a) = &config.actions[1] {
+            assert_eq!(a.idle_rounds, 3);
+            assert_eq!(a.max_scrolls, 100);
+            assert_eq!(a.item_selector, Some(".post".to_string()));
+        } else {
+            panic!("Expected ScrollInfinite action");
+        }
+    }
+
+    #[test]
+    fn test_parse_press_key_sequence() {
+        let yaml = r##"
+name: "Test"
+target:
+  url: "https://example.com"
+actions:
+  - press_key:
+      keys: ["Ctrl+A", "Delete", "Enter"]
+      delay_ms: 100
+"##;
+        let config = Config::parse(yaml).unwrap();
+
+        if let Action::PressKey(a) = &config.actions[0] {
+            assert!(a.key.is_none());
+            assert_eq!(a.sequence(), vec!["Ctrl+A", "Delete", "Enter"]);
+            assert_eq!(a.delay_ms, 100);
+        } else {
+            panic!("Expected PressKey action");
+        }
+    }
+
+    #[test]
+    fn test_action_target_omits_values() {
+        let yaml = r##"
+name: "Test"
+target:
+  url: "https://example.com"
+actions:
+  - fill:
+      selector: "#password"
+      value: "hunter2"
+  - goto:
+      url: "https://example.com/next"
+  - wait:
+      ms: 100
+"##;
+        let config = Config::parse(yaml).unwrap();
+
+        assert_eq!(
+            config.actions[0].target().as_deref(),
+            Some("selector '#password'")
+        );
+        assert_eq!(
+            config.actions[1].target().as_deref(),
+            Some("https://example.com/next")
+        );
+        assert_eq!(config.actions[2].target(), None);
+    }
+
+    #[test]
+    fn test_parse_pause() {
+        let yaml = r##"
+name: "Test"
+target:
+  url: "https://example.com"
+actions:
+  - pause
+  - pause:
+  - pause:
+      message: "Check the cart"
+      timeout_ms: 5000
+"##;
+        let config = Config::parse(yaml).unwrap();
+
+        assert!(matches!(&config.actions[0], Action::Pause(a) if a.message.is_none()));
+        assert!(matches!(&config.actions[1], Action::Pause(a) if a.timeout_ms.is_none()));
+        if let Action::Pause(a) = &config.actions[2] {
+            assert_eq!(a.message, Some("Check the cart".to_string()));
+            assert_eq!(a.timeout_ms, Some(5000));
+        } else {
+            panic!("Expected Pause action");
+        }
+    }
+
+    #[test]
+    fn test_parse_browser_launch_options() {
+        let yaml = r##"
+name: "Test"
+browser:
+  executable_path: "/usr/bin/chromium"
+  patch_binary: false
+  human_typing: false
+  locale: "de-DE"
+  timezone: "Europe/Berlin"
+  device: pixel_7
+  proxy_bypass: ["localhost", "*.internal"]
+target:
+  url: "https://example.com"
+"##;
+        let config = Config::parse(yaml).unwrap();
+
+        let browser = &config.browser;
+        assert_eq!(
+            browser.executable_path.as_deref(),
+            Some("/usr/bin/chromium")
+        );
+        assert_eq!(browser.patch_binary, Some(false));
+        assert_eq!(browser.human_typing, Some(false));
+        assert_eq!(browser.human_mouse, None);
+        assert_eq!(browser.locale.as_deref(), Some("de-DE"));
+        assert_eq!(browser.timezone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(browser.device, Some(DevicePreset::Pixel7));
+        let metrics = DevicePreset::Pixel7.metrics();
+        assert!(metrics.mobile);
+        assert_eq!((metrics.width, metrics.height), (412, 915));
+        assert_eq!(
+            proxy_bypass_arg(&browser.proxy_bypass).as_deref(),
+            Some("--proxy-bypass-list=localhost;*.internal")
+        );
+        assert_eq!(proxy_bypass_arg(&[]), None);
+
+        let bad = yaml.replace("pixel_7", "nokia_3310");
+        assert!(Config::parse(&bad).is_err());
+    }
+
+    #[test]
+    fn test_profile_deep_merge() {
+        let yaml = r##"
+name: "Test"
+params:
+  env:
+    default: "dev"
+browser:
+  headless: false
+  viewport:
+    width: 1280
+    height: 720
+target:
+  url: "http://localhost:3000/${env}"
+profiles:
+  staging:
+    target:
+      url: "https://staging.example.com/${env}"
+    params:
+      env:
+        default: "staging"
+    browser:
+      viewport:
+        width: 1920
+"##;
+        let base = Config::parse(yaml).unwrap();
+        assert_eq!(base.target.url, "http://localhost:3000/dev");
+
+        let staging = Config::parse_with_profile(yaml, &Params::new(), "staging").unwrap();
+        assert_eq!(staging.target.url, "https://staging.example.com/staging");
+        assert!(!staging.browser.headless);
+        let viewport = staging.browser.viewport.unwrap();
+        assert_eq!((viewport.width, viewport.height), (1920, 720));
+
+        let err = Config::parse_with_profile(yaml, &Params::new(), "prod").unwrap_err();
+        assert!(err.to_string().contains("available: staging"));
+    }
+
+    #[test]
+    fn test_matrix_expansion() {
+        let yaml = r##"
+name: "Checkout"
+params:
+  locale:
+    required: true
+  device:
+    required: true
+matrix:
+  locale: ["en-US", "de-DE", "ja-JP"]
+  device: [desktop, iphone_14]
+browser:
+  locale: "${locale}"
+  device: "${device}"
+target:
+  url: "https://example.com/${locale}"
+"##;
+        let configs = Config::parse_matrix(yaml, &Params::new(), None).unwrap();
+        assert_eq!(configs.len(), 6);
+        assert_eq!(configs[0].name, "Checkout [locale=en-US, device=desktop]");
+        assert_eq!(configs[5].name, "Checkout [locale=ja-JP, device=iphone_14]");
+        assert_eq!(configs[3].target.url, "https://example.com/de-DE");
+        assert_eq!(configs[3].browser.device, Some(DevicePreset::Iphone14));
+
+        // An explicit param pins that dimension
+        let params = Params::new().set("locale", "fr-FR");
+        let configs = Config::parse_matrix(yaml, &params, None).unwrap();
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs[1].name, "Checkout [device=iphone_14]");
+        assert_eq!(configs[1].browser.locale.as_deref(), Some("fr-FR"));
+
+        // No matrix: the config comes back unchanged
+        let single = Config::parse_matrix(
+            "name: Plain\ntarget:\n  url: https://example.com\n",
+            &Params::new(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].name, "Plain");
+
+        assert!(Config::parse_matrix(
+            "name: Bad\nmatrix:\n  locale: []\ntarget:\n  url: https://example.com\n",
+            &Params::new(),
+            None,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_include_pass_and_cycles() {
+        let yaml = r#"
+name: "Main"
+params:
+  email:
+    required: true
+  region:
+    default: "eu"
+target:
+  url: "https://example.com"
+actions:
+  - include:
+      path: "flows/login.yaml"
+      pass: [email, region]
+      params:
+        region: "us"
+"#;
+        let config = Config::parse_with_params(yaml, &Params::new().set("email", "a@b.c")).unwrap();
+        if let Action::Include(a) = &config.actions[0] {
+            assert_eq!(a.params["email"], "a@b.c");
+            // Explicit params win over pass-through
+            assert_eq!(a.params["region"], "us");
+        } else {
+            panic!("Expected Include action");
+        }
+
+        let err = Config::parse_with_params(
+            &yaml.replace("[email, region]", "[email, missing]"),
+            &Params::new().set("email", "a@b.c"),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("passes unknown param 'missing'"));
+
+        let dir = std::env::temp_dir().join(format!("eoka-runner-include-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("flows")).unwrap();
+        let flow = |name: &str, include: &str| {
+            format!(
+                "name: {}\ntarget:\n  url: about:blank\nactions:\n  - include: {{ path: \"{}\" }}\n",
+                name, include
+            )
+        };
+        // Nested paths resolve against the including file, not the top-level config
+        std::fs::write(dir.join("main.yaml"), flow("main", "flows/a.yaml")).unwrap();
+        std::fs::write(dir.join("flows/a.yaml"), flow("a", "b.yaml")).unwrap();
+        std::fs::write(dir.join("flows/b.yaml"), flow("b", "a.yaml")).unwrap();
+
+        let config = Config::load(dir.join("main.yaml")).unwrap();
+        let err = config.plan(&dir, &Params::new()).unwrap_err();
+        assert!(err.to_string().contains("include cycle:"), "{}", err);
+        assert!(err.to_string().contains("a.yaml -> "), "{}", err);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_toml_and_json_configs() {
+        let toml = r##"
+name = "Search"
+
+[params.query]
+default = "rust"
+
+[target]
+url = "https://example.com/?q=${query}"
+
+[[actions]]
+wait = { ms = 500 }
+
+[[actions]]
+click = { selector = "#go" }
+"##;
+        let config = Config::parse_format(toml, ConfigFormat::Toml, &Params::new()).unwrap();
+        assert_eq!(config.target.url, "https://example.com/?q=rust");
+        assert_eq!(config.actions.len(), 2);
+        assert!(matches!(config.actions[1], Action::Click(_)));
+
+        let json = r#"{
+            "name": "Search",
+            "target": { "url": "https://example.com" },
+            "actions": [{ "wait": { "ms": 500 } }, "pause"]
+        }"#;
+        let config = Config::parse_format(json, ConfigFormat::Json, &Params::new()).unwrap();
+        assert_eq!(config.actions.len(), 2);
+        assert!(matches!(config.actions[1], Action::Pause(_)));
+
+        assert_eq!(
+            ConfigFormat::from_path(std::path::Path::new("flows/login.TOML")),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            ConfigFormat::from_path(std::path::Path::new("login.yml")),
+            ConfigFormat::Yaml
+        );
+
+        let err = Config::parse_format("name = ", ConfigFormat::Toml, &Params::new()).unwrap_err();
+        assert!(err.to_string().starts_with("toml parse error"));
+    }
+
+    #[test]
+    fn test_parse_assert_screenshot() {
+        let yaml = r#"
+name: "Visual"
+target:
+  url: "https://example.com"
+actions:
+  - assert_screenshot:
+      baseline: "baselines/home.png"
+  - assert_screenshot:
+      baseline: "baselines/header.png"
+      selector: "header"
+      mode: structural
+      threshold: 0.05
+      diff: "out/header-diff.png"
+"#;
+        let config = Config::parse(yaml).unwrap();
+        if let Action::AssertScreenshot(a) = &config.actions[0] {
+            assert_eq!(a.threshold, 0.01);
+            assert_eq!(a.tolerance, 16);
+            assert_eq!(a.mode, visual::DiffMode::Pixel);
+            assert_eq!(
+                a.diff_path(),
+                std::path::PathBuf::from("baselines/home.diff.png")
+            );
+        } else {
+            panic!("Expected AssertScreenshot action");
+        }
+        if let Action::AssertScreenshot(a) = &config.actions[1] {
+            assert_eq!(a.selector.as_deref(), Some("header"));
+            assert_eq!(a.mode, visual::DiffMode::Structural);
+            assert_eq!(a.threshold, 0.05);
+            assert_eq!(
+                a.diff_path(),
+                std::path::PathBuf::from("out/header-diff.png")
+            );
+        } else {
+            panic!("Expected AssertScreenshot action");
+        }
+    }
+
+    #[test]
+    fn test_parse_assert_snapshot() {
+        let yaml = r##"
+name: "Content"
+target:
+  url: "https://example.com"
+actions:
+  - assert_snapshot:
+      golden: "snapshots/home.txt"
+  - assert_snapshot:
+      golden: "snapshots/pricing.html"
+      selector: "#pricing"
+      format: html
+      ignore: ['\d{4}-\d{2}-\d{2}']
+"##;
+        let config = Config::parse(yaml).unwrap();
+        if let Action::AssertSnapshot(a) = &config.actions[0] {
+            assert_eq!(a.format, config::actions::SnapshotFormat::Text);
+            assert!(a.ignore.is_empty());
+            assert_eq!(
+                a.actual_path(),
+                std::path::PathBuf::from("snapshots/home.txt.actual")
+            );
+        } else {
+            panic!("Expected AssertSnapshot action");
+        }
+        if let Action::AssertSnapshot(a) = &config.actions[1] {
+            assert_eq!(a.selector.as_deref(), Some("#pricing"));
+            assert_eq!(a.format, config::actions::SnapshotFormat::Html);
+            assert_eq!(a.ignore, vec![r"\d{4}-\d{2}-\d{2}".to_string()]);
+        } else {
+            panic!("Expected AssertSnapshot action");
+        }
+    }
+
+    #[test]
+    fn test_parse_audit_links() {
+        let yaml = r##"
+name: "Links"
+target:
+  url: "https://example.com"
+actions:
+  - audit_links
+  - audit_links:
+      same_origin: false
+      selector: "main a"
+      ignore: ["/logout"]
+"##;
+        let config = Config::parse(yaml).unwrap();
+        assert!(matches!(
+            &config.actions[0],
+            Action::AuditLinks(a) if a.same_origin && a.selector.is_none()
+        ));
+        if let Action::AuditLinks(a) = &config.actions[1] {
+            assert!(!a.same_origin);
+            assert_eq!(a.selector.as_deref(), Some("main a"));
+            assert_eq!(a.ignore, vec!["/logout".to_string()]);
+        } else {
+            panic!("Expected AuditLinks action");
+        }
+    }
+
+    #[test]
+    fn test_parse_autofill() {
+        use autofill::TestCard;
+
+        let yaml = r##"
+name: "Autofill"
+target:
+  url: "https://example.com"
+actions:
+  - autofill
+  - autofill:
+      form: "#checkout"
+      human: true
+      profile:
+        email: qa@example.com
+        card: amex
+"##;
+        let config = Config::parse(yaml).unwrap();
+        assert!(matches!(
+            &config.actions[0],
+            Action::Autofill(a) if a.form.is_none() && a.profile.card == TestCard::Visa
+        ));
+        if let Action::Autofill(a) = &config.actions[1] {
+            assert_eq!(a.form.as_deref(), Some("#checkout"));
+            assert!(a.human);
+            assert_eq!(a.profile.email, "qa@example.com");
+            assert_eq!(a.profile.card, TestCard::Amex);
+            assert_eq!(a.profile.first_name, "Jane");
+        } else {
+            panic!("Expected Autofill action");
+        }
+        assert_eq!(config.actions[1].target().as_deref(), Some("#checkout"));
+    }
+
+    #[test]
+    fn test_parse_assert_a11y() {
+        use a11y::Severity;
+
+        let yaml = r##"
+name: "A11y"
+target:
+  url: "https://example.com"
+actions:
+  - assert_a11y
+  - assert_a11y:
+      severity: critical
+      ignore: [color-contrast]
+"##;
+        let config = Config::parse(yaml).unwrap();
+        assert!(matches!(
+            &config.actions[0],
+            Action::AssertA11y(a) if a.severity == Severity::Serious && a.ignore.is_empty()
+        ));
+        if let Action::AssertA11y(a) = &config.actions[1] {
+            assert_eq!(a.severity, Severity::Critical);
+            assert_eq!(a.ignore, vec!["color-contrast".to_string()]);
+            assert!(Severity::Critical > Severity::Minor);
+        } else {
+            panic!("Expected AssertA11y action");
+        }
+
+        let config = Config::parse(
+            "name: T\ntarget:\n  url: https://example.com\nactions:\n  - assert_a11y: { severity: severe }\n",
+        );
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn test_parse_click_all_and_fill_all() {
+        let yaml = r#"
+name: "Bulk"
+target:
+  url: "https://example.com"
+actions:
+  - click_all:
+      selector: "input[type=checkbox]"
+  - click_all:
+      text: "Dismiss"
+      delay_ms: 250
+      max: 5
+  - fill_all:
+      selector: "input.qty"
+      value: "1"
+"#;
+        let config = Config::parse(yaml).unwrap();
+        if let Action::ClickAll(a) = &config.actions[0] {
+            assert_eq!(a.target.selector.as_deref(), Some("input[type=checkbox]"));
+            assert_eq!(a.delay_ms, 100);
+            assert_eq!(a.max, None);
+        } else {
+            panic!("Expected ClickAll action");
+        }
+        if let Action::ClickAll(a) = &config.actions[1] {
+            assert_eq!(a.target.text.as_deref(), Some("Dismiss"));
+            assert_eq!(a.delay_ms, 250);
+            assert_eq!(a.max, Some(5));
+        } else {
+            panic!("Expected ClickAll action");
+        }
+        if let Action::FillAll(a) = &config.actions[2] {
+            assert_eq!(a.value, "1");
+            assert!(!a.human);
+        } else {
+            panic!("Expected FillAll action");
+        }
+    }
+
+    #[test]
+    fn test_parse_menu_click() {
+        let yaml = r#"
+name: "Nav"
+target:
+  url: "https://example.com"
+actions:
+  - menu_click:
+      parent: { text: "Products" }
+      item: { selector: "a[href='/pricing']" }
+"#;
+        let config = Config::parse(yaml).unwrap();
+        if let Action::MenuClick(a) = &config.actions[0] {
+            assert_eq!(a.parent.text.as_deref(), Some("Products"));
+            assert_eq!(a.item.selector.as_deref(), Some("a[href='/pricing']"));
+            assert_eq!(a.timeout_ms, 5_000);
+        } else {
+            panic!("Expected MenuClick action");
+        }
+        assert_eq!(
+            config.actions[0].target().unwrap(),
+            "text 'Products' > selector 'a[href='/pricing']'"
+        );
+    }
+
+    #[test]
+    fn test_parse_wait_for_text_gone_and_element_stable() {
+        let yaml = r#"
+name: "Modal"
+target:
+  url: "https://example.com"
+actions:
+  - wait_for_text_gone:
+      text: "Loading…"
+  - wait_for_element_stable:
+      selector: ".modal"
+      timeout_ms: 2000
+"#;
+        let config = Config::parse(yaml).unwrap();
+        if let Action::WaitForTextGone(a) = &config.actions[0] {
+            assert_eq!(a.text, "Loading…");
+            assert_eq!(a.timeout_ms, 10_000);
+        } else {
+            panic!("Expected WaitForTextGone action");
+        }
+        if let Action::WaitForElementStable(a) = &config.actions[1] {
+            assert_eq!(a.target.selector.as_deref(), Some(".modal"));
+            assert_eq!(a.frames, 3);
+            assert_eq!(a.timeout_ms, 2000);
+        } else {
+            panic!("Expected WaitForElementStable action");
+        }
+        assert_eq!(config.actions[1].target().unwrap(), "selector '.modal'");
+    }
+
+    #[test]
+    fn test_parse_imap_oauth2() {
+        let yaml = r#"
+name: "OTP"
+target:
+  url: "https://example.com"
+actions:
+  - wait_for_email:
+      imap:
+        host: "outlook.office365.com"
+        username: "me@example.com"
+        oauth2:
+          provider: microsoft
+          client_id: "app"
+          refresh_token: "${refresh}"
+      filter:
+        to: "me+signup@example.com"
+        body_contains: "verification"
+        headers:
+          X-Campaign: "otp"
+        larger_than: 500
+        include_junk: true
+      extract:
+        code: {}
+      cleanup:
+        move_to: "Processed"
+"#;
+        let config =
+            Config::parse_with_params(yaml, &Params::new().set("refresh", "r-123")).unwrap();
+        if let Action::WaitForEmail(a) = &config.actions[0] {
+            assert_eq!(a.imap.password, "");
+            let oauth2 = a.imap.oauth2.as_ref().unwrap();
+            assert_eq!(oauth2.provider, config::actions::OAuth2Provider::Microsoft);
+            assert_eq!(oauth2.refresh_token.as_deref(), Some("r-123"));
+            assert!(oauth2.access_token.is_none());
+            assert_eq!(a.filter.to.as_deref(), Some("me+signup@example.com"));
+            assert_eq!(a.filter.headers["X-Campaign"], "otp");
+            assert_eq!(a.filter.larger_than, Some(500));
+            assert!(a.filter.include_junk && a.filter.mailboxes.is_empty());
+            assert!(a.filter.unseen_only);
+            assert!(a.extract.code.as_ref().unwrap().regex.is_none());
+            let cleanup = a.cleanup.as_ref().unwrap();
+            assert_eq!(cleanup.move_to.as_deref(), Some("Processed"));
+            assert!(!cleanup.delete);
+        } else {
+            panic!("Expected WaitForEmail action");
+        }
+    }
+
+    #[test]
+    fn test_parse_pop3_email() {
+        let yaml = r#"
+name: "POP3"
+target:
+  url: "https://example.com"
+actions:
+  - wait_for_email:
+      imap:
+        protocol: pop3
+        host: "pop.example.com"
+        port: 995
+        username: "me@example.com"
+        password: "secret"
+      filter:
+        from: "no-reply@example.com"
+        since_minutes: 10
+      extract:
+        code: {}
+      cleanup:
+        delete: true
+"#;
+        let config = Config::parse(yaml).unwrap();
+        if let Action::WaitForEmail(a) = &config.actions[0] {
+            assert_eq!(a.imap.protocol, config::actions::MailProtocol::Pop3);
+            assert_eq!(a.imap.port, 995);
+            assert!(a.cleanup.as_ref().unwrap().delete);
+        } else {
+            panic!("Expected WaitForEmail action");
+        }
+    }
+
+    #[test]
+    fn test_parse_gmail_email() {
+        let yaml = r#"
+name: "Gmail API"
+target:
+  url: "https://example.com"
+actions:
+  - wait_for_email:
+      imap:
+        protocol: gmail
+        username: "me@example.com"
+        oauth2:
+          client_id: "id"
+          refresh_token: "refresh"
+      filter:
+        subject_contains: "Verify"
+        include_junk: true
+      extract:
+        link: {}
+      cleanup:
+        move_to: "Processed"
+"#;
+        let config = Config::parse(yaml).unwrap();
+        if let Action::WaitForEmail(a) = &config.actions[0] {
+            assert_eq!(a.imap.protocol, config::actions::MailProtocol::Gmail);
+            assert!(a.imap.host.is_empty());
+            assert!(a.imap.oauth2.is_some());
+        } else {
+            panic!("Expected WaitForEmail action");
+        }
+    }
+
+    #[test]
+    fn test_parse_auto_dismiss() {
+        let yaml = r##"
+name: "Test"
+browser:
+  auto_dismiss: true
+  consent_policy: accept
+target:
+  url: "https://example.com"
+"##;
+        let config = Config::parse(yaml).unwrap();
+        assert!(config.browser.auto_dismiss);
+        assert_eq!(config.browser.consent_policy, ConsentPolicy::Accept);
+
+        let config = Config::parse("name: T\ntarget:\n  url: https://example.com\n").unwrap();
+        assert!(!config.browser.auto_dismiss);
+        assert!(!config.browser.debug_overlay);
+        assert_eq!(config.browser.consent_policy, ConsentPolicy::Reject);
+    }
+
+    #[test]
+    fn test_parse_popups() {
+        let yaml = r#"
+name: "Test"
+browser:
+  popups: same_tab
+target:
+  url: "https://example.com"
+"#;
+        let config = Config::parse(yaml).unwrap();
+        assert_eq!(config.browser.popups, PopupPolicy::SameTab);
+
+        let config = Config::parse("name: T\ntarget:\n  url: https://example.com\n").unwrap();
+        assert_eq!(config.browser.popups, PopupPolicy::Background);
+    }
+
+    #[test]
+    fn test_parse_crawl() {
+        let yaml = r#"
+name: "Test"
+target:
+  url: "https://example.com"
+crawl:
+  max_depth: 1
+  exclude: ["/logout"]
+  delay_ms: 250
+"#;
+        let config = Config::parse(yaml).unwrap();
+        let crawl = config.crawl.unwrap();
+        assert_eq!(crawl.max_depth, 1);
+        assert_eq!(crawl.max_pages, 100);
+        assert!(crawl.same_origin);
+        assert_eq!(crawl.exclude, vec!["/logout"]);
+        assert_eq!(crawl.delay_ms, 250);
+
+        let config = Config::parse("name: T\ntarget:\n  url: https://example.com\n").unwrap();
+        assert!(config.crawl.is_none());
+    }
 }
@@ -0,0 +1,260 @@
+//! Link audit — requests anchors and reports the targets that answer 4xx/5xx
+//! or don't answer at all. Shared by the `audit_links` action and
+//! eoka-agent's `Session::audit_links`.
+
+use crate::frontier::origin_of;
+use eoka::{Page, Result};
+use reqwest::header;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Requests in flight at once.
+const CONCURRENCY: usize = 8;
+
+/// `(doc, selector)` → HTTP(S) anchors with their text, fragments stripped.
+pub const ANCHORS_JS: &str = r#"((doc, selector) => Array.from(doc.querySelectorAll(selector))
+    .filter(a => a.href)
+    .map(a => ({ href: String(a.href).split('#')[0], text: (a.innerText || a.title || '').trim().slice(0, 80) }))
+    .filter(a => /^https?:/.test(a.href)))"#;
+
+/// An anchor found by [`ANCHORS_JS`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct Anchor {
+    pub href: String,
+    pub text: String,
+}
+
+/// Which anchors to check.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LinkScope {
+    /// Links to the page's own origin (default)
+    #[default]
+    SameOrigin,
+    /// Every HTTP(S) link, including external ones
+    All,
+}
+
+/// The result of requesting one link.
+#[derive(Debug, Clone)]
+pub struct LinkCheck {
+    pub url: String,
+    /// Text of the first anchor pointing at the URL
+    pub text: String,
+    /// Final status after redirects; `None` if the request failed
+    pub status: Option<u16>,
+    /// Why the request failed (DNS, TLS, timeout)
+    pub error: Option<String>,
+}
+
+impl LinkCheck {
+    /// 4xx/5xx, or no response.
+    pub fn is_broken(&self) -> bool {
+        self.status.is_none_or(|s| s >= 400)
+    }
+}
+
+impl fmt::Display for LinkCheck {
+    /// `404 https://example.com/old "Pricing"`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.status, &self.error) {
+            (Some(s), _) => write!(f, "{} {}", s, self.url)?,
+            (None, Some(e)) => write!(f, "{} {}", e, self.url)?,
+            (None, None) => write!(f, "no response {}", self.url)?,
+        }
+        if !self.text.is_empty() {
+            write!(f, " \"{}\"", self.text)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every link checked, in page order.
+#[derive(Debug, Clone, Default)]
+pub struct LinkAudit {
+    pub links: Vec<LinkCheck>,
+}
+
+impl LinkAudit {
+    /// Links that answered 4xx/5xx or didn't answer.
+    pub fn broken(&self) -> impl Iterator<Item = &LinkCheck> {
+        self.links.iter().filter(|l| l.is_broken())
+    }
+}
+
+impl fmt::Display for LinkAudit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let broken: Vec<&LinkCheck> = self.broken().collect();
+        write!(
+            f,
+            "checked {} links, {} broken",
+            self.links.len(),
+            broken.len()
+        )?;
+        for link in broken {
+            write!(f, "\n  {}", link)?;
+        }
+        Ok(())
+    }
+}
+
+/// Request every anchor in the top-level document within `scope`.
+pub async fn audit_links(page: &Page, scope: LinkScope) -> Result<LinkAudit> {
+    let js = format!("JSON.stringify({}(document, 'a[href]'))", ANCHORS_JS);
+    let json: String = page.evaluate(&js).await?;
+    let anchors: Vec<Anchor> = serde_json::from_str(&json)
+        .map_err(|e| eoka::Error::CdpSimple(format!("Failed to parse anchors: {}", e)))?;
+    check_anchors(page, anchors, scope).await
+}
+
+/// Request each anchor within `scope` once per URL. Uses HEAD, falling back
+/// to GET for servers that reject it. Same-origin requests carry the page's
+/// cookies and user agent, so logged-in pages audit correctly.
+pub async fn check_anchors(
+    page: &Page,
+    anchors: Vec<Anchor>,
+    scope: LinkScope,
+) -> Result<LinkAudit> {
+    let origin = origin_of(&page.url().await?, false);
+
+    let mut seen = HashSet::new();
+    let anchors: Vec<Anchor> = anchors
+        .into_iter()
+        .filter(|a| scope == LinkScope::All || origin_of(&a.href, false) == origin)
+        .filter(|a| seen.insert(a.href.clone()))
+        .collect();
+    tracing::debug!("audit_links: checking {} links", anchors.len());
+
+    let cookie = page
+        .cookies()
+        .await?
+        .iter()
+        .map(|c| format!("{}={}", c.name, c.value))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let user_agent: String = page.evaluate("navigator.userAgent").await?;
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .user_agent(user_agent)
+        .build()
+        .map_err(|e| eoka::Error::CdpSimple(format!("HTTP client: {}", e)))?;
+
+    let permits = Arc::new(Semaphore::new(CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for (i, anchor) in anchors.iter().enumerate() {
+        let client = client.clone();
+        let url = anchor.href.clone();
+        let cookie =
+            (origin_of(&url, false) == origin && !cookie.is_empty()).then(|| cookie.clone());
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            (i, check(&client, &url, cookie.as_deref()).await)
+        });
+    }
+
+    let mut results = vec![None; anchors.len()];
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((i, result)) = joined {
+            results[i] = Some(result);
+        }
+    }
+    let links = anchors
+        .into_iter()
+        .zip(results)
+        .map(|(anchor, result)| {
+            let (status, error) = match result {
+                Some(Ok(status)) => (Some(status), None),
+                Some(Err(e)) => (None, Some(e)),
+                None => (None, Some("request task failed".into())),
+            };
+            LinkCheck {
+                url: anchor.href,
+                text: anchor.text,
+                status,
+                error,
+            }
+        })
+        .collect();
+    Ok(LinkAudit { links })
+}
+
+/// Final status for `url`: HEAD, then GET if HEAD is refused.
+async fn check(
+    client: &reqwest::Client,
+    url: &str,
+    cookie: Option<&str>,
+) -> std::result::Result<u16, String> {
+    let send = |method: reqwest::Method| {
+        let mut req = client.request(method, url);
+        if let Some(cookie) = cookie {
+            req = req.header(header::COOKIE, cookie);
+        }
+        req.send()
+    };
+    let head = send(reqwest::Method::HEAD).await;
+    match head {
+        Ok(resp) if !head_refused(resp.status().as_u16()) => Ok(resp.status().as_u16()),
+        _ => send(reqwest::Method::GET)
+            .await
+            .map(|resp| resp.status().as_u16())
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Statuses some servers return for HEAD while GET works.
+fn head_refused(status: u16) -> bool {
+    matches!(status, 403 | 404 | 405 | 501)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(url: &str, status: Option<u16>) -> LinkCheck {
+        LinkCheck {
+            url: url.into(),
+            text: "Docs".into(),
+            status,
+            error: status.is_none().then(|| "dns error".into()),
+        }
+    }
+
+    #[test]
+    fn broken_links() {
+        let audit = LinkAudit {
+            links: vec![
+                link("https://a.example/ok", Some(200)),
+                link("https://a.example/gone", Some(404)),
+                link("https://a.example/down", Some(503)),
+                link("https://nowhere.example/", None),
+            ],
+        };
+        let broken: Vec<&str> = audit.broken().map(|l| l.url.as_str()).collect();
+        assert_eq!(
+            broken,
+            [
+                "https://a.example/gone",
+                "https://a.example/down",
+                "https://nowhere.example/"
+            ]
+        );
+        assert_eq!(
+            audit.to_string().lines().next(),
+            Some("checked 4 links, 3 broken")
+        );
+        assert!(audit
+            .to_string()
+            .contains("\n  dns error https://nowhere.example/ \"Docs\""));
+        assert_eq!(
+            audit.links[1].to_string(),
+            "404 https://a.example/gone \"Docs\""
+        );
+        assert!(head_refused(405));
+        assert!(!head_refused(500));
+    }
+}
@@ -0,0 +1,604 @@
+//! Dry-run planning — expands includes and params into a flat, printable action list.
+
+use crate::config::actions::{EmailAction, MailProtocol, Target, A11Y_RULES};
+use crate::config::include::IncludeChain;
+use crate::config::{Action, Config, ParamDef, Params};
+use crate::target::LivePattern;
+use crate::Result;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Shown in place of secret values.
+const MASK: &str = "••••••";
+
+/// Param names treated as secret even without `secret: true`.
+const SECRET_HINTS: &[&str] = &["password", "passwd", "secret", "token", "api_key", "apikey"];
+
+/// A fully expanded run plan.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    /// Config name.
+    pub name: String,
+    /// Start URL.
+    pub target_url: String,
+    /// Every action that would run, in order, with nested blocks indented.
+    pub steps: Vec<PlanStep>,
+    /// Problems found without a browser (bad selectors, missing targets).
+    pub warnings: Vec<String>,
+}
+
+/// One planned action.
+#[derive(Debug, Clone)]
+pub struct PlanStep {
+    /// Hierarchical step number, e.g. `3.2`.
+    pub number: String,
+    /// Nesting depth (0 = top level).
+    pub depth: usize,
+    /// Action summary with params substituted and secrets masked.
+    pub description: String,
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Plan: {}", self.name)?;
+        writeln!(f, "  Target: {}", self.target_url)?;
+        for step in &self.steps {
+            writeln!(
+                f,
+                "  {}{}. {}",
+                "  ".repeat(step.depth),
+                step.number,
+                step.description
+            )?;
+        }
+        if !self.warnings.is_empty() {
+            writeln!(f, "  Warnings:")?;
+            for w in &self.warnings {
+                writeln!(f, "    - {}", w)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Expand this config into a [`Plan`] without launching a browser.
+    ///
+    /// `base_path` resolves includes; `params` are the values the config was
+    /// loaded with, used to mask secret parameters in the output.
+    pub fn plan(&self, base_path: impl AsRef<Path>, params: &Params) -> Result<Plan> {
+        let mut planner = Planner {
+            steps: Vec::new(),
+            warnings: Vec::new(),
+            secrets: Vec::new(),
+        };
+        planner.collect_secrets(&self.params, params);
+        planner.walk(
+            &self.actions,
+            base_path.as_ref(),
+            "",
+            0,
+            &IncludeChain::default(),
+        )?;
+
+        Ok(Plan {
+            name: self.name.clone(),
+            target_url: self.target.url.clone(),
+            steps: planner.steps,
+            warnings: planner.warnings,
+        })
+    }
+}
+
+struct Planner {
+    steps: Vec<PlanStep>,
+    warnings: Vec<String>,
+    secrets: Vec<String>,
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SECRET_HINTS.iter().any(|h| name.contains(h))
+}
+
+impl Planner {
+    fn collect_secrets(&mut self, defs: &HashMap<String, ParamDef>, params: &Params) {
+        for (name, def) in defs {
+            if !(def.secret || is_secret_name(name)) {
+                continue;
+            }
+            let value = params.get(name).or(def.default.as_deref());
+            if let Some(v) = value.filter(|v| !v.is_empty()) {
+                self.secrets.push(v.to_string());
+            }
+        }
+        for (name, value) in params.iter() {
+            if is_secret_name(name) && !value.is_empty() {
+                self.secrets.push(value.to_string());
+            }
+        }
+        // Mask longer values first so a secret containing another stays hidden.
+        self.secrets
+            .sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        self.secrets.dedup();
+    }
+
+    fn mask(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for secret in &self.secrets {
+            out = out.replace(secret.as_str(), MASK);
+        }
+        out
+    }
+
+    fn push(&mut self, number: &str, depth: usize, description: String) {
+        let description = self.mask(&description);
+        self.steps.push(PlanStep {
+            number: number.to_string(),
+            depth,
+            description,
+        });
+    }
+
+    fn warn(&mut self, number: &str, message: String) {
+        let message = self.mask(&message);
+        self.warnings.push(format!("step {}: {}", number, message));
+    }
+
+    fn check_target(&mut self, number: &str, target: &Target) {
+        match target.pattern() {
+            Some(LivePattern::Css(sel)) => self.check_selector(number, &sel),
+            Some(pattern) if pattern.as_js_args().1.trim().is_empty() => {
+                self.warn(number, format!("empty {} target", pattern.as_js_args().0))
+            }
+            Some(_) => {}
+            None => self.warn(
+                number,
+                "target needs 'selector', 'text', 'placeholder', 'label', 'role' or 'id'".into(),
+            ),
+        }
+    }
+
+    fn check_selector(&mut self, number: &str, selector: &str) {
+        if let Some(problem) = selector_problem(selector) {
+            self.warn(number, format!("selector '{}': {}", selector, problem));
+        }
+    }
+
+    fn walk(
+        &mut self,
+        actions: &[Action],
+        base: &Path,
+        prefix: &str,
+        depth: usize,
+        includes: &IncludeChain,
+    ) -> Result<()> {
+        for (i, action) in actions.iter().enumerate() {
+            let number = if prefix.is_empty() {
+                (i + 1).to_string()
+            } else {
+                format!("{}.{}", prefix, i + 1)
+            };
+            self.push(&number, depth, describe(action));
+            self.check(&number, action);
+
+            match action {
+                Action::Repeat(a) => self.walk(&a.actions, base, &number, depth + 1, includes)?,
+                Action::WithinFrame(a) => {
+                    self.walk(&a.actions, base, &number, depth + 1, includes)?
+                }
+                Action::IfTextExists(a) => self.branches(
+                    &a.then_actions,
+                    &a.else_actions,
+                    base,
+                    &number,
+                    depth,
+                    includes,
+                )?,
+                Action::IfSelectorExists(a) => self.branches(
+                    &a.then_actions,
+                    &a.else_actions,
+                    base,
+                    &number,
+                    depth,
+                    includes,
+                )?,
+                Action::Include(a) => {
+                    let path = resolve(base, &a.path);
+                    let chain = includes.enter(&path)?;
+                    let included = a.load(&path)?;
+                    self.collect_secrets(&included.params, &a.scoped_params());
+                    let child_base = path.parent().unwrap_or(Path::new("."));
+                    self.walk(&included.actions, child_base, &number, depth + 1, &chain)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn branches(
+        &mut self,
+        then_actions: &[Action],
+        else_actions: &[Action],
+        base: &Path,
+        number: &str,
+        depth: usize,
+        includes: &IncludeChain,
+    ) -> Result<()> {
+        let then_number = format!("{}.then", number);
+        self.walk(then_actions, base, &then_number, depth + 1, includes)?;
+        if !else_actions.is_empty() {
+            let else_number = format!("{}.else", number);
+            self.walk(else_actions, base, &else_number, depth + 1, includes)?;
+        }
+        Ok(())
+    }
+
+    /// Static checks for a single action.
+    fn check(&mut self, number: &str, action: &Action) {
+        match action {
+            Action::Click(a) => self.check_target(number, &a.target),
+            Action::Fill(a) => self.check_target(number, &a.target),
+            Action::ClickAll(a) => self.check_target(number, &a.target),
+            Action::MenuClick(a) => {
+                self.check_target(number, &a.parent);
+                self.check_target(number, &a.item);
+            }
+            Action::FillAll(a) => self.check_target(number, &a.target),
+            Action::Type(a) => self.check_target(number, &a.target),
+            Action::Clear(a) => self.check_target(number, &a.target),
+            Action::Select(a) => self.check_target(number, &a.target),
+            Action::Upload(a) => self.check_target(number, &a.target),
+            Action::ScrollUntil(a) => self.check_target(number, &a.target),
+            Action::WaitForElementStable(a) => self.check_target(number, &a.target),
+            Action::TryClick(a) | Action::Hover(a) | Action::ScrollTo(a) => {
+                self.check_target(number, &a.target)
+            }
+            Action::TryClickAny(a) => {
+                if a.selectors.is_none() && a.texts.is_none() {
+                    self.warn(number, "try_click_any needs 'selectors' or 'texts'".into());
+                }
+                for sel in a.selectors.iter().flatten() {
+                    self.check_selector(number, sel);
+                }
+            }
+            Action::WaitFor(a) | Action::WaitForVisible(a) | Action::WaitForHidden(a) => {
+                self.check_selector(number, &a.selector)
+            }
+            Action::IfSelectorExists(a) => self.check_selector(number, &a.selector),
+            Action::ScrollInfinite(a) => {
+                if let Some(ref sel) = a.item_selector {
+                    self.check_selector(number, sel);
+                }
+            }
+            Action::WithinFrame(a) => {
+                if let Some(ref sel) = a.frame.selector {
+                    self.check_selector(number, sel);
+                }
+                if a.frame.selector.is_none()
+                    && a.frame.name.is_none()
+                    && a.frame.url_contains.is_none()
+                {
+                    self.warn(
+                        number,
+                        "within_frame needs 'selector', 'name', or 'url_contains'".into(),
+                    );
+                }
+            }
+            Action::Autofill(a) => {
+                if let Some(ref form) = a.form {
+                    self.check_selector(number, form);
+                }
+                if !(1..=12).contains(&a.profile.card_exp_month) {
+                    self.warn(number, "card_exp_month must be 1-12".into());
+                }
+            }
+            Action::AuditLinks(a) => {
+                if let Some(ref sel) = a.selector {
+                    self.check_selector(number, sel);
+                }
+                for pattern in &a.ignore {
+                    if let Err(e) = regex::Regex::new(pattern) {
+                        self.warn(number, format!("invalid ignore regex: {}", e));
+                    }
+                }
+            }
+            Action::AssertA11y(a) => {
+                for rule in &a.ignore {
+                    if !A11Y_RULES.contains(&rule.as_str()) {
+                        self.warn(number, format!("unknown a11y rule '{}'", rule));
+                    }
+                }
+            }
+            Action::PressKey(a) if a.sequence().is_empty() => {
+                self.warn(number, "press_key needs 'key' or 'keys'".into());
+            }
+            Action::WaitForEmail(a) => {
+                if let Some(ref cleanup) = a.cleanup {
+                    if cleanup.delete && cleanup.move_to.is_some() {
+                        self.warn(
+                            number,
+                            "cleanup sets both delete and move_to; the email is moved".into(),
+                        );
+                    }
+                }
+                if a.imap.protocol == MailProtocol::Pop3 {
+                    if !a.filter.mailboxes.is_empty() || a.filter.include_junk {
+                        self.warn(
+                            number,
+                            "POP3 only reads the inbox; mailboxes and include_junk are ignored"
+                                .into(),
+                        );
+                    }
+                    if a.cleanup.as_ref().is_some_and(|c| c.move_to.is_some()) {
+                        self.warn(number, "POP3 can't move emails; use cleanup.delete".into());
+                    }
+                }
+                if a.imap.protocol == MailProtocol::Gmail {
+                    if a.imap.oauth2.is_none() {
+                        self.warn(number, "the Gmail API needs oauth2 credentials".into());
+                    }
+                } else if a.imap.host.is_empty() {
+                    self.warn(number, "imap needs 'host'".into());
+                }
+                if let Some(ref oauth2) = a.imap.oauth2 {
+                    if oauth2.access_token.is_none()
+                        && (oauth2.client_id.is_none() || oauth2.refresh_token.is_none())
+                    {
+                        self.warn(
+                            number,
+                            "oauth2 needs access_token, or client_id and refresh_token".into(),
+                        );
+                    }
+                }
+                if let Some(regex) = a.extract.code.as_ref().and_then(|c| c.regex.as_ref()) {
+                    if let Err(e) = regex::Regex::new(regex) {
+                        self.warn(number, format!("invalid code regex: {}", e));
+                    }
+                }
+                if let Some(EmailAction::Fill(ref fill)) = a.action {
+                    self.check_selector(number, &fill.selector);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn resolve(base: &Path, path: &str) -> PathBuf {
+    let p = Path::new(path);
+    if p.is_absolute() {
+        p.to_path_buf()
+    } else {
+        base.join(p)
+    }
+}
+
+/// Cheap syntactic check for a CSS selector: non-empty, balanced brackets,
+/// parentheses, and quotes.
+fn selector_problem(selector: &str) -> Option<&'static str> {
+    if selector.trim().is_empty() {
+        return Some("empty selector");
+    }
+    let mut stack = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut chars = selector.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            chars.next();
+            continue;
+        }
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '[' | '(' => stack.push(c),
+            ']' if stack.pop() != Some('[') => return Some("unbalanced brackets"),
+            ')' if stack.pop() != Some('(') => return Some("unbalanced parentheses"),
+            _ => {}
+        }
+    }
+    if quote.is_some() {
+        return Some("unterminated string");
+    }
+    if !stack.is_empty() {
+        return Some("unclosed bracket or parenthesis");
+    }
+    None
+}
+
+/// One-line summary of an action.
+fn describe(action: &Action) -> String {
+    let name = action.name();
+    match action {
+        Action::Goto(a) => format!("{} {}", name, a.url),
+        Action::Wait(a) => format!("{} {}ms", name, a.ms),
+        Action::WaitFor(a) | Action::WaitForVisible(a) | Action::WaitForHidden(a) => {
+            format!("{} '{}' ({}ms)", name, a.selector, a.timeout_ms)
+        }
+        Action::WaitForText(a) | Action::WaitForTextGone(a) => {
+            format!("{} \"{}\" ({}ms)", name, a.text, a.timeout_ms)
+        }
+        Action::WaitForElementStable(a) => format!(
+            "{} {} ({} frames, {}ms)",
+            name, a.target, a.frames, a.timeout_ms
+        ),
+        Action::WaitForUrl(a) => format!("{} contains '{}' ({}ms)", name, a.contains, a.timeout_ms),
+        Action::WaitForEmail(a) => format!(
+            "{} {}@{} ({}ms)",
+            name, a.imap.username, a.imap.host, a.timeout_ms
+        ),
+        Action::Click(a) => format!("{} {}", name, a.target),
+        Action::TryClick(a) | Action::Hover(a) | Action::ScrollTo(a) => {
+            format!("{} {}", name, a.target)
+        }
+        Action::TryClickAny(a) => {
+            let mut options: Vec<String> = Vec::new();
+            options.extend(a.selectors.iter().flatten().map(|s| format!("'{}'", s)));
+            options.extend(a.texts.iter().flatten().map(|t| format!("\"{}\"", t)));
+            format!("{} [{}]", name, options.join(", "))
+        }
+        Action::Fill(a) => format!("{} {} = \"{}\"", name, a.target, a.value),
+        Action::ClickAll(a) => match a.max {
+            Some(max) => format!("{} {} (max {})", name, a.target, max),
+            None => format!("{} {}", name, a.target),
+        },
+        Action::MenuClick(a) => format!("{} {} > {}", name, a.parent, a.item),
+        Action::FillAll(a) => format!("{} {} = \"{}\"", name, a.target, a.value),
+        Action::Type(a) => format!("{} {} += \"{}\"", name, a.target, a.value),
+        Action::Clear(a) => format!("{} {}", name, a.target),
+        Action::Select(a) => format!("{} {} = \"{}\"", name, a.target, a.value),
+        Action::Autofill(a) => format!(
+            "{} {} ({} {}, {:?} card)",
+            name,
+            a.form.as_deref().unwrap_or("first form"),
+            a.profile.first_name,
+            a.profile.last_name,
+            a.profile.card
+        ),
+        Action::PressKey(a) => format!("{} {}", name, a.sequence().join(", ")),
+        Action::SetCookie(a) => format!("{} {}=\"{}\"", name, a.name, a.value),
+        Action::DeleteCookie(a) => format!("{} {}", name, a.name),
+        Action::Execute(a) => {
+            let js: String = a.js.chars().take(60).collect();
+            let ellipsis = if a.js.chars().count() > 60 { "…" } else { "" };
+            format!("{} `{}{}`", name, js.replace('\n', " "), ellipsis)
+        }
+        Action::Scroll(a) => format!("{} {:?} x{}", name, a.direction, a.amount),
+        Action::ScrollUntil(a) => format!("{} {} (max {})", name, a.target, a.max_scrolls),
+        Action::Screenshot(a) => format!("{} {}", name, a.path),
+        Action::AssertScreenshot(a) => match a.selector {
+            Some(ref selector) => format!("{} {} (selector '{}')", name, a.baseline, selector),
+            None => format!("{} {}", name, a.baseline),
+        },
+        Action::AssertSnapshot(a) => match a.selector {
+            Some(ref selector) => format!("{} {} (selector '{}')", name, a.golden, selector),
+            None => format!("{} {}", name, a.golden),
+        },
+        Action::AuditLinks(a) => {
+            let scope = if a.same_origin {
+                "same origin"
+            } else {
+                "all origins"
+            };
+            match a.selector {
+                Some(ref selector) => format!("{} '{}' ({})", name, selector, scope),
+                None => format!("{} ({})", name, scope),
+            }
+        }
+        Action::AssertA11y(a) => format!("{} (>= {})", name, a.severity),
+        Action::Log(a) => format!("{} \"{}\"", name, a.message),
+        Action::Pause(a) => match a.message {
+            Some(ref message) => format!("{} \"{}\"", name, message),
+            None => name.to_string(),
+        },
+        Action::AssertText(a) => format!("{} \"{}\"", name, a.text),
+        Action::AssertUrl(a) => format!("{} contains '{}'", name, a.contains),
+        Action::IfTextExists(a) => format!("{} \"{}\"", name, a.text),
+        Action::IfSelectorExists(a) => format!("{} '{}'", name, a.selector),
+        Action::Repeat(a) => format!("{} x{}", name, a.times),
+        Action::Include(a) => format!("{} {}", name, a.path),
+        Action::AgentTask(a) => format!("{} \"{}\" (max {} turns)", name, a.goal, a.max_turns),
+        Action::WithinFrame(a) => format!("{} {}", name, a.frame),
+        Action::Upload(a) => format!("{} {} <- {}", name, a.target, a.file),
+        Action::WaitForDownload(a) => match a.save_as {
+            Some(ref dest) => format!("{} -> {}", name, dest),
+            None => name.to_string(),
+        },
+        _ => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selector_problems() {
+        assert_eq!(selector_problem("#email"), None);
+        assert_eq!(selector_problem("input[name=\"q]\"]"), None);
+        assert_eq!(selector_problem("li:nth-child(2) > a"), None);
+        assert_eq!(selector_problem("  "), Some("empty selector"));
+        assert_eq!(
+            selector_problem("input[name=q"),
+            Some("unclosed bracket or parenthesis")
+        );
+        assert_eq!(selector_problem("a)"), Some("unbalanced parentheses"));
+        assert_eq!(selector_problem("a[href='x]"), Some("unterminated string"));
+    }
+
+    #[test]
+    fn test_plan_masks_secrets_and_nests() {
+        let yaml = r##"
+name: "Login"
+params:
+  email:
+    required: true
+  password:
+    required: true
+  pin:
+    default: "4321"
+    secret: true
+target:
+  url: "https://example.com/login"
+actions:
+  - fill:
+      selector: "#email"
+      value: "${email}"
+  - fill:
+      selector: "#password"
+      value: "${password}"
+  - repeat:
+      times: 2
+      actions:
+        - fill:
+            selector: "#pin"
+            value: "${pin}"
+        - click:
+            selector: "button[type=submit"
+"##;
+        let params = Params::new()
+            .set("email", "me@example.com")
+            .set("password", "hunter2");
+        let config = Config::parse_with_params(yaml, &params).unwrap();
+        let plan = config.plan(".", &params).unwrap();
+
+        let lines: Vec<&str> = plan.steps.iter().map(|s| s.description.as_str()).collect();
+        assert_eq!(lines[0], "fill selector '#email' = \"me@example.com\"");
+        assert_eq!(
+            lines[1],
+            format!("fill selector '#password' = \"{}\"", MASK)
+        );
+        assert_eq!(lines[2], "repeat x2");
+        assert_eq!(lines[3], format!("fill selector '#pin' = \"{}\"", MASK));
+        assert_eq!(plan.steps[4].number, "3.2");
+        assert_eq!(plan.steps[4].depth, 1);
+        assert_eq!(plan.warnings.len(), 1);
+        assert!(plan.warnings[0].starts_with("step 3.2:"));
+
+        let printed = plan.to_string();
+        assert!(!printed.contains("hunter2"));
+        assert!(!printed.contains("4321"));
+    }
+
+    #[test]
+    fn test_plan_missing_include() {
+        let yaml = r#"
+name: "Test"
+target:
+  url: "https://example.com"
+actions:
+  - include:
+      path: "does/not/exist.yaml"
+"#;
+        let config = Config::parse(yaml).unwrap();
+        let err = config.plan(".", &Params::new()).unwrap_err();
+        assert!(err.to_string().contains("does/not/exist.yaml"));
+    }
+}
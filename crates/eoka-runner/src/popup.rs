@@ -0,0 +1,129 @@
+//! Popups — keep windows opened by `window.open` and `target=_blank` from
+//! stranding a run in a tab it can't see. Shared by `browser.popups` and
+//! eoka-agent's `Session::set_popup_policy`.
+
+use eoka::{Browser, Page, Result, TabInfo};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Rewrites `window.open` and `target`ed links and forms to use this tab.
+/// Runs in every new document; idempotent.
+pub const SAME_TAB_JS: &str = r#"
+(() => {
+  if (window.__eokaSameTab) return;
+  window.__eokaSameTab = true;
+  window.open = function (url) {
+    if (url) location.href = new URL(String(url), location.href).href;
+    return window;
+  };
+  const retarget = e => {
+    const el = e.target instanceof Element ? e.target.closest('a[target], area[target], form[target]') : null;
+    if (el && el.target && el.target !== '_self') el.target = '_self';
+  };
+  document.addEventListener('click', retarget, true);
+  document.addEventListener('submit', retarget, true);
+})()
+"#;
+
+/// How long a new window gets to leave `about:blank` before it's followed.
+const FOLLOW_WAIT_MS: u64 = 2000;
+
+/// Keep new windows in `page`, in this and every later document.
+pub async fn same_tab(page: &Page) -> Result<()> {
+    let _: Value = page
+        .session()
+        .send(
+            "Page.addScriptToEvaluateOnNewDocument",
+            &json!({ "source": SAME_TAB_JS }),
+        )
+        .await?;
+    page.execute(SAME_TAB_JS).await
+}
+
+/// Ids of every open tab.
+pub async fn tab_ids(browser: &Browser) -> Result<HashSet<String>> {
+    Ok(browser.tabs().await?.into_iter().map(|t| t.id).collect())
+}
+
+/// Tabs open now that aren't in `known` or `page` itself.
+pub async fn new_tabs(
+    browser: &Browser,
+    page: &Page,
+    known: &HashSet<String>,
+) -> Result<Vec<TabInfo>> {
+    Ok(browser
+        .tabs()
+        .await?
+        .into_iter()
+        .filter(|t| t.id != page.target_id() && !known.contains(&t.id))
+        .collect())
+}
+
+/// Tabs that aren't popups, and the pages followed popups were opened from.
+#[derive(Default)]
+pub struct PopupTracker {
+    known: HashSet<String>,
+    /// Pages switched away from by [`PopupTracker::follow`], innermost last.
+    openers: Vec<Page>,
+}
+
+impl PopupTracker {
+    /// Treat every tab open now as not a popup.
+    pub async fn new(browser: &Browser) -> Result<Self> {
+        Ok(Self {
+            known: tab_ids(browser).await?,
+            openers: Vec::new(),
+        })
+    }
+
+    /// Windows opened since the tracker was created that are still open,
+    /// other than `page`.
+    pub async fn popups(&self, browser: &Browser, page: &Page) -> Result<Vec<TabInfo>> {
+        new_tabs(browser, page, &self.known).await
+    }
+
+    /// Make the newest window opened since the last call the controlled
+    /// `page`, attached as its own target so `window.opener`, POST results
+    /// and the opener's state survive. The old page stays open; when a
+    /// followed window closes itself, e.g. at the end of an OAuth sign-in,
+    /// `page` goes back to its opener. Returns whether `page` changed.
+    pub async fn follow(&mut self, browser: &Browser, page: &mut Page) -> Result<bool> {
+        let popups = self.popups(browser, page).await?;
+        if let Some(newest) = popups.last() {
+            self.known.extend(popups.iter().map(|t| t.id.clone()));
+            wait_for_url(browser, &newest.id).await?;
+            debug!("Following popup: {}", newest.id);
+            let popup = browser.attach_page(&newest.id).await?;
+            self.openers.push(std::mem::replace(page, popup));
+            return Ok(true);
+        }
+        let open = tab_ids(browser).await?;
+        if open.contains(page.target_id()) {
+            return Ok(false);
+        }
+        while let Some(opener) = self.openers.pop() {
+            if open.contains(opener.target_id()) {
+                debug!("Popup closed, back to {}", opener.target_id());
+                *page = opener;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Give a new window up to [`FOLLOW_WAIT_MS`] to leave `about:blank`.
+async fn wait_for_url(browser: &Browser, id: &str) -> Result<()> {
+    let deadline = Instant::now() + Duration::from_millis(FOLLOW_WAIT_MS);
+    while Instant::now() < deadline {
+        match browser.tabs().await?.into_iter().find(|t| t.id == id) {
+            Some(tab) if tab.url == "about:blank" => {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            _ => break,
+        }
+    }
+    Ok(())
+}
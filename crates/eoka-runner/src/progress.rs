@@ -0,0 +1,261 @@
+//! Terminal progress — the config's actions as a live list, each with its
+//! status, elapsed time and retries, plus the latest screenshot written.
+//! Driven by [`Runner::with_listener`](crate::Runner::with_listener); needs
+//! the `tui` feature.
+//!
+//! ```rust,no_run
+//! # use eoka_runner::{progress::Progress, Config, Runner};
+//! # async fn example(config: &Config) -> eoka_runner::Result<()> {
+//! let mut progress = Progress::new(config);
+//! let mut runner = Runner::new(&config.browser)
+//!     .await?
+//!     .with_listener(move |event| progress.handle(event));
+//! runner.run(config).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Config, Event, RunResult};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::time::Duration;
+
+const PENDING: &str = "  {prefix:.dim} {msg:.dim}";
+const RUNNING: &str = "  {spinner:.cyan} {msg} {elapsed:.dim}";
+const PASSED: &str = "  {prefix:.green} {msg}";
+const FAILED: &str = "  {prefix:.red} {msg}";
+const HEADER: &str = "{prefix:.bold} {msg}";
+
+/// Live action list for one config.
+pub struct Progress {
+    name: String,
+    attempts: u32,
+    header: ProgressBar,
+    steps: Vec<Step>,
+    screenshot: ProgressBar,
+    retry: u32,
+}
+
+struct Step {
+    bar: ProgressBar,
+    label: String,
+    /// Attempts that ran this action.
+    runs: u32,
+}
+
+fn style(template: &str) -> ProgressStyle {
+    ProgressStyle::with_template(template).expect("valid progress template")
+}
+
+impl Progress {
+    /// One line per top-level action, all pending. Draws to stderr, and not
+    /// at all when it isn't a terminal.
+    pub fn new(config: &Config) -> Self {
+        let multi = MultiProgress::new();
+        let header = multi.add(ProgressBar::new_spinner().with_style(style(HEADER)));
+        let attempts = config
+            .on_failure
+            .as_ref()
+            .and_then(|f| f.retry.as_ref())
+            .map_or(1, |r| r.attempts.max(1));
+        let steps = config
+            .actions
+            .iter()
+            .enumerate()
+            .map(|(i, action)| {
+                let label = match action.target() {
+                    Some(target) => format!("#{} {} {}", i + 1, action.name(), target),
+                    None => format!("#{} {}", i + 1, action.name()),
+                };
+                let bar = multi.add(
+                    ProgressBar::new_spinner()
+                        .with_style(style(PENDING))
+                        .with_prefix("·")
+                        .with_message(label.clone()),
+                );
+                bar.tick();
+                Step {
+                    bar,
+                    label,
+                    runs: 0,
+                }
+            })
+            .collect();
+        let screenshot = multi.add(ProgressBar::new_spinner().with_style(style("{msg:.dim}")));
+        let progress = Self {
+            name: config.name.clone(),
+            attempts,
+            header,
+            steps,
+            screenshot,
+            retry: 0,
+        };
+        progress.draw_header("running");
+        progress
+    }
+
+    /// Update the list for one runner event.
+    pub fn handle(&mut self, event: &Event<'_>) {
+        match *event {
+            Event::ActionStart { index, retry, .. } => {
+                if retry != self.retry {
+                    self.retry = retry;
+                    self.reset();
+                }
+                let Some(step) = self.steps.get_mut(index) else {
+                    return;
+                };
+                step.runs += 1;
+                step.bar.reset_elapsed();
+                step.bar.set_style(style(RUNNING));
+                step.bar.set_message(step.label.clone());
+                step.bar.enable_steady_tick(Duration::from_millis(100));
+                self.draw_header("running");
+            }
+            Event::ActionEnd {
+                index,
+                success,
+                ref error,
+                duration_ms,
+                ref artifacts,
+                ..
+            } => {
+                if let Some(shot) = artifacts.iter().rev().find(|a| is_image(a)) {
+                    self.screenshot
+                        .set_message(format!("  latest screenshot: {}", shot));
+                }
+                let Some(step) = self.steps.get(index) else {
+                    return;
+                };
+                let mut line = format!("{} ({} ms)", step.label, duration_ms);
+                if step.runs > 1 {
+                    line.push_str(&format!(" [{} attempts]", step.runs));
+                }
+                if let Some(error) = error {
+                    line.push_str(&format!(" — {}", error));
+                }
+                step.bar.disable_steady_tick();
+                step.bar
+                    .set_style(style(if success { PASSED } else { FAILED }));
+                step.bar.set_prefix(if success { "✓" } else { "✗" });
+                step.bar.finish_with_message(line);
+            }
+            Event::RunEnd { result, .. } => self.finish(result),
+            Event::RunStart { .. } => {}
+        }
+    }
+
+    /// Show the outcome in the header and stop the spinners.
+    fn finish(&mut self, result: &RunResult) {
+        for step in &self.steps {
+            if !step.bar.is_finished() {
+                step.bar.disable_steady_tick();
+                step.bar.set_style(style(PENDING));
+                step.bar.finish();
+            }
+        }
+        let outcome = match (result.success, &result.error) {
+            (true, _) => format!("passed in {} ms", result.duration_ms),
+            (false, Some(error)) => format!("failed: {}", error),
+            (false, None) => "failed".into(),
+        };
+        self.draw_header(&outcome);
+        self.header.finish();
+        self.screenshot.finish();
+    }
+
+    /// Back to pending for the next attempt; each step keeps its run count.
+    fn reset(&mut self) {
+        for step in &self.steps {
+            step.bar.disable_steady_tick();
+            step.bar.reset();
+            step.bar.set_style(style(PENDING));
+            step.bar.set_prefix("·");
+            step.bar.set_message(step.label.clone());
+        }
+    }
+
+    fn draw_header(&self, status: &str) {
+        let done = self.steps.iter().filter(|s| s.bar.is_finished()).count();
+        self.header.set_prefix(self.name.clone());
+        let mut msg = format!("[{}/{}] {}", done, self.steps.len(), status);
+        if self.attempts > 1 {
+            msg.push_str(&format!(" (attempt {}/{})", self.retry + 1, self.attempts));
+        }
+        self.header.set_message(msg);
+    }
+}
+
+fn is_image(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    [".png", ".jpg", ".jpeg", ".webp"]
+        .iter()
+        .any(|ext| lower.ends_with(ext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn end(index: usize, retry: u32, success: bool, artifacts: &[&str]) -> Event<'static> {
+        Event::ActionEnd {
+            index,
+            action: "click",
+            target: None,
+            retry,
+            success,
+            error: (!success).then(|| "not found".into()),
+            duration_ms: 12,
+            artifacts: artifacts.iter().map(|a| a.to_string()).collect(),
+            resolved: Vec::new(),
+        }
+    }
+
+    fn start(index: usize, retry: u32) -> Event<'static> {
+        Event::ActionStart {
+            index,
+            action: "click",
+            target: None,
+            retry,
+        }
+    }
+
+    #[test]
+    fn tracks_steps_across_retries() {
+        let config = Config::parse(
+            r#"
+name: "Progress"
+target:
+  url: "https://example.com"
+actions:
+  - screenshot:
+      path: "shot.png"
+  - click:
+      text: "Go"
+on_failure:
+  retry:
+    attempts: 2
+    delay_ms: 0
+"#,
+        )
+        .unwrap();
+        let mut progress = Progress::new(&config);
+        assert_eq!(progress.attempts, 2);
+        assert_eq!(progress.steps[1].label, "#2 click text 'Go'");
+
+        progress.handle(&start(0, 0));
+        progress.handle(&end(0, 0, true, &["shot.png"]));
+        progress.handle(&start(1, 0));
+        progress.handle(&end(1, 0, false, &[]));
+        assert!(progress.steps.iter().all(|s| s.bar.is_finished()));
+        assert_eq!(
+            progress.screenshot.message(),
+            "  latest screenshot: shot.png"
+        );
+
+        progress.handle(&start(0, 1));
+        assert_eq!(progress.retry, 1);
+        assert!(!progress.steps[1].bar.is_finished());
+        assert_eq!(progress.steps[0].runs, 2);
+        assert!(progress.header.message().ends_with("(attempt 2/2)"));
+    }
+}
@@ -0,0 +1,76 @@
+//! `agent_task` — hand a bounded sub-task to an LLM agent, then resume the
+//! deterministic actions.
+//!
+//! The runner has no agent of its own (eoka-agent depends on this crate, not
+//! the other way round). Embedders implement [`AgentTaskHandler`] around
+//! their agent loop and register it with `Runner::with_agent_task`.
+
+use super::executor::ExecutionContext;
+use crate::config::actions::AgentTaskAction;
+use crate::{Error, Result};
+use eoka::Page;
+use std::future::Future;
+use std::pin::Pin;
+use tracing::info;
+
+/// Environment variable naming the model when the action doesn't.
+const MODEL_ENV: &str = "EOKA_AGENT_MODEL";
+
+/// A sub-task for an [`AgentTaskHandler`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentTask {
+    /// What the agent should achieve.
+    pub goal: String,
+    /// Most turns the agent may take; give up with an error after that.
+    pub max_turns: u32,
+    /// Model from the action or `EOKA_AGENT_MODEL`; `None` means the
+    /// agent's default.
+    pub model: Option<String>,
+}
+
+impl AgentTask {
+    fn from_action(a: &AgentTaskAction) -> Self {
+        Self {
+            goal: a.goal.clone(),
+            max_turns: a.max_turns,
+            model: a.model.clone().or_else(|| std::env::var(MODEL_ENV).ok()),
+        }
+    }
+}
+
+/// Future returned by [`AgentTaskHandler::run`].
+pub type AgentTaskFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// Runs `agent_task` actions, e.g. an LLM agent loop driving the page.
+pub trait AgentTaskHandler: Send + Sync {
+    /// Work on `page` until `task.goal` is reached. An error fails the action.
+    fn run<'a>(&'a self, page: &'a Page, task: &'a AgentTask) -> AgentTaskFuture<'a>;
+}
+
+/// Execute an `agent_task` action with the handler in `ctx`.
+pub async fn execute(page: &Page, a: &AgentTaskAction, ctx: &ExecutionContext) -> Result<()> {
+    let handler = ctx.agent_task.as_ref().ok_or_else(|| {
+        Error::Config("agent_task needs an agent; register one with Runner::with_agent_task".into())
+    })?;
+    let task = AgentTask::from_action(a);
+    info!("agent_task: {} (max {} turns)", task.goal, task.max_turns);
+    handler.run(page, &task).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_from_action() {
+        let a = AgentTaskAction {
+            goal: "reach the search box".into(),
+            max_turns: 5,
+            model: Some("small".into()),
+        };
+        let task = AgentTask::from_action(&a);
+        assert_eq!(task.goal, "reach the search box");
+        assert_eq!(task.max_turns, 5);
+        assert_eq!(task.model.as_deref(), Some("small"));
+    }
+}
@@ -0,0 +1,207 @@
+//! Run artifacts — a thumbnail after each action, rendered into a film strip
+//! PNG or an animated GIF so a run can be reviewed at a glance.
+
+use crate::config::Action;
+use crate::visual;
+use crate::{Error, Result};
+use eoka::Page;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Rgba, RgbaImage};
+use std::path::Path;
+use tracing::debug;
+
+/// Thumbnails are scaled to this width.
+const THUMB_WIDTH: u32 = 320;
+
+/// Space around and between frames in the strip.
+const GAP: u32 = 8;
+
+/// Height of the bar under each frame: green if the action succeeded, red if
+/// it failed.
+const STATUS_HEIGHT: u32 = 4;
+
+/// How long each GIF frame shows.
+const GIF_FRAME_MS: u32 = 800;
+
+const BACKGROUND: Rgba<u8> = Rgba([31, 41, 55, 255]);
+const OK: Rgba<u8> = Rgba([34, 197, 94, 255]);
+const FAILED: Rgba<u8> = Rgba([239, 68, 68, 255]);
+
+/// The page after one action.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// Index of the action in the config.
+    pub index: usize,
+    /// Action name, e.g. `click`.
+    pub action: &'static str,
+    pub success: bool,
+    /// Viewport thumbnail.
+    pub image: RgbaImage,
+}
+
+/// Thumbnails of the last run, one per top-level action, in order.
+#[derive(Debug, Clone, Default)]
+pub struct RunArtifacts {
+    frames: Vec<Frame>,
+}
+
+impl RunArtifacts {
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Screenshot the page after an action. Best-effort: a failed capture
+    /// leaves a gap instead of failing the run.
+    pub(crate) async fn capture(
+        &mut self,
+        page: &Page,
+        index: usize,
+        action: &Action,
+        success: bool,
+    ) {
+        let shot = match page.screenshot().await {
+            Ok(png) => visual::decode(&png),
+            Err(e) => Err(e.into()),
+        };
+        match shot {
+            Ok(image) => self.push(index, action.name(), success, &image),
+            Err(e) => debug!("run artifacts: {}", e),
+        }
+    }
+
+    fn push(&mut self, index: usize, action: &'static str, success: bool, image: &RgbaImage) {
+        let height = (image.height() as u64 * THUMB_WIDTH as u64 / image.width().max(1) as u64)
+            .max(1) as u32;
+        self.frames.push(Frame {
+            index,
+            action,
+            success,
+            image: image::imageops::thumbnail(image, THUMB_WIDTH, height),
+        });
+    }
+
+    /// All frames side by side, each over its status bar. `None` without
+    /// frames.
+    pub fn render_strip(&self) -> Option<RgbaImage> {
+        if self.frames.is_empty() {
+            return None;
+        }
+        let (frame_width, frame_height) = self.frame_size();
+        let count = self.frames.len() as u32;
+        let mut strip = RgbaImage::from_pixel(
+            count * frame_width + (count + 1) * GAP,
+            frame_height + 2 * GAP,
+            BACKGROUND,
+        );
+        for (i, frame) in self.frames.iter().enumerate() {
+            let x = GAP + i as u32 * (frame_width + GAP);
+            image::imageops::overlay(&mut strip, &self.tile(frame), x as i64, GAP as i64);
+        }
+        Some(strip)
+    }
+
+    /// The frames as an animated GIF that loops forever. `None` without
+    /// frames.
+    pub fn render_gif(&self) -> Result<Option<Vec<u8>>> {
+        if self.frames.is_empty() {
+            return Ok(None);
+        }
+        let mut gif = Vec::new();
+        {
+            let mut encoder = GifEncoder::new_with_speed(&mut gif, 10);
+            encoder.set_repeat(Repeat::Infinite).map_err(gif_error)?;
+            let delay = Delay::from_numer_denom_ms(GIF_FRAME_MS, 1);
+            for frame in &self.frames {
+                let tile = image::Frame::from_parts(self.tile(frame), 0, 0, delay);
+                encoder.encode_frame(tile).map_err(gif_error)?;
+            }
+        }
+        Ok(Some(gif))
+    }
+
+    /// Write an animated GIF if `path` ends in `.gif`, a film strip PNG
+    /// otherwise. Errors if no frames were captured.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let is_gif = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("gif"));
+        if is_gif {
+            let gif = self.render_gif()?.ok_or_else(no_frames)?;
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, gif)?;
+            Ok(())
+        } else {
+            visual::save(&self.render_strip().ok_or_else(no_frames)?, path)
+        }
+    }
+
+    /// Largest thumbnail, plus the status bar.
+    fn frame_size(&self) -> (u32, u32) {
+        let (width, height) = self.frames.iter().fold((0, 0), |(w, h), f| {
+            (w.max(f.image.width()), h.max(f.image.height()))
+        });
+        (width, height + STATUS_HEIGHT)
+    }
+
+    /// One frame over its status bar, padded to the common frame size.
+    fn tile(&self, frame: &Frame) -> RgbaImage {
+        let (width, height) = self.frame_size();
+        let mut tile = RgbaImage::from_pixel(width, height, BACKGROUND);
+        image::imageops::overlay(&mut tile, &frame.image, 0, 0);
+        let status = if frame.success { OK } else { FAILED };
+        for y in height - STATUS_HEIGHT..height {
+            for x in 0..width {
+                tile.put_pixel(x, y, status);
+            }
+        }
+        tile
+    }
+}
+
+fn no_frames() -> Error {
+    Error::ActionFailed("no action screenshots were captured".into())
+}
+
+fn gif_error(e: image::ImageError) -> Error {
+    Error::ActionFailed(format!("failed to encode GIF: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_strip_and_gif() {
+        let mut artifacts = RunArtifacts::default();
+        assert!(artifacts.render_strip().is_none());
+        assert!(artifacts.render_gif().unwrap().is_none());
+
+        let page = RgbaImage::from_pixel(1280, 720, Rgba([255, 255, 255, 255]));
+        artifacts.push(0, "goto", true, &page);
+        artifacts.push(1, "click", false, &page);
+        assert_eq!(artifacts.frames()[0].image.dimensions(), (320, 180));
+
+        let strip = artifacts.render_strip().unwrap();
+        assert_eq!(
+            strip.dimensions(),
+            (2 * 320 + 3 * GAP, 180 + STATUS_HEIGHT + 2 * GAP)
+        );
+        // Status bars under each frame
+        assert_eq!(*strip.get_pixel(GAP, GAP + 180), OK);
+        assert_eq!(*strip.get_pixel(2 * GAP + 320, GAP + 180), FAILED);
+        assert_eq!(*strip.get_pixel(GAP, GAP), Rgba([255, 255, 255, 255]));
+
+        let gif = artifacts.render_gif().unwrap().unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+    }
+}
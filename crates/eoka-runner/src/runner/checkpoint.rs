@@ -0,0 +1,239 @@
+//! Checkpoints for resumable runs — how far a run got and the page state needed to continue.
+
+use super::storage;
+use super::variables::Variables;
+use crate::config::Config;
+use crate::{Error, Result};
+use eoka::cdp::Cookie;
+use eoka::Page;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use tracing::{debug, warn};
+
+/// Snapshot taken after a top-level action completes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Config name, for messages.
+    pub config: String,
+    /// Hash of the target and actions (with params substituted).
+    pub fingerprint: String,
+    /// Index of the first top-level action still to run.
+    pub next_action: usize,
+    /// Page URL when the checkpoint was taken.
+    pub url: String,
+    pub cookies: Vec<Cookie>,
+    pub local_storage: BTreeMap<String, String>,
+    pub session_storage: BTreeMap<String, String>,
+    /// Values earlier actions extracted (`email_code`, `download_path`).
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+}
+
+/// Identify a config so a checkpoint is never applied to a different flow or params.
+pub fn fingerprint(config: &Config) -> String {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", config.target).hash(&mut hasher);
+    format!("{:?}", config.actions).hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+const STORAGE_JS: &str = r#"(() => {
+    const dump = (s) => { try { return Object.fromEntries(Object.entries(s)); } catch (e) { return {}; } };
+    return JSON.stringify({ local: dump(localStorage), session: dump(sessionStorage) });
+})()"#;
+
+#[derive(Deserialize)]
+struct StorageDump {
+    local: BTreeMap<String, String>,
+    session: BTreeMap<String, String>,
+}
+
+impl Checkpoint {
+    /// Capture the page state and `variables` with `next_action` as the
+    /// resume point.
+    pub async fn capture(
+        page: &Page,
+        config: &Config,
+        next_action: usize,
+        variables: &Variables,
+    ) -> Result<Self> {
+        let json: String = page.evaluate(STORAGE_JS).await?;
+        let storage: StorageDump = serde_json::from_str(&json)
+            .map_err(|e| Error::ActionFailed(format!("failed to read storage: {}", e)))?;
+        Ok(Self {
+            config: config.name.clone(),
+            fingerprint: fingerprint(config),
+            next_action,
+            url: page.url().await?,
+            cookies: page.cookies().await?,
+            local_storage: storage.local,
+            session_storage: storage.session,
+            variables: variables
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        })
+    }
+
+    /// Whether this checkpoint was taken from `config`.
+    pub fn matches(&self, config: &Config) -> bool {
+        self.fingerprint == fingerprint(config) && self.next_action <= config.actions.len()
+    }
+
+    /// Restore cookies, navigate to the saved URL, and refill web storage.
+    pub async fn restore(&self, page: &Page) -> Result<()> {
+        storage::set_cookies(page, &self.cookies).await?;
+
+        page.goto(&self.url).await?;
+        if self.local_storage.is_empty() && self.session_storage.is_empty() {
+            return Ok(());
+        }
+        let js = format!(
+            r#"(() => {{
+                const fill = (s, items) => {{ for (const [k, v] of Object.entries(items)) s.setItem(k, v); }};
+                fill(localStorage, {});
+                fill(sessionStorage, {});
+            }})()"#,
+            serde_json::to_string(&self.local_storage).unwrap(),
+            serde_json::to_string(&self.session_storage).unwrap(),
+        );
+        page.execute(&js).await?;
+        // Reload so the app starts from the restored storage.
+        page.reload().await?;
+        Ok(())
+    }
+
+    /// Put the saved variables back, so actions after the resume point see
+    /// what the earlier ones extracted.
+    pub fn restore_variables(&self, variables: &mut Variables) {
+        for (name, value) in &self.variables {
+            variables.set(name, value);
+        }
+    }
+
+    /// Load a checkpoint, or `None` if the file does not exist.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| Error::Config(format!("invalid checkpoint {}: {}", path.display(), e)))
+    }
+
+    /// Write the checkpoint atomically, so a crash mid-write keeps the previous one.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::ActionFailed(format!("failed to serialize checkpoint: {}", e)))?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)?;
+        debug!("checkpoint: next action {}", self.next_action);
+        Ok(())
+    }
+
+    /// Delete the checkpoint once there is nothing left to resume.
+    pub fn remove(path: &Path) {
+        match std::fs::remove_file(path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Failed to remove checkpoint {}: {}", path.display(), e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(yaml_actions: &str) -> Config {
+        Config::parse(&format!(
+            "name: flow\ntarget:\n  url: https://example.com\nactions:\n{}",
+            yaml_actions
+        ))
+        .unwrap()
+    }
+
+    fn checkpoint(config: &Config, next_action: usize) -> Checkpoint {
+        Checkpoint {
+            config: config.name.clone(),
+            fingerprint: fingerprint(config),
+            next_action,
+            url: "https://example.com/step2".into(),
+            cookies: Vec::new(),
+            local_storage: BTreeMap::from([("cart".into(), "[1,2]".into())]),
+            session_storage: BTreeMap::new(),
+            variables: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn fingerprint_tracks_actions() {
+        let a = config("  - click: { selector: \"#next\" }\n  - wait: { ms: 500 }\n");
+        let b = config("  - click: { selector: \"#next\" }\n  - wait: { ms: 900 }\n");
+        assert_eq!(fingerprint(&a), fingerprint(&a.clone()));
+        assert_ne!(fingerprint(&a), fingerprint(&b));
+
+        let cp = checkpoint(&a, 1);
+        assert!(cp.matches(&a));
+        assert!(!cp.matches(&b));
+        assert!(!checkpoint(&a, 3).matches(&a));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!(
+            "eoka-runner-checkpoint-{}.json",
+            std::process::id()
+        ));
+        Checkpoint::remove(&path);
+        assert!(Checkpoint::load(&path).unwrap().is_none());
+
+        let cfg = config("  - wait: { ms: 500 }\n");
+        checkpoint(&cfg, 1).save(&path).unwrap();
+        let loaded = Checkpoint::load(&path).unwrap().unwrap();
+        assert_eq!(loaded.next_action, 1);
+        assert_eq!(loaded.local_storage["cart"], "[1,2]");
+        assert!(loaded.matches(&cfg));
+
+        Checkpoint::remove(&path);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn resume_keeps_variables() {
+        let path = std::env::temp_dir().join(format!(
+            "eoka-runner-checkpoint-vars-{}.json",
+            std::process::id()
+        ));
+        let cfg = config("  - wait_for_download: { save_as: report.pdf }\n  - wait: { ms: 500 }\n");
+        // The download finished, then the run stopped before the wait
+        let mut cp = checkpoint(&cfg, 1);
+        cp.variables
+            .insert("download_path".into(), "report.pdf".into());
+        cp.save(&path).unwrap();
+
+        let loaded = Checkpoint::load(&path).unwrap().unwrap();
+        assert!(loaded.matches(&cfg));
+        let mut variables = Variables::new();
+        variables.set("download_path", "old.pdf");
+        loaded.restore_variables(&mut variables);
+        assert_eq!(variables.get("download_path"), Some("report.pdf"));
+
+        // Checkpoints written before variables were saved still load
+        let old = r#"{"config":"flow","fingerprint":"0","next_action":1,"url":"https://example.com/",
+            "cookies":[],"local_storage":{},"session_storage":{}}"#;
+        std::fs::write(&path, old).unwrap();
+        assert!(Checkpoint::load(&path)
+            .unwrap()
+            .unwrap()
+            .variables
+            .is_empty());
+        Checkpoint::remove(&path);
+    }
+}
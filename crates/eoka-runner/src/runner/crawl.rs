@@ -0,0 +1,81 @@
+//! `crawl:` mode — the report, and the frontier built from the config.
+
+use crate::config::schema::CrawlConfig;
+use crate::frontier::Frontier;
+use crate::{Error, Result};
+use regex::Regex;
+
+/// Pages a crawl visited and the ones that failed.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct CrawlReport {
+    /// URLs whose actions all succeeded, in visit order.
+    pub pages: Vec<String>,
+    /// URLs that failed, with the error.
+    pub errors: Vec<CrawlError>,
+}
+
+/// A page the crawl couldn't complete.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CrawlError {
+    pub url: String,
+    pub error: String,
+}
+
+/// The frontier for a crawl from `seed`, with the config's patterns compiled.
+pub fn frontier(seed: &str, config: &CrawlConfig) -> Result<Frontier> {
+    let compile = |patterns: &[String]| -> Result<Vec<Regex>> {
+        patterns
+            .iter()
+            .map(|p| {
+                Regex::new(p)
+                    .map_err(|e| Error::Config(format!("crawl: invalid pattern '{}': {}", p, e)))
+            })
+            .collect()
+    };
+    Ok(Frontier::new([seed], config.max_depth, config.max_pages)
+        .same_origin(config.same_origin)
+        .include(compile(&config.include)?)
+        .exclude(compile(&config.exclude)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(yaml: &str) -> CrawlConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn frontier_limits() {
+        let mut queue = frontier(
+            "https://shop.example/#top",
+            &config("{ max_depth: 1, max_pages: 3, exclude: ['/cart'] }"),
+        )
+        .unwrap();
+        assert_eq!(queue.next_page(), Some(("https://shop.example/".into(), 0)));
+        queue.add_links(
+            vec![
+                "https://shop.example/p/1".into(),
+                "https://shop.example/cart".into(),
+                "https://other.example/p/1".into(),
+                "https://shop.example/p/1".into(),
+                "https://shop.example/p/2".into(),
+                "https://shop.example/p/3".into(),
+            ],
+            0,
+        );
+        assert_eq!(
+            queue.next_page(),
+            Some(("https://shop.example/p/1".into(), 1))
+        );
+        queue.add_links(vec!["https://shop.example/p/9".into()], 1);
+        assert_eq!(
+            queue.next_page(),
+            Some(("https://shop.example/p/2".into(), 1))
+        );
+        assert_eq!(queue.next_page(), None);
+
+        assert!(frontier("https://a.example/", &config("{ include: ['('] }")).is_err());
+    }
+}
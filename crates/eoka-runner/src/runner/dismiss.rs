@@ -0,0 +1,97 @@
+//! Cookie banner and consent dialog dismissal for `browser.auto_dismiss`.
+
+use crate::config::ConsentPolicy;
+use crate::consent;
+use crate::Result;
+use eoka::Page;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+/// Rounds of dismissal; a second banner often appears after the first.
+const MAX_ROUNDS: usize = 3;
+
+/// JavaScript that finds a consent button on banners no
+/// [`consent::builtin_rules`] rule knows: any fixed or dialog element that
+/// talks about cookies is searched for a button whose label matches the
+/// policy, falling back to a close button. Returns null when there is nothing
+/// to dismiss.
+const FIND_JS: &str = r#"
+((policy) => {
+  const shown = el => {
+    if (!el) return false;
+    const r = el.getBoundingClientRect();
+    const s = getComputedStyle(el);
+    return r.width > 1 && r.height > 1 && s.display !== 'none' && s.visibility !== 'hidden';
+  };
+  const label = el => (el.getAttribute('aria-label') || el.innerText || el.value || '').trim().replace(/\s+/g, ' ');
+  const mark = (el, source) => {
+    el.setAttribute('data-eoka-dismiss', '');
+    return JSON.stringify({ source, button: label(el) });
+  };
+  document.querySelectorAll('[data-eoka-dismiss]').forEach(el => el.removeAttribute('data-eoka-dismiss'));
+
+  const WANTED = policy === 'accept'
+    ? /\b(accept|agree|allow|got it|i understand)\b|^ok(ay)?$/i
+    : /\b(reject|decline|deny|refuse|disagree)\b|necessary only|only necessary|essential only|only essential/i;
+  const CLOSE = /^(close|dismiss|x|×|✕|✖|no,? thanks|not now)$/i;
+  for (const el of document.querySelectorAll('body *')) {
+    const style = getComputedStyle(el);
+    const layered = style.position === 'fixed' || style.position === 'sticky'
+      || el.matches('dialog[open], [role="dialog"], [role="alertdialog"], [aria-modal="true"]');
+    if (!layered || !shown(el)) continue;
+    const attrs = (el.id + ' ' + (el.className || '')).toLowerCase();
+    if (!/cookie|consent|gdpr|privacy/.test(attrs) && !/\bcookies?\b|consent/i.test(el.innerText || '')) continue;
+    const buttons = Array.from(el.querySelectorAll('button, a, [role="button"], input[type="button"], input[type="submit"]'))
+      .filter(b => shown(b) && label(b).length <= 40);
+    const button = buttons.find(b => WANTED.test(label(b))) || buttons.find(b => CLOSE.test(label(b)));
+    if (button) return mark(button, 'cookie banner');
+  }
+  return 'null';
+})
+"#;
+
+#[derive(Deserialize)]
+struct Found {
+    source: String,
+    button: String,
+}
+
+/// Dismiss consent banners on the page. Returns how many were dismissed.
+/// Failures are logged, not returned: a stubborn banner shouldn't fail the run.
+pub async fn dismiss(page: &Page, policy: ConsentPolicy) -> usize {
+    let mut count = 0;
+    for _ in 0..MAX_ROUNDS {
+        match dismiss_one(page, policy).await {
+            Ok(true) => count += 1,
+            Ok(false) => break,
+            Err(e) => {
+                debug!("auto_dismiss: {}", e);
+                break;
+            }
+        }
+        page.wait(400).await;
+    }
+    count
+}
+
+async fn dismiss_one(page: &Page, policy: ConsentPolicy) -> Result<bool> {
+    if let Some(m) = consent::find_rule(page, &consent::builtin_rules(), policy).await? {
+        info!("auto_dismiss: {} - clicking \"{}\"", m.name, m.text);
+        consent::click(page, &m.selector).await?;
+        return Ok(true);
+    }
+    let policy = match policy {
+        ConsentPolicy::Reject => "reject",
+        ConsentPolicy::Accept => "accept",
+    };
+    let json: String = page.evaluate(&format!("{}('{}')", FIND_JS, policy)).await?;
+    let Some(found) = serde_json::from_str::<Option<Found>>(&json)? else {
+        return Ok(false);
+    };
+    info!(
+        "auto_dismiss: {} - clicking \"{}\"",
+        found.source, found.button
+    );
+    consent::click(page, "[data-eoka-dismiss]").await?;
+    Ok(true)
+}
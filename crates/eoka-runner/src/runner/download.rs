@@ -0,0 +1,296 @@
+//! Download manager — detects files Chrome saves to the download directory.
+
+use crate::config::include::IncludeChain;
+use crate::config::Action;
+use crate::{Error, Result};
+use eoka::Page;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use tracing::debug;
+
+/// Extensions Chrome and Firefox use for in-progress downloads.
+const PARTIAL_EXTENSIONS: &[&str] = &["crdownload", "part", "tmp"];
+
+/// A completed download.
+#[derive(Debug, Clone)]
+pub struct DownloadInfo {
+    /// Where the file currently lives.
+    pub path: PathBuf,
+    /// File name (without directory).
+    pub filename: String,
+    /// Size in bytes.
+    pub size: u64,
+}
+
+/// Watches a directory for new, completed downloads.
+#[derive(Debug)]
+pub struct DownloadManager {
+    dir: PathBuf,
+    /// Only files modified at or after this instant count as new.
+    since: SystemTime,
+    last: Option<DownloadInfo>,
+}
+
+impl DownloadManager {
+    /// Create a manager watching `dir`. Files already present are ignored.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            since: SystemTime::now(),
+            last: None,
+        }
+    }
+
+    /// Default download directory (`~/Downloads`).
+    pub fn default_dir() -> PathBuf {
+        std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("Downloads")
+    }
+
+    /// The most recent download seen by [`wait`](Self::wait).
+    pub fn last(&self) -> Option<&DownloadInfo> {
+        self.last.as_ref()
+    }
+
+    /// Wait for a new download to finish (no partial file, size stable).
+    pub async fn wait(&mut self, timeout_ms: u64) -> Result<DownloadInfo> {
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        let mut pending: Option<(PathBuf, u64)> = None;
+
+        loop {
+            let candidate = newest_completed(&self.dir, self.since)?;
+            let in_progress = has_partial(&self.dir, self.since)?;
+
+            match (candidate, &pending) {
+                // Same file, same size as last poll, and nothing still writing.
+                (Some((path, size)), Some((prev, prev_size)))
+                    if &path == prev && size == *prev_size && !in_progress =>
+                {
+                    let info = DownloadInfo {
+                        filename: path
+                            .file_name()
+                            .map(|n| n.to_string_lossy().into_owned())
+                            .unwrap_or_default(),
+                        path,
+                        size,
+                    };
+                    debug!("download complete: {} ({} bytes)", info.filename, info.size);
+                    self.since = SystemTime::now();
+                    self.last = Some(info.clone());
+                    return Ok(info);
+                }
+                (Some(found), _) => pending = Some(found),
+                (None, _) => pending = None,
+            }
+
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout(format!(
+                    "no download completed in {} after {}ms",
+                    self.dir.display(),
+                    timeout_ms
+                )));
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+    }
+
+    /// Move the last download to `dest`, updating its recorded path.
+    pub fn save_as(&mut self, dest: impl AsRef<Path>) -> Result<DownloadInfo> {
+        let Some(ref mut info) = self.last else {
+            return Err(Error::ActionFailed("no download to save".into()));
+        };
+        let dest = dest.as_ref();
+        if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        // rename fails across filesystems; fall back to copy + remove
+        if std::fs::rename(&info.path, dest).is_err() {
+            std::fs::copy(&info.path, dest)?;
+            std::fs::remove_file(&info.path)?;
+        }
+        info.path = dest.to_path_buf();
+        Ok(info.clone())
+    }
+}
+
+/// Have Chrome save downloads to `dir` without asking (headless Chrome may
+/// otherwise drop them). Applies to the whole browser. Returns `dir` made
+/// absolute, as Chrome needs it.
+pub async fn allow_downloads(page: &Page, dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let dir = dir.canonicalize()?;
+    page.session()
+        .transport()
+        .send::<_, serde_json::Value>(
+            "Browser.setDownloadBehavior",
+            &serde_json::json!({
+                "behavior": "allow",
+                "downloadPath": dir.to_string_lossy(),
+            }),
+        )
+        .await?;
+    Ok(dir)
+}
+
+/// Whether `actions` wait for a download, looking into nested blocks and
+/// includes (resolved from `base`). An include that fails to load counts as
+/// not downloading; running it reports the error.
+pub fn uses_downloads(actions: &[Action], base: &Path, includes: &IncludeChain) -> bool {
+    actions.iter().any(|action| match action {
+        Action::WaitForDownload(_) => true,
+        Action::Repeat(a) => uses_downloads(&a.actions, base, includes),
+        Action::WithinFrame(a) => uses_downloads(&a.actions, base, includes),
+        Action::IfTextExists(a) => {
+            uses_downloads(&a.then_actions, base, includes)
+                || uses_downloads(&a.else_actions, base, includes)
+        }
+        Action::IfSelectorExists(a) => {
+            uses_downloads(&a.then_actions, base, includes)
+                || uses_downloads(&a.else_actions, base, includes)
+        }
+        Action::Include(a) => {
+            let path = base.join(&a.path);
+            let (Ok(chain), Ok(included)) = (includes.enter(&path), a.load(&path)) else {
+                return false;
+            };
+            let child_base = path.parent().unwrap_or(Path::new("."));
+            uses_downloads(&included.actions, child_base, &chain)
+        }
+        _ => false,
+    })
+}
+
+fn is_partial(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| PARTIAL_EXTENSIONS.contains(&e))
+}
+
+/// Files in `dir` modified at or after `since`, with their size and mtime.
+fn new_files(dir: &Path, since: SystemTime) -> Result<Vec<(PathBuf, u64, SystemTime)>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else { continue };
+        if !meta.is_file() {
+            continue;
+        }
+        let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        if modified >= since {
+            files.push((entry.path(), meta.len(), modified));
+        }
+    }
+    Ok(files)
+}
+
+fn newest_completed(dir: &Path, since: SystemTime) -> Result<Option<(PathBuf, u64)>> {
+    Ok(new_files(dir, since)?
+        .into_iter()
+        .filter(|(path, _, _)| !is_partial(path))
+        .max_by_key(|(_, _, modified)| *modified)
+        .map(|(path, size, _)| (path, size)))
+}
+
+fn has_partial(dir: &Path, since: SystemTime) -> Result<bool> {
+    Ok(new_files(dir, since)?
+        .iter()
+        .any(|(path, _, _)| is_partial(path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("eoka-runner-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn partial_extensions() {
+        assert!(is_partial(Path::new("report.pdf.crdownload")));
+        assert!(is_partial(Path::new("report.pdf.part")));
+        assert!(!is_partial(Path::new("report.pdf")));
+        assert!(!is_partial(Path::new("README")));
+    }
+
+    #[tokio::test]
+    async fn detects_new_download() {
+        let dir = temp_dir("download");
+        std::fs::write(dir.join("old.txt"), b"old").unwrap();
+
+        let since = SystemTime::now() - Duration::from_millis(10);
+        let mut manager = DownloadManager::new(&dir);
+        manager.since = since;
+        // Pretend old.txt predates the manager.
+        let old = std::fs::File::options()
+            .write(true)
+            .open(dir.join("old.txt"))
+            .unwrap();
+        old.set_modified(since - Duration::from_secs(60)).unwrap();
+
+        std::fs::write(dir.join("new.csv.crdownload"), b"partial").unwrap();
+        assert!(has_partial(&dir, since).unwrap());
+        std::fs::rename(dir.join("new.csv.crdownload"), dir.join("new.csv")).unwrap();
+
+        let info = manager.wait(2000).await.unwrap();
+        assert_eq!(info.filename, "new.csv");
+        assert_eq!(info.size, 7);
+
+        let saved = manager.save_as(dir.join("out/saved.csv")).unwrap();
+        assert!(saved.path.ends_with("out/saved.csv"));
+        assert!(!dir.join("new.csv").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn finds_nested_download_waits() {
+        let dir = temp_dir("uses-downloads");
+        std::fs::write(
+            dir.join("export.yaml"),
+            "name: Export\ntarget:\n  url: https://example.com\nactions:\n  - wait_for_download: {}\n",
+        )
+        .unwrap();
+        let parse = |yaml: &str| crate::Config::parse(yaml).unwrap().actions;
+        let chain = IncludeChain::default();
+
+        let plain = parse(
+            "name: T\ntarget:\n  url: https://example.com\nactions:\n  - click: { text: Export }\n",
+        );
+        assert!(!uses_downloads(&plain, &dir, &chain));
+
+        let nested = parse(
+            "name: T\ntarget:\n  url: https://example.com\nactions:\n  - if_text_exists:\n      text: Export\n      then:\n        - wait_for_download: {}\n",
+        );
+        assert!(uses_downloads(&nested, &dir, &chain));
+
+        let included = parse(
+            "name: T\ntarget:\n  url: https://example.com\nactions:\n  - include: { path: export.yaml }\n",
+        );
+        assert!(uses_downloads(&included, &dir, &chain));
+        assert!(!uses_downloads(
+            &included,
+            Path::new("/nonexistent"),
+            &chain
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn times_out_without_download() {
+        let dir = temp_dir("download-timeout");
+        let mut manager = DownloadManager::new(&dir);
+        assert!(matches!(manager.wait(300).await, Err(Error::Timeout(_))));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
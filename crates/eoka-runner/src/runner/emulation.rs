@@ -0,0 +1,52 @@
+//! Locale, timezone, and device emulation applied to the page after launch.
+
+use crate::config::BrowserConfig;
+use crate::Result;
+use eoka::Page;
+use serde_json::{json, Value};
+use tracing::debug;
+
+/// Apply the emulation overrides requested in `config`.
+pub async fn apply(page: &Page, config: &BrowserConfig) -> Result<()> {
+    let session = page.session();
+    if let Some(ref locale) = config.locale {
+        debug!("Emulating locale: {}", locale);
+        let _: Value = session
+            .send("Emulation.setLocaleOverride", &json!({ "locale": locale }))
+            .await?;
+    }
+    if let Some(ref timezone) = config.timezone {
+        debug!("Emulating timezone: {}", timezone);
+        let _: Value = session
+            .send(
+                "Emulation.setTimezoneOverride",
+                &json!({ "timezoneId": timezone }),
+            )
+            .await?;
+    }
+    if let Some(device) = config.device {
+        let metrics = device.metrics();
+        // The window size already matches; only mobile devices need the override.
+        if metrics.mobile {
+            debug!("Emulating device: {:?}", device);
+            let _: Value = session
+                .send(
+                    "Emulation.setDeviceMetricsOverride",
+                    &json!({
+                        "width": metrics.width,
+                        "height": metrics.height,
+                        "deviceScaleFactor": metrics.scale,
+                        "mobile": true,
+                    }),
+                )
+                .await?;
+            let _: Value = session
+                .send(
+                    "Emulation.setTouchEmulationEnabled",
+                    &json!({ "enabled": true, "maxTouchPoints": 5 }),
+                )
+                .await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+//! JSONL event stream — one line per run and action event, for dashboards and reports.
+
+use super::{Resolution, RunResult};
+use crate::{Error, Result};
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use tracing::warn;
+
+/// One event. Serialized with an `event` tag, e.g. `{"event":"action_end",...}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    RunStart {
+        config: &'a str,
+        url: &'a str,
+    },
+    ActionStart {
+        /// Top-level action index, starting at 0.
+        index: usize,
+        action: &'static str,
+        target: Option<String>,
+        /// Retries of the whole run so far (0 on the first attempt).
+        retry: u32,
+    },
+    ActionEnd {
+        index: usize,
+        action: &'static str,
+        target: Option<String>,
+        retry: u32,
+        success: bool,
+        error: Option<String>,
+        duration_ms: u64,
+        /// Files the action wrote (screenshots, downloads).
+        artifacts: Vec<String>,
+        /// How the action's targets were resolved.
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        resolved: Vec<Resolution>,
+    },
+    RunEnd {
+        config: &'a str,
+        #[serde(flatten)]
+        result: &'a RunResult,
+        artifacts: Vec<String>,
+    },
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    timestamp: String,
+    #[serde(flatten)]
+    event: &'a Event<'a>,
+}
+
+/// Appends events to a JSONL file.
+#[derive(Debug)]
+pub struct EventLog {
+    file: File,
+}
+
+impl EventLog {
+    /// Open `path` for appending, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::options().create(true).append(true).open(path)?;
+        Ok(Self { file })
+    }
+
+    /// Write one event. Errors are logged, never fatal to the run.
+    pub fn emit(&mut self, event: &Event<'_>) {
+        if let Err(e) = self.write(event) {
+            warn!("Failed to write event log: {}", e);
+        }
+    }
+
+    fn write(&mut self, event: &Event<'_>) -> Result<()> {
+        let line = Line {
+            timestamp: chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false),
+            event,
+        };
+        let json = serde_json::to_string(&line)
+            .map_err(|e| Error::ActionFailed(format!("failed to serialize event: {}", e)))?;
+        writeln!(self.file, "{}", json)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_tagged_lines() {
+        let path =
+            std::env::temp_dir().join(format!("eoka-runner-events-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut log = EventLog::open(&path).unwrap();
+
+        log.emit(&Event::ActionEnd {
+            index: 2,
+            action: "screenshot",
+            target: Some("shot.png".into()),
+            retry: 1,
+            success: true,
+            error: None,
+            duration_ms: 42,
+            artifacts: vec!["shot.png".into()],
+            resolved: vec![Resolution {
+                target: "text 'Go'".into(),
+                searched: "clickable elements containing 'go'".into(),
+                selector: Some("#go".into()),
+                matches: 1,
+                point: Some((10.0, 20.0)),
+            }],
+        });
+        let result = RunResult {
+            success: false,
+            error: Some("boom".into()),
+            actions_executed: 3,
+            duration_ms: 900,
+            retries: 1,
+            actions: Vec::new(),
+            crawl: None,
+        };
+        log.emit(&Event::RunEnd {
+            config: "flow",
+            result: &result,
+            artifacts: Vec::new(),
+        });
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "action_end");
+        assert_eq!(lines[0]["target"], "shot.png");
+        assert_eq!(lines[0]["retry"], 1);
+        assert_eq!(lines[0]["artifacts"][0], "shot.png");
+        assert_eq!(lines[0]["resolved"][0]["selector"], "#go");
+        assert_eq!(lines[0]["resolved"][0]["point"][1], 20.0);
+        assert!(lines[0]["timestamp"].is_string());
+        assert_eq!(lines[1]["event"], "run_end");
+        assert_eq!(lines[1]["success"], false);
+        assert_eq!(lines[1]["actions_executed"], 3);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
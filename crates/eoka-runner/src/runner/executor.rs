@@ -1,17 +1,37 @@
+use super::agent_task::{self, AgentTaskHandler};
+use super::dismiss;
+use super::download::DownloadManager;
+use super::frame;
+use super::resolution::Resolution;
+use super::snapshot;
+use super::variables::Variables;
+use crate::a11y;
+use crate::autofill::{self, FieldKind, FilledField, FormProfile, FormRef};
 use crate::config::actions::{
-    EmailAction, EmailExtractAction, EmailFilterAction, ImapConfigAction, ScrollDirection, Target,
-    TryClickAnyAction, WaitForEmailAction,
+    AssertA11yAction, AssertDownloadAction, AssertScreenshotAction, AssertSnapshotAction,
+    ClickAllAction, EmailAction, EmailCleanup, EmailExtractAction, EmailFilterAction, FrameLocator,
+    ImapConfigAction, ImapOAuth2Action, MailProtocol, MenuClickAction, OAuth2Provider,
+    ScrollDirection, ScrollInfiniteAction, ScrollUntilAction, SnapshotFormat, Target,
+    TryClickAnyAction, UploadAction, WaitForEmailAction,
 };
-use crate::config::{Action, Config, Params};
+use crate::config::include::IncludeChain;
+use crate::config::{Action, ConsentPolicy};
+use crate::deterministic;
+use crate::links::{self, LinkScope};
+use crate::target::{self, LivePattern, Resolved};
+use crate::visual;
 use crate::{Error, Result};
 use chrono::Duration as ChronoDuration;
 use eoka::Page;
 use eoka_email::{
-    extract_code, extract_first_link, AsyncImapClient, ImapConfig, LinkFilter, SearchCriteria,
-    WaitOptions,
+    extract_code, extract_first_link, otp, AsyncImapClient, GmailClient, ImapConfig, LinkFilter,
+    OAuth2, Pop3Client, Protocol, SearchCriteria, WaitOptions,
 };
 use regex::Regex;
+use serde::Deserialize;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
 use tracing::{debug, info};
 
 impl From<&ImapConfigAction> for ImapConfig {
@@ -23,45 +43,121 @@ impl From<&ImapConfigAction> for ImapConfig {
             username: a.username.clone(),
             password: a.password.clone(),
             mailbox: a.mailbox.clone(),
+            oauth2: a.oauth2.as_ref().map(OAuth2::from),
+            protocol: match a.protocol {
+                MailProtocol::Imap => Protocol::Imap,
+                MailProtocol::Pop3 => Protocol::Pop3,
+                MailProtocol::Gmail => Protocol::Gmail,
+            },
         }
     }
 }
 
-/// Maximum include depth to prevent infinite loops.
-const MAX_INCLUDE_DEPTH: usize = 10;
+impl From<&ImapOAuth2Action> for OAuth2 {
+    fn from(a: &ImapOAuth2Action) -> Self {
+        if let Some(ref token) = a.access_token {
+            return OAuth2::access_token(token);
+        }
+        let client_id = a.client_id.clone().unwrap_or_default();
+        let refresh_token = a.refresh_token.clone().unwrap_or_default();
+        let mut oauth2 = match (&a.token_url, a.provider) {
+            (Some(url), _) => {
+                OAuth2::refresh_token(url, client_id, a.client_secret.clone(), refresh_token)
+            }
+            (None, OAuth2Provider::Google) => OAuth2::google(
+                client_id,
+                a.client_secret.clone().unwrap_or_default(),
+                refresh_token,
+            ),
+            (None, OAuth2Provider::Microsoft) => {
+                OAuth2::microsoft(client_id, a.client_secret.clone(), refresh_token)
+            }
+        };
+        if let Some(ref scope) = a.scope {
+            oauth2 = oauth2.scope(scope);
+        }
+        oauth2
+    }
+}
 
 /// Context for action execution.
 #[derive(Clone)]
 pub struct ExecutionContext {
     /// Base path for resolving relative includes.
     pub base_path: PathBuf,
-    /// Current include depth.
-    pub include_depth: usize,
+    /// Include files being expanded, for cycle and depth checks.
+    pub includes: IncludeChain,
+    /// Chain of iframes (outermost first) that element actions run inside.
+    pub frames: Vec<FrameLocator>,
+    /// Download tracking, shared with included and nested contexts.
+    pub downloads: Arc<Mutex<DownloadManager>>,
+    /// Values extracted by actions, shared with included and nested contexts.
+    pub variables: Arc<Mutex<Variables>>,
+    /// Overwrite screenshot baselines and snapshot golden files instead of
+    /// comparing against them.
+    pub update_baselines: bool,
+    /// Dismiss consent banners after `goto`, answering with this policy.
+    pub auto_dismiss: Option<ConsentPolicy>,
+    /// How targets resolved since the runner last cleared it, one entry per
+    /// target; shared with included and nested contexts.
+    pub resolved: Arc<Mutex<Vec<Resolution>>>,
+    /// Runs `agent_task` actions; they fail without one.
+    pub agent_task: Option<Arc<dyn AgentTaskHandler>>,
 }
 
 impl ExecutionContext {
-    /// Create a new context with a base path.
-    pub fn new(base_path: impl Into<PathBuf>) -> Self {
+    /// Create a new context with a base path and download directory.
+    pub fn new(base_path: impl Into<PathBuf>, download_dir: impl Into<PathBuf>) -> Self {
         Self {
             base_path: base_path.into(),
-            include_depth: 0,
+            includes: IncludeChain::default(),
+            frames: Vec::new(),
+            downloads: Arc::new(Mutex::new(DownloadManager::new(download_dir))),
+            variables: Arc::new(Mutex::new(Variables::new())),
+            update_baselines: false,
+            auto_dismiss: None,
+            resolved: Arc::default(),
+            agent_task: None,
         }
     }
 
-    /// Create a child context for an include.
-    pub fn child(&self, new_base: impl Into<PathBuf>) -> Result<Self> {
-        if self.include_depth >= MAX_INCLUDE_DEPTH {
-            return Err(Error::Config(format!(
-                "maximum include depth ({}) exceeded",
-                MAX_INCLUDE_DEPTH
-            )));
-        }
+    /// Create a child context for including the file at `path`.
+    pub fn child(&self, path: &Path) -> Result<Self> {
         Ok(Self {
-            base_path: new_base.into(),
-            include_depth: self.include_depth + 1,
+            base_path: path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+            includes: self.includes.enter(path)?,
+            frames: self.frames.clone(),
+            downloads: Arc::clone(&self.downloads),
+            variables: Arc::clone(&self.variables),
+            update_baselines: self.update_baselines,
+            auto_dismiss: self.auto_dismiss,
+            resolved: Arc::clone(&self.resolved),
+            agent_task: self.agent_task.clone(),
         })
     }
 
+    /// Create a child context whose element actions run inside `frame`.
+    pub fn within_frame(&self, frame: &FrameLocator) -> Self {
+        let mut ctx = self.clone();
+        ctx.frames.push(frame.clone());
+        ctx
+    }
+
+    /// Whether element actions run inside an iframe.
+    pub fn in_frame(&self) -> bool {
+        !self.frames.is_empty()
+    }
+
+    /// Note how a target resolved, replacing an earlier lookup of the same
+    /// target (polling actions resolve theirs repeatedly).
+    async fn record(&self, resolution: Resolution) {
+        let mut resolved = self.resolved.lock().await;
+        match resolved.iter_mut().find(|r| r.target == resolution.target) {
+            Some(r) => *r = resolution,
+            None => resolved.push(resolution),
+        }
+    }
+
     /// Resolve a relative path against the base path.
     pub fn resolve_path(&self, path: &str) -> PathBuf {
         let path = Path::new(path);
@@ -75,39 +171,27 @@ impl ExecutionContext {
 
 impl Default for ExecutionContext {
     fn default() -> Self {
-        Self::new(std::env::current_dir().unwrap_or_default())
-    }
-}
-
-/// Find element by text - returns CSS selector.
-const FIND_BY_TEXT_JS: &str = r#"(() => {
-    const text = arguments[0];
-    const walker = document.createTreeWalker(document.body, NodeFilter.SHOW_ELEMENT, null);
-    while (walker.nextNode()) {
-        const el = walker.currentNode;
-        if (el.textContent?.trim().toLowerCase().includes(text.toLowerCase())) {
-            if (el.matches('a, button, input, select, [role="button"], [onclick]')) {
-                if (el.id) return '#' + el.id;
-                const path = [];
-                let node = el;
-                while (node && node !== document.body) {
-                    let selector = node.tagName.toLowerCase();
-                    if (node.id) {
-                        path.unshift('#' + node.id);
-                        break;
-                    }
-                    const siblings = Array.from(node.parentNode?.children || []);
-                    const index = siblings.indexOf(node) + 1;
-                    if (siblings.length > 1) selector += ':nth-child(' + index + ')';
-                    path.unshift(selector);
-                    node = node.parentNode;
-                }
-                return path.join(' > ');
-            }
-        }
+        Self::new(
+            std::env::current_dir().unwrap_or_default(),
+            DownloadManager::default_dir(),
+        )
     }
-    return null;
-})()"#;
+}
+
+/// Tag each of `els` (from [`target::FIND_JS`]) with `data-eoka-all`, so
+/// each can be addressed even after earlier clicks change the DOM. Returns
+/// one selector per element.
+const MARK_ALL_JS: &str = r#"((els, token, max) => {
+    if (max !== null) els = els.slice(0, max);
+    return els.map((el, i) => {
+        const id = token + '-' + i;
+        el.setAttribute('data-eoka-all', id);
+        return '[data-eoka-all="' + id + '"]';
+    });
+})"#;
+
+/// Distinguishes the markers of separate `click_all`/`fill_all` runs.
+static MARK_TOKEN: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
 
 /// Execute a single action on the page with context.
 pub async fn execute_with_context(
@@ -119,6 +203,9 @@ pub async fn execute_with_context(
         Action::Goto(a) => {
             info!("goto: {}", a.url);
             page.goto(&a.url).await?;
+            if let Some(policy) = ctx.auto_dismiss {
+                dismiss::dismiss(page, policy).await;
+            }
         }
         Action::Back => {
             debug!("back");
@@ -145,7 +232,20 @@ pub async fn execute_with_context(
         }
         Action::WaitForText(a) => {
             debug!("wait_for_text: '{}'", a.text);
-            page.wait_for_text(&a.text, a.timeout_ms).await?;
+            if ctx.in_frame() {
+                wait_for_scoped_text(page, ctx, &a.text, a.timeout_ms).await?;
+            } else {
+                page.wait_for_text(&a.text, a.timeout_ms).await?;
+            }
+        }
+        Action::WaitForTextGone(a) => {
+            debug!("wait_for_text_gone: '{}'", a.text);
+            wait_for_text_gone(page, ctx, &a.text, a.timeout_ms).await?;
+        }
+        Action::WaitForElementStable(a) => {
+            let selector = resolve_target(page, ctx, &a.target).await?;
+            debug!("wait_for_element_stable: {}", a.target);
+            wait_for_element_stable(page, ctx, &selector, a.frames, a.timeout_ms).await?;
         }
         Action::WaitForUrl(a) => {
             debug!("wait_for_url: contains '{}'", a.contains);
@@ -153,24 +253,33 @@ pub async fn execute_with_context(
                 .await?;
         }
         Action::WaitForEmail(a) => {
-            wait_for_email(page, a).await?;
+            wait_for_email(page, ctx, a).await?;
         }
         Action::Click(a) => {
-            let selector = resolve_target(page, &a.target).await?;
+            let selector = resolve_target(page, ctx, &a.target).await?;
             info!("click: {}", a.target);
+            if ctx.in_frame() {
+                if !click_in_scope(page, ctx, &selector).await? {
+                    return Err(Error::ActionFailed(format!(
+                        "click target {} not found in frame",
+                        a.target
+                    )));
+                }
+                return Ok(());
+            }
             if a.scroll_into_view {
-                scroll_into_view(page, &selector).await?;
+                scroll_into_view(page, ctx, &selector).await?;
             }
             if a.human {
-                page.human_click(&selector).await?;
+                deterministic::human_click(page, &selector).await?;
             } else {
                 page.click(&selector).await?;
             }
         }
         Action::TryClick(a) => {
             debug!("try_click: {}", a.target);
-            if let Ok(selector) = resolve_target(page, &a.target).await {
-                let _ = page.try_click(&selector).await;
+            if let Ok(selector) = resolve_target(page, ctx, &a.target).await {
+                let _ = try_click_selector(page, ctx, &selector).await;
             }
         }
         Action::TryClickAny(a) => {
@@ -178,41 +287,86 @@ pub async fn execute_with_context(
                 "try_click_any: {:?}",
                 a.texts.as_ref().or(a.selectors.as_ref())
             );
-            try_click_any(page, a).await?;
+            try_click_any(page, ctx, a).await?;
+        }
+        Action::ClickAll(a) => {
+            info!("click_all: {}", a.target);
+            click_all(page, ctx, a).await?;
         }
         Action::Fill(a) => {
             info!("fill: {} = '{}'", a.target, a.value);
-            let selector = resolve_target(page, &a.target).await?;
-            if a.human {
-                page.human_fill(&selector, &a.value).await?;
-            } else {
-                page.fill(&selector, &a.value).await?;
+            let selector = resolve_target(page, ctx, &a.target).await?;
+            fill_selector(page, ctx, &selector, &a.value, a.human).await?;
+        }
+        Action::FillAll(a) => {
+            info!("fill_all: {} = '{}'", a.target, a.value);
+            let marked = mark_all(page, ctx, &a.target, a.max).await?;
+            let mut filled = 0;
+            for selector in &marked {
+                if element_exists(page, ctx, selector).await? {
+                    fill_selector(page, ctx, selector, &a.value, a.human).await?;
+                    filled += 1;
+                }
             }
+            unmark_all(page, ctx, &marked).await?;
+            info!("fill_all: filled {} of {}", filled, marked.len());
         }
         Action::Type(a) => {
             debug!("type: {} = '{}'", a.target, a.value);
-            let selector = resolve_target(page, &a.target).await?;
-            focus_element(page, &selector).await?;
+            let selector = resolve_target(page, ctx, &a.target).await?;
+            focus_element(page, ctx, &selector).await?;
             page.type_text(&a.value).await?;
         }
         Action::Clear(a) => {
             debug!("clear: {}", a.target);
-            let selector = resolve_target(page, &a.target).await?;
-            page.fill(&selector, "").await?;
+            let selector = resolve_target(page, ctx, &a.target).await?;
+            if ctx.in_frame() {
+                clear_in_scope(page, ctx, &selector).await?;
+            } else {
+                page.fill(&selector, "").await?;
+            }
         }
         Action::Select(a) => {
             info!("select: {} = '{}'", a.target, a.value);
-            let selector = resolve_target(page, &a.target).await?;
-            select_option(page, &selector, &a.value, &a.target).await?;
+            let selector = resolve_target(page, ctx, &a.target).await?;
+            select_option(page, ctx, &selector, &a.value, &a.target).await?;
+        }
+        Action::Autofill(a) => {
+            info!("autofill: {}", a.form.as_deref().unwrap_or("first form"));
+            let form = a.form.clone().map_or(FormRef::Index(0), FormRef::Selector);
+            if fill_form(page, ctx, &form, &a.profile, a.human)
+                .await?
+                .is_empty()
+            {
+                return Err(Error::ActionFailed(format!(
+                    "autofill: no fields recognized in form {}",
+                    form
+                )));
+            }
         }
         Action::PressKey(a) => {
-            debug!("press_key: {}", a.key);
-            page.human().press_key(&a.key).await?;
+            let keys = a.sequence();
+            if keys.is_empty() {
+                return Err(Error::Config(
+                    "press_key: 'key' or 'keys' is required".into(),
+                ));
+            }
+            debug!("press_key: {}", keys.join(", "));
+            for (i, key) in keys.iter().enumerate() {
+                if i > 0 && a.delay_ms > 0 {
+                    page.wait(a.delay_ms).await;
+                }
+                press_key(page, key).await?;
+            }
         }
         Action::Hover(a) => {
             debug!("hover: {}", a.target);
-            let selector = resolve_target(page, &a.target).await?;
-            hover_element(page, &selector).await?;
+            let selector = resolve_target(page, ctx, &a.target).await?;
+            hover_element(page, ctx, &selector).await?;
+        }
+        Action::MenuClick(a) => {
+            info!("menu_click: {} > {}", a.parent, a.item);
+            menu_click(page, ctx, a).await?;
         }
         Action::SetCookie(a) => {
             debug!("set_cookie: {}={}", a.name, a.value);
@@ -225,7 +379,12 @@ pub async fn execute_with_context(
         }
         Action::Execute(a) => {
             debug!("execute: {}...", &a.js[..a.js.len().min(50)]);
-            page.execute(&a.js).await?;
+            if ctx.in_frame() {
+                let body = format!("s.win.eval({});", serde_json::to_string(&a.js).unwrap());
+                frame::execute(page, &ctx.frames, &body).await?;
+            } else {
+                page.execute(&a.js).await?;
+            }
         }
         Action::Screenshot(a) => {
             info!("screenshot: {}", a.path);
@@ -235,9 +394,57 @@ pub async fn execute_with_context(
         Action::Log(a) => {
             info!("[log] {}", a.message);
         }
+        Action::Pause(a) => {
+            let message = a.message.as_deref().unwrap_or("Paused");
+            match a.timeout_ms {
+                Some(ms) => eprintln!("⏸ {} — press Enter to continue (auto in {}ms)", message, ms),
+                None => eprintln!("⏸ {} — press Enter to continue", message),
+            }
+            wait_for_enter(a.timeout_ms).await;
+        }
+        Action::AssertScreenshot(a) => {
+            debug!("assert_screenshot: {}", a.baseline);
+            assert_screenshot(page, ctx, a).await?;
+        }
+        Action::AssertSnapshot(a) => {
+            debug!("assert_snapshot: {}", a.golden);
+            assert_snapshot(page, ctx, a).await?;
+        }
+        Action::AuditLinks(a) => {
+            info!("audit_links: same_origin={}", a.same_origin);
+            let ignore = a
+                .ignore
+                .iter()
+                .map(|p| {
+                    Regex::new(p).map_err(|e| {
+                        Error::Config(format!("audit_links: invalid ignore '{}': {}", p, e))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let mut anchors = scoped_links(page, ctx, a.selector.as_deref()).await?;
+            anchors.retain(|l| !ignore.iter().any(|re| re.is_match(&l.href)));
+            let scope = if a.same_origin {
+                LinkScope::SameOrigin
+            } else {
+                LinkScope::All
+            };
+            let audit = links::check_anchors(page, anchors, scope).await?;
+            let broken: Vec<String> = audit.broken().map(|l| l.to_string()).collect();
+            if !broken.is_empty() {
+                return Err(Error::AssertionFailed(format!(
+                    "{} broken links:\n  {}",
+                    broken.len(),
+                    broken.join("\n  ")
+                )));
+            }
+        }
+        Action::AssertA11y(a) => {
+            info!("assert_a11y: severity >= {}", a.severity);
+            assert_a11y(page, ctx, a).await?;
+        }
         Action::AssertText(a) => {
             debug!("assert_text: '{}'", a.text);
-            let text = page.text().await?;
+            let text = scoped_text(page, ctx).await?;
             if !text.contains(&a.text) {
                 return Err(Error::AssertionFailed(format!(
                     "text '{}' not found",
@@ -261,23 +468,45 @@ pub async fn execute_with_context(
         }
         Action::ScrollTo(a) => {
             debug!("scroll_to: {}", a.target);
-            let selector = resolve_target(page, &a.target).await?;
-            scroll_into_view(page, &selector).await?;
+            let selector = resolve_target(page, ctx, &a.target).await?;
+            scroll_into_view(page, ctx, &selector).await?;
+        }
+        Action::ScrollUntil(a) => {
+            info!("scroll_until: {} (max {})", a.target, a.max_scrolls);
+            scroll_until(page, ctx, a).await?;
+        }
+        Action::ScrollInfinite(a) => {
+            info!("scroll_infinite: idle_rounds={}", a.idle_rounds);
+            scroll_infinite(page, ctx, a).await?;
         }
         Action::WaitFor(a) => {
             debug!("wait_for: {}", a.selector);
-            page.wait_for(&a.selector, a.timeout_ms).await?;
+            if ctx.in_frame() {
+                wait_for_scoped(page, ctx, &a.selector, ElementState::Attached, a.timeout_ms)
+                    .await?;
+            } else {
+                page.wait_for(&a.selector, a.timeout_ms).await?;
+            }
         }
         Action::WaitForVisible(a) => {
             debug!("wait_for_visible: {}", a.selector);
-            page.wait_for_visible(&a.selector, a.timeout_ms).await?;
+            if ctx.in_frame() {
+                wait_for_scoped(page, ctx, &a.selector, ElementState::Visible, a.timeout_ms)
+                    .await?;
+            } else {
+                page.wait_for_visible(&a.selector, a.timeout_ms).await?;
+            }
         }
         Action::WaitForHidden(a) => {
             debug!("wait_for_hidden: {}", a.selector);
-            page.wait_for_hidden(&a.selector, a.timeout_ms).await?;
+            if ctx.in_frame() {
+                wait_for_scoped(page, ctx, &a.selector, ElementState::Hidden, a.timeout_ms).await?;
+            } else {
+                page.wait_for_hidden(&a.selector, a.timeout_ms).await?;
+            }
         }
         Action::IfTextExists(a) => {
-            let text = page.text().await?;
+            let text = scoped_text(page, ctx).await?;
             let exists = text.contains(&a.text);
             debug!("if_text_exists '{}': {}", a.text, exists);
             let actions = if exists {
@@ -290,7 +519,7 @@ pub async fn execute_with_context(
             }
         }
         Action::IfSelectorExists(a) => {
-            let exists = element_exists(page, &a.selector).await?;
+            let exists = element_exists(page, ctx, &a.selector).await?;
             debug!("if_selector_exists '{}': {}", a.selector, exists);
             let actions = if exists {
                 &a.then_actions
@@ -314,35 +543,62 @@ pub async fn execute_with_context(
             let path = ctx.resolve_path(&a.path);
             info!("include: {}", path.display());
 
-            // Build params from the include action
-            let mut params = Params::new();
-            for (k, v) in &a.params {
-                params = params.set(k.clone(), v.clone());
-            }
-
-            // Load the included config
-            let included_config = Config::load_with_params(&path, &params).map_err(|e| {
-                Error::Config(format!(
-                    "failed to load include '{}': {}",
-                    path.display(),
-                    e
-                ))
-            })?;
-
-            // Create child context with the included file's directory as base
-            let child_base = path.parent().unwrap_or(Path::new("."));
-            let child_ctx = ctx.child(child_base)?;
+            // Check for cycles before loading; relative paths inside resolve from its directory
+            let child_ctx = ctx.child(&path)?;
+            let included_config = a.load(&path)?;
 
             // Execute included actions
             for action in &included_config.actions {
                 Box::pin(execute_with_context(page, action, &child_ctx)).await?;
             }
         }
+        Action::AgentTask(a) => agent_task::execute(page, a, ctx).await?,
+        Action::WithinFrame(a) => {
+            info!("within_frame: {}", a.frame);
+            let frame_ctx = ctx.within_frame(&a.frame);
+            wait_for_frame(page, &frame_ctx, &a.frame, a.timeout_ms).await?;
+            for action in &a.actions {
+                Box::pin(execute_with_context(page, action, &frame_ctx)).await?;
+            }
+        }
+        Action::Upload(a) => {
+            info!("upload: {} <- {}", a.target, a.file);
+            let selector = resolve_target(page, ctx, &a.target).await?;
+            upload_file(page, ctx, &selector, a).await?;
+        }
+        Action::WaitForDownload(a) => {
+            info!("wait_for_download (timeout: {}ms)", a.timeout_ms);
+            let mut downloads = ctx.downloads.lock().await;
+            let info = downloads.wait(a.timeout_ms).await?;
+            info!("downloaded: {} ({} bytes)", info.filename, info.size);
+            let mut path = info.path;
+            if let Some(ref dest) = a.save_as {
+                let saved = downloads.save_as(dest)?;
+                info!("saved download to: {}", saved.path.display());
+                path = saved.path;
+            }
+            ctx.variables
+                .lock()
+                .await
+                .set("download_path", path.display().to_string());
+        }
+        Action::AssertDownload(a) => {
+            debug!("assert_download");
+            let downloads = ctx.downloads.lock().await;
+            let info = downloads.last().ok_or_else(|| {
+                Error::AssertionFailed("no download recorded (use wait_for_download first)".into())
+            })?;
+            assert_download(info, a)?;
+        }
     }
     Ok(())
 }
 
-async fn wait_for_email(page: &Page, action: &WaitForEmailAction) -> Result<()> {
+async fn wait_for_email(
+    page: &Page,
+    ctx: &ExecutionContext,
+    action: &WaitForEmailAction,
+) -> Result<()> {
     let imap = ImapConfig::from(&action.imap);
 
     let criteria = build_email_criteria(&action.filter);
@@ -352,16 +608,21 @@ async fn wait_for_email(page: &Page, action: &WaitForEmailAction) -> Result<()>
         ChronoDuration::milliseconds(action.poll_interval_ms as i64),
     );
 
-    let mut client = AsyncImapClient::connect(&imap)
-        .await
-        .map_err(|e| Error::ActionFailed(e.to_string()))?;
-
-    let msg = client
-        .wait_for_message(&criteria, &options)
-        .await
-        .map_err(|e| Error::ActionFailed(e.to_string()))?;
+    let (link, code) = match imap.protocol {
+        Protocol::Imap => receive_imap(&imap, &criteria, &options, action).await?,
+        Protocol::Pop3 => receive_pop3(imap, criteria, options, action).await?,
+        Protocol::Gmail => receive_gmail(imap, criteria, options, action).await?,
+    };
 
-    let (link, code) = extract_email_values(&msg, &action.extract)?;
+    {
+        let mut variables = ctx.variables.lock().await;
+        if let Some(ref link) = link {
+            variables.set("email_link", link);
+        }
+        if let Some(ref code) = code {
+            variables.set("email_code", code);
+        }
+    }
 
     match &action.action {
         Some(EmailAction::OpenLink(_)) => {
@@ -376,7 +637,12 @@ async fn wait_for_email(page: &Page, action: &WaitForEmailAction) -> Result<()>
                 Error::ActionFailed("no code extracted from email".into())
             })?;
             info!("email code: {}", code);
-            page.fill(&fill.selector, &code).await?;
+            let target = Target {
+                selector: Some(fill.selector.clone()),
+                ..Default::default()
+            };
+            let selector = resolve_target(page, ctx, &target).await?;
+            fill_selector(page, ctx, &selector, &code, false).await?;
         }
         None => {
             if let Some(link) = link {
@@ -391,17 +657,146 @@ async fn wait_for_email(page: &Page, action: &WaitForEmailAction) -> Result<()>
     Ok(())
 }
 
+/// Wait for the email, extract the link and code, then clean it up.
+async fn receive_imap(
+    imap: &ImapConfig,
+    criteria: &SearchCriteria,
+    options: &WaitOptions,
+    action: &WaitForEmailAction,
+) -> Result<(Option<String>, Option<String>)> {
+    let mut client = AsyncImapClient::connect(imap)
+        .await
+        .map_err(|e| Error::ActionFailed(e.to_string()))?;
+
+    let msg = client
+        .wait_for_message(criteria, options)
+        .await
+        .map_err(|e| Error::ActionFailed(e.to_string()))?;
+
+    debug!("email {} found in {}", msg.uid, msg.mailbox);
+    let values = extract_email_values(&msg, &action.extract)?;
+
+    if let Some(ref cleanup) = action.cleanup {
+        clean_up_email(&mut client, msg.uid, cleanup).await?;
+    }
+    let _ = client.logout().await;
+    Ok(values)
+}
+
+/// [`receive_imap`] over POP3, whose client blocks, on a blocking thread.
+async fn receive_pop3(
+    pop3: ImapConfig,
+    criteria: SearchCriteria,
+    options: WaitOptions,
+    action: &WaitForEmailAction,
+) -> Result<(Option<String>, Option<String>)> {
+    let extract = action.extract.clone();
+    let cleanup = action.cleanup.clone().unwrap_or_default();
+    // Fail before waiting for, and consuming, an email that can't be cleaned up
+    if cleanup.move_to.is_some() {
+        return Err(Error::Config(
+            "wait_for_email: POP3 can't move emails; use cleanup.delete".into(),
+        ));
+    }
+    tokio::task::spawn_blocking(move || {
+        let mut client =
+            Pop3Client::connect(&pop3).map_err(|e| Error::ActionFailed(e.to_string()))?;
+        let msg = client
+            .wait_for_message(&criteria, &options)
+            .map_err(|e| Error::ActionFailed(e.to_string()))?;
+
+        debug!("email {} found over POP3", msg.uid);
+        let values = extract_email_values(&msg, &extract)?;
+
+        if cleanup.delete {
+            client
+                .delete(msg.uid)
+                .and_then(|_| client.quit())
+                .map_err(|e| Error::ActionFailed(format!("email cleanup failed: {}", e)))?;
+        }
+        Ok(values)
+    })
+    .await
+    .map_err(|e| Error::ActionFailed(e.to_string()))?
+}
+
+/// [`receive_imap`] over the Gmail API, whose client blocks, on a blocking
+/// thread.
+async fn receive_gmail(
+    gmail: ImapConfig,
+    criteria: SearchCriteria,
+    options: WaitOptions,
+    action: &WaitForEmailAction,
+) -> Result<(Option<String>, Option<String>)> {
+    let extract = action.extract.clone();
+    let cleanup = action.cleanup.clone().unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        let mut client =
+            GmailClient::connect(&gmail).map_err(|e| Error::ActionFailed(e.to_string()))?;
+        let msg = client
+            .wait_for_message(&criteria, &options)
+            .map_err(|e| Error::ActionFailed(e.to_string()))?;
+
+        let id = msg.gmail_id.clone().unwrap_or_default();
+        debug!("email {} found in {}", id, msg.mailbox);
+        let values = extract_email_values(&msg, &extract)?;
+
+        let result = match cleanup.move_to {
+            Some(ref label) => client.move_to(&id, label),
+            None if cleanup.delete => client.delete(&id),
+            None => Ok(()),
+        };
+        result.map_err(|e| Error::ActionFailed(format!("email cleanup failed: {}", e)))?;
+        Ok(values)
+    })
+    .await
+    .map_err(|e| Error::ActionFailed(e.to_string()))?
+}
+
+async fn clean_up_email(
+    client: &mut AsyncImapClient,
+    uid: u32,
+    cleanup: &EmailCleanup,
+) -> Result<()> {
+    let result = match cleanup.move_to {
+        Some(ref mailbox) => client.move_to(uid, mailbox).await,
+        None if cleanup.delete => client.delete(uid).await,
+        None => Ok(()),
+    };
+    result.map_err(|e| Error::ActionFailed(format!("email cleanup failed: {}", e)))
+}
+
 fn build_email_criteria(filter: &EmailFilterAction) -> SearchCriteria {
     let mut criteria = SearchCriteria::new()
         .unseen_only(filter.unseen_only)
-        .mark_seen(filter.mark_seen);
+        .mark_seen(filter.mark_seen)
+        .mailboxes(filter.mailboxes.iter().cloned())
+        .include_junk(filter.include_junk);
 
     if let Some(ref from) = filter.from {
         criteria = criteria.from(from.clone());
     }
+    if let Some(ref to) = filter.to {
+        criteria = criteria.to(to.clone());
+    }
+    if let Some(ref cc) = filter.cc {
+        criteria = criteria.cc(cc.clone());
+    }
     if let Some(ref subject) = filter.subject_contains {
         criteria = criteria.subject_contains(subject.clone());
     }
+    if let Some(ref body) = filter.body_contains {
+        criteria = criteria.body_contains(body.clone());
+    }
+    for (name, value) in &filter.headers {
+        criteria = criteria.header(name.clone(), value.clone());
+    }
+    if let Some(bytes) = filter.larger_than {
+        criteria = criteria.larger(bytes);
+    }
+    if let Some(bytes) = filter.smaller_than {
+        criteria = criteria.smaller(bytes);
+    }
     if let Some(minutes) = filter.since_minutes {
         criteria = criteria.since_minutes(minutes);
     }
@@ -424,62 +819,764 @@ fn extract_email_values(
     }
 
     if let Some(ref code_cfg) = extract.code {
-        let re = Regex::new(&code_cfg.regex)
-            .map_err(|e| Error::ActionFailed(format!("invalid code regex: {}", e)))?;
-        code = extract_code(msg, &re);
+        code = match code_cfg.regex {
+            Some(ref regex) => {
+                let re = Regex::new(regex)
+                    .map_err(|e| Error::ActionFailed(format!("invalid code regex: {}", e)))?;
+                extract_code(msg, &re)
+            }
+            None => otp::best(msg),
+        };
     }
 
     Ok((link, code))
 }
 
-/// Resolve a Target to a CSS selector.
-pub async fn resolve_target(page: &Page, target: &Target) -> Result<String> {
-    if let Some(ref sel) = target.selector {
-        return Ok(sel.clone());
+/// Attach a local file to a file input. Top-level inputs go through CDP;
+/// inputs inside frames get a `File` built in the page.
+async fn upload_file(
+    page: &Page,
+    ctx: &ExecutionContext,
+    selector: &str,
+    action: &UploadAction,
+) -> Result<()> {
+    use base64::Engine;
+
+    let path = ctx.base_path.join(&action.file);
+    if !ctx.in_frame() {
+        let path = path.canonicalize().map_err(|e| {
+            Error::ActionFailed(format!(
+                "failed to read upload file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        page.upload_file(selector, &path.to_string_lossy()).await?;
+        return Ok(());
     }
-    if let Some(ref txt) = target.text {
-        let js = FIND_BY_TEXT_JS.replace("arguments[0]", &serde_json::to_string(txt).unwrap());
-        let result: Option<String> = page.evaluate(&js).await?;
-        if let Some(sel) = result {
-            return Ok(sel);
+
+    let data = std::fs::read(&path).map_err(|e| {
+        Error::ActionFailed(format!(
+            "failed to read upload file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "upload".into());
+    let body = format!(
+        r#"const input = s.doc.querySelector({sel});
+            if (!input) return 'element_not_found';
+            if (input.tagName !== 'INPUT' || input.type !== 'file') return 'not_file_input';
+            const bin = atob({data});
+            const bytes = new Uint8Array(bin.length);
+            for (let i = 0; i < bin.length; i++) bytes[i] = bin.charCodeAt(i);
+            const dt = new DataTransfer();
+            dt.items.add(new s.win.File([bytes], {name}, {{ type: {mime} }}));
+            input.files = dt.files;
+            input.dispatchEvent(new Event('input', {{ bubbles: true }}));
+            input.dispatchEvent(new Event('change', {{ bubbles: true }}));
+            return 'ok';"#,
+        sel = serde_json::to_string(selector).unwrap(),
+        data = serde_json::to_string(&base64::engine::general_purpose::STANDARD.encode(&data))
+            .unwrap(),
+        name = serde_json::to_string(&name).unwrap(),
+        mime = serde_json::to_string(mime_type(&path)).unwrap(),
+    );
+    let result: String = frame::evaluate(page, &ctx.frames, &body, "'element_not_found'").await?;
+    match result.as_str() {
+        "ok" => Ok(()),
+        "not_file_input" => Err(Error::ActionFailed(format!(
+            "{} is not a file input",
+            action.target
+        ))),
+        _ => Err(Error::ActionFailed(format!(
+            "upload target {} not found",
+            action.target
+        ))),
+    }
+}
+
+/// Best-effort MIME type from a file extension.
+fn mime_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    match ext.as_deref() {
+        Some("txt") => "text/plain",
+        Some("csv") => "text/csv",
+        Some("html" | "htm") => "text/html",
+        Some("json") => "application/json",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+fn assert_download(info: &super::download::DownloadInfo, a: &AssertDownloadAction) -> Result<()> {
+    if let Some(ref name) = a.filename {
+        if &info.filename != name {
+            return Err(Error::AssertionFailed(format!(
+                "download is '{}', expected '{}'",
+                info.filename, name
+            )));
+        }
+    }
+    if let Some(ref part) = a.filename_contains {
+        if !info.filename.contains(part.as_str()) {
+            return Err(Error::AssertionFailed(format!(
+                "download '{}' does not contain '{}'",
+                info.filename, part
+            )));
         }
-        return Err(Error::ActionFailed(format!(
-            "element with text '{}' not found",
-            txt
-        )));
     }
-    Err(Error::ActionFailed(
-        "either selector or text must be provided".into(),
-    ))
+    if let Some(min) = a.min_bytes {
+        if info.size < min {
+            return Err(Error::AssertionFailed(format!(
+                "download '{}' is {} bytes, expected at least {}",
+                info.filename, info.size, min
+            )));
+        }
+    }
+    if let Some(max) = a.max_bytes {
+        if info.size > max {
+            return Err(Error::AssertionFailed(format!(
+                "download '{}' is {} bytes, expected at most {}",
+                info.filename, info.size, max
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Capture the page or element and compare it with the baseline, writing
+/// the baseline instead when it is missing or `update_baselines` is set.
+async fn assert_screenshot(
+    page: &Page,
+    ctx: &ExecutionContext,
+    a: &AssertScreenshotAction,
+) -> Result<()> {
+    let mut actual = visual::decode(&page.screenshot().await?)?;
+    if let Some(ref selector) = a.selector {
+        let body = format!(
+            r#"const el = s.doc.querySelector({});
+            if (!el) return null;
+            el.scrollIntoView({{block:'center'}});
+            const r = el.getBoundingClientRect();
+            return {{ x: s.x + r.x, y: s.y + r.y, w: r.width, h: r.height, dpr: window.devicePixelRatio }};"#,
+            serde_json::to_string(selector).unwrap()
+        );
+        let rect: Option<serde_json::Value> =
+            frame::evaluate(page, &ctx.frames, &body, "null").await?;
+        let rect =
+            rect.ok_or_else(|| Error::ActionFailed(format!("element not found: {}", selector)))?;
+        // Re-capture: scrolling the element into view moved the page.
+        actual = visual::decode(&page.screenshot().await?)?;
+        let f = |k: &str| rect[k].as_f64().unwrap_or(0.0);
+        actual = visual::crop(&actual, f("x"), f("y"), f("w"), f("h"), f("dpr").max(1.0));
+    }
+
+    let baseline_path = Path::new(&a.baseline);
+    let baseline = match visual::load(baseline_path)? {
+        Some(baseline) if !ctx.update_baselines => baseline,
+        _ => {
+            info!("Writing baseline: {}", a.baseline);
+            return visual::save(&actual, baseline_path);
+        }
+    };
+
+    let comparison = visual::compare(&baseline, &actual, a.mode, a.tolerance);
+    debug!(
+        "assert_screenshot: difference {:.4} (threshold {})",
+        comparison.difference, a.threshold
+    );
+    if comparison.difference <= a.threshold {
+        return Ok(());
+    }
+    let diff_path = a.diff_path();
+    visual::save(&comparison.diff, &diff_path)?;
+    let size = if baseline.dimensions() == actual.dimensions() {
+        String::new()
+    } else {
+        format!(
+            ", size {}x{} vs baseline {}x{}",
+            actual.width(),
+            actual.height(),
+            baseline.width(),
+            baseline.height()
+        )
+    };
+    Err(Error::AssertionFailed(format!(
+        "screenshot differs from {} by {:.2}% (threshold {:.2}%{}); diff: {}",
+        a.baseline,
+        comparison.difference * 100.0,
+        a.threshold * 100.0,
+        size,
+        diff_path.display()
+    )))
+}
+
+/// Capture text or sanitized HTML and compare it with the golden file,
+/// writing the golden file instead when it is missing or `update_baselines` is set.
+async fn assert_snapshot(
+    page: &Page,
+    ctx: &ExecutionContext,
+    a: &AssertSnapshotAction,
+) -> Result<()> {
+    let ignore = snapshot::compile(&a.ignore)?;
+    let root = match a.selector {
+        Some(ref selector) => format!(
+            "s.doc.querySelector({})",
+            serde_json::to_string(selector).unwrap()
+        ),
+        None => "s.doc.body".to_string(),
+    };
+    let capture = match a.format {
+        SnapshotFormat::Text => "el.innerText".to_string(),
+        SnapshotFormat::Html => format!("{}(el)", snapshot::SANITIZED_HTML_JS),
+    };
+    let body = format!(
+        "const el = {}; if (!el) return null; return {};",
+        root, capture
+    );
+    let captured: Option<String> = frame::evaluate(page, &ctx.frames, &body, "null").await?;
+    let captured = captured.ok_or_else(|| {
+        Error::ActionFailed(format!(
+            "element not found: {}",
+            a.selector.as_deref().unwrap_or("body")
+        ))
+    })?;
+    let actual = snapshot::normalize(&captured, &ignore);
+
+    let golden_path = Path::new(&a.golden);
+    let expected = match snapshot::load(golden_path)? {
+        Some(expected) if !ctx.update_baselines => expected,
+        _ => {
+            info!("Writing snapshot: {}", a.golden);
+            return snapshot::save(&actual, golden_path);
+        }
+    };
+
+    let Some(difference) = snapshot::first_difference(&expected, &actual) else {
+        return Ok(());
+    };
+    let actual_path = a.actual_path();
+    snapshot::save(&actual, &actual_path)?;
+    Err(Error::AssertionFailed(format!(
+        "snapshot differs from {} at {}; actual: {}",
+        a.golden,
+        difference,
+        actual_path.display()
+    )))
+}
+
+/// Count the matches of a selector and find the first one's viewport center.
+const PROBE_JS: &str = r#"((s, selector) => {
+    let els;
+    try { els = s.doc.querySelectorAll(selector); } catch { return { matches: 0, point: null }; }
+    const r = els[0]?.getBoundingClientRect();
+    const point = r && r.width > 0 && r.height > 0
+        ? [s.x + r.x + r.width / 2, s.y + r.y + r.height / 2]
+        : null;
+    return { matches: els.length, point };
+})"#;
+
+#[derive(Default, Deserialize)]
+struct Probe {
+    matches: usize,
+    point: Option<(f64, f64)>,
 }
 
-async fn focus_element(page: &Page, selector: &str) -> Result<()> {
-    let js = format!(
-        "document.querySelector({})?.focus()",
+/// Resolve a Target to a CSS selector, recording how in `ctx.resolved`.
+/// Selectors are used as written; other patterns go through
+/// [`target::resolve_js`], the same matching eoka-agent uses.
+pub async fn resolve_target(
+    page: &Page,
+    ctx: &ExecutionContext,
+    target: &Target,
+) -> Result<String> {
+    let pattern = target.pattern().ok_or_else(|| {
+        Error::ActionFailed("target needs a selector, text, placeholder, label, role or id".into())
+    })?;
+    let selector = match pattern {
+        LivePattern::Css(ref sel) => Some(sel.clone()),
+        _ => {
+            let body = format!("return {};", target::resolve_js("s.doc", &pattern));
+            let resolved: Option<Resolved> =
+                frame::evaluate(page, &ctx.frames, &body, "null").await?;
+            resolved.filter(|r| r.found).map(|r| r.selector)
+        }
+    };
+    let searched = match ctx.in_frame() {
+        true => format!("{} in the frame", pattern.searched()),
+        false => pattern.searched(),
+    };
+    let probe = match selector {
+        Some(ref sel) => {
+            let body = format!(
+                "return {}(s, {});",
+                PROBE_JS,
+                serde_json::to_string(sel).unwrap()
+            );
+            let probe: Option<Probe> = frame::evaluate(page, &ctx.frames, &body, "null").await?;
+            probe.unwrap_or_default()
+        }
+        None => Probe::default(),
+    };
+    let resolution = Resolution {
+        target: target.to_string(),
+        searched,
+        selector,
+        matches: probe.matches,
+        point: probe.point,
+    };
+    debug!("resolved {}", resolution);
+    let result = resolution.selector.clone().ok_or_else(|| {
+        Error::ActionFailed(format!(
+            "element with {} not found; searched {}",
+            target, resolution.searched
+        ))
+    });
+    ctx.record(resolution).await;
+    result
+}
+
+async fn focus_element(page: &Page, ctx: &ExecutionContext, selector: &str) -> Result<()> {
+    let body = format!(
+        "s.doc.querySelector({})?.focus();",
         serde_json::to_string(selector).unwrap()
     );
-    page.execute(&js).await?;
+    frame::execute(page, &ctx.frames, &body).await?;
     Ok(())
 }
 
-async fn element_exists(page: &Page, selector: &str) -> Result<bool> {
-    let js = format!(
-        "!!document.querySelector({})",
+async fn element_exists(page: &Page, ctx: &ExecutionContext, selector: &str) -> Result<bool> {
+    let body = format!(
+        "return !!s.doc.querySelector({});",
         serde_json::to_string(selector).unwrap()
     );
-    Ok(page.evaluate(&js).await?)
+    frame::evaluate(page, &ctx.frames, &body, "false").await
 }
 
-async fn scroll_into_view(page: &Page, selector: &str) -> Result<()> {
-    let js = format!(
-        "document.querySelector({})?.scrollIntoView({{behavior:'smooth',block:'center'}})",
+async fn scroll_into_view(page: &Page, ctx: &ExecutionContext, selector: &str) -> Result<()> {
+    let body = format!(
+        "s.doc.querySelector({})?.scrollIntoView({{behavior:'smooth',block:'center'}});",
         serde_json::to_string(selector).unwrap()
     );
-    page.execute(&js).await?;
+    frame::execute(page, &ctx.frames, &body).await?;
     page.wait(200).await;
     Ok(())
 }
 
+/// Audit the current scope for accessibility violations, failing on any at
+/// or above the action's severity.
+async fn assert_a11y(page: &Page, ctx: &ExecutionContext, a: &AssertA11yAction) -> Result<()> {
+    let body = format!("return {};", a11y::rules_js("s.doc"));
+    let json: String = frame::evaluate(page, &ctx.frames, &body, "'[]'").await?;
+    let mut violations = a11y::parse(&json)?;
+    // Tab order is page-wide, so frame-scoped audits skip the keyboard check
+    if !ctx.in_frame() && !a.ignore.iter().any(|r| r == "focus-trap") {
+        violations.extend(a11y::focus_trap(page).await?);
+    }
+    violations.retain(|v| !a.ignore.contains(&v.rule));
+    let (failing, below): (Vec<_>, Vec<_>) = violations
+        .into_iter()
+        .partition(|v| v.severity >= a.severity);
+    for v in &below {
+        debug!("assert_a11y: {}", v);
+    }
+    if failing.is_empty() {
+        return Ok(());
+    }
+    let lines: Vec<String> = failing.iter().map(|v| v.to_string()).collect();
+    Err(Error::AssertionFailed(format!(
+        "{} accessibility violations at or above {}:\n  {}",
+        failing.len(),
+        a.severity,
+        lines.join("\n  ")
+    )))
+}
+
+/// HTTP(S) link targets in the current scope, fragments stripped, in page order.
+async fn scoped_links(
+    page: &Page,
+    ctx: &ExecutionContext,
+    selector: Option<&str>,
+) -> Result<Vec<links::Anchor>> {
+    let body = format!(
+        "return JSON.stringify({}(s.doc, {}));",
+        links::ANCHORS_JS,
+        serde_json::to_string(selector.unwrap_or("a[href]")).unwrap()
+    );
+    let json: String = frame::evaluate(page, &ctx.frames, &body, "'[]'").await?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Visible text of the current scope.
+async fn scoped_text(page: &Page, ctx: &ExecutionContext) -> Result<String> {
+    if !ctx.in_frame() {
+        return Ok(page.text().await?);
+    }
+    let body = "return s.doc.body.innerText || '';";
+    frame::evaluate(page, &ctx.frames, body, "''").await
+}
+
+/// Viewport center of an element in the current scope, scrolled into view.
+async fn element_center(
+    page: &Page,
+    ctx: &ExecutionContext,
+    selector: &str,
+) -> Result<Option<(f64, f64)>> {
+    let body = format!(
+        r#"const el = s.doc.querySelector({});
+        if (!el) return null;
+        el.scrollIntoView({{block:'center'}});
+        const r = el.getBoundingClientRect();
+        if (r.width === 0 || r.height === 0) return null;
+        return {{ x: s.x + r.x + r.width / 2, y: s.y + r.y + r.height / 2 }};"#,
+        serde_json::to_string(selector).unwrap()
+    );
+    let coords: Option<serde_json::Value> =
+        frame::evaluate(page, &ctx.frames, &body, "null").await?;
+    Ok(coords.map(|c| {
+        (
+            c["x"].as_f64().unwrap_or(0.0),
+            c["y"].as_f64().unwrap_or(0.0),
+        )
+    }))
+}
+
+/// Dispatch a real mouse click at viewport coordinates.
+async fn click_at(page: &Page, x: f64, y: f64) -> Result<()> {
+    use eoka::cdp::{MouseButton, MouseEventType};
+    let session = page.session();
+    session
+        .dispatch_mouse_event(MouseEventType::MouseMoved, x, y, None, None)
+        .await?;
+    session
+        .dispatch_mouse_event(
+            MouseEventType::MousePressed,
+            x,
+            y,
+            Some(MouseButton::Left),
+            Some(1),
+        )
+        .await?;
+    session
+        .dispatch_mouse_event(
+            MouseEventType::MouseReleased,
+            x,
+            y,
+            Some(MouseButton::Left),
+            Some(1),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Click an element in the current scope by coordinates.
+/// Returns `false` if it is missing or has no size.
+async fn click_in_scope(page: &Page, ctx: &ExecutionContext, selector: &str) -> Result<bool> {
+    match element_center(page, ctx, selector).await? {
+        Some((x, y)) => {
+            click_at(page, x, y).await?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Mark every element matching `target` and return their selectors.
+async fn mark_all(
+    page: &Page,
+    ctx: &ExecutionContext,
+    target: &Target,
+    max: Option<usize>,
+) -> Result<Vec<String>> {
+    let pattern = target.pattern().ok_or_else(|| {
+        Error::ActionFailed("target needs a selector, text, placeholder, label, role or id".into())
+    })?;
+    let token = MARK_TOKEN.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let body = format!(
+        "return {}({}, {}, {});",
+        MARK_ALL_JS,
+        target::find_js("s.doc", &pattern),
+        serde_json::to_string(&format!("m{}", token)).unwrap(),
+        serde_json::to_string(&max).unwrap()
+    );
+    frame::evaluate(page, &ctx.frames, &body, "[]").await
+}
+
+/// Remove the markers [`mark_all`] added.
+async fn unmark_all(page: &Page, ctx: &ExecutionContext, marked: &[String]) -> Result<()> {
+    let body = format!(
+        "for (const sel of {}) s.doc.querySelector(sel)?.removeAttribute('data-eoka-all');",
+        serde_json::to_string(marked).unwrap()
+    );
+    frame::execute(page, &ctx.frames, &body).await?;
+    Ok(())
+}
+
+/// Click every match, skipping elements that disappeared after earlier clicks.
+async fn click_all(page: &Page, ctx: &ExecutionContext, action: &ClickAllAction) -> Result<()> {
+    let marked = mark_all(page, ctx, &action.target, action.max).await?;
+    let mut clicked = 0;
+    for (i, selector) in marked.iter().enumerate() {
+        if i > 0 && action.delay_ms > 0 {
+            page.wait(action.delay_ms).await;
+        }
+        let hit = if ctx.in_frame() {
+            click_in_scope(page, ctx, selector).await?
+        } else {
+            page.try_click(selector).await?
+        };
+        if hit {
+            clicked += 1;
+        }
+    }
+    unmark_all(page, ctx, &marked).await?;
+    info!("click_all: clicked {} of {}", clicked, marked.len());
+    Ok(())
+}
+
+/// Clear and type into an input in the current scope.
+async fn fill_selector(
+    page: &Page,
+    ctx: &ExecutionContext,
+    selector: &str,
+    value: &str,
+    human: bool,
+) -> Result<()> {
+    if ctx.in_frame() {
+        clear_in_scope(page, ctx, selector).await?;
+        page.type_text(value).await?;
+    } else if human {
+        deterministic::human_fill(page, selector, value).await?;
+    } else {
+        page.fill(selector, value).await?;
+    }
+    Ok(())
+}
+
+/// Click if present, returning whether anything was clicked.
+async fn try_click_selector(page: &Page, ctx: &ExecutionContext, selector: &str) -> Result<bool> {
+    if ctx.in_frame() {
+        click_in_scope(page, ctx, selector).await
+    } else {
+        Ok(page.try_click(selector).await?)
+    }
+}
+
+/// Focus and clear an input in the current scope.
+async fn clear_in_scope(page: &Page, ctx: &ExecutionContext, selector: &str) -> Result<()> {
+    let body = format!(
+        r#"const el = s.doc.querySelector({});
+        if (!el) return false;
+        el.focus();
+        if ('value' in el) el.value = ''; else el.textContent = '';
+        el.dispatchEvent(new Event('input', {{ bubbles: true }}));
+        return true;"#,
+        serde_json::to_string(selector).unwrap()
+    );
+    let found: bool = frame::evaluate(page, &ctx.frames, &body, "false").await?;
+    if !found {
+        return Err(Error::ActionFailed(format!(
+            "element '{}' not found in frame",
+            selector
+        )));
+    }
+    Ok(())
+}
+
+/// Element states for scoped waits.
+#[derive(Debug, Clone, Copy)]
+enum ElementState {
+    Attached,
+    Visible,
+    Hidden,
+}
+
+/// Poll until an element in the current scope reaches `state`.
+async fn wait_for_scoped(
+    page: &Page,
+    ctx: &ExecutionContext,
+    selector: &str,
+    state: ElementState,
+    timeout_ms: u64,
+) -> Result<()> {
+    let body = format!(
+        r#"const el = s.doc.querySelector({});
+        if (!el) return 'missing';
+        const r = el.getBoundingClientRect();
+        const st = s.win.getComputedStyle(el);
+        const visible = r.width > 0 && r.height > 0 && st.visibility !== 'hidden' && st.display !== 'none';
+        return visible ? 'visible' : 'hidden';"#,
+        serde_json::to_string(selector).unwrap()
+    );
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+    loop {
+        let found: String = frame::evaluate(page, &ctx.frames, &body, "'missing'").await?;
+        let done = match state {
+            ElementState::Attached => found != "missing",
+            ElementState::Visible => found == "visible",
+            ElementState::Hidden => found != "visible",
+        };
+        if done {
+            return Ok(());
+        }
+        if std::time::Instant::now() >= deadline {
+            return Err(Error::Timeout(format!(
+                "element '{}' not {:?} in frame after {}ms",
+                selector, state, timeout_ms
+            )));
+        }
+        page.wait(100).await;
+    }
+}
+
+/// Poll until text appears in the current scope.
+async fn wait_for_scoped_text(
+    page: &Page,
+    ctx: &ExecutionContext,
+    text: &str,
+    timeout_ms: u64,
+) -> Result<()> {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+    loop {
+        if scoped_text(page, ctx).await?.contains(text) {
+            return Ok(());
+        }
+        if std::time::Instant::now() >= deadline {
+            return Err(Error::Timeout(format!(
+                "text '{}' not found in frame after {}ms",
+                text, timeout_ms
+            )));
+        }
+        page.wait(100).await;
+    }
+}
+
+/// Poll until text is no longer in the current scope.
+pub(super) async fn wait_for_text_gone(
+    page: &Page,
+    ctx: &ExecutionContext,
+    text: &str,
+    timeout_ms: u64,
+) -> Result<()> {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+    loop {
+        if !scoped_text(page, ctx).await?.contains(text) {
+            return Ok(());
+        }
+        if std::time::Instant::now() >= deadline {
+            return Err(Error::Timeout(format!(
+                "text '{}' still present after {}ms",
+                text, timeout_ms
+            )));
+        }
+        page.wait(100).await;
+    }
+}
+
+/// Resolves once the element's box has been unchanged for `frames` animation
+/// frames: `'stable'`, `'moving'` after the timeout, or `'missing'`. Hidden
+/// tabs get no animation frames, so they poll instead.
+const STABLE_JS: &str = r#"((doc, win, sel, frames, timeout) => new Promise(resolve => {
+    const start = win.performance.now();
+    let last = null;
+    let same = 0;
+    const next = () => doc.hidden ? win.setTimeout(tick, 16) : win.requestAnimationFrame(tick);
+    const tick = () => {
+        const el = doc.querySelector(sel);
+        if (!el) return resolve('missing');
+        const r = el.getBoundingClientRect();
+        const key = [r.x, r.y, r.width, r.height].map(v => Math.round(v)).join(',');
+        same = key === last ? same + 1 : 0;
+        last = key;
+        if (same >= frames) return resolve('stable');
+        if (win.performance.now() - start > timeout) return resolve('moving');
+        next();
+    };
+    next();
+}))"#;
+
+/// Wait for an element in the current scope to stop moving.
+pub(super) async fn wait_for_element_stable(
+    page: &Page,
+    ctx: &ExecutionContext,
+    selector: &str,
+    frames: u32,
+    timeout_ms: u64,
+) -> Result<()> {
+    let body = format!(
+        "return {}(s.doc, s.win, {}, {}, {});",
+        STABLE_JS,
+        serde_json::to_string(selector).unwrap(),
+        frames,
+        timeout_ms
+    );
+    let state: String = frame::evaluate(page, &ctx.frames, &body, "'missing'").await?;
+    match state.as_str() {
+        "stable" => Ok(()),
+        "missing" => Err(Error::ActionFailed(format!(
+            "element not found: {}",
+            selector
+        ))),
+        _ => Err(Error::Timeout(format!(
+            "element '{}' still moving after {}ms",
+            selector, timeout_ms
+        ))),
+    }
+}
+
+/// Wait for a frame to exist and have loaded a document.
+async fn wait_for_frame(
+    page: &Page,
+    ctx: &ExecutionContext,
+    frame: &FrameLocator,
+    timeout_ms: u64,
+) -> Result<()> {
+    if frame.selector.is_none() && frame.name.is_none() && frame.url_contains.is_none() {
+        return Err(Error::Config(
+            "within_frame: one of selector, name, or url_contains is required".into(),
+        ));
+    }
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+    loop {
+        if frame::reachable(page, &ctx.frames).await? {
+            return Ok(());
+        }
+        if std::time::Instant::now() >= deadline {
+            return Err(Error::Timeout(format!(
+                "frame {} not found or not loaded after {}ms",
+                frame, timeout_ms
+            )));
+        }
+        page.wait(100).await;
+    }
+}
+
+/// Block until a line is read from stdin (or stdin closes), up to `timeout_ms`.
+async fn wait_for_enter(timeout_ms: Option<u64>) {
+    let read = tokio::task::spawn_blocking(|| std::io::stdin().read_line(&mut String::new()));
+    match timeout_ms {
+        Some(ms) => {
+            let _ = tokio::time::timeout(std::time::Duration::from_millis(ms), read).await;
+        }
+        None => {
+            let _ = read.await;
+        }
+    }
+}
+
 async fn scroll(page: &Page, direction: &ScrollDirection, amount: u32) -> Result<()> {
     let (x, y) = match direction {
         ScrollDirection::Up => (0, -(amount as i32 * 300)),
@@ -491,10 +1588,129 @@ async fn scroll(page: &Page, direction: &ScrollDirection, amount: u32) -> Result
     Ok(())
 }
 
-async fn try_click_any(page: &Page, action: &TryClickAnyAction) -> Result<()> {
+/// Press a single key or a modifier chord like `Ctrl+Shift+K`.
+async fn press_key(page: &Page, key: &str) -> Result<()> {
+    // A lone "+" is the plus key, not a chord.
+    if key.len() > 1 && key.contains('+') {
+        page.press_key(key).await?;
+    } else {
+        deterministic::press_key(page, key).await?;
+    }
+    Ok(())
+}
+
+/// Network-idle timeout between scrolls. Pages that never go idle
+/// (long polling, analytics beacons) just continue after this.
+const SCROLL_SETTLE_TIMEOUT_MS: u64 = 5000;
+
+/// Scroll the current scope down by most of a viewport.
+async fn scroll_page_down(page: &Page, ctx: &ExecutionContext) -> Result<()> {
+    let body = "s.win.scrollBy(0, s.win.innerHeight * 0.8);";
+    frame::execute(page, &ctx.frames, body).await
+}
+
+async fn settle_after_scroll(page: &Page, idle_ms: u64) {
+    if page
+        .wait_for_network_idle(idle_ms, SCROLL_SETTLE_TIMEOUT_MS)
+        .await
+        .is_err()
+    {
+        debug!("network not idle after scroll, continuing");
+    }
+}
+
+async fn target_present(page: &Page, ctx: &ExecutionContext, target: &Target) -> Result<bool> {
+    if let Some(ref sel) = target.selector {
+        return element_exists(page, ctx, sel).await;
+    }
+    Ok(resolve_target(page, ctx, target).await.is_ok())
+}
+
+async fn scroll_until(
+    page: &Page,
+    ctx: &ExecutionContext,
+    action: &ScrollUntilAction,
+) -> Result<()> {
+    for i in 0..=action.max_scrolls {
+        if target_present(page, ctx, &action.target).await? {
+            debug!("scroll_until: found {} after {} scrolls", action.target, i);
+            let selector = resolve_target(page, ctx, &action.target).await?;
+            scroll_into_view(page, ctx, &selector).await?;
+            return Ok(());
+        }
+        if i < action.max_scrolls {
+            scroll_page_down(page, ctx).await?;
+            settle_after_scroll(page, action.idle_ms).await;
+        }
+    }
+    Err(Error::ActionFailed(format!(
+        "{} not found after {} scrolls",
+        action.target, action.max_scrolls
+    )))
+}
+
+/// Page height plus optional item count — changes when new content loads.
+async fn content_size(
+    page: &Page,
+    ctx: &ExecutionContext,
+    item_selector: Option<&str>,
+) -> Result<(u64, u64)> {
+    let items = match item_selector {
+        Some(sel) => format!(
+            "s.doc.querySelectorAll({}).length",
+            serde_json::to_string(sel).unwrap()
+        ),
+        None => "0".to_string(),
+    };
+    let body = format!(
+        "return [(s.doc.scrollingElement || s.doc.documentElement).scrollHeight, {}];",
+        items
+    );
+    let size: (u64, u64) = frame::evaluate(page, &ctx.frames, &body, "[0, 0]").await?;
+    Ok(size)
+}
+
+async fn scroll_infinite(
+    page: &Page,
+    ctx: &ExecutionContext,
+    action: &ScrollInfiniteAction,
+) -> Result<()> {
+    let item_selector = action.item_selector.as_deref();
+    let mut last = content_size(page, ctx, item_selector).await?;
+    let mut idle = 0;
+    let mut scrolls = 0;
+
+    while scrolls < action.max_scrolls && idle < action.idle_rounds {
+        let body =
+            "s.win.scrollTo(0, (s.doc.scrollingElement || s.doc.documentElement).scrollHeight);";
+        frame::execute(page, &ctx.frames, body).await?;
+        settle_after_scroll(page, action.idle_ms).await;
+        scrolls += 1;
+
+        let size = content_size(page, ctx, item_selector).await?;
+        if size == last {
+            idle += 1;
+        } else {
+            idle = 0;
+            last = size;
+        }
+    }
+
+    info!(
+        "scroll_infinite: {} scrolls, height {}px, {} items",
+        scrolls, last.0, last.1
+    );
+    Ok(())
+}
+
+async fn try_click_any(
+    page: &Page,
+    ctx: &ExecutionContext,
+    action: &TryClickAnyAction,
+) -> Result<()> {
     if let Some(ref selectors) = action.selectors {
         for sel in selectors {
-            if page.try_click(sel).await? {
+            if try_click_selector(page, ctx, sel).await? {
                 debug!("try_click_any: clicked selector '{}'", sel);
                 return Ok(());
             }
@@ -503,11 +1719,11 @@ async fn try_click_any(page: &Page, action: &TryClickAnyAction) -> Result<()> {
     if let Some(ref texts) = action.texts {
         for txt in texts {
             let target = Target {
-                selector: None,
                 text: Some(txt.clone()),
+                ..Default::default()
             };
-            if let Ok(sel) = resolve_target(page, &target).await {
-                if page.try_click(&sel).await? {
+            if let Ok(sel) = resolve_target(page, ctx, &target).await {
+                if try_click_selector(page, ctx, &sel).await? {
                     debug!("try_click_any: clicked text '{}'", txt);
                     return Ok(());
                 }
@@ -518,21 +1734,88 @@ async fn try_click_any(page: &Page, action: &TryClickAnyAction) -> Result<()> {
     Ok(())
 }
 
-async fn select_option(page: &Page, selector: &str, value: &str, target: &Target) -> Result<()> {
-    let js = format!(
-        r#"(() => {{
-            const sel = document.querySelector({sel});
+/// Fill every recognized field of `form` in the current scope and return
+/// them. Unrecognized fields, and dropdowns with no matching option, are
+/// skipped.
+pub(super) async fn fill_form(
+    page: &Page,
+    ctx: &ExecutionContext,
+    form: &FormRef,
+    profile: &FormProfile,
+    human: bool,
+) -> Result<Vec<FilledField>> {
+    let body = format!(
+        "return JSON.stringify({}(s.doc, {}));",
+        autofill::FIELDS_JS,
+        serde_json::to_string(form).unwrap()
+    );
+    let json: String = frame::evaluate(page, &ctx.frames, &body, "'null'").await?;
+    let fields: Option<Vec<autofill::Field>> = serde_json::from_str(&json)?;
+    let fields =
+        fields.ok_or_else(|| Error::ActionFailed(format!("autofill: form {} not found", form)))?;
+
+    let mut filled = Vec::new();
+    for field in &fields {
+        let Some(kind) = FieldKind::classify(&field.autocomplete, &field.hints) else {
+            debug!("autofill: skipping '{}'", field.hints);
+            continue;
+        };
+        let values = profile.values(kind, field.max_length);
+        if values[0].is_empty() {
+            continue;
+        }
+        let selector = field.selector();
+        let value = if field.select {
+            let body = format!(
+                "const el = s.doc.querySelector({}); return el ? {}(el, {}) : null;",
+                serde_json::to_string(&selector).unwrap(),
+                autofill::SELECT_JS,
+                serde_json::to_string(&values).unwrap()
+            );
+            let picked: Option<String> = frame::evaluate(page, &ctx.frames, &body, "null").await?;
+            let Some(text) = picked else {
+                debug!("autofill: no option for {:?} in '{}'", kind, field.hints);
+                continue;
+            };
+            text
+        } else {
+            fill_selector(page, ctx, &selector, &values[0], human).await?;
+            values[0].clone()
+        };
+        debug!("autofill: {:?} = '{}'", kind, value);
+        filled.push(FilledField {
+            kind,
+            label: field.hints.clone(),
+            value,
+        });
+    }
+    info!(
+        "autofill: filled {} of {} fields",
+        filled.len(),
+        fields.len()
+    );
+    Ok(filled)
+}
+
+async fn select_option(
+    page: &Page,
+    ctx: &ExecutionContext,
+    selector: &str,
+    value: &str,
+    target: &Target,
+) -> Result<()> {
+    let body = format!(
+        r#"const sel = s.doc.querySelector({sel});
             if (!sel) return 'element_not_found';
             const opt = Array.from(sel.options).find(o => o.value === {val} || o.text === {val});
             if (!opt) return 'option_not_found';
             sel.value = opt.value;
             sel.dispatchEvent(new Event('change', {{ bubbles: true }}));
-            return 'ok';
-        }})()"#,
+            return 'ok';"#,
         sel = serde_json::to_string(selector).unwrap(),
         val = serde_json::to_string(value).unwrap()
     );
-    let result: String = page.evaluate(&js).await?;
+    let result: String = frame::evaluate(page, &ctx.frames, &body, "'element_not_found'").await?;
     match result.as_str() {
         "ok" => Ok(()),
         "element_not_found" => Err(Error::ActionFailed(format!(
@@ -547,17 +1830,57 @@ async fn select_option(page: &Page, selector: &str, value: &str, target: &Target
     }
 }
 
-async fn hover_element(page: &Page, selector: &str) -> Result<()> {
-    let js = format!(
-        r#"(() => {{
-            const el = document.querySelector({});
+/// Hover the parent, wait for the item to become visible, and click it with
+/// a real mouse event so the hover chain stays intact.
+async fn menu_click(page: &Page, ctx: &ExecutionContext, action: &MenuClickAction) -> Result<()> {
+    let parent = resolve_target(page, ctx, &action.parent).await?;
+    hover_element(page, ctx, &parent).await?;
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(action.timeout_ms);
+    let not_shown = || {
+        Error::Timeout(format!(
+            "menu item {} not visible {}ms after hovering {}",
+            action.item, action.timeout_ms, action.parent
+        ))
+    };
+    // Text items may not be in the DOM until the menu opens.
+    let item = loop {
+        match resolve_target(page, ctx, &action.item).await {
+            Ok(selector) => break selector,
+            Err(_) if std::time::Instant::now() < deadline => page.wait(100).await,
+            Err(_) => return Err(not_shown()),
+        }
+    };
+    let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+    wait_for_scoped(
+        page,
+        ctx,
+        &item,
+        ElementState::Visible,
+        remaining.as_millis() as u64,
+    )
+    .await
+    .map_err(|_| not_shown())?;
+
+    if !click_in_scope(page, ctx, &item).await? {
+        return Err(Error::ActionFailed(format!(
+            "menu item {} disappeared before it could be clicked",
+            action.item
+        )));
+    }
+    Ok(())
+}
+
+async fn hover_element(page: &Page, ctx: &ExecutionContext, selector: &str) -> Result<()> {
+    let body = format!(
+        r#"const el = s.doc.querySelector({});
             if (!el) return null;
             const rect = el.getBoundingClientRect();
-            return {{ x: rect.x + rect.width / 2, y: rect.y + rect.height / 2 }};
-        }})()"#,
+            return {{ x: s.x + rect.x + rect.width / 2, y: s.y + rect.y + rect.height / 2 }};"#,
         serde_json::to_string(selector).unwrap()
     );
-    let coords: Option<serde_json::Value> = page.evaluate(&js).await?;
+    let coords: Option<serde_json::Value> =
+        frame::evaluate(page, &ctx.frames, &body, "null").await?;
     if let Some(c) = coords {
         let x = c["x"].as_f64().unwrap_or(0.0);
         let y = c["y"].as_f64().unwrap_or(0.0);
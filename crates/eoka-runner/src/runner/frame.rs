@@ -0,0 +1,336 @@
+//! Frame scoping — resolves a chain of iframes to the document actions run in.
+//!
+//! Frames are reached through CDP rather than `contentDocument`, so
+//! cross-origin frames work too: an in-process frame's document is resolved
+//! from the iframe's DOM node, and an out-of-process frame gets its own
+//! target session.
+
+use crate::config::actions::FrameLocator;
+use crate::{Error, Result};
+use eoka::Page;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tracing::debug;
+
+/// Scope expression for the top-level document.
+const TOP_SCOPE_JS: &str = "({ doc: document, win: window, x: 0, y: 0 })";
+
+/// Object group for the remote objects a resolution creates.
+const OBJECT_GROUP: &str = "eoka-frame";
+
+/// Called on a document with a locator; returns the matching frame element.
+const FIND_FRAME_JS: &str = r#"function(loc) {
+    const frames = Array.from(this.querySelectorAll('iframe, frame'));
+    if (loc.selector) return this.querySelector(loc.selector);
+    if (loc.name) return frames.find(f => f.name === loc.name || f.id === loc.name) || null;
+    if (loc.url) return frames.find(f => {
+        let href = '';
+        try { href = f.contentWindow.location.href; } catch (e) {}
+        return (f.src || '').includes(loc.url) || href.includes(loc.url);
+    }) || null;
+    return null;
+}"#;
+
+/// Called on a frame element; returns its content box offset in its
+/// document's viewport.
+const OFFSET_JS: &str = r#"function() {
+    const r = this.getBoundingClientRect();
+    return [r.left + this.clientLeft, r.top + this.clientTop];
+}"#;
+
+/// The document an action runs in, as remote objects.
+struct Scope {
+    /// Target session the document lives in; `None` for the page's own.
+    session: Option<String>,
+    /// Remote object id of the document.
+    doc: String,
+    /// Offset in top-level viewport coordinates.
+    x: f64,
+    y: f64,
+    /// Sessions attached while resolving, each with the session it was
+    /// attached from; detached on release.
+    attached: Vec<(Option<String>, String)>,
+}
+
+/// Run `body` with `s` bound to the scope of `chain` (`{ doc, win, x, y }`)
+/// and return its result. `missing` is the JS value returned instead if a
+/// frame is missing or still loading.
+pub async fn evaluate<T: DeserializeOwned>(
+    page: &Page,
+    chain: &[FrameLocator],
+    body: &str,
+    missing: &str,
+) -> Result<T> {
+    if chain.is_empty() {
+        let js = format!("(() => {{ const s = {}; {} }})()", TOP_SCOPE_JS, body);
+        return Ok(page.evaluate(&js).await?);
+    }
+    let Some(scope) = resolve(page, chain).await? else {
+        return Ok(page.evaluate(missing).await?);
+    };
+    let value = call_value(page, &scope, body).await;
+    release(page, scope).await;
+    Ok(serde_json::from_value(value?)?)
+}
+
+/// Run `body` in the scope of `chain`, ignoring its result.
+pub async fn execute(page: &Page, chain: &[FrameLocator], body: &str) -> Result<()> {
+    if chain.is_empty() {
+        let js = format!("(() => {{ const s = {}; {} }})()", TOP_SCOPE_JS, body);
+        return Ok(page.execute(&js).await?);
+    }
+    if let Some(scope) = resolve(page, chain).await? {
+        let result = call_value(page, &scope, body).await;
+        release(page, scope).await;
+        result?;
+    }
+    Ok(())
+}
+
+/// Whether every frame in `chain` exists and has loaded a document.
+pub async fn reachable(page: &Page, chain: &[FrameLocator]) -> Result<bool> {
+    match resolve(page, chain).await? {
+        Some(scope) => {
+            release(page, scope).await;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Walk the frame chain from the top-level document. `None` if a frame is
+/// missing or its document has no body yet.
+async fn resolve(page: &Page, chain: &[FrameLocator]) -> Result<Option<Scope>> {
+    let top = send(
+        page,
+        None,
+        "Runtime.evaluate",
+        json!({ "expression": "document", "objectGroup": OBJECT_GROUP }),
+    )
+    .await?;
+    let Some(doc) = object_id(&top["result"]) else {
+        return Ok(None);
+    };
+    let mut scope = Scope {
+        session: None,
+        doc,
+        x: 0.0,
+        y: 0.0,
+        attached: Vec::new(),
+    };
+    for loc in chain {
+        match enter(page, &mut scope, loc).await {
+            Ok(true) => {}
+            Ok(false) => {
+                release(page, scope).await;
+                return Ok(None);
+            }
+            Err(e) => {
+                // Frames detach and navigate under us; treat that as not there yet.
+                debug!("frame {} not reachable: {}", loc, e);
+                release(page, scope).await;
+                return Ok(None);
+            }
+        }
+    }
+    Ok(Some(scope))
+}
+
+/// Move `scope` into the frame `loc` matches. `false` if there is none.
+async fn enter(page: &Page, scope: &mut Scope, loc: &FrameLocator) -> Result<bool> {
+    let session = scope.session.clone();
+    let found = send(
+        page,
+        session.as_deref(),
+        "Runtime.callFunctionOn",
+        json!({
+            "functionDeclaration": FIND_FRAME_JS,
+            "objectId": scope.doc,
+            "arguments": [{ "value": locator_json(loc) }],
+        }),
+    )
+    .await?;
+    let Some(element) = object_id(&found["result"]) else {
+        return Ok(false);
+    };
+    let offset = send(
+        page,
+        session.as_deref(),
+        "Runtime.callFunctionOn",
+        json!({
+            "functionDeclaration": OFFSET_JS,
+            "objectId": element,
+            "returnByValue": true,
+        }),
+    )
+    .await?;
+    let (dx, dy): (f64, f64) = serde_json::from_value(offset["result"]["value"].clone())?;
+
+    let node = send(
+        page,
+        session.as_deref(),
+        "DOM.describeNode",
+        json!({ "objectId": element }),
+    )
+    .await?;
+    let node = &node["node"];
+    let doc = if let Some(backend) = node["contentDocument"]["backendNodeId"].as_i64() {
+        // In-process frame, same-origin or not: resolve its document node
+        // in the frame's main world.
+        let resolved = send(
+            page,
+            session.as_deref(),
+            "DOM.resolveNode",
+            json!({ "backendNodeId": backend, "objectGroup": OBJECT_GROUP }),
+        )
+        .await?;
+        object_id(&resolved["object"])
+    } else if let Some(frame_id) = node["frameId"].as_str() {
+        // Out-of-process frame: its target id is the frame id. A frame
+        // nested in another OOPIF is only visible from its parent's session.
+        let attached = send(
+            page,
+            session.as_deref(),
+            "Target.attachToTarget",
+            json!({ "targetId": frame_id, "flatten": true }),
+        )
+        .await?;
+        let Some(id) = attached["sessionId"].as_str() else {
+            return Ok(false);
+        };
+        scope.attached.push((session.clone(), id.to_string()));
+        scope.session = Some(id.to_string());
+        let top = send(
+            page,
+            Some(id),
+            "Runtime.evaluate",
+            json!({ "expression": "document", "objectGroup": OBJECT_GROUP }),
+        )
+        .await?;
+        object_id(&top["result"])
+    } else {
+        None
+    };
+    let Some(doc) = doc else {
+        return Ok(false);
+    };
+    scope.doc = doc;
+    scope.x += dx;
+    scope.y += dy;
+    let loaded = call_value(page, scope, "return !!s.doc.body;").await?;
+    Ok(loaded == Value::Bool(true))
+}
+
+/// Run `body` against the scope's document, returning its value by value.
+async fn call_value(page: &Page, scope: &Scope, body: &str) -> Result<Value> {
+    let result = send(
+        page,
+        scope.session.as_deref(),
+        "Runtime.callFunctionOn",
+        json!({
+            "functionDeclaration": call_js(body, scope.x, scope.y),
+            "objectId": scope.doc,
+            "returnByValue": true,
+            "awaitPromise": true,
+        }),
+    )
+    .await?;
+    if let Some(exception) = result.get("exceptionDetails") {
+        return Err(Error::Browser(eoka::Error::CdpSimple(format!(
+            "JavaScript error: {} at {}:{}",
+            exception["exception"]["description"]
+                .as_str()
+                .or(exception["text"].as_str())
+                .unwrap_or("exception"),
+            exception["lineNumber"],
+            exception["columnNumber"]
+        ))));
+    }
+    Ok(result["result"]["value"].clone())
+}
+
+/// Detach the sessions a resolution attached and free its remote objects.
+async fn release(page: &Page, scope: Scope) {
+    for (parent, id) in scope.attached.iter().rev() {
+        let params = json!({ "sessionId": id });
+        let _ = send(page, parent.as_deref(), "Target.detachFromTarget", params).await;
+    }
+    let params = json!({ "objectGroup": OBJECT_GROUP });
+    let _ = send(page, None, "Runtime.releaseObjectGroup", params).await;
+}
+
+/// Send a CDP command to the page's session or another target's.
+async fn send(page: &Page, session: Option<&str>, method: &str, params: Value) -> Result<Value> {
+    Ok(match session {
+        Some(id) => {
+            page.session()
+                .transport()
+                .send_to_session(id, method, &params)
+                .await?
+        }
+        None => page.session().send(method, &params).await?,
+    })
+}
+
+/// Object id of a remote object, unless it is `null` or a primitive.
+fn object_id(remote: &Value) -> Option<String> {
+    match remote["subtype"].as_str() {
+        Some("null") => None,
+        _ => remote["objectId"].as_str().map(String::from),
+    }
+}
+
+fn locator_json(loc: &FrameLocator) -> Value {
+    json!({
+        "selector": loc.selector,
+        "name": loc.name,
+        "url": loc.url_contains,
+    })
+}
+
+/// Function declaration called on a frame document, binding `s` the way
+/// [`TOP_SCOPE_JS`] does for the top level.
+fn call_js(body: &str, x: f64, y: f64) -> String {
+    format!(
+        "function() {{ const s = {{ doc: this, win: this.defaultView, x: {}, y: {} }}; {} }}",
+        x, y, body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_call_binds_scope() {
+        let js = call_js("return s.doc.title;", 10.0, 24.5);
+        assert!(js.starts_with("function() {"));
+        assert!(js.contains("doc: this, win: this.defaultView, x: 10, y: 24.5"));
+        assert!(js.ends_with("return s.doc.title; }"));
+    }
+
+    #[test]
+    fn locator_fields() {
+        let loc = FrameLocator {
+            url_contains: Some("js.stripe.com".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            locator_json(&loc),
+            json!({ "selector": null, "name": null, "url": "js.stripe.com" })
+        );
+    }
+
+    #[test]
+    fn null_is_not_an_object() {
+        assert_eq!(
+            object_id(&json!({ "type": "object", "subtype": "null", "value": null })),
+            None
+        );
+        assert_eq!(object_id(&json!({ "type": "undefined" })), None);
+        assert_eq!(
+            object_id(&json!({ "type": "object", "subtype": "node", "objectId": "1.2.3" })),
+            Some("1.2.3".into())
+        );
+    }
+}
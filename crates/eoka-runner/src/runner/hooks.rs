@@ -0,0 +1,157 @@
+//! on_success / on_failure hooks — webhooks, result files, follow-up includes.
+
+use super::executor::{self, ExecutionContext};
+use super::RunResult;
+use crate::config::schema::{Hooks, WebhookConfig, WebhookFormat};
+use crate::config::Action;
+use crate::{Error, Result};
+use eoka::Page;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Run all hooks. Failures are logged and never change the run result.
+pub async fn run(
+    page: &Page,
+    hooks: &Hooks,
+    config_name: &str,
+    result: &RunResult,
+    ctx: &ExecutionContext,
+) {
+    if let Some(ref path) = hooks.write_result {
+        if let Err(e) = write_result(path, config_name, result) {
+            warn!("Failed to write result file: {}", e);
+        }
+    }
+    if let Some(ref webhook) = hooks.webhook {
+        if let Err(e) = post_webhook(webhook, config_name, result).await {
+            warn!("Webhook failed: {}", e);
+        }
+    }
+    if let Some(ref include) = hooks.include {
+        info!("Running hook include: {}", include.path);
+        let action = Action::Include(include.clone());
+        if let Err(e) = executor::execute_with_context(page, &action, ctx).await {
+            warn!("Hook include failed: {}", e);
+        }
+    }
+}
+
+fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Generic JSON payload describing a run.
+pub fn result_json(config_name: &str, result: &RunResult) -> serde_json::Value {
+    serde_json::json!({
+        "config": config_name,
+        "timestamp": timestamp(),
+        "result": result,
+    })
+}
+
+/// One-line human summary, used for chat webhooks.
+fn summary(config_name: &str, result: &RunResult) -> String {
+    if result.success {
+        format!(
+            "✓ {} succeeded in {}ms ({} actions)",
+            config_name, result.duration_ms, result.actions_executed
+        )
+    } else {
+        format!(
+            "✗ {} failed after {}ms: {}",
+            config_name,
+            result.duration_ms,
+            result.error.as_deref().unwrap_or("unknown error")
+        )
+    }
+}
+
+/// Build the webhook body for the configured format.
+fn webhook_body(format: WebhookFormat, config_name: &str, result: &RunResult) -> serde_json::Value {
+    match format {
+        WebhookFormat::Json => result_json(config_name, result),
+        WebhookFormat::Slack => serde_json::json!({ "text": summary(config_name, result) }),
+        WebhookFormat::Discord => serde_json::json!({ "content": summary(config_name, result) }),
+    }
+}
+
+fn write_result(path: &str, config_name: &str, result: &RunResult) -> Result<()> {
+    let path = path.replace("{timestamp}", &timestamp().to_string());
+    info!("Writing result to: {}", path);
+    let json = serde_json::to_string_pretty(&result_json(config_name, result))
+        .map_err(|e| Error::ActionFailed(format!("failed to serialize result: {}", e)))?;
+    std::fs::write(&path, json)?;
+    Ok(())
+}
+
+async fn post_webhook(
+    webhook: &WebhookConfig,
+    config_name: &str,
+    result: &RunResult,
+) -> Result<()> {
+    info!("Posting {:?} webhook", webhook.format);
+    let client = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .map_err(|e| Error::ActionFailed(format!("webhook client: {}", e)))?;
+    let body = webhook_body(webhook.format, config_name, result);
+    let mut request = client.post(&webhook.url).json(&body);
+    for (name, value) in &webhook.headers {
+        request = request.header(name, value);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| Error::ActionFailed(format!("webhook request: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(Error::ActionFailed(format!(
+            "webhook returned {}",
+            response.status()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed() -> RunResult {
+        RunResult {
+            success: false,
+            error: Some("timeout: element '#pay' not found".into()),
+            actions_executed: 4,
+            duration_ms: 1234,
+            retries: 2,
+            actions: Vec::new(),
+            crawl: None,
+        }
+    }
+
+    #[test]
+    fn slack_and_discord_bodies() {
+        let slack = webhook_body(WebhookFormat::Slack, "checkout", &failed());
+        assert_eq!(
+            slack["text"],
+            "✗ checkout failed after 1234ms: timeout: element '#pay' not found"
+        );
+        let discord = webhook_body(WebhookFormat::Discord, "checkout", &failed());
+        assert!(discord["content"]
+            .as_str()
+            .unwrap()
+            .starts_with("✗ checkout"));
+    }
+
+    #[test]
+    fn json_body_includes_result() {
+        let body = webhook_body(WebhookFormat::Json, "checkout", &failed());
+        assert_eq!(body["config"], "checkout");
+        assert_eq!(body["result"]["success"], false);
+        assert_eq!(body["result"]["retries"], 2);
+    }
+}
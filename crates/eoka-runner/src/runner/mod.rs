@@ -1,15 +1,49 @@
+mod agent_task;
+mod artifacts;
+mod checkpoint;
+mod crawl;
+mod dismiss;
+mod download;
+mod emulation;
+mod events;
 mod executor;
+mod frame;
+mod hooks;
+mod resolution;
+mod snapshot;
+mod storage;
+mod timing;
+mod traffic;
+mod variables;
 
-use crate::config::{BrowserConfig, Config};
+pub use agent_task::{AgentTask, AgentTaskFuture, AgentTaskHandler};
+pub use artifacts::{Frame, RunArtifacts};
+pub use crawl::{CrawlError, CrawlReport};
+pub use events::Event;
+pub use resolution::Resolution;
+pub use timing::ActionTiming;
+pub use variables::Variables;
+
+use crate::autofill::{FilledField, FormProfile, FormRef};
+use crate::config::include::IncludeChain;
+use crate::config::{BrowserConfig, Config, CrawlConfig, PopupPolicy};
+use crate::popup::{self, PopupTracker};
 use crate::Result;
+use crate::{debug_overlay, deterministic, frontier};
+use checkpoint::Checkpoint;
+use download::DownloadManager;
 use eoka::{Browser, Page};
+use events::EventLog;
 use executor::ExecutionContext;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use storage::StorageState;
+use timing::{Timings, Transfer};
 use tracing::{debug, info, warn};
 
 /// Result of running a config.
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize)]
 pub struct RunResult {
     /// Whether the run succeeded.
     pub success: bool,
@@ -21,25 +55,105 @@ pub struct RunResult {
     pub duration_ms: u64,
     /// Number of retry attempts made.
     pub retries: u32,
+    /// Per-action breakdown, in action order.
+    pub actions: Vec<ActionTiming>,
+    /// Pages visited in `crawl:` mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crawl: Option<CrawlReport>,
+}
+
+impl RunResult {
+    /// The `n` actions that took longest, slowest first.
+    pub fn slowest_actions(&self, n: usize) -> Vec<&ActionTiming> {
+        let mut actions: Vec<&ActionTiming> = self.actions.iter().collect();
+        actions.sort_by_key(|t| std::cmp::Reverse(t.duration_ms));
+        actions.truncate(n);
+        actions
+    }
+}
+
+/// Chrome's `--proxy-bypass-list` flag for `rules`, or `None` when there
+/// are none.
+pub fn proxy_bypass_arg(rules: &[String]) -> Option<String> {
+    if rules.is_empty() {
+        return None;
+    }
+    Some(format!("--proxy-bypass-list={}", rules.join(";")))
+}
+
+/// eoka launch settings for `config`.
+pub(crate) fn stealth_config(config: &BrowserConfig) -> eoka::StealthConfig {
+    let device = config.device.map(|d| d.metrics());
+    let defaults = eoka::StealthConfig::default();
+    let mut extra_args = defaults.extra_args.clone();
+    extra_args.extend(proxy_bypass_arg(&config.proxy_bypass));
+    eoka::StealthConfig {
+        headless: config.headless,
+        proxy: config.proxy.clone(),
+        user_agent: config
+            .user_agent
+            .clone()
+            .or_else(|| device.and_then(|d| d.user_agent).map(String::from)),
+        viewport_width: config
+            .viewport
+            .as_ref()
+            .map(|v| v.width)
+            .or(device.map(|d| d.width))
+            .unwrap_or(1280),
+        viewport_height: config
+            .viewport
+            .as_ref()
+            .map(|v| v.height)
+            .or(device.map(|d| d.height))
+            .unwrap_or(720),
+        chrome_path: config.executable_path.clone(),
+        patch_binary: config.patch_binary.unwrap_or(defaults.patch_binary),
+        human_mouse: config.human_mouse.unwrap_or(defaults.human_mouse),
+        human_typing: config.human_typing.unwrap_or(defaults.human_typing),
+        webgl_spoof: config.webgl_spoof.unwrap_or(defaults.webgl_spoof),
+        canvas_spoof: config.canvas_spoof.unwrap_or(defaults.canvas_spoof),
+        audio_spoof: config.audio_spoof.unwrap_or(defaults.audio_spoof),
+        extra_args,
+        ..defaults
+    }
+}
+
+/// Best-effort: a page that refuses the overlay doesn't fail the run.
+async fn show_debug_overlay(page: &Page, caption: &str) {
+    if let Err(e) = debug_overlay::show_visible(page, caption).await {
+        debug!("debug_overlay: {}", e);
+    }
 }
 
+/// Callback for [`Runner::with_listener`].
+type Listener = Box<dyn FnMut(&Event<'_>) + Send>;
+
 /// Executes automation configs.
 pub struct Runner {
     browser: Browser,
     page: Page,
+    download_dir: PathBuf,
+    storage_state: Option<PathBuf>,
+    checkpoint: Option<PathBuf>,
+    events: Option<EventLog>,
+    listeners: Vec<Listener>,
+    update_baselines: bool,
+    debug_overlay: bool,
+    artifacts: Option<RunArtifacts>,
+    /// Set when popups are followed.
+    popups: Option<PopupTracker>,
+    /// Launch settings, reapplied to followed popups.
+    config: BrowserConfig,
+    /// Whether network traffic is being recorded for success conditions.
+    recording_traffic: bool,
+    /// Runs `agent_task` actions.
+    agent_task: Option<Arc<dyn AgentTaskHandler>>,
 }
 
 impl Runner {
     /// Create a new runner with browser config.
     pub async fn new(config: &BrowserConfig) -> Result<Self> {
-        let stealth = eoka::StealthConfig {
-            headless: config.headless,
-            proxy: config.proxy.clone(),
-            user_agent: config.user_agent.clone(),
-            viewport_width: config.viewport.as_ref().map(|v| v.width).unwrap_or(1280),
-            viewport_height: config.viewport.as_ref().map(|v| v.height).unwrap_or(720),
-            ..Default::default()
-        };
+        let stealth = stealth_config(config);
 
         debug!(
             "Launching browser (headless: {}, proxy: {:?})",
@@ -47,8 +161,94 @@ impl Runner {
         );
         let browser = Browser::launch_with_config(stealth).await?;
         let page = browser.new_page("about:blank").await?;
+        emulation::apply(&page, config).await?;
+        deterministic::install(&page).await?;
+        let popups = match config.popups {
+            PopupPolicy::Background => None,
+            PopupPolicy::Follow => Some(PopupTracker::new(&browser).await?),
+            PopupPolicy::SameTab => {
+                popup::same_tab(&page).await?;
+                None
+            }
+        };
+        let storage_state = config.storage_state.as_ref().map(PathBuf::from);
+        if let Some(ref path) = storage_state {
+            info!("Loading storage state: {}", path.display());
+            StorageState::load(path)?.apply(&page).await?;
+        }
+        let download_dir = match config.download_dir {
+            Some(ref dir) => download::allow_downloads(&page, Path::new(dir)).await?,
+            None => DownloadManager::default_dir(),
+        };
+
+        Ok(Self {
+            browser,
+            page,
+            download_dir,
+            storage_state,
+            checkpoint: None,
+            events: None,
+            listeners: Vec::new(),
+            update_baselines: false,
+            debug_overlay: config.debug_overlay && !config.headless,
+            artifacts: None,
+            popups,
+            config: config.clone(),
+            recording_traffic: false,
+            agent_task: None,
+        })
+    }
+
+    /// Write a checkpoint to `path` after each top-level action, and resume
+    /// from a matching checkpoint instead of starting over.
+    ///
+    /// The checkpoint holds the next action index, the page URL, cookies, web
+    /// storage, and the variables actions extracted. It is deleted once all
+    /// actions have run. Only a run's first attempt resumes; `on_failure`
+    /// retries start over from the target URL.
+    pub fn with_checkpoint(mut self, path: impl Into<PathBuf>) -> Self {
+        self.checkpoint = Some(path.into());
+        self
+    }
 
-        Ok(Self { browser, page })
+    /// Make `assert_screenshot` and `assert_snapshot` overwrite their baselines
+    /// with fresh captures instead of comparing against them.
+    pub fn with_update_baselines(mut self, update: bool) -> Self {
+        self.update_baselines = update;
+        self
+    }
+
+    /// Screenshot the page after each top-level action, for
+    /// [`RunArtifacts::render_strip`]. Only the last attempt is kept.
+    pub fn with_artifacts(mut self, capture: bool) -> Self {
+        self.artifacts = capture.then(RunArtifacts::default);
+        self
+    }
+
+    /// Thumbnails from the last run, if [`Runner::with_artifacts`] is on.
+    pub fn artifacts(&self) -> Option<&RunArtifacts> {
+        self.artifacts.as_ref()
+    }
+
+    /// Append a JSON line per run and action event to `path`.
+    pub fn with_event_log(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        self.events = Some(EventLog::open(path)?);
+        Ok(self)
+    }
+
+    /// Call `listener` with every run and action event, e.g. to draw
+    /// progress. Runs alongside [`Runner::with_event_log`] and any other
+    /// listeners.
+    pub fn with_listener(mut self, listener: impl FnMut(&Event<'_>) + Send + 'static) -> Self {
+        self.listeners.push(Box::new(listener));
+        self
+    }
+
+    /// Run `agent_task` actions with `handler`, e.g. an LLM agent loop.
+    /// Without one, `agent_task` fails the run.
+    pub fn with_agent_task(mut self, handler: impl AgentTaskHandler + 'static) -> Self {
+        self.agent_task = Some(Arc::new(handler));
+        self
     }
 
     /// Get a reference to the page (for swarm integration).
@@ -56,6 +256,155 @@ impl Runner {
         &self.page
     }
 
+    /// Run actions on a page the caller owns, e.g. a YAML sub-flow (login,
+    /// cookie dismissal) inside an agent session.
+    ///
+    /// No navigation, retries, success checks, hooks, or events: actions run
+    /// in order and the first error is returned. Includes resolve from the
+    /// working directory. Only when the actions contain `wait_for_download`
+    /// is the browser set to save downloads to `~/Downloads`, where it
+    /// watches. Values the actions extract are written to `variables`, even
+    /// on error.
+    pub async fn execute_actions(
+        page: &Page,
+        actions: &[crate::Action],
+        variables: &mut Variables,
+    ) -> Result<()> {
+        let mut download_dir = DownloadManager::default_dir();
+        if download::uses_downloads(actions, Path::new("."), &IncludeChain::default()) {
+            download_dir = download::allow_downloads(page, &download_dir).await?;
+        }
+        let ctx = ExecutionContext::new(".", download_dir);
+        *ctx.variables.lock().await = std::mem::take(variables);
+        let mut outcome = Ok(());
+        for action in actions {
+            debug!("Executing action: {}", action.name());
+            outcome = executor::execute_with_context(page, action, &ctx).await;
+            if outcome.is_err() {
+                break;
+            }
+        }
+        *variables = std::mem::take(&mut *ctx.variables.lock().await);
+        outcome
+    }
+
+    /// Fill a form on a page the caller owns from a test-data profile, the
+    /// way the `autofill` action does, and return the fields filled.
+    pub async fn autofill_form(
+        page: &Page,
+        form: &FormRef,
+        profile: &FormProfile,
+    ) -> Result<Vec<FilledField>> {
+        let ctx = ExecutionContext::new(".", DownloadManager::default_dir());
+        executor::fill_form(page, &ctx, form, profile, false).await
+    }
+
+    /// Wait until `text` is gone from a page the caller owns, the way the
+    /// `wait_for_text_gone` action does.
+    pub async fn wait_for_text_gone(page: &Page, text: &str, timeout_ms: u64) -> Result<()> {
+        let ctx = ExecutionContext::new(".", DownloadManager::default_dir());
+        executor::wait_for_text_gone(page, &ctx, text, timeout_ms).await
+    }
+
+    /// Wait until the element matching `selector` on a page the caller owns
+    /// keeps the same box for `frames` animation frames, the way the
+    /// `wait_for_element_stable` action does.
+    pub async fn wait_for_element_stable(
+        page: &Page,
+        selector: &str,
+        frames: u32,
+        timeout_ms: u64,
+    ) -> Result<()> {
+        let ctx = ExecutionContext::new(".", DownloadManager::default_dir());
+        executor::wait_for_element_stable(page, &ctx, selector, frames, timeout_ms).await
+    }
+
+    /// Run a whole config on a page the caller owns, e.g. delegating a
+    /// login from an agent session, and report it like [`Runner::run`].
+    ///
+    /// Navigates to the target, runs the actions once and checks the success
+    /// conditions. No retries, checkpoints, hooks, events, storage state, or
+    /// popup policy; crawl configs are rejected. A failed action ends the run with
+    /// `success: false` rather than an error. When `browser.download_dir` is
+    /// set or the actions contain `wait_for_download`, the browser is set to
+    /// save downloads to `browser.download_dir` (default: `~/Downloads`).
+    pub async fn run_on_page(
+        page: &Page,
+        config: &Config,
+        base_path: impl AsRef<Path>,
+    ) -> Result<RunResult> {
+        if config.crawl.is_some() {
+            return Err(crate::Error::Config(
+                "crawl configs need a runner of their own".into(),
+            ));
+        }
+        let mut download_dir = config
+            .browser
+            .download_dir
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(DownloadManager::default_dir);
+        if config.browser.download_dir.is_some()
+            || download::uses_downloads(
+                &config.actions,
+                base_path.as_ref(),
+                &IncludeChain::default(),
+            )
+        {
+            download_dir = download::allow_downloads(page, &download_dir).await?;
+        }
+        let mut ctx = ExecutionContext::new(base_path.as_ref(), download_dir);
+        ctx.auto_dismiss = config
+            .browser
+            .auto_dismiss
+            .then_some(config.browser.consent_policy);
+        let start = Instant::now();
+        let mut timings = Timings::default();
+        if uses_traffic(config) {
+            traffic::install(page).await?;
+        }
+
+        info!("Navigating to: {}", config.target.url);
+        page.goto(&config.target.url).await?;
+        if let Some(policy) = ctx.auto_dismiss {
+            dismiss::dismiss(page, policy).await;
+        }
+
+        let mut actions_executed = 0;
+        let mut error = None;
+        for (i, action) in config.actions.iter().enumerate() {
+            debug!("Executing action {}: {}", i + 1, action.name());
+            let started = Instant::now();
+            let before = Transfer::read(page).await;
+            let outcome = executor::execute_with_context(page, action, &ctx).await;
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let bytes = Transfer::since(before, Transfer::read(page).await);
+            timings.record(i, action, duration_ms, bytes, outcome.is_ok());
+            if let Err(e) = outcome {
+                error = Some(e.to_string());
+                break;
+            }
+            actions_executed += 1;
+        }
+
+        let success = match error {
+            Some(_) => false,
+            None => check_success(page, config).await?,
+        };
+        if !success && error.is_none() {
+            error = Some("success conditions not met".to_string());
+        }
+        Ok(RunResult {
+            success,
+            error,
+            actions_executed,
+            duration_ms: start.elapsed().as_millis() as u64,
+            retries: 0,
+            actions: timings.to_vec(),
+            crawl: None,
+        })
+    }
+
     /// Run the config with retry support.
     pub async fn run(&mut self, config: &Config) -> Result<RunResult> {
         self.run_with_base_path(config, ".").await
@@ -67,15 +416,34 @@ impl Runner {
         config: &Config,
         base_path: impl AsRef<Path>,
     ) -> Result<RunResult> {
-        let ctx = ExecutionContext::new(base_path.as_ref());
+        let mut ctx = ExecutionContext::new(base_path.as_ref(), &self.download_dir);
+        ctx.update_baselines = self.update_baselines;
+        ctx.agent_task = self.agent_task.clone();
+        ctx.auto_dismiss = config
+            .browser
+            .auto_dismiss
+            .then_some(config.browser.consent_policy);
         let start = Instant::now();
+        if uses_traffic(config) && !self.recording_traffic {
+            traffic::install(&self.page).await?;
+            self.recording_traffic = true;
+        }
+        if let Some(ref crawl) = config.crawl {
+            return self.run_crawl(config, crawl, &ctx, start).await;
+        }
         let retry_config = config.on_failure.as_ref().and_then(|f| f.retry.as_ref());
         let max_attempts = retry_config.map(|r| r.attempts).unwrap_or(1);
         let retry_delay = retry_config.map(|r| r.delay_ms).unwrap_or(0);
+        self.emit(&Event::RunStart {
+            config: &config.name,
+            url: &config.target.url,
+        });
 
         let mut last_error = None;
         let mut last_actions_executed = 0;
         let mut retries = 0;
+        let mut artifacts = Vec::new();
+        let mut timings = Timings::default();
 
         for attempt in 1..=max_attempts {
             if attempt > 1 {
@@ -86,72 +454,308 @@ impl Runner {
                 }
             }
 
-            match self.run_once(config, &ctx).await {
+            match self.run_once(config, &ctx, retries, &mut timings).await {
                 Ok(result) if result.success => {
-                    return Ok(RunResult {
+                    let result = RunResult {
                         success: true,
                         error: None,
                         actions_executed: result.actions_executed,
                         duration_ms: start.elapsed().as_millis() as u64,
                         retries,
+                        actions: timings.to_vec(),
+                        crawl: None,
+                    };
+                    self.save_storage_state().await;
+                    if let Some(ref hooks) = config.on_success {
+                        hooks::run(&self.page, hooks, &config.name, &result, &ctx).await;
+                    }
+                    self.emit(&Event::RunEnd {
+                        config: &config.name,
+                        result: &result,
+                        artifacts,
                     });
+                    return Ok(result);
                 }
                 Ok(result) => {
                     last_actions_executed = result.actions_executed;
                     last_error = Some("success conditions not met".to_string());
                     if attempt == max_attempts {
-                        self.handle_failure(config).await;
+                        artifacts.extend(self.handle_failure(config).await);
                     }
                 }
                 Err(e) => {
                     warn!("Attempt {} failed: {}", attempt, e);
                     last_error = Some(e.to_string());
                     if attempt == max_attempts {
-                        self.handle_failure(config).await;
+                        artifacts.extend(self.handle_failure(config).await);
                     }
                 }
             }
         }
 
-        Ok(RunResult {
+        let result = RunResult {
             success: false,
             error: last_error,
             actions_executed: last_actions_executed,
             duration_ms: start.elapsed().as_millis() as u64,
             retries,
-        })
+            actions: timings.to_vec(),
+            crawl: None,
+        };
+        if let Some(ref on_failure) = config.on_failure {
+            hooks::run(&self.page, &on_failure.hooks, &config.name, &result, &ctx).await;
+        }
+        self.emit(&Event::RunEnd {
+            config: &config.name,
+            result: &result,
+            artifacts,
+        });
+        Ok(result)
     }
 
-    async fn handle_failure(&self, config: &Config) {
-        if let Some(ref on_failure) = config.on_failure {
-            if let Some(ref screenshot_path) = on_failure.screenshot {
-                let timestamp = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .map(|d| d.as_secs())
-                    .unwrap_or(0);
-                let path = screenshot_path.replace("{timestamp}", &timestamp.to_string());
-                info!("Saving failure screenshot to: {}", path);
-                if let Ok(data) = self.page.screenshot().await {
-                    if let Err(e) = std::fs::write(&path, data) {
-                        warn!("Failed to save screenshot: {}", e);
-                    }
+    /// Run the actions on every page reachable from the target URL. A page
+    /// fails when an action errors or the success conditions aren't met; the
+    /// crawl carries on and the run succeeds only if no page failed.
+    async fn run_crawl(
+        &mut self,
+        config: &Config,
+        crawl: &CrawlConfig,
+        ctx: &ExecutionContext,
+        start: Instant,
+    ) -> Result<RunResult> {
+        self.emit(&Event::RunStart {
+            config: &config.name,
+            url: &config.target.url,
+        });
+        let mut frontier = crawl::frontier(&config.target.url, crawl)?;
+        let mut report = CrawlReport::default();
+        let mut actions_executed = 0;
+
+        while let Some((url, depth)) = frontier.next_page() {
+            if !report.pages.is_empty() || !report.errors.is_empty() {
+                tokio::time::sleep(std::time::Duration::from_millis(crawl.delay_ms)).await;
+            }
+            info!("Crawling [depth {}]: {}", depth, url);
+            match self.crawl_page(config, crawl, ctx, &url).await {
+                Ok((links, executed)) => {
+                    actions_executed += executed;
+                    frontier.add_links(links, depth);
+                    report.pages.push(url);
+                }
+                Err(e) => {
+                    warn!("Crawl of {} failed: {}", url, e);
+                    report.errors.push(CrawlError {
+                        url,
+                        error: e.to_string(),
+                    });
                 }
             }
         }
-    }
 
-    async fn run_once(&mut self, config: &Config, ctx: &ExecutionContext) -> Result<RunResult> {
-        info!("Navigating to: {}", config.target.url);
-        self.page.goto(&config.target.url).await?;
+        let total = report.pages.len() + report.errors.len();
+        info!("Crawled {} pages, {} errors", total, report.errors.len());
+        let success = report.errors.is_empty();
+        let result = RunResult {
+            success,
+            error: (!success).then(|| format!("{} of {} pages failed", report.errors.len(), total)),
+            actions_executed,
+            duration_ms: start.elapsed().as_millis() as u64,
+            retries: 0,
+            actions: Vec::new(),
+            crawl: Some(report),
+        };
+        if success {
+            self.save_storage_state().await;
+            if let Some(ref hooks) = config.on_success {
+                hooks::run(&self.page, hooks, &config.name, &result, ctx).await;
+            }
+        } else if let Some(ref on_failure) = config.on_failure {
+            hooks::run(&self.page, &on_failure.hooks, &config.name, &result, ctx).await;
+        }
+        self.emit(&Event::RunEnd {
+            config: &config.name,
+            result: &result,
+            artifacts: Vec::new(),
+        });
+        Ok(result)
+    }
 
-        let mut actions_executed = 0;
+    /// Load one crawled page, collect its links, then run the actions.
+    /// Returns the links and the number of actions run.
+    async fn crawl_page(
+        &self,
+        config: &Config,
+        crawl: &CrawlConfig,
+        ctx: &ExecutionContext,
+        url: &str,
+    ) -> Result<(Vec<String>, usize)> {
+        self.page.goto(url).await?;
+        let _ = self.page.wait_for_network_idle(500, 5000).await;
+        if let Some(policy) = ctx.auto_dismiss {
+            dismiss::dismiss(&self.page, policy).await;
+        }
+        let links = frontier::links(&self.page, crawl.link_selector.as_deref()).await?;
         for (i, action) in config.actions.iter().enumerate() {
             debug!("Executing action {}: {}", i + 1, action.name());
             executor::execute_with_context(&self.page, action, ctx).await?;
+        }
+        if !check_success(&self.page, config).await? {
+            return Err(crate::Error::AssertionFailed(
+                "success conditions not met".into(),
+            ));
+        }
+        Ok((links, config.actions.len()))
+    }
+
+    /// Save cookies and localStorage after a successful run, if configured.
+    async fn save_storage_state(&self) {
+        let Some(ref path) = self.storage_state else {
+            return;
+        };
+        info!("Saving storage state: {}", path.display());
+        let saved = match StorageState::load(path) {
+            Ok(mut state) => match state.capture(&self.page).await {
+                Ok(()) => state.save(path),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        if let Err(e) = saved {
+            warn!("Failed to save storage state: {}", e);
+        }
+    }
+
+    /// Save the failure screenshot, if configured. Returns its path.
+    async fn handle_failure(&self, config: &Config) -> Option<String> {
+        let on_failure = config.on_failure.as_ref()?;
+        let screenshot_path = on_failure.screenshot.as_ref()?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = screenshot_path.replace("{timestamp}", &timestamp.to_string());
+        info!("Saving failure screenshot to: {}", path);
+        let data = self.page.screenshot().await.ok()?;
+        if let Err(e) = std::fs::write(&path, data) {
+            warn!("Failed to save screenshot: {}", e);
+            return None;
+        }
+        Some(path)
+    }
+
+    async fn run_once(
+        &mut self,
+        config: &Config,
+        ctx: &ExecutionContext,
+        retry: u32,
+        timings: &mut Timings,
+    ) -> Result<RunResult> {
+        if let Some(ref mut artifacts) = self.artifacts {
+            artifacts.clear();
+        }
+        // Only a run's first attempt resumes: a retry replays from the target
+        // rather than continuing from the step that just failed.
+        let resume = match retry {
+            0 => self.resume_point(config)?,
+            _ => None,
+        };
+        let first = match resume {
+            Some(checkpoint) => {
+                info!(
+                    "Resuming {} at action {} ({})",
+                    config.name,
+                    checkpoint.next_action + 1,
+                    checkpoint.url
+                );
+                checkpoint.restore(&self.page).await?;
+                checkpoint.restore_variables(&mut *ctx.variables.lock().await);
+                checkpoint.next_action
+            }
+            None => {
+                info!("Navigating to: {}", config.target.url);
+                self.page.goto(&config.target.url).await?;
+                if let Some(policy) = ctx.auto_dismiss {
+                    dismiss::dismiss(&self.page, policy).await;
+                }
+                0
+            }
+        };
+
+        let mut actions_executed = 0;
+        let mut last_step: Option<String> = None;
+        for (i, action) in config.actions.iter().enumerate().skip(first) {
+            debug!("Executing action {}: {}", i + 1, action.name());
+            let step = debug_overlay::step(i, config.actions.len(), action);
+            if self.debug_overlay {
+                let caption = match last_step {
+                    Some(ref last) => format!("{}\nlast: {}", step, last),
+                    None => step.clone(),
+                };
+                show_debug_overlay(&self.page, &caption).await;
+            }
+            self.emit(&Event::ActionStart {
+                index: i,
+                action: action.name(),
+                target: action.target(),
+                retry,
+            });
+            ctx.resolved.lock().await.clear();
+            let started = Instant::now();
+            let before = Transfer::read(&self.page).await;
+            let mut outcome = executor::execute_with_context(&self.page, action, ctx).await;
+            let bytes = Transfer::since(before, Transfer::read(&self.page).await);
+            if outcome.is_ok() {
+                outcome = self.follow_popup().await;
+            }
+            let duration_ms = started.elapsed().as_millis() as u64;
+            timings.record(i, action, duration_ms, bytes, outcome.is_ok());
+            if let Some(ref mut artifacts) = self.artifacts {
+                artifacts
+                    .capture(&self.page, i, action, outcome.is_ok())
+                    .await;
+            }
+            if self.events.is_some() || !self.listeners.is_empty() {
+                let artifacts = action_artifacts(action, ctx, outcome.is_ok()).await;
+                self.emit(&Event::ActionEnd {
+                    index: i,
+                    action: action.name(),
+                    target: action.target(),
+                    retry,
+                    success: outcome.is_ok(),
+                    error: outcome.as_ref().err().map(|e| e.to_string()),
+                    duration_ms,
+                    artifacts,
+                    resolved: std::mem::take(&mut *ctx.resolved.lock().await),
+                });
+            }
+            if self.debug_overlay {
+                let ended = match outcome {
+                    Ok(()) => format!("{} → ok ({} ms)", step, duration_ms),
+                    Err(ref e) => format!("{} → failed: {}", step, e),
+                };
+                if outcome.is_err() {
+                    show_debug_overlay(&self.page, &ended).await;
+                }
+                last_step = Some(ended);
+            }
+            outcome?;
             actions_executed += 1;
+            if let Some(ref path) = self.checkpoint {
+                let variables = ctx.variables.lock().await.clone();
+                let saved = match Checkpoint::capture(&self.page, config, i + 1, &variables).await {
+                    Ok(checkpoint) => checkpoint.save(path),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = saved {
+                    warn!("Failed to write checkpoint: {}", e);
+                }
+            }
+        }
+        if let Some(ref path) = self.checkpoint {
+            Checkpoint::remove(path);
         }
 
-        let success = self.check_success(config).await?;
+        let success = check_success(&self.page, config).await?;
         debug!("Success check: {}", success);
 
         Ok(RunResult {
@@ -160,45 +764,53 @@ impl Runner {
             actions_executed,
             duration_ms: 0,
             retries: 0,
+            actions: Vec::new(),
+            crawl: None,
         })
     }
 
-    async fn check_success(&self, config: &Config) -> Result<bool> {
-        let Some(ref success) = config.success else {
-            return Ok(true);
+    /// Under `popups: follow`, switch to a window the last action opened, or
+    /// back to the opener of one that closed, with the launch emulation and
+    /// traffic recording in place.
+    async fn follow_popup(&mut self) -> Result<()> {
+        let Some(ref mut popups) = self.popups else {
+            return Ok(());
         };
-
-        if let Some(ref any) = success.any {
-            for cond in any {
-                if self.check_condition(cond).await? {
-                    return Ok(true);
-                }
+        if popups.follow(&self.browser, &mut self.page).await? {
+            emulation::apply(&self.page, &self.config).await?;
+            deterministic::install(&self.page).await?;
+            if self.recording_traffic {
+                traffic::install(&self.page).await?;
             }
-            return Ok(false);
         }
+        Ok(())
+    }
 
-        if let Some(ref all) = success.all {
-            for cond in all {
-                if !self.check_condition(cond).await? {
-                    return Ok(false);
-                }
-            }
+    fn emit(&mut self, event: &Event<'_>) {
+        if let Some(ref mut log) = self.events {
+            log.emit(event);
+        }
+        for listener in &mut self.listeners {
+            listener(event);
         }
-
-        Ok(true)
     }
 
-    async fn check_condition(&self, condition: &crate::config::schema::Condition) -> Result<bool> {
-        use crate::config::schema::Condition;
-        match condition {
-            Condition::UrlContains(pattern) => {
-                let url = self.page.url().await?;
-                Ok(url.contains(pattern))
-            }
-            Condition::TextContains(pattern) => {
-                let text = self.page.text().await?;
-                Ok(text.contains(pattern))
+    /// The checkpoint to resume from, if checkpointing is on and one matches.
+    fn resume_point(&self, config: &Config) -> Result<Option<Checkpoint>> {
+        let Some(ref path) = self.checkpoint else {
+            return Ok(None);
+        };
+        match Checkpoint::load(path)? {
+            Some(checkpoint) if checkpoint.matches(config) => Ok(Some(checkpoint)),
+            Some(checkpoint) => {
+                warn!(
+                    "Ignoring checkpoint {} from a different version of '{}'",
+                    path.display(),
+                    checkpoint.config
+                );
+                Ok(None)
             }
+            None => Ok(None),
         }
     }
 
@@ -208,3 +820,118 @@ impl Runner {
         Ok(())
     }
 }
+
+fn uses_traffic(config: &Config) -> bool {
+    config.success.as_ref().is_some_and(|s| s.uses_traffic())
+}
+
+async fn check_success(page: &Page, config: &Config) -> Result<bool> {
+    let Some(ref success) = config.success else {
+        return Ok(true);
+    };
+
+    if let Some(ref any) = success.any {
+        for cond in any {
+            if check_condition(page, cond).await? {
+                return Ok(true);
+            }
+        }
+        return Ok(false);
+    }
+
+    if let Some(ref all) = success.all {
+        for cond in all {
+            if !check_condition(page, cond).await? {
+                return Ok(false);
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+async fn check_condition(
+    page: &Page,
+    condition: &crate::config::schema::Condition,
+) -> Result<bool> {
+    use crate::config::schema::Condition;
+    match condition {
+        Condition::UrlContains(pattern) => {
+            let url = page.url().await?;
+            Ok(url.contains(pattern))
+        }
+        Condition::TextContains(pattern) => {
+            let text = page.text().await?;
+            Ok(text.contains(pattern))
+        }
+        Condition::ElementExists(selector) => Ok(element_exists(page, selector).await?),
+        Condition::ElementAbsent(selector) => Ok(!element_exists(page, selector).await?),
+        Condition::JsTrue(js) => Ok(page.evaluate(&format!("!!({})", js)).await?),
+        Condition::CookieExists(name) => {
+            let cookies = page.cookies().await?;
+            Ok(cookies.iter().any(|c| &c.name == name))
+        }
+        Condition::RequestMade(want) => {
+            Ok(traffic::request_made(&traffic::requests(page).await?, want))
+        }
+        Condition::ResponseStatus(want) => Ok(traffic::response_status(
+            &traffic::requests(page).await?,
+            want,
+        )),
+        Condition::Any(conditions) => {
+            for cond in conditions {
+                if Box::pin(check_condition(page, cond)).await? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+        Condition::All(conditions) => {
+            for cond in conditions {
+                if !Box::pin(check_condition(page, cond)).await? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+    }
+}
+
+async fn element_exists(page: &Page, selector: &str) -> Result<bool> {
+    let js = format!(
+        "!!document.querySelector({})",
+        serde_json::to_string(selector).unwrap()
+    );
+    Ok(page.evaluate(&js).await?)
+}
+
+/// Files an action wrote.
+async fn action_artifacts(
+    action: &crate::Action,
+    ctx: &ExecutionContext,
+    success: bool,
+) -> Vec<String> {
+    match action {
+        crate::Action::Screenshot(a) if success => vec![a.path.clone()],
+        crate::Action::AssertScreenshot(a) if !success => existing(a.diff_path()),
+        crate::Action::AssertSnapshot(a) if !success => existing(a.actual_path()),
+        crate::Action::WaitForDownload(_) if success => ctx
+            .downloads
+            .lock()
+            .await
+            .last()
+            .map(|d| d.path.display().to_string())
+            .into_iter()
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `path` as a one-item artifact list, if the file was written.
+fn existing(path: PathBuf) -> Vec<String> {
+    if path.exists() {
+        vec![path.display().to_string()]
+    } else {
+        Vec::new()
+    }
+}
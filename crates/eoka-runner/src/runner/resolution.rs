@@ -0,0 +1,72 @@
+//! How each target was resolved — the selector it became, how many elements
+//! that matches and where the first one is — for logs, JSONL events and
+//! `--explain`.
+
+use serde::Serialize;
+use std::fmt;
+
+/// One target lookup.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Resolution {
+    /// The target as written, e.g. `text 'Sign in'`.
+    pub target: String,
+    /// What was searched for it.
+    pub searched: String,
+    /// CSS selector it resolved to; `None` if nothing matched.
+    pub selector: Option<String>,
+    /// Elements the selector matches.
+    pub matches: usize,
+    /// Viewport center of the first match, if it has a size.
+    pub point: Option<(f64, f64)>,
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(ref selector) = self.selector else {
+            return write!(f, "{} → not found; searched {}", self.target, self.searched);
+        };
+        write!(f, "{} → {}", self.target, selector)?;
+        match self.matches {
+            0 => write!(f, " (no match)")?,
+            1 => write!(f, " (1 match")?,
+            n => write!(f, " ({} matches", n)?,
+        }
+        match self.point {
+            Some((x, y)) if self.matches > 0 => write!(f, ", at {:.0},{:.0})", x, y),
+            _ if self.matches > 0 => write!(f, ", not rendered)"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let mut r = Resolution {
+            target: "text 'Sign in'".into(),
+            searched: "clickable elements containing 'sign in'".into(),
+            selector: Some("#nav > button:nth-child(2)".into()),
+            matches: 1,
+            point: Some((412.4, 300.0)),
+        };
+        assert_eq!(
+            r.to_string(),
+            "text 'Sign in' → #nav > button:nth-child(2) (1 match, at 412,300)"
+        );
+        r.matches = 3;
+        r.point = None;
+        assert_eq!(
+            r.to_string(),
+            "text 'Sign in' → #nav > button:nth-child(2) (3 matches, not rendered)"
+        );
+        r.selector = None;
+        r.matches = 0;
+        assert_eq!(
+            r.to_string(),
+            "text 'Sign in' → not found; searched clickable elements containing 'sign in'"
+        );
+    }
+}
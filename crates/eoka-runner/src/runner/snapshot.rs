@@ -0,0 +1,134 @@
+//! Content snapshots — normalized text or sanitized HTML compared with golden files.
+
+use crate::{Error, Result};
+use regex::Regex;
+use std::path::Path;
+
+/// Replaces matches of `ignore` patterns.
+pub const IGNORED: &str = "<ignored>";
+
+/// Renders an element as one tag or text run per line, indented by depth.
+/// Drops scripts, styles, comments, inline styles, and event handlers, and
+/// sorts attributes so the output only changes when the content does.
+pub const SANITIZED_HTML_JS: &str = r#"((root) => {
+    const SKIP = new Set(['SCRIPT', 'STYLE', 'NOSCRIPT', 'TEMPLATE', 'LINK', 'META']);
+    const lines = [];
+    const walk = (node, depth) => {
+        const pad = '  '.repeat(depth);
+        if (node.nodeType === 3) {
+            const text = node.textContent.replace(/\s+/g, ' ').trim();
+            if (text) lines.push(pad + text);
+            return;
+        }
+        if (node.nodeType !== 1 || SKIP.has(node.tagName)) return;
+        const attrs = Array.from(node.attributes)
+            .filter(a => a.name !== 'style' && !a.name.startsWith('on'))
+            .map(a => ` ${a.name}="${a.value}"`)
+            .sort()
+            .join('');
+        lines.push(`${pad}<${node.tagName.toLowerCase()}${attrs}>`);
+        for (const child of node.childNodes) walk(child, depth + 1);
+    };
+    walk(root, 0);
+    return lines.join('\n');
+})"#;
+
+/// Compile `ignore` patterns up front so a typo fails before capturing.
+pub fn compile(patterns: &[String]) -> Result<Vec<Regex>> {
+    patterns
+        .iter()
+        .map(|p| {
+            Regex::new(p)
+                .map_err(|e| Error::Config(format!("invalid ignore pattern '{}': {}", p, e)))
+        })
+        .collect()
+}
+
+/// Collapse whitespace within lines, drop blank lines, and mask ignored patterns.
+pub fn normalize(text: &str, ignore: &[Regex]) -> String {
+    let mut lines: Vec<String> = text
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|l| !l.is_empty())
+        .collect();
+    for line in &mut lines {
+        for re in ignore {
+            *line = re.replace_all(line, IGNORED).into_owned();
+        }
+    }
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Read a golden file, or `None` if it does not exist yet.
+pub fn load(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write a snapshot, creating parent directories.
+pub fn save(text: &str, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+/// Describe the first differing line, or `None` if the snapshots match.
+/// Line endings in the golden file are ignored.
+pub fn first_difference(expected: &str, actual: &str) -> Option<String> {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let line = (0..expected.len().max(actual.len())).find(|&i| expected.get(i) != actual.get(i))?;
+    let show = |l: Option<&&str>| match l {
+        Some(l) => format!("{:?}", l),
+        None => "<end of snapshot>".to_string(),
+    };
+    Some(format!(
+        "line {}: expected {}, got {}",
+        line + 1,
+        show(expected.get(line)),
+        show(actual.get(line))
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_and_diffs() {
+        let ignore = compile(&[r"\d{4}-\d{2}-\d{2}".into(), r"#\d+".into()]).unwrap();
+        let text = "  Order   #4821 \n\n\tPlaced 2024-05-01\r\nTotal: $10\n";
+        let normalized = normalize(text, &ignore);
+        assert_eq!(
+            normalized,
+            "Order <ignored>\nPlaced <ignored>\nTotal: $10\n"
+        );
+
+        assert_eq!(first_difference(&normalized, &normalized), None);
+        assert_eq!(
+            first_difference(&normalized.replace("\n", "\r\n"), &normalized),
+            None
+        );
+        assert_eq!(
+            first_difference(
+                &normalized,
+                "Order <ignored>\nPlaced <ignored>\nTotal: $12\n"
+            )
+            .unwrap(),
+            r#"line 3: expected "Total: $10", got "Total: $12""#
+        );
+        assert_eq!(
+            first_difference(&normalized, "Order <ignored>\n").unwrap(),
+            r#"line 2: expected "Placed <ignored>", got <end of snapshot>"#
+        );
+
+        assert!(compile(&["(".into()]).is_err());
+    }
+}
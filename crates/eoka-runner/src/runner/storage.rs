@@ -0,0 +1,156 @@
+//! Storage state — cookies and localStorage persisted between runs.
+
+use crate::{Error, Result};
+use eoka::cdp::{Cookie, NetworkSetCookie, NetworkSetCookieResult};
+use eoka::Page;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::{debug, warn};
+
+/// Cookies plus localStorage keyed by origin.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageState {
+    pub cookies: Vec<Cookie>,
+    /// `origin -> key -> value`
+    #[serde(default)]
+    pub local_storage: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+const CAPTURE_JS: &str = r#"(() => {
+    let items = {};
+    try { items = Object.fromEntries(Object.entries(localStorage)); } catch (e) {}
+    return JSON.stringify({ origin: location.origin, items });
+})()"#;
+
+#[derive(Deserialize)]
+struct OriginDump {
+    origin: String,
+    items: BTreeMap<String, String>,
+}
+
+/// Set cookies with all their attributes (the page-level helper only takes name/value/domain/path).
+pub async fn set_cookies(page: &Page, cookies: &[Cookie]) -> Result<()> {
+    for cookie in cookies {
+        let params = NetworkSetCookie {
+            name: cookie.name.clone(),
+            value: cookie.value.clone(),
+            domain: Some(cookie.domain.clone()),
+            path: Some(cookie.path.clone()),
+            secure: Some(cookie.secure),
+            http_only: Some(cookie.http_only),
+            same_site: cookie.same_site.clone(),
+            expires: (!cookie.session).then_some(cookie.expires),
+            ..Default::default()
+        };
+        let result: NetworkSetCookieResult =
+            page.session().send("Network.setCookie", &params).await?;
+        if !result.success {
+            warn!("Failed to restore cookie: {}", cookie.name);
+        }
+    }
+    Ok(())
+}
+
+impl StorageState {
+    /// Load state from `path`, or an empty state if the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_str(&json)
+            .map_err(|e| Error::Config(format!("invalid storage state {}: {}", path.display(), e)))
+    }
+
+    /// Write the state as JSON, creating parent directories.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            Error::ActionFailed(format!("failed to serialize storage state: {}", e))
+        })?;
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Capture the browser's cookies and the current origin's localStorage.
+    ///
+    /// localStorage saved earlier for other origins is kept.
+    pub async fn capture(&mut self, page: &Page) -> Result<()> {
+        self.cookies = page.cookies().await?;
+        let json: String = page.evaluate(CAPTURE_JS).await?;
+        let dump: OriginDump = serde_json::from_str(&json)
+            .map_err(|e| Error::ActionFailed(format!("failed to read localStorage: {}", e)))?;
+        // Opaque origins (about:blank, data:) report "null".
+        if dump.origin != "null" {
+            self.local_storage.insert(dump.origin, dump.items);
+        }
+        Ok(())
+    }
+
+    /// Set cookies now and install localStorage for every later page load.
+    pub async fn apply(&self, page: &Page) -> Result<()> {
+        debug!(
+            "Restoring {} cookies and localStorage for {} origins",
+            self.cookies.len(),
+            self.local_storage.len()
+        );
+        set_cookies(page, &self.cookies).await?;
+        if !self.local_storage.is_empty() {
+            page.session()
+                .add_script_to_evaluate_on_new_document(&self.restore_script())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Runs before page scripts; fills localStorage once per origin per tab so
+    /// the app's own changes during the run are not overwritten.
+    fn restore_script(&self) -> String {
+        format!(
+            r#"(() => {{
+                const items = {}[location.origin];
+                if (!items) return;
+                try {{
+                    if (sessionStorage.getItem('__eoka_storage_state')) return;
+                    for (const [k, v] of Object.entries(items)) localStorage.setItem(k, v);
+                    sessionStorage.setItem('__eoka_storage_state', '1');
+                }} catch (e) {{}}
+            }})()"#,
+            serde_json::to_string(&self.local_storage).unwrap()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_file_is_empty_and_round_trips() {
+        let path = std::env::temp_dir().join(format!(
+            "eoka-runner-storage-{}/state.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+
+        let mut state = StorageState::load(&path).unwrap();
+        assert!(state.cookies.is_empty());
+
+        state.local_storage.insert(
+            "https://example.com".into(),
+            BTreeMap::from([("token".into(), "abc".into())]),
+        );
+        state.save(&path).unwrap();
+        let loaded = StorageState::load(&path).unwrap();
+        assert_eq!(loaded.local_storage["https://example.com"]["token"], "abc");
+        assert!(loaded
+            .restore_script()
+            .contains(r#"{"https://example.com":{"token":"abc"}}"#));
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
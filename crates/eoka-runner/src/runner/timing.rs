@@ -0,0 +1,127 @@
+//! Per-action timing — duration, attempts, and bytes transferred by each top-level action.
+
+use crate::config::Action;
+use eoka::Page;
+use serde::{Deserialize, Serialize};
+
+/// Time and traffic spent on one top-level action, summed over run retries.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionTiming {
+    /// Top-level action index, starting at 0.
+    pub index: usize,
+    pub action: &'static str,
+    pub target: Option<String>,
+    /// Total time across all attempts.
+    pub duration_ms: u64,
+    /// Times the action ran again because the run was retried.
+    pub retries: u32,
+    /// Bytes the page transferred while the action ran, per Resource Timing.
+    /// Cross-origin responses without `Timing-Allow-Origin` count as 0.
+    pub bytes: u64,
+    /// Whether the last attempt succeeded.
+    pub success: bool,
+}
+
+/// Sums `transferSize` over the document and its resources. `origin` changes
+/// when a navigation replaces the document.
+const TRANSFER_JS: &str = r#"JSON.stringify({
+    origin: performance.timeOrigin,
+    bytes: performance.getEntriesByType('navigation')
+        .concat(performance.getEntriesByType('resource'))
+        .reduce((n, e) => n + (e.transferSize || 0), 0)
+})"#;
+
+/// Bytes transferred by the current document so far.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Transfer {
+    origin: f64,
+    bytes: u64,
+}
+
+impl Transfer {
+    /// Read the counter; `None` if the page can't be evaluated (e.g. mid-navigation).
+    pub async fn read(page: &Page) -> Option<Self> {
+        let json: String = page.evaluate(TRANSFER_JS).await.ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Bytes transferred between `before` and `after`. After a navigation
+    /// everything the new document loaded counts.
+    pub fn since(before: Option<Self>, after: Option<Self>) -> u64 {
+        match (before, after) {
+            (Some(b), Some(a)) if b.origin == a.origin => a.bytes.saturating_sub(b.bytes),
+            (_, Some(a)) => a.bytes,
+            (_, None) => 0,
+        }
+    }
+}
+
+/// Collects timings across the attempts of one run.
+#[derive(Debug, Default)]
+pub struct Timings {
+    actions: Vec<ActionTiming>,
+}
+
+impl Timings {
+    /// Add one execution of the action at `index`.
+    pub fn record(
+        &mut self,
+        index: usize,
+        action: &Action,
+        duration_ms: u64,
+        bytes: u64,
+        success: bool,
+    ) {
+        if let Some(t) = self.actions.iter_mut().find(|t| t.index == index) {
+            t.duration_ms += duration_ms;
+            t.bytes += bytes;
+            t.retries += 1;
+            t.success = success;
+            return;
+        }
+        self.actions.push(ActionTiming {
+            index,
+            action: action.name(),
+            target: action.target(),
+            duration_ms,
+            retries: 0,
+            bytes,
+            success,
+        });
+    }
+
+    /// Timings in action order.
+    pub fn to_vec(&self) -> Vec<ActionTiming> {
+        let mut actions = self.actions.clone();
+        actions.sort_by_key(|t| t.index);
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_attempts_and_transfers() {
+        let wait = Action::Wait(crate::config::actions::WaitAction { ms: 10 });
+        let mut timings = Timings::default();
+        timings.record(1, &wait, 300, 10, false);
+        timings.record(0, &wait, 100, 0, true);
+        timings.record(1, &wait, 200, 5, true);
+
+        let actions = timings.to_vec();
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].index, 0);
+        assert_eq!(actions[1].duration_ms, 500);
+        assert_eq!(actions[1].bytes, 15);
+        assert_eq!(actions[1].retries, 1);
+        assert!(actions[1].success);
+
+        let at = |origin, bytes| Some(Transfer { origin, bytes });
+        assert_eq!(Transfer::since(at(1.0, 100), at(1.0, 150)), 50);
+        assert_eq!(Transfer::since(at(1.0, 100), at(2.0, 40)), 40);
+        assert_eq!(Transfer::since(None, at(2.0, 40)), 40);
+        assert_eq!(Transfer::since(at(1.0, 100), None), 0);
+    }
+}
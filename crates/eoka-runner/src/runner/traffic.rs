@@ -0,0 +1,139 @@
+//! Network traffic for the `request_made` and `response_status` success
+//! conditions.
+//!
+//! eoka doesn't stream CDP network events, so the [`crate::capture`] script
+//! records `fetch`, XHR, `sendBeacon` and form submissions in
+//! `sessionStorage`, where they survive same-origin navigations. Resource
+//! Timing adds images, scripts and the document itself. Traffic is kept per
+//! origin: a run that ends on another origin only sees that origin's
+//! requests.
+
+use crate::capture;
+use crate::config::schema::{RequestMade, ResponseStatus};
+use crate::Result;
+use eoka::Page;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Requests kept per origin; the oldest are dropped first.
+const MAX_ENTRIES: usize = 500;
+
+/// Recorded requests plus Resource Timing entries of the current document.
+const READ_JS: &str = r#"
+(() => {
+  let all = [];
+  try { all = JSON.parse(sessionStorage.getItem('__eokaTraffic') || '[]'); } catch (e) {}
+  const status = e => e.responseStatus || null;
+  for (const e of performance.getEntriesByType('navigation')) {
+    all.push({ method: null, url: e.name, status: status(e) });
+  }
+  for (const e of performance.getEntriesByType('resource')) {
+    const method = e.initiatorType === 'beacon' ? 'POST'
+      : (e.initiatorType === 'fetch' || e.initiatorType === 'xmlhttprequest') ? null
+      : 'GET';
+    all.push({ method, url: e.name, status: status(e) });
+  }
+  return all;
+})()
+"#;
+
+/// One observed request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Request {
+    /// `None` when only Resource Timing saw it.
+    pub method: Option<String>,
+    pub url: String,
+    /// `None` until answered, or when the status is hidden cross-origin.
+    pub status: Option<u16>,
+}
+
+/// Start recording in this and every later document of `page`.
+pub async fn install(page: &Page) -> Result<()> {
+    let script = capture::script(&json!({ "traffic": true, "maxTraffic": MAX_ENTRIES }));
+    let _: Value = page
+        .session()
+        .send(
+            "Page.addScriptToEvaluateOnNewDocument",
+            &json!({ "source": script }),
+        )
+        .await?;
+    page.execute(&script).await?;
+    Ok(())
+}
+
+/// Requests the current origin's pages made so far.
+pub async fn requests(page: &Page) -> Result<Vec<Request>> {
+    Ok(page.evaluate(READ_JS).await?)
+}
+
+/// Some request went to a matching URL, with the method if one is given.
+pub fn request_made(requests: &[Request], want: &RequestMade) -> bool {
+    requests.iter().any(|r| {
+        r.url.contains(&want.url_contains)
+            && match want.method {
+                Some(ref method) => r
+                    .method
+                    .as_ref()
+                    .is_some_and(|m| m.eq_ignore_ascii_case(method)),
+                None => true,
+            }
+    })
+}
+
+/// Some response from a matching URL had the status.
+pub fn response_status(requests: &[Request], want: &ResponseStatus) -> bool {
+    requests
+        .iter()
+        .any(|r| r.url.contains(&want.url_contains) && r.status == Some(want.status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: Option<&str>, url: &str, status: Option<u16>) -> Request {
+        Request {
+            method: method.map(String::from),
+            url: url.into(),
+            status,
+        }
+    }
+
+    #[test]
+    fn matches_requests() {
+        let requests = [
+            request(Some("POST"), "https://example.com/collect?v=2", None),
+            request(Some("GET"), "https://example.com/api/cart", Some(200)),
+            request(None, "https://example.com/api/order", Some(500)),
+        ];
+        let made = |url: &str, method: Option<&str>| {
+            request_made(
+                &requests,
+                &RequestMade {
+                    url_contains: url.into(),
+                    method: method.map(String::from),
+                },
+            )
+        };
+        assert!(made("/collect", None));
+        assert!(made("/collect", Some("post")));
+        assert!(!made("/collect", Some("GET")));
+        // Resource Timing doesn't know the method
+        assert!(made("/api/order", None));
+        assert!(!made("/api/order", Some("POST")));
+
+        let status = |url: &str, status: u16| {
+            response_status(
+                &requests,
+                &ResponseStatus {
+                    url_contains: url.into(),
+                    status,
+                },
+            )
+        };
+        assert!(status("/api/cart", 200));
+        assert!(status("/api/order", 500));
+        assert!(!status("/api/order", 200));
+        assert!(!status("/collect", 200));
+    }
+}
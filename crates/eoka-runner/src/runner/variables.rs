@@ -0,0 +1,62 @@
+//! Variables — values actions produce while they run, readable by the caller.
+
+use crate::Params;
+use std::collections::BTreeMap;
+
+/// Named values shared with embedded action runs.
+///
+/// Actions write what they extract: `wait_for_email` sets `email_link` and
+/// `email_code`, and `wait_for_download` sets `download_path`. Values the
+/// caller sets are kept, so one `Variables` can carry state across several
+/// [`Runner::execute_actions`](crate::Runner::execute_actions) calls.
+#[derive(Debug, Clone, Default)]
+pub struct Variables {
+    values: BTreeMap<String, String>,
+}
+
+impl Variables {
+    /// Create empty variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a value.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(|s| s.as_str())
+    }
+
+    /// Set a value, replacing any previous one.
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Iterate over all name/value pairs, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// The values as params, for loading a config that references them as `${name}`.
+    pub fn to_params(&self) -> Params {
+        self.iter()
+            .fold(Params::new(), |params, (k, v)| params.set(k, v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_get_and_params() {
+        let mut vars = Variables::new();
+        vars.set("email", "a@b.c");
+        vars.set("email_code", "123456");
+        vars.set("email_code", "654321");
+        assert_eq!(vars.get("email_code"), Some("654321"));
+        assert_eq!(vars.iter().count(), 2);
+
+        let params = vars.to_params();
+        assert_eq!(params.get("email"), Some("a@b.c"));
+        assert_eq!(params.get("email_code"), Some("654321"));
+    }
+}
@@ -0,0 +1,333 @@
+//! Scheduling for repeated runs — cron expressions, intervals, jitter, and run history.
+
+use crate::{Error, Result, RunResult};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, Timelike};
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::Write;
+use std::path::Path;
+
+/// A standard 5-field cron expression: `minute hour day-of-month month day-of-week`.
+///
+/// Fields accept `*`, numbers, ranges (`1-5`), steps (`*/15`, `0-30/10`), and
+/// comma lists. Day-of-week is 0-7 with both 0 and 7 meaning Sunday. As in
+/// classic cron, when both day fields are restricted a day matching either runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    /// Parse a cron expression.
+    pub fn parse(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(Error::Config(format!(
+                "cron expression '{}' must have 5 fields (minute hour day month weekday)",
+                expr
+            )));
+        }
+        let mut weekdays = parse_field(fields[4], 0, 7)?;
+        // 7 is Sunday too
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            weekdays,
+            days_restricted: fields[2] != "*",
+            weekdays_restricted: fields[4] != "*",
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let dom = self.days & (1 << date.day()) != 0;
+        let dow = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+
+    /// The first matching minute strictly after `after`.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        // Bounded search: a valid expression matches within ~4 years (Feb 29).
+        let limit = t + Duration::days(366 * 4 + 1);
+        while t < limit {
+            if self.months & (1 << t.month()) == 0 {
+                let (y, m) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = NaiveDate::from_ymd_opt(y, m, 1)?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.day_matches(t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if self.minutes & (1 << t.minute()) == 0 {
+                t += Duration::minutes(1);
+                continue;
+            }
+            return Some(t);
+        }
+        None
+    }
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let invalid = || Error::Config(format!("invalid cron field '{}' ({}-{})", field, min, max));
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((r, s)) => (r, s.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (
+                a.parse().map_err(|_| invalid())?,
+                b.parse().map_err(|_| invalid())?,
+            )
+        } else {
+            let n: u32 = range.parse().map_err(|_| invalid())?;
+            // "5/15" means every 15 starting at 5
+            (n, if part.contains('/') { max } else { n })
+        };
+        if start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for n in (start..=end).step_by(step as usize) {
+            mask |= 1 << n;
+        }
+    }
+    Ok(mask)
+}
+
+/// Parse a duration like `90s`, `15m`, `2h`, `1d`, or plain seconds.
+pub fn parse_duration(s: &str) -> Result<std::time::Duration> {
+    let s = s.trim();
+    let invalid = || Error::Config(format!("invalid duration '{}' (e.g. 30s, 15m, 2h)", s));
+    let (num, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let n: u64 = num.parse().map_err(|_| invalid())?;
+    let secs = match unit {
+        "ms" => return Ok(std::time::Duration::from_millis(n)),
+        "s" => n,
+        "m" => n * 60,
+        "h" => n * 3600,
+        "d" => n * 86400,
+        _ => return Err(invalid()),
+    };
+    Ok(std::time::Duration::from_secs(secs))
+}
+
+/// When to run next.
+#[derive(Debug, Clone)]
+pub enum Schedule {
+    /// Cron expression, evaluated in local time.
+    Cron(CronSchedule),
+    /// Fixed delay between the end of one run and the start of the next.
+    Interval(std::time::Duration),
+}
+
+impl Schedule {
+    /// How long to wait from `now` until the next run.
+    pub fn delay_from(&self, now: DateTime<Local>) -> Result<std::time::Duration> {
+        match self {
+            Self::Interval(d) => Ok(*d),
+            Self::Cron(cron) => {
+                let now = now.naive_local();
+                let next = cron
+                    .next_after(now)
+                    .ok_or_else(|| Error::Config("cron expression never matches".into()))?;
+                Ok((next - now).to_std().unwrap_or_default())
+            }
+        }
+    }
+}
+
+/// A random delay in `[0, max]`, used to spread scheduled runs.
+pub fn jitter(max: std::time::Duration) -> std::time::Duration {
+    if max.is_zero() {
+        return max;
+    }
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(Local::now().timestamp_nanos_opt().unwrap_or(0) as u128);
+    let ms = max.as_millis() as u64;
+    std::time::Duration::from_millis(hasher.finish() % (ms + 1))
+}
+
+/// One line of run history.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunRecord {
+    /// Run number, starting at 1.
+    pub run: u64,
+    /// When the run started (RFC 3339).
+    pub started_at: String,
+    pub config: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub actions_executed: usize,
+    pub duration_ms: u64,
+    pub retries: u32,
+    /// Failures in a row, including this run.
+    pub consecutive_failures: u32,
+}
+
+impl RunRecord {
+    /// Build a record from a finished run.
+    pub fn new(
+        run: u64,
+        started_at: DateTime<Local>,
+        config: &str,
+        result: &RunResult,
+        consecutive_failures: u32,
+    ) -> Self {
+        Self {
+            run,
+            started_at: started_at.to_rfc3339(),
+            config: config.to_string(),
+            success: result.success,
+            error: result.error.clone(),
+            actions_executed: result.actions_executed,
+            duration_ms: result.duration_ms,
+            retries: result.retries,
+            consecutive_failures,
+        }
+    }
+
+    /// Append this record as one JSON line.
+    pub fn append_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        let line = serde_json::to_string(self)
+            .map_err(|e| Error::Config(format!("failed to serialize run record: {}", e)))?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn every_15_minutes() {
+        let cron = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            cron.next_after(at("2024-05-01 10:07")),
+            Some(at("2024-05-01 10:15"))
+        );
+        assert_eq!(
+            cron.next_after(at("2024-05-01 10:15")),
+            Some(at("2024-05-01 10:30"))
+        );
+        assert_eq!(
+            cron.next_after(at("2024-05-01 23:50")),
+            Some(at("2024-05-02 00:00"))
+        );
+    }
+
+    #[test]
+    fn weekdays_at_nine() {
+        let cron = CronSchedule::parse("0 9 * * 1-5").unwrap();
+        // 2024-05-03 is a Friday
+        assert_eq!(
+            cron.next_after(at("2024-05-03 09:00")),
+            Some(at("2024-05-06 09:00"))
+        );
+        assert_eq!(
+            cron.next_after(at("2024-05-06 08:59")),
+            Some(at("2024-05-06 09:00"))
+        );
+    }
+
+    #[test]
+    fn day_fields_are_ored() {
+        // 1st of the month or any Sunday (7)
+        let cron = CronSchedule::parse("30 6 1 * 7").unwrap();
+        // 2024-05-05 is a Sunday
+        assert_eq!(
+            cron.next_after(at("2024-05-02 00:00")),
+            Some(at("2024-05-05 06:30"))
+        );
+        assert_eq!(
+            cron.next_after(at("2024-05-26 07:00")),
+            Some(at("2024-06-01 06:30"))
+        );
+    }
+
+    #[test]
+    fn lists_and_month_rollover() {
+        let cron = CronSchedule::parse("0,30 12 * 1,7 *").unwrap();
+        assert_eq!(
+            cron.next_after(at("2024-05-01 00:00")),
+            Some(at("2024-07-01 12:00"))
+        );
+        assert_eq!(
+            cron.next_after(at("2024-07-31 12:30")),
+            Some(at("2025-01-01 12:00"))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_expressions() {
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("5-1 * * * *").is_err());
+        assert!(CronSchedule::parse("mon * * * *").is_err());
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("90").unwrap().as_secs(), 90);
+        assert_eq!(parse_duration("30s").unwrap().as_secs(), 30);
+        assert_eq!(parse_duration("15m").unwrap().as_secs(), 900);
+        assert_eq!(parse_duration("2h").unwrap().as_secs(), 7200);
+        assert_eq!(parse_duration("500ms").unwrap().as_millis(), 500);
+        assert!(parse_duration("soon").is_err());
+        assert!(parse_duration("5w").is_err());
+    }
+
+    #[test]
+    fn jitter_in_range() {
+        let max = std::time::Duration::from_secs(2);
+        for _ in 0..20 {
+            assert!(jitter(max) <= max);
+        }
+        assert!(jitter(std::time::Duration::ZERO).is_zero());
+    }
+}
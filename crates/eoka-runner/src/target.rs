@@ -0,0 +1,284 @@
+//! Live element patterns — `text:`, `placeholder:`, `label:`, `role:`,
+//! `css:` and `id:` — and the one JS implementation that resolves them,
+//! shared by config targets and eoka-agent so both match elements the
+//! same way.
+
+use eoka::{Page, Result};
+use serde::Deserialize;
+use std::fmt;
+
+/// A way of addressing an element, resolved in the page at action time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LivePattern {
+    /// `text:Submit` - find by visible text
+    Text(String),
+    /// `placeholder:Enter code` - find by placeholder
+    Placeholder(String),
+    /// `label:Email` - find a form control by its `<label>` or ARIA label
+    Label(String),
+    /// `role:button` - find by tag/ARIA role
+    Role(String),
+    /// `css:form button` - direct CSS selector
+    Css(String),
+    /// `id:submit-btn` - find by ID
+    Id(String),
+}
+
+impl LivePattern {
+    /// Parse a live pattern. Unprefixed strings default to text search.
+    pub fn parse(s: &str) -> Self {
+        if let Some(v) = s.strip_prefix("text:") {
+            return LivePattern::Text(v.into());
+        }
+        if let Some(v) = s.strip_prefix("placeholder:") {
+            return LivePattern::Placeholder(v.into());
+        }
+        if let Some(v) = s.strip_prefix("label:") {
+            return LivePattern::Label(v.into());
+        }
+        if let Some(v) = s.strip_prefix("role:") {
+            return LivePattern::Role(v.into());
+        }
+        if let Some(v) = s.strip_prefix("css:") {
+            return LivePattern::Css(v.into());
+        }
+        if let Some(v) = s.strip_prefix("id:") {
+            return LivePattern::Id(v.into());
+        }
+        // Default: treat as text search
+        LivePattern::Text(s.into())
+    }
+
+    /// The pattern kind and value, as passed to [`FIND_JS`].
+    pub fn as_js_args(&self) -> (&'static str, &str) {
+        match self {
+            LivePattern::Text(v) => ("text", v),
+            LivePattern::Placeholder(v) => ("placeholder", v),
+            LivePattern::Label(v) => ("label", v),
+            LivePattern::Role(v) => ("role", v),
+            LivePattern::Css(v) => ("css", v),
+            LivePattern::Id(v) => ("id", v),
+        }
+    }
+
+    /// What [`FIND_JS`] looks at for this pattern, for error messages.
+    pub fn searched(&self) -> String {
+        match self {
+            LivePattern::Text(v) => format!(
+                "visible links, buttons, form fields, [role=button], [onclick] and [tabindex] \
+                 elements whose text, value, aria-label, title or placeholder contains '{}' \
+                 (any case)",
+                v
+            ),
+            LivePattern::Placeholder(v) => {
+                format!(
+                    "inputs and textareas whose placeholder contains '{}' (any case)",
+                    v
+                )
+            }
+            LivePattern::Label(v) => format!(
+                "controls of <label>s, and elements with aria-label or aria-labelledby, \
+                 containing '{}' (any case)",
+                v
+            ),
+            LivePattern::Role(v) => format!("<{}> elements, then [role=\"{}\"]", v, v),
+            LivePattern::Css(v) => format!("CSS selector '{}'", v),
+            LivePattern::Id(v) => format!("the element with id '{}'", v),
+        }
+    }
+}
+
+impl fmt::Display for LivePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, value) = self.as_js_args();
+        write!(f, "{}:{}", kind, value)
+    }
+}
+
+/// Bounding box.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct BBox {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Result from live resolution.
+#[derive(Debug, Deserialize)]
+pub struct Resolved {
+    pub selector: String,
+    pub tag: String,
+    pub text: String,
+    pub found: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub bbox: BBox,
+}
+
+/// `(doc, kind, value) => Element[]` — every element in `doc` a pattern
+/// matches, best first.
+pub const FIND_JS: &str = r#"((doc, type, value) => {
+    const lc = s => (s || '').toLowerCase().trim();
+    const valLc = lc(value);
+
+    function text(el) {
+        return el.innerText?.trim() || el.value || el.getAttribute('aria-label') || el.title || el.placeholder || '';
+    }
+
+    function interactive() {
+        return [...doc.querySelectorAll('a,button,input,select,textarea,[role="button"],[onclick],[tabindex]')]
+            .filter(el => {
+                const r = el.getBoundingClientRect();
+                const s = (doc.defaultView || window).getComputedStyle(el);
+                return r.width > 0 && r.height > 0 && s.visibility !== 'hidden' && s.display !== 'none';
+            });
+    }
+
+    function byTag(tag) {
+        try { return [...doc.querySelectorAll(tag)]; } catch { return []; }
+    }
+
+    switch (type) {
+        case 'text':
+            return interactive().filter(e => lc(text(e)).includes(valLc));
+        case 'placeholder':
+            return [...doc.querySelectorAll('input[placeholder],textarea[placeholder]')]
+                .filter(e => lc(e.placeholder).includes(valLc));
+        case 'label': {
+            const found = [];
+            for (const l of doc.querySelectorAll('label')) {
+                if (l.control && lc(l.textContent).includes(valLc) && !found.includes(l.control)) found.push(l.control);
+            }
+            for (const e of doc.querySelectorAll('[aria-label],[aria-labelledby]')) {
+                const name = e.getAttribute('aria-label') || (e.getAttribute('aria-labelledby') || '')
+                    .split(/\s+/).map(id => doc.getElementById(id)?.textContent || '').join(' ');
+                if (lc(name).includes(valLc) && !found.includes(e)) found.push(e);
+            }
+            return found;
+        }
+        case 'role': {
+            const tagged = byTag(valLc);
+            return tagged.length ? tagged : [...doc.querySelectorAll('[role]')].filter(e => e.getAttribute('role') === value);
+        }
+        case 'css':
+            return [...doc.querySelectorAll(value)];
+        case 'id': {
+            const el = doc.getElementById(value);
+            return el ? [el] : [];
+        }
+    }
+    return [];
+})"#;
+
+/// `(els, kind, value) => Resolved` for the first of `els`.
+const RESOLVE_JS: &str = r#"((els, type, value) => {
+    function selector(el) {
+        if (el.id) return '#' + CSS.escape(el.id);
+        const path = [];
+        let n = el;
+        while (n && n.nodeType === 1) {
+            let s = n.tagName.toLowerCase();
+            if (n.id) { path.unshift('#' + CSS.escape(n.id)); break; }
+            const p = n.parentElement;
+            if (p) {
+                const sibs = [...p.children].filter(c => c.tagName === n.tagName);
+                if (sibs.length > 1) s += ':nth-of-type(' + (sibs.indexOf(n) + 1) + ')';
+            }
+            path.unshift(s);
+            n = p;
+        }
+        return path.join(' > ');
+    }
+
+    const el = els[0];
+    if (!el) return { found: false, error: `${type}:${value} not found`, selector: '', tag: '', text: '', bbox: {x:0,y:0,width:0,height:0} };
+
+    const text = el.innerText?.trim() || el.value || el.getAttribute('aria-label') || el.title || el.placeholder || '';
+    const r = el.getBoundingClientRect();
+    return { found: true, selector: selector(el), tag: el.tagName.toLowerCase(), text: text.slice(0, 50), bbox: {x:r.x,y:r.y,width:r.width,height:r.height} };
+})"#;
+
+/// JS expression for the elements `pattern` matches in the document
+/// expression `doc`, e.g. `document`.
+pub fn find_js(doc: &str, pattern: &LivePattern) -> String {
+    let (t, v) = pattern.as_js_args();
+    format!(
+        "{}({}, {}, {})",
+        FIND_JS,
+        doc,
+        serde_json::to_string(t).unwrap(),
+        serde_json::to_string(v).unwrap()
+    )
+}
+
+/// JS expression resolving `pattern` in `doc` to a [`Resolved`].
+pub fn resolve_js(doc: &str, pattern: &LivePattern) -> String {
+    let (t, v) = pattern.as_js_args();
+    format!(
+        "{}({}, {}, {})",
+        RESOLVE_JS,
+        find_js(doc, pattern),
+        serde_json::to_string(t).unwrap(),
+        serde_json::to_string(v).unwrap()
+    )
+}
+
+/// Resolve a live pattern to element info via JS.
+pub async fn resolve(page: &Page, pattern: &LivePattern) -> Result<Resolved> {
+    page.evaluate(&resolve_js("document", pattern)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_prefixes() {
+        assert_eq!(
+            LivePattern::parse("label:Email"),
+            LivePattern::Label("Email".into())
+        );
+        assert_eq!(LivePattern::parse("id:a:b"), LivePattern::Id("a:b".into()));
+        assert_eq!(
+            LivePattern::parse("Price: $5"),
+            LivePattern::Text("Price: $5".into())
+        );
+        assert_eq!(
+            LivePattern::Role("button".into()).to_string(),
+            "role:button"
+        );
+    }
+
+    #[test]
+    fn as_js_args() {
+        assert_eq!(
+            LivePattern::Text("foo".into()).as_js_args(),
+            ("text", "foo")
+        );
+        assert_eq!(
+            LivePattern::Placeholder("bar".into()).as_js_args(),
+            ("placeholder", "bar")
+        );
+        assert_eq!(
+            LivePattern::Css("div.x".into()).as_js_args(),
+            ("css", "div.x")
+        );
+        assert_eq!(LivePattern::Id("myid".into()).as_js_args(), ("id", "myid"));
+        assert_eq!(
+            LivePattern::Role("button".into()).as_js_args(),
+            ("role", "button")
+        );
+        assert_eq!(
+            LivePattern::Label("Email".into()).as_js_args(),
+            ("label", "Email")
+        );
+    }
+
+    #[test]
+    fn js_quotes_values() {
+        let js = resolve_js("s.doc", &LivePattern::Text("it's \"here\"".into()));
+        assert!(js.contains(r#"(s.doc, "text", "it's \"here\"")"#));
+    }
+}
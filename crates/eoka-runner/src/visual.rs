@@ -0,0 +1,292 @@
+//! Visual regression — compare screenshots against stored baselines.
+//!
+//! Shared by `assert_screenshot` and eoka-agent's
+//! `annotate::diff_screenshots`, so both see the same changes.
+
+use crate::{Error, Result};
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Per-channel change a pixel may have and still count as unchanged, so
+/// anti-aliasing and compression noise don't.
+pub const TOLERANCE: u8 = 16;
+
+/// Changed pixels are grouped in cells this many pixels square; changes in
+/// touching cells make one region.
+const CELL: u32 = 8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffMode {
+    /// Count pixels that changed beyond the tolerance.
+    #[default]
+    Pixel,
+    /// Compare local structure (SSIM), ignoring small shifts in color and antialiasing.
+    Structural,
+}
+
+/// A rectangle in screenshot pixels (device pixels, not CSS pixels).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Outcome of comparing a capture with its baseline.
+#[derive(Debug)]
+pub struct Comparison {
+    /// 0.0 (identical) to 1.0 (completely different).
+    pub difference: f64,
+    /// Changed pixels in red over a faded copy of the capture.
+    pub diff: RgbaImage,
+    /// Boxes around each changed area, largest first.
+    pub regions: Vec<Region>,
+}
+
+/// Decode PNG bytes.
+pub fn decode(png: &[u8]) -> Result<RgbaImage> {
+    image::load_from_memory(png)
+        .map(|img| img.to_rgba8())
+        .map_err(|e| Error::ActionFailed(format!("failed to decode screenshot: {}", e)))
+}
+
+/// Load a baseline PNG, or `None` if it does not exist yet.
+pub fn load(path: &Path) -> Result<Option<RgbaImage>> {
+    match std::fs::read(path) {
+        Ok(bytes) => decode(&bytes).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write an image as PNG, creating parent directories.
+pub fn save(image: &RgbaImage, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    image
+        .save_with_format(path, image::ImageFormat::Png)
+        .map_err(|e| Error::ActionFailed(format!("failed to write {}: {}", path.display(), e)))
+}
+
+/// Crop to a CSS-pixel rect, scaled by the device pixel ratio and clamped to the image.
+pub fn crop(image: &RgbaImage, x: f64, y: f64, width: f64, height: f64, dpr: f64) -> RgbaImage {
+    let left = ((x * dpr).max(0.0) as u32).min(image.width());
+    let top = ((y * dpr).max(0.0) as u32).min(image.height());
+    let w = ((width * dpr).round() as u32).min(image.width() - left);
+    let h = ((height * dpr).round() as u32).min(image.height() - top);
+    image::imageops::crop_imm(image, left, top, w, h).to_image()
+}
+
+/// Compare two images. A pixel counts as changed when any channel differs
+/// by more than `tolerance`. Images of different sizes differ completely.
+pub fn compare(
+    baseline: &RgbaImage,
+    actual: &RgbaImage,
+    mode: DiffMode,
+    tolerance: u8,
+) -> Comparison {
+    let (width, height) = actual.dimensions();
+    if baseline.dimensions() != actual.dimensions() {
+        return Comparison {
+            difference: 1.0,
+            diff: RgbaImage::from_pixel(width, height, Rgba([255, 0, 0, 255])),
+            regions: vec![Region {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            }],
+        };
+    }
+
+    let (cols, rows) = (width.div_ceil(CELL), height.div_ceil(CELL));
+    let mut cells = vec![false; (cols * rows) as usize];
+    let mut diff = RgbaImage::new(width, height);
+    let mut changed = 0u64;
+    for (x, y, a) in actual.enumerate_pixels() {
+        let b = baseline.get_pixel(x, y);
+        let differs = a.0.iter().zip(b.0).any(|(&p, q)| p.abs_diff(q) > tolerance);
+        let pixel = if differs {
+            changed += 1;
+            cells[((y / CELL) * cols + x / CELL) as usize] = true;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let l = luma(a) as u8;
+            Rgba([l, l, l, 64])
+        };
+        diff.put_pixel(x, y, pixel);
+    }
+
+    let pixels = (width as u64 * height as u64).max(1);
+    let difference = match mode {
+        DiffMode::Pixel => changed as f64 / pixels as f64,
+        DiffMode::Structural => 1.0 - ssim(baseline, actual),
+    };
+    Comparison {
+        difference,
+        diff,
+        regions: regions(&cells, cols, rows, width, height),
+    }
+}
+
+/// Flood-fill touching cells (diagonals included) into regions, largest first.
+fn regions(cells: &[bool], cols: u32, rows: u32, width: u32, height: u32) -> Vec<Region> {
+    let mut regions = Vec::new();
+    let mut seen = vec![false; cells.len()];
+    for start in 0..cells.len() {
+        if !cells[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut stack = vec![start];
+        let (mut min_c, mut min_r, mut max_c, mut max_r) = (u32::MAX, u32::MAX, 0, 0);
+        while let Some(i) = stack.pop() {
+            let (c, r) = (i as u32 % cols, i as u32 / cols);
+            (min_c, min_r, max_c, max_r) = (min_c.min(c), min_r.min(r), max_c.max(c), max_r.max(r));
+            for dr in -1i64..=1 {
+                for dc in -1i64..=1 {
+                    let (nc, nr) = (c as i64 + dc, r as i64 + dr);
+                    if nc < 0 || nr < 0 || nc >= cols as i64 || nr >= rows as i64 {
+                        continue;
+                    }
+                    let n = (nr * cols as i64 + nc) as usize;
+                    if cells[n] && !seen[n] {
+                        seen[n] = true;
+                        stack.push(n);
+                    }
+                }
+            }
+        }
+        let (x, y) = (min_c * CELL, min_r * CELL);
+        regions.push(Region {
+            x,
+            y,
+            width: ((max_c + 1) * CELL).min(width) - x,
+            height: ((max_r + 1) * CELL).min(height) - y,
+        });
+    }
+    regions.sort_by_key(|r| std::cmp::Reverse(r.width as u64 * r.height as u64));
+    regions
+}
+
+fn luma(p: &Rgba<u8>) -> f64 {
+    0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64
+}
+
+/// Mean SSIM over 8x8 luma windows.
+fn ssim(a: &RgbaImage, b: &RgbaImage) -> f64 {
+    const WINDOW: u32 = 8;
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    let (width, height) = a.dimensions();
+    let mut total = 0.0;
+    let mut windows = 0u32;
+    for wy in (0..height).step_by(WINDOW as usize) {
+        for wx in (0..width).step_by(WINDOW as usize) {
+            let (mut sa, mut sb, mut saa, mut sbb, mut sab, mut n) = (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+            for y in wy..(wy + WINDOW).min(height) {
+                for x in wx..(wx + WINDOW).min(width) {
+                    let la = luma(a.get_pixel(x, y));
+                    let lb = luma(b.get_pixel(x, y));
+                    sa += la;
+                    sb += lb;
+                    saa += la * la;
+                    sbb += lb * lb;
+                    sab += la * lb;
+                    n += 1.0;
+                }
+            }
+            let (ma, mb) = (sa / n, sb / n);
+            let va = saa / n - ma * ma;
+            let vb = sbb / n - mb * mb;
+            let cov = sab / n - ma * mb;
+            total += ((2.0 * ma * mb + C1) * (2.0 * cov + C2))
+                / ((ma * ma + mb * mb + C1) * (va + vb + C2));
+            windows += 1;
+        }
+    }
+    if windows == 0 {
+        1.0
+    } else {
+        total / windows as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_pixels_and_structure() {
+        let base = RgbaImage::from_pixel(16, 16, Rgba([200, 200, 200, 255]));
+        let same = compare(&base, &base.clone(), DiffMode::Pixel, 0);
+        assert_eq!(same.difference, 0.0);
+        assert!((1.0 - ssim(&base, &base)).abs() < 1e-9);
+
+        let mut changed = base.clone();
+        for x in 0..16 {
+            for y in 0..4 {
+                changed.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        let pixel = compare(&base, &changed, DiffMode::Pixel, 16);
+        assert!((pixel.difference - 0.25).abs() < 1e-9);
+        assert_eq!(pixel.diff.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(pixel.diff.get_pixel(0, 8)[3], 64);
+        assert!(compare(&base, &changed, DiffMode::Structural, 16).difference > 0.1);
+
+        // Small noise is within tolerance
+        let noisy = RgbaImage::from_pixel(16, 16, Rgba([205, 195, 200, 255]));
+        assert_eq!(compare(&base, &noisy, DiffMode::Pixel, 16).difference, 0.0);
+
+        let other = RgbaImage::new(8, 8);
+        assert_eq!(compare(&base, &other, DiffMode::Pixel, 16).difference, 1.0);
+
+        let cropped = crop(&base, 4.0, 4.0, 100.0, 2.0, 2.0);
+        assert_eq!(cropped.dimensions(), (8, 4));
+    }
+
+    #[test]
+    fn groups_changes_into_regions() {
+        let a = RgbaImage::from_pixel(100, 50, Rgba([255, 255, 255, 255]));
+        let mut b = a.clone();
+        // A button that turned blue, and a one-pixel change far away
+        for y in 10..20 {
+            for x in 10..30 {
+                b.put_pixel(x, y, Rgba([37, 99, 235, 255]));
+            }
+        }
+        b.put_pixel(90, 40, Rgba([0, 0, 0, 255]));
+        // Below the tolerance: not a change
+        b.put_pixel(60, 5, Rgba([250, 250, 250, 255]));
+
+        let comparison = compare(&a, &b, DiffMode::Pixel, TOLERANCE);
+        assert_eq!(
+            comparison.regions,
+            [
+                Region {
+                    x: 8,
+                    y: 8,
+                    width: 24,
+                    height: 16
+                },
+                Region {
+                    x: 88,
+                    y: 40,
+                    width: 8,
+                    height: 8
+                },
+            ]
+        );
+        assert!(compare(&a, &a, DiffMode::Pixel, TOLERANCE)
+            .regions
+            .is_empty());
+        let resized = compare(&a, &RgbaImage::new(10, 10), DiffMode::Pixel, TOLERANCE);
+        assert_eq!(resized.regions[0].width, 10);
+    }
+}